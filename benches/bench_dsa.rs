@@ -6,7 +6,7 @@
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
-/// Benchmark CRC32 computation: DSA vs crc32fast.
+/// Benchmark CRC32 computation: DSA vs the software CRC-32C backend.
 fn bench_crc32(c: &mut Criterion) {
     let sizes: Vec<usize> = vec![
         1024,            // 1 KB
@@ -25,15 +25,15 @@ fn bench_crc32(c: &mut Criterion) {
 
         group.throughput(Throughput::Bytes(size as u64));
 
-        // Software baseline using crc32fast
-        group.bench_with_input(BenchmarkId::new("crc32fast", size), &data, |b, data| {
-            b.iter(|| crc32fast::hash(data));
+        // Software baseline computing the same CRC-32C as DSA
+        group.bench_with_input(BenchmarkId::new("software", size), &data, |b, data| {
+            b.iter(|| dsa_rust::crc::software_crc().crc32(0, data));
         });
 
         // DSA hardware (only if available)
-        #[cfg(target_os = "linux")]
+        #[cfg(dsa_portal)]
         {
-            if let Ok(engine) = intel_dsa::DsaEngine::open_first() {
+            if let Ok(engine) = dsa_rust::DsaEngine::open_first() {
                 group.bench_with_input(BenchmarkId::new("dsa", size), &data, |b, data| {
                     b.iter(|| engine.crc32(data).unwrap());
                 });
@@ -58,7 +58,6 @@ fn bench_memcpy(c: &mut Criterion) {
     for size in sizes {
         let src: Vec<u8> = (0..size).map(|i| (i & 0xFF) as u8).collect();
         let mut dst_software = vec![0u8; size];
-        let _dst_dsa = vec![0u8; size];
 
         group.throughput(Throughput::Bytes(size as u64));
 
@@ -70,9 +69,10 @@ fn bench_memcpy(c: &mut Criterion) {
         });
 
         // DSA hardware (only if available)
-        #[cfg(target_os = "linux")]
+        #[cfg(dsa_portal)]
         {
-            if let Ok(engine) = intel_dsa::DsaEngine::open_first() {
+            if let Ok(engine) = dsa_rust::DsaEngine::open_first() {
+                let mut dst_dsa = vec![0u8; size];
                 group.bench_with_input(BenchmarkId::new("dsa", size), &src, |b, src| {
                     b.iter(|| engine.memcpy(&mut dst_dsa, src).unwrap());
                 });
@@ -109,9 +109,9 @@ fn bench_memcmp(c: &mut Criterion) {
         );
 
         // DSA hardware (only if available)
-        #[cfg(target_os = "linux")]
+        #[cfg(dsa_portal)]
         {
-            if let Ok(engine) = intel_dsa::DsaEngine::open_first() {
+            if let Ok(engine) = dsa_rust::DsaEngine::open_first() {
                 group.bench_with_input(
                     BenchmarkId::new("dsa", size),
                     &(&a, &b),
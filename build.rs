@@ -0,0 +1,39 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Derive the backend cfgs from the target and the `backend-*` features.
+//!
+//! * `dsa_portal` - work queues submit descriptors, to device portals
+//!   (`backend-hw`) or to the emulator (`backend-emu`); Linux on x86_64
+//!   only, since submission uses MOVDIR64B and ENQCMD.
+//! * `dsa_software` - work queues run operations in software; on Windows,
+//!   on other architectures, and wherever `backend-sw` is selected without
+//!   a descriptor backend.
+//! * `dsa_windows` - device discovery through the Windows SetupAPI.
+//!
+//! Without any of them, work queues are stubs that report
+//! `DsaError::PlatformNotSupported`.
+
+use std::env;
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    println!("cargo::rustc-check-cfg=cfg(dsa_portal, dsa_software, dsa_windows)");
+
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let x86_64 = env::var("CARGO_CFG_TARGET_ARCH").is_ok_and(|arch| arch == "x86_64");
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{name}")).is_some();
+
+    let portal =
+        target_os == "linux" && x86_64 && (feature("BACKEND_HW") || feature("BACKEND_EMU"));
+    if portal {
+        println!("cargo::rustc-cfg=dsa_portal");
+    }
+    if !portal && (target_os == "windows" || !x86_64 || feature("BACKEND_SW")) {
+        println!("cargo::rustc-cfg=dsa_software");
+    }
+    if target_os == "windows" && feature("BACKEND_HW") {
+        println!("cargo::rustc-cfg=dsa_windows");
+    }
+}
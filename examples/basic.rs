@@ -37,8 +37,8 @@ fn main() {
                     println!("    Enabled WQs: {}", device.enabled_wq_count());
                     for wq in &device.work_queues {
                         println!(
-                            "      - {} (state: {}, type: {:?})",
-                            wq.name, wq.state, wq.wq_type
+                            "      - {} (state: {}, type: {:?}, driver: {})",
+                            wq.name, wq.state, wq.wq_type, wq.binding
                         );
                     }
                 }
@@ -0,0 +1,70 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Feeds arbitrary operation parameters through the descriptor builders and
+//! checks that the descriptors validate and that the emulator's results
+//! match a software computation.
+
+#![no_main]
+
+mod common;
+
+use common::{Input, BUF_LEN};
+use dsa_rust::crc::{Slicing8, SoftwareCrc};
+use dsa_rust::descriptor::{DsaCompletionRecord, DsaHwDesc};
+use dsa_rust::emulator::Emulator;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input::new(data);
+    let op = input.u8() % 6;
+    let len = input.len(BUF_LEN);
+    let pattern = input.u64();
+    let seed = input.u32();
+    let src: Vec<u8> = (0..BUF_LEN).map(|_| input.u8()).collect();
+    let mut dst = vec![0u8; BUF_LEN];
+
+    let mut record = DsaCompletionRecord::new();
+    let desc = match op {
+        0 => DsaHwDesc::crc_gen(src.as_ptr(), len, seed, &mut record),
+        1 => DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), len, &mut record),
+        2 => DsaHwDesc::mem_fill(dst.as_mut_ptr(), len, pattern, &mut record),
+        3 => DsaHwDesc::compare(src.as_ptr(), dst.as_ptr(), len, &mut record),
+        4 => DsaHwDesc::compare_imm(src.as_ptr(), len, pattern, &mut record),
+        _ => DsaHwDesc::noop(&mut record),
+    };
+    if let Err(e) = desc.validate() {
+        panic!("builder produced an invalid descriptor {desc:#x?}: {e}");
+    }
+
+    let emulator = Emulator::new();
+    // SAFETY: the buffers outlive the synchronous emulated operation
+    unsafe { emulator.submit(&desc) }.unwrap();
+    assert!(record.get_status().is_success(), "{record:#x?}");
+
+    let pattern = pattern.to_le_bytes();
+    let src = &src[..len];
+    match op {
+        0 => {
+            assert_eq!(record.crc32_result(), Slicing8.crc32(seed, src));
+        }
+        1 => assert_eq!(&dst[..len], src),
+        2 => assert!(dst[..len]
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| b == pattern[i % 8])),
+        3 => {
+            let expected = src.iter().zip(&dst).position(|(a, b)| a != b);
+            assert_eq!(record.mismatch_offset(), expected);
+        }
+        4 => {
+            let expected = src
+                .iter()
+                .enumerate()
+                .position(|(i, &b)| b != pattern[i % 8]);
+            assert_eq!(record.mismatch_offset(), expected);
+        }
+        _ => {}
+    }
+});
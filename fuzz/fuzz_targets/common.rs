@@ -0,0 +1,43 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Helpers shared by the fuzz targets.
+
+/// Size of the source and destination buffers descriptors may reference.
+pub const BUF_LEN: usize = 4096;
+
+/// Reads fuzzer input as little-endian integers, yielding zeros once the
+/// input is exhausted.
+pub struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0u8; N];
+        let n = N.min(self.0.len());
+        out[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        out
+    }
+
+    pub fn u8(&mut self) -> u8 {
+        self.bytes::<1>()[0]
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.bytes())
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.bytes())
+    }
+
+    /// A length in `0..=max`.
+    pub fn len(&mut self, max: usize) -> usize {
+        self.u32() as usize % (max + 1)
+    }
+}
@@ -0,0 +1,167 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Feeds descriptors with arbitrary flags, opcodes, sizes and completion
+//! record addresses to the emulator and checks that
+//! [`DsaHwDesc::validate`] accepts exactly the descriptors the emulator
+//! accepts.
+
+#![no_main]
+
+mod common;
+
+use common::{Input, BUF_LEN};
+use dsa_rust::descriptor::{CompletionStatus, DsaCompletionRecord, DsaHwDesc};
+use dsa_rust::emulator::Emulator;
+use dsa_rust::DsaOpcode;
+use libfuzzer_sys::fuzz_target;
+
+/// Maximum number of descriptors in a fuzzed batch list.
+const MAX_BATCH: usize = 8;
+
+/// Completion status the emulator writes when a batched descriptor failed.
+const BATCH_FAIL: CompletionStatus = CompletionStatus::Unknown(0x05);
+
+/// Returns true if the emulator executes opcode `op`; others complete as
+/// unsupported.
+fn is_emulated(op: u8) -> bool {
+    Emulator::SUPPORTED_OPS
+        .iter()
+        .any(|known| known.as_u8() == op)
+}
+
+/// Returns true if opcode `op` checks the DIFs of its source.
+fn checks_dif(op: u8) -> bool {
+    matches!(
+        DsaOpcode::try_from(op),
+        Ok(DsaOpcode::DifCheck | DsaOpcode::DifStrip | DsaOpcode::DifUpdate)
+    )
+}
+
+/// Memory the fuzzed descriptors may reference.
+struct Memory {
+    src: Vec<u8>,
+    dst: Vec<u8>,
+    /// Delta record written by CreateDelta.
+    delta: Vec<u8>,
+    /// Record 0 belongs to the top-level descriptor, the rest to the batch.
+    records: Vec<DsaCompletionRecord>,
+    list: Vec<DsaHwDesc>,
+}
+
+/// Build a work descriptor from raw fuzzer input.
+///
+/// Addresses always point into `src`/`dst` for at least `xfer_size` bytes,
+/// into `delta` for at most its length, or at a (possibly misaligned)
+/// location inside `record`.
+fn raw_desc(
+    input: &mut Input,
+    src: &[u8],
+    dst: &mut [u8],
+    delta: &mut [u8],
+    record: &mut DsaCompletionRecord,
+) -> DsaHwDesc {
+    let mut desc = DsaHwDesc::new();
+    desc.flags_opcode = input.u32();
+    desc.xfer_size = input.len(BUF_LEN) as u32;
+    desc.src_addr = src.as_ptr() as u64;
+    desc.dst_addr = dst.as_mut_ptr() as u64;
+    // MemFill and CompareImm carry a pattern in place of an address
+    if desc.opcode() == DsaOpcode::MemFill as u8 {
+        desc.src_addr = input.u64();
+    } else if desc.opcode() == DsaOpcode::CompareImm as u8 {
+        desc.dst_addr = input.u64();
+    }
+    desc.as_crc_mut().crc_seed = input.u32();
+    if desc.opcode() == DsaOpcode::CreateDelta as u8 {
+        let fields = desc.as_delta_mut();
+        fields.delta_addr = delta.as_mut_ptr() as u64;
+        fields.max_delta_size = input.len(delta.len()) as u32;
+    } else if desc.opcode() == DsaOpcode::ApplyDelta as u8 {
+        desc.as_apply_delta_mut().delta_rec_size = input.len(BUF_LEN) as u32;
+    }
+
+    let record = record as *mut DsaCompletionRecord as u64;
+    desc.completion_addr = match input.u8() % 4 {
+        0 => 0,
+        1 => record + 1 + (input.u8() % 31) as u64,
+        _ => record,
+    };
+    desc
+}
+
+/// Check a submitted descriptor's completion against its validation result.
+fn check(desc: &DsaHwDesc, nested: bool) {
+    if desc.completion_addr == 0 || !desc.completion_addr.is_multiple_of(32) {
+        // Nothing was reported
+        return;
+    }
+    // SAFETY: the address points at an aligned record in `Memory`
+    let status = unsafe { &*(desc.completion_addr as *const DsaCompletionRecord) }.get_status();
+    let op = desc.opcode();
+
+    match desc.validate() {
+        Err(e) => assert!(status.is_error(), "accepted invalid {desc:#x?}: {e}"),
+        Ok(()) if nested && op == DsaOpcode::Batch as u8 => assert!(status.is_error()),
+        // Whether the entries fit the destination depends on memory contents
+        Ok(()) if op == DsaOpcode::ApplyDelta as u8 => {}
+        // So does whether the DIF checks pass
+        Ok(()) if checks_dif(op) => {}
+        Ok(()) if op == DsaOpcode::Batch as u8 => assert!(
+            status.is_success() || status == BATCH_FAIL,
+            "batch completed with {status:?}"
+        ),
+        Ok(()) if is_emulated(op) => {
+            assert!(status.is_success(), "rejected valid {desc:#x?}: {status:?}")
+        }
+        Ok(()) => assert_eq!(status, CompletionStatus::UnsupportedOp),
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input::new(data);
+    let mut mem = Memory {
+        src: (0..BUF_LEN).map(|i| i as u8).collect(),
+        // DIF Insert writes 8 more bytes per block than it reads
+        dst: vec![0u8; 2 * BUF_LEN],
+        delta: vec![0u8; BUF_LEN / 8 * 10],
+        records: vec![DsaCompletionRecord::new(); MAX_BATCH + 1],
+        list: vec![DsaHwDesc::new(); MAX_BATCH],
+    };
+
+    let (top, batch_records) = mem.records.split_at_mut(1);
+    let mut desc = raw_desc(
+        &mut input,
+        &mem.src,
+        &mut mem.dst,
+        &mut mem.delta,
+        &mut top[0],
+    );
+    let is_batch = desc.opcode() == DsaOpcode::Batch as u8;
+    if is_batch {
+        let count = input.len(MAX_BATCH);
+        for (sub, record) in mem.list.iter_mut().zip(batch_records.iter_mut()) {
+            *sub = raw_desc(&mut input, &mem.src, &mut mem.dst, &mut mem.delta, record);
+        }
+        desc.xfer_size = count as u32;
+        // Occasionally misalign the descriptor list
+        desc.src_addr = mem.list.as_ptr() as u64 + (input.u8() % 2) as u64 * 32;
+    }
+
+    let emulator = Emulator::new();
+    // SAFETY: every address refers to `mem`, and list entries beyond `count`
+    // are initialized descriptors
+    if let Err(e) = unsafe { emulator.submit(&desc) } {
+        assert!(desc.validate().is_err(), "rejected valid {desc:#x?}: {e}");
+        return;
+    }
+
+    check(&desc, false);
+    if is_batch && desc.validate().is_ok() {
+        let count = desc.xfer_size as usize;
+        for sub in &mem.list[..count] {
+            check(sub, true);
+        }
+    }
+});
@@ -0,0 +1,186 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Preallocated descriptor and completion record slots.
+//!
+//! Each work queue owns an [`Arena`] of slots allocated once at open time.
+//! The per-operation path acquires a slot with a single atomic operation,
+//! writes the descriptor in place and resets only the completion status
+//! byte, so no heap allocation or full record zeroing happens per operation.
+
+use crate::descriptor::{DsaCompletionRecord, DsaHwDesc};
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of slots in an arena (one bit per slot in the free mask).
+pub(crate) const ARENA_SLOTS: usize = 64;
+
+/// A descriptor and its completion record.
+#[repr(C, align(64))]
+struct Slot {
+    desc: UnsafeCell<DsaHwDesc>,
+    record: UnsafeCell<DsaCompletionRecord>,
+}
+
+/// Fixed pool of descriptor/completion record slots.
+pub(crate) struct Arena {
+    slots: Box<[Slot]>,
+    /// Bit `i` is set while slot `i` is in use or abandoned.
+    used: AtomicU64,
+}
+
+// SAFETY: Slots are only accessed through a `SlotGuard`, and the `used` mask
+// guarantees at most one guard exists per slot.
+unsafe impl Send for Arena {}
+unsafe impl Sync for Arena {}
+
+impl Arena {
+    /// Allocate an arena with all slots free.
+    pub(crate) fn new() -> Self {
+        let slots = (0..ARENA_SLOTS)
+            .map(|_| Slot {
+                desc: UnsafeCell::new(DsaHwDesc::new()),
+                record: UnsafeCell::new(DsaCompletionRecord::new()),
+            })
+            .collect();
+        Self {
+            slots,
+            used: AtomicU64::new(0),
+        }
+    }
+
+    /// Acquire a free slot, or `None` if all slots are in use.
+    ///
+    /// The slot's completion status is reset; other record fields keep
+    /// whatever the hardware last wrote.
+    pub(crate) fn acquire(&self) -> Option<SlotGuard<'_>> {
+        let mut used = self.used.load(Ordering::Relaxed);
+        loop {
+            let index = (!used).trailing_zeros() as usize;
+            if index >= ARENA_SLOTS {
+                return None;
+            }
+            match self.used.compare_exchange_weak(
+                used,
+                used | (1 << index),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let guard = SlotGuard { arena: self, index };
+                    // SAFETY: the slot is exclusively owned by `guard`.
+                    unsafe {
+                        std::ptr::write_volatile(&mut (*guard.record_ptr()).status, 0);
+                    }
+                    return Some(guard);
+                }
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    /// Number of slots currently available.
+    pub(crate) fn available(&self) -> usize {
+        self.used.load(Ordering::Relaxed).count_zeros() as usize
+    }
+}
+
+/// Exclusive access to one arena slot. The slot is released on drop.
+pub(crate) struct SlotGuard<'a> {
+    arena: &'a Arena,
+    index: usize,
+}
+
+impl SlotGuard<'_> {
+    fn record_ptr(&self) -> *mut DsaCompletionRecord {
+        self.arena.slots[self.index].record.get()
+    }
+
+    /// The slot's completion record.
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) fn record(&self) -> &mut DsaCompletionRecord {
+        // SAFETY: the slot is exclusively owned by this guard.
+        unsafe { &mut *self.record_ptr() }
+    }
+
+    /// The slot's completion record, for polling.
+    pub(crate) fn completion(&self) -> &DsaCompletionRecord {
+        // SAFETY: the slot is exclusively owned by this guard.
+        unsafe { &*self.record_ptr() }
+    }
+
+    /// Store `desc` in the slot and return a reference for submission.
+    pub(crate) fn store(&self, desc: DsaHwDesc) -> &DsaHwDesc {
+        // SAFETY: the slot is exclusively owned by this guard.
+        unsafe {
+            let ptr = self.arena.slots[self.index].desc.get();
+            ptr.write(desc);
+            &*ptr
+        }
+    }
+
+    /// Give up the slot without returning it to the arena.
+    ///
+    /// Used when the hardware may still write the completion record (for
+    /// example after a timeout), so the slot must never be reused.
+    pub(crate) fn abandon(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        self.arena
+            .used
+            .fetch_and(!(1 << self.index), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_alignment() {
+        assert_eq!(std::mem::align_of::<Slot>(), 64);
+        assert_eq!(std::mem::size_of::<Slot>(), 128);
+    }
+
+    #[test]
+    fn test_acquire_release() {
+        let arena = Arena::new();
+        assert_eq!(arena.available(), ARENA_SLOTS);
+
+        let a = arena.acquire().unwrap();
+        let b = arena.acquire().unwrap();
+        assert_ne!(a.index, b.index);
+        assert_eq!(arena.available(), ARENA_SLOTS - 2);
+
+        drop(a);
+        assert_eq!(arena.available(), ARENA_SLOTS - 1);
+    }
+
+    #[test]
+    fn test_exhaustion_and_abandon() {
+        let arena = Arena::new();
+        let guards: Vec<_> = (0..ARENA_SLOTS).map(|_| arena.acquire().unwrap()).collect();
+        assert!(arena.acquire().is_none());
+
+        for guard in guards {
+            guard.abandon();
+        }
+        assert_eq!(arena.available(), 0);
+    }
+
+    #[test]
+    fn test_acquire_resets_status() {
+        let arena = Arena::new();
+        let slot = arena.acquire().unwrap();
+        slot.record().status = 0x01;
+        drop(slot);
+
+        let slot = arena.acquire().unwrap();
+        assert!(!slot.record().is_complete());
+    }
+}
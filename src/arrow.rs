@@ -0,0 +1,188 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! DSA operations on Apache Arrow buffers.
+//!
+//! Analytics engines shuffle record batches between memory pools, which
+//! comes down to copying and checksumming many Arrow [`Buffer`]s. The
+//! methods here do that on the device. Copies are allocated the way Arrow
+//! allocates: aligned to and padded to a multiple of [`ARROW_ALIGNMENT`]
+//! bytes, with the padding zeroed, so the destination of every descriptor
+//! starts on a cache line.
+//!
+//! A column consists of several buffers (validity bitmap, offsets, values);
+//! [`DsaEngine::arrow_column_checksums`] checksums every buffer of every
+//! column in one batch and joins the CRC32s of each column's buffers with
+//! [`crc32c_combine`].
+//!
+//! Requires the `arrow` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use arrow_buffer::Buffer;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let values = Buffer::from_vec((0..1024i64).collect::<Vec<_>>());
+//! let copy = engine.copy_arrow_buffer_verified(&values)?;
+//! assert_eq!(copy, values);
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::crc::crc32c_combine;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use arrow_buffer::{Buffer, MutableBuffer};
+
+/// Alignment and padding of Arrow buffers in bytes, as recommended by the
+/// Arrow columnar format.
+pub const ARROW_ALIGNMENT: usize = 64;
+
+/// Returns true if `buf` starts on an [`ARROW_ALIGNMENT`] boundary.
+///
+/// Buffers allocated by Arrow are aligned, but slices of them need not be.
+pub fn is_arrow_aligned(buf: &Buffer) -> bool {
+    (buf.as_ptr() as usize).is_multiple_of(ARROW_ALIGNMENT)
+}
+
+/// Allocate a zeroed, aligned and padded buffer for `len` bytes.
+fn allocate(len: usize) -> MutableBuffer {
+    let mut buf = MutableBuffer::from_len_zeroed(len.next_multiple_of(ARROW_ALIGNMENT));
+    buf.truncate(len);
+    buf
+}
+
+impl DsaEngine {
+    /// Copy an Arrow buffer into a new aligned buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails.
+    pub fn copy_arrow_buffer(&self, src: &Buffer) -> Result<Buffer, DsaError> {
+        let mut dst = allocate(src.len());
+        self.memcpy(dst.as_slice_mut(), src.as_slice())?;
+        Ok(dst.into())
+    }
+
+    /// Copy an Arrow buffer like [`DsaEngine::copy_arrow_buffer`], then
+    /// verify the copy like [`DsaEngine::memcpy_verified`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::CopyVerificationFailed` if the copy differs from
+    /// the source, or an error if an operation fails.
+    pub fn copy_arrow_buffer_verified(&self, src: &Buffer) -> Result<Buffer, DsaError> {
+        let mut dst = allocate(src.len());
+        self.memcpy_verified(dst.as_slice_mut(), src.as_slice())?;
+        Ok(dst.into())
+    }
+
+    /// Compare the contents of two Arrow buffers.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the buffers have the same length and bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails.
+    pub fn arrow_buffers_equal(&self, a: &Buffer, b: &Buffer) -> Result<bool, DsaError> {
+        if a.len() != b.len() {
+            return Ok(false);
+        }
+        self.memcmp(a.as_slice(), b.as_slice())
+    }
+
+    /// Check an Arrow buffer against an expected CRC32.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::CrcMismatch` if the CRC32 differs, or an error if
+    /// the operation fails.
+    pub fn verify_arrow_buffer(&self, buf: &Buffer, expected: u32) -> Result<(), DsaError> {
+        self.verify_crc32(buf.as_slice(), expected)
+    }
+
+    /// Compute one CRC32 per column.
+    ///
+    /// The CRC32 of a column is that of its buffers concatenated in order.
+    /// All buffers are checksummed in one batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - The buffers of each column
+    ///
+    /// # Returns
+    ///
+    /// The CRC32 of every column, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation fails.
+    pub fn arrow_column_checksums(&self, columns: &[&[Buffer]]) -> Result<Vec<u32>, DsaError> {
+        let bufs: Vec<&[u8]> = columns
+            .iter()
+            .flat_map(|column| column.iter().map(Buffer::as_slice))
+            .collect();
+        let mut crcs = self.crc32_many(&bufs)?.into_iter().zip(bufs);
+
+        Ok(columns
+            .iter()
+            .map(|column| {
+                crcs.by_ref()
+                    .take(column.len())
+                    .fold(0, |crc, (buf_crc, buf)| {
+                        crc32c_combine(crc, buf_crc, buf.len() as u64)
+                    })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_is_aligned_and_padded() {
+        let buf: Buffer = allocate(100).into();
+        assert_eq!(buf.len(), 100);
+        assert!(buf.capacity() >= 128);
+        assert!(is_arrow_aligned(&buf));
+        assert!(!is_arrow_aligned(&buf.slice(1)));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_arrow_buffers() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let values = Buffer::from_vec((0..1000i32).collect::<Vec<_>>());
+        let offsets = Buffer::from_vec(vec![0i32, 3, 7]);
+        let validity = Buffer::from_vec(vec![0b1011u8]);
+
+        let copy = engine.copy_arrow_buffer(&values.slice(3)).unwrap();
+        assert!(is_arrow_aligned(&copy));
+        assert_eq!(copy.as_slice(), &values.as_slice()[3..]);
+        let copy = engine.copy_arrow_buffer_verified(&values).unwrap();
+        assert!(engine.arrow_buffers_equal(&copy, &values).unwrap());
+        assert!(!engine.arrow_buffers_equal(&copy, &offsets).unwrap());
+
+        let crc = engine.crc32(values.as_slice()).unwrap();
+        engine.verify_arrow_buffer(&copy, crc).unwrap();
+        assert!(matches!(
+            engine.verify_arrow_buffer(&offsets, crc),
+            Err(DsaError::CrcMismatch { .. })
+        ));
+
+        let column: Vec<u8> = [validity.as_slice(), offsets.as_slice(), values.as_slice()].concat();
+        let crcs = engine
+            .arrow_column_checksums(&[&[validity, offsets, values.clone()], &[], &[values]])
+            .unwrap();
+        assert_eq!(crcs, vec![engine.crc32(&column).unwrap(), 0, crc]);
+    }
+}
@@ -0,0 +1,162 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Completion polling backoff.
+//!
+//! A blocking operation polls its completion record until the hardware
+//! writes it. A fixed tight loop suits neither end of the range: a 4 KiB
+//! copy completes within a microsecond and should be noticed immediately,
+//! while a multi-megabyte CRC takes milliseconds during which spinning only
+//! burns a core and steals cycles from a sibling hyperthread.
+//!
+//! [`Backoff`] escalates with the time an operation has been pending: first
+//! a single `pause` between polls, then exponentially more `pause`
+//! instructions, and finally short sleeps.
+
+use std::time::{Duration, Instant};
+
+/// How completion polling waits between polls, by time pending.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::{Backoff, DsaEngine};
+/// use std::time::Duration;
+///
+/// let mut engine = DsaEngine::open_first()?;
+/// // Latency-critical small copies: spin longer before backing off
+/// engine.set_backoff(Backoff {
+///     spin: Duration::from_micros(20),
+///     ..Backoff::default()
+/// });
+/// # Ok::<(), dsa_rust::DsaError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Backoff {
+    /// Pending time during which polls are separated by a single `pause`.
+    pub spin: Duration,
+    /// Pending time after which polls are separated by sleeps; until then
+    /// the number of `pause` instructions doubles on every poll.
+    pub pause: Duration,
+    /// Upper bound on the `pause` instructions between two polls.
+    pub max_pauses: u32,
+    /// Sleep between polls once the operation has been pending for `pause`.
+    pub sleep: Duration,
+}
+
+impl Backoff {
+    /// Poll in a tight loop with a single `pause` between polls, never
+    /// sleeping.
+    pub const SPIN: Self = Self {
+        spin: Duration::MAX,
+        pause: Duration::MAX,
+        max_pauses: 1,
+        sleep: Duration::ZERO,
+    };
+}
+
+impl Default for Backoff {
+    /// Spin for 2 µs, pause up to 64 times per poll until 100 µs, then
+    /// sleep 20 µs between polls.
+    fn default() -> Self {
+        Self {
+            spin: Duration::from_micros(2),
+            pause: Duration::from_micros(100),
+            max_pauses: 64,
+            sleep: Duration::from_micros(20),
+        }
+    }
+}
+
+/// Backoff state of one wait.
+#[derive(Debug)]
+pub(crate) struct Poller {
+    backoff: Backoff,
+    started: Instant,
+    pauses: u32,
+}
+
+impl Poller {
+    /// Start waiting for an operation submitted just now.
+    pub(crate) fn new(backoff: Backoff) -> Self {
+        Self {
+            backoff,
+            started: Instant::now(),
+            pauses: 1,
+        }
+    }
+
+    /// Wait before the next poll.
+    pub(crate) fn snooze(&mut self) {
+        let pending = self.started.elapsed();
+        if pending < self.backoff.spin {
+            core::hint::spin_loop();
+        } else if pending < self.backoff.pause {
+            for _ in 0..self.pauses {
+                core::hint::spin_loop();
+            }
+            self.pauses = (self.pauses * 2).min(self.backoff.max_pauses.max(1));
+        } else {
+            std::thread::sleep(self.backoff.sleep);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pauses_escalate_and_cap() {
+        let mut poller = Poller::new(Backoff {
+            spin: Duration::ZERO,
+            pause: Duration::MAX,
+            max_pauses: 5,
+            sleep: Duration::ZERO,
+        });
+        let mut counts = Vec::new();
+        for _ in 0..5 {
+            counts.push(poller.pauses);
+            poller.snooze();
+        }
+        assert_eq!(counts, [1, 2, 4, 5, 5]);
+    }
+
+    #[test]
+    fn test_spin_never_escalates() {
+        let mut poller = Poller::new(Backoff::SPIN);
+        for _ in 0..100 {
+            poller.snooze();
+        }
+        assert_eq!(poller.pauses, 1);
+    }
+
+    #[test]
+    fn test_sleeps_after_pause_phase() {
+        let mut poller = Poller::new(Backoff {
+            spin: Duration::ZERO,
+            pause: Duration::ZERO,
+            max_pauses: 1,
+            sleep: Duration::from_millis(2),
+        });
+        let start = Instant::now();
+        poller.snooze();
+        assert!(start.elapsed() >= Duration::from_millis(2));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_backoff() {
+        use crate::emulator::Emulator;
+        use crate::engine::DsaEngine;
+        use std::sync::Arc;
+
+        let mut engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        assert_eq!(engine.work_queue().backoff(), Backoff::default());
+        engine.set_backoff(Backoff::SPIN);
+        assert_eq!(engine.work_queue().backoff(), Backoff::SPIN);
+        assert_eq!(engine.crc32(b"123456789").unwrap(), 0xE306_9283);
+    }
+}
@@ -0,0 +1,412 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Mixed-operation batches with per-operation results.
+//!
+//! A [`BatchBuilder`] collects operations of different kinds and submits
+//! them as hardware batches. [`BatchBuilder::submit`] returns a
+//! [`BatchResults`] with the outcome of every operation, so one failed
+//! descriptor in a batch of hundreds can be identified and retried on its
+//! own.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::batch::OpOutput;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let header = [1u8; 512];
+//! let mut copy = [0u8; 512];
+//!
+//! let mut batch = engine.batch();
+//! batch.crc32(&header, 0).memcpy(&mut copy, &header);
+//! for (index, opcode, result) in batch.submit()? {
+//!     match result {
+//!         Ok(OpOutput::Crc32(crc)) => println!("#{index} {opcode}: {crc:#010x}"),
+//!         Ok(_) => println!("#{index} {opcode}: done"),
+//!         Err(e) => eprintln!("#{index} {opcode} failed: {e}"),
+//!     }
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::opcode::DsaOpcode;
+#[cfg(dsa_portal)]
+use crate::{
+    descriptor::{DsaCompletionRecord, DsaHwDesc},
+    wq::check_completion,
+};
+
+/// Output of one successful operation in a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpOutput {
+    /// CRC32 of the data.
+    Crc32(u32),
+    /// Offset of the first difference of a compare, or `None` if equal.
+    Mismatch(Option<usize>),
+    /// The operation completed and has no value (copy, fill, no-op).
+    Done,
+}
+
+/// An operation queued in a [`BatchBuilder`].
+enum BatchOp<'a> {
+    Crc32 { data: &'a [u8], seed: u32 },
+    Memcpy { dst: &'a mut [u8], src: &'a [u8] },
+    Memset { dst: &'a mut [u8], pattern: u64 },
+    Memcmp { a: &'a [u8], b: &'a [u8] },
+    ComparePattern { buf: &'a [u8], pattern: u64 },
+    Noop,
+}
+
+impl BatchOp<'_> {
+    fn opcode(&self) -> DsaOpcode {
+        match self {
+            Self::Crc32 { .. } => DsaOpcode::CrcGen,
+            Self::Memcpy { .. } => DsaOpcode::MemMove,
+            Self::Memset { .. } => DsaOpcode::MemFill,
+            Self::Memcmp { .. } => DsaOpcode::Compare,
+            Self::ComparePattern { .. } => DsaOpcode::CompareImm,
+            Self::Noop => DsaOpcode::Noop,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Crc32 { data, .. } => data.len(),
+            Self::Memcpy { src, .. } => src.len(),
+            Self::Memset { dst, .. } => dst.len(),
+            Self::Memcmp { a, .. } => a.len(),
+            Self::ComparePattern { buf, .. } => buf.len(),
+            Self::Noop => 0,
+        }
+    }
+
+    /// Check the buffer sizes and produce the output directly for
+    /// operations that need no hardware.
+    fn precheck(&self) -> Option<Result<OpOutput, DsaError>> {
+        match self {
+            Self::Memcpy { dst, src } if dst.len() < src.len() => {
+                Some(Err(DsaError::BufferSizeMismatch {
+                    expected: src.len(),
+                    actual: dst.len(),
+                }))
+            }
+            Self::Memcmp { a, b } if a.len() != b.len() => {
+                Some(Err(DsaError::BufferSizeMismatch {
+                    expected: a.len(),
+                    actual: b.len(),
+                }))
+            }
+            Self::Crc32 { seed, .. } if self.len() == 0 => Some(Ok(OpOutput::Crc32(*seed))),
+            Self::Memcmp { .. } | Self::ComparePattern { .. } if self.len() == 0 => {
+                Some(Ok(OpOutput::Mismatch(None)))
+            }
+            Self::Noop => None,
+            _ if self.len() == 0 => Some(Ok(OpOutput::Done)),
+            _ => None,
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    fn descriptor(&mut self, record: &mut DsaCompletionRecord) -> DsaHwDesc {
+        match self {
+            Self::Crc32 { data, seed } => {
+                DsaHwDesc::crc_gen(data.as_ptr(), data.len(), *seed, record)
+            }
+            Self::Memcpy { dst, src } => {
+                DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), src.len(), record)
+            }
+            Self::Memset { dst, pattern } => {
+                DsaHwDesc::mem_fill(dst.as_mut_ptr(), dst.len(), *pattern, record)
+            }
+            Self::Memcmp { a, b } => DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), record),
+            Self::ComparePattern { buf, pattern } => {
+                DsaHwDesc::compare_imm(buf.as_ptr(), buf.len(), *pattern, record)
+            }
+            Self::Noop => DsaHwDesc::noop(record),
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    fn output(&self, record: &DsaCompletionRecord) -> Result<OpOutput, DsaError> {
+        check_completion(record)?;
+        Ok(match self {
+            Self::Crc32 { .. } => OpOutput::Crc32(record.crc32_result()),
+            Self::Memcmp { .. } | Self::ComparePattern { .. } => {
+                OpOutput::Mismatch(record.mismatch_offset())
+            }
+            _ => OpOutput::Done,
+        })
+    }
+
+    /// Run the operation on its own.
+    #[cfg(not(dsa_portal))]
+    fn run(&mut self, engine: &DsaEngine) -> Result<OpOutput, DsaError> {
+        let wq = engine.work_queue();
+        match self {
+            Self::Crc32 { data, seed } => wq.crc32(data, *seed).map(OpOutput::Crc32),
+            Self::Memcpy { dst, src } => wq.memcpy(dst, src).map(|()| OpOutput::Done),
+            Self::Memset { dst, pattern } => wq.memset(dst, *pattern).map(|()| OpOutput::Done),
+            Self::Memcmp { a, b } => wq.mismatch(a, b).map(OpOutput::Mismatch),
+            Self::ComparePattern { buf, pattern } => {
+                wq.compare_pattern(buf, *pattern).map(OpOutput::Mismatch)
+            }
+            Self::Noop => wq.noop().map(|()| OpOutput::Done),
+        }
+    }
+}
+
+/// Builder for a batch of mixed operations.
+///
+/// Created by [`DsaEngine::batch`]. Operations run in the order added but
+/// may complete in any order; use a separate batch when one operation
+/// depends on another's result. Batches larger than the device's batch size
+/// limit are split.
+pub struct BatchBuilder<'a> {
+    engine: &'a DsaEngine,
+    ops: Vec<BatchOp<'a>>,
+}
+
+impl<'a> BatchBuilder<'a> {
+    fn push(&mut self, op: BatchOp<'a>) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+
+    /// Add a CRC32 of `data`, starting from `seed`.
+    pub fn crc32(&mut self, data: &'a [u8], seed: u32) -> &mut Self {
+        self.push(BatchOp::Crc32 { data, seed })
+    }
+
+    /// Add a copy of `src` into the start of `dst`.
+    pub fn memcpy(&mut self, dst: &'a mut [u8], src: &'a [u8]) -> &mut Self {
+        self.push(BatchOp::Memcpy { dst, src })
+    }
+
+    /// Add a fill of `dst` with a 64-bit pattern.
+    pub fn memset(&mut self, dst: &'a mut [u8], pattern: u64) -> &mut Self {
+        self.push(BatchOp::Memset { dst, pattern })
+    }
+
+    /// Add a comparison of `a` and `b`.
+    pub fn memcmp(&mut self, a: &'a [u8], b: &'a [u8]) -> &mut Self {
+        self.push(BatchOp::Memcmp { a, b })
+    }
+
+    /// Add a check that `buf` consists of a repeated 64-bit pattern.
+    pub fn compare_pattern(&mut self, buf: &'a [u8], pattern: u64) -> &mut Self {
+        self.push(BatchOp::ComparePattern { buf, pattern })
+    }
+
+    /// Add a no-op.
+    pub fn noop(&mut self) -> &mut Self {
+        self.push(BatchOp::Noop)
+    }
+
+    /// Number of operations added.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns true if no operations were added.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Submit all operations and wait for them to complete.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of every operation, in the order added. An operation
+    /// that fails (including one with mismatched buffer sizes) does not
+    /// prevent the others from running.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if a batch could not be submitted or did not
+    /// complete, in which case no per-operation outcome is known.
+    pub fn submit(self) -> Result<BatchResults, DsaError> {
+        let BatchBuilder { engine, mut ops } = self;
+        let bytes = ops.iter().map(BatchOp::len).sum();
+        engine.throttle(bytes, ops.len());
+
+        let mut outcomes: Vec<Option<Result<OpOutput, DsaError>>> =
+            ops.iter().map(BatchOp::precheck).collect();
+
+        #[cfg(dsa_portal)]
+        {
+            let pending: Vec<usize> = (0..ops.len()).filter(|&i| outcomes[i].is_none()).collect();
+            let records = engine.retry(|| {
+                let mut records = vec![DsaCompletionRecord::new(); pending.len()];
+                let descs: Vec<DsaHwDesc> = pending
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&i, record)| ops[i].descriptor(record))
+                    .collect();
+                engine.work_queue().run_descriptors(descs, records)
+            })?;
+            for (&i, record) in pending.iter().zip(&records) {
+                outcomes[i] = Some(ops[i].output(record));
+            }
+        }
+        #[cfg(not(dsa_portal))]
+        for (op, outcome) in ops.iter_mut().zip(outcomes.iter_mut()) {
+            if outcome.is_none() {
+                *outcome = Some(op.run(engine));
+            }
+        }
+
+        let results = ops
+            .iter()
+            .zip(outcomes)
+            .map(|(op, outcome)| {
+                (
+                    op.opcode(),
+                    outcome.expect("every operation has an outcome"),
+                )
+            })
+            .collect();
+        Ok(BatchResults { results })
+    }
+}
+
+/// Outcomes of the operations in a batch, in the order they were added.
+///
+/// Iterating yields `(index, opcode, result)` for each operation.
+#[derive(Debug)]
+pub struct BatchResults {
+    results: Vec<(DsaOpcode, Result<OpOutput, DsaError>)>,
+}
+
+impl BatchResults {
+    /// Number of operations.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns true if the batch had no operations.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Outcome of the operation at `index`.
+    pub fn get(&self, index: usize) -> Option<&Result<OpOutput, DsaError>> {
+        self.results.get(index).map(|(_, result)| result)
+    }
+
+    /// Returns true if every operation succeeded.
+    pub fn all_succeeded(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Iterate over `(index, opcode, result)` without consuming the results.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, DsaOpcode, &Result<OpOutput, DsaError>)> {
+        self.results
+            .iter()
+            .enumerate()
+            .map(|(i, (opcode, result))| (i, *opcode, result))
+    }
+}
+
+impl IntoIterator for BatchResults {
+    type Item = (usize, DsaOpcode, Result<OpOutput, DsaError>);
+    type IntoIter = std::iter::Map<
+        std::iter::Enumerate<std::vec::IntoIter<(DsaOpcode, Result<OpOutput, DsaError>)>>,
+        fn((usize, (DsaOpcode, Result<OpOutput, DsaError>))) -> Self::Item,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results
+            .into_iter()
+            .enumerate()
+            .map(|(i, (opcode, result))| (i, opcode, result))
+    }
+}
+
+impl DsaEngine {
+    /// Start a batch of mixed operations on this engine.
+    pub fn batch(&self) -> BatchBuilder<'_> {
+        BatchBuilder {
+            engine: self,
+            ops: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precheck() {
+        let (a, b) = ([0u8; 4], [0u8; 8]);
+        let op = BatchOp::Memcmp { a: &a, b: &b };
+        assert!(matches!(
+            op.precheck(),
+            Some(Err(DsaError::BufferSizeMismatch { .. }))
+        ));
+        let op = BatchOp::Crc32 { data: &[], seed: 9 };
+        assert!(matches!(op.precheck(), Some(Ok(OpOutput::Crc32(9)))));
+        assert!(BatchOp::Noop.precheck().is_none());
+        let op = BatchOp::ComparePattern {
+            buf: &b,
+            pattern: 0,
+        };
+        assert!(op.precheck().is_none());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_per_operation_results() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let data = [5u8; 256];
+        let mut other = data;
+        other[17] = 0;
+        let mut copy = [0u8; 256];
+        let mut small = [0u8; 16];
+
+        // The descriptor at hardware index 2 (the copy) fails
+        emulator.inject(Fault::BatchFailure { index: 2 });
+        let mut batch = engine.batch();
+        batch
+            .crc32(&data, 0)
+            .memcmp(&data, &other)
+            .memcpy(&mut small, &data)
+            .memcpy(&mut copy, &data)
+            .compare_pattern(&data, 0x0505050505050505)
+            .noop();
+        let results = batch.submit().unwrap();
+
+        assert_eq!(results.len(), 6);
+        assert!(!results.all_succeeded());
+        let outcomes: Vec<_> = results.into_iter().collect();
+        assert_eq!(outcomes[0].1, DsaOpcode::CrcGen);
+        assert_eq!(
+            *outcomes[0].2.as_ref().unwrap(),
+            OpOutput::Crc32(crate::crc::software_crc32(&data, 0))
+        );
+        assert_eq!(
+            *outcomes[1].2.as_ref().unwrap(),
+            OpOutput::Mismatch(Some(17))
+        );
+        assert!(matches!(
+            outcomes[2].2,
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            outcomes[3].2,
+            Err(DsaError::OperationFailed { status: 0x10, .. })
+        ));
+        assert_eq!(*outcomes[4].2.as_ref().unwrap(), OpOutput::Mismatch(None));
+        assert_eq!(outcomes[5].0, 5);
+        assert!(outcomes[5].2.is_ok());
+    }
+}
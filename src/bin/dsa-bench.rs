@@ -0,0 +1,304 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Measure DSA throughput and latency on this host and print JSON.
+//!
+//! Unlike the criterion benchmarks, this binary is meant to run on
+//! production nodes: it measures every operation across a range of sizes on
+//! the first available work queue and writes one JSON document to stdout, so
+//! results can be collected and trended per node.
+//!
+//! Run with: `cargo run --release --bin dsa-bench -- [OPTIONS]`
+//!
+//! Options:
+//! - `--sizes 4096,65536,...` - buffer sizes in bytes
+//! - `--iterations N` - timed operations per op and size (default 1000)
+//! - `--ops crc32,memcpy,memset,memcmp,noop` - operations to measure
+//! - `--emulated` - use the software emulator instead of hardware (Linux)
+
+use dsa_rust::{DsaEngine, DsaError};
+use std::fmt::Write as _;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_SIZES: [usize; 6] = [256, 4096, 65536, 262144, 1 << 20, 4 << 20];
+const DEFAULT_ITERATIONS: usize = 1000;
+const OPS: [&str; 5] = ["crc32", "memcpy", "memset", "memcmp", "noop"];
+/// Untimed operations run before each measurement.
+const WARMUP: usize = 16;
+
+/// Command line options.
+struct Options {
+    sizes: Vec<usize>,
+    iterations: usize,
+    ops: Vec<String>,
+    emulated: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            sizes: DEFAULT_SIZES.to_vec(),
+            iterations: DEFAULT_ITERATIONS,
+            ops: OPS.iter().map(|op| op.to_string()).collect(),
+            emulated: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} requires a value"));
+            match arg.as_str() {
+                "--sizes" => {
+                    options.sizes = value()?
+                        .split(',')
+                        .map(|s| s.trim().parse().map_err(|_| format!("invalid size {s:?}")))
+                        .collect::<Result<_, _>>()?;
+                }
+                "--iterations" => {
+                    options.iterations = value()?
+                        .parse()
+                        .map_err(|_| "invalid iteration count".to_string())?;
+                }
+                "--ops" => {
+                    options.ops = value()?.split(',').map(|s| s.trim().to_string()).collect();
+                    if let Some(op) = options.ops.iter().find(|op| !OPS.contains(&op.as_str())) {
+                        return Err(format!("unknown op {op:?}; expected one of {OPS:?}"));
+                    }
+                }
+                "--emulated" => options.emulated = true,
+                _ => return Err(format!("unknown argument {arg:?}")),
+            }
+        }
+
+        if options.iterations == 0 {
+            return Err("--iterations must be positive".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// Measurements for one operation and size.
+struct Measurement {
+    op: String,
+    size: usize,
+    latencies: Vec<Duration>,
+    total: Duration,
+}
+
+impl Measurement {
+    /// Latency at quantile `q` (0.0..=1.0), from sorted latencies.
+    fn percentile(&self, q: f64) -> Duration {
+        let index = ((self.latencies.len() - 1) as f64 * q).round() as usize;
+        self.latencies[index]
+    }
+
+    fn write_json(&self, out: &mut String) {
+        let ops_per_sec = self.latencies.len() as f64 / self.total.as_secs_f64();
+        let ns = |d: Duration| d.as_nanos() as u64;
+        let _ = write!(
+            out,
+            "{{\"op\":\"{}\",\"size\":{},\"iterations\":{},\"ops_per_sec\":{:.1},\
+             \"bytes_per_sec\":{:.1},\"latency_ns\":{{\"min\":{},\"p50\":{},\"p99\":{},\
+             \"max\":{},\"mean\":{}}}}}",
+            escape(&self.op),
+            self.size,
+            self.latencies.len(),
+            ops_per_sec,
+            ops_per_sec * self.size as f64,
+            ns(self.latencies[0]),
+            ns(self.percentile(0.5)),
+            ns(self.percentile(0.99)),
+            ns(self.latencies[self.latencies.len() - 1]),
+            ns(self.total / self.latencies.len() as u32),
+        );
+    }
+}
+
+/// Time `iterations` runs of `op` after a short warm-up.
+fn measure(
+    name: &str,
+    size: usize,
+    iterations: usize,
+    mut op: impl FnMut() -> Result<(), DsaError>,
+) -> Result<Measurement, DsaError> {
+    for _ in 0..WARMUP {
+        op()?;
+    }
+
+    let mut latencies = Vec::with_capacity(iterations);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let t = Instant::now();
+        op()?;
+        latencies.push(t.elapsed());
+    }
+    let total = start.elapsed();
+    latencies.sort_unstable();
+
+    Ok(Measurement {
+        op: name.to_string(),
+        size,
+        latencies,
+        total,
+    })
+}
+
+/// Measure one operation at one size.
+fn run(
+    engine: &DsaEngine,
+    op: &str,
+    size: usize,
+    iterations: usize,
+) -> Result<Measurement, DsaError> {
+    let src: Vec<u8> = (0..size).map(|i| (i & 0xFF) as u8).collect();
+    let mut dst = vec![0u8; size];
+    match op {
+        "crc32" => measure(op, size, iterations, || engine.crc32(&src).map(drop)),
+        "memcpy" => measure(op, size, iterations, || engine.memcpy(&mut dst, &src)),
+        "memset" => measure(op, size, iterations, || engine.memset(&mut dst, 0)),
+        "memcmp" => {
+            dst.copy_from_slice(&src);
+            measure(op, size, iterations, || engine.memcmp(&src, &dst).map(drop))
+        }
+        _ => measure(op, 0, iterations, || engine.noop()),
+    }
+}
+
+#[cfg(dsa_portal)]
+fn open_engine(emulated: bool) -> Result<(DsaEngine, &'static str), DsaError> {
+    if emulated {
+        let emulator = std::sync::Arc::new(dsa_rust::emulator::Emulator::new());
+        return Ok((DsaEngine::emulated(emulator)?, "emulated"));
+    }
+    Ok((DsaEngine::open_first()?, "hardware"))
+}
+
+#[cfg(not(dsa_portal))]
+fn open_engine(emulated: bool) -> Result<(DsaEngine, &'static str), DsaError> {
+    if emulated {
+        return Err(DsaError::PlatformNotSupported);
+    }
+    Ok((DsaEngine::open_first()?, "software"))
+}
+
+/// Escape a string for inclusion in a JSON string literal.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("dsa-bench: {e}");
+            std::process::exit(2);
+        }
+    };
+    let (engine, backend) = match open_engine(options.emulated) {
+        Ok(opened) => opened,
+        Err(e) => {
+            eprintln!("dsa-bench: cannot open a DSA engine: {e}");
+            std::process::exit(1);
+        }
+    };
+    let devices: Vec<String> = dsa_rust::discover_devices()
+        .map(|devices| devices.into_iter().map(|d| d.name).collect())
+        .unwrap_or_default();
+
+    let mut results = Vec::new();
+    for op in &options.ops {
+        // The no-op does not depend on the buffer size
+        let sizes = if op == "noop" {
+            &[0][..]
+        } else {
+            &options.sizes[..]
+        };
+        for &size in sizes {
+            match run(&engine, op, size, options.iterations) {
+                Ok(measurement) => results.push(measurement),
+                Err(e) => eprintln!("dsa-bench: {op} at {size} bytes failed: {e}"),
+            }
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "{{\"host\":\"{}\",\"timestamp\":{},\"version\":\"{}\",\"backend\":\"{}\",\"devices\":[",
+        escape(&hostname()),
+        timestamp,
+        env!("CARGO_PKG_VERSION"),
+        backend,
+    );
+    for (i, device) in devices.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        let _ = write!(out, "{sep}\"{}\"", escape(device));
+    }
+    out.push_str("],\"results\":[");
+    for (i, measurement) in results.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        measurement.write_json(&mut out);
+    }
+    out.push_str("]}");
+    println!("{out}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let args = ["--sizes", "64,4096", "--iterations", "10", "--ops", "crc32"];
+        let options = Options::parse(args.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(options.sizes, vec![64, 4096]);
+        assert_eq!(options.iterations, 10);
+        assert_eq!(options.ops, vec!["crc32"]);
+
+        assert!(Options::parse(["--ops".to_string(), "fft".to_string()]).is_err());
+        assert!(Options::parse(["--sizes".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_measurement_json() {
+        let measurement = Measurement {
+            op: "crc32".to_string(),
+            size: 1000,
+            latencies: (1..=4).map(Duration::from_micros).collect(),
+            total: Duration::from_micros(10),
+        };
+        let mut out = String::new();
+        measurement.write_json(&mut out);
+        assert_eq!(
+            out,
+            "{\"op\":\"crc32\",\"size\":1000,\"iterations\":4,\"ops_per_sec\":400000.0,\
+             \"bytes_per_sec\":400000000.0,\"latency_ns\":{\"min\":1000,\"p50\":3000,\
+             \"p99\":4000,\"max\":4000,\"mean\":2500}}"
+        );
+        assert_eq!(escape("a\"b\\\n"), "a\\\"b\\\\\\u000a");
+    }
+}
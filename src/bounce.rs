@@ -0,0 +1,638 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Bounce buffers for tiny and unaligned transfers.
+//!
+//! DSA throughput falls off a cliff at odd buffer shapes. A descriptor
+//! costs the same whatever it moves, a destination that does not start or
+//! end on a cache line costs partial writes, and a buffer whose pages are
+//! not resident stops the descriptor with a page fault.
+//!
+//! With [`DsaEngine::set_bounce_buffers`], the engine keeps a small pool of
+//! page-aligned buffers that are faulted in and, where the memory lock
+//! limit allows, locked in memory. `crc32`, `memcpy`, `memset` and `memcmp`
+//! then pick a path for each transfer:
+//!
+//! - Transfers below [`BouncePolicy::threshold`], and unaligned ones that
+//!   fit a bounce buffer, are staged: the operands are copied into a bounce
+//!   buffer in software, the hardware runs on the aligned copy, and the
+//!   result is copied out.
+//! - Larger unaligned transfers are split: the unaligned head and tail are
+//!   handled in software and the aligned bulk goes through the hardware.
+//! - An aligned transfer that fits a bounce buffer but stops with a page
+//!   fault is run again staged.
+//!
+//! Everything else goes to the hardware as it is. When every bounce buffer
+//! is in use, transfers that would be staged go to the hardware directly.
+//! A buffer whose staged operation timed out or was cancelled is never
+//! returned to the pool, since the hardware may still write it.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::bounce::BouncePolicy;
+//! use dsa_rust::DsaEngine;
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! engine.set_bounce_buffers(Some(BouncePolicy::default()));
+//!
+//! let src = vec![7u8; 100_003];
+//! let mut dst = vec![0u8; 100_003];
+//! // The first 5 bytes are copied in software, the rest by the hardware
+//! engine.memcpy(&mut dst[5..], &src[5..])?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::crc;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::alloc::{self, Layout};
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+/// Alignment of the bounce buffers themselves.
+const PAGE_SIZE: usize = 4096;
+
+/// When and how transfers are bounced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BouncePolicy {
+    /// Transfers smaller than this many bytes are staged.
+    pub threshold: usize,
+    /// Alignment in bytes, a power of two, that the hardware should see.
+    pub alignment: usize,
+    /// Size of each bounce buffer in bytes. A staged transfer needs room
+    /// for all its operands, each rounded up to `alignment`.
+    pub buffer_size: usize,
+    /// Number of bounce buffers in the pool.
+    pub buffers: usize,
+}
+
+impl Default for BouncePolicy {
+    /// Stage transfers below 256 bytes and align to cache lines, with eight
+    /// 4 KiB buffers.
+    fn default() -> Self {
+        Self {
+            threshold: 256,
+            alignment: 64,
+            buffer_size: 4096,
+            buffers: 8,
+        }
+    }
+}
+
+/// How a transfer is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Plan {
+    /// Submit the user buffers as they are.
+    Direct,
+    /// Run the hardware on a copy in a bounce buffer.
+    Stage,
+    /// Handle `head` and `tail` bytes in software and the aligned bulk
+    /// between them in hardware.
+    Split { head: usize, tail: usize },
+}
+
+/// A page-aligned buffer, faulted in and locked in memory if allowed.
+struct BounceBuffer {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// SAFETY: The buffer is plain memory owned by this value.
+unsafe impl Send for BounceBuffer {}
+
+impl BounceBuffer {
+    fn new(len: usize) -> Self {
+        let layout =
+            Layout::from_size_align(len.max(1), PAGE_SIZE).expect("bounce buffer size overflows");
+        // SAFETY: the layout has a nonzero size
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        // Zeroed pages may not be backed yet; writing makes them resident
+        for offset in (0..layout.size()).step_by(PAGE_SIZE) {
+            // SAFETY: `offset` is within the allocation
+            unsafe { ptr.as_ptr().add(offset).write_volatile(0) };
+        }
+        #[cfg(dsa_portal)]
+        // SAFETY: the range is the allocation
+        if unsafe { libc::mlock(ptr.as_ptr().cast(), layout.size()) } != 0 {
+            log::debug!(
+                "bounce buffer not locked in memory: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Self { ptr, layout }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: the allocation is initialized and exclusively owned
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl Drop for BounceBuffer {
+    fn drop(&mut self) {
+        // SAFETY: the range is the allocation, which was allocated with
+        // `layout`; unlocking memory that is not locked is harmless
+        unsafe {
+            #[cfg(dsa_portal)]
+            libc::munlock(self.ptr.as_ptr().cast(), self.layout.size());
+            alloc::dealloc(self.ptr.as_ptr(), self.layout);
+        }
+    }
+}
+
+/// An engine's bounce buffers.
+pub(crate) struct BouncePool {
+    policy: BouncePolicy,
+    free: Mutex<Vec<BounceBuffer>>,
+}
+
+/// A bounce buffer taken from the pool, returned on drop.
+struct BounceGuard<'p> {
+    pool: &'p BouncePool,
+    buffer: Option<BounceBuffer>,
+}
+
+impl BounceGuard<'_> {
+    /// The first `count` regions of `len` bytes, each starting at a
+    /// multiple of the alignment.
+    fn regions(&mut self, len: usize, count: usize) -> Vec<&mut [u8]> {
+        let stride = len.next_multiple_of(self.pool.policy.alignment);
+        let buffer = self.buffer.as_mut().expect("guard holds a buffer");
+        buffer.as_mut_slice()[..stride * count]
+            .chunks_mut(stride)
+            .map(|region| &mut region[..len])
+            .collect()
+    }
+}
+
+impl BounceGuard<'_> {
+    /// Keep the buffer out of the pool if the operation behind `result`
+    /// was abandoned, since the hardware may still access it.
+    fn settle<T>(&mut self, result: &Result<T, DsaError>) {
+        if let Err(e) = result {
+            if crate::wq::abandons_record(e) {
+                std::mem::forget(self.buffer.take());
+            }
+        }
+    }
+}
+
+impl Drop for BounceGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.pool.free.lock().unwrap().push(buffer);
+        }
+    }
+}
+
+impl BouncePool {
+    /// Allocate the pool's buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alignment is not a power of two or exceeds the page
+    /// size.
+    pub(crate) fn new(policy: BouncePolicy) -> Self {
+        assert!(
+            policy.alignment.is_power_of_two() && policy.alignment <= PAGE_SIZE,
+            "bounce alignment must be a power of two of at most {PAGE_SIZE} bytes"
+        );
+        let free = (0..policy.buffers)
+            .map(|_| BounceBuffer::new(policy.buffer_size))
+            .collect();
+        Self {
+            policy,
+            free: Mutex::new(free),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> BouncePolicy {
+        self.policy
+    }
+
+    /// Take a free buffer, if any.
+    fn acquire(&self) -> Option<BounceGuard<'_>> {
+        let buffer = self.free.lock().unwrap().pop()?;
+        Some(BounceGuard {
+            pool: self,
+            buffer: Some(buffer),
+        })
+    }
+
+    /// Returns true if `operands` regions of `len` bytes fit one buffer.
+    fn fits(&self, len: usize, operands: usize) -> bool {
+        len.next_multiple_of(self.policy.alignment)
+            .checked_mul(operands)
+            .is_some_and(|size| size <= self.policy.buffer_size)
+    }
+
+    /// Choose how to run a transfer of `len` bytes with `operands` buffers,
+    /// aligning the one at `addr`.
+    fn plan(&self, len: usize, operands: usize, addr: usize) -> Plan {
+        let align = self.policy.alignment;
+        let fits = self.fits(len, operands);
+        if len == 0 {
+            return Plan::Direct;
+        }
+        if len < self.policy.threshold {
+            return if fits { Plan::Stage } else { Plan::Direct };
+        }
+        let head = (addr.next_multiple_of(align) - addr).min(len);
+        let tail = (len - head) % align;
+        if head == 0 && tail == 0 {
+            Plan::Direct
+        } else if fits {
+            Plan::Stage
+        } else if head + tail == len {
+            Plan::Direct
+        } else {
+            Plan::Split { head, tail }
+        }
+    }
+
+    /// Returns true if a direct transfer that failed with `result` should
+    /// run again staged.
+    fn restage<T>(&self, result: &Result<T, DsaError>, len: usize, operands: usize) -> bool {
+        matches!(result, Err(DsaError::PageFault { .. })) && self.fits(len, operands)
+    }
+}
+
+impl std::fmt::Debug for BouncePool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BouncePool")
+            .field("policy", &self.policy)
+            .field("free", &self.free.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Fill `buf` with `pattern` as if it started `phase` bytes into a fill.
+fn fill(buf: &mut [u8], pattern: u64, phase: usize) {
+    let bytes = pattern.to_le_bytes();
+    for (i, byte) in buf.iter_mut().enumerate() {
+        *byte = bytes[(phase + i) % 8];
+    }
+}
+
+impl DsaEngine {
+    /// Bounce tiny and unaligned transfers through a pool of aligned,
+    /// resident buffers.
+    ///
+    /// Pass `None` to send every transfer to the hardware as it is. See
+    /// the [`bounce`](crate::bounce) module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the alignment is not a power of two or exceeds 4096.
+    pub fn set_bounce_buffers(&mut self, policy: Option<BouncePolicy>) {
+        self.bounce = policy.map(BouncePool::new);
+    }
+
+    /// Get the current bounce policy, if bouncing is enabled.
+    pub fn bounce_buffers(&self) -> Option<BouncePolicy> {
+        self.bounce.as_ref().map(BouncePool::policy)
+    }
+
+    /// Compute a CRC32 of `data` as planned by `pool`.
+    pub(crate) fn crc32_bounced(
+        &self,
+        pool: &BouncePool,
+        data: &[u8],
+        seed: u32,
+    ) -> Result<u32, DsaError> {
+        let len = data.len();
+        let result = match pool.plan(len, 1, data.as_ptr() as usize) {
+            Plan::Stage => match self.crc32_staged(pool, data, seed) {
+                Some(result) => return result,
+                None => self.crc32_direct(data, seed),
+            },
+            Plan::Direct => self.crc32_direct(data, seed),
+            Plan::Split { head, tail } => {
+                let seed = crc::software_crc32(&data[..head], seed);
+                let seed = self.crc32_direct(&data[head..len - tail], seed)?;
+                return Ok(crc::software_crc32(&data[len - tail..], seed));
+            }
+        };
+        if pool.restage(&result, len, 1) {
+            return self.crc32_staged(pool, data, seed).unwrap_or(result);
+        }
+        result
+    }
+
+    fn crc32_staged(
+        &self,
+        pool: &BouncePool,
+        data: &[u8],
+        seed: u32,
+    ) -> Option<Result<u32, DsaError>> {
+        let mut guard = pool.acquire()?;
+        let mut regions = guard.regions(data.len(), 1);
+        regions[0].copy_from_slice(data);
+        self.throttle(data.len(), 1);
+        let result = self.retry(|| self.work_queue().crc32(regions[0], seed));
+        guard.settle(&result);
+        Some(result)
+    }
+
+    /// Copy `src` into `dst` as planned by `pool`.
+    pub(crate) fn memcpy_bounced(
+        &self,
+        pool: &BouncePool,
+        dst: &mut [u8],
+        src: &[u8],
+    ) -> Result<(), DsaError> {
+        let len = src.len();
+        if dst.len() < len {
+            return self.memcpy_direct(dst, src);
+        }
+        let result = match pool.plan(len, 2, dst.as_ptr() as usize) {
+            Plan::Stage => match self.memcpy_staged(pool, dst, src) {
+                Some(result) => return result,
+                None => self.memcpy_direct(dst, src),
+            },
+            Plan::Direct => self.memcpy_direct(dst, src),
+            Plan::Split { head, tail } => {
+                dst[..head].copy_from_slice(&src[..head]);
+                dst[len - tail..len].copy_from_slice(&src[len - tail..]);
+                return self.memcpy_direct(&mut dst[head..len - tail], &src[head..len - tail]);
+            }
+        };
+        if pool.restage(&result, len, 2) {
+            return self.memcpy_staged(pool, dst, src).unwrap_or(result);
+        }
+        result
+    }
+
+    fn memcpy_staged(
+        &self,
+        pool: &BouncePool,
+        dst: &mut [u8],
+        src: &[u8],
+    ) -> Option<Result<(), DsaError>> {
+        let len = src.len();
+        let mut guard = pool.acquire()?;
+        let mut regions = guard.regions(len, 2);
+        let (staged_src, staged_dst) = regions.split_at_mut(1);
+        staged_src[0].copy_from_slice(src);
+        self.throttle(len, 1);
+        let result = self.retry(|| self.work_queue().memcpy(staged_dst[0], staged_src[0]));
+        if result.is_ok() {
+            dst[..len].copy_from_slice(staged_dst[0]);
+        }
+        guard.settle(&result);
+        Some(result)
+    }
+
+    /// Fill `dst` with `pattern` as planned by `pool`.
+    pub(crate) fn memset_bounced(
+        &self,
+        pool: &BouncePool,
+        dst: &mut [u8],
+        pattern: u64,
+    ) -> Result<(), DsaError> {
+        let len = dst.len();
+        let result = match pool.plan(len, 1, dst.as_ptr() as usize) {
+            Plan::Stage => match self.memset_staged(pool, dst, pattern) {
+                Some(result) => return result,
+                None => self.memset_direct(dst, pattern),
+            },
+            Plan::Direct => self.memset_direct(dst, pattern),
+            Plan::Split { head, tail } => {
+                fill(&mut dst[..head], pattern, 0);
+                fill(&mut dst[len - tail..], pattern, len - tail);
+                // The bulk starts `head` bytes into the pattern
+                let rotated = pattern.rotate_right(8 * (head % 8) as u32);
+                return self.memset_direct(&mut dst[head..len - tail], rotated);
+            }
+        };
+        if pool.restage(&result, len, 1) {
+            return self.memset_staged(pool, dst, pattern).unwrap_or(result);
+        }
+        result
+    }
+
+    fn memset_staged(
+        &self,
+        pool: &BouncePool,
+        dst: &mut [u8],
+        pattern: u64,
+    ) -> Option<Result<(), DsaError>> {
+        let mut guard = pool.acquire()?;
+        let mut regions = guard.regions(dst.len(), 1);
+        self.throttle(dst.len(), 1);
+        let result = self.retry(|| self.work_queue().memset(regions[0], pattern));
+        if result.is_ok() {
+            dst.copy_from_slice(regions[0]);
+        }
+        guard.settle(&result);
+        Some(result)
+    }
+
+    /// Compare `a` and `b` as planned by `pool`.
+    pub(crate) fn memcmp_bounced(
+        &self,
+        pool: &BouncePool,
+        a: &[u8],
+        b: &[u8],
+    ) -> Result<bool, DsaError> {
+        let len = a.len();
+        if b.len() != len {
+            return self.memcmp_direct(a, b);
+        }
+        let result = match pool.plan(len, 2, a.as_ptr() as usize) {
+            Plan::Stage => match self.memcmp_staged(pool, a, b) {
+                Some(result) => return result,
+                None => self.memcmp_direct(a, b),
+            },
+            Plan::Direct => self.memcmp_direct(a, b),
+            Plan::Split { head, tail } => {
+                if a[..head] != b[..head] || a[len - tail..] != b[len - tail..] {
+                    return Ok(false);
+                }
+                return self.memcmp_direct(&a[head..len - tail], &b[head..len - tail]);
+            }
+        };
+        if pool.restage(&result, len, 2) {
+            return self.memcmp_staged(pool, a, b).unwrap_or(result);
+        }
+        result
+    }
+
+    fn memcmp_staged(
+        &self,
+        pool: &BouncePool,
+        a: &[u8],
+        b: &[u8],
+    ) -> Option<Result<bool, DsaError>> {
+        let mut guard = pool.acquire()?;
+        let mut regions = guard.regions(a.len(), 2);
+        regions[0].copy_from_slice(a);
+        regions[1].copy_from_slice(b);
+        self.throttle(a.len(), 1);
+        let result = self.retry(|| self.work_queue().memcmp(regions[0], regions[1]));
+        guard.settle(&result);
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let pool = BouncePool::new(BouncePolicy {
+            buffers: 1,
+            ..BouncePolicy::default()
+        });
+        assert_eq!(pool.plan(0, 1, 0x1001), Plan::Direct);
+        assert_eq!(pool.plan(100, 1, 0x1000), Plan::Stage);
+        assert_eq!(pool.plan(100, 2, 0x1001), Plan::Stage);
+        // Aligned transfers go straight to the hardware
+        assert_eq!(pool.plan(1024, 2, 0x1000), Plan::Direct);
+        assert_eq!(pool.plan(1 << 20, 1, 0x1000), Plan::Direct);
+        // Unaligned ones are staged if they fit, split otherwise
+        assert_eq!(pool.plan(1000, 2, 0x1000), Plan::Stage);
+        assert_eq!(pool.plan(4000, 1, 0x1001), Plan::Stage);
+        assert_eq!(
+            pool.plan(4000, 2, 0x1001),
+            Plan::Split { head: 63, tail: 33 }
+        );
+        assert_eq!(pool.plan(5000, 1, 0x1000), Plan::Split { head: 0, tail: 8 });
+    }
+
+    #[test]
+    fn test_pool_hands_out_each_buffer_once() {
+        let pool = BouncePool::new(BouncePolicy {
+            buffers: 2,
+            ..BouncePolicy::default()
+        });
+        let mut a = pool.acquire().unwrap();
+        let b = pool.acquire().unwrap();
+        assert!(pool.acquire().is_none());
+        drop(b);
+        assert!(pool.acquire().is_some());
+
+        let regions = a.regions(100, 2);
+        assert_eq!(regions[0].len(), 100);
+        assert_eq!(regions[0].as_ptr() as usize % PAGE_SIZE, 0);
+        assert_eq!(
+            regions[1].as_ptr() as usize - regions[0].as_ptr() as usize,
+            128
+        );
+    }
+
+    #[test]
+    fn test_fill_phase() {
+        let pattern = 0x0807_0605_0403_0201;
+        let mut buf = [0u8; 5];
+        fill(&mut buf, pattern, 6);
+        assert_eq!(buf, [7, 8, 1, 2, 3]);
+        assert_eq!(pattern.rotate_right(8 * 6).to_le_bytes()[0], 7);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_bounced_operations() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let mut engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        engine.set_bounce_buffers(Some(BouncePolicy::default()));
+        let src: Vec<u8> = (0..20_000).map(|i| (i * 7 % 251) as u8).collect();
+        let pattern = 0x1122_3344_5566_7788;
+
+        for (offset, len) in [
+            (0, 1),
+            (3, 100),
+            (0, 1000),
+            (5, 1500),
+            (17, 3000),
+            (9, 12_345),
+        ] {
+            let data = &src[offset..offset + len];
+            assert_eq!(
+                engine.crc32(data).unwrap(),
+                crate::crc::software_crc32(data, 0)
+            );
+
+            let mut dst = vec![0u8; len + 64];
+            engine.memcpy(&mut dst[offset % 64..], data).unwrap();
+            assert_eq!(&dst[offset % 64..offset % 64 + len], data);
+
+            let region = &mut dst[offset % 64..offset % 64 + len];
+            engine.memset(region, pattern).unwrap();
+            let mut expected = vec![0u8; len];
+            fill(&mut expected, pattern, 0);
+            assert_eq!(region, &expected[..]);
+
+            assert!(engine.memcmp(region, &expected).unwrap());
+            expected[len - 1] ^= 1;
+            assert!(!engine.memcmp(region, &expected).unwrap());
+            expected[len - 1] ^= 1;
+            expected[0] ^= 1;
+            assert!(!engine.memcmp(region, &expected).unwrap());
+        }
+        assert_eq!(engine.bounce_buffers(), Some(BouncePolicy::default()));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_abandoned_buffer_not_reused() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        engine.work_queue_mut().set_spin_iterations(100);
+        engine.set_bounce_buffers(Some(BouncePolicy {
+            buffers: 1,
+            ..BouncePolicy::default()
+        }));
+        let free = |engine: &DsaEngine| engine.bounce.as_ref().unwrap().free.lock().unwrap().len();
+        let data = [5u8; 100];
+
+        // The hardware may still write the buffer of a timed out operation
+        emulator.inject(Fault::Stall);
+        assert!(matches!(engine.crc32(&data), Err(DsaError::Timeout { .. })));
+        assert_eq!(free(&engine), 0);
+
+        // Later transfers go to the hardware directly
+        assert_eq!(
+            engine.crc32(&data).unwrap(),
+            crate::crc::software_crc32(&data, 0)
+        );
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_page_fault_restaged() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        #[repr(align(64))]
+        struct Aligned([u8; 1024]);
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let src = [3u8; 1024];
+        let mut dst = Box::new(Aligned([0u8; 1024]));
+        let dst = &mut dst.0;
+
+        emulator.inject(Fault::PageFault { offset: 512 });
+        assert!(matches!(
+            engine.memcpy(dst, &src),
+            Err(DsaError::PageFault { .. })
+        ));
+
+        engine.set_bounce_buffers(Some(BouncePolicy::default()));
+        dst.fill(0);
+        emulator.inject(Fault::PageFault { offset: 512 });
+        engine.memcpy(dst, &src).unwrap();
+        assert_eq!(*dst, src);
+    }
+}
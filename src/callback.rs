@@ -0,0 +1,435 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Completion callbacks.
+//!
+//! [`DsaEngine::submit_with_callback`] submits an operation and returns
+//! immediately; the completion reactor thread invokes a callback once the
+//! hardware has finished. This suits callback-driven pipelines that do not
+//! run an async executor.
+//!
+//! Because the caller returns before the hardware is done, the operation
+//! owns its buffers ([`DsaOp`]) and hands them back to the callback.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::callback::{DsaOp, DsaOutput};
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let op = DsaOp::Crc32 { data: vec![0u8; 1 << 20], seed: 0 };
+//! engine.submit_with_callback(op, |op, result| match result {
+//!     Ok(DsaOutput::Crc32(crc)) => println!("CRC32: {crc:#010x}"),
+//!     Ok(_) => unreachable!(),
+//!     Err(e) => eprintln!("CRC failed: {e}"),
+//! });
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+#[cfg(dsa_portal)]
+use crate::{
+    descriptor::{DsaCompletionRecord, DsaHwDesc},
+    reactor::InFlight,
+};
+#[cfg(dsa_portal)]
+use std::sync::{Arc, Mutex};
+
+/// Callback receiving a completed operation and its result.
+pub(crate) type OpCallback = Box<dyn FnOnce(DsaOp, Result<DsaOutput, DsaError>) + Send>;
+
+/// An operation whose buffers are owned, for callback submission.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DsaOp {
+    /// Compute the CRC32 of `data`, starting from `seed`.
+    Crc32 { data: Vec<u8>, seed: u32 },
+    /// Copy `src` into the start of `dst`.
+    Memcpy { dst: Vec<u8>, src: Vec<u8> },
+    /// Fill `dst` with a 64-bit pattern.
+    Memset { dst: Vec<u8>, pattern: u64 },
+    /// Compare `a` and `b`, which must have the same length.
+    Memcmp { a: Vec<u8>, b: Vec<u8> },
+    /// Do nothing; completes once preceding work has been accepted.
+    Noop,
+}
+
+/// Result of a completed [`DsaOp`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsaOutput {
+    /// CRC32 of the data.
+    Crc32(u32),
+    /// The copy completed.
+    Memcpy,
+    /// The fill completed.
+    Memset,
+    /// True if the buffers are equal.
+    Memcmp(bool),
+    /// The no-op completed.
+    Noop,
+}
+
+impl DsaOp {
+    /// Check the buffer sizes and produce the output directly for
+    /// operations that need no hardware.
+    pub(crate) fn precheck(&self) -> Result<Option<DsaOutput>, DsaError> {
+        match self {
+            Self::Crc32 { data, seed } if data.is_empty() => Ok(Some(DsaOutput::Crc32(*seed))),
+            Self::Memcpy { dst, src } if dst.len() < src.len() => {
+                Err(DsaError::BufferSizeMismatch {
+                    expected: src.len(),
+                    actual: dst.len(),
+                })
+            }
+            Self::Memcpy { src, .. } if src.is_empty() => Ok(Some(DsaOutput::Memcpy)),
+            Self::Memset { dst, .. } if dst.is_empty() => Ok(Some(DsaOutput::Memset)),
+            Self::Memcmp { a, b } if a.len() != b.len() => Err(DsaError::BufferSizeMismatch {
+                expected: a.len(),
+                actual: b.len(),
+            }),
+            Self::Memcmp { a, .. } if a.is_empty() => Ok(Some(DsaOutput::Memcmp(true))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Build the descriptor for this operation.
+    ///
+    /// The descriptor points into the operation's heap buffers, which stay
+    /// in place when the operation is moved.
+    #[cfg(dsa_portal)]
+    pub(crate) fn descriptor(&mut self, record: &mut DsaCompletionRecord) -> DsaHwDesc {
+        match self {
+            Self::Crc32 { data, seed } => {
+                DsaHwDesc::crc_gen(data.as_ptr(), data.len(), *seed, record)
+            }
+            Self::Memcpy { dst, src } => {
+                DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), src.len(), record)
+            }
+            Self::Memset { dst, pattern } => {
+                DsaHwDesc::mem_fill(dst.as_mut_ptr(), dst.len(), *pattern, record)
+            }
+            Self::Memcmp { a, b } => DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), record),
+            Self::Noop => DsaHwDesc::noop(record),
+        }
+    }
+
+    /// Extract the output from a successfully completed record.
+    #[cfg(dsa_portal)]
+    pub(crate) fn output(&self, record: &DsaCompletionRecord) -> DsaOutput {
+        match self {
+            Self::Crc32 { .. } => DsaOutput::Crc32(record.crc32_result()),
+            Self::Memcpy { .. } => DsaOutput::Memcpy,
+            Self::Memset { .. } => DsaOutput::Memset,
+            Self::Memcmp { .. } => DsaOutput::Memcmp(record.compare_result()),
+            Self::Noop => DsaOutput::Noop,
+        }
+    }
+
+    /// Run the operation to completion on `engine`.
+    #[cfg(not(dsa_portal))]
+    pub(crate) fn run(&mut self, engine: &DsaEngine) -> Result<DsaOutput, DsaError> {
+        match self {
+            Self::Crc32 { data, seed } => engine.crc32_with_seed(data, *seed).map(DsaOutput::Crc32),
+            Self::Memcpy { dst, src } => engine.memcpy(dst, src).map(|()| DsaOutput::Memcpy),
+            Self::Memset { dst, pattern } => {
+                engine.memset(dst, *pattern).map(|()| DsaOutput::Memset)
+            }
+            Self::Memcmp { a, b } => engine.memcmp(a, b).map(DsaOutput::Memcmp),
+            Self::Noop => engine.noop().map(|()| DsaOutput::Noop),
+        }
+    }
+}
+
+impl DsaEngine {
+    /// Submit an operation and invoke `callback` when it completes.
+    ///
+    /// Returns as soon as the descriptor is submitted. The callback receives
+    /// the operation back, with ownership of its buffers, together with the
+    /// result. It is invoked exactly once: on the completion reactor thread
+    /// once the hardware is done, or on the calling thread if the operation
+    /// is rejected before submission (for example a size mismatch or a full
+    /// queue) or needs no hardware (empty buffers).
+    ///
+    /// The queue-full policy is not applied. Callbacks run one at a time on
+    /// the reactor thread, so they should hand long-running work elsewhere.
+    /// On platforms without hardware DSA, the operation runs in software and
+    /// the callback is invoked before this method returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The operation and its buffers
+    /// * `callback` - Called with the operation and its result
+    pub fn submit_with_callback<F>(&self, op: DsaOp, callback: F)
+    where
+        F: FnOnce(DsaOp, Result<DsaOutput, DsaError>) + Send + 'static,
+    {
+        self.submit_boxed(op, Box::new(callback), false);
+    }
+
+    /// Submit an operation like `submit_with_callback`, applying the
+    /// queue-full policy if `retry` is set.
+    pub(crate) fn submit_boxed(&self, op: DsaOp, callback: OpCallback, retry: bool) {
+        match op.precheck() {
+            Ok(Some(output)) => return callback(op, Ok(output)),
+            Err(e) => return callback(op, Err(e)),
+            Ok(None) => {}
+        }
+
+        #[cfg(dsa_portal)]
+        {
+            let mut op = op;
+            let in_flight = InFlight::new();
+            let desc = op.descriptor(in_flight.record_mut());
+            // The reactor holds the operation while running its callback
+            let origin = Arc::downgrade(&in_flight);
+            in_flight.set_callback(Box::new(move |record| {
+                let result = record
+                    .and_then(|record| crate::wq::check_completion(record).map(|()| record))
+                    .map_err(|e| match origin.upgrade() {
+                        Some(in_flight) => in_flight.add_context(e),
+                        None => e,
+                    })
+                    .map(|record| op.output(record));
+                callback(op, result);
+            }));
+            let start = || self.work_queue().start_in_flight(&in_flight, &desc);
+            let started = if retry { self.retry(start) } else { start() };
+            if let Err(e) = started {
+                if let Some(callback) = in_flight.take_callback() {
+                    callback(Err(e));
+                }
+            }
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            // Blocking operations apply the queue-full policy themselves
+            let _ = retry;
+            let mut op = op;
+            let result = op.run(self);
+            callback(op, result);
+        }
+    }
+
+    /// Submit `ops` together as one batch descriptor.
+    ///
+    /// Each callback is invoked exactly once, as by `submit_boxed`, but only
+    /// when the whole batch has completed. Operations that need no hardware
+    /// or are rejected by their precheck are reported immediately. If the
+    /// batch cannot be submitted under the queue-full policy, each
+    /// operation is tried once on its own and reports its own error.
+    pub(crate) fn submit_coalesced(&self, ops: Vec<(DsaOp, OpCallback)>) {
+        let mut pending = Vec::with_capacity(ops.len());
+        for (op, callback) in ops {
+            match op.precheck() {
+                Ok(Some(output)) => callback(op, Ok(output)),
+                Err(e) => callback(op, Err(e)),
+                Ok(None) => pending.push((op, callback)),
+            }
+        }
+
+        #[cfg(dsa_portal)]
+        if pending.len() >= 2 {
+            return self.start_coalesced(pending);
+        }
+        for (op, callback) in pending {
+            self.submit_boxed(op, callback, true);
+        }
+    }
+
+    /// Submit at least two prechecked operations as one batch descriptor.
+    #[cfg(dsa_portal)]
+    fn start_coalesced(&self, mut ops: Vec<(DsaOp, OpCallback)>) {
+        let mut records = vec![DsaCompletionRecord::new(); ops.len()].into_boxed_slice();
+        let descs: Vec<DsaHwDesc> = ops
+            .iter_mut()
+            .zip(records.iter_mut())
+            .map(|((op, _), record)| op.descriptor(record))
+            .collect();
+        let batch = InFlight::new();
+        let desc = DsaHwDesc::batch(descs.as_ptr(), descs.len(), batch.record_mut());
+
+        // The batch callback owns the operations, but they must be taken
+        // back if the batch is never submitted
+        let members = Arc::new(Mutex::new(Some(Coalesced {
+            ops,
+            records,
+            descs,
+        })));
+        let completed = Arc::clone(&members);
+        batch.set_callback(Box::new(move |outcome| {
+            if let Some(members) = completed.lock().unwrap().take() {
+                members.complete(outcome);
+            }
+        }));
+
+        let started = self.retry(|| self.work_queue().start_in_flight(&batch, &desc));
+        if started.is_err() {
+            drop(batch.take_callback());
+            let members = members.lock().unwrap().take();
+            for (op, callback) in members.map(|members| members.ops).unwrap_or_default() {
+                self.submit_boxed(op, callback, false);
+            }
+        }
+    }
+}
+
+/// Operations coalesced into one batch descriptor, with the completion
+/// records and descriptor list the hardware accesses until it completes.
+#[cfg(dsa_portal)]
+struct Coalesced {
+    ops: Vec<(DsaOp, OpCallback)>,
+    records: Box<[DsaCompletionRecord]>,
+    descs: Vec<DsaHwDesc>,
+}
+
+#[cfg(dsa_portal)]
+impl Coalesced {
+    /// Report every operation once the batch has its `outcome`.
+    fn complete(self, outcome: Result<&DsaCompletionRecord, DsaError>) {
+        let total = self.ops.len() as u32;
+        for ((op, callback), record) in self.ops.into_iter().zip(self.records.iter()) {
+            let result = if record.is_complete() {
+                crate::wq::check_completion(record).map(|()| op.output(record))
+            } else {
+                // The batch stopped before reaching this descriptor
+                Err(match &outcome {
+                    Err(DsaError::WorkQueueDisabled(name)) => {
+                        DsaError::WorkQueueDisabled(name.clone())
+                    }
+                    _ => DsaError::BatchFailed {
+                        status: outcome.as_ref().map_or(0, |batch| batch.status),
+                        descriptors_completed: outcome
+                            .as_ref()
+                            .map_or(0, |batch| batch.bytes_completed),
+                        descriptors_total: total,
+                    },
+                })
+            };
+            callback(op, result);
+        }
+        if outcome.is_err() {
+            // The work queue went away; the hardware may still write these
+            std::mem::forget(self.records);
+            std::mem::forget(self.descs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precheck() {
+        let op = DsaOp::Crc32 {
+            data: Vec::new(),
+            seed: 7,
+        };
+        assert_eq!(op.precheck().unwrap(), Some(DsaOutput::Crc32(7)));
+
+        let op = DsaOp::Memcpy {
+            dst: vec![0; 4],
+            src: vec![0; 8],
+        };
+        assert!(matches!(
+            op.precheck(),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 8,
+                actual: 4
+            })
+        ));
+
+        let op = DsaOp::Memset {
+            dst: vec![0; 8],
+            pattern: 0,
+        };
+        assert_eq!(op.precheck().unwrap(), None);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_callback_with_emulator() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::{mpsc, Arc};
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        let src: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let op = DsaOp::Memcpy {
+            dst: vec![0; 4096],
+            src: src.clone(),
+        };
+        let sender = tx.clone();
+        engine.submit_with_callback(op, move |op, result| sender.send((op, result)).unwrap());
+        let (op, result) = rx.recv().unwrap();
+        assert_eq!(result.unwrap(), DsaOutput::Memcpy);
+        let DsaOp::Memcpy { dst, .. } = op else {
+            panic!("wrong operation returned");
+        };
+        assert_eq!(dst, src);
+
+        emulator.inject(Fault::InvalidFlags);
+        let op = DsaOp::Crc32 { data: src, seed: 0 };
+        engine.submit_with_callback(op, move |op, result| tx.send((op, result)).unwrap());
+        let (op, result) = rx.recv().unwrap();
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            DsaError::OperationFailed { status: 0x10, .. }
+        ));
+        let context = err.context().unwrap();
+        assert_eq!(context.opcode, crate::DsaOpcode::CrcGen.into());
+        assert_eq!(context.xfer_size, 4096);
+        assert!(matches!(op, DsaOp::Crc32 { .. }));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_coalesced_batch() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::{mpsc, Arc};
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let ops: Vec<(DsaOp, OpCallback)> = (0..4u8)
+            .map(|i| {
+                let tx = tx.clone();
+                let op = DsaOp::Memset {
+                    dst: vec![0; 64],
+                    pattern: u64::from(i),
+                };
+                let callback: OpCallback = Box::new(move |op, result| {
+                    tx.send((i, op, result)).unwrap();
+                });
+                (op, callback)
+            })
+            .collect();
+        drop(tx);
+
+        emulator.inject(Fault::BatchFailure { index: 2 });
+        engine.submit_coalesced(ops);
+        let mut done: Vec<_> = rx.iter().collect();
+        done.sort_by_key(|&(i, ..)| i);
+        assert_eq!(done.len(), 4);
+        assert_eq!(emulator.submitted(), 1);
+        for (i, op, result) in done {
+            if i == 2 {
+                assert!(matches!(
+                    result,
+                    Err(DsaError::OperationFailed { status: 0x10, .. })
+                ));
+            } else {
+                assert_eq!(result.unwrap(), DsaOutput::Memset);
+                let DsaOp::Memset { dst, .. } = op else {
+                    panic!("wrong operation returned");
+                };
+                assert_eq!(dst[..8], u64::from(i).to_le_bytes());
+            }
+        }
+    }
+}
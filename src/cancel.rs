@@ -0,0 +1,420 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Per-operation deadlines and cancellation.
+//!
+//! An [`OpContext`] carries an optional deadline and an optional
+//! [`CancellationToken`] into a blocking operation, so request-scoped
+//! services can give up on an offload that is stuck or no longer needed.
+//!
+//! The `*_with_context` methods of [`DsaEngine`](crate::DsaEngine) split
+//! large buffers into chunks of [`CONTEXT_CHUNK_SIZE`] and check the context
+//! before each chunk. While waiting for a single descriptor they stop
+//! polling as soon as the context expires; the descriptor's completion
+//! record is then abandoned, as after a timeout, so the hardware can never
+//! write into reused memory. With a soft timeout per chunk (see
+//! [`degrade`](crate::degrade)), a stalled chunk is completed in software
+//! instead.
+//!
+//! A context may also carry a [`Profile`], which replaces the engine's
+//! profile for the descriptors of that call.
+//!
+//! The hardware cannot be told to stop: the abandoned descriptor may still
+//! read its source and write its destination. Like the buffers of a timed
+//! out operation, they must not be reused until the device has gone idle.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::cancel::{CancellationToken, OpContext};
+//! use dsa_rust::DsaEngine;
+//! use std::time::Duration;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let token = CancellationToken::new();
+//! let ctx = OpContext::new()
+//!     .with_timeout(Duration::from_millis(5))
+//!     .with_token(token.clone());
+//!
+//! let data = vec![0u8; 64 << 20];
+//! match engine.crc32_with_context(&data, &ctx) {
+//!     Ok(crc) => println!("CRC32: {crc:#010x}"),
+//!     Err(e) => eprintln!("gave up: {e}"),
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::profile::Profile;
+use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Bytes per chunk when an operation runs with an [`OpContext`].
+pub const CONTEXT_CHUNK_SIZE: usize = 1 << 20;
+
+/// A flag shared between an operation and whoever may cancel it.
+///
+/// Clones share the flag; cancelling any clone cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every operation using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns true once `cancel` has been called on any clone.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Deadline, cancellation token and profile of one operation.
+///
+/// The default context never expires and uses the engine's profile.
+#[derive(Debug, Clone, Default)]
+pub struct OpContext {
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+    profile: Option<Profile>,
+}
+
+impl OpContext {
+    /// A context that never expires.
+    pub const NONE: Self = Self {
+        deadline: None,
+        token: None,
+        profile: None,
+    };
+
+    /// Create a context that never expires.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up once `deadline` has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Give up once `timeout` has elapsed from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Give up once `token` is cancelled.
+    pub fn with_token(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Run with the flags and backoff of `profile`, instead of the
+    /// engine's profile.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// The deadline, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The profile, if any.
+    pub fn profile(&self) -> Option<Profile> {
+        self.profile
+    }
+
+    /// Check whether the operation may continue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` if the token was cancelled, or
+    /// `DsaError::DeadlineExceeded` if the deadline has passed.
+    pub fn check(&self) -> Result<(), DsaError> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(DsaError::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(DsaError::DeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+impl DsaEngine {
+    /// Compute a CRC32 like [`DsaEngine::crc32`], giving up once `ctx`
+    /// expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` or `DsaError::DeadlineExceeded` if
+    /// `ctx` expires first, or an error if an operation fails.
+    pub fn crc32_with_context(&self, data: &[u8], ctx: &OpContext) -> Result<u32, DsaError> {
+        ctx.check()?;
+        let mut crc = 0;
+        let mut software = false;
+        for (i, chunk) in data.chunks(CONTEXT_CHUNK_SIZE).enumerate() {
+            ctx.check()?;
+            if !software {
+                self.throttle(chunk.len(), 1);
+                let remaining = data.len() - i * CONTEXT_CHUNK_SIZE;
+                match self.soft_timed(ctx, remaining, |ctx| {
+                    self.retry(|| self.work_queue().crc32_in(chunk, crc, ctx))
+                })? {
+                    Some(chunk_crc) => crc = chunk_crc,
+                    None => software = true,
+                }
+            }
+            if software {
+                crc = crate::crc::software_crc32(chunk, crc);
+            }
+        }
+        Ok(crc)
+    }
+
+    /// Compute CRC32s of many buffers like [`DsaEngine::crc32_many`],
+    /// checking `ctx` between batches.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` or `DsaError::DeadlineExceeded` if
+    /// `ctx` expires first, or an error if an operation fails.
+    pub fn crc32_many_with_context(
+        &self,
+        bufs: &[&[u8]],
+        ctx: &OpContext,
+    ) -> Result<Vec<u32>, DsaError> {
+        let mut crcs = Vec::with_capacity(bufs.len());
+        for batch in bufs.chunks(DEFAULT_MAX_BATCH_SIZE) {
+            ctx.check()?;
+            crcs.extend(self.crc32_many(batch)?);
+        }
+        Ok(crcs)
+    }
+
+    /// Copy memory like [`DsaEngine::memcpy`], giving up once `ctx` expires.
+    ///
+    /// If the copy is given up, a prefix of `dst` may have been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` or `DsaError::DeadlineExceeded` if
+    /// `ctx` expires first, or an error if `dst` is smaller than `src` or
+    /// an operation fails.
+    pub fn memcpy_with_context(
+        &self,
+        dst: &mut [u8],
+        src: &[u8],
+        ctx: &OpContext,
+    ) -> Result<(), DsaError> {
+        if dst.len() < src.len() {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: src.len(),
+                actual: dst.len(),
+            });
+        }
+        ctx.check()?;
+        let chunks = dst
+            .chunks_mut(CONTEXT_CHUNK_SIZE)
+            .zip(src.chunks(CONTEXT_CHUNK_SIZE));
+        let mut software = false;
+        for (i, (dst, src_chunk)) in chunks.enumerate() {
+            ctx.check()?;
+            if !software {
+                self.throttle(src_chunk.len(), 1);
+                let remaining = src.len() - i * CONTEXT_CHUNK_SIZE;
+                let copied = self.soft_timed(ctx, remaining, |ctx| {
+                    self.retry(|| self.work_queue().memcpy_in(dst, src_chunk, ctx))
+                })?;
+                software = copied.is_none();
+            }
+            if software {
+                dst[..src_chunk.len()].copy_from_slice(src_chunk);
+            }
+        }
+        if software {
+            self.await_abandoned(ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Fill memory like [`DsaEngine::memset`], giving up once `ctx` expires.
+    ///
+    /// If the fill is given up, a prefix of `dst` may have been written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` or `DsaError::DeadlineExceeded` if
+    /// `ctx` expires first, or an error if an operation fails.
+    pub fn memset_with_context(
+        &self,
+        dst: &mut [u8],
+        pattern: u64,
+        ctx: &OpContext,
+    ) -> Result<(), DsaError> {
+        ctx.check()?;
+        // Chunks are a multiple of 8 bytes, so the pattern stays in phase
+        let len = dst.len();
+        let mut software = false;
+        for (i, dst) in dst.chunks_mut(CONTEXT_CHUNK_SIZE).enumerate() {
+            ctx.check()?;
+            if !software {
+                self.throttle(dst.len(), 1);
+                let remaining = len - i * CONTEXT_CHUNK_SIZE;
+                let filled = self.soft_timed(ctx, remaining, |ctx| {
+                    self.retry(|| self.work_queue().memset_in(dst, pattern, ctx))
+                })?;
+                software = filled.is_none();
+            }
+            if software {
+                crate::degrade::fill(dst, pattern);
+            }
+        }
+        if software {
+            self.await_abandoned(ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Compare memory like [`DsaEngine::memcmp`], giving up once `ctx`
+    /// expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Cancelled` or `DsaError::DeadlineExceeded` if
+    /// `ctx` expires first, or an error if the lengths differ or an
+    /// operation fails.
+    pub fn memcmp_with_context(
+        &self,
+        a: &[u8],
+        b: &[u8],
+        ctx: &OpContext,
+    ) -> Result<bool, DsaError> {
+        if a.len() != b.len() {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: a.len(),
+                actual: b.len(),
+            });
+        }
+        ctx.check()?;
+        let len = a.len();
+        let chunks = a
+            .chunks(CONTEXT_CHUNK_SIZE)
+            .zip(b.chunks(CONTEXT_CHUNK_SIZE));
+        let mut software = false;
+        for (i, (a, b)) in chunks.enumerate() {
+            ctx.check()?;
+            let mut equal = None;
+            if !software {
+                self.throttle(2 * a.len(), 1);
+                let remaining = len - i * CONTEXT_CHUNK_SIZE;
+                equal = self.soft_timed(ctx, remaining, |ctx| {
+                    self.retry(|| self.work_queue().memcmp_in(a, b, ctx))
+                })?;
+                software = equal.is_none();
+            }
+            if !equal.unwrap_or_else(|| a == b) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_shared_by_clones() {
+        let token = CancellationToken::new();
+        let ctx = OpContext::new().with_token(token.clone());
+        assert!(ctx.check().is_ok());
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(ctx.check(), Err(DsaError::Cancelled)));
+    }
+
+    #[test]
+    fn test_deadline() {
+        assert!(OpContext::NONE.check().is_ok());
+        let ctx = OpContext::new().with_timeout(Duration::from_secs(60));
+        assert!(ctx.check().is_ok());
+        let ctx = OpContext::new().with_deadline(Instant::now());
+        assert!(matches!(ctx.check(), Err(DsaError::DeadlineExceeded)));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_operations_with_context() {
+        use crate::emulator::Emulator;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let src: Vec<u8> = (0..3 * CONTEXT_CHUNK_SIZE + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let ctx = OpContext::new().with_timeout(Duration::from_secs(60));
+
+        assert_eq!(
+            engine.crc32_with_context(&src, &ctx).unwrap(),
+            engine.crc32(&src).unwrap()
+        );
+        let mut dst = vec![0u8; src.len()];
+        engine.memcpy_with_context(&mut dst, &src, &ctx).unwrap();
+        assert!(engine.memcmp_with_context(&dst, &src, &ctx).unwrap());
+        engine
+            .memset_with_context(&mut dst, 0x0102_0304_0506_0708, &ctx)
+            .unwrap();
+        assert_eq!(
+            engine.verify_pattern(&dst, 0x0102_0304_0506_0708).unwrap(),
+            None
+        );
+        let bufs: Vec<&[u8]> = src.chunks(4096).collect();
+        assert_eq!(
+            engine.crc32_many_with_context(&bufs, &ctx).unwrap(),
+            engine.crc32_many(&bufs).unwrap()
+        );
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let ctx = OpContext::new().with_token(token);
+        assert!(matches!(
+            engine.memcpy_with_context(&mut dst, &src, &ctx),
+            Err(DsaError::Cancelled)
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_single_descriptor_abandoned() {
+        use crate::emulator::{Emulator, Fault};
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        emulator.inject(Fault::Stall);
+        let ctx = OpContext::new().with_timeout(Duration::from_millis(10));
+        assert!(matches!(
+            engine.crc32_with_context(b"never completes", &ctx),
+            Err(DsaError::DeadlineExceeded)
+        ));
+    }
+}
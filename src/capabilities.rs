@@ -0,0 +1,270 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Engine capabilities.
+//!
+//! [`DsaEngine::capabilities`] merges what the device reports in sysfs with
+//! what the submission path supports, so applications can detect features
+//! at startup without reading sysfs themselves.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::{Backend, DsaEngine, DsaOpcode};
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let caps = engine.capabilities();
+//! if caps.backend == Backend::Hardware && caps.supports(DsaOpcode::CreateDelta) {
+//!     println!("delta records offloaded, up to {} bytes", caps.max_transfer_size);
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::opcode::DsaOpcode;
+use crate::wq::WorkQueueType;
+
+/// Descriptors assumed to fit in flight when sysfs reports neither the
+/// work queue's threshold nor its size, and for software backends.
+pub const DEFAULT_MAX_IN_FLIGHT: u32 = 32;
+
+/// What executes an engine's operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// DSA hardware through a work queue portal.
+    Hardware,
+    /// The software [`Emulator`](crate::emulator::Emulator).
+    Emulated,
+    /// Software fallback implementations (Windows).
+    Software,
+}
+
+/// What an engine can do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// What executes the operations.
+    pub backend: Backend,
+    /// Submission type of the work queue.
+    pub wq_type: WorkQueueType,
+    /// Opcodes the engine executes, in ascending order.
+    pub supported_ops: Vec<DsaOpcode>,
+    /// Largest transfer size of a single descriptor, in bytes.
+    pub max_transfer_size: u64,
+    /// Largest number of descriptors in one batch.
+    pub max_batch_size: u32,
+    /// Most descriptors to keep in flight at once without overfilling the
+    /// work queue: its threshold for a shared work queue, its size for a
+    /// dedicated one, or [`DEFAULT_MAX_IN_FLIGHT`] if sysfs reports
+    /// neither.
+    pub max_in_flight: u32,
+    /// Whether completion interrupts can be requested.
+    pub interrupts: bool,
+    /// Whether descriptors may set `DescriptorFlags::BLOCK_ON_FAULT`, so
+    /// the device resolves page faults instead of completing partially.
+    pub block_on_fault: bool,
+}
+
+impl Capabilities {
+    /// Returns true if the engine executes `op`.
+    pub fn supports(&self, op: DsaOpcode) -> bool {
+        self.supported_ops.contains(&op)
+    }
+
+    /// Capabilities of a work queue backed by the software emulator.
+    pub(crate) fn emulated(max_batch_size: u32) -> Self {
+        let mut supported_ops = crate::emulator::Emulator::SUPPORTED_OPS.to_vec();
+        supported_ops.sort_unstable();
+        Self {
+            backend: Backend::Emulated,
+            wq_type: WorkQueueType::Shared,
+            supported_ops,
+            max_transfer_size: u32::MAX as u64,
+            max_batch_size,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            interrupts: false,
+            block_on_fault: true,
+        }
+    }
+
+    /// Capabilities of the software fallback work queue.
+    pub(crate) fn software(supported_ops: &[DsaOpcode]) -> Self {
+        Self {
+            backend: Backend::Software,
+            wq_type: WorkQueueType::Shared,
+            supported_ops: supported_ops.to_vec(),
+            max_transfer_size: u32::MAX as u64,
+            max_batch_size: u32::MAX,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            interrupts: false,
+            block_on_fault: false,
+        }
+    }
+
+    /// Capabilities of the hardware work queue behind `dev_path`, as
+    /// reported by sysfs.
+    ///
+    /// Values sysfs does not report fall back to the DSA 1.0 opcode set
+    /// and the given batch size, with interrupts and block-on-fault assumed
+    /// unavailable.
+    #[cfg(dsa_portal)]
+    pub(crate) fn from_sysfs(
+        dev_path: &std::path::Path,
+        wq_type: WorkQueueType,
+        default_max_batch_size: u32,
+    ) -> Self {
+        use std::path::Path;
+
+        const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
+        let wq_name = dev_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let wq_path = Path::new(SYSFS_DSA_PATH).join(&wq_name);
+        let device_path = crate::device::parse_wq_name(&wq_name)
+            .ok()
+            .map(|(device, _)| Path::new(SYSFS_DSA_PATH).join(device));
+        let read = |path: &Path| std::fs::read_to_string(path).ok();
+
+        let supported_ops = device_path
+            .as_ref()
+            .and_then(|path| read(&path.join("op_cap")))
+            .and_then(|raw| parse_op_cap(&raw))
+            .unwrap_or_else(|| {
+                DsaOpcode::ALL
+                    .into_iter()
+                    .filter(|op| *op < DsaOpcode::UpdateWindow)
+                    .collect()
+            });
+        let positive = |attr: &str| {
+            read(&wq_path.join(attr))
+                .and_then(|raw| raw.trim().parse::<u64>().ok())
+                .filter(|&value| value > 0)
+        };
+        let interrupts = device_path
+            .is_some_and(|path| crate::interrupt::InterruptManager::for_device(&path).is_ok());
+
+        Self {
+            backend: Backend::Hardware,
+            wq_type,
+            supported_ops,
+            max_transfer_size: positive("max_transfer_size").unwrap_or(u32::MAX as u64),
+            max_batch_size: positive("max_batch_size")
+                .map_or(default_max_batch_size, |size| size as u32),
+            max_in_flight: positive("threshold")
+                .or_else(|| positive("size"))
+                .map_or(DEFAULT_MAX_IN_FLIGHT, |size| size as u32),
+            interrupts,
+            block_on_fault: positive("block_on_fault").is_some(),
+        }
+    }
+}
+
+/// Parse the sysfs `op_cap` bitmap into the known opcodes it contains.
+///
+/// The bitmap is printed as comma-separated hex words, most significant
+/// first: 32-bit words by current kernels, 64-bit words by older ones.
+fn parse_op_cap(raw: &str) -> Option<Vec<DsaOpcode>> {
+    let words: Vec<&str> = raw
+        .trim()
+        .split(',')
+        .map(|word| word.trim().trim_start_matches("0x"))
+        .collect();
+    let width = if words.len() > 4 { 32 } else { 64 };
+
+    let mut ops = Vec::new();
+    for (index, word) in words.iter().rev().enumerate() {
+        let bits = u64::from_str_radix(word, 16).ok()?;
+        for op in DsaOpcode::ALL {
+            let bit = op.as_u8() as usize;
+            if bit / width == index && bits & (1 << (bit % width)) != 0 {
+                ops.push(op);
+            }
+        }
+    }
+    ops.sort_unstable();
+    Some(ops)
+}
+
+impl DsaEngine {
+    /// Report what this engine can do: the backend executing operations,
+    /// supported opcodes, transfer and batch limits, interrupt support and
+    /// the work queue type.
+    ///
+    /// Hardware limits are read from sysfs on each call.
+    pub fn capabilities(&self) -> Capabilities {
+        self.work_queue().capabilities()
+    }
+
+    /// Iterate over the opcodes this engine executes, in ascending order.
+    ///
+    /// For hardware these come from the device's `op_cap` in sysfs; the
+    /// emulator and the software fallback support a fixed set. Like
+    /// [`capabilities`](Self::capabilities), sysfs is read on each call, so
+    /// code that checks often should collect the opcodes once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use dsa_rust::{DsaEngine, DsaOpcode};
+    ///
+    /// let engine = DsaEngine::open_first()?;
+    /// let delta = engine.supported_ops().any(|op| op == DsaOpcode::CreateDelta);
+    /// println!("delta records offloaded: {delta}");
+    /// # Ok::<(), dsa_rust::DsaError>(())
+    /// ```
+    pub fn supported_ops(&self) -> impl Iterator<Item = DsaOpcode> {
+        self.capabilities().supported_ops.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_op_cap() {
+        // Noop, Batch, Drain, MemMove and CrcGen
+        let ops = parse_op_cap("0x1001b\n").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                DsaOpcode::Noop,
+                DsaOpcode::Batch,
+                DsaOpcode::Drain,
+                DsaOpcode::MemMove,
+                DsaOpcode::CrcGen,
+            ]
+        );
+
+        // 32-bit words, most significant first; bit 0x20 is CacheFlush
+        let raw = "00000000,00000000,00000000,00000000,00000000,00000000,00000001,00000010";
+        assert_eq!(
+            parse_op_cap(raw).unwrap(),
+            vec![DsaOpcode::MemMove, DsaOpcode::CacheFlush]
+        );
+        assert!(parse_op_cap("zz").is_none());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_emulated_capabilities() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let caps = engine.capabilities();
+        assert_eq!(caps.backend, Backend::Emulated);
+        assert!(caps.supports(DsaOpcode::CrcGen));
+        assert!(caps.supports(DsaOpcode::DifCheck));
+        assert!(!caps.supports(DsaOpcode::Dualcast));
+        assert!(!caps.interrupts);
+        assert!(caps.block_on_fault);
+        assert_eq!(caps.max_in_flight, DEFAULT_MAX_IN_FLIGHT);
+        assert!(caps.supported_ops.windows(2).all(|w| w[0] < w[1]));
+        assert!(engine.supported_ops().eq(caps.supported_ops));
+        assert!(engine
+            .supported_ops()
+            .all(|op| Emulator::SUPPORTED_OPS.contains(&op)));
+    }
+}
@@ -0,0 +1,266 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Snapshots of an engine's configuration.
+//!
+//! [`DsaEngine::to_config`] captures the work queue an engine was opened
+//! on, the device behind it and every setting made through the engine's
+//! setters in an [`EngineConfig`]. [`DsaEngine::from_config`] opens the
+//! same work queue and applies the settings again, so a setup seen in the
+//! field can be reproduced on a test rig. With the `serde` feature the
+//! snapshot implements `Serialize` and `Deserialize`, for attaching it to a
+//! bug report as JSON.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::{DsaEngine, EngineConfig};
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let config: EngineConfig = engine.to_config();
+//! println!("{config:#?}");
+//!
+//! // Later, on another host with the same work queue configured
+//! let engine = DsaEngine::from_config(&config)?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::backoff::Backoff;
+use crate::bounce::BouncePolicy;
+use crate::engine::{DsaEngine, QueueFullPolicy};
+use crate::error::DsaError;
+use crate::fair::FairShare;
+use crate::profile::Profile;
+use crate::rate_limit::RateLimit;
+use crate::shard::ShardPolicy;
+use crate::trace::TraceConfig;
+use crate::warm::WarmPolicy;
+use crate::wq::WorkQueueType;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Base sysfs path for DSA devices.
+const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
+
+/// The work queue and settings of a [`DsaEngine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineConfig {
+    /// Path to the work queue device, e.g. `/dev/dsa/wq0.0`; empty for
+    /// emulated and software engines.
+    pub work_queue: PathBuf,
+    /// Name of the device the work queue belongs to, e.g. `dsa0`, if it
+    /// could be read from sysfs when the snapshot was taken.
+    pub device: Option<String>,
+    /// Type of the work queue.
+    pub wq_type: WorkQueueType,
+    /// Maximum retries for ENQCMD submissions.
+    pub max_retries: u32,
+    /// Spin iterations for completion polling.
+    pub spin_iterations: u32,
+    /// See [`DsaEngine::set_queue_full_policy`].
+    pub queue_full_policy: QueueFullPolicy,
+    /// See [`DsaEngine::set_backoff`].
+    pub backoff: Backoff,
+    /// See [`DsaEngine::set_serialized`].
+    pub serialized: Option<usize>,
+    /// See [`DsaEngine::set_compact_errors`].
+    pub compact_errors: bool,
+    /// See [`DsaEngine::set_rate_limit`].
+    pub rate_limit: Option<RateLimit>,
+    /// See [`DsaEngine::set_bounce_buffers`].
+    pub bounce_buffers: Option<BouncePolicy>,
+    /// See [`DsaEngine::set_sharding`].
+    pub sharding: Option<ShardPolicy>,
+    /// See [`DsaEngine::set_region_grouping`].
+    pub region_grouping: Option<usize>,
+    /// See [`DsaEngine::set_soft_timeout`].
+    pub soft_timeout: Option<Duration>,
+    /// See [`DsaEngine::set_fair_share`].
+    pub fair_share: Option<FairShare>,
+    /// See [`DsaEngine::set_profile`].
+    pub profile: Option<Profile>,
+    /// See [`DsaEngine::set_attach_device_errors`].
+    pub attach_device_errors: bool,
+    /// See [`DsaEngine::set_operation_trace`].
+    pub operation_trace: Option<TraceConfig>,
+    /// See [`DsaEngine::set_translation_warming`].
+    pub translation_warming: Option<WarmPolicy>,
+    /// See [`DsaEngine::seal`].
+    pub sealed: bool,
+}
+
+impl DsaEngine {
+    /// Capture the work queue and settings of this engine.
+    ///
+    /// The device is looked up in sysfs unless the engine is sealed.
+    pub fn to_config(&self) -> EngineConfig {
+        let wq = self.work_queue();
+        EngineConfig {
+            work_queue: wq.path().to_path_buf(),
+            device: if self.is_sealed() {
+                None
+            } else {
+                device_of(wq.path())
+            },
+            wq_type: wq.wq_type(),
+            max_retries: wq.max_retries(),
+            spin_iterations: wq.spin_iterations(),
+            queue_full_policy: self.queue_full_policy(),
+            backoff: wq.backoff(),
+            serialized: wq.serialized(),
+            compact_errors: self.compact_errors(),
+            rate_limit: self.rate_limit(),
+            bounce_buffers: self.bounce_buffers(),
+            sharding: self.sharding(),
+            region_grouping: self.region_grouping(),
+            soft_timeout: self.soft_timeout(),
+            fair_share: self.fair_share(),
+            profile: self.profile(),
+            attach_device_errors: self.attaches_device_errors(),
+            operation_trace: self.operation_trace_config(),
+            translation_warming: self.translation_warming(),
+            sealed: self.is_sealed(),
+        }
+    }
+
+    /// Open the work queue of `config` and apply its settings.
+    ///
+    /// The device name is informational: the work queue is opened by path.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - A snapshot taken with [`DsaEngine::to_config`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the work queue cannot be opened, or as
+    /// [`DsaEngine::apply_config`].
+    pub fn from_config(config: &EngineConfig) -> Result<Self, DsaError> {
+        let mut engine = Self::open(&config.work_queue)?;
+        engine.apply_config(config)?;
+        Ok(engine)
+    }
+
+    /// Apply the settings of `config` to this engine, ignoring its work
+    /// queue and device.
+    ///
+    /// The engine is sealed last if `config.sealed` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error as [`DsaEngine::set_fair_share`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a setting is rejected by its setter, e.g. a region size
+    /// that is not a power of two.
+    pub fn apply_config(&mut self, config: &EngineConfig) -> Result<(), DsaError> {
+        let wq = self.work_queue_mut();
+        wq.set_wq_type(config.wq_type);
+        wq.set_max_retries(config.max_retries);
+        wq.set_spin_iterations(config.spin_iterations);
+        // A profile sets its own backoff, which the configured one overrides
+        self.set_profile(config.profile);
+        self.set_backoff(config.backoff);
+        self.set_queue_full_policy(config.queue_full_policy);
+        self.set_serialized(config.serialized);
+        self.set_compact_errors(config.compact_errors);
+        self.set_rate_limit(config.rate_limit);
+        self.set_bounce_buffers(config.bounce_buffers);
+        self.set_sharding(config.sharding);
+        self.set_region_grouping(config.region_grouping);
+        self.set_soft_timeout(config.soft_timeout);
+        self.set_attach_device_errors(config.attach_device_errors);
+        self.set_operation_trace(config.operation_trace);
+        self.set_translation_warming(config.translation_warming);
+        self.set_fair_share(config.fair_share)?;
+        if config.sealed {
+            self.seal();
+        }
+        Ok(())
+    }
+}
+
+/// Name of the device a work queue belongs to, from sysfs.
+fn device_of(wq_path: &Path) -> Option<String> {
+    let name = wq_path.file_name()?;
+    // The work queue's sysfs entry links into its device's directory
+    let wq_dir = std::fs::canonicalize(Path::new(SYSFS_DSA_PATH).join(name)).ok()?;
+    Some(wq_dir.parent()?.file_name()?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_of() {
+        assert_eq!(device_of(Path::new("")), None);
+        assert_eq!(device_of(Path::new("/dev/dsa/no-such-wq")), None);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_config_round_trip() {
+        use crate::emulator::Emulator;
+        use crate::iotlb::REGION_1G;
+        use crate::warm::WarmMethod;
+        use std::sync::Arc;
+
+        let mut engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let defaults = engine.to_config();
+        assert_eq!(defaults.work_queue, PathBuf::new());
+        assert_eq!(defaults.device, None);
+        assert!(!defaults.sealed);
+
+        engine.set_profile(Some(Profile::LowLatency));
+        engine.set_backoff(Backoff::SPIN);
+        engine.set_queue_full_policy(QueueFullPolicy::WaitAndRetry {
+            backoff: Duration::from_micros(50),
+            timeout: Duration::from_millis(5),
+        });
+        engine.set_serialized(Some(16));
+        engine.set_compact_errors(true);
+        engine.set_rate_limit(Some(RateLimit::bytes_per_sec(1 << 30)));
+        engine.set_bounce_buffers(Some(BouncePolicy::default()));
+        engine.set_sharding(Some(ShardPolicy {
+            shards: 2,
+            min_shard: 4096,
+        }));
+        engine.set_region_grouping(Some(REGION_1G));
+        engine.set_soft_timeout(Some(Duration::from_millis(3)));
+        engine.set_attach_device_errors(true);
+        engine.set_operation_trace(Some(TraceConfig::default()));
+        engine.set_translation_warming(Some(WarmPolicy {
+            method: WarmMethod::TranslFetch,
+            ..WarmPolicy::default()
+        }));
+        engine.work_queue_mut().set_max_retries(7);
+        engine.work_queue_mut().set_spin_iterations(1000);
+        let config = engine.to_config();
+        assert_ne!(config, defaults);
+        assert_eq!(config.max_retries, 7);
+        assert_eq!(config.region_grouping, Some(REGION_1G));
+
+        // A fresh engine configured from the snapshot matches the original
+        let mut copy = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        copy.apply_config(&config).unwrap();
+        assert_eq!(copy.to_config(), config);
+        assert_eq!(
+            copy.crc32(&[1u8; 4096]).unwrap(),
+            crate::crc::software_crc32(&[1u8; 4096], 0)
+        );
+
+        // Back to the defaults
+        copy.apply_config(&defaults).unwrap();
+        assert_eq!(copy.to_config(), defaults);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&config).unwrap();
+            assert_eq!(serde_json::from_str::<EngineConfig>(&json).unwrap(), config);
+        }
+    }
+}
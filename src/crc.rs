@@ -0,0 +1,690 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Incremental CRC32 computation.
+//!
+//! [`DsaCrc32`] computes a CRC32 over data that arrives in pieces, such as
+//! from a streaming parser. Large updates are offloaded to DSA; small updates
+//! are buffered and computed in software, since per-descriptor submission
+//! overhead dominates for tiny inputs.
+//!
+//! Software CRCs throughout the crate go through a [`SoftwareCrc`] backend:
+//! [`Sse42`] by default, the portable [`Slicing8`], or a custom
+//! implementation installed with [`set_software_crc`].
+//!
+//! DSA CRC Generation and Copy with CRC compute CRC-32C (Castagnoli,
+//! polynomial 0x1EDC6F41), the CRC32 of iSCSI and ext4, not the IEEE CRC32
+//! of zlib and `crc32fast`. Hardware and software CRCs chain the same way:
+//! the seed of a computation is the finished CRC of the data before it. A
+//! pipeline can therefore hand a running CRC between hardware and software
+//! freely. [`crc32c_combine`] joins CRC-32Cs of chunks computed
+//! independently, [`crc32_combine`] does the same for IEEE CRC32s, and
+//! [`Crc32Params`] describes and converts between other CRC32 conventions,
+//! such as unreflected or non-inverted ones. The `crc32fast` feature adds
+//! `Crc32Fast`, a software IEEE CRC32 for data checked against zlib.
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::sync::OnceLock;
+
+/// Default update size below which data is buffered for software CRC.
+pub const DEFAULT_HW_THRESHOLD: usize = 4096;
+
+/// Incremental CRC32 state backed by a DSA engine.
+///
+/// The CRC is chained across calls by passing the running value as the seed
+/// of the next computation, so the result of any sequence of `update` calls
+/// equals `engine.crc32()` over the concatenated data.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::{DsaCrc32, DsaEngine};
+///
+/// let engine = DsaEngine::open_first()?;
+/// let mut crc = DsaCrc32::new(&engine);
+/// crc.update(b"Hello, ")?;
+/// crc.update(b"DSA!")?;
+/// println!("CRC32: {:#010x}", crc.finalize()?);
+/// # Ok::<(), dsa_rust::DsaError>(())
+/// ```
+pub struct DsaCrc32<'a> {
+    engine: &'a DsaEngine,
+    /// CRC of all data consumed so far (excluding `pending`).
+    state: u32,
+    /// Buffered small updates not yet folded into `state`.
+    pending: Vec<u8>,
+    /// Update size at which data is sent to hardware.
+    hw_threshold: usize,
+    /// Total number of bytes consumed.
+    len: u64,
+}
+
+impl<'a> DsaCrc32<'a> {
+    /// Create a new incremental CRC32 with a zero seed.
+    pub fn new(engine: &'a DsaEngine) -> Self {
+        Self::with_seed(engine, 0)
+    }
+
+    /// Create a new incremental CRC32 continuing from `seed`.
+    pub fn with_seed(engine: &'a DsaEngine, seed: u32) -> Self {
+        Self {
+            engine,
+            state: seed,
+            pending: Vec::new(),
+            hw_threshold: DEFAULT_HW_THRESHOLD,
+            len: 0,
+        }
+    }
+
+    /// Set the size at which buffered or incoming data is sent to hardware.
+    pub fn set_hw_threshold(&mut self, threshold: usize) {
+        self.hw_threshold = threshold;
+    }
+
+    /// Total number of bytes passed to `update` so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if no data has been passed to `update`.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Feed more data into the CRC.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a hardware CRC operation fails. The state is left
+    /// unchanged in that case, so the update may be retried.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), DsaError> {
+        if self.pending.len() + data.len() < self.hw_threshold {
+            self.pending.extend_from_slice(data);
+        } else {
+            let state = software_crc32(&self.pending, self.state);
+            self.state = self.engine.crc32_with_seed(data, state)?;
+            self.pending.clear();
+        }
+        self.len += data.len() as u64;
+        Ok(())
+    }
+
+    /// Return the CRC of all data passed to `update` so far.
+    ///
+    /// The state is not consumed; further updates continue from this value.
+    pub fn value(&self) -> u32 {
+        software_crc32(&self.pending, self.state)
+    }
+
+    /// Finish the computation and return the CRC32.
+    pub fn finalize(self) -> Result<u32, DsaError> {
+        Ok(self.value())
+    }
+}
+
+/// A software CRC-32C implementation, computing the same CRC as DSA.
+///
+/// Implement this to plug in a custom (for example SIMD) implementation
+/// with [`set_software_crc`].
+pub trait SoftwareCrc: Send + Sync {
+    /// Compute the CRC-32C of `data`, chaining from `seed`.
+    ///
+    /// `seed` is a previously returned CRC (zero to start), so that
+    /// `crc32(crc32(0, a), b)` equals the CRC of `a` followed by `b`.
+    fn crc32(&self, seed: u32, data: &[u8]) -> u32;
+}
+
+/// Software CRC-32C using the SSE4.2 `crc32` instruction.
+///
+/// Falls back to [`Slicing8`] on CPUs without SSE4.2 and on other
+/// architectures.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sse42;
+
+impl SoftwareCrc for Sse42 {
+    fn crc32(&self, seed: u32, data: &[u8]) -> u32 {
+        #[cfg(target_arch = "x86_64")]
+        if std::is_x86_feature_detected!("sse4.2") {
+            // SAFETY: the CPU supports SSE4.2, checked above
+            return unsafe { sse42_crc32(seed, data) };
+        }
+        Slicing8.crc32(seed, data)
+    }
+}
+
+/// Software IEEE CRC32 using the `crc32fast` crate (SIMD-accelerated).
+///
+/// This is the CRC32 of zlib and gzip ([`Crc32Params::CRC32FAST`]), for
+/// data that must interoperate with them. It is not the CRC-32C computed
+/// by DSA, so it is not a [`SoftwareCrc`] backend; join its CRCs with
+/// [`crc32_combine`].
+///
+/// # Example
+///
+/// ```rust
+/// use dsa_rust::crc::Crc32Fast;
+///
+/// assert_eq!(Crc32Fast.crc32(0, b"123456789"), 0xCBF4_3926);
+/// ```
+#[cfg(feature = "crc32fast")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc32Fast;
+
+#[cfg(feature = "crc32fast")]
+impl Crc32Fast {
+    /// Compute the IEEE CRC32 of `data`, chaining from `seed`.
+    ///
+    /// `seed` is a previously returned CRC (zero to start), as for
+    /// [`SoftwareCrc::crc32`].
+    pub fn crc32(&self, seed: u32, data: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new_with_initial(seed);
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// CRC-32C with the SSE4.2 `crc32` instruction, eight bytes per step.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+unsafe fn sse42_crc32(seed: u32, data: &[u8]) -> u32 {
+    use std::arch::x86_64::{_mm_crc32_u64, _mm_crc32_u8};
+
+    let mut crc = !seed as u64;
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        crc = _mm_crc32_u64(crc, word);
+    }
+    let mut crc = crc as u32;
+    for &byte in chunks.remainder() {
+        crc = _mm_crc32_u8(crc, byte);
+    }
+    !crc
+}
+
+/// Portable table-driven CRC-32C, processing eight bytes per step.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Slicing8;
+
+/// The IEEE CRC32 polynomial, bit-reflected.
+const POLY_IEEE: u32 = 0xEDB8_8320;
+
+/// The CRC-32C (Castagnoli) polynomial, bit-reflected.
+const POLY_CASTAGNOLI: u32 = 0x82F6_3B78;
+
+/// Slicing-by-8 lookup tables; `tables[0]` is the classic byte-at-a-time
+/// table.
+type Tables = [[u32; 256]; 8];
+
+/// Lookup tables for [`Slicing8`].
+static SLICING8_TABLES: Tables = slicing8_tables(POLY_CASTAGNOLI);
+
+/// Lookup tables for [`ieee_crc32`].
+#[cfg(not(feature = "crc32fast"))]
+static IEEE_TABLES: Tables = slicing8_tables(POLY_IEEE);
+
+const fn slicing8_tables(poly: u32) -> Tables {
+    let mut tables = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+    let mut t = 1;
+    while t < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[t - 1][i];
+            tables[t][i] = (prev >> 8) ^ tables[0][(prev & 0xFF) as usize];
+            i += 1;
+        }
+        t += 1;
+    }
+    tables
+}
+
+/// Slicing-by-8 CRC32 of `data` with the tables of a reflected polynomial.
+fn slicing8(t: &Tables, seed: u32, data: &[u8]) -> u32 {
+    let mut crc = !seed;
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let lo = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let hi = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        crc = t[7][(lo & 0xFF) as usize]
+            ^ t[6][((lo >> 8) & 0xFF) as usize]
+            ^ t[5][((lo >> 16) & 0xFF) as usize]
+            ^ t[4][(lo >> 24) as usize]
+            ^ t[3][(hi & 0xFF) as usize]
+            ^ t[2][((hi >> 8) & 0xFF) as usize]
+            ^ t[1][((hi >> 16) & 0xFF) as usize]
+            ^ t[0][(hi >> 24) as usize];
+    }
+    for &byte in chunks.remainder() {
+        crc = (crc >> 8) ^ t[0][((crc ^ byte as u32) & 0xFF) as usize];
+    }
+    !crc
+}
+
+impl SoftwareCrc for Slicing8 {
+    fn crc32(&self, seed: u32, data: &[u8]) -> u32 {
+        slicing8(&SLICING8_TABLES, seed, data)
+    }
+}
+
+/// Compute an IEEE CRC32 (zlib, gzip) in software, chaining from `seed`.
+///
+/// For emulating devices that compute the IEEE CRC32, such as IAA. Uses
+/// `Crc32Fast` with the `crc32fast` feature.
+pub(crate) fn ieee_crc32(data: &[u8], seed: u32) -> u32 {
+    #[cfg(feature = "crc32fast")]
+    {
+        Crc32Fast.crc32(seed, data)
+    }
+    #[cfg(not(feature = "crc32fast"))]
+    {
+        slicing8(&IEEE_TABLES, seed, data)
+    }
+}
+
+/// Multiply `a` and `b` modulo the reflected polynomial `poly`.
+const fn multmodp(a: u32, mut b: u32, poly: u32) -> u32 {
+    let mut m = 1 << 31;
+    let mut p = 0;
+    loop {
+        if a & m != 0 {
+            p ^= b;
+            if a & (m - 1) == 0 {
+                break;
+            }
+        }
+        m >>= 1;
+        b = if b & 1 != 0 { (b >> 1) ^ poly } else { b >> 1 };
+    }
+    p
+}
+
+/// `X2N_IEEE[k]` is x^(2^k) modulo the reflected IEEE polynomial.
+static X2N_IEEE: [u32; 32] = x2n_table(POLY_IEEE);
+
+/// `X2N_CASTAGNOLI[k]` is x^(2^k) modulo the reflected CRC-32C polynomial.
+static X2N_CASTAGNOLI: [u32; 32] = x2n_table(POLY_CASTAGNOLI);
+
+const fn x2n_table(poly: u32) -> [u32; 32] {
+    let mut table = [0u32; 32];
+    let mut p = 1 << 30; // x^1
+    let mut k = 0;
+    while k < 32 {
+        table[k] = p;
+        p = multmodp(p, p, poly);
+        k += 1;
+    }
+    table
+}
+
+/// Combine two CRCs modulo the reflected polynomial `poly`, whose powers
+/// x^(2^k) are in `x2n`.
+fn combine(crc1: u32, crc2: u32, len2: u64, poly: u32, x2n: &[u32; 32]) -> u32 {
+    // x^(8 * len2), the shift of crc1 past the second chunk
+    let mut p = 1 << 31; // x^0
+    let (mut n, mut k) = (len2, 3);
+    while n != 0 {
+        if n & 1 != 0 {
+            p = multmodp(x2n[k & 31], p, poly);
+        }
+        n >>= 1;
+        k += 1;
+    }
+    multmodp(p, crc1, poly) ^ crc2
+}
+
+/// Combine the CRC-32Cs of two adjacent chunks into the CRC-32C of both.
+///
+/// This is the combine for CRCs computed by DSA and by [`SoftwareCrc`]
+/// backends. Like zlib's `crc32_combine`, it takes O(log `len2`) time and
+/// does not need the data, so chunks can be checksummed independently,
+/// e.g. on different devices, and joined afterwards.
+///
+/// # Arguments
+///
+/// * `crc1` - CRC-32C of the first chunk
+/// * `crc2` - CRC-32C of the second chunk, computed with seed 0
+/// * `len2` - Length of the second chunk in bytes
+///
+/// # Example
+///
+/// ```rust
+/// use dsa_rust::crc::{crc32c_combine, Slicing8, SoftwareCrc};
+///
+/// let (a, b) = (b"Hello, ", b"DSA!");
+/// let crc = crc32c_combine(Slicing8.crc32(0, a), Slicing8.crc32(0, b), b.len() as u64);
+/// assert_eq!(crc, Slicing8.crc32(0, b"Hello, DSA!"));
+/// ```
+pub fn crc32c_combine(crc1: u32, crc2: u32, len2: u64) -> u32 {
+    combine(crc1, crc2, len2, POLY_CASTAGNOLI, &X2N_CASTAGNOLI)
+}
+
+/// Combine the IEEE CRC32s of two adjacent chunks into the CRC32 of both.
+///
+/// The same as zlib's `crc32_combine`, for CRCs computed by zlib,
+/// `crc32fast` or `Crc32Fast`. CRCs computed by DSA are CRC-32Cs; join
+/// those with [`crc32c_combine`].
+///
+/// # Arguments
+///
+/// * `crc1` - CRC32 of the first chunk
+/// * `crc2` - CRC32 of the second chunk, computed with seed 0
+/// * `len2` - Length of the second chunk in bytes
+pub fn crc32_combine(crc1: u32, crc2: u32, len2: u64) -> u32 {
+    combine(crc1, crc2, len2, POLY_IEEE, &X2N_IEEE)
+}
+
+/// Parameters of a CRC32 variant, in the notation of the Rocksoft model
+/// used by CRC catalogues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32Params {
+    /// Generator polynomial, unreflected, without the x^32 term.
+    pub poly: u32,
+    /// Initial register value, unreflected.
+    pub init: u32,
+    /// Whether input bytes are processed least significant bit first.
+    pub refin: bool,
+    /// Whether the final register is bit-reversed before `xorout`.
+    pub refout: bool,
+    /// Value XORed into the final register.
+    pub xorout: u32,
+}
+
+impl Crc32Params {
+    /// CRC-32/ISO-HDLC, the CRC32 of Ethernet, zip and PNG.
+    pub const IEEE: Self = Self {
+        poly: 0x04C1_1DB7,
+        init: 0xFFFF_FFFF,
+        refin: true,
+        refout: true,
+        xorout: 0xFFFF_FFFF,
+    };
+
+    /// The CRC32 computed by DSA CRC Generation and Copy with CRC:
+    /// CRC-32C.
+    pub const DSA: Self = Self::CASTAGNOLI;
+
+    /// The CRC32 computed by `crc32fast::Hasher`.
+    pub const CRC32FAST: Self = Self::IEEE;
+
+    /// The CRC32 computed by zlib's `crc32`.
+    pub const ZLIB: Self = Self::IEEE;
+
+    /// CRC-32/JAMCRC: the IEEE CRC32 without the final inversion.
+    pub const JAMCRC: Self = Self {
+        xorout: 0,
+        ..Self::IEEE
+    };
+
+    /// CRC-32/BZIP2: the IEEE polynomial, unreflected.
+    pub const BZIP2: Self = Self {
+        refin: false,
+        refout: false,
+        ..Self::IEEE
+    };
+
+    /// CRC-32/MPEG-2: the IEEE polynomial, unreflected and not inverted.
+    pub const MPEG2: Self = Self {
+        xorout: 0,
+        ..Self::BZIP2
+    };
+
+    /// CRC-32C (Castagnoli), as computed by DSA and the SSE4.2 `crc32`
+    /// instruction.
+    pub const CASTAGNOLI: Self = Self {
+        poly: 0x1EDC_6F41,
+        ..Self::IEEE
+    };
+
+    /// Compute the CRC of `data` bit by bit.
+    ///
+    /// This is a slow reference implementation for checking and converting
+    /// values; use [`software_crc`] or DSA for bulk data.
+    pub fn checksum(&self, data: &[u8]) -> u32 {
+        let mut reg = if self.refin {
+            let poly = self.poly.reverse_bits();
+            let mut reg = self.init.reverse_bits();
+            for &byte in data {
+                reg ^= byte as u32;
+                for _ in 0..8 {
+                    reg = if reg & 1 != 0 {
+                        (reg >> 1) ^ poly
+                    } else {
+                        reg >> 1
+                    };
+                }
+            }
+            reg.reverse_bits()
+        } else {
+            let mut reg = self.init;
+            for &byte in data {
+                reg ^= (byte as u32) << 24;
+                for _ in 0..8 {
+                    reg = if reg & 0x8000_0000 != 0 {
+                        (reg << 1) ^ self.poly
+                    } else {
+                        reg << 1
+                    };
+                }
+            }
+            reg
+        };
+        if self.refout {
+            reg = reg.reverse_bits();
+        }
+        reg ^ self.xorout
+    }
+
+    /// Convert a CRC computed with these parameters to the value `to`
+    /// would have computed over the same data.
+    ///
+    /// Only the output conventions (`refout` and `xorout`) can differ: CRCs
+    /// with different polynomials, initial values or input reflection are
+    /// unrelated.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `poly`, `init` or `refin` differ.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use dsa_rust::crc::Crc32Params;
+    ///
+    /// let crc = Crc32Params::IEEE.checksum(b"123456789");
+    /// let jam = Crc32Params::IEEE.convert(crc, &Crc32Params::JAMCRC);
+    /// assert_eq!(jam, Some(!crc));
+    ///
+    /// // DSA computes CRC-32C, which is unrelated to the IEEE CRC32
+    /// assert_eq!(Crc32Params::DSA.convert(crc, &Crc32Params::IEEE), None);
+    /// ```
+    pub fn convert(&self, crc: u32, to: &Crc32Params) -> Option<u32> {
+        if (self.poly, self.init, self.refin) != (to.poly, to.init, to.refin) {
+            return None;
+        }
+        let reg = crc ^ self.xorout;
+        let reg = if self.refout != to.refout {
+            reg.reverse_bits()
+        } else {
+            reg
+        };
+        Some(reg ^ to.xorout)
+    }
+}
+
+/// The process-wide software CRC backend, fixed on first use.
+static SOFTWARE_CRC: OnceLock<Box<dyn SoftwareCrc>> = OnceLock::new();
+
+/// Install `backend` as the software CRC implementation for the process.
+///
+/// Must be called before the first software CRC is computed, typically at
+/// startup.
+///
+/// # Errors
+///
+/// Returns `DsaError::InvalidArgument` if a backend is already in use.
+pub fn set_software_crc(backend: Box<dyn SoftwareCrc>) -> Result<(), DsaError> {
+    SOFTWARE_CRC
+        .set(backend)
+        .map_err(|_| DsaError::InvalidArgument("software CRC backend already in use".to_string()))
+}
+
+/// The software CRC backend in use.
+pub fn software_crc() -> &'static dyn SoftwareCrc {
+    SOFTWARE_CRC.get_or_init(|| Box::new(Sse42)).as_ref()
+}
+
+/// Compute a CRC-32C in software, chaining from `seed`.
+#[inline]
+pub(crate) fn software_crc32(data: &[u8], seed: u32) -> u32 {
+    if data.is_empty() {
+        return seed;
+    }
+    software_crc().crc32(seed, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_software_crc32_chaining() {
+        let data = b"The quick brown fox jumps over the lazy dog";
+        let (a, b) = data.split_at(10);
+        assert_eq!(
+            software_crc32(b, software_crc32(a, 0)),
+            software_crc32(data, 0)
+        );
+        assert_eq!(software_crc32(&[], 0x1234), 0x1234);
+    }
+
+    #[test]
+    fn test_slicing8_matches_reference() {
+        let check = b"123456789";
+        assert_eq!(Slicing8.crc32(0, check), 0xE306_9283);
+        assert_eq!(Sse42.crc32(0, check), 0xE306_9283);
+        assert_eq!(ieee_crc32(check, 0), 0xCBF4_3926);
+
+        let data: Vec<u8> = (0..1027).map(|i| (i * 7 % 256) as u8).collect();
+        for len in [0, 1, 7, 8, 9, 64, 1027] {
+            assert_eq!(
+                Slicing8.crc32(0x1234_5678, &data[..len]),
+                Sse42.crc32(0x1234_5678, &data[..len])
+            );
+            let mut hasher = crc32fast::Hasher::new_with_initial(0x1234_5678);
+            hasher.update(&data[..len]);
+            assert_eq!(ieee_crc32(&data[..len], 0x1234_5678), hasher.finalize());
+        }
+        let (a, b) = data.split_at(13);
+        assert_eq!(
+            Slicing8.crc32(Slicing8.crc32(0, a), b),
+            Slicing8.crc32(0, &data)
+        );
+    }
+
+    #[test]
+    fn test_crc32_combine() {
+        let data: Vec<u8> = (0..5000).map(|i| (i * 13 % 256) as u8).collect();
+        for split in [0, 1, 7, 1024, 4999, 5000] {
+            let (a, b) = data.split_at(split);
+            assert_eq!(
+                crc32c_combine(software_crc32(a, 0), software_crc32(b, 0), b.len() as u64),
+                software_crc32(&data, 0)
+            );
+            assert_eq!(
+                crc32_combine(crc32fast::hash(a), crc32fast::hash(b), b.len() as u64),
+                crc32fast::hash(&data)
+            );
+        }
+    }
+
+    #[cfg(feature = "crc32fast")]
+    #[test]
+    fn test_crc32fast_is_ieee() {
+        let data: Vec<u8> = (0..1027).map(|i| (i * 7 % 256) as u8).collect();
+        assert_eq!(Crc32Fast.crc32(0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            Crc32Fast.crc32(0, &data),
+            Crc32Params::CRC32FAST.checksum(&data)
+        );
+        let (a, b) = data.split_at(13);
+        assert_eq!(
+            Crc32Fast.crc32(Crc32Fast.crc32(0, a), b),
+            crc32fast::hash(&data)
+        );
+    }
+
+    #[test]
+    fn test_crc32_params() {
+        // Check values from the CRC catalogue
+        let check = b"123456789";
+        assert_eq!(Crc32Params::IEEE.checksum(check), 0xCBF4_3926);
+        assert_eq!(Crc32Params::JAMCRC.checksum(check), 0x340B_C6D9);
+        assert_eq!(Crc32Params::BZIP2.checksum(check), 0xFC89_1918);
+        assert_eq!(Crc32Params::MPEG2.checksum(check), 0x0376_E6E7);
+        assert_eq!(Crc32Params::CASTAGNOLI.checksum(check), 0xE306_9283);
+        assert_eq!(Crc32Params::DSA.checksum(check), software_crc32(check, 0));
+
+        let crc = Crc32Params::MPEG2.checksum(check);
+        assert_eq!(
+            Crc32Params::MPEG2.convert(crc, &Crc32Params::BZIP2),
+            Some(0xFC89_1918)
+        );
+        let reflected = Crc32Params {
+            refout: true,
+            ..Crc32Params::BZIP2
+        };
+        let converted = Crc32Params::MPEG2.convert(crc, &reflected).unwrap();
+        assert_eq!(converted, reflected.checksum(check));
+        assert_eq!(reflected.convert(converted, &Crc32Params::MPEG2), Some(crc));
+        assert_eq!(
+            Crc32Params::IEEE.convert(crc, &Crc32Params::CASTAGNOLI),
+            None
+        );
+        assert_eq!(Crc32Params::IEEE.convert(crc, &Crc32Params::BZIP2), None);
+    }
+
+    #[test]
+    fn test_backend_fixed_after_use() {
+        software_crc32(b"in use", 0);
+        assert!(set_software_crc(Box::new(Slicing8)).is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_incremental_matches_one_shot() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+
+        let data: Vec<u8> = (0..20_000).map(|i| (i % 251) as u8).collect();
+        let mut crc = DsaCrc32::new(&engine);
+        for piece in data.chunks(777) {
+            crc.update(piece).unwrap();
+        }
+        crc.update(&data[..5000]).unwrap();
+
+        let mut all = data.clone();
+        all.extend_from_slice(&data[..5000]);
+        assert_eq!(crc.finalize().unwrap(), software_crc32(&all, 0));
+        assert!(emulator.submitted() > 0);
+    }
+}
@@ -0,0 +1,477 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Interleaved CRC32 streams.
+//!
+//! A single CRC32 stream is sequential: [`DsaCrc32`](crate::DsaCrc32)
+//! waits for each descriptor before it submits the next, so the device's
+//! per-descriptor latency is paid once per chunk. [`CrcStreams`] drives
+//! many independent streams at once instead. Data is cut into chunks, every
+//! chunk is submitted on its own with its CRC32 computed from zero, and as
+//! many chunks of all streams as the work queue holds are in flight
+//! together. The oldest chunk is reaped when the window is full, and each
+//! stream's chunk CRC32s are joined in order with [`crc32c_combine`].
+//!
+//! This suits log-structured storage computing CRCs for many segments at
+//! once. [`DsaEngine::crc32_interleaved`] does the same for buffers that
+//! are all at hand, submitting their chunks round-robin.
+//! [`DsaEngine::crc32_parallel`] keeps the chunks of one large buffer in
+//! flight together, and [`DsaEnginePool::crc32_parallel`] spreads them over
+//! several work queues.
+//!
+//! [`DsaEnginePool::crc32_parallel`]: crate::DsaEnginePool::crc32_parallel
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let segments = vec![vec![1u8; 1 << 20], vec![2u8; 3 << 20]];
+//!
+//! let mut streams = engine.crc32_streams(segments.len());
+//! for (i, segment) in segments.iter().enumerate() {
+//!     streams.update(i, &segment[..4096])?;
+//! }
+//! for (i, segment) in segments.iter().enumerate() {
+//!     streams.update(i, &segment[4096..])?;
+//! }
+//! let crcs = streams.finalize()?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::crc::crc32c_combine;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+#[cfg(dsa_portal)]
+use crate::descriptor::DsaHwDesc;
+#[cfg(dsa_portal)]
+use crate::reactor::InFlight;
+#[cfg(dsa_portal)]
+use std::sync::Arc;
+
+/// Default bytes per chunk.
+pub const DEFAULT_STREAM_CHUNK: usize = 64 << 10;
+
+/// A chunk submitted but not yet joined into its stream.
+struct Chunk {
+    stream: usize,
+    len: usize,
+    #[cfg(dsa_portal)]
+    op: Arc<InFlight>,
+    /// CRC32 computed on submission, without hardware.
+    #[cfg(not(dsa_portal))]
+    crc: Result<u32, DsaError>,
+}
+
+/// Several CRC32 streams computed concurrently on one engine.
+///
+/// Created by [`DsaEngine::crc32_streams`]. The data passed to
+/// [`CrcStreams::update`] is borrowed until the streams are finalized or
+/// dropped; dropping waits for the chunks still in flight.
+pub struct CrcStreams<'e, 'd> {
+    engine: &'e DsaEngine,
+    chunk_size: usize,
+    depth: usize,
+    /// CRC32 of each stream's reaped chunks.
+    crcs: Vec<u32>,
+    /// Chunks in flight, oldest first.
+    pending: VecDeque<Chunk>,
+    _data: PhantomData<&'d [u8]>,
+}
+
+impl<'e, 'd> CrcStreams<'e, 'd> {
+    fn new(engine: &'e DsaEngine, count: usize) -> Self {
+        let caps = engine.capabilities();
+        let max_transfer = usize::try_from(caps.max_transfer_size)
+            .unwrap_or(usize::MAX)
+            .max(1);
+        Self {
+            engine,
+            chunk_size: DEFAULT_STREAM_CHUNK.min(max_transfer),
+            depth: (caps.max_in_flight as usize).max(1),
+            crcs: vec![0; count],
+            pending: VecDeque::new(),
+            _data: PhantomData,
+        }
+    }
+
+    /// Set the bytes per chunk, at most the maximum transfer size.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        let max_transfer =
+            usize::try_from(self.engine.capabilities().max_transfer_size).unwrap_or(usize::MAX);
+        self.chunk_size = chunk_size.clamp(1, max_transfer.max(1));
+        self
+    }
+
+    /// Set the maximum number of chunks in flight, at least one. Defaults
+    /// to the work queue's
+    /// [`max_in_flight`](crate::Capabilities::max_in_flight).
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Add a stream, returning its index.
+    pub fn add_stream(&mut self) -> usize {
+        self.crcs.push(0);
+        self.crcs.len() - 1
+    }
+
+    /// Number of streams.
+    pub fn len(&self) -> usize {
+        self.crcs.len()
+    }
+
+    /// Returns true if there are no streams.
+    pub fn is_empty(&self) -> bool {
+        self.crcs.is_empty()
+    }
+
+    /// Number of chunks in flight.
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Append `data` to stream `stream`.
+    ///
+    /// The data is submitted in chunks; this only waits when the window of
+    /// chunks in flight is full, and then for the oldest chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if there is no such stream, or an
+    /// error if an operation fails. The CRC32s of the streams are
+    /// unspecified after an error.
+    pub fn update(&mut self, stream: usize, data: &'d [u8]) -> Result<(), DsaError> {
+        if stream >= self.crcs.len() {
+            return Err(DsaError::InvalidArgument(format!(
+                "stream {stream} out of range for {} streams",
+                self.crcs.len()
+            )));
+        }
+        for chunk in data.chunks(self.chunk_size) {
+            while self.pending.len() >= self.depth {
+                self.reap()?;
+            }
+            self.submit(stream, chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for every chunk and return the CRC32 of each stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation fails.
+    pub fn finalize(mut self) -> Result<Vec<u32>, DsaError> {
+        while !self.pending.is_empty() {
+            self.reap()?;
+        }
+        Ok(std::mem::take(&mut self.crcs))
+    }
+
+    #[cfg(dsa_portal)]
+    fn submit(&mut self, stream: usize, chunk: &'d [u8]) -> Result<(), DsaError> {
+        let engine = self.engine;
+        engine.throttle(chunk.len(), 1);
+        let op = engine.retry(|| {
+            engine
+                .work_queue()
+                .start(|c| DsaHwDesc::crc_gen(chunk.as_ptr(), chunk.len(), 0, c))
+        })?;
+        self.pending.push_back(Chunk {
+            stream,
+            len: chunk.len(),
+            op,
+        });
+        Ok(())
+    }
+
+    #[cfg(not(dsa_portal))]
+    fn submit(&mut self, stream: usize, chunk: &'d [u8]) -> Result<(), DsaError> {
+        self.pending.push_back(Chunk {
+            stream,
+            len: chunk.len(),
+            crc: self.engine.crc32(chunk),
+        });
+        Ok(())
+    }
+
+    /// Wait for the oldest chunk and join it into its stream.
+    ///
+    /// Chunks of a stream are reaped in submission order, so each one
+    /// extends the stream's CRC32.
+    fn reap(&mut self) -> Result<(), DsaError> {
+        let Some(chunk) = self.pending.pop_front() else {
+            return Ok(());
+        };
+        #[cfg(dsa_portal)]
+        let crc = {
+            chunk.op.wait();
+            chunk
+                .op
+                .outcome()
+                .and_then(|record| {
+                    crate::wq::check_completion(record).map(|()| record.crc32_result())
+                })
+                .map_err(|e| chunk.op.add_context(e))?
+        };
+        #[cfg(not(dsa_portal))]
+        let crc = chunk.crc?;
+        let joined = &mut self.crcs[chunk.stream];
+        *joined = crc32c_combine(*joined, crc, chunk.len as u64);
+        Ok(())
+    }
+}
+
+impl Drop for CrcStreams<'_, '_> {
+    fn drop(&mut self) {
+        // The hardware may still read the borrowed data
+        #[cfg(dsa_portal)]
+        for chunk in &self.pending {
+            chunk.op.wait();
+        }
+    }
+}
+
+impl std::fmt::Debug for CrcStreams<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CrcStreams")
+            .field("streams", &self.crcs.len())
+            .field("chunk_size", &self.chunk_size)
+            .field("depth", &self.depth)
+            .field("in_flight", &self.pending.len())
+            .finish()
+    }
+}
+
+impl DsaEngine {
+    /// Start `count` CRC32 streams computed concurrently.
+    ///
+    /// More streams can be added with [`CrcStreams::add_stream`].
+    pub fn crc32_streams<'d>(&self, count: usize) -> CrcStreams<'_, 'd> {
+        CrcStreams::new(self, count)
+    }
+
+    /// Compute the CRC32 of every buffer, submitting their chunks
+    /// round-robin so that all buffers progress together.
+    ///
+    /// # Arguments
+    ///
+    /// * `bufs` - Buffers to checksum
+    /// * `chunk_size` - Bytes per chunk
+    ///
+    /// # Returns
+    ///
+    /// The CRC32 of each buffer, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `chunk_size` is zero, or an
+    /// error if an operation fails.
+    pub fn crc32_interleaved(
+        &self,
+        bufs: &[&[u8]],
+        chunk_size: usize,
+    ) -> Result<Vec<u32>, DsaError> {
+        if chunk_size == 0 {
+            return Err(DsaError::InvalidArgument(
+                "chunk size must be nonzero".to_string(),
+            ));
+        }
+
+        let mut streams = self.crc32_streams(bufs.len()).with_chunk_size(chunk_size);
+        let chunk_size = streams.chunk_size;
+        let rounds = bufs.iter().map(|buf| buf.len().div_ceil(chunk_size)).max();
+        for round in 0..rounds.unwrap_or(0) {
+            for (stream, buf) in bufs.iter().enumerate() {
+                if let Some(chunk) = buf.chunks(chunk_size).nth(round) {
+                    streams.update(stream, chunk)?;
+                }
+            }
+        }
+        streams.finalize()
+    }
+
+    /// Compute the CRC32 of one large buffer with its chunks in flight
+    /// together.
+    ///
+    /// A sequential CRC waits for each descriptor before submitting the
+    /// next; here every chunk is checksummed on its own, up to the work
+    /// queue's [`max_in_flight`](crate::Capabilities::max_in_flight) at
+    /// once, and the chunk CRC32s are joined with [`crc32c_combine`]. For
+    /// multi-gigabyte buffers this keeps the device busy instead of paying
+    /// its latency once per chunk.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to checksum
+    /// * `chunk_size` - Bytes per chunk, at most the maximum transfer size
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `chunk_size` is zero, or an
+    /// error if an operation fails.
+    pub fn crc32_parallel(&self, data: &[u8], chunk_size: usize) -> Result<u32, DsaError> {
+        crc32_split(&[self], data, chunk_size)
+    }
+}
+
+/// Compute the CRC32 of `data` split into one contiguous part per engine,
+/// submitting the chunks of all parts round-robin so that every engine
+/// works at once.
+pub(crate) fn crc32_split(
+    engines: &[&DsaEngine],
+    data: &[u8],
+    chunk_size: usize,
+) -> Result<u32, DsaError> {
+    if chunk_size == 0 {
+        return Err(DsaError::InvalidArgument(
+            "chunk size must be nonzero".to_string(),
+        ));
+    }
+    if engines.is_empty() {
+        return Err(DsaError::NoWorkQueue);
+    }
+
+    let part_size = data.len().div_ceil(engines.len()).max(1);
+    let parts: Vec<&[u8]> = data.chunks(part_size).collect();
+    let mut streams: Vec<CrcStreams> = engines
+        .iter()
+        .zip(&parts)
+        .map(|(engine, _)| engine.crc32_streams(1).with_chunk_size(chunk_size))
+        .collect();
+    let mut chunks: Vec<_> = parts
+        .iter()
+        .zip(&streams)
+        .map(|(part, streams)| part.chunks(streams.chunk_size))
+        .collect();
+    loop {
+        let mut submitted = false;
+        for (streams, chunks) in streams.iter_mut().zip(&mut chunks) {
+            if let Some(chunk) = chunks.next() {
+                streams.update(0, chunk)?;
+                submitted = true;
+            }
+        }
+        if !submitted {
+            break;
+        }
+    }
+
+    let mut crc = 0;
+    for (streams, part) in streams.into_iter().zip(&parts) {
+        crc = crc32c_combine(crc, streams.finalize()?[0], part.len() as u64);
+    }
+    Ok(crc)
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use crate::emulator::Emulator;
+
+    fn data(len: usize, salt: usize) -> Vec<u8> {
+        (0..len).map(|i| ((i * 31 + salt) % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_crc32_interleaved() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let bufs = [data(10_000, 1), Vec::new(), data(100, 2), data(33_333, 3)];
+        let slices: Vec<&[u8]> = bufs.iter().map(Vec::as_slice).collect();
+        let expected: Vec<u32> = bufs
+            .iter()
+            .map(|buf| crate::crc::software_crc32(buf, 0))
+            .collect();
+
+        for chunk_size in [1, 64, 4096, 1 << 20] {
+            assert_eq!(
+                engine.crc32_interleaved(&slices, chunk_size).unwrap(),
+                expected
+            );
+        }
+        assert!(matches!(
+            engine.crc32_interleaved(&slices, 0),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_crc32_parallel() {
+        let emulator = Arc::new(Emulator::new());
+        let engines: Vec<DsaEngine> = (0..3)
+            .map(|_| DsaEngine::emulated(Arc::clone(&emulator)).unwrap())
+            .collect();
+        let refs: Vec<&DsaEngine> = engines.iter().collect();
+        let buf = data(100_000, 7);
+
+        assert_eq!(
+            engines[0].crc32_parallel(&buf, 4096).unwrap(),
+            crate::crc::software_crc32(&buf, 0)
+        );
+        assert_eq!(emulator.submitted(), 25);
+        for len in [0, 1, 2, 4095, 100_000] {
+            assert_eq!(
+                crc32_split(&refs, &buf[..len], 1000).unwrap(),
+                crate::crc::software_crc32(&buf[..len], 0)
+            );
+        }
+        assert!(matches!(
+            engines[0].crc32_parallel(&buf, 0),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            crc32_split(&[], &buf, 4096),
+            Err(DsaError::NoWorkQueue)
+        ));
+    }
+
+    #[test]
+    fn test_streams_window() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let a = data(5000, 4);
+        let b = data(7000, 5);
+
+        let mut streams = engine.crc32_streams(1).with_chunk_size(1000).with_depth(3);
+        let second = streams.add_stream();
+        streams.update(0, &a[..2500]).unwrap();
+        streams.update(second, &b).unwrap();
+        assert!(streams.in_flight() <= 3);
+        streams.update(0, &a[2500..]).unwrap();
+        assert!(matches!(
+            streams.update(2, &a),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            streams.finalize().unwrap(),
+            vec![
+                crate::crc::software_crc32(&a, 0),
+                crate::crc::software_crc32(&b, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_chunk() {
+        use crate::emulator::Fault;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let a = data(4096, 6);
+
+        emulator.inject(Fault::InvalidFlags);
+        let mut streams = engine.crc32_streams(1).with_chunk_size(1024);
+        streams.update(0, &a).unwrap();
+        assert!(matches!(
+            streams.finalize(),
+            Err(DsaError::OperationFailed { status: 0x10, .. })
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Detecting identical pages.
+//!
+//! Memory deduplication services merge pages with identical contents.
+//! [`DsaEngine::find_duplicate_pages`] does the detection in two passes on
+//! the device: a batch of CRC32 descriptors buckets the pages by checksum
+//! and length, then batches of compare descriptors confirm which candidates
+//! in a bucket really are equal, so a CRC collision never merges pages that
+//! differ.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let memory = vec![0u8; 256 * 4096];
+//! let pages: Vec<&[u8]> = memory.chunks(4096).collect();
+//! for group in engine.find_duplicate_pages(&pages)? {
+//!     println!("pages {group:?} are identical");
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::batch::OpOutput;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::collections::HashMap;
+
+impl DsaEngine {
+    /// Find groups of identical buffers among `pages`.
+    ///
+    /// Buffers are usually pages, but any lengths are accepted; buffers of
+    /// different lengths are never identical.
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - Buffers to compare
+    ///
+    /// # Returns
+    ///
+    /// The groups of two or more indices into `pages` whose buffers are
+    /// byte-for-byte equal. Indices within a group are ascending, and groups
+    /// are ordered by their first index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation fails.
+    pub fn find_duplicate_pages(&self, pages: &[&[u8]]) -> Result<Vec<Vec<usize>>, DsaError> {
+        let crcs = self.crc32_many(pages)?;
+
+        let mut buckets: HashMap<(u32, usize), Vec<usize>> = HashMap::new();
+        for (i, (page, crc)) in pages.iter().zip(crcs).enumerate() {
+            buckets.entry((crc, page.len())).or_default().push(i);
+        }
+        let mut candidates: Vec<Vec<usize>> = buckets
+            .into_values()
+            .filter(|bucket| bucket.len() > 1)
+            .collect();
+
+        // Each round compares every candidate against the first of its
+        // bucket; candidates that differ form a new bucket for the next
+        // round, which only happens on a CRC collision
+        let mut groups = Vec::new();
+        while !candidates.is_empty() {
+            let mut batch = self.batch();
+            for bucket in &candidates {
+                for &i in &bucket[1..] {
+                    batch.memcmp(pages[bucket[0]], pages[i]);
+                }
+            }
+            let mut results = batch.submit()?.into_iter();
+
+            let mut next = Vec::new();
+            for bucket in candidates {
+                let mut group = vec![bucket[0]];
+                let mut rest = Vec::new();
+                for &i in &bucket[1..] {
+                    let (_, _, result) = results.next().expect("one result per compare");
+                    match result? {
+                        OpOutput::Mismatch(None) => group.push(i),
+                        _ => rest.push(i),
+                    }
+                }
+                if group.len() > 1 {
+                    groups.push(group);
+                }
+                if rest.len() > 1 {
+                    next.push(rest);
+                }
+            }
+            candidates = next;
+        }
+
+        groups.sort_unstable_by_key(|group| group[0]);
+        Ok(groups)
+    }
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use crate::emulator::Emulator;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_find_duplicate_pages() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let page = |fill: u8| vec![fill; 4096];
+        let memory = [
+            page(0),
+            page(1),
+            page(0),
+            page(2),
+            page(1),
+            page(0),
+            page(3),
+        ];
+        let mut pages: Vec<&[u8]> = memory.iter().map(Vec::as_slice).collect();
+        let short = [0u8; 512];
+        pages.push(&short);
+
+        let groups = engine.find_duplicate_pages(&pages).unwrap();
+        assert_eq!(groups, vec![vec![0, 2, 5], vec![1, 4]]);
+        assert!(engine.find_duplicate_pages(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_crc_collision_is_not_a_duplicate() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        // The CRC32 is affine, so XORing a buffer with any message whose
+        // linear CRC is zero keeps its CRC32; a message followed by its own
+        // linear CRC in little-endian order is such a message
+        let prefix = *b"dsa!";
+        let linear = engine.crc32(&prefix).unwrap() ^ engine.crc32(&[0; 4]).unwrap();
+        let a = [0x11u8; 8];
+        let mut b = a;
+        for (byte, delta) in b.iter_mut().zip(prefix.iter().chain(&linear.to_le_bytes())) {
+            *byte ^= delta;
+        }
+        assert_ne!(a, b);
+        assert_eq!(engine.crc32(&a).unwrap(), engine.crc32(&b).unwrap());
+
+        // The first candidate of the bucket differs from the others, which
+        // are confirmed in a second round
+        let pages: Vec<&[u8]> = vec![&b, &a, &a];
+        let groups = engine.find_duplicate_pages(&pages).unwrap();
+        assert_eq!(groups, vec![vec![1, 2]]);
+    }
+}
@@ -0,0 +1,459 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Software DEFLATE (RFC 1951) for IAA compression.
+//!
+//! IAA Compress encodes with the Huffman codes it is given in its
+//! configuration (AECS) as `(length << 15) | code` entries, one per
+//! literal/length and distance symbol. This module builds the entries for
+//! the fixed Huffman codes, encodes with such entries for the emulator, and
+//! inflates any DEFLATE stream for the emulator's Decompress.
+
+/// Literal/length symbols with a code in an AECS.
+pub(crate) const LITLEN_SYMBOLS: usize = 286;
+/// Distance symbols with a code in an AECS.
+pub(crate) const DIST_SYMBOLS: usize = 30;
+/// The end-of-block symbol.
+const END_OF_BLOCK: usize = 256;
+/// Longest Huffman code.
+const MAX_BITS: usize = 15;
+
+/// Base lengths of length symbols 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits of length symbols 257..=285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances of distance symbols.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Extra bits of distance symbols.
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Code lengths of the fixed literal/length code.
+fn fixed_litlen_lengths() -> [u8; 288] {
+    let mut lengths = [8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths
+}
+
+/// Canonical Huffman codes for `lengths`, as in RFC 1951 section 3.2.2.
+fn canonical_codes(lengths: &[u8]) -> Vec<u16> {
+    let mut count = [0u16; MAX_BITS + 1];
+    for &len in lengths {
+        count[len as usize] += 1;
+    }
+    count[0] = 0;
+    let mut next = [0u16; MAX_BITS + 1];
+    let mut code = 0;
+    for bits in 1..=MAX_BITS {
+        code = (code + count[bits - 1]) << 1;
+        next[bits] = code;
+    }
+    lengths
+        .iter()
+        .map(|&len| {
+            let code = next[len as usize];
+            next[len as usize] += 1;
+            code
+        })
+        .collect()
+}
+
+/// AECS entries of the fixed Huffman codes: literal/length, then distance.
+pub(crate) fn fixed_entries() -> ([u32; LITLEN_SYMBOLS], [u32; DIST_SYMBOLS]) {
+    let lengths = fixed_litlen_lengths();
+    let codes = canonical_codes(&lengths);
+    let litlen = std::array::from_fn(|i| (lengths[i] as u32) << 15 | codes[i] as u32);
+    let dist = std::array::from_fn(|i| 5 << 15 | i as u32);
+    (litlen, dist)
+}
+
+/// Writes bits least significant first, as DEFLATE packs them.
+pub(crate) struct BitWriter<'a> {
+    out: &'a mut [u8],
+    len: usize,
+    acc: u64,
+    bits: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    pub(crate) fn new(out: &'a mut [u8]) -> Self {
+        Self {
+            out,
+            len: 0,
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    /// Append the low `count` bits of `value`; false if the output is full.
+    pub(crate) fn put(&mut self, value: u64, count: u32) -> bool {
+        self.acc |= (value & ((1 << count) - 1)) << self.bits;
+        self.bits += count;
+        while self.bits >= 8 {
+            let Some(byte) = self.out.get_mut(self.len) else {
+                return false;
+            };
+            *byte = self.acc as u8;
+            self.len += 1;
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+        true
+    }
+
+    /// Append a Huffman code from an AECS entry, most significant bit first.
+    pub(crate) fn put_code(&mut self, entry: u32) -> bool {
+        let len = entry >> 15 & 0xF;
+        let code = (entry & 0x7FFF) as u16;
+        let reversed = code.reverse_bits() >> (16 - len);
+        self.put(reversed as u64, len)
+    }
+
+    /// Pad the last byte with zeros and return the number of bytes written,
+    /// or `None` if the output is full.
+    pub(crate) fn finish(mut self) -> Option<usize> {
+        if self.bits > 0 && !self.put(0, 8 - self.bits) {
+            return None;
+        }
+        Some(self.len)
+    }
+}
+
+/// Encode `src` as literals with AECS entries `litlen`, followed by the
+/// end-of-block code if `end_of_block` is set.
+///
+/// # Returns
+///
+/// `false` if `out` is too small.
+pub(crate) fn encode_literals(
+    out: &mut BitWriter<'_>,
+    src: &[u8],
+    litlen: &[u32; LITLEN_SYMBOLS],
+    end_of_block: bool,
+) -> bool {
+    src.iter().all(|&byte| out.put_code(litlen[byte as usize]))
+        && (!end_of_block || out.put_code(litlen[END_OF_BLOCK]))
+}
+
+/// Why a stream could not be inflated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InflateError {
+    /// The output does not fit the destination.
+    Overflow,
+    /// The stream is malformed or truncated.
+    Malformed,
+}
+
+/// Reads bits least significant first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, InflateError> {
+        while self.bits < count {
+            let byte = *self.data.get(self.pos).ok_or(InflateError::Malformed)?;
+            self.acc |= (byte as u64) << self.bits;
+            self.pos += 1;
+            self.bits += 8;
+        }
+        let value = (self.acc & ((1 << count) - 1)) as u32;
+        self.acc >>= count;
+        self.bits -= count;
+        Ok(value)
+    }
+
+    /// Skip to the next byte boundary.
+    fn align(&mut self) {
+        self.acc >>= self.bits % 8;
+        self.bits -= self.bits % 8;
+    }
+}
+
+/// A canonical Huffman decoding table.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the table for code `lengths`, rejecting over-subscribed codes.
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(InflateError::Malformed);
+            }
+        }
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        counts[0] = 0;
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, input: &mut BitReader<'_>) -> Result<usize, InflateError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= input.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize] as usize);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(InflateError::Malformed)
+    }
+}
+
+/// Inflate the raw DEFLATE stream `src` into `dst`.
+///
+/// # Returns
+///
+/// The number of bytes written.
+pub(crate) fn inflate(src: &[u8], dst: &mut [u8]) -> Result<usize, InflateError> {
+    let mut input = BitReader {
+        data: src,
+        pos: 0,
+        acc: 0,
+        bits: 0,
+    };
+    let mut len = 0;
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => len = stored(&mut input, dst, len)?,
+            1 => {
+                let litlen = Huffman::new(&fixed_litlen_lengths())?;
+                let dist = Huffman::new(&[5; DIST_SYMBOLS])?;
+                len = codes(&mut input, dst, len, &litlen, &dist)?;
+            }
+            2 => {
+                let (litlen, dist) = dynamic_tables(&mut input)?;
+                len = codes(&mut input, dst, len, &litlen, &dist)?;
+            }
+            _ => return Err(InflateError::Malformed),
+        }
+        if last {
+            return Ok(len);
+        }
+    }
+}
+
+/// Copy a stored block to `dst[len..]`.
+fn stored(input: &mut BitReader<'_>, dst: &mut [u8], len: usize) -> Result<usize, InflateError> {
+    input.align();
+    let size = input.bits(16)?;
+    if input.bits(16)? != !size & 0xFFFF {
+        return Err(InflateError::Malformed);
+    }
+    let mut len = len;
+    for _ in 0..size {
+        let byte = input.bits(8)? as u8;
+        *dst.get_mut(len).ok_or(InflateError::Overflow)? = byte;
+        len += 1;
+    }
+    Ok(len)
+}
+
+/// Read the code tables of a dynamic block.
+fn dynamic_tables(input: &mut BitReader<'_>) -> Result<(Huffman, Huffman), InflateError> {
+    let nlen = input.bits(5)? as usize + 257;
+    let ndist = input.bits(5)? as usize + 1;
+    let ncode = input.bits(4)? as usize + 4;
+    if nlen > LITLEN_SYMBOLS || ndist > DIST_SYMBOLS {
+        return Err(InflateError::Malformed);
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..ncode] {
+        code_lengths[symbol] = input.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; nlen + ndist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let (len, repeat) = match code_lengths.decode(input)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 if i > 0 => (lengths[i - 1], 3 + input.bits(2)? as usize),
+            17 => (0, 3 + input.bits(3)? as usize),
+            18 => (0, 11 + input.bits(7)? as usize),
+            _ => return Err(InflateError::Malformed),
+        };
+        let run = lengths
+            .get_mut(i..i + repeat)
+            .ok_or(InflateError::Malformed)?;
+        run.fill(len);
+        i += repeat;
+    }
+    if lengths[END_OF_BLOCK] == 0 {
+        return Err(InflateError::Malformed);
+    }
+    Ok((
+        Huffman::new(&lengths[..nlen])?,
+        Huffman::new(&lengths[nlen..])?,
+    ))
+}
+
+/// Decode a Huffman-coded block into `dst[len..]`.
+fn codes(
+    input: &mut BitReader<'_>,
+    dst: &mut [u8],
+    mut len: usize,
+    litlen: &Huffman,
+    dist: &Huffman,
+) -> Result<usize, InflateError> {
+    loop {
+        let symbol = litlen.decode(input)?;
+        if symbol < END_OF_BLOCK {
+            *dst.get_mut(len).ok_or(InflateError::Overflow)? = symbol as u8;
+            len += 1;
+            continue;
+        }
+        if symbol == END_OF_BLOCK {
+            return Ok(len);
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(InflateError::Malformed);
+        }
+        let count =
+            LENGTH_BASE[symbol] as usize + input.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+        let symbol = dist.decode(input)?;
+        if symbol >= DIST_BASE.len() {
+            return Err(InflateError::Malformed);
+        }
+        let distance = DIST_BASE[symbol] as usize + input.bits(DIST_EXTRA[symbol] as u32)? as usize;
+        if distance > len {
+            return Err(InflateError::Malformed);
+        }
+        if len + count > dst.len() {
+            return Err(InflateError::Overflow);
+        }
+        // Copies may overlap their own output
+        for i in len..len + count {
+            dst[i] = dst[i - distance];
+        }
+        len += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode `src` as one final fixed Huffman block of literals.
+    fn fixed_block(src: &[u8]) -> Vec<u8> {
+        let (litlen, _) = fixed_entries();
+        let mut out = vec![0; src.len() * 2 + 8];
+        let mut writer = BitWriter::new(&mut out);
+        assert!(writer.put(0b011, 3));
+        assert!(encode_literals(&mut writer, src, &litlen, true));
+        let len = writer.finish().unwrap();
+        out.truncate(len);
+        out
+    }
+
+    #[test]
+    fn test_fixed_entries() {
+        let (litlen, dist) = fixed_entries();
+        assert_eq!(litlen[0], 0x40030);
+        assert_eq!(litlen[143], 0x400BF);
+        assert_eq!(litlen[144], 0x48190);
+        assert_eq!(litlen[256], 0x38000);
+        assert_eq!(litlen[285], 0x400C5);
+        assert_eq!(dist[29], 0x2801D);
+    }
+
+    #[test]
+    fn test_literals_round_trip() {
+        let src: Vec<u8> = (0..1000).map(|i| (i * 7 % 256) as u8).collect();
+        let stream = fixed_block(&src);
+        let mut dst = vec![0; src.len()];
+        assert_eq!(inflate(&stream, &mut dst), Ok(src.len()));
+        assert_eq!(dst, src);
+
+        let mut short = vec![0; src.len() - 1];
+        assert_eq!(inflate(&stream, &mut short), Err(InflateError::Overflow));
+        assert_eq!(
+            inflate(&stream[..stream.len() / 2], &mut dst),
+            Err(InflateError::Malformed)
+        );
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_inflate_known_streams() {
+        let mut dst = [0u8; 256];
+
+        // zlib raw deflate of b"hello hello hello hello": a fixed block
+        // with a back-reference
+        let len = inflate(&hex("cb48cdc9c957c8402701"), &mut dst).unwrap();
+        assert_eq!(&dst[..len], b"hello hello hello hello");
+
+        // A stored block followed by an empty final fixed block
+        let len = inflate(&hex("000300fcff6162630300"), &mut dst).unwrap();
+        assert_eq!(&dst[..len], b"abc");
+
+        // A dynamic block, from zlib at level 9
+        let text = "abcccaaaacaabacaaaadcaabccabaabcabadaaaabbadabaababacaabaaabacaadaacdbd\
+                    baabbcaabadbbbdabcdbaaabdacbabcaaabcaabaabdbcbbaaabbacabcaaabaaaabcbbbab\
+                    aabaacabdcbaabacbaadabbbbaacccdbbcabcbaaaacabbabaacaaaabb";
+        let stream = hex(
+            "2d8ed1150020080267f560ff1902ad0fe401a183a4c90b30cb5c1a918ea02bd259e552cc8fc7\
+             94711d563790a4bc118f68cb56ed7f23b8427d67fba3720baa5b4b8b29ab35b9d3dd81b87b2f\
+             afbbee01",
+        );
+        assert_eq!(stream[0] >> 1 & 3, 2);
+        let len = inflate(&stream, &mut dst).unwrap();
+        assert_eq!(&dst[..len], text.as_bytes());
+        assert_eq!(
+            inflate(&stream, &mut dst[..100]),
+            Err(InflateError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Completing chunked operations in software when the device stalls.
+//!
+//! The `*_with_context` methods of [`DsaEngine`] submit large buffers as
+//! chunks of [`CONTEXT_CHUNK_SIZE`](crate::cancel::CONTEXT_CHUNK_SIZE).
+//! With [`DsaEngine::set_soft_timeout`], each chunk also gets a deadline of
+//! its own. A chunk that misses it is abandoned like any expired
+//! descriptor, and that chunk and the rest of the call are completed in
+//! software instead of failing the call: a service would rather have a
+//! slower answer than an error when the accelerator hiccups. Each such
+//! event is counted in [`DsaEngine::degrade_stats`] and logged as a warning.
+//!
+//! The deadline and token of the call's [`OpContext`] still apply: once
+//! they expire, the call fails as before. The abandoned descriptor may
+//! still write its destination later, so before a degraded `memcpy` or
+//! `memset` returns, it waits for that descriptor with a Drain descriptor,
+//! under the same context. If the drain does not complete, the call fails
+//! with its error, and as after a timeout the buffers must not be reused
+//! until the device has gone idle.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::cancel::OpContext;
+//! use dsa_rust::DsaEngine;
+//! use std::time::Duration;
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! engine.set_soft_timeout(Some(Duration::from_millis(2)));
+//!
+//! let src = vec![7u8; 64 << 20];
+//! let mut dst = vec![0u8; 64 << 20];
+//! engine.memcpy_with_context(&mut dst, &src, &OpContext::NONE)?;
+//! let stats = engine.degrade_stats();
+//! println!("{} soft timeouts, {} bytes in software", stats.soft_timeouts, stats.software_bytes);
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::cancel::OpContext;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Operations completed in software after a soft timeout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DegradeStats {
+    /// Chunks that missed the soft timeout.
+    pub soft_timeouts: u64,
+    /// Bytes processed in software after a soft timeout.
+    pub software_bytes: u64,
+}
+
+/// Counters behind [`DegradeStats`].
+#[derive(Debug, Default)]
+pub(crate) struct DegradeCounters {
+    soft_timeouts: AtomicU64,
+    software_bytes: AtomicU64,
+}
+
+impl DsaEngine {
+    /// Give each chunk of the `*_with_context` operations `timeout` to
+    /// complete before the rest of the call is done in software, or fail
+    /// only on the call's own context with `None` (the default). See the
+    /// [`degrade`](crate::degrade) module for details.
+    pub fn set_soft_timeout(&mut self, timeout: Option<Duration>) {
+        self.soft_timeout = timeout;
+    }
+
+    /// Get the per-chunk soft timeout, if any.
+    pub fn soft_timeout(&self) -> Option<Duration> {
+        self.soft_timeout
+    }
+
+    /// Get the soft timeouts seen by this engine so far.
+    pub fn degrade_stats(&self) -> DegradeStats {
+        DegradeStats {
+            soft_timeouts: self.degraded.soft_timeouts.load(Ordering::Relaxed),
+            software_bytes: self.degraded.software_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run one chunk on the device under the soft timeout.
+    ///
+    /// Returns `Ok(None)` if the chunk missed the soft timeout while `ctx`
+    /// is still live; the event is recorded with the `remaining` bytes of
+    /// the call, which the caller must then complete in software.
+    pub(crate) fn soft_timed<T>(
+        &self,
+        ctx: &OpContext,
+        remaining: usize,
+        chunk: impl FnOnce(&OpContext) -> Result<T, DsaError>,
+    ) -> Result<Option<T>, DsaError> {
+        let Some(timeout) = self.soft_timeout else {
+            return chunk(ctx).map(Some);
+        };
+        let soft = Instant::now() + timeout;
+        let deadline = ctx.deadline().map_or(soft, |deadline| deadline.min(soft));
+        match chunk(&ctx.clone().with_deadline(deadline)) {
+            Err(e @ (DsaError::DeadlineExceeded | DsaError::Timeout { .. }))
+                if ctx.check().is_ok() =>
+            {
+                log::warn!("DSA chunk missed its soft timeout ({e}), completing in software");
+                self.degraded.soft_timeouts.fetch_add(1, Ordering::Relaxed);
+                self.degraded
+                    .software_bytes
+                    .fetch_add(remaining as u64, Ordering::Relaxed);
+                Ok(None)
+            }
+            result => result.map(Some),
+        }
+    }
+
+    /// Wait, bounded by `ctx`, until the chunks abandoned by a soft timeout
+    /// can no longer write their destination.
+    pub(crate) fn await_abandoned(&self, ctx: &OpContext) -> Result<(), DsaError> {
+        self.retry(|| self.work_queue().drain_in(ctx))
+    }
+}
+
+/// Fill `dst` with `pattern` in software, starting in phase.
+pub(crate) fn fill(dst: &mut [u8], pattern: u64) {
+    let bytes = pattern.to_le_bytes();
+    for (i, byte) in dst.iter_mut().enumerate() {
+        *byte = bytes[i % bytes.len()];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        let mut dst = [0u8; 11];
+        fill(&mut dst, 0x0807_0605_0403_0201);
+        assert_eq!(dst, [1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3]);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_soft_timeout() {
+        use crate::cancel::CONTEXT_CHUNK_SIZE;
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let src: Vec<u8> = (0..3 * CONTEXT_CHUNK_SIZE + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut dst = vec![0u8; src.len()];
+
+        // Without a soft timeout, only the call's deadline ends the wait
+        emulator.inject(Fault::Stall);
+        let ctx = OpContext::new().with_timeout(Duration::from_millis(10));
+        assert!(matches!(
+            engine.memcpy_with_context(&mut dst, &src, &ctx),
+            Err(DsaError::DeadlineExceeded)
+        ));
+        assert_eq!(engine.degrade_stats(), DegradeStats::default());
+
+        // The stalled chunk and the rest of the call complete in software
+        engine.set_soft_timeout(Some(Duration::from_millis(10)));
+        assert_eq!(engine.soft_timeout(), Some(Duration::from_millis(10)));
+        emulator.inject(Fault::Stall);
+        assert_eq!(
+            engine.crc32_with_context(&src, &OpContext::NONE).unwrap(),
+            crate::crc::software_crc32(&src, 0)
+        );
+        assert_eq!(
+            engine.degrade_stats(),
+            DegradeStats {
+                soft_timeouts: 1,
+                software_bytes: src.len() as u64,
+            }
+        );
+
+        // Writers wait for the stalled chunk with a Drain before returning
+        emulator.inject(Fault::Stall);
+        let submitted = emulator.submitted();
+        engine
+            .memcpy_with_context(&mut dst, &src, &OpContext::NONE)
+            .unwrap();
+        assert_eq!(dst, src);
+        assert_eq!(emulator.submitted(), submitted + 2);
+
+        // A drain that does not finish within the call's context fails it
+        emulator.inject(Fault::Stall);
+        emulator.inject(Fault::Stall);
+        let ctx = OpContext::new().with_timeout(Duration::from_millis(50));
+        assert!(matches!(
+            engine.memcpy_with_context(&mut dst, &src, &ctx),
+            Err(DsaError::DeadlineExceeded)
+        ));
+        emulator.inject(Fault::Stall);
+        engine
+            .memset_with_context(&mut dst, 0x0807_0605_0403_0201, &OpContext::NONE)
+            .unwrap();
+        assert!(dst
+            .chunks(8)
+            .all(|c| c == &[1, 2, 3, 4, 5, 6, 7, 8][..c.len()]));
+        let mut other = dst.clone();
+        other[2 * CONTEXT_CHUNK_SIZE] ^= 1;
+        emulator.inject(Fault::Stall);
+        assert!(!engine
+            .memcmp_with_context(&dst, &other, &OpContext::NONE)
+            .unwrap());
+        assert_eq!(engine.degrade_stats().soft_timeouts, 5);
+
+        // The call's own deadline still fails the call
+        emulator.inject(Fault::Stall);
+        let ctx = OpContext::new().with_timeout(Duration::from_millis(5));
+        assert!(matches!(
+            engine.crc32_with_context(&src, &ctx),
+            Err(DsaError::DeadlineExceeded)
+        ));
+        assert_eq!(engine.degrade_stats().soft_timeouts, 5);
+    }
+}
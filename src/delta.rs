@@ -0,0 +1,358 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Delta records.
+//!
+//! The CreateDelta operation compares an original and a modified buffer in
+//! 8-byte words and writes a delta record listing the words that changed;
+//! ApplyDelta writes those words into a copy of the original. A
+//! [`DeltaRecord`] owns such a record together with the length of the buffer
+//! it describes, so dirty-page deltas can be shipped between hosts (live
+//! migration, checkpoint shipping). With the `serde` feature it implements
+//! `Serialize` and `Deserialize`; deserialized records are validated.
+//!
+//! # Format
+//!
+//! A delta record is a sequence of 10-byte entries: the little-endian
+//! 16-bit offset of the changed word, in 8-byte units, followed by the 8
+//! bytes of the modified word. Offsets are strictly increasing.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let original = vec![0u8; 4096];
+//! let mut modified = original.clone();
+//! modified[100] = 1;
+//!
+//! // Ship the delta if it is smaller than half a page, else the whole page
+//! if let Some(delta) = engine.create_delta(&original, &modified, 2048)? {
+//!     let mut replica = original.clone();
+//!     delta.apply(&engine, &mut replica)?;
+//!     assert_eq!(replica, modified);
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+
+/// Size of one delta record entry in bytes.
+pub const ENTRY_SIZE: usize = crate::descriptor::DELTA_ENTRY_SIZE;
+
+/// Largest buffer a delta record can describe, limited by the 16-bit
+/// word offset (512 KiB).
+pub const MAX_LEN: usize = crate::descriptor::DELTA_MAX_LEN;
+
+/// A delta record and the length of the buffer it describes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawDeltaRecord")
+)]
+pub struct DeltaRecord {
+    /// Length of the original and modified buffers.
+    len: usize,
+    /// Encoded entries.
+    entries: Vec<u8>,
+}
+
+/// Unvalidated form of a deserialized [`DeltaRecord`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawDeltaRecord {
+    len: usize,
+    entries: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawDeltaRecord> for DeltaRecord {
+    type Error = DsaError;
+
+    fn try_from(raw: RawDeltaRecord) -> Result<Self, DsaError> {
+        Self::from_parts(raw.len, raw.entries)
+    }
+}
+
+impl DeltaRecord {
+    /// Create a delta record from its parts, for example after receiving
+    /// them over the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - Length of the buffer the record describes
+    /// * `entries` - Encoded delta record entries
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `len` is not a multiple of 8
+    /// or exceeds [`MAX_LEN`], or if the entries are malformed, out of
+    /// order or refer to words beyond `len`.
+    pub fn from_parts(len: usize, entries: Vec<u8>) -> Result<Self, DsaError> {
+        check_len(len)?;
+        check_entries(&entries, len)?;
+        Ok(Self { len, entries })
+    }
+
+    /// Length of the buffer the record describes.
+    pub fn buffer_len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of changed 8-byte words.
+    pub fn changed_words(&self) -> usize {
+        self.entries.len() / ENTRY_SIZE
+    }
+
+    /// Returns true if the buffers were identical.
+    pub fn is_unchanged(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encoded entries, as written by CreateDelta.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.entries
+    }
+
+    /// Consume the record, returning its encoded entries.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.entries
+    }
+
+    /// Apply the record to a copy of the original buffer using ApplyDelta.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - Engine to run ApplyDelta on
+    /// * `dst` - Copy of the original buffer; receives the modified words
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BufferSizeMismatch` if `dst` does not have the
+    /// record's buffer length, or an error if the operation fails.
+    pub fn apply(&self, engine: &DsaEngine, dst: &mut [u8]) -> Result<(), DsaError> {
+        if dst.len() != self.len {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: self.len,
+                actual: dst.len(),
+            });
+        }
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        engine.throttle(self.entries.len(), 1);
+        engine.retry(|| engine.work_queue().apply_delta(&self.entries, dst))
+    }
+}
+
+impl DsaEngine {
+    /// Create a delta record of the words in which `modified` differs from
+    /// `original`, using CreateDelta.
+    ///
+    /// # Arguments
+    ///
+    /// * `original` - The original buffer
+    /// * `modified` - The modified buffer (same length as `original`)
+    /// * `max_size` - Largest delta record to produce, in bytes
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the delta record would exceed `max_size`; shipping the
+    /// modified buffer itself is then cheaper.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer lengths differ, are not a multiple of
+    /// 8 or exceed [`MAX_LEN`], or if the operation fails.
+    pub fn create_delta(
+        &self,
+        original: &[u8],
+        modified: &[u8],
+        max_size: usize,
+    ) -> Result<Option<DeltaRecord>, DsaError> {
+        // A record never needs more than one entry per word
+        let capacity = max_size.min(original.len() / 8 * ENTRY_SIZE);
+        let mut entries = vec![0u8; capacity - capacity % ENTRY_SIZE];
+        self.throttle(original.len(), 1);
+        let size = self.retry(|| {
+            self.work_queue()
+                .create_delta(original, modified, &mut entries)
+        })?;
+        Ok(size.map(|size| {
+            entries.truncate(size);
+            DeltaRecord {
+                len: original.len(),
+                entries,
+            }
+        }))
+    }
+}
+
+/// Check that a buffer length can be described by a delta record.
+fn check_len(len: usize) -> Result<(), DsaError> {
+    if !len.is_multiple_of(8) || len > MAX_LEN {
+        return Err(DsaError::InvalidArgument(format!(
+            "delta buffers must be a multiple of 8 bytes and at most {MAX_LEN} bytes, got {len}"
+        )));
+    }
+    Ok(())
+}
+
+/// Check the arguments of a CreateDelta operation.
+pub(crate) fn check_create(original: &[u8], modified: &[u8]) -> Result<(), DsaError> {
+    if original.len() != modified.len() {
+        return Err(DsaError::BufferSizeMismatch {
+            expected: original.len(),
+            actual: modified.len(),
+        });
+    }
+    check_len(original.len())
+}
+
+/// Check that `entries` is a well-formed delta record for a `len`-byte buffer.
+pub(crate) fn check_entries(entries: &[u8], len: usize) -> Result<(), DsaError> {
+    if !entries.len().is_multiple_of(ENTRY_SIZE) {
+        return Err(DsaError::InvalidArgument(format!(
+            "delta record size {} is not a multiple of {ENTRY_SIZE}",
+            entries.len()
+        )));
+    }
+    let mut next = 0;
+    for entry in entries.chunks_exact(ENTRY_SIZE) {
+        let word = u16::from_le_bytes([entry[0], entry[1]]) as usize;
+        if word < next || (word + 1) * 8 > len {
+            return Err(DsaError::InvalidArgument(format!(
+                "delta record entry for word {word} is out of order or beyond {len} bytes"
+            )));
+        }
+        next = word + 1;
+    }
+    Ok(())
+}
+
+/// Encode the words in which `modified` differs from `original` into
+/// `delta`, as CreateDelta does.
+///
+/// Returns the record size, or `None` if it does not fit in `delta`. The
+/// buffers must have passed [`check_create`].
+pub(crate) fn encode(original: &[u8], modified: &[u8], delta: &mut [u8]) -> Option<usize> {
+    let mut size = 0;
+    let words = original.chunks_exact(8).zip(modified.chunks_exact(8));
+    for (word, (a, b)) in words.enumerate() {
+        if a == b {
+            continue;
+        }
+        let entry = delta.get_mut(size..size + ENTRY_SIZE)?;
+        entry[..2].copy_from_slice(&(word as u16).to_le_bytes());
+        entry[2..].copy_from_slice(b);
+        size += ENTRY_SIZE;
+    }
+    Some(size)
+}
+
+/// Write the words in `delta` into `dst`, as ApplyDelta does.
+///
+/// The record must have passed [`check_entries`] for `dst.len()`.
+pub(crate) fn decode(delta: &[u8], dst: &mut [u8]) {
+    for entry in delta.chunks_exact(ENTRY_SIZE) {
+        let offset = u16::from_le_bytes([entry[0], entry[1]]) as usize * 8;
+        dst[offset..offset + 8].copy_from_slice(&entry[2..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let original = vec![0u8; 64];
+        let mut modified = original.clone();
+        modified[3] = 1;
+        modified[56..].fill(2);
+
+        let mut delta = [0u8; 4 * ENTRY_SIZE];
+        let size = encode(&original, &modified, &mut delta).unwrap();
+        assert_eq!(size, 2 * ENTRY_SIZE);
+        assert_eq!(&delta[..2], &[0, 0]);
+        assert_eq!(&delta[ENTRY_SIZE..ENTRY_SIZE + 2], &[7, 0]);
+        check_entries(&delta[..size], 64).unwrap();
+
+        let mut replica = original.clone();
+        decode(&delta[..size], &mut replica);
+        assert_eq!(replica, modified);
+
+        assert_eq!(encode(&original, &modified, &mut delta[..ENTRY_SIZE]), None);
+    }
+
+    #[test]
+    fn test_from_parts_validates() {
+        let mut entry = vec![0u8; ENTRY_SIZE];
+        assert!(DeltaRecord::from_parts(8, entry.clone()).is_ok());
+        assert!(DeltaRecord::from_parts(12, entry.clone()).is_err());
+        assert!(DeltaRecord::from_parts(MAX_LEN + 8, Vec::new()).is_err());
+        assert!(DeltaRecord::from_parts(8, vec![0; ENTRY_SIZE - 1]).is_err());
+
+        entry[0] = 1;
+        assert!(DeltaRecord::from_parts(8, entry.clone()).is_err());
+        let twice = [entry.clone(), entry].concat();
+        assert!(DeltaRecord::from_parts(16, twice).is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_create_and_apply_with_emulator() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let original: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let mut modified = original.clone();
+        modified[10] ^= 0xFF;
+        modified[4000..4020].fill(0);
+
+        let delta = engine
+            .create_delta(&original, &modified, 4096)
+            .unwrap()
+            .unwrap();
+        assert_eq!(delta.buffer_len(), 4096);
+        assert_eq!(delta.changed_words(), 4);
+
+        let mut replica = original.clone();
+        delta.apply(&engine, &mut replica).unwrap();
+        assert_eq!(replica, modified);
+
+        let same = engine.create_delta(&original, &original, 0).unwrap();
+        assert!(same.unwrap().is_unchanged());
+        assert!(engine
+            .create_delta(&original, &modified, 3 * ENTRY_SIZE)
+            .unwrap()
+            .is_none());
+        assert!(engine
+            .create_delta(&original[..5], &modified[..5], 64)
+            .is_err());
+        assert!(delta.apply(&engine, &mut replica[..8]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let original = vec![0u8; 32];
+        let mut modified = original.clone();
+        modified[9] = 9;
+        let mut entries = vec![0u8; 4 * ENTRY_SIZE];
+        let size = encode(&original, &modified, &mut entries).unwrap();
+        entries.truncate(size);
+        let delta = DeltaRecord::from_parts(32, entries).unwrap();
+
+        let json = serde_json::to_string(&delta).unwrap();
+        assert_eq!(serde_json::from_str::<DeltaRecord>(&json).unwrap(), delta);
+
+        let bad = r#"{"len":32,"entries":[9,0,0,0,0,0,0,0,0,0]}"#;
+        assert!(serde_json::from_str::<DeltaRecord>(bad).is_err());
+    }
+}
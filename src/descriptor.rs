@@ -7,7 +7,10 @@
 //! These structures match the hardware layout defined in the Intel DSA
 //! Architecture Specification and Linux kernel's `include/uapi/linux/idxd.h`.
 
-use crate::opcode::DsaOpcode;
+use crate::error::{DescriptorError, DsaError};
+#[cfg(feature = "std")]
+use crate::interrupt::InterruptHandle;
+use crate::opcode::{DecodedOpcode, DsaOpcode};
 use bitflags::bitflags;
 
 bitflags! {
@@ -32,11 +35,57 @@ bitflags! {
         const STATUS_WRITEBACK = 1 << 7;
         /// Destination readback.
         const DEST_READBACK = 1 << 8;
-        /// Cache control - don't allocate destination in cache.
+        /// Cache control - write the destination to cache rather than memory.
         const CACHE_CTRL = 1 << 9;
+        /// Check result - a compare that finds a difference completes with
+        /// a failure status instead of success with a nonzero result.
+        const CHECK_RESULT = 1 << 10;
+        /// Traffic class selector for the source address.
+        const ADDR1_TCS = 1 << 11;
+        /// Traffic class selector for the destination or second source address.
+        const ADDR2_TCS = 1 << 12;
+        /// Traffic class selector for the second destination address (Dualcast).
+        const ADDR3_TCS = 1 << 13;
+        /// Traffic class selector for the completion record address.
+        const CR_TCS = 1 << 14;
+        /// Strict ordering - write the destination in address order.
+        const STRICT_ORDERING = 1 << 15;
+        /// Readback of the second destination (Dualcast).
+        const DEST2_READBACK = 1 << 16;
+        /// Addresses are already translated (ATS translated requests).
+        const ADDR_TRANSLATED = 1 << 17;
+        /// Destination writes do not snoop processor caches.
+        const DEST_NO_SNOOP = 1 << 18;
     }
 }
 
+impl DescriptorFlags {
+    /// Pairs of flags that must not be set together.
+    ///
+    /// Cache control and no-snoop writes keep the destination out of the
+    /// cache that a steering tag would direct it into, and translated
+    /// addresses cannot fault, so blocking on a fault is meaningless.
+    pub const EXCLUSIVE: [(DescriptorFlags, DescriptorFlags); 3] = [
+        (Self::CACHE_CTRL, Self::DEST_STEERING_TAG),
+        (Self::DEST_NO_SNOOP, Self::DEST_STEERING_TAG),
+        (Self::ADDR_TRANSLATED, Self::BLOCK_ON_FAULT),
+    ];
+
+    /// Returns the first pair of mutually exclusive flags that are both set.
+    pub fn conflict(self) -> Option<(DescriptorFlags, DescriptorFlags)> {
+        Self::EXCLUSIVE
+            .iter()
+            .copied()
+            .find(|&(a, b)| self.contains(a | b))
+    }
+}
+
+/// PASID bits of the descriptor's PASID word.
+const PASID_MASK: u32 = 0x000F_FFFF;
+
+/// Privileged (supervisor) bit of the descriptor's PASID word.
+const PASID_PRIVILEGED: u32 = 1 << 31;
+
 /// 64-byte DSA hardware descriptor.
 ///
 /// This structure is submitted to the DSA hardware via MOVDIR64B or ENQCMD
@@ -44,8 +93,10 @@ bitflags! {
 ///
 /// # Layout
 ///
-/// The descriptor layout varies slightly depending on the operation,
-/// but the first 32 bytes are common to all operations.
+/// The first 40 bytes are common to all operations. Bytes 40-63 are an
+/// operation-specific union, as in the kernel's `struct dsa_hw_desc`; they
+/// are accessed through typed views such as [`as_crc`](Self::as_crc) and
+/// [`as_delta`](Self::as_delta).
 #[derive(Debug, Clone, Copy)]
 #[repr(C, align(64))]
 pub struct DsaHwDesc {
@@ -80,16 +131,8 @@ pub struct DsaHwDesc {
     /// Reserved field.
     reserved1: u16,
 
-    // Operation-specific fields (bytes 40-63)
-    /// Source 2 address or delta record address.
-    pub src2_addr: u64,
-
-    /// Maximum delta record size or CRC seed.
-    /// For CRC operations, bits [31:0] contain the seed value.
-    pub crc_seed_or_delta_size: u64,
-
-    /// Reserved/operation-specific field.
-    reserved2: u64,
+    /// Operation-specific fields (bytes 40-63).
+    op_specific: [u64; 3],
 }
 
 impl DsaHwDesc {
@@ -105,12 +148,116 @@ impl DsaHwDesc {
             xfer_size: 0,
             int_handle: 0,
             reserved1: 0,
-            src2_addr: 0,
-            crc_seed_or_delta_size: 0,
-            reserved2: 0,
+            op_specific: [0; 3],
         }
     }
 
+    /// The operation-specific bytes 40-63, uninterpreted.
+    #[inline]
+    pub fn op_specific(&self) -> &[u8; 24] {
+        self.view()
+    }
+
+    /// The operation-specific bytes 40-63, uninterpreted, for writing.
+    #[inline]
+    pub fn op_specific_mut(&mut self) -> &mut [u8; 24] {
+        self.view_mut()
+    }
+
+    /// The operation-specific fields of a CRC generation or copy-with-CRC
+    /// descriptor.
+    #[inline]
+    pub fn as_crc(&self) -> &CrcDesc {
+        self.view()
+    }
+
+    /// The operation-specific fields of a CRC generation or copy-with-CRC
+    /// descriptor, for writing.
+    #[inline]
+    pub fn as_crc_mut(&mut self) -> &mut CrcDesc {
+        self.view_mut()
+    }
+
+    /// The operation-specific fields of a create delta record descriptor.
+    #[inline]
+    pub fn as_delta(&self) -> &DeltaDesc {
+        self.view()
+    }
+
+    /// The operation-specific fields of a create delta record descriptor,
+    /// for writing.
+    #[inline]
+    pub fn as_delta_mut(&mut self) -> &mut DeltaDesc {
+        self.view_mut()
+    }
+
+    /// The operation-specific fields of an apply delta record descriptor.
+    #[inline]
+    pub fn as_apply_delta(&self) -> &ApplyDeltaDesc {
+        self.view()
+    }
+
+    /// The operation-specific fields of an apply delta record descriptor,
+    /// for writing.
+    #[inline]
+    pub fn as_apply_delta_mut(&mut self) -> &mut ApplyDeltaDesc {
+        self.view_mut()
+    }
+
+    /// The operation-specific fields of a DIF check, insert, strip or
+    /// update descriptor.
+    #[inline]
+    pub fn as_dif(&self) -> &DifDesc {
+        self.view()
+    }
+
+    /// The operation-specific fields of a DIF check, insert, strip or
+    /// update descriptor, for writing.
+    #[inline]
+    pub fn as_dif_mut(&mut self) -> &mut DifDesc {
+        self.view_mut()
+    }
+
+    /// The operation-specific fields of an IAA descriptor.
+    #[cfg(feature = "iaa")]
+    #[inline]
+    pub fn as_iaa(&self) -> &IaaDesc {
+        self.view()
+    }
+
+    /// The operation-specific fields of an IAA descriptor, for writing.
+    #[cfg(feature = "iaa")]
+    #[inline]
+    pub fn as_iaa_mut(&mut self) -> &mut IaaDesc {
+        self.view_mut()
+    }
+
+    /// The IAA operation flags in bytes 38-39 (compression, decompression
+    /// or CRC64 flags), which are reserved in DSA descriptors.
+    #[cfg(feature = "iaa")]
+    #[inline]
+    pub fn iaa_flags(&self) -> u16 {
+        self.reserved1
+    }
+
+    /// Set the IAA operation flags in bytes 38-39.
+    #[cfg(feature = "iaa")]
+    #[inline]
+    pub fn set_iaa_flags(&mut self, flags: u16) {
+        self.reserved1 = flags;
+    }
+
+    fn view<T: OpSpecific>(&self) -> &T {
+        // SAFETY: views are plain data of exactly 24 bytes aligned to at most
+        // 8, and bytes 40-63 of the 64-byte aligned descriptor are 8-aligned
+        unsafe { &*(self.op_specific.as_ptr() as *const T) }
+    }
+
+    fn view_mut<T: OpSpecific>(&mut self) -> &mut T {
+        // SAFETY: as for `view`; any bit pattern is a valid view
+        unsafe { &mut *(self.op_specific.as_mut_ptr() as *mut T) }
+    }
+
     /// Set the opcode for this descriptor.
     #[inline]
     pub fn set_opcode(&mut self, opcode: DsaOpcode) {
@@ -124,6 +271,12 @@ impl DsaHwDesc {
         (self.flags_opcode >> 24) as u8
     }
 
+    /// Get the opcode from this descriptor, decoded.
+    #[inline]
+    pub fn decoded_opcode(&self) -> DecodedOpcode {
+        DecodedOpcode::from(self.opcode())
+    }
+
     /// Set descriptor flags.
     #[inline]
     pub fn set_flags(&mut self, flags: DescriptorFlags) {
@@ -137,6 +290,45 @@ impl DsaHwDesc {
         self.flags_opcode |= flags.bits() & 0x00FFFFFF;
     }
 
+    /// Get the PASID (bits [19:0] of the PASID word).
+    #[inline]
+    pub fn pasid(&self) -> u32 {
+        self.pasid & PASID_MASK
+    }
+
+    /// Returns true if the privileged bit of the PASID word is set.
+    #[inline]
+    pub fn is_privileged(&self) -> bool {
+        self.pasid & PASID_PRIVILEGED != 0
+    }
+
+    /// Set the PASID and privilege bit explicitly.
+    ///
+    /// Normally the PASID word is left zero: ENQCMD fills in the submitting
+    /// process's PASID and dedicated work queues use their configured one.
+    /// Privileged integrators, such as a userspace VMM submitting for guest
+    /// PASIDs through vfio, can target another address space with this
+    /// setter. It is only available with the `manual-pasid` feature, and
+    /// [`validate`](Self::validate) rejects a nonzero PASID word without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pasid` - The 20-bit PASID
+    /// * `privileged` - Whether the descriptor executes in supervisor mode
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidDescriptor` if `pasid` does not fit in 20
+    /// bits.
+    #[cfg(feature = "manual-pasid")]
+    pub fn set_pasid(&mut self, pasid: u32, privileged: bool) -> Result<(), DsaError> {
+        if pasid & !PASID_MASK != 0 {
+            return Err(DescriptorError::PasidOutOfRange(pasid).into());
+        }
+        self.pasid = pasid | if privileged { PASID_PRIVILEGED } else { 0 };
+        Ok(())
+    }
+
     /// Set the completion record address.
     #[inline]
     pub fn set_completion(&mut self, record: &mut DsaCompletionRecord) {
@@ -144,6 +336,112 @@ impl DsaHwDesc {
         self.add_flags(DescriptorFlags::REQUEST_COMPLETION);
     }
 
+    /// Request a completion interrupt using the given handle.
+    ///
+    /// Only the raw handle value is copied; the descriptor does not keep
+    /// `handle` allocated. Keep it alive until the descriptor's completion
+    /// record is written: a handle dropped earlier can be allocated again
+    /// for another descriptor, whose interrupt this descriptor would then
+    /// raise. See [`crate::interrupt`].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_interrupt(&mut self, handle: &InterruptHandle) {
+        self.int_handle = handle.raw();
+        self.add_flags(DescriptorFlags::COMPLETION_INTERRUPT);
+    }
+
+    /// Check the descriptor for errors that do not depend on the memory it
+    /// references.
+    ///
+    /// This catches reserved flag bits, mutually exclusive flags, a PASID
+    /// word set without the `manual-pasid` feature, unknown opcodes, delta
+    /// sizes the record format cannot express, DIF transfer sizes that are
+    /// not a whole number of blocks, a missing or
+    /// misaligned completion record and malformed batch descriptors, which
+    /// the hardware would otherwise report in the completion record. Buffers
+    /// are not dereferenced, so the descriptors in a batch list must be
+    /// validated separately.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidDescriptor` describing the first problem
+    /// found.
+    pub fn validate(&self) -> Result<(), DsaError> {
+        let invalid = |problem: DescriptorError| Err(DsaError::InvalidDescriptor(problem));
+        let flags = self.flags_opcode & 0x00FFFFFF;
+        let reserved = flags & !DescriptorFlags::all().bits();
+        if reserved != 0 {
+            return invalid(DescriptorError::ReservedFlags(reserved));
+        }
+        if let Some((a, b)) = DescriptorFlags::from_bits_truncate(flags).conflict() {
+            return invalid(DescriptorError::ConflictingFlags(a, b));
+        }
+
+        if DescriptorFlags::from_bits_truncate(flags).contains(DescriptorFlags::ADDR_TRANSLATED)
+            && !self.is_privileged()
+        {
+            return invalid(DescriptorError::TranslatedUnprivileged);
+        }
+
+        let reserved = self.pasid & !(PASID_MASK | PASID_PRIVILEGED);
+        if reserved != 0 {
+            return invalid(DescriptorError::ReservedPasidBits(reserved));
+        }
+        if !cfg!(feature = "manual-pasid") && self.pasid != 0 {
+            return invalid(DescriptorError::PasidWithoutFeature(self.pasid));
+        }
+
+        let op = DsaOpcode::try_from(self.opcode())?;
+
+        if flags & DescriptorFlags::REQUEST_COMPLETION.bits() != 0 && self.completion_addr == 0 {
+            return invalid(DescriptorError::MissingCompletionRecord);
+        }
+        if !self.completion_addr.is_multiple_of(32) {
+            return invalid(DescriptorError::MisalignedCompletionRecord(
+                self.completion_addr,
+            ));
+        }
+
+        let len = self.xfer_size as usize;
+        if op == DsaOpcode::CreateDelta && (!len.is_multiple_of(8) || len > DELTA_MAX_LEN) {
+            return invalid(DescriptorError::CreateDeltaSize(self.xfer_size));
+        }
+        if op == DsaOpcode::ApplyDelta {
+            let size = self.as_apply_delta().delta_rec_size;
+            if !(size as usize).is_multiple_of(DELTA_ENTRY_SIZE) {
+                return invalid(DescriptorError::DeltaRecordSize(size));
+            }
+        }
+        if let Some(block) = self.dif_src_block_size() {
+            if !len.is_multiple_of(block) {
+                return invalid(DescriptorError::DifSize(self.xfer_size));
+            }
+        }
+
+        if op == DsaOpcode::Batch {
+            if self.xfer_size < 2 {
+                return invalid(DescriptorError::BatchTooSmall(self.xfer_size));
+            }
+            if !self.src_addr.is_multiple_of(64) {
+                return invalid(DescriptorError::MisalignedDescriptorList(self.src_addr));
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of a source block of a DIF descriptor, including its DIF unless
+    /// the operation is DIF Insert; `None` for other operations.
+    pub(crate) fn dif_src_block_size(&self) -> Option<usize> {
+        let block = dif_block_size(self.as_dif().dif_flags);
+        match DsaOpcode::try_from(self.opcode()).ok()? {
+            DsaOpcode::DifInsert => Some(block),
+            DsaOpcode::DifCheck | DsaOpcode::DifStrip | DsaOpcode::DifUpdate => {
+                Some(block + DIF_SIZE)
+            }
+            _ => None,
+        }
+    }
+
     /// Create a CRC generation descriptor.
     pub fn crc_gen(
         src: *const u8,
@@ -155,7 +453,28 @@ impl DsaHwDesc {
         desc.set_opcode(DsaOpcode::CrcGen);
         desc.src_addr = src as u64;
         desc.xfer_size = len as u32;
-        desc.crc_seed_or_delta_size = seed as u64;
+        desc.as_crc_mut().crc_seed = seed;
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a copy-with-CRC descriptor.
+    ///
+    /// Copies `len` bytes from `src` to `dst` and computes the CRC32 of the
+    /// source data, starting from `seed`.
+    pub fn copy_crc(
+        dst: *mut u8,
+        src: *const u8,
+        len: usize,
+        seed: u32,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::CopyCrc);
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = len as u32;
+        desc.as_crc_mut().crc_seed = seed;
         desc.set_completion(completion);
         desc
     }
@@ -208,6 +527,212 @@ impl DsaHwDesc {
         desc
     }
 
+    /// Create a compare-immediate (compare pattern) descriptor.
+    pub fn compare_imm(
+        src: *const u8,
+        len: usize,
+        pattern: u64,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::CompareImm);
+        desc.src_addr = src as u64;
+        desc.dst_addr = pattern; // Pattern goes in dst_addr for CompareImm
+        desc.xfer_size = len as u32;
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a create-delta-record descriptor.
+    ///
+    /// Compares `original` and `modified` in 8-byte words and writes an
+    /// entry to `delta` for every word that differs (see [`crate::delta`]).
+    /// `len` must be a multiple of 8 and at most [`crate::delta::MAX_LEN`].
+    pub fn create_delta(
+        original: *const u8,
+        modified: *const u8,
+        len: usize,
+        delta: *mut u8,
+        max_delta_size: usize,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::CreateDelta);
+        desc.src_addr = original as u64;
+        desc.dst_addr = modified as u64; // Second source goes in dst_addr, as for Compare
+        desc.xfer_size = len as u32;
+        let fields = desc.as_delta_mut();
+        fields.delta_addr = delta as u64;
+        fields.max_delta_size = max_delta_size as u32;
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create an apply-delta-record descriptor.
+    ///
+    /// Writes the words recorded in `delta` into the `dst_len`-byte buffer
+    /// `dst`.
+    pub fn apply_delta(
+        delta: *const u8,
+        delta_size: usize,
+        dst: *mut u8,
+        dst_len: usize,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::ApplyDelta);
+        desc.src_addr = delta as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = dst_len as u32;
+        desc.as_apply_delta_mut().delta_rec_size = delta_size as u32;
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a DIF check descriptor.
+    ///
+    /// Checks the DIF of every block of the `len`-byte protected buffer
+    /// `src` against `tags`; the reference tag of each later block is one
+    /// higher. `dif_flags` selects the block size (see
+    /// [`crate::t10pi::SectorSize`]).
+    pub fn dif_check(
+        src: *const u8,
+        len: usize,
+        dif_flags: u8,
+        tags: DifTags,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::DifCheck);
+        desc.src_addr = src as u64;
+        desc.xfer_size = len as u32;
+        let fields = desc.as_dif_mut();
+        fields.dif_flags = dif_flags;
+        fields.set_src_tags(tags);
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a DIF insert descriptor.
+    ///
+    /// Copies the `len`-byte data buffer `src` to `dst`, appending a DIF
+    /// computed from `tags` to every block.
+    pub fn dif_insert(
+        src: *const u8,
+        dst: *mut u8,
+        len: usize,
+        dif_flags: u8,
+        tags: DifTags,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::DifInsert);
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = len as u32;
+        let fields = desc.as_dif_mut();
+        fields.dif_flags = dif_flags;
+        fields.set_dest_tags(tags);
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a DIF strip descriptor.
+    ///
+    /// Checks the DIF of every block of the `len`-byte protected buffer
+    /// `src` against `tags` and copies the data without the DIFs to `dst`.
+    pub fn dif_strip(
+        src: *const u8,
+        dst: *mut u8,
+        len: usize,
+        dif_flags: u8,
+        tags: DifTags,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::DifStrip);
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = len as u32;
+        let fields = desc.as_dif_mut();
+        fields.dif_flags = dif_flags;
+        fields.set_src_tags(tags);
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a DIF update descriptor.
+    ///
+    /// Checks the DIF of every block of the `len`-byte protected buffer
+    /// `src` against `src_tags` and copies the blocks to `dst` with DIFs
+    /// computed from `dest_tags`.
+    pub fn dif_update(
+        src: *const u8,
+        dst: *mut u8,
+        len: usize,
+        dif_flags: u8,
+        src_tags: DifTags,
+        dest_tags: DifTags,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::DifUpdate);
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = len as u32;
+        let fields = desc.as_dif_mut();
+        fields.dif_flags = dif_flags;
+        fields.set_src_tags(src_tags);
+        fields.set_dest_tags(dest_tags);
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a translation fetch descriptor.
+    ///
+    /// Prefetches the device's address translations for the `len` bytes at
+    /// `addr`, so later operations on the region do not miss in the device
+    /// translation cache. Pages not present in the CPU page tables complete
+    /// with a page fault, as for any other read.
+    pub fn transl_fetch(addr: *const u8, len: usize, completion: &mut DsaCompletionRecord) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::TranslFetch);
+        desc.src_addr = addr as u64;
+        desc.xfer_size = len as u32;
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a batch descriptor referencing a list of work descriptors.
+    ///
+    /// The descriptor list must be 64-byte aligned, contain between 2 and the
+    /// device's `max_batch_size` entries, and must not itself contain batch
+    /// descriptors. On completion, the batch completion record's
+    /// `bytes_completed` field holds the number of descriptors completed.
+    pub fn batch(
+        descs: *const DsaHwDesc,
+        count: usize,
+        completion: &mut DsaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::Batch);
+        desc.src_addr = descs as u64; // Descriptor list address
+        desc.xfer_size = count as u32; // Descriptor count
+        desc.set_completion(completion);
+        desc
+    }
+
+    /// Create a drain descriptor.
+    ///
+    /// The drain completes once every descriptor submitted to the work
+    /// queue before it has completed.
+    pub fn drain(completion: &mut DsaCompletionRecord) -> Self {
+        let mut desc = Self::new();
+        desc.set_opcode(DsaOpcode::Drain);
+        desc.set_completion(completion);
+        desc
+    }
+
     /// Create a no-op descriptor (useful for testing/synchronization).
     pub fn noop(completion: &mut DsaCompletionRecord) -> Self {
         let mut desc = Self::new();
@@ -223,6 +748,179 @@ impl Default for DsaHwDesc {
     }
 }
 
+/// Typed layout of a descriptor's operation-specific bytes 40-63.
+///
+/// # Safety
+///
+/// Implementors must be `repr(C)` plain data of exactly 24 bytes, aligned
+/// to at most 8 bytes, for which every bit pattern is valid.
+unsafe trait OpSpecific {}
+
+// SAFETY: 24 bytes, alignment 1
+unsafe impl OpSpecific for [u8; 24] {}
+
+/// Operation-specific fields of CRC generation and copy-with-CRC
+/// descriptors (`crc_seed`, `seed_addr` in `idxd.h`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CrcDesc {
+    /// Initial CRC value.
+    pub crc_seed: u32,
+    reserved1: u32,
+    /// Address of the initial CRC value, for descriptors that read the seed
+    /// from memory.
+    pub seed_addr: u64,
+    reserved2: u64,
+}
+
+// SAFETY: repr(C) integers, 24 bytes, alignment 8
+unsafe impl OpSpecific for CrcDesc {}
+
+/// Operation-specific fields of create delta record descriptors
+/// (`delta_addr`, `max_delta_size`, `expected_res_mask` in `idxd.h`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct DeltaDesc {
+    /// Address the delta record is written to.
+    pub delta_addr: u64,
+    /// Size of the buffer at `delta_addr`.
+    pub max_delta_size: u32,
+    reserved1: u32,
+    /// Results that count as success with the check-result flag.
+    pub expected_res_mask: u8,
+    reserved2: [u8; 7],
+}
+
+// SAFETY: repr(C) integers, 24 bytes, alignment 8
+unsafe impl OpSpecific for DeltaDesc {}
+
+/// Operation-specific fields of apply delta record descriptors
+/// (`delta_rec_size` in `idxd.h`).
+///
+/// The descriptor's source is the delta record, and its transfer size is
+/// the size of the destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct ApplyDeltaDesc {
+    /// Size of the delta record in bytes.
+    pub delta_rec_size: u32,
+    reserved: [u32; 5],
+}
+
+// SAFETY: repr(C) integers, 24 bytes, alignment 4
+unsafe impl OpSpecific for ApplyDeltaDesc {}
+
+/// Size of one delta record entry in bytes.
+pub(crate) const DELTA_ENTRY_SIZE: usize = 10;
+
+/// Largest buffer a delta record can describe, limited by the 16-bit
+/// word offset (512 KiB).
+pub(crate) const DELTA_MAX_LEN: usize = (u16::MAX as usize + 1) * 8;
+
+/// Operation-specific fields of DIF descriptors.
+///
+/// One layout serves all four DIF operations, as the kernel's unions
+/// overlap: DIF check and strip use the `src_*` fields, DIF insert the
+/// `dest_*` fields, and DIF update both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct DifDesc {
+    /// Source DIF flags (check, strip, update).
+    pub src_dif_flags: u8,
+    /// Destination DIF flags (insert, update).
+    pub dest_dif_flags: u8,
+    /// DIF operation flags, such as the block size.
+    pub dif_flags: u8,
+    reserved: [u8; 5],
+    /// Reference tag seed of the source.
+    pub src_ref_tag_seed: u32,
+    /// Application tag mask of the source.
+    pub src_app_tag_mask: u16,
+    /// Application tag seed of the source.
+    pub src_app_tag_seed: u16,
+    /// Reference tag seed of the destination.
+    pub dest_ref_tag_seed: u32,
+    /// Application tag mask of the destination.
+    pub dest_app_tag_mask: u16,
+    /// Application tag seed of the destination.
+    pub dest_app_tag_seed: u16,
+}
+
+impl DifDesc {
+    /// The source tag seeds.
+    pub fn src_tags(&self) -> DifTags {
+        DifTags {
+            ref_tag: self.src_ref_tag_seed,
+            app_tag_mask: self.src_app_tag_mask,
+            app_tag: self.src_app_tag_seed,
+        }
+    }
+
+    /// Set the source tag seeds.
+    pub fn set_src_tags(&mut self, tags: DifTags) {
+        self.src_ref_tag_seed = tags.ref_tag;
+        self.src_app_tag_mask = tags.app_tag_mask;
+        self.src_app_tag_seed = tags.app_tag;
+    }
+
+    /// The destination tag seeds.
+    pub fn dest_tags(&self) -> DifTags {
+        DifTags {
+            ref_tag: self.dest_ref_tag_seed,
+            app_tag_mask: self.dest_app_tag_mask,
+            app_tag: self.dest_app_tag_seed,
+        }
+    }
+
+    /// Set the destination tag seeds.
+    pub fn set_dest_tags(&mut self, tags: DifTags) {
+        self.dest_ref_tag_seed = tags.ref_tag;
+        self.dest_app_tag_mask = tags.app_tag_mask;
+        self.dest_app_tag_seed = tags.app_tag;
+    }
+}
+
+// SAFETY: repr(C) integers, 24 bytes, alignment 4
+unsafe impl OpSpecific for DifDesc {}
+
+/// Size of the Data Integrity Field appended to each block.
+pub(crate) const DIF_SIZE: usize = 8;
+
+/// Data block sizes selected by the block size bits of the DIF flags.
+const DIF_BLOCK_SIZES: [usize; 4] = [512, 520, 4096, 4104];
+
+/// Data block size selected by a descriptor's DIF flags.
+pub(crate) fn dif_block_size(dif_flags: u8) -> usize {
+    DIF_BLOCK_SIZES[(dif_flags & 0x3) as usize]
+}
+
+/// Operation-specific fields of IAA descriptors (`src2_addr`,
+/// `max_dst_size`, `src2_size` in `struct iax_hw_desc`).
+///
+/// Compress reads its Huffman tables from the second source; CRC64 takes
+/// its polynomial in the last eight bytes, where analytics operations keep
+/// their filter flags and number of inputs.
+#[cfg(feature = "iaa")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct IaaDesc {
+    /// Address of the second source.
+    pub src2_addr: u64,
+    /// Size of the destination buffer.
+    pub max_dst_size: u32,
+    /// Size of the second source.
+    pub src2_size: u32,
+    /// CRC64 generator polynomial, without the x^64 term.
+    pub crc64_poly: u64,
+}
+
+// SAFETY: repr(C) integers, 24 bytes, alignment 8
+#[cfg(feature = "iaa")]
+unsafe impl OpSpecific for IaaDesc {}
+
+/// CreateDelta result when the delta record exceeded its maximum size.
+pub(crate) const DELTA_RECORD_FULL: u8 = 2;
+
 /// 64-byte DSA completion record.
 ///
 /// The DSA hardware writes to this structure when an operation completes.
@@ -239,9 +937,9 @@ impl Default for DsaHwDesc {
 /// | 3 | 1 | reserved |
 /// | 4 | 4 | bytes_completed |
 /// | 8 | 8 | fault_addr |
-/// | 16 | 8 | result_value (CRC, etc.) |
-/// | 24 | 8 | result_value2 (extended results) |
-/// | 32 | 32 | operation-specific (DIF, etc.) |
+/// | 16 | 8 | result_value (CRC, DIF tags, etc.) |
+/// | 24 | 8 | result_value2 (extended results, DIF tags) |
+/// | 32 | 32 | reserved |
 ///
 /// The structure is 64 bytes to match the Linux kernel's `dsa_completion_record`
 /// and support all DSA operations including DIF.
@@ -254,6 +952,7 @@ pub struct DsaCompletionRecord {
 
     /// Result code (operation-specific).
     /// - For Compare: 0 = equal, 1 = not equal
+    /// - For CreateDelta: 0 = equal, 1 = delta written, 2 = delta record full
     /// - For other ops: error details
     pub result: u8,
 
@@ -264,6 +963,8 @@ pub struct DsaCompletionRecord {
     reserved1: u8,
 
     /// Number of bytes completed (for partial completions on page fault).
+    /// For Compare operations that find a difference, this is the offset of
+    /// the first differing byte.
     pub bytes_completed: u32,
 
     /// Fault address (if page fault occurred).
@@ -271,17 +972,15 @@ pub struct DsaCompletionRecord {
 
     /// Primary result value (operation-dependent).
     /// - CRC operations: CRC32 value in bits [31:0]
-    /// - Compare: first differing offset
+    /// - CreateDelta: size of the delta record in bytes
     pub result_value: u64,
 
     /// Secondary result value (extended operations).
     /// - CRC64: upper bits of CRC
-    /// - DIF operations: additional status
+    /// - DIF Insert and DIF Update: tags, see [`Self::dif_insert_tags`]
     pub result_value2: u64,
 
-    /// Operation-specific extended results.
-    /// Used by DIF check/insert/update operations for detailed status.
-    /// For basic operations (CRC, memcpy, etc.), this field is unused.
+    /// Reserved; the kernel's record ends before these bytes.
     reserved_op_specific: [u8; 32],
 }
 
@@ -312,13 +1011,13 @@ impl DsaCompletionRecord {
     #[inline]
     pub fn is_complete(&self) -> bool {
         // Use volatile read to prevent compiler from caching the value
-        unsafe { std::ptr::read_volatile(&self.status) != 0 }
+        unsafe { core::ptr::read_volatile(&self.status) != 0 }
     }
 
     /// Get the completion status (volatile read).
     #[inline]
     pub fn get_status(&self) -> CompletionStatus {
-        let status = unsafe { std::ptr::read_volatile(&self.status) };
+        let status = unsafe { core::ptr::read_volatile(&self.status) };
         CompletionStatus::from(status)
     }
 
@@ -328,12 +1027,137 @@ impl DsaCompletionRecord {
         self.result_value as u32
     }
 
+    /// Get the size of the delta record written (for CreateDelta operations).
+    ///
+    /// Returns `None` if the record did not fit in the maximum delta size.
+    #[inline]
+    pub fn delta_record_size(&self) -> Option<usize> {
+        (self.result != DELTA_RECORD_FULL).then_some(self.result_value as usize)
+    }
+
     /// Get the comparison result (for Compare operations).
     /// Returns true if buffers are equal.
     #[inline]
     pub fn compare_result(&self) -> bool {
         self.result == 0
     }
+
+    /// Get the offset of the first difference (for Compare operations).
+    /// Returns `None` if the buffers are equal.
+    #[inline]
+    pub fn mismatch_offset(&self) -> Option<usize> {
+        if self.compare_result() {
+            None
+        } else {
+            Some(self.bytes_completed as usize)
+        }
+    }
+
+    /// Get the failed DIF checks (for DIF operations).
+    ///
+    /// Empty unless the status is [`CompletionStatus::DifError`].
+    #[inline]
+    pub fn dif_status(&self) -> DifStatus {
+        if self.status == DIF_ERROR {
+            DifStatus::from_bits_retain(self.result)
+        } else {
+            DifStatus::empty()
+        }
+    }
+
+    /// Get the index of the block that failed a DIF check.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_size` - Size of a source block including its 8-byte DIF,
+    ///   e.g. 520 for 512-byte sectors
+    ///
+    /// # Returns
+    ///
+    /// `None` unless the status is [`CompletionStatus::DifError`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    #[inline]
+    pub fn dif_failed_block(&self, block_size: usize) -> Option<usize> {
+        assert!(block_size > 0, "block size must be nonzero");
+        (self.status == DIF_ERROR).then_some(self.bytes_completed as usize / block_size)
+    }
+
+    /// Get the tags the next source block is checked against (for DIF
+    /// Check and DIF Strip operations).
+    ///
+    /// After a partial completion these continue the operation with a new
+    /// descriptor starting at `bytes_completed`.
+    #[inline]
+    pub fn dif_check_tags(&self) -> DifTags {
+        DifTags::from_word(self.result_value)
+    }
+
+    /// Get the tags the next destination block is given (for DIF Insert
+    /// operations).
+    #[inline]
+    pub fn dif_insert_tags(&self) -> DifTags {
+        DifTags::from_word(self.result_value2)
+    }
+
+    /// Get the source and destination tags of the next block (for DIF Update
+    /// operations).
+    #[inline]
+    pub fn dif_update_tags(&self) -> (DifTags, DifTags) {
+        (
+            DifTags::from_word(self.result_value),
+            DifTags::from_word(self.result_value2),
+        )
+    }
+}
+
+/// Completion status of an operation that failed a DIF check.
+pub(crate) const DIF_ERROR: u8 = 0x09;
+
+bitflags! {
+    /// DIF checks that failed (the `result` byte of a DIF completion record).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DifStatus: u8 {
+        /// Guard tag (CRC16 of the block) mismatch.
+        const GUARD_MISMATCH = 1 << 0;
+        /// Application tag mismatch.
+        const APP_TAG_MISMATCH = 1 << 1;
+        /// Reference tag mismatch.
+        const REF_TAG_MISMATCH = 1 << 2;
+        /// All-F application and reference tags detected with all-F
+        /// detection errors enabled.
+        const ALL_F_DETECTED = 1 << 3;
+    }
+}
+
+/// Reference and application tags reported in a DIF completion record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DifTags {
+    /// Reference tag.
+    pub ref_tag: u32,
+    /// Application tag mask.
+    pub app_tag_mask: u16,
+    /// Application tag.
+    pub app_tag: u16,
+}
+
+impl DifTags {
+    /// Decode tags stored as `ref_tag`, `app_tag_mask`, `app_tag` in a
+    /// little-endian word.
+    fn from_word(word: u64) -> Self {
+        Self {
+            ref_tag: word as u32,
+            app_tag_mask: (word >> 32) as u16,
+            app_tag: (word >> 48) as u16,
+        }
+    }
+
+    /// Encode the tags the way completion records store them.
+    pub(crate) fn to_word(self) -> u64 {
+        self.ref_tag as u64 | (self.app_tag_mask as u64) << 32 | (self.app_tag as u64) << 48
+    }
 }
 
 impl Default for DsaCompletionRecord {
@@ -361,6 +1185,8 @@ pub enum CompletionStatus {
     InvalidCompletionAddr,
     /// Hardware error.
     HardwareError,
+    /// A DIF check failed, see [`DsaCompletionRecord::dif_status`].
+    DifError,
     /// Unknown status code.
     Unknown(u8),
 }
@@ -371,6 +1197,7 @@ impl From<u8> for CompletionStatus {
             0x00 => Self::Pending,
             0x01 => Self::Success,
             0x03 => Self::PageFault,
+            DIF_ERROR => Self::DifError,
             0x10 => Self::InvalidFlags,
             0x11 => Self::UnsupportedOp,
             0x13 => Self::InvalidSize,
@@ -402,10 +1229,10 @@ impl CompletionStatus {
 }
 
 // Compile-time size and alignment checks per Intel DSA Architecture Specification
-const _: () = assert!(std::mem::size_of::<DsaHwDesc>() == 64);
-const _: () = assert!(std::mem::align_of::<DsaHwDesc>() == 64);
-const _: () = assert!(std::mem::size_of::<DsaCompletionRecord>() == 64);
-const _: () = assert!(std::mem::align_of::<DsaCompletionRecord>() == 32);
+const _: () = assert!(core::mem::size_of::<DsaHwDesc>() == 64);
+const _: () = assert!(core::mem::align_of::<DsaHwDesc>() == 64);
+const _: () = assert!(core::mem::size_of::<DsaCompletionRecord>() == 64);
+const _: () = assert!(core::mem::align_of::<DsaCompletionRecord>() == 32);
 
 #[cfg(test)]
 mod tests {
@@ -417,6 +1244,87 @@ mod tests {
         assert_eq!(std::mem::align_of::<DsaHwDesc>(), 64);
     }
 
+    #[test]
+    fn test_op_specific_views() {
+        use std::mem::{align_of, offset_of, size_of};
+
+        assert_eq!(size_of::<CrcDesc>(), 24);
+        assert_eq!(size_of::<DeltaDesc>(), 24);
+        assert_eq!(size_of::<ApplyDeltaDesc>(), 24);
+        assert_eq!(size_of::<DifDesc>(), 24);
+        assert!(align_of::<CrcDesc>() <= 8 && align_of::<DeltaDesc>() <= 8);
+        assert_eq!(offset_of!(DsaHwDesc, op_specific), 40);
+
+        // Offsets within bytes 40-63, as in idxd.h
+        assert_eq!(offset_of!(CrcDesc, seed_addr), 8);
+        assert_eq!(offset_of!(DeltaDesc, max_delta_size), 8);
+        assert_eq!(offset_of!(DeltaDesc, expected_res_mask), 16);
+        assert_eq!(offset_of!(DifDesc, src_ref_tag_seed), 8);
+        assert_eq!(offset_of!(DifDesc, dest_ref_tag_seed), 16);
+        assert_eq!(offset_of!(DifDesc, dest_app_tag_seed), 22);
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::crc_gen(std::ptr::null(), 8, 0x1234_5678, &mut record);
+        assert_eq!(desc.as_crc().crc_seed, 0x1234_5678);
+        assert_eq!(desc.op_specific()[..4], 0x1234_5678u32.to_le_bytes());
+
+        let desc =
+            DsaHwDesc::apply_delta(std::ptr::null(), 20, std::ptr::null_mut(), 64, &mut record);
+        assert_eq!(desc.xfer_size, 64);
+        assert_eq!(desc.as_apply_delta().delta_rec_size, 20);
+
+        let mut desc = DsaHwDesc::new();
+        desc.as_dif_mut().dest_app_tag_mask = 0xFFFF;
+        assert_eq!(desc.op_specific()[20..22], [0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_dif_results() {
+        let mut record = DsaCompletionRecord::new();
+        record.status = 0x01;
+        record.result = 0x04;
+        assert!(record.dif_status().is_empty());
+        assert_eq!(record.dif_failed_block(520), None);
+
+        record.status = DIF_ERROR;
+        record.bytes_completed = 3 * 520;
+        record.result_value = 0xBEEF_FFFF_0000_0007;
+        record.result_value2 = 0x0001_00FF_0000_0100;
+        assert_eq!(record.get_status(), CompletionStatus::DifError);
+        assert_eq!(record.dif_status(), DifStatus::REF_TAG_MISMATCH);
+        assert_eq!(record.dif_failed_block(520), Some(3));
+        assert_eq!(
+            record.dif_check_tags(),
+            DifTags {
+                ref_tag: 7,
+                app_tag_mask: 0xFFFF,
+                app_tag: 0xBEEF
+            }
+        );
+        let (src, dst) = record.dif_update_tags();
+        assert_eq!(src, record.dif_check_tags());
+        assert_eq!(dst, record.dif_insert_tags());
+        assert_eq!(
+            (dst.ref_tag, dst.app_tag_mask, dst.app_tag),
+            (0x100, 0xFF, 1)
+        );
+        assert_eq!(dst.to_word(), record.result_value2);
+
+        let desc = DsaHwDesc::dif_update(
+            std::ptr::null(),
+            std::ptr::null_mut(),
+            1040,
+            1,
+            src,
+            dst,
+            &mut record,
+        );
+        assert_eq!(desc.opcode(), DsaOpcode::DifUpdate as u8);
+        assert_eq!(desc.as_dif().dif_flags, 1);
+        assert_eq!(desc.as_dif().src_tags(), src);
+        assert_eq!(desc.as_dif().dest_tags(), dst);
+    }
+
     #[test]
     fn test_completion_record_size_and_alignment() {
         assert_eq!(std::mem::size_of::<DsaCompletionRecord>(), 64);
@@ -447,6 +1355,100 @@ mod tests {
         assert!(desc.flags_opcode & DescriptorFlags::FENCE.bits() != 0);
     }
 
+    #[test]
+    fn test_batch_descriptor() {
+        let descs = [DsaHwDesc::new(); 4];
+        let mut completion = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::batch(descs.as_ptr(), descs.len(), &mut completion);
+
+        assert_eq!(desc.opcode(), DsaOpcode::Batch.as_u8());
+        assert_eq!(desc.src_addr, descs.as_ptr() as u64);
+        assert_eq!(desc.xfer_size, 4);
+        assert!(desc.flags_opcode & DescriptorFlags::REQUEST_COMPLETION.bits() != 0);
+    }
+
+    #[test]
+    fn test_flag_conflicts() {
+        assert_eq!(DescriptorFlags::all().bits(), 0x7FFFF);
+        assert_eq!(DescriptorFlags::REQUEST_COMPLETION.conflict(), None);
+        assert_eq!(
+            (DescriptorFlags::ADDR_TRANSLATED | DescriptorFlags::BLOCK_ON_FAULT).conflict(),
+            Some((
+                DescriptorFlags::ADDR_TRANSLATED,
+                DescriptorFlags::BLOCK_ON_FAULT
+            ))
+        );
+    }
+
+    #[test]
+    fn test_pasid_validation() {
+        let mut completion = DsaCompletionRecord::new();
+        let mut desc = DsaHwDesc::noop(&mut completion);
+        assert_eq!(desc.pasid(), 0);
+        assert!(!desc.is_privileged());
+
+        desc.pasid = 1 << 25;
+        assert!(desc.validate().is_err());
+
+        desc.pasid = 0;
+        desc.add_flags(DescriptorFlags::ADDR_TRANSLATED);
+        assert!(desc.validate().is_err());
+
+        desc.pasid = 0x42 | PASID_PRIVILEGED;
+        assert_eq!(desc.pasid(), 0x42);
+        assert!(desc.is_privileged());
+        assert_eq!(desc.validate().is_ok(), cfg!(feature = "manual-pasid"));
+    }
+
+    #[cfg(feature = "manual-pasid")]
+    #[test]
+    fn test_set_pasid() {
+        let mut desc = DsaHwDesc::new();
+        desc.set_pasid(0xABCDE, true).unwrap();
+        assert_eq!(desc.pasid(), 0xABCDE);
+        assert!(desc.is_privileged());
+        assert!(desc.set_pasid(1 << 20, false).is_err());
+        assert_eq!(desc.pasid(), 0xABCDE);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut completion = DsaCompletionRecord::new();
+        let data = [0u8; 16];
+        let desc = DsaHwDesc::crc_gen(data.as_ptr(), data.len(), 0, &mut completion);
+        assert!(desc.validate().is_ok());
+
+        let rejects = |desc: &DsaHwDesc, problem| matches!(desc.validate(), Err(DsaError::InvalidDescriptor(p)) if p == problem);
+        let mut bad = desc;
+        bad.flags_opcode |= 1 << 20;
+        assert!(rejects(&bad, DescriptorError::ReservedFlags(1 << 20)));
+
+        let mut bad = desc;
+        bad.add_flags(DescriptorFlags::CACHE_CTRL | DescriptorFlags::DEST_STEERING_TAG);
+        assert!(bad.validate().is_err());
+
+        let mut bad = desc;
+        bad.flags_opcode = (bad.flags_opcode & 0x00FFFFFF) | (0x0B << 24);
+        assert!(rejects(&bad, DescriptorError::UnknownOpcode(0x0B)));
+
+        let mut bad = desc;
+        bad.completion_addr += 8;
+        assert!(rejects(
+            &bad,
+            DescriptorError::MisalignedCompletionRecord(bad.completion_addr)
+        ));
+
+        let mut bad = desc;
+        bad.completion_addr = 0;
+        assert!(bad.validate().is_err());
+
+        let descs = [DsaHwDesc::new(); 2];
+        let batch = DsaHwDesc::batch(descs.as_ptr(), 1, &mut completion);
+        assert!(rejects(&batch, DescriptorError::BatchTooSmall(1)));
+        let batch = DsaHwDesc::batch(descs.as_ptr(), 2, &mut completion);
+        assert!(batch.validate().is_ok());
+    }
+
     #[test]
     fn test_completion_status() {
         assert!(CompletionStatus::Success.is_success());
@@ -465,4 +1467,15 @@ mod tests {
         assert!(record.is_complete());
         assert!(record.get_status().is_success());
     }
+
+    #[test]
+    fn test_mismatch_offset() {
+        let mut record = DsaCompletionRecord::new();
+        record.status = 0x01;
+        assert_eq!(record.mismatch_offset(), None);
+
+        record.result = 1;
+        record.bytes_completed = 42;
+        assert_eq!(record.mismatch_offset(), Some(42));
+    }
 }
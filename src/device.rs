@@ -11,26 +11,39 @@
 //! - `dsa0`, `dsa1`, ... - DSA device instances
 //! - `wq0.0`, `wq0.1`, ... - Work queues on device 0
 //!
-//! Work queue character devices appear at `/dev/dsa/wq0.0`, etc.
+//! Work queue character devices conventionally appear at `/dev/dsa/wq0.0`,
+//! etc.; [`wq_device_node`] finds them by device number when udev rules or
+//! container bind mounts place them elsewhere.
 //!
 //! ## Windows
 //! Windows support is planned but not yet implemented.
 
-use crate::error::DsaError;
-use crate::wq::{WorkQueue, WorkQueueInfo};
+use crate::error::{DsaError, WqUnavailableReason};
+use crate::interrupt::InterruptManager;
+use crate::wq::{WorkQueue, WorkQueueBinding, WorkQueueInfo};
 use std::path::PathBuf;
 
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 use std::fs;
+#[cfg(dsa_portal)]
+use std::path::Path;
 
 /// Sysfs base path for DSA devices (Linux only).
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
 
 /// Device node base path for DSA work queues (Linux only).
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 const DEV_DSA_PATH: &str = "/dev/dsa";
 
+/// Device node base path for IAA work queues (Linux only).
+#[cfg(all(feature = "iaa", dsa_portal))]
+const DEV_IAX_PATH: &str = "/dev/iax";
+
+/// Sysfs class path of the work queue character devices (Linux only).
+#[cfg(dsa_portal)]
+const SYSFS_CLASS_DSA_PATH: &str = "/sys/class/dsa";
+
 /// Information about a DSA device.
 #[derive(Debug, Clone)]
 pub struct DsaDevice {
@@ -40,15 +53,60 @@ pub struct DsaDevice {
     pub sysfs_path: PathBuf,
     /// Available work queues on this device.
     pub work_queues: Vec<WorkQueueInfo>,
+    /// Device state ("enabled", "disabled", etc.).
+    pub state: String,
+    /// Whether PASID is enabled, which shared work queues require.
+    pub pasid_enabled: bool,
+    /// Maximum number of groups the device supports.
+    pub max_groups: u32,
+    /// Maximum number of engines the device supports.
+    pub max_engines: u32,
+    /// Maximum number of work queues the device supports.
+    pub max_work_queues: u32,
+    /// Hardware version reported by the device (e.g., 0x100 for DSA 1.0).
+    pub version: u32,
+    /// PCI address of the device (e.g., "0000:6a:01.0"), if known.
+    pub pci_address: Option<String>,
 }
 
 impl DsaDevice {
+    /// Find the device at a PCI address, as handed out by orchestration
+    /// systems such as Kubernetes device plugins.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - PCI address in `domain:bus:device.function` form
+    ///   (e.g., "0000:6a:01.0"); the domain may be omitted and defaults to 0
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if the address is malformed,
+    /// `DsaError::NoDeviceFound` if no DSA device has that address, or an
+    /// error if device discovery fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use dsa_rust::DsaDevice;
+    ///
+    /// let device = DsaDevice::open_by_pci("0000:6a:01.0")?;
+    /// let wq = device.open_first_wq()?;
+    /// # Ok::<(), dsa_rust::DsaError>(())
+    /// ```
+    pub fn open_by_pci(address: &str) -> Result<DsaDevice, DsaError> {
+        let address = parse_pci_address(address)?;
+        discover_devices()?
+            .into_iter()
+            .find(|device| device.pci_address.as_deref() == Some(address.as_str()))
+            .ok_or(DsaError::NoDeviceFound)
+    }
+
     /// Open the first available enabled work queue on this device.
-    #[cfg(target_os = "linux")]
+    #[cfg(dsa_portal)]
     pub fn open_first_wq(&self) -> Result<WorkQueue, DsaError> {
         for wq_info in &self.work_queues {
-            if wq_info.state == "enabled" {
-                let dev_path = Path::new(DEV_DSA_PATH).join(&wq_info.name);
+            if wq_info.state.is_enabled() && self.can_use(wq_info) {
+                let dev_path = wq_device_node(&wq_info.name);
                 if dev_path.exists() {
                     return WorkQueue::open(&dev_path);
                 }
@@ -58,24 +116,88 @@ impl DsaDevice {
     }
 
     /// Open the first available enabled work queue on this device.
-    #[cfg(not(target_os = "linux"))]
+    #[cfg(not(dsa_portal))]
     pub fn open_first_wq(&self) -> Result<WorkQueue, DsaError> {
         Err(DsaError::PlatformNotSupported)
     }
 
     /// Open a specific work queue by name (e.g., "wq0.0").
-    #[cfg(target_os = "linux")]
+    #[cfg(dsa_portal)]
     pub fn open_wq(&self, name: &str) -> Result<WorkQueue, DsaError> {
-        let dev_path = Path::new(DEV_DSA_PATH).join(name);
-        WorkQueue::open(&dev_path)
+        WorkQueue::open(&wq_device_node(name))
     }
 
     /// Open a specific work queue by name.
-    #[cfg(not(target_os = "linux"))]
+    #[cfg(not(dsa_portal))]
     pub fn open_wq(&self, _name: &str) -> Result<WorkQueue, DsaError> {
         Err(DsaError::PlatformNotSupported)
     }
 
+    /// Open an enabled work queue by name, configuring its submission type
+    /// from sysfs.
+    ///
+    /// Dedicated work queues are reserved exclusively for this process with
+    /// [`WorkQueue::lock_exclusive`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::NoWorkQueue` if the device has no work queue with
+    /// that name, `DsaError::DeviceNotEnabled` if it is not enabled, or
+    /// `DsaError::WorkQueueUnavailable` if a kernel driver owns it.
+    #[cfg(dsa_portal)]
+    pub fn open_enabled_wq(&self, name: &str) -> Result<WorkQueue, DsaError> {
+        let wq_info = self
+            .work_queues
+            .iter()
+            .find(|wq| wq.name == name)
+            .ok_or(DsaError::NoWorkQueue)?;
+        if !wq_info.state.is_enabled() {
+            return Err(DsaError::DeviceNotEnabled);
+        }
+        if wq_info.binding.is_kernel() {
+            return Err(DsaError::WorkQueueUnavailable {
+                name: name.to_string(),
+                reason: WqUnavailableReason::KernelOwned {
+                    wq_type: wq_info.binding.to_string(),
+                },
+            });
+        }
+
+        let mut wq = self.open_wq(name)?;
+        wq.set_wq_type(wq_info.wq_type);
+        if wq_info.wq_type == crate::wq::WorkQueueType::Dedicated {
+            wq.lock_exclusive()?;
+        }
+        Ok(wq)
+    }
+
+    /// Open an enabled work queue by name.
+    #[cfg(not(dsa_portal))]
+    pub fn open_enabled_wq(&self, _name: &str) -> Result<WorkQueue, DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+
+    /// Create an interrupt handle manager sized to this device's IMS table.
+    ///
+    /// Handles must still be registered with the returned manager before
+    /// they can be allocated; see [`crate::interrupt`].
+    pub fn interrupt_manager(&self) -> Result<InterruptManager, DsaError> {
+        InterruptManager::for_device(&self.sysfs_path)
+    }
+
+    /// Returns true if shared work queues on this device can be used.
+    ///
+    /// Submitting to a shared work queue with ENQCMD requires PASID.
+    pub fn supports_shared_wq(&self) -> bool {
+        self.pasid_enabled
+    }
+
+    /// Returns true if the device's configuration allows using `wq_info`.
+    fn can_use(&self, wq_info: &WorkQueueInfo) -> bool {
+        !wq_info.binding.is_kernel()
+            && (wq_info.wq_type == crate::wq::WorkQueueType::Dedicated || self.supports_shared_wq())
+    }
+
     /// Get the number of available work queues.
     pub fn wq_count(&self) -> usize {
         self.work_queues.len()
@@ -85,7 +207,7 @@ impl DsaDevice {
     pub fn enabled_wq_count(&self) -> usize {
         self.work_queues
             .iter()
-            .filter(|wq| wq.state == "enabled")
+            .filter(|wq| wq.state.is_enabled())
             .count()
     }
 }
@@ -94,11 +216,22 @@ impl DsaDevice {
 // Linux Implementation
 // ============================================================================
 
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 mod linux_impl {
     use super::*;
+    use crate::wq::{WorkQueueState, WorkQueueType};
 
     pub fn discover_devices() -> Result<Vec<DsaDevice>, DsaError> {
+        discover_devices_named("dsa")
+    }
+
+    #[cfg(feature = "iaa")]
+    pub fn discover_iaa_devices() -> Result<Vec<DsaDevice>, DsaError> {
+        discover_devices_named("iax")
+    }
+
+    /// Discover the devices on the bus whose names start with `prefix`.
+    fn discover_devices_named(prefix: &str) -> Result<Vec<DsaDevice>, DsaError> {
         let sysfs_path = Path::new(SYSFS_DSA_PATH);
 
         if !sysfs_path.exists() {
@@ -112,16 +245,28 @@ mod linux_impl {
         for entry in entries {
             let entry = entry?;
             let name = entry.file_name().to_string_lossy().to_string();
-            if name.starts_with("dsa") && !name.contains('.') {
+            if name.starts_with(prefix) && !name.contains('.') {
                 device_names.push(name);
             }
         }
 
         for device_name in device_names {
             let device_sysfs = sysfs_path.join(&device_name);
-            let work_queues = discover_work_queues(&device_name)?;
+            let work_queues = discover_work_queues(&device_name, prefix)?;
 
+            let read_u32 = |attr: &str| read_sysfs_u32(&device_sysfs.join(attr)).unwrap_or(0);
             devices.push(DsaDevice {
+                state: read_sysfs_string(&device_sysfs.join("state"))
+                    .unwrap_or_else(|_| "unknown".to_string()),
+                pasid_enabled: read_u32("pasid_enabled") != 0,
+                max_groups: read_u32("max_groups"),
+                max_engines: read_u32("max_engines"),
+                max_work_queues: read_u32("max_work_queues"),
+                version: read_sysfs_string(&device_sysfs.join("version"))
+                    .ok()
+                    .and_then(|v| parse_hex_u32(&v))
+                    .unwrap_or(0),
+                pci_address: pci_address_of(&device_sysfs),
                 name: device_name,
                 sysfs_path: device_sysfs,
                 work_queues,
@@ -131,12 +276,25 @@ mod linux_impl {
         Ok(devices)
     }
 
-    fn discover_work_queues(device_name: &str) -> Result<Vec<WorkQueueInfo>, DsaError> {
+    /// PCI address of the device whose sysfs entry is `device_sysfs`.
+    ///
+    /// The bus entry links to the device's directory, which lives under its
+    /// PCI function's directory.
+    pub(super) fn pci_address_of(device_sysfs: &Path) -> Option<String> {
+        let device_dir = fs::canonicalize(device_sysfs).ok()?;
+        let name = device_dir.parent()?.file_name()?.to_str()?;
+        parse_pci_address(name).ok()
+    }
+
+    fn discover_work_queues(
+        device_name: &str,
+        prefix: &str,
+    ) -> Result<Vec<WorkQueueInfo>, DsaError> {
         let sysfs_path = Path::new(SYSFS_DSA_PATH);
         let mut work_queues = Vec::new();
 
         let device_num = device_name
-            .strip_prefix("dsa")
+            .strip_prefix(prefix)
             .and_then(|s| s.parse::<u32>().ok())
             .unwrap_or(0);
 
@@ -158,11 +316,13 @@ mod linux_impl {
     }
 
     fn read_wq_info(name: &str, path: &Path) -> Result<WorkQueueInfo, DsaError> {
-        let state =
-            read_sysfs_string(&path.join("state")).unwrap_or_else(|_| "unknown".to_string());
+        let state = read_sysfs_string(&path.join("state"))
+            .map(|s| WorkQueueState::from(s.as_str()))
+            .unwrap_or_else(|_| WorkQueueState::Unknown("unknown".to_string()));
         let mode = read_sysfs_string(&path.join("mode")).unwrap_or_else(|_| "unknown".to_string());
         let size = read_sysfs_u32(&path.join("size")).unwrap_or(0);
         let threshold = read_sysfs_u32(&path.join("threshold")).unwrap_or(0);
+        let flag = |attr: &str| read_sysfs_u32(&path.join(attr)).is_ok_and(|v| v != 0);
 
         let wq_type = match mode.as_str() {
             "dedicated" => WorkQueueType::Dedicated,
@@ -176,9 +336,45 @@ mod linux_impl {
             wq_type,
             size,
             threshold,
+            block_on_fault: flag("block_on_fault"),
+            max_batch_size: read_sysfs_u32(&path.join("max_batch_size")).unwrap_or(0),
+            max_transfer_size: read_sysfs_u64(&path.join("max_transfer_size")).unwrap_or(0),
+            driver_name: read_sysfs_string(&path.join("driver_name")).unwrap_or_default(),
+            clients: read_sysfs_u32(&path.join("clients")).unwrap_or(0),
+            priority: read_sysfs_u32(&path.join("priority")).unwrap_or(0),
+            ats_disable: flag("ats_disable"),
+            binding: read_wq_binding(path),
         })
     }
 
+    /// Driver the work queue whose sysfs entry is `path` is bound to.
+    ///
+    /// Read from the `driver` link, or on kernels without per-type drivers
+    /// from the `type` of an enabled work queue (`kernel` meaning the DMA
+    /// engine driver).
+    pub(super) fn read_wq_binding(path: &Path) -> WorkQueueBinding {
+        if let Ok(driver) = fs::read_link(path.join("driver")) {
+            let name = driver.file_name().map(|name| name.to_string_lossy());
+            return WorkQueueBinding::from(name.as_deref().unwrap_or_default());
+        }
+        let state = read_sysfs_string(&path.join("state")).unwrap_or_default();
+        if !WorkQueueState::from(state.as_str()).is_enabled() {
+            return WorkQueueBinding::Unbound;
+        }
+        match read_sysfs_string(&path.join("type")).as_deref() {
+            Ok("kernel") => WorkQueueBinding::DmaEngine,
+            Ok("none") | Err(_) => WorkQueueBinding::Unbound,
+            Ok(wq_type) => WorkQueueBinding::from(wq_type),
+        }
+    }
+
+    /// Driver work queue `name` is bound to, or `None` if sysfs has no
+    /// entry for it.
+    pub fn wq_binding(name: &str) -> Option<WorkQueueBinding> {
+        let wq_path = Path::new(SYSFS_DSA_PATH).join(name);
+        wq_path.exists().then(|| read_wq_binding(&wq_path))
+    }
+
     fn read_sysfs_string(path: &Path) -> Result<String, DsaError> {
         Ok(fs::read_to_string(path)?.trim().to_string())
     }
@@ -189,6 +385,137 @@ mod linux_impl {
             .map_err(|_| DsaError::InvalidArgument(format!("invalid u32 in sysfs: {}", s)))
     }
 
+    fn read_sysfs_u64(path: &Path) -> Result<u64, DsaError> {
+        let s = read_sysfs_string(path)?;
+        s.parse()
+            .map_err(|_| DsaError::InvalidArgument(format!("invalid u64 in sysfs: {}", s)))
+    }
+
+    /// Determine from sysfs why the work queue behind `dev_path` cannot be used.
+    ///
+    /// Returns `None` if sysfs has no entry for the work queue or reports
+    /// nothing wrong with it.
+    pub fn diagnose_wq(dev_path: &Path) -> Option<WqUnavailableReason> {
+        let name = dev_path.file_name()?.to_str()?;
+        let wq_path = Path::new(SYSFS_DSA_PATH).join(name);
+        if !wq_path.exists() {
+            return None;
+        }
+
+        let state = read_sysfs_string(&wq_path.join("state")).unwrap_or_default();
+        if !state.is_empty() && !WorkQueueState::from(state.as_str()).is_enabled() {
+            return Some(WqUnavailableReason::Disabled { state });
+        }
+
+        let binding = read_wq_binding(&wq_path);
+        if binding.is_kernel() {
+            return Some(WqUnavailableReason::KernelOwned {
+                wq_type: binding.to_string(),
+            });
+        }
+
+        let mode = read_sysfs_string(&wq_path.join("mode")).unwrap_or_default();
+        match mode.as_str() {
+            "dedicated" => {}
+            "shared" => {
+                let device = parse_wq_name(name).ok()?.0;
+                let pasid = read_sysfs_string(
+                    &Path::new(SYSFS_DSA_PATH).join(device).join("pasid_enabled"),
+                );
+                if matches!(pasid.as_deref(), Ok("0")) {
+                    return Some(WqUnavailableReason::NoPasid);
+                }
+            }
+            _ => return Some(WqUnavailableReason::WrongMode { mode }),
+        }
+
+        if !dev_path.exists() {
+            return Some(WqUnavailableReason::MissingDevNode);
+        }
+        None
+    }
+
+    /// Device number of a work queue's character device, from sysfs.
+    ///
+    /// Read from the character device's `dev` attribute, or else from the
+    /// work queue's `cdev_minor` and the `dsa` major in `/proc/devices`.
+    fn wq_dev_number(name: &str) -> Option<libc::dev_t> {
+        let wq_path = Path::new(SYSFS_DSA_PATH).join(name);
+        let dev_attrs = [
+            wq_path.join(name).join("dev"),
+            Path::new(SYSFS_CLASS_DSA_PATH).join(name).join("dev"),
+        ];
+        dev_attrs
+            .iter()
+            .find_map(|path| parse_dev_number(&read_sysfs_string(path).ok()?))
+            .or_else(|| {
+                let minor = read_sysfs_u32(&wq_path.join("cdev_minor")).ok()?;
+                let devices = fs::read_to_string("/proc/devices").ok()?;
+                let major = parse_char_major(&devices, "dsa")?;
+                Some(libc::makedev(major, minor))
+            })
+    }
+
+    /// Resolve the character device node of work queue `name`.
+    pub fn wq_device_node(name: &str) -> PathBuf {
+        let conventional = Path::new(DEV_DSA_PATH).join(name);
+        // IAA work queues share the bus, and the names, but not the directory
+        #[cfg(feature = "iaa")]
+        let conventional = Some(Path::new(DEV_IAX_PATH).join(name))
+            .filter(|path| !conventional.exists() && path.exists())
+            .unwrap_or(conventional);
+        let Some(dev) = wq_dev_number(name) else {
+            return conventional;
+        };
+        let by_number =
+            Path::new("/dev/char").join(format!("{}:{}", libc::major(dev), libc::minor(dev)));
+        let dirs = [
+            Path::new(DEV_DSA_PATH),
+            #[cfg(feature = "iaa")]
+            Path::new(DEV_IAX_PATH),
+            Path::new("/dev"),
+        ];
+        find_char_device(dev, name, &[conventional.clone(), by_number], &dirs)
+            .unwrap_or(conventional)
+    }
+
+    /// Find a character device node with device number `dev`.
+    ///
+    /// `candidates` are tried in order, then the entries of `dirs`, where a
+    /// node named `name` is preferred. Other matches are canonicalized, so
+    /// a `/dev/char` link resolves to the node it points to: work queues
+    /// look up their sysfs attributes by the node's file name.
+    pub(super) fn find_char_device(
+        dev: libc::dev_t,
+        name: &str,
+        candidates: &[PathBuf],
+        dirs: &[&Path],
+    ) -> Option<PathBuf> {
+        use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+        let is_node = |path: &Path| {
+            fs::metadata(path)
+                .is_ok_and(|meta| meta.file_type().is_char_device() && meta.rdev() == dev)
+        };
+        let scanned: Vec<PathBuf> = dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_node(path))
+            .collect();
+
+        let named = |path: &&PathBuf| path.file_name().is_some_and(|file| file == name);
+        let found = candidates
+            .iter()
+            .filter(|path| is_node(path))
+            .chain(&scanned)
+            .find(named)
+            .or_else(|| candidates.iter().find(|path| is_node(path)))
+            .or_else(|| scanned.first())?;
+        Some(fs::canonicalize(found).unwrap_or_else(|_| found.clone()))
+    }
+
     pub fn is_dsa_available() -> bool {
         Path::new(SYSFS_DSA_PATH).exists()
     }
@@ -202,10 +529,10 @@ mod linux_impl {
 // Windows Implementation
 // ============================================================================
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_windows)]
 mod windows_impl {
     use super::*;
-    use crate::wq::WorkQueueType;
+    use crate::wq::{WorkQueueState, WorkQueueType};
     use windows::core::PCWSTR;
     use windows::Win32::Devices::DeviceAndDriverInstallation::{
         SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInfo, SetupDiGetClassDevsW,
@@ -324,12 +651,28 @@ mod windows_impl {
                     devices.push(DsaDevice {
                         name: format!("dsa{}", dsa_count),
                         sysfs_path: PathBuf::from(format!("\\\\.\\DSA{}", dsa_count)),
+                        state: "software".to_string(),
+                        pasid_enabled: false,
+                        max_groups: 1,
+                        max_engines: 1,
+                        max_work_queues: 1,
+                        version: 0,
+                        pci_address: None,
                         work_queues: vec![WorkQueueInfo {
                             name: format!("wq{}.0", dsa_count),
-                            state: "software".to_string(), // Hardware access not available
+                            // Hardware access not available
+                            state: WorkQueueState::Unknown("software".to_string()),
                             wq_type: WorkQueueType::Shared,
                             size: 128,
                             threshold: 64,
+                            block_on_fault: false,
+                            max_batch_size: 1024,
+                            max_transfer_size: u64::from(u32::MAX),
+                            driver_name: String::new(),
+                            clients: 0,
+                            priority: 0,
+                            ats_disable: false,
+                            binding: WorkQueueBinding::Unbound,
                         }],
                     });
 
@@ -368,7 +711,7 @@ mod windows_impl {
 // Unsupported Platform Stub
 // ============================================================================
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_windows)))]
 mod stub_impl {
     use super::*;
 
@@ -406,7 +749,7 @@ mod stub_impl {
 /// # Example
 ///
 /// ```rust,no_run
-/// use intel_dsa::discover_devices;
+/// use dsa_rust::discover_devices;
 ///
 /// let devices = discover_devices()?;
 /// for device in &devices {
@@ -415,53 +758,256 @@ mod stub_impl {
 ///         println!("  Work queue: {} ({})", wq.name, wq.state);
 ///     }
 /// }
-/// # Ok::<(), intel_dsa::DsaError>(())
+/// # Ok::<(), dsa_rust::DsaError>(())
 /// ```
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 pub fn discover_devices() -> Result<Vec<DsaDevice>, DsaError> {
     linux_impl::discover_devices()
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_windows)]
 pub fn discover_devices() -> Result<Vec<DsaDevice>, DsaError> {
     windows_impl::discover_devices()
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_windows)))]
 pub fn discover_devices() -> Result<Vec<DsaDevice>, DsaError> {
     stub_impl::discover_devices()
 }
 
+/// Discover all IAA (In-Memory Analytics Accelerator) devices.
+///
+/// IAA devices (`iax0`, `iax1`, ...) sit on the same bus as DSA devices and
+/// their work queues are opened the same way, through
+/// [`DsaDevice::open_first_wq`] and friends.
+///
+/// # Errors
+///
+/// Returns `DsaError::PlatformNotSupported` if the accelerator bus is
+/// absent or on platforms other than Linux.
+#[cfg(all(feature = "iaa", dsa_portal))]
+pub fn discover_iaa_devices() -> Result<Vec<DsaDevice>, DsaError> {
+    linux_impl::discover_iaa_devices()
+}
+
+#[cfg(all(feature = "iaa", not(dsa_portal)))]
+pub fn discover_iaa_devices() -> Result<Vec<DsaDevice>, DsaError> {
+    Err(DsaError::PlatformNotSupported)
+}
+
+/// Parse a sysfs hex attribute such as `"0x100"`.
+fn parse_hex_u32(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Parse and normalize a PCI address of the form `DDDD:BB:DD.F` or
+/// `BB:DD.F`.
+///
+/// # Returns
+///
+/// The address in the lowercase, zero-padded form sysfs uses, with the
+/// domain defaulting to 0 (e.g., "6A:1.0" becomes "0000:6a:01.0").
+///
+/// # Errors
+///
+/// Returns `DsaError::InvalidArgument` if the address is malformed.
+pub fn parse_pci_address(address: &str) -> Result<String, DsaError> {
+    let invalid = || DsaError::InvalidArgument(format!("invalid PCI address: {}", address));
+    let hex = |field: &str, digits: usize, max: u32| {
+        u32::from_str_radix(field, 16)
+            .ok()
+            .filter(|&value| !field.is_empty() && field.len() <= digits && value <= max)
+            .ok_or_else(invalid)
+    };
+
+    let (rest, function) = address.trim().rsplit_once('.').ok_or_else(invalid)?;
+    let fields: Vec<&str> = rest.split(':').collect();
+    let (domain, bus, device) = match fields[..] {
+        [domain, bus, device] => (hex(domain, 4, 0xFFFF)?, bus, device),
+        [bus, device] => (0, bus, device),
+        _ => return Err(invalid()),
+    };
+    Ok(format!(
+        "{:04x}:{:02x}:{:02x}.{:x}",
+        domain,
+        hex(bus, 2, 0xFF)?,
+        hex(device, 2, 0x1F)?,
+        hex(function, 1, 7)?
+    ))
+}
+
+/// Parse a `major:minor` device number, as in sysfs `dev` attributes.
+#[cfg(dsa_portal)]
+fn parse_dev_number(s: &str) -> Option<libc::dev_t> {
+    let (major, minor) = s.trim().split_once(':')?;
+    Some(libc::makedev(major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Find the major number of character device driver `driver` in the
+/// contents of `/proc/devices`.
+fn parse_char_major(devices: &str, driver: &str) -> Option<u32> {
+    devices
+        .lines()
+        .skip_while(|line| line.trim() != "Character devices:")
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .find_map(|line| {
+            let (major, name) = line.trim().split_once(' ')?;
+            if name.trim() == driver {
+                major.parse().ok()
+            } else {
+                None
+            }
+        })
+}
+
+/// Parse a work queue name of the form `wqD.Q` or `dsaD/wqD.Q`.
+///
+/// Returns the device name (derived from the work queue name if not given)
+/// and the work queue name.
+///
+/// # Errors
+///
+/// Returns `DsaError::InvalidArgument` if the name is malformed or the work
+/// queue does not belong to the named device.
+pub fn parse_wq_name(name: &str) -> Result<(String, &str), DsaError> {
+    let invalid = || DsaError::InvalidArgument(format!("invalid work queue name: {}", name));
+
+    let (device, wq) = match name.split_once('/') {
+        Some((device, wq)) => (Some(device), wq),
+        None => (None, name),
+    };
+
+    let (device_num, queue_num) = wq
+        .strip_prefix("wq")
+        .and_then(|rest| rest.split_once('.'))
+        .ok_or_else(invalid)?;
+    if device_num.parse::<u32>().is_err() || queue_num.parse::<u32>().is_err() {
+        return Err(invalid());
+    }
+
+    let derived = format!("dsa{}", device_num);
+    match device {
+        Some(device) if device != derived => Err(invalid()),
+        _ => Ok((derived, wq)),
+    }
+}
+
+/// Open an enabled work queue by name (`wq0.2` or `dsa1/wq1.0`).
+///
+/// The name is resolved against the devices found by [`discover_devices`],
+/// the work queue state is validated, and the submission type is taken from
+/// its sysfs `mode`.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The name is malformed
+/// - No device or work queue with that name exists
+/// - The work queue is not enabled
+pub fn open_named_wq(name: &str) -> Result<WorkQueue, DsaError> {
+    let (device_name, wq_name) = parse_wq_name(name)?;
+    let device = discover_devices()?
+        .into_iter()
+        .find(|device| device.name == device_name)
+        .ok_or(DsaError::NoDeviceFound)?;
+    device.open_enabled_wq(wq_name)
+}
+
+/// Resolve the character device node of a work queue.
+///
+/// Reads the work queue's device number from sysfs and looks for the node
+/// with that number: at the conventional `/dev/dsa/<name>`, through
+/// `/dev/char`, then in `/dev/dsa` and `/dev`. This finds nodes placed by
+/// custom udev rules or bind-mounted into containers under another path.
+/// With the `iaa` feature, IAA work queues are also looked for in `/dev/iax`.
+///
+/// # Arguments
+///
+/// * `name` - Work queue name (e.g., "wq0.0")
+///
+/// # Returns
+///
+/// The node found, or `/dev/dsa/<name>` if the device number is unknown or
+/// no node has it.
+#[cfg(dsa_portal)]
+pub fn wq_device_node(name: &str) -> PathBuf {
+    linux_impl::wq_device_node(name)
+}
+
+/// Determine from sysfs why a work queue device cannot be opened.
+///
+/// # Arguments
+///
+/// * `dev_path` - Path to the work queue device (e.g., `/dev/dsa/wq0.0`)
+///
+/// # Returns
+///
+/// `None` if no problem could be identified.
+#[cfg(dsa_portal)]
+pub fn diagnose_wq(dev_path: &Path) -> Option<WqUnavailableReason> {
+    linux_impl::diagnose_wq(dev_path)
+}
+
+#[cfg(not(dsa_portal))]
+pub fn diagnose_wq(_dev_path: &std::path::Path) -> Option<WqUnavailableReason> {
+    None
+}
+
+/// Read which driver a work queue is bound to from sysfs.
+///
+/// # Arguments
+///
+/// * `name` - Work queue name (e.g., "wq0.0")
+///
+/// # Returns
+///
+/// `None` if sysfs has no entry for the work queue.
+#[cfg(dsa_portal)]
+pub fn wq_binding(name: &str) -> Option<WorkQueueBinding> {
+    linux_impl::wq_binding(name)
+}
+
+#[cfg(not(dsa_portal))]
+pub fn wq_binding(_name: &str) -> Option<WorkQueueBinding> {
+    None
+}
+
 /// Check if DSA is available on this system.
 ///
 /// This performs a quick check without full device enumeration.
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 pub fn is_dsa_available() -> bool {
     linux_impl::is_dsa_available()
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_windows)]
 pub fn is_dsa_available() -> bool {
     windows_impl::is_dsa_available()
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_windows)))]
 pub fn is_dsa_available() -> bool {
     stub_impl::is_dsa_available()
 }
 
 /// Check if DSA devices are configured and ready to use.
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 pub fn is_dsa_configured() -> bool {
     linux_impl::is_dsa_configured()
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_windows)]
 pub fn is_dsa_configured() -> bool {
     windows_impl::is_dsa_configured()
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_windows)))]
 pub fn is_dsa_configured() -> bool {
     stub_impl::is_dsa_configured()
 }
@@ -482,6 +1028,142 @@ mod tests {
         let _ = is_dsa_configured();
     }
 
+    #[test]
+    fn test_parse_wq_name() {
+        assert_eq!(
+            parse_wq_name("wq0.2").unwrap(),
+            ("dsa0".to_string(), "wq0.2")
+        );
+        assert_eq!(
+            parse_wq_name("dsa1/wq1.0").unwrap(),
+            ("dsa1".to_string(), "wq1.0")
+        );
+        assert!(parse_wq_name("dsa0/wq1.0").is_err());
+        assert!(parse_wq_name("wq0").is_err());
+        assert!(parse_wq_name("wqa.b").is_err());
+        assert!(parse_wq_name("/dev/dsa/wq0.0").is_err());
+    }
+
+    #[test]
+    fn test_parse_pci_address() {
+        assert_eq!(parse_pci_address("0000:6a:01.0").unwrap(), "0000:6a:01.0");
+        assert_eq!(parse_pci_address("6A:1.0").unwrap(), "0000:6a:01.0");
+        assert_eq!(parse_pci_address(" 1:e7:02.7\n").unwrap(), "0001:e7:02.7");
+        assert!(parse_pci_address("0000:6a:01").is_err());
+        assert!(parse_pci_address("0000:6a:20.0").is_err());
+        assert!(parse_pci_address("0000:6a:01.8").is_err());
+        assert!(parse_pci_address("0:0:6a:01.0").is_err());
+        assert!(parse_pci_address("dsa0").is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_pci_address_of() {
+        let root = std::env::temp_dir().join(format!("dsa-pci-{}", std::process::id()));
+        let device_dir = root.join("pci0000:6a/0000:6a:01.0/dsa0");
+        fs::create_dir_all(&device_dir).unwrap();
+        fs::create_dir_all(root.join("bus")).unwrap();
+        std::os::unix::fs::symlink(&device_dir, root.join("bus/dsa0")).unwrap();
+
+        assert_eq!(
+            linux_impl::pci_address_of(&root.join("bus/dsa0")).as_deref(),
+            Some("0000:6a:01.0")
+        );
+        // A device not under a PCI function has no address
+        assert_eq!(linux_impl::pci_address_of(&root.join("bus")), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_read_wq_binding() {
+        let root = std::env::temp_dir().join(format!("dsa-binding-{}", std::process::id()));
+        let drivers = root.join("drivers");
+        for driver in ["user", "dmaengine", "crypto"] {
+            fs::create_dir_all(drivers.join(driver)).unwrap();
+        }
+        let wq = |name: &str, driver: Option<&str>, state: &str, wq_type: Option<&str>| {
+            let path = root.join(name);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("state"), format!("{state}\n")).unwrap();
+            if let Some(driver) = driver {
+                std::os::unix::fs::symlink(drivers.join(driver), path.join("driver")).unwrap();
+            }
+            if let Some(wq_type) = wq_type {
+                fs::write(path.join("type"), format!("{wq_type}\n")).unwrap();
+            }
+            linux_impl::read_wq_binding(&path)
+        };
+
+        assert_eq!(
+            wq("wq0.0", Some("user"), "enabled", None),
+            WorkQueueBinding::User
+        );
+        assert_eq!(
+            wq("wq0.1", Some("dmaengine"), "enabled", Some("kernel")),
+            WorkQueueBinding::DmaEngine
+        );
+        assert_eq!(
+            wq("wq0.2", Some("crypto"), "enabled", None),
+            WorkQueueBinding::Other("crypto".to_string())
+        );
+        assert_eq!(
+            wq("wq0.3", None, "disabled", Some("user")),
+            WorkQueueBinding::Unbound
+        );
+        // Kernels without per-type drivers report the type
+        assert_eq!(
+            wq("wq0.4", None, "enabled", Some("kernel")),
+            WorkQueueBinding::DmaEngine
+        );
+        assert_eq!(
+            wq("wq0.5", None, "enabled", Some("user")),
+            WorkQueueBinding::User
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_char_major() {
+        let devices = "Character devices:\n  1 mem\n237 dsa\n238 idxd\n\nBlock devices:\n  8 dsa\n";
+        assert_eq!(parse_char_major(devices, "dsa"), Some(237));
+        assert_eq!(parse_char_major(devices, "idxd"), Some(238));
+        assert_eq!(parse_char_major(devices, "iax"), None);
+        assert_eq!(parse_char_major("", "dsa"), None);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_find_char_device() {
+        assert_eq!(parse_dev_number("237:5\n"), Some(libc::makedev(237, 5)));
+        assert_eq!(parse_dev_number("237"), None);
+
+        // /dev/null is 1:3 on Linux; it is found under its own name
+        let null = libc::makedev(1, 3);
+        let dev = Path::new("/dev");
+        assert_eq!(
+            linux_impl::find_char_device(null, "null", &[], &[dev]),
+            Some(PathBuf::from("/dev/null"))
+        );
+        // A candidate path with the number wins over scanned entries
+        assert_eq!(
+            linux_impl::find_char_device(null, "wq0.0", &[PathBuf::from("/dev/null")], &[]),
+            Some(PathBuf::from("/dev/null"))
+        );
+        assert_eq!(
+            linux_impl::find_char_device(libc::makedev(4095, 4095), "wq0.0", &[], &[dev]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_u32() {
+        assert_eq!(parse_hex_u32("0x100\n"), Some(0x100));
+        assert_eq!(parse_hex_u32("0X1a"), Some(0x1a));
+        assert_eq!(parse_hex_u32("200"), Some(0x200));
+        assert_eq!(parse_hex_u32("0xzz"), None);
+    }
+
     #[test]
     fn test_discover_on_non_dsa_system() {
         let result = discover_devices();
@@ -0,0 +1,740 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Software emulation of a DSA work queue.
+//!
+//! The [`Emulator`] executes descriptors in software and writes completion
+//! records the way the hardware does, so the full descriptor path (batching,
+//! completion polling, error mapping) can run without an accelerator. Faults
+//! can be injected to make it fail deterministically, which lets
+//! applications test their recovery paths in CI.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dsa_rust::emulator::{Emulator, Fault};
+//! use dsa_rust::{DsaEngine, DsaError};
+//! use std::sync::Arc;
+//!
+//! let emulator = Arc::new(Emulator::new());
+//! # #[cfg(dsa_portal)]
+//! # {
+//! let engine = DsaEngine::emulated(Arc::clone(&emulator))?;
+//!
+//! emulator.inject(Fault::PageFault { offset: 100 });
+//! let mut dst = vec![0u8; 4096];
+//! let err = engine.memcpy(&mut dst, &[1u8; 4096]).unwrap_err();
+//! assert_eq!(err.bytes_completed(), Some(100));
+//! # }
+//! # Ok::<(), DsaError>(())
+//! ```
+
+use crate::crc::software_crc32;
+use crate::delta;
+use crate::descriptor::{
+    DescriptorFlags, DifTags, DsaCompletionRecord, DsaHwDesc, DELTA_RECORD_FULL, DIF_ERROR,
+};
+use crate::error::DsaError;
+use crate::opcode::DsaOpcode;
+use crate::t10pi;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Completion status written on success.
+const STATUS_SUCCESS: u8 = 0x01;
+/// Completion status written when a page fault stops an operation.
+const STATUS_PAGE_FAULT: u8 = 0x03;
+/// Completion status written when a descriptor in a batch failed.
+const STATUS_BATCH_FAIL: u8 = 0x05;
+/// Completion status written for invalid descriptor flags.
+const STATUS_INVALID_FLAGS: u8 = 0x10;
+/// Completion status written for unsupported opcodes.
+const STATUS_UNSUPPORTED_OP: u8 = 0x11;
+/// Completion status written for invalid batch, delta or DIF sizes.
+const STATUS_INVALID_SIZE: u8 = 0x13;
+/// Completion status written for a misaligned batch descriptor list.
+const STATUS_INVALID_LIST_ADDR: u8 = 0x18;
+
+/// A failure to inject into the emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Reject the next `count` submissions as if the queue were full.
+    QueueFull { count: u32 },
+    /// Stop the next work descriptor with a page fault after `offset` bytes.
+    ///
+    /// Bytes before the fault are processed, as on hardware. A descriptor
+    /// with `DescriptorFlags::BLOCK_ON_FAULT` waits for the fault to be
+    /// resolved and completes normally.
+    PageFault { offset: u32 },
+    /// Complete the next work descriptor with an invalid flags status.
+    InvalidFlags,
+    /// Fail the descriptor at `index` of the next batch with an invalid
+    /// flags status; the other descriptors in the batch still complete.
+    BatchFailure { index: u32 },
+    /// Accept the next work descriptor but never complete it, as a wedged
+    /// device would; a batch containing it never completes either.
+    Stall,
+}
+
+/// Faults waiting to be triggered.
+#[derive(Debug, Default)]
+struct Faults {
+    /// Remaining submissions to reject.
+    queue_full: u32,
+    /// Faults for upcoming work descriptors, in order.
+    descriptor: VecDeque<Fault>,
+    /// Failing indices for upcoming batches, in order.
+    batch: VecDeque<u32>,
+}
+
+/// Software DSA work queue with fault injection.
+///
+/// Descriptors complete synchronously during submission. Supported
+/// operations are Noop, Batch, Drain, MemMove, MemFill, Compare, CompareImm,
+/// CreateDelta, ApplyDelta, CrcGen, CopyCrc and the four DIF operations, and
+/// with the `iaa` feature the IAA CRC64, Compress and Decompress operations;
+/// other opcodes complete with an unsupported-operation status.
+#[derive(Debug, Default)]
+pub struct Emulator {
+    faults: Mutex<Faults>,
+    submitted: AtomicU64,
+}
+
+impl Emulator {
+    /// Opcodes the emulator executes.
+    pub const SUPPORTED_OPS: [DsaOpcode; 16] = [
+        DsaOpcode::Noop,
+        DsaOpcode::Batch,
+        DsaOpcode::Drain,
+        DsaOpcode::MemMove,
+        DsaOpcode::TranslFetch,
+        DsaOpcode::MemFill,
+        DsaOpcode::Compare,
+        DsaOpcode::CompareImm,
+        DsaOpcode::CreateDelta,
+        DsaOpcode::ApplyDelta,
+        DsaOpcode::CrcGen,
+        DsaOpcode::CopyCrc,
+        DsaOpcode::DifCheck,
+        DsaOpcode::DifInsert,
+        DsaOpcode::DifStrip,
+        DsaOpcode::DifUpdate,
+    ];
+
+    /// Create an emulator with no faults configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arm a fault. Faults of the same kind trigger in the order injected.
+    pub fn inject(&self, fault: Fault) {
+        let mut faults = self.faults.lock().unwrap();
+        match fault {
+            Fault::QueueFull { count } => faults.queue_full += count,
+            Fault::PageFault { .. } | Fault::InvalidFlags | Fault::Stall => {
+                faults.descriptor.push_back(fault)
+            }
+            Fault::BatchFailure { index } => faults.batch.push_back(index),
+        }
+    }
+
+    /// Disarm all faults that have not triggered yet.
+    pub fn clear_faults(&self) {
+        *self.faults.lock().unwrap() = Faults::default();
+    }
+
+    /// Number of descriptors accepted so far (batches count as one).
+    pub fn submitted(&self) -> u64 {
+        self.submitted.load(Ordering::Relaxed)
+    }
+
+    /// Execute a descriptor and write its completion record.
+    ///
+    /// # Safety
+    ///
+    /// All addresses in `desc` (and in any batched descriptors) must be
+    /// valid for the accesses the operation performs, as for hardware.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::QueueFull` when an injected queue-full fault
+    /// triggers, or `DsaError::InvalidArgument` if the completion record
+    /// address is not 32-byte aligned or is missing although a completion
+    /// was requested.
+    pub unsafe fn submit(&self, desc: &DsaHwDesc) -> Result<(), DsaError> {
+        {
+            let mut faults = self.faults.lock().unwrap();
+            if faults.queue_full > 0 {
+                faults.queue_full -= 1;
+                return Err(DsaError::QueueFull {
+                    attempts: 1,
+                    elapsed: Duration::ZERO,
+                    threshold: None,
+                    occupancy: None,
+                });
+            }
+        }
+        if let Some(problem) = record_error(desc) {
+            return Err(DsaError::InvalidArgument(problem));
+        }
+
+        self.submitted.fetch_add(1, Ordering::Relaxed);
+        if desc.opcode() == DsaOpcode::Batch as u8 {
+            self.run_batch(desc);
+        } else {
+            let fault = self.faults.lock().unwrap().descriptor.pop_front();
+            if !matches!(fault, Some(Fault::Stall)) {
+                complete(desc, run(desc, fault));
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute a batch descriptor's list and complete the batch record.
+    unsafe fn run_batch(&self, desc: &DsaHwDesc) {
+        let count = desc.xfer_size as usize;
+        let status = if has_invalid_flags(desc) {
+            STATUS_INVALID_FLAGS
+        } else if count < 2 {
+            STATUS_INVALID_SIZE
+        } else if !desc.src_addr.is_multiple_of(64) {
+            STATUS_INVALID_LIST_ADDR
+        } else {
+            STATUS_SUCCESS
+        };
+        if status != STATUS_SUCCESS {
+            complete(desc, Outcome::status(status));
+            return;
+        }
+
+        let failing = self.faults.lock().unwrap().batch.pop_front();
+        let descs = std::slice::from_raw_parts(desc.src_addr as *const DsaHwDesc, count);
+        let mut succeeded = 0;
+        for (i, sub) in descs.iter().enumerate() {
+            // A descriptor whose record cannot be written fails unreported
+            if record_error(sub).is_some() {
+                continue;
+            }
+            // Nested batches are invalid
+            let outcome = if sub.opcode() == DsaOpcode::Batch as u8 || failing == Some(i as u32) {
+                Outcome::status(STATUS_INVALID_FLAGS)
+            } else {
+                let fault = self.faults.lock().unwrap().descriptor.pop_front();
+                if matches!(fault, Some(Fault::Stall)) {
+                    return;
+                }
+                run(sub, fault)
+            };
+            if outcome.status == STATUS_SUCCESS {
+                succeeded += 1;
+            }
+            complete(sub, outcome);
+        }
+
+        let mut outcome = Outcome::status(if succeeded == count {
+            STATUS_SUCCESS
+        } else {
+            STATUS_BATCH_FAIL
+        });
+        outcome.bytes_completed = succeeded as u32;
+        complete(desc, outcome);
+    }
+}
+
+/// Result of executing one descriptor.
+#[derive(Debug, Default)]
+struct Outcome {
+    status: u8,
+    result: u8,
+    bytes_completed: u32,
+    fault_addr: u64,
+    result_value: u64,
+    result_value2: u64,
+    /// CRC of an IAA operation, at bytes 32-39 of its record.
+    iaa_crc: Option<u64>,
+}
+
+impl Outcome {
+    fn status(status: u8) -> Self {
+        Self {
+            status,
+            ..Self::default()
+        }
+    }
+
+    fn success() -> Self {
+        Self::status(STATUS_SUCCESS)
+    }
+}
+
+/// Execute a work descriptor, honoring an injected fault.
+unsafe fn run(desc: &DsaHwDesc, fault: Option<Fault>) -> Outcome {
+    // The device resolves the faults of descriptors that block on them
+    let fault = match fault {
+        Some(Fault::PageFault { .. })
+            if desc.flags_opcode & DescriptorFlags::BLOCK_ON_FAULT.bits() != 0 =>
+        {
+            None
+        }
+        fault => fault,
+    };
+    let len = desc.xfer_size as usize;
+    let limit = match fault {
+        Some(Fault::InvalidFlags) => return Outcome::status(STATUS_INVALID_FLAGS),
+        _ if has_invalid_flags(desc) => return Outcome::status(STATUS_INVALID_FLAGS),
+        Some(Fault::PageFault { offset }) => (offset as usize).min(len),
+        _ => len,
+    };
+
+    #[cfg(feature = "iaa")]
+    if let Ok(op) = crate::iaa::IaaOpcode::try_from(desc.opcode()) {
+        if limit < len {
+            return page_fault(desc, limit);
+        }
+        let (status, result, output_size, crc) = crate::iaa::emulate(desc, op);
+        return Outcome {
+            result,
+            result_value2: output_size as u64,
+            iaa_crc: Some(crc),
+            ..Outcome::status(status)
+        };
+    }
+
+    let mut outcome = match DsaOpcode::try_from(desc.opcode()) {
+        // Emulated operations share the CPU's translations
+        Ok(DsaOpcode::Noop | DsaOpcode::Drain | DsaOpcode::TranslFetch) => Outcome::success(),
+        Ok(DsaOpcode::MemMove) => {
+            std::ptr::copy(desc.src_addr as *const u8, desc.dst_addr as *mut u8, limit);
+            Outcome::success()
+        }
+        Ok(DsaOpcode::MemFill) => {
+            let pattern = desc.src_addr.to_le_bytes();
+            let dst = std::slice::from_raw_parts_mut(desc.dst_addr as *mut u8, limit);
+            for (i, byte) in dst.iter_mut().enumerate() {
+                *byte = pattern[i % 8];
+            }
+            Outcome::success()
+        }
+        Ok(DsaOpcode::Compare) => {
+            let a = std::slice::from_raw_parts(desc.src_addr as *const u8, limit);
+            let b = std::slice::from_raw_parts(desc.dst_addr as *const u8, limit);
+            compare_outcome(a.iter().zip(b).position(|(x, y)| x != y))
+        }
+        Ok(DsaOpcode::CompareImm) => {
+            let pattern = desc.dst_addr.to_le_bytes();
+            let a = std::slice::from_raw_parts(desc.src_addr as *const u8, limit);
+            compare_outcome(a.iter().enumerate().position(|(i, &x)| x != pattern[i % 8]))
+        }
+        Ok(DsaOpcode::CrcGen) => {
+            let data = std::slice::from_raw_parts(desc.src_addr as *const u8, limit);
+            Outcome {
+                result_value: software_crc32(data, desc.as_crc().crc_seed) as u64,
+                ..Outcome::success()
+            }
+        }
+        Ok(DsaOpcode::CopyCrc) => {
+            std::ptr::copy(desc.src_addr as *const u8, desc.dst_addr as *mut u8, limit);
+            let data = std::slice::from_raw_parts(desc.dst_addr as *const u8, limit);
+            Outcome {
+                result_value: software_crc32(data, desc.as_crc().crc_seed) as u64,
+                ..Outcome::success()
+            }
+        }
+        Ok(DsaOpcode::CreateDelta) => return create_delta(desc, limit),
+        Ok(DsaOpcode::ApplyDelta) => return apply_delta(desc, fault),
+        Ok(
+            op @ (DsaOpcode::DifCheck
+            | DsaOpcode::DifInsert
+            | DsaOpcode::DifStrip
+            | DsaOpcode::DifUpdate),
+        ) => return dif(desc, op, limit),
+        _ => return Outcome::status(STATUS_UNSUPPORTED_OP),
+    };
+
+    // A mismatch before the fault point ends the compare without faulting
+    if limit < len && outcome.result == 0 {
+        outcome = page_fault(desc, limit);
+    }
+    outcome
+}
+
+/// Execute a CreateDelta descriptor over its first `limit` bytes.
+unsafe fn create_delta(desc: &DsaHwDesc, limit: usize) -> Outcome {
+    let len = desc.xfer_size as usize;
+    if !len.is_multiple_of(8) || len > delta::MAX_LEN {
+        return Outcome::status(STATUS_INVALID_SIZE);
+    }
+    // A fault stops the operation at the faulting word
+    let limit = limit & !7;
+    let original = std::slice::from_raw_parts(desc.src_addr as *const u8, limit);
+    let modified = std::slice::from_raw_parts(desc.dst_addr as *const u8, limit);
+    let fields = desc.as_delta();
+    let record = std::slice::from_raw_parts_mut(
+        fields.delta_addr as *mut u8,
+        fields.max_delta_size as usize,
+    );
+    if limit < len {
+        return page_fault(desc, limit);
+    }
+    match delta::encode(original, modified, record) {
+        Some(size) => Outcome {
+            result: (size > 0) as u8,
+            result_value: size as u64,
+            ..Outcome::success()
+        },
+        None => Outcome {
+            result: DELTA_RECORD_FULL,
+            ..Outcome::success()
+        },
+    }
+}
+
+/// Execute an ApplyDelta descriptor, stopping at an injected page fault in
+/// its delta record.
+unsafe fn apply_delta(desc: &DsaHwDesc, fault: Option<Fault>) -> Outcome {
+    let size = desc.as_apply_delta().delta_rec_size as usize;
+    let record = std::slice::from_raw_parts(desc.src_addr as *const u8, size);
+    let dst_len = desc.xfer_size as usize;
+    if delta::check_entries(record, dst_len).is_err() {
+        return Outcome::status(STATUS_INVALID_SIZE);
+    }
+    let limit = match fault {
+        Some(Fault::PageFault { offset }) => (offset as usize).min(size),
+        _ => size,
+    };
+    let limit = limit - limit % delta::ENTRY_SIZE;
+    let dst = std::slice::from_raw_parts_mut(desc.dst_addr as *mut u8, dst_len);
+    delta::decode(&record[..limit], dst);
+    if limit < size {
+        return page_fault(desc, limit);
+    }
+    Outcome::success()
+}
+
+/// Execute a DIF descriptor over the whole source blocks in its first
+/// `limit` bytes.
+unsafe fn dif(desc: &DsaHwDesc, op: DsaOpcode, limit: usize) -> Outcome {
+    let fields = desc.as_dif();
+    let block = crate::descriptor::dif_block_size(fields.dif_flags);
+    let protected = block + t10pi::DIF_SIZE;
+    let src_block = desc.dif_src_block_size().unwrap_or(protected);
+    let len = desc.xfer_size as usize;
+    if !len.is_multiple_of(src_block) {
+        return Outcome::status(STATUS_INVALID_SIZE);
+    }
+    // A fault stops the operation at the faulting block
+    let blocks = limit / src_block;
+    let limit = blocks * src_block;
+
+    let src = std::slice::from_raw_parts(desc.src_addr as *const u8, limit);
+    let dst = |block_size: usize| {
+        std::slice::from_raw_parts_mut(desc.dst_addr as *mut u8, blocks * block_size)
+    };
+    let result = match op {
+        DsaOpcode::DifInsert => Ok((
+            DifTags::default(),
+            t10pi::insert(dst(protected), src, block, fields.dest_tags()),
+        )),
+        DsaOpcode::DifCheck => t10pi::check(src, block, fields.src_tags(), |_, _, _| {})
+            .map(|tags| (tags, DifTags::default())),
+        DsaOpcode::DifStrip => t10pi::strip(dst(block), src, block, fields.src_tags())
+            .map(|tags| (tags, DifTags::default())),
+        _ => t10pi::update(
+            dst(protected),
+            src,
+            block,
+            fields.src_tags(),
+            fields.dest_tags(),
+        ),
+    };
+
+    match result {
+        Err(failure) => Outcome {
+            result: failure.status.bits(),
+            bytes_completed: failure.bytes_completed as u32,
+            result_value: failure.tags.to_word(),
+            ..Outcome::status(DIF_ERROR)
+        },
+        Ok(_) if limit < len => page_fault(desc, limit),
+        Ok((src_tags, dest_tags)) => Outcome {
+            result_value: src_tags.to_word(),
+            result_value2: dest_tags.to_word(),
+            ..Outcome::success()
+        },
+    }
+}
+
+/// Outcome of a descriptor stopped by a page fault after `offset` bytes.
+fn page_fault(desc: &DsaHwDesc, offset: usize) -> Outcome {
+    Outcome {
+        bytes_completed: offset as u32,
+        fault_addr: desc.src_addr.wrapping_add(offset as u64),
+        ..Outcome::status(STATUS_PAGE_FAULT)
+    }
+}
+
+/// Describe why the descriptor's completion record cannot be written.
+pub(crate) fn record_error(desc: &DsaHwDesc) -> Option<String> {
+    let requested = desc.flags_opcode & DescriptorFlags::REQUEST_COMPLETION.bits() != 0;
+    if requested && desc.completion_addr == 0 {
+        Some("completion requested without a completion record".to_string())
+    } else if !desc.completion_addr.is_multiple_of(32) {
+        Some(format!(
+            "completion record address {:#x} is not 32-byte aligned",
+            desc.completion_addr
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns true if flag bits the hardware does not define, mutually
+/// exclusive flags, or flags the descriptor's privilege level does not
+/// allow are set.
+fn has_invalid_flags(desc: &DsaHwDesc) -> bool {
+    let flags = desc.flags_opcode & 0x00FFFFFF;
+    flags & !DescriptorFlags::all().bits() != 0
+        || DescriptorFlags::from_bits_truncate(flags)
+            .conflict()
+            .is_some()
+        || (flags & DescriptorFlags::ADDR_TRANSLATED.bits() != 0 && !desc.is_privileged())
+}
+
+fn compare_outcome(mismatch: Option<usize>) -> Outcome {
+    match mismatch {
+        Some(offset) => Outcome {
+            result: 1,
+            bytes_completed: offset as u32,
+            ..Outcome::success()
+        },
+        None => Outcome::success(),
+    }
+}
+
+/// Write `outcome` to the descriptor's completion record, status last.
+unsafe fn complete(desc: &DsaHwDesc, outcome: Outcome) {
+    let record = desc.completion_addr as *mut DsaCompletionRecord;
+    if record.is_null() {
+        return;
+    }
+    (*record).result = outcome.result;
+    (*record).bytes_completed = outcome.bytes_completed;
+    (*record).fault_addr = outcome.fault_addr;
+    (*record).result_value = outcome.result_value;
+    (*record).result_value2 = outcome.result_value2;
+    if let Some(crc) = outcome.iaa_crc {
+        std::ptr::write((record as *mut u8).add(32) as *mut u64, crc);
+    }
+    std::sync::atomic::fence(Ordering::Release);
+    std::ptr::write_volatile(&mut (*record).status, outcome.status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submit(emulator: &Emulator, desc: &DsaHwDesc) {
+        unsafe { emulator.submit(desc).unwrap() };
+    }
+
+    #[test]
+    fn test_mem_move_and_crc() {
+        let emulator = Emulator::new();
+        let src: Vec<u8> = (0..=255).collect();
+        let mut dst = vec![0u8; 256];
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), 256, &mut record);
+        submit(&emulator, &desc);
+        assert!(record.get_status().is_success());
+        assert_eq!(src, dst);
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::crc_gen(src.as_ptr(), src.len(), 0, &mut record);
+        submit(&emulator, &desc);
+        assert_eq!(record.crc32_result(), crate::crc::software_crc32(&src, 0));
+        assert_eq!(emulator.submitted(), 2);
+    }
+
+    #[test]
+    fn test_compare_mismatch() {
+        let emulator = Emulator::new();
+        let a = [7u8; 64];
+        let mut b = a;
+        b[40] = 0;
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), 64, &mut record);
+        submit(&emulator, &desc);
+        assert!(record.get_status().is_success());
+        assert_eq!(record.mismatch_offset(), Some(40));
+    }
+
+    #[test]
+    fn test_injected_page_fault() {
+        let emulator = Emulator::new();
+        emulator.inject(Fault::PageFault { offset: 10 });
+        let mut dst = [0u8; 32];
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::mem_fill(dst.as_mut_ptr(), 32, u64::MAX, &mut record);
+        submit(&emulator, &desc);
+        assert_eq!(record.status, STATUS_PAGE_FAULT);
+        assert_eq!(record.bytes_completed, 10);
+        assert!(dst[..10].iter().all(|&b| b == 0xFF));
+        assert!(dst[10..].iter().all(|&b| b == 0));
+
+        // The fault triggers once
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::mem_fill(dst.as_mut_ptr(), 32, u64::MAX, &mut record);
+        submit(&emulator, &desc);
+        assert!(record.get_status().is_success());
+    }
+
+    #[test]
+    fn test_dif_insert_and_strip() {
+        let emulator = Emulator::new();
+        let tags = DifTags {
+            ref_tag: 5,
+            ..DifTags::default()
+        };
+        let data = [0x3Cu8; 2 * 512];
+        let mut protected = [0u8; 2 * 520];
+
+        // A fault stops the insert at the faulting block
+        emulator.inject(Fault::PageFault { offset: 700 });
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::dif_insert(
+            data.as_ptr(),
+            protected.as_mut_ptr(),
+            data.len(),
+            0,
+            tags,
+            &mut record,
+        );
+        submit(&emulator, &desc);
+        assert_eq!(record.status, STATUS_PAGE_FAULT);
+        assert_eq!(record.bytes_completed, 512);
+
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::dif_insert(
+            data.as_ptr(),
+            protected.as_mut_ptr(),
+            data.len(),
+            0,
+            tags,
+            &mut record,
+        );
+        submit(&emulator, &desc);
+        assert!(record.get_status().is_success());
+        assert_eq!(record.dif_insert_tags().ref_tag, 7);
+
+        protected[520 + 516..].copy_from_slice(&9u32.to_be_bytes());
+        let mut stripped = [0u8; 2 * 512];
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::dif_strip(
+            protected.as_ptr(),
+            stripped.as_mut_ptr(),
+            protected.len(),
+            0,
+            tags,
+            &mut record,
+        );
+        submit(&emulator, &desc);
+        assert_eq!(record.dif_status(), crate::DifStatus::REF_TAG_MISMATCH);
+        assert_eq!(record.dif_failed_block(520), Some(1));
+        assert_eq!(record.dif_check_tags().ref_tag, 6);
+        assert_eq!(stripped[..512], data[..512]);
+    }
+
+    #[test]
+    fn test_injected_queue_full() {
+        let emulator = Emulator::new();
+        emulator.inject(Fault::QueueFull { count: 2 });
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::noop(&mut record);
+
+        for _ in 0..2 {
+            let err = unsafe { emulator.submit(&desc) }.unwrap_err();
+            assert!(err.is_retryable());
+        }
+        submit(&emulator, &desc);
+        assert_eq!(emulator.submitted(), 1);
+    }
+
+    #[test]
+    fn test_injected_batch_failure() {
+        let emulator = Emulator::new();
+        emulator.inject(Fault::BatchFailure { index: 1 });
+
+        let mut records = [DsaCompletionRecord::new(); 3];
+        let descs: Vec<DsaHwDesc> = records.iter_mut().map(DsaHwDesc::noop).collect();
+        let mut batch_record = DsaCompletionRecord::new();
+        let batch = DsaHwDesc::batch(descs.as_ptr(), descs.len(), &mut batch_record);
+        submit(&emulator, &batch);
+
+        assert_eq!(batch_record.status, STATUS_BATCH_FAIL);
+        assert_eq!(batch_record.bytes_completed, 2);
+        assert!(records[0].get_status().is_success());
+        assert_eq!(records[1].status, STATUS_INVALID_FLAGS);
+        assert!(records[2].get_status().is_success());
+    }
+
+    #[test]
+    fn test_agrees_with_validate() {
+        let emulator = Emulator::new();
+        let data = [0u8; 64];
+        let descs = [DsaHwDesc::new(); 2];
+        let mut record = DsaCompletionRecord::new();
+        let work = DsaHwDesc::crc_gen(data.as_ptr(), data.len(), 0, &mut record);
+        let batch = DsaHwDesc::batch(descs.as_ptr(), 2, &mut record);
+
+        let mut cases = vec![work, batch];
+        for base in [work, batch] {
+            let mut desc = base;
+            desc.flags_opcode |= 1 << 20;
+            cases.push(desc);
+            let mut desc = base;
+            desc.add_flags(DescriptorFlags::DEST_NO_SNOOP | DescriptorFlags::DEST_STEERING_TAG);
+            cases.push(desc);
+            let mut desc = base;
+            desc.add_flags(DescriptorFlags::ADDR_TRANSLATED);
+            cases.push(desc);
+            let mut desc = base;
+            desc.completion_addr += 16;
+            cases.push(desc);
+            let mut desc = base;
+            desc.completion_addr = 0;
+            cases.push(desc);
+        }
+        let mut desc = batch;
+        desc.xfer_size = 1;
+        cases.push(desc);
+        let mut desc = batch;
+        desc.src_addr += 32;
+        cases.push(desc);
+        let mut desc = work;
+        desc.flags_opcode = (desc.flags_opcode & 0x00FFFFFF) | (0x7F << 24);
+        cases.push(desc);
+
+        for desc in cases {
+            record.reset();
+            let accepted =
+                unsafe { emulator.submit(&desc) }.is_ok() && record.get_status().is_success();
+            assert_eq!(desc.validate().is_ok(), accepted, "{desc:#x?}");
+        }
+    }
+
+    #[test]
+    fn test_unsupported_opcode() {
+        let emulator = Emulator::new();
+        let mut record = DsaCompletionRecord::new();
+        let mut desc = DsaHwDesc::noop(&mut record);
+        desc.set_opcode(DsaOpcode::Dualcast);
+        submit(&emulator, &desc);
+        assert_eq!(record.status, STATUS_UNSUPPORTED_OP);
+    }
+}
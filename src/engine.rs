@@ -4,10 +4,28 @@
 
 //! High-level DSA engine API.
 
+use crate::backoff::Backoff;
+use crate::bounce::BouncePool;
+use crate::crc;
+use crate::degrade::DegradeCounters;
+#[cfg(all(feature = "async", dsa_portal))]
+use crate::descriptor::DsaHwDesc;
+#[cfg(any(dsa_portal, dsa_software))]
 use crate::device::discover_devices;
+use crate::emulator::Emulator;
 use crate::error::DsaError;
+#[cfg(feature = "async")]
+use crate::future::DsaFuture;
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::shard::ShardPolicy;
+use crate::warm::WarmCache;
 use crate::wq::WorkQueue;
+use crate::zero_scan::PageBitmap;
+use core::cmp::Ordering;
+use core::ops::Range;
 use std::path::Path;
+use std::sync::{atomic, Arc};
+use std::time::{Duration, Instant};
 
 /// High-level DSA engine providing safe access to DSA operations.
 ///
@@ -31,13 +49,150 @@ use std::path::Path;
 /// ```
 pub struct DsaEngine {
     wq: WorkQueue,
+    queue_full_policy: QueueFullPolicy,
+    rate_limiter: Option<RateLimiter>,
+    pub(crate) warm_cache: Option<WarmCache>,
+    pub(crate) bounce: Option<BouncePool>,
+    pub(crate) sharding: Option<ShardPolicy>,
+    pub(crate) soft_timeout: Option<Duration>,
+    pub(crate) degraded: DegradeCounters,
+}
+
+/// How the engine reacts when a shared work queue rejects a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueueFullPolicy {
+    /// Return `DsaError::QueueFull` to the caller immediately.
+    #[default]
+    Fail,
+    /// Sleep for `backoff` and resubmit, until `timeout` has elapsed.
+    ///
+    /// Queues with a threshold of zero are never retried, since they cannot
+    /// accept descriptors until reconfigured.
+    WaitAndRetry {
+        backoff: Duration,
+        timeout: Duration,
+    },
+}
+
+/// The source elements of a [`DsaEngine::gather`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gather<'a> {
+    /// Elements start at these byte offsets into the source.
+    Offsets(&'a [usize]),
+    /// `count` elements start every `stride` bytes, from the start of the
+    /// source.
+    Stride { stride: usize, count: usize },
 }
 
 impl DsaEngine {
+    pub(crate) fn from_wq(wq: WorkQueue) -> Self {
+        Self {
+            wq,
+            queue_full_policy: QueueFullPolicy::default(),
+            rate_limiter: None,
+            warm_cache: None,
+            bounce: None,
+            sharding: None,
+            soft_timeout: None,
+            degraded: DegradeCounters::default(),
+        }
+    }
+
+    /// Set the policy for handling `DsaError::QueueFull`.
+    pub fn set_queue_full_policy(&mut self, policy: QueueFullPolicy) {
+        self.queue_full_policy = policy;
+    }
+
+    /// Get the policy for handling `DsaError::QueueFull`.
+    pub fn queue_full_policy(&self) -> QueueFullPolicy {
+        self.queue_full_policy
+    }
+
+    /// Set how blocking operations wait between completion polls.
+    ///
+    /// The default escalates from spinning to sleeping the longer an
+    /// operation is pending; [`Backoff::SPIN`] restores a tight loop. Has no
+    /// effect on platforms where operations complete synchronously.
+    pub fn set_backoff(&mut self, backoff: Backoff) {
+        self.wq.set_backoff(backoff);
+    }
+
+    /// Serialize submissions and bound the descriptors in flight at
+    /// `budget`, so the engine can be shared by many threads; see
+    /// [`WorkQueue::set_serialized`]. Pass `None` to turn it off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `budget` is zero.
+    pub fn set_serialized(&mut self, budget: Option<usize>) {
+        self.wq.set_serialized(budget);
+    }
+
+    /// Build errors without allocating, for latency-critical callers that
+    /// see bursts of failures, such as a full work queue.
+    ///
+    /// Failed operations then carry no [`ErrorContext`](crate::ErrorContext)
+    /// (which names the work queue), device errors are not attached, and
+    /// `DsaError::QueueFull` reports the threshold read when sealing, if
+    /// any, and no occupancy instead of reading sysfs. Errors returned
+    /// while opening or configuring the engine still carry messages.
+    /// Without the `std` feature, errors never allocate.
+    pub fn set_compact_errors(&mut self, compact: bool) {
+        self.wq.set_compact_errors(compact);
+    }
+
+    /// Returns true if errors are built without allocating.
+    pub fn compact_errors(&self) -> bool {
+        self.wq.compact_errors()
+    }
+
+    /// Limit the rate at which this engine submits work.
+    ///
+    /// Blocking operations wait as needed to stay within the limit; pass
+    /// `None` to remove it. The limit is shared by all threads using the
+    /// engine. Non-blocking `*_async` operations are not throttled.
+    pub fn set_rate_limit(&mut self, limit: Option<RateLimit>) {
+        self.rate_limiter = limit.map(RateLimiter::new);
+    }
+
+    /// Get the current rate limit, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.as_ref().map(RateLimiter::limit)
+    }
+
+    /// Wait until `ops` operations totalling `bytes` bytes fit the rate limit.
+    pub(crate) fn throttle(&self, bytes: usize, ops: usize) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(bytes as u64, ops as u64);
+        }
+    }
+
+    /// Run `op`, resubmitting on retryable queue-full errors per the policy.
+    pub(crate) fn retry<T>(
+        &self,
+        mut op: impl FnMut() -> Result<T, DsaError>,
+    ) -> Result<T, DsaError> {
+        let QueueFullPolicy::WaitAndRetry { backoff, timeout } = self.queue_full_policy else {
+            return op();
+        };
+
+        let start = Instant::now();
+        loop {
+            match op() {
+                Err(e) if e.is_retryable() && start.elapsed() < timeout => {
+                    std::thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Open the first available DSA work queue.
     ///
     /// This discovers all DSA devices on the system and opens the first
-    /// enabled work queue found.
+    /// enabled work queue found. Engines opened on the same work queue in
+    /// one process share its portal mapping.
     ///
     /// # Platform Behavior
     ///
@@ -51,20 +206,21 @@ impl DsaEngine {
     /// - No DSA devices are found (Linux only)
     /// - No enabled work queues are available
     /// - Failed to open the work queue
-    #[cfg(target_os = "linux")]
+    #[cfg(dsa_portal)]
     pub fn open_first() -> Result<Self, DsaError> {
         let devices = discover_devices()?;
         let device = devices.into_iter().next().ok_or(DsaError::NoDeviceFound)?;
         let wq = device.open_first_wq()?;
-        Ok(Self { wq })
+        Ok(Self::from_wq(wq))
     }
 
     /// Open a software-emulated DSA engine on Windows.
     ///
     /// On Windows, hardware DSA access is not available through userspace APIs.
     /// This creates a software-emulated work queue that provides the same API
-    /// but uses optimized software implementations (e.g., crc32fast for CRC32).
-    #[cfg(target_os = "windows")]
+    /// but uses optimized software implementations (e.g., the
+    /// [`SoftwareCrc`](crate::crc::SoftwareCrc) backend for CRC32).
+    #[cfg(dsa_software)]
     pub fn open_first() -> Result<Self, DsaError> {
         // Try to discover hardware first (for informational purposes)
         match discover_devices() {
@@ -81,15 +237,50 @@ impl DsaEngine {
 
         // Always use software work queue on Windows
         let wq = WorkQueue::open(std::path::Path::new(""))?;
-        Ok(Self { wq })
+        Ok(Self::from_wq(wq))
     }
 
     /// Open a software-emulated DSA engine (platform-independent fallback).
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    #[cfg(not(any(dsa_portal, dsa_software)))]
     pub fn open_first() -> Result<Self, DsaError> {
         Err(DsaError::PlatformNotSupported)
     }
 
+    /// Open a work queue by name.
+    ///
+    /// Accepts either a bare work queue name (`"wq0.2"`) or a device-relative
+    /// name (`"dsa1/wq1.0"`). The name is resolved through sysfs, so no
+    /// `/dev` path needs to be hard-coded in configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The name is malformed
+    /// - No matching device or work queue exists
+    /// - The work queue is not enabled
+    /// - Failed to open the work queue
+    #[cfg(dsa_portal)]
+    pub fn open_named(name: &str) -> Result<Self, DsaError> {
+        let wq = crate::device::open_named_wq(name)?;
+        Ok(Self::from_wq(wq))
+    }
+
+    /// Open a software-emulated DSA engine on Windows.
+    ///
+    /// The name is validated but, as with [`DsaEngine::open_first`], a
+    /// software work queue is always used.
+    #[cfg(dsa_software)]
+    pub fn open_named(name: &str) -> Result<Self, DsaError> {
+        crate::device::parse_wq_name(name)?;
+        Self::open_first()
+    }
+
+    /// Open a work queue by name (unsupported platform).
+    #[cfg(not(any(dsa_portal, dsa_software)))]
+    pub fn open_named(name: &str) -> Result<Self, DsaError> {
+        crate::device::open_named_wq(name).map(Self::from_wq)
+    }
+
     /// Open a specific work queue by path.
     ///
     /// # Arguments
@@ -99,9 +290,29 @@ impl DsaEngine {
     /// # Errors
     ///
     /// Returns an error if the work queue cannot be opened.
+    ///
+    /// The work queue is used as a shared work queue and is not reserved
+    /// for this process; open dedicated work queues with
+    /// [`DsaEngine::open_named`], which takes their exclusive lock.
     pub fn open(path: &Path) -> Result<Self, DsaError> {
         let wq = WorkQueue::open(path)?;
-        Ok(Self { wq })
+        Ok(Self::from_wq(wq))
+    }
+
+    /// Create an engine backed by a software emulator.
+    ///
+    /// Operations run through the same descriptor path as on hardware, with
+    /// the emulator executing descriptors. Use [`Emulator::inject`] to make
+    /// operations fail deterministically in tests.
+    ///
+    /// [`Emulator::inject`]: crate::emulator::Emulator::inject
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::PlatformNotSupported` on platforms other than
+    /// Linux, where operations do not go through descriptors.
+    pub fn emulated(emulator: Arc<Emulator>) -> Result<Self, DsaError> {
+        WorkQueue::emulated(emulator).map(Self::from_wq)
     }
 
     /// Get a reference to the underlying work queue.
@@ -116,8 +327,14 @@ impl DsaEngine {
 
     /// Compute CRC32 checksum of the given data using DSA hardware.
     ///
-    /// This offloads CRC32 computation to the DSA accelerator, freeing
-    /// CPU cycles for other work. Most efficient for buffers >= 4KB.
+    /// DSA computes CRC-32C (Castagnoli, [`Crc32Params::DSA`]), not the
+    /// IEEE CRC32 of zlib. This offloads CRC32 computation to the DSA
+    /// accelerator, freeing CPU cycles for other work. Most efficient for
+    /// buffers >= 4KB. Buffers larger than one descriptor can describe
+    /// (2 GiB) are checksummed in several descriptors, each seeded with the
+    /// CRC of the data before it.
+    ///
+    /// [`Crc32Params::DSA`]: crate::Crc32Params::DSA
     ///
     /// # Arguments
     ///
@@ -125,7 +342,7 @@ impl DsaEngine {
     ///
     /// # Returns
     ///
-    /// The CRC32 checksum value.
+    /// The CRC-32C checksum value.
     pub fn crc32(&self, data: &[u8]) -> Result<u32, DsaError> {
         self.crc32_with_seed(data, 0)
     }
@@ -141,7 +358,120 @@ impl DsaEngine {
     ///
     /// The CRC32 checksum value.
     pub fn crc32_with_seed(&self, data: &[u8], seed: u32) -> Result<u32, DsaError> {
-        self.wq.crc32(data, seed)
+        match &self.bounce {
+            Some(pool) => self.crc32_bounced(pool, data, seed),
+            None => self.crc32_direct(data, seed),
+        }
+    }
+
+    /// Compute a CRC32 without bounce buffers.
+    pub(crate) fn crc32_direct(&self, data: &[u8], seed: u32) -> Result<u32, DsaError> {
+        self.throttle(data.len(), 1);
+        self.warm(data);
+        self.note_fault(self.retry(|| self.wq.crc32(data, seed)))
+    }
+
+    /// Check that data has the expected CRC32.
+    ///
+    /// Buffers smaller than [`crc::DEFAULT_HW_THRESHOLD`] are checked in
+    /// software, where submission overhead would dominate; larger ones are
+    /// offloaded to DSA.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to check
+    /// * `expected` - The CRC32 the data should have
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::CrcMismatch` if the CRC differs, or an error if
+    /// the operation fails.
+    pub fn verify_crc32(&self, data: &[u8], expected: u32) -> Result<(), DsaError> {
+        let computed = if data.len() < crc::DEFAULT_HW_THRESHOLD {
+            crc::software_crc32(data, 0)
+        } else {
+            self.crc32(data)?
+        };
+        if computed != expected {
+            return Err(DsaError::CrcMismatch { computed, expected });
+        }
+        Ok(())
+    }
+
+    /// Compute CRC32 checksums of many buffers in a single batch submission.
+    ///
+    /// This amortizes submission overhead across all buffers, which matters
+    /// when hashing thousands of small (4-64 KB) blocks. Buffers are split
+    /// into multiple batches if they exceed the device's batch size limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `bufs` - Buffers to compute checksums over
+    ///
+    /// # Returns
+    ///
+    /// One CRC32 value per buffer, in the same order as `bufs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BatchFailed` if any descriptor in a batch fails.
+    pub fn crc32_many(&self, bufs: &[&[u8]]) -> Result<Vec<u32>, DsaError> {
+        self.throttle(bufs.iter().map(|buf| buf.len()).sum(), bufs.len());
+        self.retry(|| self.wq.crc32_many(bufs, 0))
+    }
+
+    /// Compute the CRC32 of selected lanes of an interleaved buffer.
+    ///
+    /// Lane `i` is `width` bytes starting at `base[i * stride]`; the result
+    /// is the CRC32 of all lanes concatenated in order, such as one field
+    /// of every record in a packet ring or row group. The lanes are
+    /// checksummed as a batch (split only at the device's batch size
+    /// limit) and their CRC32s chained with [`crc32c_combine`], which gives
+    /// the same result as seeding each lane with the CRC32 of the ones
+    /// before it.
+    ///
+    /// [`crc32c_combine`]: crate::crc32c_combine
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - Buffer holding the first lane at its start
+    /// * `stride` - Distance in bytes between the starts of lanes
+    /// * `width` - Bytes per lane
+    /// * `count` - Number of lanes
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BufferSizeMismatch` if the last lane extends past
+    /// the end of `base`, `DsaError::InvalidArgument` if its range
+    /// overflows, or `DsaError::BatchFailed` if a descriptor fails.
+    pub fn crc32_strided(
+        &self,
+        base: &[u8],
+        stride: usize,
+        width: usize,
+        count: usize,
+    ) -> Result<u32, DsaError> {
+        if width == 0 || count == 0 {
+            return Ok(0);
+        }
+        let required = (count - 1)
+            .checked_mul(stride)
+            .and_then(|offset| offset.checked_add(width))
+            .ok_or_else(|| DsaError::InvalidArgument("strided lanes overflow".to_string()))?;
+        if base.len() < required {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: required,
+                actual: base.len(),
+            });
+        }
+
+        let lanes: Vec<&[u8]> = (0..count)
+            .map(|i| &base[i * stride..i * stride + width])
+            .collect();
+        let crcs = self.crc32_many(&lanes)?;
+        Ok(crcs
+            .into_iter()
+            .fold(0, |crc, lane| crc::crc32c_combine(crc, lane, width as u64)))
     }
 
     /// Copy memory from source to destination using DSA hardware.
@@ -155,7 +485,50 @@ impl DsaEngine {
     ///
     /// Returns an error if `dst` is smaller than `src` or the operation fails.
     pub fn memcpy(&self, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
-        self.wq.memcpy(dst, src)
+        match &self.bounce {
+            Some(pool) => self.memcpy_bounced(pool, dst, src),
+            None => self.memcpy_direct(dst, src),
+        }
+    }
+
+    /// Copy memory without bounce buffers.
+    pub(crate) fn memcpy_direct(&self, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+        self.throttle(src.len(), 1);
+        self.warm(src);
+        self.warm_mut(dst);
+        self.note_fault(self.retry(|| match &self.sharding {
+            Some(policy) => self.memcpy_sharded(policy, dst, src),
+            None => self.wq.memcpy(dst, src),
+        }))
+    }
+
+    /// Copy memory and verify the copy end to end.
+    ///
+    /// The copy runs as a CopyCrc operation, which computes the CRC32 of the
+    /// source as it is read; a separate CRC of the destination is then
+    /// compared against it. This detects corruption anywhere on the path
+    /// between the source read and the destination write.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Destination buffer (must be at least as large as `src`)
+    /// * `src` - Source buffer
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::CopyVerificationFailed` if the destination does
+    /// not match what was copied, or an error if `dst` is smaller than `src`
+    /// or an operation fails.
+    pub fn memcpy_verified(&self, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+        self.throttle(2 * src.len(), 2);
+        self.warm(src);
+        self.warm_mut(dst);
+        let src_crc = self.note_fault(self.retry(|| self.wq.copy_crc(dst, src, 0)))?;
+        let dst_crc = self.retry(|| self.wq.crc32(&dst[..src.len()], 0))?;
+        if src_crc != dst_crc {
+            return Err(DsaError::CopyVerificationFailed { src_crc, dst_crc });
+        }
+        Ok(())
     }
 
     /// Fill memory with a 64-bit pattern using DSA hardware.
@@ -167,7 +540,196 @@ impl DsaEngine {
     /// * `dst` - Destination buffer to fill
     /// * `pattern` - 64-bit pattern to fill with
     pub fn memset(&self, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
-        self.wq.memset(dst, pattern)
+        match &self.bounce {
+            Some(pool) => self.memset_bounced(pool, dst, pattern),
+            None => self.memset_direct(dst, pattern),
+        }
+    }
+
+    /// Fill memory without bounce buffers.
+    pub(crate) fn memset_direct(&self, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
+        self.throttle(dst.len(), 1);
+        self.warm_mut(dst);
+        self.note_fault(self.retry(|| match &self.sharding {
+            Some(policy) => self.memset_sharded(policy, dst, pattern),
+            None => self.wq.memset(dst, pattern),
+        }))
+    }
+
+    /// Fill many non-contiguous regions with a 64-bit pattern in one batch.
+    ///
+    /// Equivalent to calling [`memset`] on each region, but all regions are
+    /// submitted as a single batch (split only at the device's batch size
+    /// limit), which avoids one descriptor round trip per page.
+    ///
+    /// [`memset`]: DsaEngine::memset
+    ///
+    /// # Arguments
+    ///
+    /// * `pages` - Regions to fill
+    /// * `pattern` - 64-bit pattern to fill with
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BatchFailed` if any fill in a batch fails.
+    pub fn fill_pages(&self, pages: &mut [&mut [u8]], pattern: u64) -> Result<(), DsaError> {
+        self.throttle(pages.iter().map(|page| page.len()).sum(), pages.len());
+        self.retry(|| self.wq.memset_many(pages, pattern))
+    }
+
+    /// Copy a rectangle between two strided buffers.
+    ///
+    /// Row `i` of the rectangle is `width` bytes starting at
+    /// `src[i * src_stride]` and is copied to `dst[i * dst_stride]`. Rows are
+    /// submitted as a batch of copies (split only at the device's batch size
+    /// limit), which suits image planes and matrix tiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Destination buffer
+    /// * `dst_stride` - Distance in bytes between destination rows
+    /// * `src` - Source buffer
+    /// * `src_stride` - Distance in bytes between source rows
+    /// * `width` - Bytes per row
+    /// * `height` - Number of rows
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `width` exceeds either stride,
+    /// `DsaError::BufferSizeMismatch` if a buffer is too small for the
+    /// rectangle, or `DsaError::BatchFailed` if a row copy fails.
+    pub fn copy_rect(
+        &self,
+        dst: &mut [u8],
+        dst_stride: usize,
+        src: &[u8],
+        src_stride: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<(), DsaError> {
+        self.throttle(width.saturating_mul(height), height);
+        self.retry(|| {
+            self.wq
+                .copy_rect(dst, dst_stride, src, src_stride, width, height)
+        })
+    }
+
+    /// Gather elements of a source buffer into a contiguous buffer.
+    ///
+    /// Element `i` is `element_len` bytes starting at the `i`-th offset of
+    /// `elements` into `src_base`, and is copied to
+    /// `dst[i * element_len..]`, which suits materializing selected rows or
+    /// columns into a scratch buffer. Elements are submitted as a batch of
+    /// copies, split only at the device's batch size limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Destination buffer
+    /// * `src_base` - Source buffer the offsets are relative to
+    /// * `elements` - Offsets of the elements, or their stride and count
+    /// * `element_len` - Bytes per element
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BufferSizeMismatch` if `dst` cannot hold every
+    /// element or an element extends past the end of `src_base`,
+    /// `DsaError::InvalidArgument` if an element's range overflows, or
+    /// `DsaError::BatchFailed` if a copy fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use dsa_rust::{DsaEngine, Gather};
+    ///
+    /// let engine = DsaEngine::open_first()?;
+    /// let rows = vec![7u8; 1000 * 64];
+    /// let mut selected = vec![0u8; 3 * 64];
+    /// engine.gather(&mut selected, &rows, Gather::Offsets(&[0, 640, 6400]), 64)?;
+    /// # Ok::<(), dsa_rust::DsaError>(())
+    /// ```
+    pub fn gather(
+        &self,
+        dst: &mut [u8],
+        src_base: &[u8],
+        elements: Gather<'_>,
+        element_len: usize,
+    ) -> Result<(), DsaError> {
+        let strided: Vec<usize>;
+        let offsets = match elements {
+            Gather::Offsets(offsets) => offsets,
+            Gather::Stride { stride, count } => {
+                // Saturated offsets are rejected as out of bounds
+                strided = (0..count).map(|i| i.saturating_mul(stride)).collect();
+                &strided
+            }
+        };
+
+        self.throttle(offsets.len().saturating_mul(element_len), offsets.len());
+        self.retry(|| self.wq.gather(dst, src_base, offsets, element_len))
+    }
+
+    /// Reconstruct a sparse buffer from its extent map.
+    ///
+    /// Each extent `(offset, src)` is copied to `dst[offset..]`, and the
+    /// rest of `dst` is zeroed, as when a filesystem or snapshot reader
+    /// reads a block with holes. The fills and copies are submitted as one
+    /// batch (split only at the device's batch size limit). Only the holes
+    /// are filled, so no descriptor depends on another and the batch needs
+    /// no fence.
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Destination buffer
+    /// * `extents` - Offsets into `dst` and the data stored there
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BufferSizeMismatch` if an extent extends past the
+    /// end of `dst`, `DsaError::InvalidArgument` if extents overlap or an
+    /// extent's range overflows, or `DsaError::BatchFailed` if a descriptor
+    /// fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use dsa_rust::DsaEngine;
+    ///
+    /// let engine = DsaEngine::open_first()?;
+    /// let (head, tail) = (vec![1u8; 4096], vec![2u8; 8192]);
+    /// let mut block = vec![0xFFu8; 64 << 10];
+    /// engine.materialize(&mut block, &[(0, &head), (32 << 10, &tail)])?;
+    /// # Ok::<(), dsa_rust::DsaError>(())
+    /// ```
+    pub fn materialize(&self, dst: &mut [u8], extents: &[(usize, &[u8])]) -> Result<(), DsaError> {
+        self.throttle(dst.len(), 2 * extents.len() + 1);
+        self.retry(|| self.wq.materialize(dst, extents))
+    }
+
+    /// Securely zero a buffer, e.g. to scrub key material.
+    ///
+    /// The buffer is cleared with a hardware MemFill. If the hardware
+    /// operation fails, the buffer is cleared in software with volatile
+    /// writes instead, so the buffer is always zeroed when this returns.
+    /// A memory fence and an optimization barrier follow, so the compiler
+    /// cannot elide the wipe even if `buf` is never read again.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to wipe
+    pub fn secure_zero(&self, buf: &mut [u8]) {
+        self.throttle(buf.len(), 1);
+        if let Err(e) = self.wq.memset(buf, 0) {
+            log::warn!("DSA secure_zero failed ({}), wiping in software", e);
+            for byte in buf.iter_mut() {
+                // SAFETY: `byte` is a valid, aligned, exclusive reference.
+                unsafe { std::ptr::write_volatile(byte, 0) };
+            }
+        }
+
+        // Order the wipe before any subsequent release of the memory and keep
+        // the compiler from treating the buffer contents as dead.
+        atomic::fence(atomic::Ordering::SeqCst);
+        std::hint::black_box(buf);
     }
 
     /// Compare two memory regions using DSA hardware.
@@ -185,7 +747,150 @@ impl DsaEngine {
     ///
     /// Returns an error if buffer sizes don't match or the operation fails.
     pub fn memcmp(&self, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
-        self.wq.memcmp(a, b)
+        match &self.bounce {
+            Some(pool) => self.memcmp_bounced(pool, a, b),
+            None => self.memcmp_direct(a, b),
+        }
+    }
+
+    /// Compare memory without bounce buffers.
+    pub(crate) fn memcmp_direct(&self, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
+        self.throttle(a.len(), 1);
+        self.warm(a);
+        self.warm(b);
+        self.note_fault(self.retry(|| self.wq.memcmp(a, b)))
+    }
+
+    /// Find the offset of the first differing byte between two buffers.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - First buffer
+    /// * `b` - Second buffer (must be same length as `a`)
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the regions are equal, otherwise `Ok(Some(offset))`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if buffer sizes don't match or the operation fails.
+    pub fn mismatch(&self, a: &[u8], b: &[u8]) -> Result<Option<usize>, DsaError> {
+        self.throttle(a.len(), 1);
+        self.warm(a);
+        self.warm(b);
+        self.note_fault(self.retry(|| self.wq.mismatch(a, b)))
+    }
+
+    /// Compare many pairs of buffers in batch submissions.
+    ///
+    /// One Compare descriptor is submitted per pair, up to the device's
+    /// batch size limit per batch, so verifying thousands of replicated
+    /// blocks costs a few round trips instead of one each.
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs` - Pairs of buffers; both buffers of a pair must have the
+    ///   same length
+    ///
+    /// # Returns
+    ///
+    /// A bitmap with bit `i` set if the buffers of pair `i` are equal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::BufferSizeMismatch` if the buffers of a pair
+    /// differ in length, or `DsaError::BatchFailed` if a descriptor fails.
+    pub fn compare_many(&self, pairs: &[(&[u8], &[u8])]) -> Result<PageBitmap, DsaError> {
+        self.throttle(pairs.iter().map(|(a, _)| a.len()).sum(), pairs.len());
+        let equal = self.retry(|| self.wq.compare_many(pairs))?;
+        let mut bitmap = PageBitmap::new(equal.len());
+        for (i, _) in equal.iter().enumerate().filter(|(_, &equal)| equal) {
+            bitmap.set(i, true);
+        }
+        Ok(bitmap)
+    }
+
+    /// Lexicographically compare two buffers using DSA hardware.
+    ///
+    /// The hardware locates the first differing byte; only that byte is
+    /// compared on the CPU. This gives the same result as `a.cmp(b)` for
+    /// byte slices, including for buffers of different lengths.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - First buffer
+    /// * `b` - Second buffer
+    pub fn cmp(&self, a: &[u8], b: &[u8]) -> Result<Ordering, DsaError> {
+        let len = a.len().min(b.len());
+        Ok(match self.mismatch(&a[..len], &b[..len])? {
+            Some(offset) => a[offset].cmp(&b[offset]),
+            None => a.len().cmp(&b.len()),
+        })
+    }
+
+    /// List the ranges in which two buffers differ.
+    ///
+    /// Each hardware Compare locates the next differing byte; the end of
+    /// that differing run is found on the CPU and the compare restarts
+    /// after it. Adjacent differing bytes form one range, so the ranges are
+    /// sorted, disjoint and separated by at least one equal byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - First buffer
+    /// * `b` - Second buffer (must be same length as `a`)
+    ///
+    /// # Returns
+    ///
+    /// The byte ranges where `a` and `b` differ; empty if they are equal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if buffer sizes don't match or an operation fails.
+    pub fn diff(&self, a: &[u8], b: &[u8]) -> Result<Vec<Range<usize>>, DsaError> {
+        if a.len() != b.len() {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: a.len(),
+                actual: b.len(),
+            });
+        }
+
+        let mut ranges = Vec::new();
+        let mut pos = 0;
+        while pos < a.len() {
+            let Some(offset) = self.mismatch(&a[pos..], &b[pos..])? else {
+                break;
+            };
+            let start = pos + offset;
+            let end = (start..a.len()).find(|&i| a[i] == b[i]).unwrap_or(a.len());
+            ranges.push(start..end);
+            pos = end;
+        }
+        Ok(ranges)
+    }
+
+    /// Verify that a buffer consists of a repeating 64-bit pattern.
+    ///
+    /// Uses the CompareImm operation, so the buffer is scanned without
+    /// materializing a reference copy. The pattern is applied in
+    /// little-endian byte order starting at `buf[0]`, matching [`memset`].
+    ///
+    /// [`memset`]: DsaEngine::memset
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - Buffer to verify
+    /// * `pattern` - Expected 64-bit pattern
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if every byte matches, otherwise `Ok(Some(offset))` of the
+    /// first byte that violates the pattern.
+    pub fn verify_pattern(&self, buf: &[u8], pattern: u64) -> Result<Option<usize>, DsaError> {
+        self.throttle(buf.len(), 1);
+        self.warm(buf);
+        self.note_fault(self.retry(|| self.wq.compare_pattern(buf, pattern)))
     }
 
     /// Execute a no-op operation (for testing/benchmarking).
@@ -193,15 +898,548 @@ impl DsaEngine {
     /// This submits a descriptor that does nothing, useful for measuring
     /// submission overhead.
     pub fn noop(&self) -> Result<(), DsaError> {
-        self.wq.noop()
+        self.throttle(0, 1);
+        self.retry(|| self.wq.noop())
+    }
+}
+
+/// Non-blocking operations.
+///
+/// Each method submits one descriptor and returns a [`DsaFuture`] that
+/// resolves when the hardware writes the completion record. The operation
+/// takes ownership of its buffers, as the hardware may still access them
+/// after the future is dropped or forgotten; pass shared buffers
+/// (`Arc<[u8]>`, ...) to keep using a source. The queue-full policy is not
+/// applied: a full queue resolves the future with `DsaError::QueueFull`
+/// rather than blocking the executor thread. On platforms without hardware
+/// DSA, the operation runs in software and the future is ready immediately.
+#[cfg(feature = "async")]
+impl DsaEngine {
+    /// Compute the CRC32 of `data` without blocking.
+    pub fn crc32_async<B>(&self, data: B) -> DsaFuture<u32>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        #[cfg(dsa_portal)]
+        {
+            if data.as_ref().is_empty() {
+                return DsaFuture::ready(Ok(0));
+            }
+            DsaFuture::start(
+                &self.wq,
+                data,
+                |data, c| {
+                    let data = data.as_ref();
+                    DsaHwDesc::crc_gen(data.as_ptr(), data.len(), 0, c)
+                },
+                |record, _| record.crc32_result(),
+            )
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            DsaFuture::ready(self.crc32(data.as_ref()))
+        }
+    }
+
+    /// Copy `src` into `dst` without blocking.
+    ///
+    /// # Returns
+    ///
+    /// A future resolving to `dst` once the copy has completed.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to an error if `dst` is smaller than `src` or the
+    /// operation fails.
+    pub fn memcpy_async<D, S>(&self, dst: D, src: S) -> DsaFuture<D>
+    where
+        D: AsMut<[u8]> + Send + 'static,
+        S: AsRef<[u8]> + Send + 'static,
+    {
+        #[cfg(dsa_portal)]
+        {
+            let mut dst = dst;
+            let (dst_len, src_len) = (dst.as_mut().len(), src.as_ref().len());
+            if dst_len < src_len {
+                return DsaFuture::ready(Err(DsaError::BufferSizeMismatch {
+                    expected: src_len,
+                    actual: dst_len,
+                }));
+            }
+            if src_len == 0 {
+                return DsaFuture::ready(Ok(dst));
+            }
+            DsaFuture::start(
+                &self.wq,
+                (dst, src),
+                |(dst, src), c| {
+                    let src = src.as_ref();
+                    DsaHwDesc::mem_move(dst.as_mut().as_mut_ptr(), src.as_ptr(), src.len(), c)
+                },
+                |_, (dst, _)| dst,
+            )
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            let mut dst = dst;
+            DsaFuture::ready(self.memcpy(dst.as_mut(), src.as_ref()).map(|()| dst))
+        }
+    }
+
+    /// Fill `dst` with a 64-bit pattern without blocking.
+    ///
+    /// # Returns
+    ///
+    /// A future resolving to `dst` once the fill has completed.
+    pub fn memset_async<D>(&self, dst: D, pattern: u64) -> DsaFuture<D>
+    where
+        D: AsMut<[u8]> + Send + 'static,
+    {
+        #[cfg(dsa_portal)]
+        {
+            let mut dst = dst;
+            if dst.as_mut().is_empty() {
+                return DsaFuture::ready(Ok(dst));
+            }
+            DsaFuture::start(
+                &self.wq,
+                dst,
+                |dst, c| {
+                    let dst = dst.as_mut();
+                    DsaHwDesc::mem_fill(dst.as_mut_ptr(), dst.len(), pattern, c)
+                },
+                |_, dst| dst,
+            )
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            let mut dst = dst;
+            DsaFuture::ready(self.memset(dst.as_mut(), pattern).map(|()| dst))
+        }
+    }
+
+    /// Compare two buffers without blocking.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to an error if the buffer sizes don't match or
+    /// the operation fails.
+    pub fn memcmp_async<A, B>(&self, a: A, b: B) -> DsaFuture<bool>
+    where
+        A: AsRef<[u8]> + Send + 'static,
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        #[cfg(dsa_portal)]
+        {
+            let (a_len, b_len) = (a.as_ref().len(), b.as_ref().len());
+            if a_len != b_len {
+                return DsaFuture::ready(Err(DsaError::BufferSizeMismatch {
+                    expected: a_len,
+                    actual: b_len,
+                }));
+            }
+            if a_len == 0 {
+                return DsaFuture::ready(Ok(true));
+            }
+            DsaFuture::start(
+                &self.wq,
+                (a, b),
+                |(a, b), c| {
+                    let (a, b) = (a.as_ref(), b.as_ref());
+                    DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), c)
+                },
+                |record, _| record.compare_result(),
+            )
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            DsaFuture::ready(self.memcmp(a.as_ref(), b.as_ref()))
+        }
+    }
+
+    /// Execute a no-op operation without blocking.
+    pub fn noop_async(&self) -> DsaFuture<()> {
+        #[cfg(dsa_portal)]
+        {
+            DsaFuture::submitted(self.wq.start(DsaHwDesc::noop), |_| ())
+        }
+        #[cfg(not(dsa_portal))]
+        {
+            DsaFuture::ready(self.noop())
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(dsa_portal)]
+    use super::*;
+
     #[test]
     fn test_engine_requires_hardware() {
         // DsaEngine tests require actual DSA hardware
         // These are integration tests that should be skipped on systems without DSA
     }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_emulated_engine_faults() {
+        use crate::emulator::Fault;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let data = vec![0x5Au8; 8192];
+        assert_eq!(
+            engine.crc32(&data).unwrap(),
+            crate::crc::software_crc32(&data, 0)
+        );
+
+        emulator.inject(Fault::InvalidFlags);
+        let err = engine.crc32(&data).unwrap_err();
+        assert!(matches!(
+            err,
+            DsaError::OperationFailed { status: 0x10, .. }
+        ));
+        let context = err.context().unwrap();
+        assert_eq!(context.opcode, crate::DsaOpcode::CrcGen.into());
+        assert_eq!(context.xfer_size, 8192);
+        assert!(err
+            .to_string()
+            .ends_with(&format!("({context}); hint: {}", err.hint().unwrap())));
+
+        emulator.inject(Fault::BatchFailure { index: 2 });
+        let bufs: Vec<&[u8]> = data.chunks(1024).collect();
+        let err = engine.crc32_many(&bufs).unwrap_err();
+        assert_eq!(err.descriptors_completed(), Some(7));
+
+        emulator.inject(Fault::QueueFull { count: 3 });
+        engine.set_queue_full_policy(QueueFullPolicy::WaitAndRetry {
+            backoff: Duration::ZERO,
+            timeout: Duration::from_secs(1),
+        });
+        engine.noop().unwrap();
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_crc32_strided() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let records: Vec<u8> = (0..100 * 64).map(|i| (i * 13 % 251) as u8).collect();
+        // Bytes 8..20 of every 64-byte record
+        let field: Vec<u8> = records
+            .chunks(64)
+            .flat_map(|record| record[8..20].to_vec())
+            .collect();
+
+        assert_eq!(
+            engine.crc32_strided(&records[8..], 64, 12, 100).unwrap(),
+            crate::crc::software_crc32(&field, 0)
+        );
+        assert_eq!(engine.crc32_strided(&records, 64, 0, 100).unwrap(), 0);
+        assert_eq!(engine.crc32_strided(&records, 64, 12, 0).unwrap(), 0);
+        assert!(matches!(
+            engine.crc32_strided(&records[8..], 64, 60, 100),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            engine.crc32_strided(&records, usize::MAX, 1, 3),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_compact_errors() {
+        use crate::emulator::Fault;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        assert!(!engine.compact_errors());
+        engine.set_compact_errors(true);
+        assert!(engine.compact_errors());
+        let data = vec![0x5Au8; 8192];
+
+        emulator.inject(Fault::InvalidFlags);
+        let err = engine.crc32(&data).unwrap_err();
+        assert!(matches!(
+            err,
+            DsaError::OperationFailed { status: 0x10, .. }
+        ));
+        assert!(err.context().is_none());
+
+        // Operations in flight do not record their origin either
+        emulator.inject(Fault::InvalidFlags);
+        let err = engine.crc32_parallel(&data, 4096).unwrap_err();
+        assert!(err.context().is_none());
+
+        engine.set_compact_errors(false);
+        emulator.inject(Fault::InvalidFlags);
+        assert!(engine.crc32(&data).unwrap_err().context().is_some());
+
+        // A batch of one descriptor is submitted on its own
+        let mut page = vec![0u8; 64];
+        emulator.inject(Fault::InvalidFlags);
+        let err = engine
+            .fill_pages(&mut [page.as_mut_slice()], 0)
+            .unwrap_err();
+        assert!(err.context().is_some());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_memcpy_verified() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let src: Vec<u8> = (0..8192).map(|i| (i * 7) as u8).collect();
+        let mut dst = vec![0u8; 8192];
+        engine.memcpy_verified(&mut dst, &src).unwrap();
+        assert_eq!(dst, src);
+        assert_eq!(
+            engine.work_queue().copy_crc(&mut dst, &src, 0).unwrap(),
+            crate::crc::software_crc32(&src, 0)
+        );
+        assert!(matches!(
+            engine.memcpy_verified(&mut dst[..10], &src),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_verify_crc32() {
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        for len in [100, 65536] {
+            let data = vec![0xA5u8; len];
+            let crc = crate::crc::software_crc32(&data, 0);
+            engine.verify_crc32(&data, crc).unwrap();
+            assert!(matches!(
+                engine.verify_crc32(&data, !crc),
+                Err(DsaError::CrcMismatch { computed, expected }) if computed == crc && expected == !crc
+            ));
+        }
+        // Only the large buffer went to the (emulated) hardware
+        assert_eq!(emulator.submitted(), 2);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_diff() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let a = vec![0u8; 4096];
+        let mut b = a.clone();
+        assert!(engine.diff(&a, &b).unwrap().is_empty());
+
+        b[0] = 1;
+        b[100..108].fill(2);
+        b[110] = 3;
+        b[4095] = 4;
+        assert_eq!(
+            engine.diff(&a, &b).unwrap(),
+            vec![0..1, 100..108, 110..111, 4095..4096]
+        );
+        assert!(engine.diff(&a, &b[..10]).is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_compare_many() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let blocks: Vec<Vec<u8>> = (0..1500).map(|i| vec![i as u8; 512]).collect();
+        let mut replicas = blocks.clone();
+        replicas[7][100] ^= 1;
+        replicas[1100][511] ^= 1;
+        let pairs: Vec<(&[u8], &[u8])> = blocks
+            .iter()
+            .zip(&replicas)
+            .map(|(a, b)| (a.as_slice(), b.as_slice()))
+            .collect();
+
+        let equal = engine.compare_many(&pairs).unwrap();
+        assert_eq!(equal.len(), pairs.len());
+        assert_eq!(equal.count_ones(), pairs.len() - 2);
+        assert!(!equal.get(7) && !equal.get(1100) && equal.get(8));
+
+        assert!(engine.compare_many(&[(&[], &[])]).unwrap().get(0));
+        assert!(matches!(
+            engine.compare_many(&[(&[0u8; 8], &[0u8; 4])]),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_materialize() {
+        use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let src: Vec<u8> = (0..8192).map(|i| (i % 251 + 1) as u8).collect();
+
+        let extents: [(usize, &[u8]); 3] = [(9000, &src[..100]), (100, &src[..4096]), (16384, &[])];
+        let mut dst = vec![0xFFu8; 16384];
+        engine.materialize(&mut dst, &extents).unwrap();
+        let mut expected = vec![0u8; 16384];
+        expected[100..4196].copy_from_slice(&src[..4096]);
+        expected[9000..9100].copy_from_slice(&src[..100]);
+        assert_eq!(dst, expected);
+
+        // No extents zeroes everything; more descriptors than fit in one batch
+        engine.materialize(&mut dst, &[]).unwrap();
+        assert!(dst.iter().all(|&b| b == 0));
+        let extents: Vec<(usize, &[u8])> = (0..DEFAULT_MAX_BATCH_SIZE)
+            .map(|i| (16 * i + 8, &src[i..i + 8]))
+            .collect();
+        let mut dst = vec![0xFFu8; 16 * DEFAULT_MAX_BATCH_SIZE];
+        engine.materialize(&mut dst, &extents).unwrap();
+        for (i, block) in dst.chunks(16).enumerate() {
+            assert_eq!(block[..8], [0; 8]);
+            assert_eq!(block[8..], src[i..i + 8]);
+        }
+
+        assert!(matches!(
+            engine.materialize(&mut dst[..16], &[(12, &src[..8])]),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+        assert!(matches!(
+            engine.materialize(&mut dst, &[(0, &src[..8]), (4, &src[..8])]),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_gather() {
+        use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let src: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+
+        let offsets = [4000, 17, 0, 4000];
+        let mut dst = vec![0u8; offsets.len() * 24];
+        engine
+            .gather(&mut dst, &src, Gather::Offsets(&offsets), 24)
+            .unwrap();
+        for (element, offset) in dst.chunks(24).zip(offsets) {
+            assert_eq!(element, &src[offset..offset + 24]);
+        }
+
+        // More elements than fit in one batch
+        let count = DEFAULT_MAX_BATCH_SIZE + 5;
+        let mut dst = vec![0u8; count * 8];
+        engine
+            .gather(&mut dst, &src, Gather::Stride { stride: 40, count }, 8)
+            .unwrap();
+        for (i, element) in dst.chunks(8).enumerate() {
+            assert_eq!(element, &src[i * 40..i * 40 + 8]);
+        }
+
+        assert!(matches!(
+            engine.gather(&mut dst, &src, Gather::Offsets(&[src.len() - 4]), 8),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+        assert!(engine
+            .gather(
+                &mut dst,
+                &src,
+                Gather::Stride {
+                    stride: usize::MAX,
+                    count: 2
+                },
+                8
+            )
+            .is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_mismatch_and_cmp() {
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let a: Vec<u8> = (0..4096).map(|i| (i % 251) as u8).collect();
+        assert_eq!(engine.mismatch(&a, &a).unwrap(), None);
+        assert_eq!(engine.cmp(&a, &a).unwrap(), Ordering::Equal);
+
+        for offset in [0, 1000, 4095] {
+            let mut b = a.clone();
+            b[offset] = b[offset].wrapping_add(1);
+            let submitted = emulator.submitted();
+            assert_eq!(engine.mismatch(&a, &b).unwrap(), Some(offset));
+            assert_eq!(emulator.submitted(), submitted + 1);
+            assert_eq!(engine.cmp(&a, &b).unwrap(), a.cmp(&b));
+            assert_eq!(engine.cmp(&b, &a).unwrap(), b.cmp(&a));
+        }
+
+        // Lengths decide only between a buffer and its prefix
+        assert_eq!(engine.cmp(&a[..100], &a).unwrap(), Ordering::Less);
+        assert_eq!(engine.cmp(&a, &a[..100]).unwrap(), Ordering::Greater);
+        assert_eq!(engine.cmp(&[2], &a[..100]).unwrap(), Ordering::Greater);
+        assert_eq!(engine.cmp(&[], &[]).unwrap(), Ordering::Equal);
+        assert!(matches!(
+            engine.mismatch(&a, &a[..100]),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_secure_zero() {
+        use crate::emulator::Fault;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+
+        // Wiped by the device
+        let mut key = vec![0xA5u8; 10_000];
+        let submitted = emulator.submitted();
+        engine.secure_zero(&mut key);
+        assert_eq!(emulator.submitted(), submitted + 1);
+        assert!(key.iter().all(|&b| b == 0));
+
+        // Wiped in software when the device fails, wholly or part way
+        for fault in [Fault::InvalidFlags, Fault::PageFault { offset: 100 }] {
+            key.fill(0xA5);
+            emulator.inject(fault);
+            engine.secure_zero(&mut key);
+            assert!(key.iter().all(|&b| b == 0));
+        }
+
+        engine.secure_zero(&mut []);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_fill_pages() {
+        use crate::emulator::Fault;
+        use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let pattern = 0x0807_0605_0403_0201u64;
+        let filled = |region: &[u8]| {
+            region
+                .iter()
+                .zip(pattern.to_le_bytes().iter().cycle())
+                .all(|(a, b)| a == b)
+        };
+
+        // Scattered regions of odd sizes, some empty, more than one batch
+        let mut backing: Vec<Vec<u8>> = (0..2 * DEFAULT_MAX_BATCH_SIZE)
+            .map(|i| vec![0xEEu8; [4096, 13, 0, 100][i % 4]])
+            .collect();
+        let mut pages: Vec<&mut [u8]> = backing.iter_mut().map(|v| v.as_mut_slice()).collect();
+        let submitted = emulator.submitted();
+        engine.fill_pages(&mut pages, pattern).unwrap();
+        assert_eq!(emulator.submitted(), submitted + 2);
+        assert!(backing.iter().all(|page| filled(page)));
+
+        // A failed fill is reported
+        let mut backing = vec![vec![0u8; 64]; 4];
+        let mut pages: Vec<&mut [u8]> = backing.iter_mut().map(|v| v.as_mut_slice()).collect();
+        emulator.inject(Fault::BatchFailure { index: 1 });
+        assert!(matches!(
+            engine.fill_pages(&mut pages, pattern),
+            Err(DsaError::BatchFailed { .. })
+        ));
+
+        engine.fill_pages(&mut [], pattern).unwrap();
+    }
 }
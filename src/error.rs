@@ -3,9 +3,337 @@
 // SPDX-License-Identifier: MIT
 
 //! Error types for DSA operations.
+//!
+//! The variants reported by the descriptor layer and by completion records
+//! carry only plain values, so they need neither `std` nor an allocator.
+//! Variants that carry strings, paths or I/O errors come from opening
+//! devices and require the `std` feature. Without it the crate builds as
+//! `no_std` with only this module and the descriptor layer: [`descriptor`],
+//! [`opcode`] and [`submit`].
+//!
+//! [`descriptor`]: crate::descriptor
+//! [`opcode`]: crate::opcode
+//! [`submit`]: crate::submit
 
+#[cfg(feature = "std")]
+use crate::descriptor::DsaHwDesc;
+use crate::descriptor::{DescriptorFlags, DifStatus};
+#[cfg(feature = "std")]
+use crate::opcode::DecodedOpcode;
+use crate::opcode::DsaOpcode;
+#[cfg(feature = "std")]
+use crate::swerr::SoftwareError;
+use core::time::Duration;
 use thiserror::Error;
 
+/// Reason a work queue could not be opened, as determined from sysfs.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WqUnavailableReason {
+    /// The work queue is not enabled.
+    Disabled { state: String },
+    /// The work queue is claimed by a kernel driver (e.g. dmaengine) rather
+    /// than the user-space character device driver.
+    KernelOwned { wq_type: String },
+    /// The work queue mode is neither `dedicated` nor `shared`.
+    WrongMode { mode: String },
+    /// A shared work queue on a device without PASID support.
+    NoPasid,
+    /// The work queue exists in sysfs but has no `/dev` node.
+    MissingDevNode,
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for WqUnavailableReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Disabled { state } => write!(f, "work queue is {}", state),
+            Self::KernelOwned { wq_type } => {
+                write!(f, "work queue is bound to the {} driver, not user", wq_type)
+            }
+            Self::WrongMode { mode } => write!(f, "unsupported work queue mode '{}'", mode),
+            Self::NoPasid => write!(f, "shared work queue requires PASID support"),
+            Self::MissingDevNode => write!(f, "device node is missing"),
+        }
+    }
+}
+
+/// The operation behind an error, so a logged failure can be traced to its
+/// call site.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Opcode of the failed descriptor.
+    pub opcode: DecodedOpcode,
+    /// Transfer size of the failed descriptor in bytes.
+    pub xfer_size: u32,
+    /// Name of the work queue it was submitted to, e.g. `wq0.0`.
+    pub work_queue: Option<String>,
+    /// Time from submission until the error was detected.
+    pub elapsed: Duration,
+    /// Error logged by the device for a hardware error status, if attached
+    /// ([`DsaEngine::set_attach_device_errors`](crate::DsaEngine::set_attach_device_errors)).
+    pub device_error: Option<SoftwareError>,
+}
+
+#[cfg(feature = "std")]
+impl ErrorContext {
+    /// Context of `desc`, submitted `elapsed` ago.
+    pub(crate) fn new(desc: &DsaHwDesc, work_queue: Option<String>, elapsed: Duration) -> Self {
+        Self {
+            opcode: DecodedOpcode::from(desc.opcode()),
+            xfer_size: desc.xfer_size,
+            work_queue,
+            elapsed,
+            device_error: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}, {} bytes", self.opcode, self.xfer_size)?;
+        if let Some(work_queue) = &self.work_queue {
+            write!(f, " on {}", work_queue)?;
+        }
+        write!(f, ", after {:?}", self.elapsed)?;
+        if let Some(device_error) = &self.device_error {
+            write!(f, ", device error: {}", device_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the context of an error, if any, as a suffix of its message.
+struct ContextSuffix<'a>(&'a DsaError);
+
+impl core::fmt::Display for ContextSuffix<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        #[cfg(feature = "std")]
+        if let Some(context) = self.0.context() {
+            return write!(f, " ({context})");
+        }
+        // Only errors built with `std` carry a context
+        #[cfg(not(feature = "std"))]
+        let _ = f;
+        Ok(())
+    }
+}
+
+/// Formats the name of a completion status byte.
+struct StatusName(u8);
+
+impl core::fmt::Display for StatusName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(status_name(self.0))
+    }
+}
+
+/// Formats the remediation hint of a completion status byte, if any, as a
+/// suffix of a message.
+struct HintSuffix(u8);
+
+impl core::fmt::Display for HintSuffix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match status_hint(self.0) {
+            Some(hint) => write!(f, "; hint: {hint}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Name and remediation hint of a completion status byte.
+///
+/// Bit 7 of the status reports whether a page fault was on a write and is
+/// ignored.
+const fn describe_status(status: u8) -> (&'static str, Option<&'static str>) {
+    match status & 0x7F {
+        0x00 => ("not complete", None),
+        0x01 => ("success", None),
+        0x02 => ("success with false predicate", None),
+        0x03 => (
+            "page fault",
+            Some("touch or lock the buffers before submitting, or use a work queue with block on fault"),
+        ),
+        0x04 => (
+            "page response error",
+            Some("the address is not mapped in this process; check buffer lifetimes"),
+        ),
+        0x05 => (
+            "batch descriptor failed",
+            Some("check the completion record of each descriptor in the batch"),
+        ),
+        0x06 => (
+            "page fault reading batch descriptor list",
+            Some("keep the descriptor list in locked memory"),
+        ),
+        0x07 => (
+            "delta record offsets not increasing",
+            Some("the delta record is corrupt"),
+        ),
+        0x08 => (
+            "delta record offset out of range",
+            Some("the delta record was created for a larger buffer"),
+        ),
+        0x09 => ("DIF error", None),
+        0x0A => ("analytics error", None),
+        0x10 => (
+            "invalid flags",
+            Some("a flag is reserved, conflicts with another or is not enabled on the work queue; validate the descriptor"),
+        ),
+        0x11 => (
+            "unsupported operation",
+            Some("the device does not support this opcode; check Capabilities::supported_ops"),
+        ),
+        0x12 => (
+            "non-zero reserved field",
+            Some("reserved descriptor fields must be zero; validate the descriptor"),
+        ),
+        0x13 => (
+            "invalid transfer size",
+            Some("transfer is empty or exceeds max_transfer_size; enable chunking"),
+        ),
+        0x14 => (
+            "descriptor count out of range",
+            Some("a batch needs between 2 and max_batch_size descriptors"),
+        ),
+        0x15 => (
+            "delta record size out of range",
+            Some("the maximum delta record size exceeds what the device supports"),
+        ),
+        0x16 => (
+            "overlapping buffers",
+            Some("source and destination must not overlap"),
+        ),
+        0x17 => (
+            "dualcast destinations misaligned",
+            Some("bits 11:0 of both dualcast destinations must be equal"),
+        ),
+        0x18 => (
+            "misaligned descriptor list",
+            Some("a batch descriptor list must be 64-byte aligned"),
+        ),
+        0x19 => (
+            "invalid completion record address",
+            Some("the completion record must be 32-byte aligned and stay valid until completion"),
+        ),
+        0x1A => (
+            "completion record address translation failed",
+            Some("the completion record must stay mapped until completion"),
+        ),
+        0x1B => (
+            "misaligned completion record",
+            Some("the completion record must be 32-byte aligned"),
+        ),
+        0x1C => (
+            "misaligned address",
+            Some("an address or size is not aligned as the operation requires"),
+        ),
+        0x1D => (
+            "privileged descriptor not allowed",
+            Some("user-space work queues accept only unprivileged descriptors"),
+        ),
+        0x1E => (
+            "traffic class configuration error",
+            Some("check the traffic class configuration of the group"),
+        ),
+        0x1F..=0x21 => (
+            "hardware error",
+            Some("check the device's error status in sysfs and the kernel log"),
+        ),
+        0x22 => (
+            "address translation failed",
+            Some("check the IOMMU and PASID configuration"),
+        ),
+        _ => ("unknown status", None),
+    }
+}
+
+/// Name of a completion status byte, e.g. `"invalid transfer size"` for
+/// 0x13.
+pub const fn status_name(status: u8) -> &'static str {
+    describe_status(status).0
+}
+
+/// Short hint on how to avoid a failure with completion status `status`,
+/// e.g. `"transfer is empty or exceeds max_transfer_size; enable
+/// chunking"` for 0x13.
+///
+/// Returns `None` for statuses that are not failures or that have no
+/// general remedy.
+pub const fn status_hint(status: u8) -> Option<&'static str> {
+    describe_status(status).1
+}
+
+/// Reason a descriptor was rejected before submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum DescriptorError {
+    /// Reserved flag bits are set.
+    #[error("reserved descriptor flags {0:#x} are set")]
+    ReservedFlags(u32),
+    /// Two flags that cannot be combined are set.
+    #[error("descriptor flags {0:?} and {1:?} are mutually exclusive")]
+    ConflictingFlags(DescriptorFlags, DescriptorFlags),
+    /// Translated addresses on an unprivileged descriptor.
+    #[error("translated addresses require a privileged descriptor")]
+    TranslatedUnprivileged,
+    /// Reserved bits of the PASID word are set.
+    #[error("reserved PASID bits {0:#x} are set")]
+    ReservedPasidBits(u32),
+    /// The PASID word is set without the `manual-pasid` feature.
+    #[error("PASID word {0:#x} is set; manual PASID control requires the `manual-pasid` feature")]
+    PasidWithoutFeature(u32),
+    /// A PASID that does not fit in 20 bits.
+    #[error("PASID {0:#x} does not fit in 20 bits")]
+    PasidOutOfRange(u32),
+    /// An opcode byte that is not a defined operation.
+    #[error("unknown opcode {0:#04x}")]
+    UnknownOpcode(u8),
+    /// A completion is requested without a completion record.
+    #[error("completion requested without a completion record")]
+    MissingCompletionRecord,
+    /// The completion record is not 32-byte aligned.
+    #[error("completion record address {0:#x} is not 32-byte aligned")]
+    MisalignedCompletionRecord(u64),
+    /// A Create Delta size that is not a multiple of 8 or is too large.
+    #[error("create delta size {0} is not a multiple of 8 or exceeds the delta record limit")]
+    CreateDeltaSize(u32),
+    /// An Apply Delta record size that is not a whole number of entries.
+    #[error("delta record size {0} is not a multiple of the entry size")]
+    DeltaRecordSize(u32),
+    /// A DIF transfer size that is not a whole number of source blocks.
+    #[error("DIF transfer size {0} is not a whole number of blocks")]
+    DifSize(u32),
+    /// A batch of fewer than two descriptors.
+    #[error("batch of {0} descriptors; at least 2 are required")]
+    BatchTooSmall(u32),
+    /// The descriptor list of a batch is not 64-byte aligned.
+    #[error("descriptor list address {0:#x} is not 64-byte aligned")]
+    MisalignedDescriptorList(u64),
+    /// A transfer larger than the size field or the work queue allows.
+    #[error("transfer of {len} bytes exceeds the maximum of {max}")]
+    TransferTooLarge { len: u64, max: u64 },
+    /// A transfer of zero bytes.
+    #[error("{0:?} descriptor with an empty transfer")]
+    EmptyTransfer(DsaOpcode),
+    /// A batch of more descriptors than the work queue allows.
+    #[error("batch of {count} descriptors exceeds the maximum of {max}")]
+    BatchTooLarge { count: u32, max: u32 },
+    /// A batch descriptor inside a batch.
+    #[error("batch descriptor inside a batch")]
+    NestedBatch,
+    /// Source and destination of a copying operation overlap.
+    #[error("source and destination of {0:?} overlap")]
+    OverlappingBuffers(DsaOpcode),
+    /// An opcode the work queue does not support.
+    #[error("opcode {0:?} is not supported by the work queue")]
+    UnsupportedOpcode(DsaOpcode),
+    /// Flags the work queue does not allow.
+    #[error("descriptor flags {0:?} are not supported by the work queue")]
+    UnsupportedFlags(DescriptorFlags),
+}
+
 /// Errors that can occur during DSA operations.
 #[derive(Debug, Error)]
 pub enum DsaError {
@@ -18,29 +346,120 @@ pub enum DsaError {
     NoWorkQueue,
 
     /// Work queue is full (ENQCMD returned busy).
-    #[error("work queue full")]
-    QueueFull,
+    ///
+    /// `threshold` and `occupancy` are read from sysfs when available. A
+    /// threshold of zero means the shared work queue is misconfigured and
+    /// will never accept descriptors, so retrying is pointless.
+    #[error(
+        "work queue full after {attempts} attempts in {elapsed:?} (threshold={threshold:?}, occupancy={occupancy:?})"
+    )]
+    QueueFull {
+        attempts: u32,
+        elapsed: Duration,
+        threshold: Option<u32>,
+        occupancy: Option<u32>,
+    },
 
     /// DSA operation failed with hardware error.
-    #[error("DSA operation failed: status={status:#04x}, result={result:#04x}")]
-    OperationFailed { status: u8, result: u8 },
+    ///
+    /// The message names the status and, where there is one, hints at the
+    /// remedy; see [`status_name`] and [`status_hint`].
+    #[error(
+        "DSA operation failed: status={status:#04x} ({}), result={result:#04x}, completed {bytes_completed} bytes{}{}",
+        StatusName(*status),
+        ContextSuffix(self),
+        HintSuffix(*status)
+    )]
+    OperationFailed {
+        status: u8,
+        result: u8,
+        bytes_completed: u32,
+        #[cfg(feature = "std")]
+        context: Option<Box<ErrorContext>>,
+    },
+
+    /// A DIF check failed.
+    ///
+    /// `bytes_completed` is the number of source bytes before the failing
+    /// block; divide by the source block size for its index.
+    #[error("DIF check failed: {status:?}, completed {bytes_completed} bytes")]
+    DifCheckFailed {
+        status: DifStatus,
+        bytes_completed: u32,
+    },
+
+    /// Operation did not complete within the polling budget.
+    ///
+    /// The progress of the operation is unknown: the device writes the
+    /// completion record only when it finishes.
+    #[error("DSA operation timed out{}", ContextSuffix(self))]
+    Timeout {
+        #[cfg(feature = "std")]
+        context: Option<Box<ErrorContext>>,
+    },
+
+    /// The operation's cancellation token was cancelled.
+    #[error("DSA operation cancelled")]
+    Cancelled,
+
+    /// The operation's deadline passed before it completed.
+    #[error("DSA operation deadline exceeded")]
+    DeadlineExceeded,
+
+    /// Batch descriptor failed or completed with errors.
+    ///
+    /// `descriptors_completed` is the number of descriptors in the batch that
+    /// completed successfully before the failure.
+    #[error(
+        "DSA batch failed: status={status:#04x} ({}), completed {descriptors_completed} of {descriptors_total} descriptors",
+        StatusName(*status)
+    )]
+    BatchFailed {
+        status: u8,
+        descriptors_completed: u32,
+        descriptors_total: u32,
+    },
 
     /// Page fault during DSA operation.
-    #[error("page fault at address {fault_addr:#018x}, completed {bytes_completed} bytes")]
+    #[error(
+        "page fault at address {fault_addr:#018x}, completed {bytes_completed} bytes{}",
+        ContextSuffix(self)
+    )]
     PageFault {
         fault_addr: u64,
         bytes_completed: u32,
+        #[cfg(feature = "std")]
+        context: Option<Box<ErrorContext>>,
     },
 
+    /// Data did not have the expected CRC32.
+    #[error("CRC32 mismatch: computed {computed:#010x}, expected {expected:#010x}")]
+    CrcMismatch { computed: u32, expected: u32 },
+
+    /// A verified copy read back different data than it copied.
+    ///
+    /// `src_crc` is the CRC32 computed while copying and `dst_crc` the CRC32
+    /// of the destination afterwards.
+    #[error(
+        "copy verification failed: source CRC {src_crc:#010x}, destination CRC {dst_crc:#010x}"
+    )]
+    CopyVerificationFailed { src_crc: u32, dst_crc: u32 },
+
     /// Invalid argument provided.
+    #[cfg(feature = "std")]
     #[error("invalid argument: {0}")]
     InvalidArgument(String),
 
+    /// A descriptor was rejected before submission.
+    #[error("invalid descriptor: {0}")]
+    InvalidDescriptor(#[from] DescriptorError),
+
     /// Buffer size mismatch.
     #[error("buffer size mismatch: expected {expected}, got {actual}")]
     BufferSizeMismatch { expected: usize, actual: usize },
 
     /// I/O error from system calls.
+    #[cfg(feature = "std")]
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -53,13 +472,434 @@ pub enum DsaError {
     DeviceNotEnabled,
 
     /// Permission denied accessing DSA device.
+    #[cfg(feature = "std")]
     #[error("permission denied: {0}")]
     PermissionDenied(String),
 
+    /// Work queue cannot be opened for the given reason.
+    #[cfg(feature = "std")]
+    #[error("work queue {name} unavailable: {reason}")]
+    WorkQueueUnavailable {
+        name: String,
+        reason: WqUnavailableReason,
+    },
+
+    /// The work queue was disabled or its device reset or removed while in
+    /// use.
+    ///
+    /// Outstanding operations on it fail with this error. Later operations
+    /// reopen the work queue once it is enabled again.
+    #[cfg(feature = "std")]
+    #[error("work queue {0} was disabled or its device was removed")]
+    WorkQueueDisabled(String),
+
+    /// Dedicated work queue is already reserved by another process.
+    #[cfg(feature = "std")]
+    #[error("work queue in use by another process: {0}")]
+    WorkQueueBusy(String),
+
     /// Memory mapping failed.
+    #[cfg(feature = "std")]
     #[error("mmap failed: {0}")]
     MmapFailed(String),
+
+    /// Completion interrupts are not available on this device or platform.
+    #[error("completion interrupts not supported")]
+    InterruptsUnsupported,
+
+    /// All registered interrupt handles are in use.
+    #[error("no interrupt handle available")]
+    NoInterruptHandle,
+}
+
+impl DsaError {
+    /// The stable numeric code of this error, see [`ErrorCode`].
+    pub fn code(&self) -> u32 {
+        self.error_code().code()
+    }
+
+    /// The [`ErrorCode`] of this error.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::NoDeviceFound => ErrorCode::NoDeviceFound,
+            Self::NoWorkQueue => ErrorCode::NoWorkQueue,
+            Self::QueueFull { .. } => ErrorCode::QueueFull,
+            Self::OperationFailed { .. } => ErrorCode::OperationFailed,
+            Self::DifCheckFailed { .. } => ErrorCode::DifCheckFailed,
+            Self::Timeout { .. } => ErrorCode::Timeout,
+            Self::Cancelled => ErrorCode::Cancelled,
+            Self::DeadlineExceeded => ErrorCode::DeadlineExceeded,
+            Self::BatchFailed { .. } => ErrorCode::BatchFailed,
+            Self::PageFault { .. } => ErrorCode::PageFault,
+            Self::CrcMismatch { .. } => ErrorCode::CrcMismatch,
+            Self::CopyVerificationFailed { .. } => ErrorCode::CopyVerificationFailed,
+            #[cfg(feature = "std")]
+            Self::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            Self::BufferSizeMismatch { .. } => ErrorCode::BufferSizeMismatch,
+            #[cfg(feature = "std")]
+            Self::Io(_) => ErrorCode::Io,
+            Self::PlatformNotSupported => ErrorCode::PlatformNotSupported,
+            Self::DeviceNotEnabled => ErrorCode::DeviceNotEnabled,
+            #[cfg(feature = "std")]
+            Self::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            #[cfg(feature = "std")]
+            Self::WorkQueueUnavailable { .. } => ErrorCode::WorkQueueUnavailable,
+            #[cfg(feature = "std")]
+            Self::WorkQueueDisabled(_) => ErrorCode::WorkQueueDisabled,
+            #[cfg(feature = "std")]
+            Self::WorkQueueBusy(_) => ErrorCode::WorkQueueBusy,
+            #[cfg(feature = "std")]
+            Self::MmapFailed(_) => ErrorCode::MmapFailed,
+            Self::InterruptsUnsupported => ErrorCode::InterruptsUnsupported,
+            Self::NoInterruptHandle => ErrorCode::NoInterruptHandle,
+            Self::InvalidDescriptor(_) => ErrorCode::InvalidDescriptor,
+        }
+    }
+
+    /// Number of bytes processed before the error, if the error carries
+    /// partial-completion progress.
+    ///
+    /// Callers can use this to resume the operation at the returned offset.
+    pub fn bytes_completed(&self) -> Option<u32> {
+        match self {
+            Self::OperationFailed {
+                bytes_completed, ..
+            }
+            | Self::PageFault {
+                bytes_completed, ..
+            } => Some(*bytes_completed),
+            _ => None,
+        }
+    }
+
+    /// The operation behind the error, for errors reported by a descriptor
+    /// whose submission is known.
+    #[cfg(feature = "std")]
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::OperationFailed { context, .. }
+            | Self::PageFault { context, .. }
+            | Self::Timeout { context, .. } => context.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Attach the context built by `context` to an error that carries one
+    /// and has none yet.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_context(mut self, context: impl FnOnce() -> ErrorContext) -> Self {
+        if let Self::OperationFailed { context: slot, .. }
+        | Self::PageFault { context: slot, .. }
+        | Self::Timeout { context: slot, .. } = &mut self
+        {
+            if slot.is_none() {
+                *slot = Some(Box::new(context()));
+            }
+        }
+        self
+    }
+
+    /// Returns true if the error is transient queue pressure that may
+    /// succeed when retried.
+    ///
+    /// A full shared work queue with a threshold of zero is a configuration
+    /// error and is not considered retryable.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::QueueFull { threshold, .. } if *threshold != Some(0))
+    }
+
+    /// Hint on how to avoid the error, for errors reported by a completion
+    /// status that has one.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::OperationFailed { status, .. } | Self::BatchFailed { status, .. } => {
+                status_hint(*status)
+            }
+            _ => None,
+        }
+    }
+
+    /// Number of batch descriptors completed before the error, if the error
+    /// came from a batch submission.
+    pub fn descriptors_completed(&self) -> Option<u32> {
+        match self {
+            Self::BatchFailed {
+                descriptors_completed,
+                ..
+            } => Some(*descriptors_completed),
+            _ => None,
+        }
+    }
+}
+
+/// Stable numeric code of a [`DsaError`] variant, for FFI and structured
+/// logging.
+///
+/// Codes never change and are never reused: new error variants get new
+/// codes. Zero is not an error code, so C callers can use it for success.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ErrorCode {
+    /// [`DsaError::NoDeviceFound`].
+    NoDeviceFound = 1,
+    /// [`DsaError::NoWorkQueue`].
+    NoWorkQueue = 2,
+    /// [`DsaError::QueueFull`].
+    QueueFull = 3,
+    /// [`DsaError::OperationFailed`].
+    OperationFailed = 4,
+    /// [`DsaError::DifCheckFailed`].
+    DifCheckFailed = 5,
+    /// [`DsaError::Timeout`].
+    Timeout = 6,
+    /// [`DsaError::Cancelled`].
+    Cancelled = 7,
+    /// [`DsaError::DeadlineExceeded`].
+    DeadlineExceeded = 8,
+    /// [`DsaError::BatchFailed`].
+    BatchFailed = 9,
+    /// [`DsaError::PageFault`].
+    PageFault = 10,
+    /// [`DsaError::CrcMismatch`].
+    CrcMismatch = 11,
+    /// [`DsaError::CopyVerificationFailed`].
+    CopyVerificationFailed = 12,
+    /// [`DsaError::InvalidArgument`].
+    InvalidArgument = 13,
+    /// [`DsaError::BufferSizeMismatch`].
+    BufferSizeMismatch = 14,
+    /// [`DsaError::Io`].
+    Io = 15,
+    /// [`DsaError::PlatformNotSupported`].
+    PlatformNotSupported = 16,
+    /// [`DsaError::DeviceNotEnabled`].
+    DeviceNotEnabled = 17,
+    /// [`DsaError::PermissionDenied`].
+    PermissionDenied = 18,
+    /// [`DsaError::WorkQueueUnavailable`].
+    WorkQueueUnavailable = 19,
+    /// [`DsaError::WorkQueueDisabled`].
+    WorkQueueDisabled = 20,
+    /// [`DsaError::WorkQueueBusy`].
+    WorkQueueBusy = 21,
+    /// [`DsaError::MmapFailed`].
+    MmapFailed = 22,
+    /// [`DsaError::InterruptsUnsupported`].
+    InterruptsUnsupported = 23,
+    /// [`DsaError::NoInterruptHandle`].
+    NoInterruptHandle = 24,
+    /// [`DsaError::InvalidDescriptor`].
+    InvalidDescriptor = 25,
+}
+
+impl ErrorCode {
+    /// The numeric code.
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    /// Look up a numeric code.
+    ///
+    /// Returns `None` for zero and for codes not assigned by this version.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            1 => Self::NoDeviceFound,
+            2 => Self::NoWorkQueue,
+            3 => Self::QueueFull,
+            4 => Self::OperationFailed,
+            5 => Self::DifCheckFailed,
+            6 => Self::Timeout,
+            7 => Self::Cancelled,
+            8 => Self::DeadlineExceeded,
+            9 => Self::BatchFailed,
+            10 => Self::PageFault,
+            11 => Self::CrcMismatch,
+            12 => Self::CopyVerificationFailed,
+            13 => Self::InvalidArgument,
+            14 => Self::BufferSizeMismatch,
+            15 => Self::Io,
+            16 => Self::PlatformNotSupported,
+            17 => Self::DeviceNotEnabled,
+            18 => Self::PermissionDenied,
+            19 => Self::WorkQueueUnavailable,
+            20 => Self::WorkQueueDisabled,
+            21 => Self::WorkQueueBusy,
+            22 => Self::MmapFailed,
+            23 => Self::InterruptsUnsupported,
+            24 => Self::NoInterruptHandle,
+            25 => Self::InvalidDescriptor,
+            _ => return None,
+        })
+    }
 }
 
 /// Result type alias for DSA operations.
 pub type DsaResult<T> = Result<T, DsaError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_completed() {
+        let err = DsaError::Timeout { context: None };
+        assert_eq!(err.bytes_completed(), None);
+
+        let err = DsaError::OperationFailed {
+            status: 0x13,
+            result: 0,
+            bytes_completed: 128,
+            context: None,
+        };
+        assert_eq!(err.bytes_completed(), Some(128));
+        assert_eq!(err.descriptors_completed(), None);
+
+        let err = DsaError::QueueFull {
+            attempts: 1000,
+            elapsed: Duration::from_micros(50),
+            threshold: None,
+            occupancy: None,
+        };
+        assert_eq!(err.bytes_completed(), None);
+    }
+
+    #[test]
+    fn test_queue_full_retryable() {
+        let full = |threshold| DsaError::QueueFull {
+            attempts: 1000,
+            elapsed: Duration::from_micros(50),
+            threshold,
+            occupancy: Some(16),
+        };
+        assert!(full(None).is_retryable());
+        assert!(full(Some(16)).is_retryable());
+        assert!(!full(Some(0)).is_retryable());
+        assert!(!DsaError::NoWorkQueue.is_retryable());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(ErrorCode::from_code(0), None);
+        let mut code = 1;
+        while let Some(error_code) = ErrorCode::from_code(code) {
+            assert_eq!(error_code.code(), code);
+            code += 1;
+        }
+        assert_eq!(code, 26);
+
+        // Codes are part of the public interface and must not change
+        assert_eq!(DsaError::NoDeviceFound.code(), 1);
+        assert_eq!(
+            DsaError::PageFault {
+                fault_addr: 0,
+                bytes_completed: 0,
+                context: None
+            }
+            .code(),
+            10
+        );
+        assert_eq!(DsaError::Io(std::io::ErrorKind::Other.into()).code(), 15);
+        assert_eq!(DsaError::NoInterruptHandle.code(), 24);
+    }
+
+    #[test]
+    fn test_wq_unavailable_display() {
+        let err = DsaError::WorkQueueUnavailable {
+            name: "wq0.0".to_string(),
+            reason: WqUnavailableReason::KernelOwned {
+                wq_type: "kernel".to_string(),
+            },
+        };
+        assert_eq!(
+            err.to_string(),
+            "work queue wq0.0 unavailable: work queue is bound to the kernel driver, not user"
+        );
+    }
+
+    #[test]
+    fn test_error_context() {
+        use crate::opcode::DsaOpcode;
+
+        let err = DsaError::OperationFailed {
+            status: 0x13,
+            result: 0,
+            bytes_completed: 0,
+            context: None,
+        };
+        assert!(err.context().is_none());
+        let message = err.to_string();
+
+        let mut desc = DsaHwDesc::new();
+        desc.set_opcode(DsaOpcode::MemMove);
+        desc.xfer_size = 4096;
+        let context = ErrorContext::new(&desc, Some("wq0.0".to_string()), Duration::from_micros(5));
+        let err = err.with_context(|| context.clone());
+        assert_eq!(err.context(), Some(&context));
+        let (head, hint) = message.split_once("; hint").unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!("{head} (MEMMOVE (0x04), 4096 bytes on wq0.0, after 5µs); hint{hint}")
+        );
+        let device_error = SoftwareError::from_words([0x1F01, 0, 0, 0]);
+        let with_device = ErrorContext {
+            device_error,
+            ..context.clone()
+        };
+        assert!(with_device
+            .to_string()
+            .ends_with(", device error: status=0x1f (hardware error)"));
+
+        // An existing context is kept, and other errors carry none
+        let err = err.with_context(|| ErrorContext::new(&DsaHwDesc::new(), None, Duration::ZERO));
+        assert_eq!(err.context(), Some(&context));
+        let err = DsaError::Cancelled.with_context(|| context.clone());
+        assert!(err.context().is_none());
+    }
+
+    #[test]
+    fn test_status_hints() {
+        let err = DsaError::OperationFailed {
+            status: 0x13,
+            result: 0,
+            bytes_completed: 0,
+            context: None,
+        };
+        assert_eq!(
+            err.to_string(),
+            "DSA operation failed: status=0x13 (invalid transfer size), result=0x00, \
+             completed 0 bytes; hint: transfer is empty or exceeds max_transfer_size; \
+             enable chunking"
+        );
+        assert_eq!(err.hint(), status_hint(0x13));
+
+        // The write bit of a page fault status is ignored
+        assert_eq!(status_name(0x83), "page fault");
+        assert_eq!(status_name(0x01), "success");
+        assert_eq!(status_hint(0x01), None);
+        assert_eq!(status_name(0x7E), "unknown status");
+        assert_eq!(status_hint(0x7E), None);
+        assert_eq!(DsaError::Cancelled.hint(), None);
+
+        let err = DsaError::BatchFailed {
+            status: 0x05,
+            descriptors_completed: 1,
+            descriptors_total: 4,
+        };
+        assert_eq!(
+            err.to_string(),
+            "DSA batch failed: status=0x05 (batch descriptor failed), completed 1 of 4 descriptors"
+        );
+        assert!(err.hint().is_some());
+    }
+
+    #[test]
+    fn test_descriptors_completed() {
+        let err = DsaError::BatchFailed {
+            status: 0x05,
+            descriptors_completed: 7,
+            descriptors_total: 10,
+        };
+        assert_eq!(err.descriptors_completed(), Some(7));
+        assert_eq!(err.bytes_completed(), None);
+    }
+}
@@ -0,0 +1,413 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Fair sharing of a shared work queue between processes.
+//!
+//! A shared work queue accepts descriptors from every process until it is
+//! full, and then rejects them until some complete. Processes that submit
+//! in a tight loop keep it full, and the others spend their ENQCMD retries
+//! on a queue that is never free: they starve.
+//!
+//! With [`DsaEngine::set_fair_share`], cooperating processes count their
+//! descriptors in flight in a table in shared memory
+//! (`/dev/shm/dsa-rust-fair-<wq>`), one slot per engine. A descriptor is
+//! admitted only while the queue has room for it and the engine holds no
+//! more than its share: the capacity divided by the engines with
+//! descriptors in flight. An idle engine does not reduce the share of the
+//! others, so a single busy process can use the whole queue. The slots of
+//! processes that exited are reclaimed. Otherwise an engine waits like a
+//! serialized work queue does (see [`WorkQueue::set_serialized`]), and
+//! fails with `DsaError::QueueFull` if no room is found within the polling
+//! budget.
+//!
+//! Sharing is cooperative: processes that do not join are not throttled.
+//! Concurrent admissions may briefly exceed the capacity by a few
+//! descriptors, which ENQCMD then retries as usual. The table is created
+//! subject to the umask; processes of different users share it only if
+//! they can all open it. An engine on the software emulator uses a table
+//! private to itself. Fair sharing is available on Linux.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::fair::FairShare;
+//! use dsa_rust::DsaEngine;
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! // Share the work queue's capacity, as read from sysfs
+//! engine.set_fair_share(Some(FairShare::default()))?;
+//! let crc = engine.crc32(&[0u8; 4096])?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+//!
+//! [`WorkQueue::set_serialized`]: crate::wq::WorkQueue::set_serialized
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+
+/// Most engines sharing one work queue's table.
+pub const MAX_TENANTS: usize = 64;
+
+/// Capacity shared when sysfs reports none.
+pub const DEFAULT_FAIR_CAPACITY: u32 = crate::capabilities::DEFAULT_MAX_IN_FLIGHT;
+
+/// Configuration of fair sharing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FairShare {
+    /// Descriptors all cooperating engines keep in flight together. `None`
+    /// uses the work queue's
+    /// [`max_in_flight`](crate::Capabilities::max_in_flight): its
+    /// threshold, or its size, from sysfs, and [`DEFAULT_FAIR_CAPACITY`]
+    /// if sysfs reports neither.
+    pub capacity: Option<u32>,
+}
+
+#[cfg(dsa_portal)]
+pub(crate) use linux_impl::{ShareToken, Tenancy};
+
+#[cfg(dsa_portal)]
+mod linux_impl {
+    use super::MAX_TENANTS;
+    use crate::error::DsaError;
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::ptr::NonNull;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Marks a slot whose dead owner is being cleared.
+    const RECLAIMING: u32 = u32::MAX;
+
+    /// One engine's entry in the table.
+    #[repr(C)]
+    struct Slot {
+        /// Process owning the slot, or zero if free.
+        pid: AtomicU32,
+        /// Descriptors the owner has in flight.
+        in_flight: AtomicU32,
+    }
+
+    /// The table in shared memory. A new file is zero-filled, which is an
+    /// empty table.
+    #[repr(C)]
+    struct Table {
+        slots: [Slot; MAX_TENANTS],
+    }
+
+    /// An engine's slot in the table of a work queue.
+    pub(crate) struct Tenancy {
+        table: NonNull<Table>,
+        slot: usize,
+        capacity: u32,
+    }
+
+    // SAFETY: The table is only accessed through atomics
+    unsafe impl Send for Tenancy {}
+    unsafe impl Sync for Tenancy {}
+
+    impl Tenancy {
+        /// Take a slot in the table at `path`, creating it if needed, or in
+        /// a table private to this tenancy if `path` is `None`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the table cannot be opened or mapped, or
+        /// `DsaError::WorkQueueBusy` if all its slots are taken by live
+        /// processes.
+        pub(crate) fn join(path: Option<&Path>, capacity: u32) -> Result<Arc<Self>, DsaError> {
+            let size = std::mem::size_of::<Table>();
+            let addr = match path {
+                Some(path) => {
+                    let file = OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .create(true)
+                        .truncate(false)
+                        .mode(0o666)
+                        .open(path)?;
+                    if file.metadata()?.len() < size as u64 {
+                        file.set_len(size as u64)?;
+                    }
+                    // The mapping stays valid after the file is closed
+                    unsafe {
+                        libc::mmap(
+                            std::ptr::null_mut(),
+                            size,
+                            libc::PROT_READ | libc::PROT_WRITE,
+                            libc::MAP_SHARED,
+                            file.as_raw_fd(),
+                            0,
+                        )
+                    }
+                }
+                None => unsafe {
+                    libc::mmap(
+                        std::ptr::null_mut(),
+                        size,
+                        libc::PROT_READ | libc::PROT_WRITE,
+                        libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                },
+            };
+            let name = || path.map_or("private".into(), |path| path.display().to_string());
+            if addr == libc::MAP_FAILED {
+                return Err(DsaError::MmapFailed(format!(
+                    "cannot map fair share table {}",
+                    name()
+                )));
+            }
+            let table: NonNull<Table> = NonNull::new(addr.cast()).expect("mmap returned null");
+            let Some(slot) = claim(unsafe { &table.as_ref().slots }) else {
+                unsafe { libc::munmap(addr, size) };
+                return Err(DsaError::WorkQueueBusy(name()));
+            };
+            Ok(Arc::new(Self {
+                table,
+                slot,
+                capacity: capacity.max(1),
+            }))
+        }
+
+        fn slots(&self) -> &[Slot; MAX_TENANTS] {
+            unsafe { &self.table.as_ref().slots }
+        }
+
+        /// Free the slots of processes that exited.
+        pub(crate) fn reclaim(&self) {
+            reclaim(self.slots());
+        }
+
+        /// Descriptors all engines may keep in flight together.
+        pub(crate) fn capacity(&self) -> u32 {
+            self.capacity
+        }
+
+        /// Count a descriptor of this engine as in flight, if the queue has
+        /// room and the engine is within its share.
+        ///
+        /// # Errors
+        ///
+        /// Returns the number of descriptors in flight if there is no room.
+        pub(crate) fn try_acquire(self: &Arc<Self>) -> Result<ShareToken, u32> {
+            let mut busy = 1;
+            let mut total = 0;
+            for (i, slot) in self.slots().iter().enumerate() {
+                let in_flight = slot.in_flight.load(Ordering::Acquire);
+                total += in_flight;
+                if i != self.slot && in_flight > 0 {
+                    busy += 1;
+                }
+            }
+            let share = self.capacity.div_ceil(busy);
+            let own = &self.slots()[self.slot].in_flight;
+            if total >= self.capacity || own.load(Ordering::Acquire) >= share {
+                return Err(total);
+            }
+            own.fetch_add(1, Ordering::AcqRel);
+            Ok(ShareToken(Arc::clone(self)))
+        }
+    }
+
+    impl Drop for Tenancy {
+        fn drop(&mut self) {
+            let slot = &self.slots()[self.slot];
+            slot.in_flight.store(0, Ordering::Release);
+            slot.pid.store(0, Ordering::Release);
+            unsafe { libc::munmap(self.table.as_ptr().cast(), std::mem::size_of::<Table>()) };
+        }
+    }
+
+    /// Take a free slot, reclaiming those of exited processes if none is
+    /// free.
+    fn claim(slots: &[Slot; MAX_TENANTS]) -> Option<usize> {
+        let pid = std::process::id();
+        let take = |slot: &Slot| {
+            slot.pid
+                .compare_exchange(0, pid, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        };
+        slots.iter().position(take).or_else(|| {
+            reclaim(slots);
+            slots.iter().position(take)
+        })
+    }
+
+    /// Free the slots of processes that exited.
+    fn reclaim(slots: &[Slot; MAX_TENANTS]) {
+        for slot in slots {
+            let pid = slot.pid.load(Ordering::Acquire);
+            if pid == 0 || pid == RECLAIMING || is_alive(pid) {
+                continue;
+            }
+            // Nobody claims a slot while its count is being cleared
+            if slot
+                .pid
+                .compare_exchange(pid, RECLAIMING, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                slot.in_flight.store(0, Ordering::Release);
+                slot.pid.store(0, Ordering::Release);
+            }
+        }
+    }
+
+    /// A descriptor counted in a tenancy, uncounted when dropped.
+    pub(crate) struct ShareToken(Arc<Tenancy>);
+
+    impl Drop for ShareToken {
+        fn drop(&mut self) {
+            self.0.slots()[self.0.slot]
+                .in_flight
+                .fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Returns true if process `pid` exists.
+    fn is_alive(pid: u32) -> bool {
+        let Ok(pid) = libc::pid_t::try_from(pid) else {
+            return false;
+        };
+        // EPERM: it exists, but belongs to another user
+        let ret = unsafe { libc::kill(pid, 0) };
+        ret == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+
+    #[cfg(test)]
+    impl Tenancy {
+        /// Pretend process `pid` owns a free slot with `in_flight`
+        /// descriptors.
+        pub(crate) fn plant(&self, pid: u32, in_flight: u32) {
+            let slot = self
+                .slots()
+                .iter()
+                .find(|slot| slot.pid.load(Ordering::Acquire) == 0)
+                .unwrap();
+            slot.pid.store(pid, Ordering::Release);
+            slot.in_flight.store(in_flight, Ordering::Release);
+        }
+    }
+}
+
+impl DsaEngine {
+    /// Share the work queue fairly with other processes, or (with `None`)
+    /// stop sharing.
+    ///
+    /// See the [module documentation](self) for how capacity is shared.
+    /// Has no effect on platforms where operations complete synchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shared table cannot be opened or mapped, or
+    /// `DsaError::WorkQueueBusy` if [`MAX_TENANTS`] engines already share
+    /// the work queue.
+    pub fn set_fair_share(&mut self, share: Option<FairShare>) -> Result<(), DsaError> {
+        self.work_queue_mut().set_fair_share(share)
+    }
+
+    /// Get the fair sharing configuration, with the capacity in use, if the
+    /// work queue is shared fairly.
+    pub fn fair_share(&self) -> Option<FairShare> {
+        self.work_queue().fair_share()
+    }
+}
+
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A table path unique to this test.
+    fn table_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("dsa-rust-fair-{}-{test}", std::process::id()))
+    }
+
+    #[test]
+    fn test_busy_tenants_split_capacity() {
+        let path = table_path("split");
+        let a = Tenancy::join(Some(&path), 4).unwrap();
+        let b = Tenancy::join(Some(&path), 4).unwrap();
+
+        // Alone, a tenant may use the whole capacity
+        let mut held_a: Vec<_> = (0..4).map(|_| a.try_acquire().unwrap()).collect();
+        assert_eq!(a.try_acquire().err(), Some(4));
+        assert_eq!(b.try_acquire().err(), Some(4));
+
+        // Once both are busy, each gets half
+        held_a.truncate(2);
+        let held_b: Vec<_> = (0..2).map(|_| b.try_acquire().unwrap()).collect();
+        assert!(b.try_acquire().is_err());
+        assert!(a.try_acquire().is_err());
+
+        // When b goes idle, a may use the whole capacity again
+        drop(held_b);
+        held_a.extend((0..2).map(|_| a.try_acquire().unwrap()));
+        assert!(a.try_acquire().is_err());
+
+        drop(held_a);
+        drop(a);
+        drop(b);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_exited_tenants_are_reclaimed() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead = child.id();
+        child.wait().unwrap();
+
+        let tenancy = Tenancy::join(None, 2).unwrap();
+        tenancy.plant(dead, 2);
+        assert_eq!(tenancy.try_acquire().err(), Some(2));
+        tenancy.reclaim();
+        assert!(tenancy.try_acquire().is_ok());
+
+        // Live processes keep their slots
+        tenancy.plant(1, 2);
+        tenancy.reclaim();
+        assert!(tenancy.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_engine_fair_share() {
+        use crate::callback::DsaOp;
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        assert_eq!(engine.fair_share(), None);
+        engine
+            .set_fair_share(Some(FairShare { capacity: Some(2) }))
+            .unwrap();
+        assert_eq!(engine.fair_share().unwrap().capacity, Some(2));
+        engine.work_queue_mut().set_spin_iterations(100);
+
+        // Blocking operations give their place back
+        for _ in 0..4 {
+            engine.noop().unwrap();
+        }
+
+        // Operations that never complete hold theirs
+        emulator.inject(Fault::Stall);
+        emulator.inject(Fault::Stall);
+        engine.submit_with_callback(DsaOp::Noop, |_, _| {});
+        engine.submit_with_callback(DsaOp::Noop, |_, _| {});
+        assert!(matches!(
+            engine.noop(),
+            Err(DsaError::QueueFull {
+                threshold: Some(2),
+                occupancy: Some(2),
+                ..
+            })
+        ));
+
+        engine.set_fair_share(None).unwrap();
+        engine.noop().unwrap();
+    }
+}
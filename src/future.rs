@@ -0,0 +1,311 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Executor-agnostic completion futures.
+//!
+//! [`DsaFuture`] resolves when the hardware writes the operation's completion
+//! record. It relies only on `std::task::Waker`: the completion reactor
+//! thread wakes the task, so the future works on any executor (tokio,
+//! async-std, smol, or a hand-written `block_on`).
+//!
+//! The future does not own what the hardware accesses. The completion
+//! record and the operation's buffers live in a heap allocation owned by
+//! the reactor until the hardware is done, and the future only shares it.
+//! Dropping a pending future therefore never blocks, and forgetting it with
+//! [`std::mem::forget`] merely leaks that allocation once the operation
+//! has completed.
+
+use crate::descriptor::DsaCompletionRecord;
+use crate::error::DsaError;
+use crate::reactor::InFlight;
+use std::any::Any;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[cfg(dsa_portal)]
+use crate::{descriptor::DsaHwDesc, wq::WorkQueue};
+
+/// Extracts the result from a successfully completed record and the
+/// buffers handed back by the reactor.
+type Finish<T> = Box<dyn FnOnce(&DsaCompletionRecord, Option<Box<dyn Any + Send>>) -> T + Send>;
+
+enum State<T> {
+    /// Result already known (software path or submission failure).
+    Ready(Option<Result<T, DsaError>>),
+    /// Descriptor submitted; waiting for the reactor.
+    Pending {
+        op: Arc<InFlight>,
+        finish: Finish<T>,
+    },
+}
+
+/// Future for a DSA operation.
+///
+/// The operation owns its buffers, which are handed back in the output
+/// where the caller needs them (the destination of a copy or fill).
+/// Dropping a pending future detaches the operation: it still runs to
+/// completion, after which the reactor releases its buffers.
+#[must_use = "futures do nothing unless polled"]
+pub struct DsaFuture<T> {
+    state: State<T>,
+}
+
+// The future never hands out pinned references to its fields.
+impl<T> Unpin for DsaFuture<T> {}
+
+impl<T> DsaFuture<T> {
+    /// Create a future that resolves immediately with `result`.
+    pub(crate) fn ready(result: Result<T, DsaError>) -> Self {
+        Self {
+            state: State::Ready(Some(result)),
+        }
+    }
+
+    /// Create a future for a submitted operation without buffers.
+    ///
+    /// `finish` extracts the result from a successfully completed record.
+    pub(crate) fn submitted(
+        op: Result<Arc<InFlight>, DsaError>,
+        finish: fn(&DsaCompletionRecord) -> T,
+    ) -> Self
+    where
+        T: 'static,
+    {
+        match op {
+            Ok(op) => Self {
+                state: State::Pending {
+                    op,
+                    finish: Box::new(move |record, _| finish(record)),
+                },
+            },
+            Err(e) => Self::ready(Err(e)),
+        }
+    }
+
+    /// Submit the descriptor `build` makes for `buffers` to `wq`.
+    ///
+    /// The buffers are moved to the heap before the descriptor is built,
+    /// so it may point into them, and are held by the reactor until the
+    /// hardware is done. `finish` extracts the result from a successfully
+    /// completed record and the buffers.
+    #[cfg(dsa_portal)]
+    pub(crate) fn start<B>(
+        wq: &WorkQueue,
+        buffers: B,
+        build: impl FnOnce(&mut B, &mut DsaCompletionRecord) -> DsaHwDesc,
+        finish: fn(&DsaCompletionRecord, B) -> T,
+    ) -> Self
+    where
+        B: Send + 'static,
+        T: 'static,
+    {
+        let op = InFlight::new();
+        let mut buffers = Box::new(buffers);
+        let desc = build(&mut buffers, op.record_mut());
+        op.hold(buffers);
+        let finish: Finish<T> = Box::new(move |record, buffers| {
+            let buffers = buffers.and_then(|buffers| buffers.downcast::<B>().ok());
+            finish(
+                record,
+                *buffers.expect("completed operations keep their buffers"),
+            )
+        });
+        match wq.start_in_flight(&op, &desc) {
+            Ok(()) => Self {
+                state: State::Pending { op, finish },
+            },
+            Err(e) => Self::ready(Err(e)),
+        }
+    }
+}
+
+impl<T> Future for DsaFuture<T> {
+    type Output = Result<T, DsaError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match &mut this.state {
+            State::Ready(result) => {
+                Poll::Ready(result.take().expect("DsaFuture polled after completion"))
+            }
+            State::Pending { op, .. } => {
+                if !op.register(cx.waker()) {
+                    return Poll::Pending;
+                }
+                let State::Pending { op, finish } =
+                    std::mem::replace(&mut this.state, State::Ready(None))
+                else {
+                    unreachable!()
+                };
+                let result = op
+                    .outcome()
+                    .and_then(|record| {
+                        crate::wq::check_completion(record)
+                            .map(|()| finish(record, op.take_buffers()))
+                    })
+                    .map_err(|e| op.add_context(e));
+                Poll::Ready(result)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reactor::Reactor;
+    use std::task::{Wake, Waker};
+    use std::thread::Thread;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor: poll on the current thread, park until woken.
+    fn block_on<F: Future + Unpin>(mut fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(out) = Pin::new(&mut fut).poll(&mut cx) {
+                return out;
+            }
+            std::thread::park();
+        }
+    }
+
+    #[test]
+    fn test_ready_future() {
+        let fut: DsaFuture<u32> = DsaFuture::ready(Ok(7));
+        assert_eq!(block_on(fut).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_pending_future_woken_by_reactor() {
+        let op = InFlight::new();
+        Reactor::global().register(Arc::clone(&op));
+        let fut = DsaFuture::submitted(Ok(Arc::clone(&op)), |record| record.crc32_result());
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            let record = op.record_mut();
+            record.result_value = 0xABCD;
+            unsafe { std::ptr::write_volatile(&mut record.status, 0x01) };
+        });
+
+        assert_eq!(block_on(fut).unwrap(), 0xABCD);
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_failed_completion() {
+        let op = InFlight::new();
+        Reactor::global().register(Arc::clone(&op));
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x13) };
+
+        let fut = DsaFuture::submitted(Ok(op), |_| ());
+        assert!(matches!(
+            block_on(fut),
+            Err(DsaError::OperationFailed { status: 0x13, .. })
+        ));
+    }
+
+    /// A pending future holding `buffers`, and its operation.
+    fn pending(buffers: Arc<Vec<u8>>) -> (DsaFuture<()>, Arc<InFlight>) {
+        let op = InFlight::new();
+        op.hold(Box::new(buffers));
+        let fut = DsaFuture {
+            state: State::Pending {
+                op: Arc::clone(&op),
+                finish: Box::new(|_, _| ()),
+            },
+        };
+        (fut, op)
+    }
+
+    /// Wait until only `expected` references to `value` are left.
+    fn settle<T>(value: &Arc<T>, expected: usize) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while Arc::strong_count(value) != expected && std::time::Instant::now() < deadline {
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_dropped_future_detaches() {
+        let buffers = Arc::new(vec![0u8; 64]);
+        let (fut, op) = pending(Arc::clone(&buffers));
+        Reactor::global().register(Arc::clone(&op));
+
+        // Dropping does not wait; the reactor keeps the buffers
+        drop(fut);
+        assert_eq!(Arc::strong_count(&buffers), 2);
+
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x01) };
+        op.wait();
+        drop(op);
+        settle(&buffers, 1);
+        assert_eq!(Arc::strong_count(&buffers), 1);
+    }
+
+    #[test]
+    fn test_forgotten_future_leaks() {
+        let buffers = Arc::new(vec![0u8; 64]);
+        let (fut, op) = pending(Arc::clone(&buffers));
+        Reactor::global().register(Arc::clone(&op));
+        std::mem::forget(fut);
+
+        // Once the reactor lets go, the forgotten reference keeps the
+        // operation and its buffers
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x01) };
+        op.wait();
+        settle(&op, 2);
+        assert_eq!(Arc::strong_count(&op), 2);
+        assert_eq!(Arc::strong_count(&buffers), 2);
+    }
+
+    #[test]
+    fn test_failed_operation_keeps_buffers() {
+        let buffers = Arc::new(vec![0u8; 64]);
+        let (fut, op) = pending(Arc::clone(&buffers));
+        op.fail(DsaError::WorkQueueDisabled("wq0.0".to_string()));
+        assert!(matches!(block_on(fut), Err(DsaError::WorkQueueDisabled(_))));
+        drop(op);
+
+        // The hardware may still access them
+        assert_eq!(Arc::strong_count(&buffers), 2);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_owned_buffers() {
+        use crate::emulator::{Emulator, Fault};
+        use crate::engine::DsaEngine;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let src: Arc<[u8]> = (0..5000).map(|i| (i % 251) as u8).collect();
+
+        let dst = block_on(engine.memcpy_async(vec![0u8; 5000], Arc::clone(&src))).unwrap();
+        assert_eq!(dst, &src[..]);
+        let dst = block_on(engine.memset_async(dst, 0x0101_0101_0101_0101)).unwrap();
+        assert!(dst.iter().all(|&b| b == 1));
+        assert!(!block_on(engine.memcmp_async(dst, Arc::clone(&src))).unwrap());
+        assert_eq!(
+            block_on(engine.crc32_async(Arc::clone(&src))).unwrap(),
+            crate::crc::software_crc32(&src, 0)
+        );
+
+        // A stalled copy outlives its forgotten future without the source
+        // being released
+        emulator.inject(Fault::Stall);
+        std::mem::forget(engine.memcpy_async(vec![0u8; 5000], Arc::clone(&src)));
+        assert_eq!(Arc::strong_count(&src), 2);
+    }
+}
@@ -0,0 +1,392 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Group quality-of-service configuration.
+//!
+//! A DSA device divides its work queues and engines into groups. Each group
+//! has two traffic classes for its memory accesses and a share of the
+//! device's read buffers, which bounds how much read bandwidth its engines
+//! can take. Placing bulk and latency-sensitive work queues in separate
+//! groups with different settings keeps a bulk copy from starving small
+//! operations on the same device.
+//!
+//! [`DsaDevice::groups`] reads the groups of a device from sysfs and
+//! [`DsaDevice::configure_group`] writes a [`GroupConfig`], the way
+//! `accel-config config-group` does. The driver only accepts group
+//! settings while the device is disabled, and writing sysfs needs root.
+//! Kernels before 5.17 name read buffers "tokens"; both names are
+//! understood.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::group::GroupConfig;
+//! use dsa_rust::discover_devices;
+//!
+//! let device = &discover_devices()?[0];
+//! // Group 0 serves latency-sensitive work queues, group 1 bulk copies
+//! device.configure_group(0, &GroupConfig {
+//!     read_buffers_reserved: Some(8),
+//!     ..GroupConfig::default()
+//! })?;
+//! device.configure_group(1, &GroupConfig {
+//!     traffic_class_a: Some(1),
+//!     traffic_class_b: Some(1),
+//!     read_buffers_allowed: Some(16),
+//!     use_read_buffer_limit: Some(true),
+//!     ..GroupConfig::default()
+//! })?;
+//! device.set_read_buffer_limit(16)?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::device::DsaDevice;
+use crate::error::DsaError;
+
+#[cfg(dsa_portal)]
+use std::fs;
+#[cfg(dsa_portal)]
+use std::path::Path;
+
+/// Highest traffic class a group accepts.
+pub const MAX_TRAFFIC_CLASS: u8 = 7;
+
+/// A group of a device as configured in sysfs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    /// Group name (e.g., "group0.1").
+    pub name: String,
+    /// Index of the group within its device.
+    pub id: u32,
+    /// Names of the work queues in the group.
+    pub work_queues: Vec<String>,
+    /// Names of the engines in the group.
+    pub engines: Vec<String>,
+    /// Traffic class A.
+    pub traffic_class_a: Option<u8>,
+    /// Traffic class B.
+    pub traffic_class_b: Option<u8>,
+    /// Read buffers reserved for the group.
+    pub read_buffers_reserved: Option<u32>,
+    /// Maximum read buffers the group may use.
+    pub read_buffers_allowed: Option<u32>,
+    /// Whether the group is subject to the device's read buffer limit.
+    pub use_read_buffer_limit: Option<bool>,
+}
+
+/// Group settings to write; `None` leaves a setting as it is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupConfig {
+    /// Traffic class A (0-7).
+    pub traffic_class_a: Option<u8>,
+    /// Traffic class B (0-7).
+    pub traffic_class_b: Option<u8>,
+    /// Read buffers reserved for the group.
+    pub read_buffers_reserved: Option<u32>,
+    /// Maximum read buffers the group may use.
+    pub read_buffers_allowed: Option<u32>,
+    /// Subject the group to the device's read buffer limit.
+    pub use_read_buffer_limit: Option<bool>,
+}
+
+impl GroupConfig {
+    /// Check the settings against each other.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if a traffic class exceeds
+    /// [`MAX_TRAFFIC_CLASS`] or more read buffers are reserved than allowed.
+    pub fn validate(&self) -> Result<(), DsaError> {
+        for tc in [self.traffic_class_a, self.traffic_class_b]
+            .into_iter()
+            .flatten()
+        {
+            if tc > MAX_TRAFFIC_CLASS {
+                return Err(DsaError::InvalidArgument(format!(
+                    "traffic class {tc} exceeds {MAX_TRAFFIC_CLASS}"
+                )));
+            }
+        }
+        if let (Some(reserved), Some(allowed)) =
+            (self.read_buffers_reserved, self.read_buffers_allowed)
+        {
+            if reserved > allowed {
+                return Err(DsaError::InvalidArgument(format!(
+                    "{reserved} read buffers reserved but only {allowed} allowed"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DsaDevice {
+    /// Get the groups of this device, ordered by index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sysfs cannot be read, or
+    /// `DsaError::PlatformNotSupported` on platforms other than Linux.
+    #[cfg(dsa_portal)]
+    pub fn groups(&self) -> Result<Vec<GroupInfo>, DsaError> {
+        let prefix = format!("group{}.", self.number());
+        let mut groups = Vec::new();
+        for entry in fs::read_dir(self.bus_path())? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let Some(id) = name.strip_prefix(&prefix).and_then(|id| id.parse().ok()) else {
+                continue;
+            };
+            groups.push(read_group(&self.bus_path().join(&name), name, id));
+        }
+        groups.sort_by_key(|group| group.id);
+        Ok(groups)
+    }
+
+    /// Get the groups of this device.
+    #[cfg(not(dsa_portal))]
+    pub fn groups(&self) -> Result<Vec<GroupInfo>, DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+
+    /// Write the settings in `config` to group `group` of this device.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Index of the group within the device
+    /// * `config` - Settings to write; unset fields are left as they are
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `config` is invalid, the
+    /// group does not exist or the device is enabled, `DsaError::Io` if
+    /// the driver rejects a setting (for example without root), or
+    /// `DsaError::PlatformNotSupported` on platforms other than Linux.
+    #[cfg(dsa_portal)]
+    pub fn configure_group(&self, group: u32, config: &GroupConfig) -> Result<(), DsaError> {
+        config.validate()?;
+        self.check_disabled()?;
+        let path = self
+            .bus_path()
+            .join(format!("group{}.{group}", self.number()));
+        if !path.exists() {
+            return Err(DsaError::InvalidArgument(format!(
+                "{} has no group {group}",
+                self.name
+            )));
+        }
+        let tc = |tc: Option<u8>| tc.map(u32::from);
+        let flag = |flag: Option<bool>| flag.map(u32::from);
+        write_attr(&path, &["traffic_class_a"], tc(config.traffic_class_a))?;
+        write_attr(&path, &["traffic_class_b"], tc(config.traffic_class_b))?;
+        write_attr(
+            &path,
+            &["read_buffers_allowed", "tokens_allowed"],
+            config.read_buffers_allowed,
+        )?;
+        write_attr(
+            &path,
+            &["read_buffers_reserved", "tokens_reserved"],
+            config.read_buffers_reserved,
+        )?;
+        write_attr(
+            &path,
+            &["use_read_buffer_limit", "use_token_limit"],
+            flag(config.use_read_buffer_limit),
+        )
+    }
+
+    /// Write group settings.
+    #[cfg(not(dsa_portal))]
+    pub fn configure_group(&self, _group: u32, _config: &GroupConfig) -> Result<(), DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+
+    /// Set the device-wide limit on read buffers that groups with
+    /// [`GroupConfig::use_read_buffer_limit`] may use.
+    ///
+    /// # Errors
+    ///
+    /// As for [`DsaDevice::configure_group`].
+    #[cfg(dsa_portal)]
+    pub fn set_read_buffer_limit(&self, limit: u32) -> Result<(), DsaError> {
+        self.check_disabled()?;
+        write_attr(
+            &self.sysfs_path,
+            &["read_buffer_limit", "token_limit"],
+            Some(limit),
+        )
+    }
+
+    /// Set the device-wide read buffer limit.
+    #[cfg(not(dsa_portal))]
+    pub fn set_read_buffer_limit(&self, _limit: u32) -> Result<(), DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+
+    /// Directory holding the device's entry and those of its groups.
+    #[cfg(dsa_portal)]
+    fn bus_path(&self) -> &Path {
+        self.sysfs_path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Index of the device (0 for "dsa0").
+    #[cfg(dsa_portal)]
+    fn number(&self) -> u32 {
+        let digits = self.name.trim_start_matches(|c: char| !c.is_ascii_digit());
+        digits.parse().unwrap_or(0)
+    }
+
+    /// Fail unless the device is disabled, which the driver requires for
+    /// configuration.
+    #[cfg(dsa_portal)]
+    fn check_disabled(&self) -> Result<(), DsaError> {
+        let state = fs::read_to_string(self.sysfs_path.join("state")).unwrap_or_default();
+        match state.trim() {
+            "enabled" => Err(DsaError::InvalidArgument(format!(
+                "{} must be disabled to be configured",
+                self.name
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Read the group whose sysfs entry is `path`.
+#[cfg(dsa_portal)]
+fn read_group(path: &Path, name: String, id: u32) -> GroupInfo {
+    let read = |attrs: &[&str]| {
+        attrs
+            .iter()
+            .find_map(|attr| fs::read_to_string(path.join(attr)).ok())
+            .map(|value| value.trim().to_string())
+    };
+    let number = |attrs: &[&str]| read(attrs).and_then(|value| value.parse::<u32>().ok());
+    let list = |attr: &str| {
+        read(&[attr])
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    GroupInfo {
+        name,
+        id,
+        work_queues: list("work_queues"),
+        engines: list("engines"),
+        traffic_class_a: number(&["traffic_class_a"]).and_then(|tc| u8::try_from(tc).ok()),
+        traffic_class_b: number(&["traffic_class_b"]).and_then(|tc| u8::try_from(tc).ok()),
+        read_buffers_reserved: number(&["read_buffers_reserved", "tokens_reserved"]),
+        read_buffers_allowed: number(&["read_buffers_allowed", "tokens_allowed"]),
+        use_read_buffer_limit: number(&["use_read_buffer_limit", "use_token_limit"])
+            .map(|flag| flag != 0),
+    }
+}
+
+/// Write `value`, if any, to the first of `attrs` that exists under `path`.
+#[cfg(dsa_portal)]
+fn write_attr(path: &Path, attrs: &[&str], value: Option<u32>) -> Result<(), DsaError> {
+    let Some(value) = value else {
+        return Ok(());
+    };
+    let attr = attrs
+        .iter()
+        .map(|attr| path.join(attr))
+        .find(|attr| attr.exists())
+        .ok_or_else(|| {
+            DsaError::InvalidArgument(format!("{} has no {} attribute", path.display(), attrs[0]))
+        })?;
+    fs::write(attr, format!("{value}\n"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(GroupConfig::default().validate().is_ok());
+        let config = GroupConfig {
+            traffic_class_b: Some(8),
+            ..GroupConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = GroupConfig {
+            read_buffers_reserved: Some(9),
+            read_buffers_allowed: Some(8),
+            ..GroupConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_configure_group() {
+        let root = std::env::temp_dir().join(format!("dsa-group-{}", std::process::id()));
+        let attrs = [
+            ("dsa1/state", "disabled"),
+            ("dsa1/token_limit", "0"),
+            ("group1.0/work_queues", "wq1.0 wq1.1"),
+            ("group1.0/engines", "engine1.0"),
+            ("group1.0/traffic_class_a", "1"),
+            ("group1.0/traffic_class_b", "1"),
+            ("group1.0/tokens_reserved", "0"),
+            ("group1.0/tokens_allowed", "8"),
+            ("group1.0/use_token_limit", "0"),
+            ("group1.1/traffic_class_a", "0"),
+            ("group0.0/traffic_class_a", "0"),
+        ];
+        for (attr, value) in attrs {
+            let path = root.join(attr);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, format!("{value}\n")).unwrap();
+        }
+        let device = DsaDevice {
+            name: "dsa1".to_string(),
+            sysfs_path: root.join("dsa1"),
+            work_queues: Vec::new(),
+            state: "disabled".to_string(),
+            pasid_enabled: true,
+            max_groups: 4,
+            max_engines: 4,
+            max_work_queues: 8,
+            version: 0x100,
+            pci_address: None,
+        };
+
+        let groups = device.groups().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].work_queues, ["wq1.0", "wq1.1"]);
+        assert_eq!(groups[0].read_buffers_allowed, Some(8));
+        assert_eq!(groups[1].name, "group1.1");
+        assert_eq!(groups[1].engines, Vec::<String>::new());
+
+        device
+            .configure_group(
+                0,
+                &GroupConfig {
+                    traffic_class_b: Some(3),
+                    read_buffers_reserved: Some(4),
+                    use_read_buffer_limit: Some(true),
+                    ..GroupConfig::default()
+                },
+            )
+            .unwrap();
+        device.set_read_buffer_limit(12).unwrap();
+        let group = &device.groups().unwrap()[0];
+        assert_eq!(group.traffic_class_a, Some(1));
+        assert_eq!(group.traffic_class_b, Some(3));
+        assert_eq!(group.read_buffers_reserved, Some(4));
+        assert_eq!(group.use_read_buffer_limit, Some(true));
+        let limit = fs::read_to_string(root.join("dsa1/token_limit")).unwrap();
+        assert_eq!(limit, "12\n");
+
+        assert!(device.configure_group(2, &GroupConfig::default()).is_err());
+        // An enabled device is not configured
+        fs::write(root.join("dsa1/state"), "enabled\n").unwrap();
+        assert!(matches!(
+            device.set_read_buffer_limit(8),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,708 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Intel In-Memory Analytics Accelerator (IAA) support.
+//!
+//! IAA devices (`iax0`, `iax1`, ...) are driven by the same IDXD driver as
+//! DSA devices: their work queues sit on the same bus, are opened and
+//! mapped the same way, and take 64-byte descriptors through the same
+//! portal. A [`WorkQueue`](crate::WorkQueue) opened on an IAA work queue
+//! therefore submits IAA descriptors with the usual machinery; only the
+//! opcodes, the operation fields ([`IaaDesc`]) and the completion record
+//! ([`IaaCompletionRecord`]) differ.
+//!
+//! Three operations are supported:
+//!
+//! - **CRC64** with any polynomial, see [`Crc64Params`]
+//! - **Compress** to a raw DEFLATE (RFC 1951) stream with the fixed Huffman
+//!   codes
+//! - **Decompress** of any raw DEFLATE stream
+//!
+//! Requires the `iaa` feature. The [`Emulator`](crate::emulator::Emulator)
+//! executes IAA descriptors too; its Compress emits literals only, which is
+//! valid DEFLATE but does not shrink the data.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::iaa::Crc64Params;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first_iaa()?;
+//! let data = vec![7u8; 64 << 10];
+//! let crc = engine.crc64(&data, &Crc64Params::NVME)?;
+//!
+//! let mut packed = vec![0u8; data.len() + 1024];
+//! let size = engine.compress(&mut packed, &data)?;
+//! let mut unpacked = vec![0u8; data.len()];
+//! assert_eq!(engine.decompress(&mut unpacked, &packed[..size])?, data.len());
+//! assert_eq!(engine.crc64(&unpacked, &Crc64Params::NVME)?, crc);
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::deflate::{self, DIST_SYMBOLS, LITLEN_SYMBOLS};
+use crate::descriptor::{DescriptorFlags, DsaCompletionRecord, DsaHwDesc};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::sync::OnceLock;
+
+/// IAA operation codes supported by this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum IaaOpcode {
+    /// Decompress a DEFLATE stream.
+    Decompress = 0x42,
+    /// Compress to a DEFLATE stream.
+    Compress = 0x43,
+    /// Compute a CRC64.
+    Crc64 = 0x44,
+}
+
+impl IaaOpcode {
+    /// Get the opcode as a u8.
+    #[inline]
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Get the human-readable name of the operation.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Decompress => "Decompress",
+            Self::Compress => "Compress",
+            Self::Crc64 => "CRC64",
+        }
+    }
+}
+
+impl TryFrom<u8> for IaaOpcode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x42 => Ok(Self::Decompress),
+            0x43 => Ok(Self::Compress),
+            0x44 => Ok(Self::Crc64),
+            other => Err(other),
+        }
+    }
+}
+
+/// Completion status of an analytics operation that failed; the reason is
+/// in [`IaaCompletionRecord::error_code`].
+pub const ANALYTICS_ERROR: u8 = 0x0A;
+
+/// Error code of a Decompress whose output did not fit the destination.
+pub const ERROR_DECOMP_BUF_OVERFLOW: u8 = 0x0B;
+
+/// Error code of a Compress whose output did not fit the destination.
+pub const ERROR_COMP_BUF_OVERFLOW: u8 = 0x19;
+
+/// Compress flag: write out all output bits, padding the last byte.
+const COMPRESS_FLUSH_OUTPUT: u16 = 1 << 1;
+/// Compress flag: end the block with the end-of-block code.
+const COMPRESS_APPEND_EOB: u16 = 1 << 2;
+
+/// Decompress flag: decompression is enabled.
+const DECOMPRESS_ENABLE: u16 = 1 << 0;
+/// Decompress flag: write out all output bits.
+const DECOMPRESS_FLUSH_OUTPUT: u16 = 1 << 1;
+/// Decompress flag: fail if the input does not end with a final block.
+const DECOMPRESS_CHECK_FOR_EOB: u16 = 1 << 2;
+/// Decompress flag: stop at the end of the final block.
+const DECOMPRESS_STOP_ON_EOB: u16 = 1 << 3;
+
+/// CRC64 flag: process bits most significant first.
+const CRC64_MSB_FIRST: u16 = 1 << 15;
+/// CRC64 flag: invert the CRC before and after.
+const CRC64_INVERT: u16 = 1 << 14;
+
+/// 64-byte IAA completion record.
+///
+/// # Layout
+///
+/// | Offset | Size | Field |
+/// |--------|------|-------|
+/// | 0 | 1 | status |
+/// | 1 | 1 | error_code |
+/// | 2 | 1 | fault_info |
+/// | 4 | 4 | bytes_completed |
+/// | 8 | 8 | fault_addr |
+/// | 16 | 4 | invalid_flags |
+/// | 24 | 4 | output_size |
+/// | 28 | 1 | output_bits |
+/// | 30 | 2 | xor_checksum |
+/// | 32 | 8 | crc |
+///
+/// The first 16 bytes match [`DsaCompletionRecord`], so completions are
+/// polled and checked the same way. IAA requires 64-byte alignment.
+#[derive(Debug, Clone, Copy)]
+#[repr(C, align(64))]
+pub struct IaaCompletionRecord {
+    /// Completion status (non-zero when complete).
+    pub status: u8,
+    /// Error code of an [`ANALYTICS_ERROR`].
+    pub error_code: u8,
+    /// Fault information flags.
+    pub fault_info: u8,
+    /// Reserved.
+    reserved1: u8,
+    /// Number of source bytes processed.
+    pub bytes_completed: u32,
+    /// Fault address (if a page fault occurred).
+    pub fault_addr: u64,
+    /// Flags the device found invalid.
+    pub invalid_flags: u32,
+    /// Reserved.
+    reserved2: u32,
+    /// Bytes written to the destination.
+    pub output_size: u32,
+    /// Valid bits in the last output byte, if output was not flushed.
+    pub output_bits: u8,
+    /// Reserved.
+    reserved3: u8,
+    /// XOR checksum of the uncompressed data.
+    pub xor_checksum: u16,
+    /// CRC64 result, or the CRC32 of the uncompressed data in the low 32
+    /// bits for Compress and Decompress.
+    pub crc: u64,
+    /// Reserved.
+    reserved4: [u8; 24],
+}
+
+impl IaaCompletionRecord {
+    /// Create a new zeroed completion record.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            status: 0,
+            error_code: 0,
+            fault_info: 0,
+            reserved1: 0,
+            bytes_completed: 0,
+            fault_addr: 0,
+            invalid_flags: 0,
+            reserved2: 0,
+            output_size: 0,
+            output_bits: 0,
+            reserved3: 0,
+            xor_checksum: 0,
+            crc: 0,
+            reserved4: [0; 24],
+        }
+    }
+
+    /// View the record as a DSA completion record, whose status, result
+    /// (error code), bytes completed and fault address are at the same
+    /// offsets.
+    pub(crate) fn as_dsa(&self) -> &DsaCompletionRecord {
+        // SAFETY: both are 64-byte repr(C) records of plain integers, and
+        // this one is aligned to 64, more than the 32 DSA records need
+        unsafe { &*(self as *const Self as *const DsaCompletionRecord) }
+    }
+}
+
+impl Default for IaaCompletionRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters of a CRC64.
+///
+/// The polynomial is given without the x^64 term, most significant bit
+/// first, as in CRC catalogues, also for CRCs computed least significant
+/// bit first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crc64Params {
+    /// Generator polynomial.
+    pub poly: u64,
+    /// Process the bits of each byte most significant first; otherwise the
+    /// CRC is reflected.
+    pub msb_first: bool,
+    /// Start from all ones and invert the result.
+    pub invert: bool,
+}
+
+impl Crc64Params {
+    /// CRC-64/ECMA-182.
+    pub const ECMA_182: Self = Self {
+        poly: 0x42F0_E1EB_A9EA_3693,
+        msb_first: true,
+        invert: false,
+    };
+
+    /// CRC-64/XZ, as used by xz and 7-Zip.
+    pub const XZ: Self = Self {
+        poly: 0x42F0_E1EB_A9EA_3693,
+        msb_first: false,
+        invert: true,
+    };
+
+    /// CRC-64/NVME, the CRC of NVMe 64-bit protection information.
+    pub const NVME: Self = Self {
+        poly: 0xAD93_D235_94C9_3659,
+        msb_first: false,
+        invert: true,
+    };
+
+    /// CRC-64/GO-ISO.
+    pub const GO_ISO: Self = Self {
+        poly: 0x1B,
+        msb_first: false,
+        invert: true,
+    };
+
+    /// The IAA CRC64 flags selecting these parameters.
+    fn flags(&self) -> u16 {
+        let mut flags = 0;
+        if self.msb_first {
+            flags |= CRC64_MSB_FIRST;
+        }
+        if self.invert {
+            flags |= CRC64_INVERT;
+        }
+        flags
+    }
+}
+
+/// Compute a CRC64 in software.
+///
+/// Bitwise and slow; meant for checking results and for small buffers.
+pub fn software_crc64(data: &[u8], params: &Crc64Params) -> u64 {
+    let init = if params.invert { u64::MAX } else { 0 };
+    let mut crc = init;
+    if params.msb_first {
+        for &byte in data {
+            crc ^= (byte as u64) << 56;
+            for _ in 0..8 {
+                crc = if crc >> 63 != 0 {
+                    crc << 1 ^ params.poly
+                } else {
+                    crc << 1
+                };
+            }
+        }
+    } else {
+        let poly = params.poly.reverse_bits();
+        for &byte in data {
+            crc ^= byte as u64;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    crc >> 1 ^ poly
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+    }
+    crc ^ init
+}
+
+/// Size of the compression state read by Compress.
+pub const COMPRESS_AECS_SIZE: usize = 1568;
+
+/// Analytics engine configuration and state (AECS) of Compress.
+///
+/// Holds the bits to emit before the compressed data, and the Huffman code
+/// of every literal/length and distance symbol as `(length << 15) | code`.
+#[derive(Clone)]
+#[repr(C, align(32))]
+pub(crate) struct CompressAecs {
+    /// CRC32 of the data compressed so far.
+    pub(crate) crc: u32,
+    /// XOR checksum of the data compressed so far.
+    pub(crate) xor_checksum: u32,
+    reserved0: [u32; 5],
+    /// Number of valid bits in `output_accum`.
+    pub(crate) num_output_accum_bits: u32,
+    /// Bits emitted before the compressed data, least significant first.
+    pub(crate) output_accum: [u8; 256],
+    /// Literal/length codes.
+    pub(crate) ll_sym: [u32; LITLEN_SYMBOLS],
+    reserved1: [u32; 2],
+    /// Distance codes.
+    pub(crate) d_sym: [u32; DIST_SYMBOLS],
+    reserved2: [u32; 2],
+}
+
+impl CompressAecs {
+    /// The state that compresses into one final block with the fixed
+    /// Huffman codes.
+    fn fixed() -> Self {
+        let (ll_sym, d_sym) = deflate::fixed_entries();
+        let mut output_accum = [0; 256];
+        // BFINAL = 1, BTYPE = 01 (fixed Huffman codes)
+        output_accum[0] = 0b011;
+        Self {
+            crc: 0,
+            xor_checksum: 0,
+            reserved0: [0; 5],
+            num_output_accum_bits: 3,
+            output_accum,
+            ll_sym,
+            reserved1: [0; 2],
+            d_sym,
+            reserved2: [0; 2],
+        }
+    }
+}
+
+/// The shared fixed-code compression state; the device only reads it.
+fn fixed_aecs() -> &'static CompressAecs {
+    static AECS: OnceLock<Box<CompressAecs>> = OnceLock::new();
+    AECS.get_or_init(|| Box::new(CompressAecs::fixed()))
+}
+
+/// A complete DEFLATE stream of no data: a final, empty fixed block.
+const EMPTY_STREAM: [u8; 2] = [0x03, 0x00];
+
+impl DsaHwDesc {
+    /// Start an IAA descriptor for `op` reporting to `completion`.
+    fn iaa(op: IaaOpcode, completion: &mut IaaCompletionRecord) -> Self {
+        let mut desc = Self::new();
+        desc.flags_opcode = (op.as_u8() as u32) << 24 | DescriptorFlags::REQUEST_COMPLETION.bits();
+        desc.completion_addr = completion as *mut IaaCompletionRecord as u64;
+        desc
+    }
+
+    /// Create an IAA CRC64 descriptor.
+    ///
+    /// The CRC is written to [`IaaCompletionRecord::crc`].
+    pub fn iaa_crc64(
+        src: *const u8,
+        size: usize,
+        params: &Crc64Params,
+        completion: &mut IaaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::iaa(IaaOpcode::Crc64, completion);
+        desc.src_addr = src as u64;
+        desc.xfer_size = size as u32;
+        desc.set_iaa_flags(params.flags());
+        desc.as_iaa_mut().crc64_poly = params.poly;
+        desc
+    }
+
+    /// Create an IAA Compress descriptor producing one final DEFLATE block
+    /// with the fixed Huffman codes.
+    ///
+    /// The size of the output is written to
+    /// [`IaaCompletionRecord::output_size`].
+    pub fn iaa_compress(
+        dst: *mut u8,
+        dst_size: usize,
+        src: *const u8,
+        size: usize,
+        completion: &mut IaaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::iaa(IaaOpcode::Compress, completion);
+        desc.flags_opcode |= DescriptorFlags::SRC2_AECS.bits();
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = size as u32;
+        desc.set_iaa_flags(COMPRESS_FLUSH_OUTPUT | COMPRESS_APPEND_EOB);
+        let fields = desc.as_iaa_mut();
+        fields.src2_addr = fixed_aecs() as *const CompressAecs as u64;
+        fields.src2_size = COMPRESS_AECS_SIZE as u32;
+        fields.max_dst_size = dst_size as u32;
+        desc
+    }
+
+    /// Create an IAA Decompress descriptor for a complete raw DEFLATE
+    /// stream.
+    ///
+    /// The size of the output is written to
+    /// [`IaaCompletionRecord::output_size`].
+    pub fn iaa_decompress(
+        dst: *mut u8,
+        dst_size: usize,
+        src: *const u8,
+        size: usize,
+        completion: &mut IaaCompletionRecord,
+    ) -> Self {
+        let mut desc = Self::iaa(IaaOpcode::Decompress, completion);
+        desc.src_addr = src as u64;
+        desc.dst_addr = dst as u64;
+        desc.xfer_size = size as u32;
+        desc.set_iaa_flags(
+            DECOMPRESS_ENABLE
+                | DECOMPRESS_FLUSH_OUTPUT
+                | DECOMPRESS_CHECK_FOR_EOB
+                | DECOMPRESS_STOP_ON_EOB,
+        );
+        desc.as_iaa_mut().max_dst_size = dst_size as u32;
+        desc
+    }
+}
+
+/// Execute an IAA descriptor in software, as the emulator does.
+///
+/// Returns the completion status, error code, output size and CRC.
+///
+/// # Safety
+///
+/// The addresses in `desc` must be valid for the accesses the operation
+/// performs.
+pub(crate) unsafe fn emulate(desc: &DsaHwDesc, op: IaaOpcode) -> (u8, u8, u32, u64) {
+    const SUCCESS: u8 = 0x01;
+    const INVALID_SIZE: u8 = 0x13;
+    // Error code for a malformed stream; the device reports the reason
+    const ERROR_BAD_STREAM: u8 = 0x01;
+
+    let src = std::slice::from_raw_parts(desc.src_addr as *const u8, desc.xfer_size as usize);
+    let fields = *desc.as_iaa();
+    let dst =
+        || std::slice::from_raw_parts_mut(desc.dst_addr as *mut u8, fields.max_dst_size as usize);
+    match op {
+        IaaOpcode::Crc64 => {
+            let params = Crc64Params {
+                poly: fields.crc64_poly,
+                msb_first: desc.iaa_flags() & CRC64_MSB_FIRST != 0,
+                invert: desc.iaa_flags() & CRC64_INVERT != 0,
+            };
+            (SUCCESS, 0, 0, software_crc64(src, &params))
+        }
+        IaaOpcode::Compress => {
+            if (fields.src2_size as usize) < COMPRESS_AECS_SIZE {
+                return (INVALID_SIZE, 0, 0, 0);
+            }
+            let aecs = &*(fields.src2_addr as *const CompressAecs);
+            let mut out = deflate::BitWriter::new(dst());
+            let header_bits = aecs.num_output_accum_bits.min(8 * 256);
+            let header = (0..header_bits)
+                .all(|bit| out.put((aecs.output_accum[bit as usize / 8] >> (bit % 8)) as u64, 1));
+            let append_eob = desc.iaa_flags() & COMPRESS_APPEND_EOB != 0;
+            let size =
+                if header && deflate::encode_literals(&mut out, src, &aecs.ll_sym, append_eob) {
+                    out.finish()
+                } else {
+                    None
+                };
+            match size {
+                Some(size) => (
+                    SUCCESS,
+                    0,
+                    size as u32,
+                    crate::crc::ieee_crc32(src, 0) as u64,
+                ),
+                None => (ANALYTICS_ERROR, ERROR_COMP_BUF_OVERFLOW, 0, 0),
+            }
+        }
+        IaaOpcode::Decompress => {
+            let dst = dst();
+            match deflate::inflate(src, dst) {
+                Ok(size) => (
+                    SUCCESS,
+                    0,
+                    size as u32,
+                    crate::crc::ieee_crc32(&dst[..size], 0) as u64,
+                ),
+                Err(deflate::InflateError::Overflow) => {
+                    (ANALYTICS_ERROR, ERROR_DECOMP_BUF_OVERFLOW, 0, 0)
+                }
+                Err(deflate::InflateError::Malformed) => (ANALYTICS_ERROR, ERROR_BAD_STREAM, 0, 0),
+            }
+        }
+    }
+}
+
+impl DsaEngine {
+    /// Open the first enabled IAA work queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::NoDeviceFound` if there is no IAA device, or an
+    /// error if no work queue can be opened; `DsaError::PlatformNotSupported`
+    /// on platforms other than Linux.
+    pub fn open_first_iaa() -> Result<Self, DsaError> {
+        let devices = crate::device::discover_iaa_devices()?;
+        let device = devices.into_iter().next().ok_or(DsaError::NoDeviceFound)?;
+        let wq = device.open_first_wq()?;
+        Ok(Self::from_wq(wq))
+    }
+
+    /// Compute a CRC64 of `data` on an IAA work queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to compute the CRC over
+    /// * `params` - CRC64 polynomial and bit order
+    ///
+    /// # Returns
+    ///
+    /// The CRC64, equal to [`software_crc64`] of the same data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails, for instance on a DSA work
+    /// queue, which does not know the opcode.
+    pub fn crc64(&self, data: &[u8], params: &Crc64Params) -> Result<u64, DsaError> {
+        self.throttle(data.len(), 1);
+        self.retry(|| self.work_queue().crc64(data, params))
+    }
+
+    /// Compress `src` into `dst` as a raw DEFLATE stream on an IAA work
+    /// queue.
+    ///
+    /// The stream is one final block with the fixed Huffman codes, which
+    /// any inflater (zlib with negative window bits, `miniz_oxide`, ...)
+    /// reads.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::OperationFailed` with status [`ANALYTICS_ERROR`]
+    /// and result [`ERROR_COMP_BUF_OVERFLOW`] if the stream does not fit
+    /// `dst`, or an error if the operation fails.
+    pub fn compress(&self, dst: &mut [u8], src: &[u8]) -> Result<usize, DsaError> {
+        self.throttle(src.len(), 1);
+        self.retry(|| self.work_queue().compress(dst, src))
+    }
+
+    /// Decompress the raw DEFLATE stream `src` into `dst` on an IAA work
+    /// queue.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::OperationFailed` with status [`ANALYTICS_ERROR`]
+    /// and result [`ERROR_DECOMP_BUF_OVERFLOW`] if the output does not fit
+    /// `dst`, or with another result if the stream is malformed, or an
+    /// error if the operation fails.
+    pub fn decompress(&self, dst: &mut [u8], src: &[u8]) -> Result<usize, DsaError> {
+        self.throttle(src.len(), 1);
+        self.retry(|| self.work_queue().decompress(dst, src))
+    }
+}
+
+/// Write the stream of empty input, which needs no descriptor.
+pub(crate) fn compress_empty(dst: &mut [u8]) -> Result<usize, DsaError> {
+    match dst.get_mut(..EMPTY_STREAM.len()) {
+        Some(out) => {
+            out.copy_from_slice(&EMPTY_STREAM);
+            Ok(EMPTY_STREAM.len())
+        }
+        None => Err(DsaError::OperationFailed {
+            status: ANALYTICS_ERROR,
+            result: ERROR_COMP_BUF_OVERFLOW,
+            bytes_completed: 0,
+            context: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        assert_eq!(std::mem::size_of::<IaaCompletionRecord>(), 64);
+        assert_eq!(std::mem::align_of::<IaaCompletionRecord>(), 64);
+        assert_eq!(std::mem::offset_of!(IaaCompletionRecord, output_size), 24);
+        assert_eq!(std::mem::offset_of!(IaaCompletionRecord, crc), 32);
+        assert_eq!(std::mem::size_of::<CompressAecs>(), COMPRESS_AECS_SIZE);
+        assert_eq!(std::mem::offset_of!(CompressAecs, ll_sym), 288);
+        assert_eq!(std::mem::offset_of!(CompressAecs, d_sym), 1440);
+    }
+
+    #[test]
+    fn test_software_crc64_check_values() {
+        let check = b"123456789";
+        assert_eq!(
+            software_crc64(check, &Crc64Params::ECMA_182),
+            0x6C40_DF5F_0B49_7347
+        );
+        assert_eq!(
+            software_crc64(check, &Crc64Params::XZ),
+            0x995D_C9BB_DF19_39FA
+        );
+        assert_eq!(
+            software_crc64(check, &Crc64Params::NVME),
+            0xAE8B_1486_0A79_9888
+        );
+        assert_eq!(
+            software_crc64(check, &Crc64Params::GO_ISO),
+            0xB909_56C7_75A4_1001
+        );
+        assert_eq!(software_crc64(&[], &Crc64Params::XZ), 0);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_round_trip() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let data: Vec<u8> = (0..10_000).map(|i| (i * 7 % 256) as u8).collect();
+        for params in [Crc64Params::ECMA_182, Crc64Params::XZ, Crc64Params::NVME] {
+            assert_eq!(
+                engine.crc64(&data, &params).unwrap(),
+                software_crc64(&data, &params)
+            );
+        }
+
+        let mut packed = vec![0u8; data.len() * 9 / 8 + 16];
+        let size = engine.compress(&mut packed, &data).unwrap();
+        let mut unpacked = vec![0u8; data.len()];
+        assert_eq!(
+            engine.decompress(&mut unpacked, &packed[..size]).unwrap(),
+            data.len()
+        );
+        assert_eq!(unpacked, data);
+
+        let size = engine.compress(&mut packed, &[]).unwrap();
+        assert_eq!(
+            engine.decompress(&mut unpacked, &packed[..size]).unwrap(),
+            0
+        );
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_errors() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let data = vec![0xA5u8; 1000];
+        let mut small = vec![0u8; 100];
+        assert!(matches!(
+            engine.compress(&mut small, &data),
+            Err(DsaError::OperationFailed {
+                status: ANALYTICS_ERROR,
+                result: ERROR_COMP_BUF_OVERFLOW,
+                ..
+            })
+        ));
+
+        // "hello hello hello hello" compressed by zlib, which uses matches
+        let stream = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01];
+        let mut out = vec![0u8; 64];
+        let size = engine.decompress(&mut out, &stream).unwrap();
+        assert_eq!(&out[..size], b"hello hello hello hello");
+        assert!(matches!(
+            engine.decompress(&mut out[..10], &stream),
+            Err(DsaError::OperationFailed {
+                status: ANALYTICS_ERROR,
+                result: ERROR_DECOMP_BUF_OVERFLOW,
+                ..
+            })
+        ));
+        assert!(matches!(
+            engine.decompress(&mut out, &[0xff; 4]),
+            Err(DsaError::OperationFailed {
+                status: ANALYTICS_ERROR,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,425 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Bindings to the Linux uapi header `include/uapi/linux/idxd.h`.
+//!
+//! Projects that already build descriptors with the kernel's structs, or
+//! receive them from C libraries such as `libaccel-config` based code, can
+//! convert them to and from [`DsaHwDesc`] and [`DsaCompletionRecord`]
+//! instead of copying bytes by hand.
+//!
+//! The bindings are vendored rather than generated at build time, so no
+//! kernel headers or libclang are needed. The kernel's bitfields and
+//! anonymous unions have no Rust equivalent; they are flattened to the raw
+//! words that contain them, with accessors for the bitfields, and the
+//! union members are listed on each field. Only the DSA parts of the header
+//! are included.
+//!
+//! Conversions map opcodes and descriptor flags by meaning. Opcodes and
+//! flags with no counterpart on the other side are rejected. Addresses are
+//! copied as they are, so a converted batch descriptor still points at the
+//! original descriptor list, which must be converted separately.
+//!
+//! Requires the `idxd-uapi` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dsa_rust::idxd::{dsa_hw_desc, DSA_OPCODE_MEMMOVE, IDXD_OP_FLAG_CRAV, IDXD_OP_FLAG_RCR};
+//! use dsa_rust::{DsaHwDesc, DsaOpcode};
+//!
+//! let mut c_desc = dsa_hw_desc::default();
+//! c_desc.set_opcode(DSA_OPCODE_MEMMOVE);
+//! c_desc.set_flags(IDXD_OP_FLAG_CRAV | IDXD_OP_FLAG_RCR);
+//! c_desc.xfer_size = 4096;
+//!
+//! let desc = DsaHwDesc::try_from(&c_desc)?;
+//! assert_eq!(desc.opcode(), DsaOpcode::MemMove as u8);
+//! assert_eq!(dsa_hw_desc::try_from(&desc)?.flags(), c_desc.flags());
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+#![allow(non_camel_case_types)]
+
+use crate::descriptor::{DescriptorFlags, DsaCompletionRecord, DsaHwDesc};
+use crate::error::DsaError;
+use crate::opcode::DsaOpcode;
+
+/// `enum dsa_opcode`.
+pub type dsa_opcode = u32;
+
+pub const DSA_OPCODE_NOOP: dsa_opcode = 0;
+pub const DSA_OPCODE_BATCH: dsa_opcode = 1;
+pub const DSA_OPCODE_DRAIN: dsa_opcode = 2;
+pub const DSA_OPCODE_MEMMOVE: dsa_opcode = 3;
+pub const DSA_OPCODE_MEMFILL: dsa_opcode = 4;
+pub const DSA_OPCODE_COMPARE: dsa_opcode = 5;
+pub const DSA_OPCODE_COMPVAL: dsa_opcode = 6;
+pub const DSA_OPCODE_CR_DELTA: dsa_opcode = 7;
+pub const DSA_OPCODE_AP_DELTA: dsa_opcode = 8;
+pub const DSA_OPCODE_DUALCAST: dsa_opcode = 9;
+pub const DSA_OPCODE_TRANSL_FETCH: dsa_opcode = 0x0a;
+pub const DSA_OPCODE_CRCGEN: dsa_opcode = 0x10;
+pub const DSA_OPCODE_COPY_CRC: dsa_opcode = 0x11;
+pub const DSA_OPCODE_DIF_CHECK: dsa_opcode = 0x12;
+pub const DSA_OPCODE_DIF_INS: dsa_opcode = 0x13;
+pub const DSA_OPCODE_DIF_STRP: dsa_opcode = 0x14;
+pub const DSA_OPCODE_DIF_UPDT: dsa_opcode = 0x15;
+pub const DSA_OPCODE_DIX_GEN: dsa_opcode = 0x17;
+pub const DSA_OPCODE_CFLUSH: dsa_opcode = 0x20;
+
+pub const IDXD_OP_FLAG_FENCE: u32 = 0x0001;
+pub const IDXD_OP_FLAG_BOF: u32 = 0x0002;
+pub const IDXD_OP_FLAG_CRAV: u32 = 0x0004;
+pub const IDXD_OP_FLAG_RCR: u32 = 0x0008;
+pub const IDXD_OP_FLAG_RCI: u32 = 0x0010;
+pub const IDXD_OP_FLAG_CRSTS: u32 = 0x0020;
+pub const IDXD_OP_FLAG_CR: u32 = 0x0080;
+pub const IDXD_OP_FLAG_CC: u32 = 0x0100;
+pub const IDXD_OP_FLAG_ADDR1_TCS: u32 = 0x0200;
+pub const IDXD_OP_FLAG_ADDR2_TCS: u32 = 0x0400;
+pub const IDXD_OP_FLAG_ADDR3_TCS: u32 = 0x0800;
+pub const IDXD_OP_FLAG_CR_TCS: u32 = 0x1000;
+pub const IDXD_OP_FLAG_STORD: u32 = 0x2000;
+pub const IDXD_OP_FLAG_DRDBK: u32 = 0x4000;
+pub const IDXD_OP_FLAG_DSTS: u32 = 0x8000;
+pub const IDXD_OP_FLAG_RD_SRC2_AECS: u32 = 0x010000;
+
+pub const DSA_COMP_NONE: u8 = 0;
+pub const DSA_COMP_SUCCESS: u8 = 1;
+pub const DSA_COMP_SUCCESS_PRED: u8 = 2;
+pub const DSA_COMP_PAGE_FAULT_NOBOF: u8 = 3;
+pub const DSA_COMP_PAGE_FAULT_IR: u8 = 4;
+pub const DSA_COMP_BATCH_FAIL: u8 = 5;
+pub const DSA_COMP_BATCH_PAGE_FAULT: u8 = 6;
+pub const DSA_COMP_DR_OFFSET_NOINC: u8 = 7;
+pub const DSA_COMP_DR_OFFSET_ERANGE: u8 = 8;
+pub const DSA_COMP_DIF_ERR: u8 = 9;
+pub const DSA_COMP_BAD_OPCODE: u8 = 0x10;
+pub const DSA_COMP_INVALID_FLAGS: u8 = 0x11;
+pub const DSA_COMP_NOZERO_RESERVE: u8 = 0x12;
+pub const DSA_COMP_XFER_ERANGE: u8 = 0x13;
+pub const DSA_COMP_DESC_CNT_ERANGE: u8 = 0x14;
+pub const DSA_COMP_DR_ERANGE: u8 = 0x15;
+pub const DSA_COMP_OVERLAP_BUFFERS: u8 = 0x16;
+pub const DSA_COMP_DCAST_ERR: u8 = 0x17;
+pub const DSA_COMP_DESCLIST_ALIGN: u8 = 0x18;
+pub const DSA_COMP_INT_HANDLE_INVAL: u8 = 0x19;
+pub const DSA_COMP_CRA_XLAT: u8 = 0x1a;
+pub const DSA_COMP_CRA_ALIGN: u8 = 0x1b;
+pub const DSA_COMP_ADDR_ALIGN: u8 = 0x1c;
+pub const DSA_COMP_PRIV_BAD: u8 = 0x1d;
+pub const DSA_COMP_TRAFFIC_CLASS_CONF: u8 = 0x1e;
+pub const DSA_COMP_PFAULT_RDBA: u8 = 0x1f;
+pub const DSA_COMP_HW_ERR1: u8 = 0x20;
+pub const DSA_COMP_HW_ERR_DRB: u8 = 0x21;
+pub const DSA_COMP_TRANSLATION_FAIL: u8 = 0x22;
+
+/// `struct dsa_hw_desc`.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct dsa_hw_desc {
+    /// Bitfields `pasid:20`, `rsvd:11`, `priv:1`.
+    pub pasid_priv: u32,
+    /// Bitfields `flags:24`, `opcode:8`.
+    pub flags_opcode: u32,
+    pub completion_addr: u64,
+    /// Union of `src_addr`, `rdback_addr`, `pattern`, `desc_list_addr`,
+    /// `pattern_lower` and `transl_fetch_addr`.
+    pub src_addr: u64,
+    /// Union of `dst_addr`, `rdback_addr2`, `src2_addr` and `comp_pattern`.
+    pub dst_addr: u64,
+    /// Union of `xfer_size`, `desc_count` and `region_size`.
+    pub xfer_size: u32,
+    pub int_handle: u16,
+    pub rsvd1: u16,
+    /// Union of the operation-specific members, such as `crc_seed`,
+    /// `delta_addr` and the DIF tags.
+    pub op_specific: [u8; 24],
+}
+
+impl dsa_hw_desc {
+    /// The `opcode` bitfield.
+    pub fn opcode(&self) -> dsa_opcode {
+        self.flags_opcode >> 24
+    }
+
+    /// Set the `opcode` bitfield.
+    pub fn set_opcode(&mut self, opcode: dsa_opcode) {
+        self.flags_opcode = (self.flags_opcode & 0x00FF_FFFF) | (opcode << 24);
+    }
+
+    /// The `flags` bitfield.
+    pub fn flags(&self) -> u32 {
+        self.flags_opcode & 0x00FF_FFFF
+    }
+
+    /// Set the `flags` bitfield.
+    pub fn set_flags(&mut self, flags: u32) {
+        self.flags_opcode = (self.flags_opcode & 0xFF00_0000) | (flags & 0x00FF_FFFF);
+    }
+}
+
+/// `struct dsa_completion_record`.
+///
+/// The kernel record is 32 bytes; [`DsaCompletionRecord`] reserves another
+/// 32 bytes after it, which conversions leave zero.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct dsa_completion_record {
+    pub status: u8,
+    /// Union of `result` and `dif_status`.
+    pub result: u8,
+    pub fault_info: u8,
+    pub rsvd: u8,
+    /// Union of `bytes_completed` and `descs_completed`.
+    pub bytes_completed: u32,
+    pub fault_addr: u64,
+    /// Union of the operation-specific members, such as `crc_val` and
+    /// `delta_rec_size`.
+    pub op_specific: [u8; 16],
+}
+
+/// Opcodes with a kernel counterpart.
+const OPCODES: [(DsaOpcode, dsa_opcode); 19] = [
+    (DsaOpcode::Noop, DSA_OPCODE_NOOP),
+    (DsaOpcode::Batch, DSA_OPCODE_BATCH),
+    (DsaOpcode::Drain, DSA_OPCODE_DRAIN),
+    (DsaOpcode::MemMove, DSA_OPCODE_MEMMOVE),
+    (DsaOpcode::MemFill, DSA_OPCODE_MEMFILL),
+    (DsaOpcode::Compare, DSA_OPCODE_COMPARE),
+    (DsaOpcode::CompareImm, DSA_OPCODE_COMPVAL),
+    (DsaOpcode::CreateDelta, DSA_OPCODE_CR_DELTA),
+    (DsaOpcode::ApplyDelta, DSA_OPCODE_AP_DELTA),
+    (DsaOpcode::Dualcast, DSA_OPCODE_DUALCAST),
+    (DsaOpcode::TranslFetch, DSA_OPCODE_TRANSL_FETCH),
+    (DsaOpcode::CrcGen, DSA_OPCODE_CRCGEN),
+    (DsaOpcode::CopyCrc, DSA_OPCODE_COPY_CRC),
+    (DsaOpcode::DifCheck, DSA_OPCODE_DIF_CHECK),
+    (DsaOpcode::DifInsert, DSA_OPCODE_DIF_INS),
+    (DsaOpcode::DifStrip, DSA_OPCODE_DIF_STRP),
+    (DsaOpcode::DifUpdate, DSA_OPCODE_DIF_UPDT),
+    (DsaOpcode::DixGen, DSA_OPCODE_DIX_GEN),
+    (DsaOpcode::CacheFlush, DSA_OPCODE_CFLUSH),
+];
+
+/// Descriptor flags with a kernel counterpart.
+const FLAGS: [(DescriptorFlags, u32); 16] = [
+    (DescriptorFlags::FENCE, IDXD_OP_FLAG_FENCE),
+    (DescriptorFlags::BLOCK_ON_FAULT, IDXD_OP_FLAG_BOF),
+    (DescriptorFlags::CR_ADDR_VALID, IDXD_OP_FLAG_CRAV),
+    (DescriptorFlags::REQUEST_COMPLETION, IDXD_OP_FLAG_RCR),
+    (DescriptorFlags::COMPLETION_INTERRUPT, IDXD_OP_FLAG_RCI),
+    (DescriptorFlags::CHECK_RESULT, IDXD_OP_FLAG_CR),
+    (DescriptorFlags::CACHE_CTRL, IDXD_OP_FLAG_CC),
+    (DescriptorFlags::ADDR1_TCS, IDXD_OP_FLAG_ADDR1_TCS),
+    (DescriptorFlags::ADDR2_TCS, IDXD_OP_FLAG_ADDR2_TCS),
+    (DescriptorFlags::ADDR3_TCS, IDXD_OP_FLAG_ADDR3_TCS),
+    (DescriptorFlags::CR_TCS, IDXD_OP_FLAG_CR_TCS),
+    (DescriptorFlags::STRICT_ORDERING, IDXD_OP_FLAG_STORD),
+    (DescriptorFlags::DEST_READBACK, IDXD_OP_FLAG_DRDBK),
+    (DescriptorFlags::DEST_STEERING_TAG, IDXD_OP_FLAG_DSTS),
+    (DescriptorFlags::SRC2_AECS, IDXD_OP_FLAG_RD_SRC2_AECS),
+    (DescriptorFlags::STATUS_WRITEBACK, IDXD_OP_FLAG_CRSTS),
+];
+
+impl TryFrom<&dsa_hw_desc> for DsaHwDesc {
+    type Error = DsaError;
+
+    /// Convert a kernel descriptor.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if the opcode or a flag has no
+    /// counterpart in [`DsaOpcode`] or [`DescriptorFlags`].
+    fn try_from(c_desc: &dsa_hw_desc) -> Result<Self, DsaError> {
+        let opcode = c_desc.opcode();
+        let (op, _) = OPCODES
+            .iter()
+            .find(|&&(_, c_op)| c_op == opcode)
+            .ok_or_else(|| {
+                DsaError::InvalidArgument(format!("kernel opcode {opcode:#x} is not supported"))
+            })?;
+        let mut flags = DescriptorFlags::empty();
+        let mut unknown = c_desc.flags();
+        for &(flag, c_flag) in &FLAGS {
+            if unknown & c_flag != 0 {
+                flags |= flag;
+                unknown &= !c_flag;
+            }
+        }
+        if unknown != 0 {
+            return Err(DsaError::InvalidArgument(format!(
+                "kernel descriptor flags {unknown:#x} are not supported"
+            )));
+        }
+
+        let mut desc = DsaHwDesc::new();
+        desc.pasid = c_desc.pasid_priv;
+        desc.set_opcode(*op);
+        desc.set_flags(flags);
+        desc.completion_addr = c_desc.completion_addr;
+        desc.src_addr = c_desc.src_addr;
+        desc.dst_addr = c_desc.dst_addr;
+        desc.xfer_size = c_desc.xfer_size;
+        desc.int_handle = c_desc.int_handle;
+        *desc.op_specific_mut() = c_desc.op_specific;
+        Ok(desc)
+    }
+}
+
+impl TryFrom<&DsaHwDesc> for dsa_hw_desc {
+    type Error = DsaError;
+
+    /// Convert a descriptor to the kernel's layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if the opcode or a flag has no
+    /// kernel counterpart, or the opcode is not a known [`DsaOpcode`].
+    fn try_from(desc: &DsaHwDesc) -> Result<Self, DsaError> {
+        let op = DsaOpcode::try_from(desc.opcode())?;
+        let (_, opcode) = OPCODES
+            .iter()
+            .find(|&&(o, _)| o == op)
+            .ok_or_else(|| DsaError::InvalidArgument(format!("{op:?} has no kernel opcode")))?;
+        let mut flags = 0;
+        let mut unknown = DescriptorFlags::from_bits_retain(desc.flags_opcode & 0x00FF_FFFF);
+        for &(flag, c_flag) in &FLAGS {
+            if unknown.contains(flag) {
+                flags |= c_flag;
+                unknown.remove(flag);
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(DsaError::InvalidArgument(format!(
+                "descriptor flags {unknown:?} have no kernel counterpart"
+            )));
+        }
+
+        let mut c_desc = dsa_hw_desc {
+            pasid_priv: desc.pasid,
+            flags_opcode: 0,
+            completion_addr: desc.completion_addr,
+            src_addr: desc.src_addr,
+            dst_addr: desc.dst_addr,
+            xfer_size: desc.xfer_size,
+            int_handle: desc.int_handle,
+            rsvd1: 0,
+            op_specific: *desc.op_specific(),
+        };
+        c_desc.set_opcode(*opcode);
+        c_desc.set_flags(flags);
+        Ok(c_desc)
+    }
+}
+
+impl From<&dsa_completion_record> for DsaCompletionRecord {
+    fn from(c_record: &dsa_completion_record) -> Self {
+        let op_specific = c_record.op_specific;
+        let mut record = DsaCompletionRecord::new();
+        record.status = c_record.status;
+        record.result = c_record.result;
+        record.fault_info = c_record.fault_info;
+        record.bytes_completed = c_record.bytes_completed;
+        record.fault_addr = c_record.fault_addr;
+        record.result_value = u64::from_le_bytes(op_specific[..8].try_into().unwrap());
+        record.result_value2 = u64::from_le_bytes(op_specific[8..].try_into().unwrap());
+        record
+    }
+}
+
+impl From<&DsaCompletionRecord> for dsa_completion_record {
+    fn from(record: &DsaCompletionRecord) -> Self {
+        let mut op_specific = [0; 16];
+        op_specific[..8].copy_from_slice(&record.result_value.to_le_bytes());
+        op_specific[8..].copy_from_slice(&record.result_value2.to_le_bytes());
+        Self {
+            status: record.status,
+            result: record.result,
+            fault_info: record.fault_info,
+            rsvd: 0,
+            bytes_completed: record.bytes_completed,
+            fault_addr: record.fault_addr,
+            op_specific,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{offset_of, size_of};
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(size_of::<dsa_hw_desc>(), 64);
+        assert_eq!(offset_of!(dsa_hw_desc, completion_addr), 8);
+        assert_eq!(offset_of!(dsa_hw_desc, xfer_size), 32);
+        assert_eq!(offset_of!(dsa_hw_desc, op_specific), 40);
+        assert_eq!(size_of::<dsa_completion_record>(), 32);
+        assert_eq!(offset_of!(dsa_completion_record, fault_addr), 8);
+        assert_eq!(offset_of!(dsa_completion_record, op_specific), 16);
+    }
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        let src = [1u8; 64];
+        let mut record = DsaCompletionRecord::new();
+        let mut desc = DsaHwDesc::crc_gen(src.as_ptr(), src.len(), 0xDEAD_BEEF, &mut record);
+        desc.add_flags(DescriptorFlags::FENCE | DescriptorFlags::CACHE_CTRL);
+
+        let c_desc = dsa_hw_desc::try_from(&desc).unwrap();
+        assert_eq!(c_desc.opcode(), DSA_OPCODE_CRCGEN);
+        assert_eq!(
+            c_desc.flags(),
+            IDXD_OP_FLAG_RCR | IDXD_OP_FLAG_FENCE | IDXD_OP_FLAG_CC
+        );
+        assert_eq!(c_desc.op_specific[..4], 0xDEAD_BEEFu32.to_le_bytes());
+
+        let back = DsaHwDesc::try_from(&c_desc).unwrap();
+        assert_eq!(back.flags_opcode, desc.flags_opcode);
+        assert_eq!(back.as_crc().crc_seed, 0xDEAD_BEEF);
+        assert_eq!(back.src_addr, desc.src_addr);
+        assert_eq!(back.completion_addr, desc.completion_addr);
+    }
+
+    #[test]
+    fn test_unsupported_rejected() {
+        let mut c_desc = dsa_hw_desc::default();
+        c_desc.set_opcode(0x16);
+        assert!(matches!(
+            DsaHwDesc::try_from(&c_desc),
+            Err(DsaError::InvalidArgument(_))
+        ));
+
+        c_desc.set_opcode(DSA_OPCODE_NOOP);
+        c_desc.set_flags(0x40);
+        assert!(DsaHwDesc::try_from(&c_desc).is_err());
+
+        let mut desc = DsaHwDesc::new();
+        desc.set_flags(DescriptorFlags::DEST_NO_SNOOP);
+        assert!(dsa_hw_desc::try_from(&desc).is_err());
+    }
+
+    #[test]
+    fn test_completion_record_round_trip() {
+        let c_record = dsa_completion_record {
+            status: DSA_COMP_SUCCESS,
+            result: 1,
+            bytes_completed: 100,
+            fault_addr: 0x1000,
+            op_specific: [0x11; 16],
+            ..Default::default()
+        };
+        let record = DsaCompletionRecord::from(&c_record);
+        assert!(record.get_status().is_success());
+        assert_eq!(record.bytes_completed, 100);
+        assert_eq!(record.result_value, 0x1111_1111_1111_1111);
+
+        let back = dsa_completion_record::from(&record);
+        assert_eq!({ back.fault_addr }, 0x1000);
+        assert_eq!(back.op_specific, [0x11; 16]);
+    }
+}
@@ -0,0 +1,296 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Interrupt handle (IMS) management.
+//!
+//! Descriptors that set [`DescriptorFlags::COMPLETION_INTERRUPT`] must carry a
+//! valid interrupt handle in their `int_handle` field. The handle selects an
+//! entry in the device's Interrupt Message Storage (IMS) table; the hardware
+//! raises that interrupt when the descriptor completes.
+//!
+//! # Linux
+//!
+//! The IMS table size is advertised by the device's `gen_cap` register,
+//! which the IDXD driver exposes as `/sys/bus/dsa/devices/dsaN/gen_cap`.
+//! Bits [30:25] hold a multiplier in units of 256 entries.
+//!
+//! The user-space IDXD character device does not hand out IMS entries to
+//! processes. Handles are therefore provided by the integrator (for example a
+//! VMM that programmed the IMS table through vfio) and registered with
+//! [`InterruptManager::with_handles`]. [`InterruptManager::for_device`] reads
+//! the table size from sysfs so that registered handles can be validated.
+//!
+//! # Scope
+//!
+//! This module only tracks which registered handles are in use. The IDXD
+//! driver offers no ioctl or sysfs interface through which a process could
+//! allocate IMS entries or receive their interrupts, so there is no kernel
+//! allocation path and no interrupt-driven wait mode: work queue operations
+//! always poll their completion records. Waiting for the interrupts of
+//! registered handles is up to the integrator that programmed them.
+//!
+//! # Handle lifetime
+//!
+//! [`DsaHwDesc::set_interrupt`] copies the raw handle into the descriptor;
+//! it does not keep the [`InterruptHandle`] alive. If the handle is dropped
+//! while a descriptor that carries it is still in flight, the handle
+//! returns to the pool and can be allocated for another descriptor, and the
+//! completion of the first raises the interrupt of the second. Keep the
+//! handle alive until the completion record of every descriptor that
+//! carries it is written.
+//!
+//! [`DescriptorFlags::COMPLETION_INTERRUPT`]: crate::descriptor::DescriptorFlags::COMPLETION_INTERRUPT
+//! [`DsaHwDesc::set_interrupt`]: crate::descriptor::DsaHwDesc::set_interrupt
+
+use crate::error::DsaError;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+#[cfg(dsa_portal)]
+use std::path::Path;
+
+/// Number of IMS entries per unit of the `gen_cap` IMS multiplier.
+pub const IMS_ENTRIES_PER_MULT: u32 = 256;
+
+/// Bit offset of the IMS size multiplier in the `gen_cap` register.
+const GEN_CAP_IMS_MULT_SHIFT: u32 = 25;
+
+/// Mask of the IMS size multiplier (6 bits).
+const GEN_CAP_IMS_MULT_MASK: u64 = 0x3F;
+
+/// Decode the IMS table size from a raw `gen_cap` register value.
+#[inline]
+pub const fn ims_size_from_gen_cap(gen_cap: u64) -> u32 {
+    ((gen_cap >> GEN_CAP_IMS_MULT_SHIFT) & GEN_CAP_IMS_MULT_MASK) as u32 * IMS_ENTRIES_PER_MULT
+}
+
+/// Shared allocation state behind an [`InterruptManager`].
+#[derive(Debug)]
+struct HandlePool {
+    /// Handles that are registered but not currently allocated.
+    free: Vec<u16>,
+    /// All registered handles, allocated or not.
+    registered: BTreeSet<u16>,
+}
+
+/// Allocator for interrupt handles.
+///
+/// Cloning an `InterruptManager` yields another reference to the same pool.
+/// Allocated handles are returned to the pool when the [`InterruptHandle`]
+/// is dropped.
+#[derive(Debug, Clone)]
+pub struct InterruptManager {
+    /// IMS table size reported by the device (0 if unknown).
+    ims_size: u32,
+    pool: Arc<Mutex<HandlePool>>,
+}
+
+impl InterruptManager {
+    /// Create a manager for a device with `ims_size` IMS entries and no
+    /// registered handles.
+    pub fn new(ims_size: u32) -> Self {
+        Self {
+            ims_size,
+            pool: Arc::new(Mutex::new(HandlePool {
+                free: Vec::new(),
+                registered: BTreeSet::new(),
+            })),
+        }
+    }
+
+    /// Create a manager with the given range of handles registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if any handle lies outside the
+    /// IMS table.
+    pub fn with_handles(ims_size: u32, handles: Range<u16>) -> Result<Self, DsaError> {
+        let manager = Self::new(ims_size);
+        manager.register(handles)?;
+        Ok(manager)
+    }
+
+    /// Create a manager for a device, reading the IMS table size from sysfs.
+    ///
+    /// # Arguments
+    ///
+    /// * `sysfs_path` - Sysfs directory of the device (e.g., `/sys/bus/dsa/devices/dsa0`)
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InterruptsUnsupported` if the device reports no
+    /// IMS entries.
+    #[cfg(dsa_portal)]
+    pub fn for_device(sysfs_path: &Path) -> Result<Self, DsaError> {
+        let raw = std::fs::read_to_string(sysfs_path.join("gen_cap"))?;
+        let raw = raw.trim();
+        let gen_cap = u64::from_str_radix(raw.trim_start_matches("0x"), 16)
+            .map_err(|_| DsaError::InvalidArgument(format!("invalid gen_cap in sysfs: {}", raw)))?;
+
+        let ims_size = ims_size_from_gen_cap(gen_cap);
+        if ims_size == 0 {
+            return Err(DsaError::InterruptsUnsupported);
+        }
+        Ok(Self::new(ims_size))
+    }
+
+    /// Interrupt handles are only available through the Linux IDXD driver.
+    #[cfg(not(dsa_portal))]
+    pub fn for_device(_sysfs_path: &std::path::Path) -> Result<Self, DsaError> {
+        Err(DsaError::InterruptsUnsupported)
+    }
+
+    /// Register additional handles with the pool.
+    ///
+    /// Handles that are already registered, whether free or allocated, are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if any handle lies outside the
+    /// IMS table. No handles are registered in that case.
+    pub fn register(&self, handles: Range<u16>) -> Result<(), DsaError> {
+        if self.ims_size != 0 && u32::from(handles.end) > self.ims_size {
+            return Err(DsaError::InvalidArgument(format!(
+                "interrupt handle {} exceeds IMS table size {}",
+                handles.end - 1,
+                self.ims_size
+            )));
+        }
+
+        let mut pool = self.pool.lock().unwrap();
+        for handle in handles {
+            if pool.registered.insert(handle) {
+                pool.free.push(handle);
+            }
+        }
+        Ok(())
+    }
+
+    /// Allocate an interrupt handle.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InterruptsUnsupported` if no handles were ever
+    /// registered, or `DsaError::NoInterruptHandle` if all handles are in use.
+    pub fn allocate(&self) -> Result<InterruptHandle, DsaError> {
+        let mut pool = self.pool.lock().unwrap();
+        if pool.registered.is_empty() {
+            return Err(DsaError::InterruptsUnsupported);
+        }
+        let handle = pool.free.pop().ok_or(DsaError::NoInterruptHandle)?;
+        Ok(InterruptHandle {
+            handle,
+            pool: Arc::clone(&self.pool),
+        })
+    }
+
+    /// IMS table size reported by the device (0 if unknown).
+    pub fn ims_size(&self) -> u32 {
+        self.ims_size
+    }
+
+    /// Number of handles registered with this manager.
+    pub fn capacity(&self) -> usize {
+        self.pool.lock().unwrap().registered.len()
+    }
+
+    /// Number of handles currently available for allocation.
+    pub fn available(&self) -> usize {
+        self.pool.lock().unwrap().free.len()
+    }
+}
+
+/// An allocated interrupt handle.
+///
+/// The handle is returned to its [`InterruptManager`] when dropped, after
+/// which it can be allocated again. It must outlive every in-flight
+/// descriptor that references it; see the [module documentation](self).
+#[derive(Debug)]
+pub struct InterruptHandle {
+    handle: u16,
+    pool: Arc<Mutex<HandlePool>>,
+}
+
+impl InterruptHandle {
+    /// Raw handle value for the descriptor's `int_handle` field.
+    #[inline]
+    pub fn raw(&self) -> u16 {
+        self.handle
+    }
+}
+
+impl Drop for InterruptHandle {
+    fn drop(&mut self) {
+        if let Ok(mut pool) = self.pool.lock() {
+            pool.free.push(self.handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ims_size_from_gen_cap() {
+        assert_eq!(ims_size_from_gen_cap(0), 0);
+        assert_eq!(ims_size_from_gen_cap(1 << 25), 256);
+        assert_eq!(ims_size_from_gen_cap(8 << 25), 2048);
+        // Bits outside the multiplier field are ignored
+        assert_eq!(ims_size_from_gen_cap((1 << 31) | (1 << 25) | 0x1), 256);
+    }
+
+    #[test]
+    fn test_allocate_without_handles() {
+        let manager = InterruptManager::new(256);
+        assert!(matches!(
+            manager.allocate(),
+            Err(DsaError::InterruptsUnsupported)
+        ));
+    }
+
+    #[test]
+    fn test_allocate_and_release() {
+        let manager = InterruptManager::with_handles(256, 4..6).unwrap();
+        assert_eq!(manager.capacity(), 2);
+
+        let a = manager.allocate().unwrap();
+        let b = manager.allocate().unwrap();
+        assert_ne!(a.raw(), b.raw());
+        assert_eq!(manager.available(), 0);
+        assert!(matches!(
+            manager.allocate(),
+            Err(DsaError::NoInterruptHandle)
+        ));
+
+        drop(a);
+        assert_eq!(manager.available(), 1);
+        assert!(manager.allocate().is_ok());
+    }
+
+    #[test]
+    fn test_register_allocated_handle() {
+        let manager = InterruptManager::with_handles(256, 4..5).unwrap();
+        let handle = manager.allocate().unwrap();
+
+        // Registering a handle that is in use must not make it free twice
+        manager.register(4..6).unwrap();
+        assert_eq!(manager.capacity(), 2);
+        assert_eq!(manager.available(), 1);
+        drop(handle);
+        assert_eq!(manager.available(), 2);
+        let a = manager.allocate().unwrap();
+        let b = manager.allocate().unwrap();
+        assert_ne!(a.raw(), b.raw());
+    }
+
+    #[test]
+    fn test_register_out_of_range() {
+        let manager = InterruptManager::new(256);
+        assert!(manager.register(250..300).is_err());
+        assert_eq!(manager.capacity(), 0);
+    }
+}
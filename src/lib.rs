@@ -9,10 +9,11 @@
 //!
 //! ## Supported Operations
 //!
-//! - CRC32 generation
+//! - CRC32 generation (one-shot, batched, and incremental)
 //! - Memory copy (memcpy)
 //! - Memory fill (memset)
 //! - Memory compare (memcmp)
+//! - Delta records (create and apply; serializable with the `serde` feature)
 //! - Batch operations
 //!
 //! ## Platform Support
@@ -26,8 +27,8 @@
 //! ### Windows
 //!
 //! On Windows, hardware DSA access is not available through userspace APIs.
-//! This crate provides optimized software fallback using `crc32fast` (SIMD-accelerated)
-//! and standard library memory operations.
+//! This crate provides optimized software fallback using the SSE4.2 `crc32` instruction
+//! (or a pluggable [`SoftwareCrc`] backend) and standard library memory operations.
 //!
 //! ### WSL2 Limitations
 //!
@@ -56,6 +57,28 @@
 //! }
 //! ```
 //!
+//! ## Async Support
+//!
+//! With the `async` feature, operations such as [`DsaEngine::crc32_async`]
+//! return a [`DsaFuture`]. Completions are observed by a background reactor
+//! thread that wakes tasks through `std::task::Waker`, so the futures run on
+//! any executor. The `tokio`, `async-std` and `smol` features add a thin
+//! [`rt`] module for moving blocking batch operations off the executor.
+//! [`CompletionStream`] yields many in-flight operations as a
+//! `futures_core::Stream`, in completion order.
+//!
+//! Applications without an executor can use
+//! [`DsaEngine::submit_with_callback`] instead; the reactor thread invokes
+//! the callback when the operation completes.
+//!
+//! With the `io-uring` feature, a [`uring::UringPipeline`] hands buffers
+//! filled by io_uring reads to the engine and awaits reads and operations
+//! together on the calling thread.
+//!
+//! With the `perf-events` feature, [`DsaEngine::perf_counters`] reads the
+//! device's own performance counters (descriptors processed, engine busy
+//! cycles, memory bandwidth) through the kernel's perf PMU.
+//!
 //! ## Requirements
 //!
 //! ### Hardware
@@ -70,27 +93,209 @@
 //! ### Software (Windows - software fallback)
 //! - Windows 10/11 or Windows Server 2019+
 //! - No additional configuration required
+//!
+//! ### Other architectures (software fallback)
+//! - Descriptor submission uses x86_64 instructions; on other targets, such
+//!   as aarch64, work queues run operations in software
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg(target_arch = "x86_64")]
 #![allow(dead_code)] // During development
 
 #[cfg(feature = "std")]
 extern crate std;
 
 // Module declarations
+#[cfg(feature = "std")]
+mod arena;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "std")]
+pub mod backoff;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bounce;
+#[cfg(feature = "std")]
+pub mod callback;
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "std")]
+pub mod capabilities;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod crc;
+#[cfg(feature = "std")]
+pub mod crc_streams;
+#[cfg(feature = "std")]
+pub mod dedup;
+#[cfg(feature = "iaa")]
+mod deflate;
+#[cfg(feature = "std")]
+pub mod degrade;
+#[cfg(feature = "std")]
+pub mod delta;
 pub mod descriptor;
+#[cfg(feature = "std")]
 pub mod device;
+#[cfg(feature = "std")]
+pub mod emulator;
+#[cfg(feature = "std")]
 pub mod engine;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod fair;
+#[cfg(feature = "async")]
+pub mod future;
+#[cfg(feature = "std")]
+pub mod group;
+#[cfg(feature = "iaa")]
+pub mod iaa;
+#[cfg(feature = "idxd-uapi")]
+pub mod idxd;
+#[cfg(feature = "std")]
+pub mod interrupt;
+#[cfg(feature = "std")]
+pub mod iotlb;
+#[cfg(feature = "memmap2")]
+pub mod mapped;
+#[cfg(feature = "std")]
+pub mod mock;
+#[cfg(feature = "std")]
+pub mod multipart;
 pub mod opcode;
+#[cfg(all(feature = "perf-events", dsa_portal))]
+pub mod perf;
+#[cfg(feature = "std")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod probe;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "std")]
+pub mod rate_limit;
+#[cfg(feature = "std")]
+mod reactor;
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+pub mod rt;
+#[cfg(feature = "std")]
+pub mod sandbox;
+#[cfg(feature = "std")]
+pub mod shard;
+#[cfg(feature = "std")]
+pub mod spawn;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(feature = "strict-validation")]
+mod strict;
 pub mod submit;
+#[cfg(feature = "std")]
+pub mod submitter;
+#[cfg(feature = "std")]
+pub mod swerr;
+#[cfg(feature = "std")]
+pub mod t10pi;
+#[cfg(feature = "std")]
+pub mod topology;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(all(feature = "io-uring", dsa_portal))]
+pub mod uring;
+#[cfg(feature = "verify")]
+mod verify;
+#[cfg(feature = "std")]
+pub mod warm;
+#[cfg(feature = "std")]
+pub mod watch;
+#[cfg(feature = "std")]
 pub mod wq;
+#[cfg(feature = "std")]
+pub mod zero_pool;
+#[cfg(feature = "std")]
+pub mod zero_scan;
 
 // Re-exports for convenient access
-pub use descriptor::{CompletionStatus, DsaCompletionRecord, DsaHwDesc};
+#[cfg(feature = "std")]
+pub use backoff::Backoff;
+#[cfg(feature = "std")]
+pub use batch::{BatchBuilder, BatchResults, OpOutput};
+#[cfg(feature = "std")]
+pub use bounce::BouncePolicy;
+#[cfg(feature = "std")]
+pub use callback::{DsaOp, DsaOutput};
+#[cfg(feature = "std")]
+pub use cancel::{CancellationToken, OpContext};
+#[cfg(feature = "std")]
+pub use capabilities::{Backend, Capabilities};
+#[cfg(feature = "std")]
+pub use config::EngineConfig;
+#[cfg(feature = "std")]
+pub use crc::{crc32_combine, crc32c_combine, Crc32Params, DsaCrc32, SoftwareCrc};
+#[cfg(feature = "std")]
+pub use crc_streams::CrcStreams;
+#[cfg(feature = "std")]
+pub use degrade::DegradeStats;
+#[cfg(feature = "std")]
+pub use delta::DeltaRecord;
+#[cfg(feature = "iaa")]
+pub use descriptor::IaaDesc;
+pub use descriptor::{
+    ApplyDeltaDesc, CompletionStatus, CrcDesc, DeltaDesc, DifDesc, DifStatus, DifTags,
+    DsaCompletionRecord, DsaHwDesc,
+};
+#[cfg(feature = "iaa")]
+pub use device::discover_iaa_devices;
+#[cfg(feature = "std")]
 pub use device::{discover_devices, is_dsa_available, is_dsa_configured, DsaDevice};
-pub use engine::DsaEngine;
-pub use error::DsaError;
-pub use opcode::DsaOpcode;
-pub use wq::{WorkQueue, WorkQueueType};
+#[cfg(feature = "std")]
+pub use engine::{DsaEngine, Gather, QueueFullPolicy};
+pub use error::{DescriptorError, DsaError, ErrorCode};
+#[cfg(feature = "std")]
+pub use error::{ErrorContext, WqUnavailableReason};
+#[cfg(feature = "std")]
+pub use fair::FairShare;
+#[cfg(feature = "async")]
+pub use future::DsaFuture;
+#[cfg(feature = "std")]
+pub use group::{GroupConfig, GroupInfo};
+#[cfg(feature = "iaa")]
+pub use iaa::{Crc64Params, IaaCompletionRecord};
+#[cfg(feature = "std")]
+pub use interrupt::{InterruptHandle, InterruptManager};
+#[cfg(feature = "memmap2")]
+pub use mapped::DsaMappedFile;
+#[cfg(feature = "std")]
+pub use mock::MockBackend;
+pub use opcode::{DecodedOpcode, DsaOpcode};
+#[cfg(feature = "std")]
+pub use pool::{Balance, DsaEnginePool, Priority};
+#[cfg(feature = "std")]
+pub use probe::ProbeReport;
+#[cfg(feature = "std")]
+pub use profile::Profile;
+#[cfg(feature = "std")]
+pub use rate_limit::RateLimit;
+#[cfg(feature = "std")]
+pub use shard::ShardPolicy;
+#[cfg(feature = "std")]
+pub use spawn::DsaJoinHandle;
+#[cfg(feature = "async")]
+pub use stream::CompletionStream;
+#[cfg(feature = "std")]
+pub use submitter::{Coalescing, Submitter};
+#[cfg(feature = "std")]
+pub use swerr::SoftwareError;
+#[cfg(feature = "std")]
+pub use topology::{device_topology, DeviceTopology};
+#[cfg(feature = "std")]
+pub use trace::{TraceConfig, TraceEntry, TraceOutcome};
+#[cfg(feature = "std")]
+pub use warm::{WarmMethod, WarmPolicy};
+#[cfg(feature = "std")]
+pub use watch::{DeviceEvent, DeviceWatcher};
+#[cfg(feature = "std")]
+pub use wq::{WorkQueue, WorkQueueBinding, WorkQueueState, WorkQueueType};
+#[cfg(feature = "std")]
+pub use zero_pool::ZeroPool;
+#[cfg(feature = "std")]
+pub use zero_scan::PageBitmap;
@@ -0,0 +1,335 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! DSA operations on memory-mapped files.
+//!
+//! Checksumming or copying a file through a memory mapping avoids read and
+//! write system calls, but pages of a mapping are only faulted in on first
+//! access, and a descriptor that reaches a page not yet present stops with
+//! a page fault partial completion. [`DsaMappedFile`] wraps a `memmap2`
+//! mapping and packages the recipe: files are mapped with `MAP_POPULATE`
+//! where supported, operations are split into chunks of
+//! [`MAPPED_CHUNK_SIZE`], each chunk is touched before it is submitted, and
+//! a chunk that still faults, e.g. after its pages were reclaimed, is
+//! touched again and resubmitted once.
+//!
+//! Requires the `memmap2` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::mapped::DsaMappedFile;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let src = DsaMappedFile::open("input.bin")?;
+//! let mut dst = DsaMappedFile::create("output.bin", src.len())?;
+//! src.copy_to(&engine, &mut dst)?;
+//! dst.flush()?;
+//! assert_eq!(dst.crc32(&engine)?, src.crc32(&engine)?);
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::warm::touch;
+use memmap2::{Mmap, MmapMut, MmapOptions};
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+
+/// Bytes per descriptor when operating on a mapped file.
+pub const MAPPED_CHUNK_SIZE: usize = 2 << 20;
+
+/// The mapping behind a [`DsaMappedFile`].
+enum Map {
+    ReadOnly(Mmap),
+    Writable(MmapMut),
+}
+
+/// A memory-mapped file whose contents DSA operations work on directly.
+pub struct DsaMappedFile {
+    map: Map,
+}
+
+impl DsaMappedFile {
+    /// Map an existing file read-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the file cannot be opened or mapped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DsaError> {
+        let file = File::open(path)?;
+        // SAFETY: the caller must not truncate or modify the file while it is
+        // mapped; this is the usual memmap2 contract
+        let map = unsafe { MmapOptions::new().populate().map(&file)? };
+        Ok(Self::from(map))
+    }
+
+    /// Map an existing file for reading and writing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the file cannot be opened or mapped.
+    pub fn open_mut(path: impl AsRef<Path>) -> Result<Self, DsaError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::map_mut(&file)
+    }
+
+    /// Create or truncate a file of `len` bytes and map it for writing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the file cannot be created, sized or
+    /// mapped.
+    pub fn create(path: impl AsRef<Path>, len: usize) -> Result<Self, DsaError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        Self::map_mut(&file)
+    }
+
+    fn map_mut(file: &File) -> Result<Self, DsaError> {
+        // SAFETY: as for `open`
+        let map = unsafe { MmapOptions::new().populate().map_mut(file)? };
+        Ok(Self::from(map))
+    }
+
+    /// Length of the mapping in bytes.
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns true if the mapping is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the mapping can be written.
+    pub fn is_writable(&self) -> bool {
+        matches!(self.map, Map::Writable(_))
+    }
+
+    /// The mapped bytes.
+    pub fn as_slice(&self) -> &[u8] {
+        match &self.map {
+            Map::ReadOnly(map) => map,
+            Map::Writable(map) => map,
+        }
+    }
+
+    /// The mapped bytes, if the mapping is writable.
+    pub fn as_mut_slice(&mut self) -> Option<&mut [u8]> {
+        match &mut self.map {
+            Map::ReadOnly(_) => None,
+            Map::Writable(map) => Some(map),
+        }
+    }
+
+    /// Write modified pages back to the file.
+    ///
+    /// Does nothing for read-only mappings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the pages cannot be written back.
+    pub fn flush(&self) -> Result<(), DsaError> {
+        if let Map::Writable(map) = &self.map {
+            map.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Compute the CRC32 of the mapped bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation fails.
+    pub fn crc32(&self, engine: &DsaEngine) -> Result<u32, DsaError> {
+        let mut crc = 0;
+        for chunk in self.as_slice().chunks(MAPPED_CHUNK_SIZE) {
+            prefault(chunk);
+            crc = match engine.crc32_with_seed(chunk, crc) {
+                Err(DsaError::PageFault { .. }) => {
+                    prefault(chunk);
+                    engine.crc32_with_seed(chunk, crc)?
+                }
+                result => result?,
+            };
+        }
+        Ok(crc)
+    }
+
+    /// Compute the CRC32 of every part of the mapped bytes, like
+    /// [`DsaEngine::crc32_parts`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `part_size` is zero, or an
+    /// error if an operation fails.
+    pub fn crc32_parts(&self, engine: &DsaEngine, part_size: usize) -> Result<Vec<u32>, DsaError> {
+        let data = self.as_slice();
+        prefault(data);
+        match engine.crc32_parts(data, part_size) {
+            Err(DsaError::PageFault { .. }) => {
+                prefault(data);
+                engine.crc32_parts(data, part_size)
+            }
+            result => result,
+        }
+    }
+
+    /// Copy the mapped bytes to the start of `dst`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `dst` is read-only,
+    /// `DsaError::BufferSizeMismatch` if it is shorter than `self`, or an
+    /// error if an operation fails.
+    pub fn copy_to(&self, engine: &DsaEngine, dst: &mut DsaMappedFile) -> Result<(), DsaError> {
+        let src = self.as_slice();
+        let dst_len = dst.len();
+        let dst = dst.as_mut_slice().ok_or_else(|| {
+            DsaError::InvalidArgument("destination mapping is read-only".to_string())
+        })?;
+        if dst_len < src.len() {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: src.len(),
+                actual: dst_len,
+            });
+        }
+
+        let chunks = dst
+            .chunks_mut(MAPPED_CHUNK_SIZE)
+            .zip(src.chunks(MAPPED_CHUNK_SIZE));
+        for (dst, src) in chunks {
+            prefault(src);
+            prefault_mut(dst);
+            match engine.memcpy(dst, src) {
+                Err(DsaError::PageFault {
+                    bytes_completed, ..
+                }) => {
+                    // Resume after the bytes already copied
+                    let (dst, src) = (
+                        &mut dst[bytes_completed as usize..],
+                        &src[bytes_completed as usize..],
+                    );
+                    prefault(src);
+                    prefault_mut(dst);
+                    engine.memcpy(dst, src)?;
+                }
+                result => result?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Mmap> for DsaMappedFile {
+    fn from(map: Mmap) -> Self {
+        Self {
+            map: Map::ReadOnly(map),
+        }
+    }
+}
+
+impl From<MmapMut> for DsaMappedFile {
+    fn from(map: MmapMut) -> Self {
+        Self {
+            map: Map::Writable(map),
+        }
+    }
+}
+
+impl std::fmt::Debug for DsaMappedFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DsaMappedFile")
+            .field("len", &self.len())
+            .field("writable", &self.is_writable())
+            .finish()
+    }
+}
+
+/// Fault in every page of `buf` for reading.
+fn prefault(buf: &[u8]) {
+    let start = buf.as_ptr() as usize;
+    // SAFETY: `buf` is a live, readable allocation
+    unsafe { touch(start..start + buf.len(), false) };
+}
+
+/// Fault in every page of `buf` for writing.
+fn prefault_mut(buf: &mut [u8]) {
+    let start = buf.as_mut_ptr() as usize;
+    // SAFETY: `buf` is a live allocation, borrowed mutably
+    unsafe { touch(start..start + buf.len(), true) };
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_and_copy() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let dir = std::env::temp_dir();
+        let src_path = dir.join(format!("dsa-mapped-src-{}", std::process::id()));
+        let dst_path = dir.join(format!("dsa-mapped-dst-{}", std::process::id()));
+        let data: Vec<u8> = (0..MAPPED_CHUNK_SIZE + 12345)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&src_path, &data).unwrap();
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let src = DsaMappedFile::open(&src_path).unwrap();
+        assert!(!src.is_writable());
+        assert_eq!(src.crc32(&engine).unwrap(), engine.crc32(&data).unwrap());
+        assert_eq!(
+            src.crc32_parts(&engine, MAPPED_CHUNK_SIZE).unwrap(),
+            engine.crc32_parts(&data, MAPPED_CHUNK_SIZE).unwrap()
+        );
+
+        let mut dst = DsaMappedFile::create(&dst_path, data.len()).unwrap();
+        src.copy_to(&engine, &mut dst).unwrap();
+        dst.flush().unwrap();
+        drop(dst);
+        assert_eq!(std::fs::read(&dst_path).unwrap(), data);
+
+        // Read-only and short destinations are rejected
+        let mut read_only = DsaMappedFile::open(&dst_path).unwrap();
+        assert!(matches!(
+            src.copy_to(&engine, &mut read_only),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        let mut short = DsaMappedFile::create(&dst_path, 10).unwrap();
+        assert!(matches!(
+            src.copy_to(&engine, &mut short),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+
+        std::fs::remove_file(&src_path).unwrap();
+        std::fs::remove_file(&dst_path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_resumes_after_page_fault() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let mut src = DsaMappedFile::from(MmapMut::map_anon(3 * 4096).unwrap());
+        let mut dst = DsaMappedFile::from(MmapMut::map_anon(3 * 4096).unwrap());
+        src.as_mut_slice().unwrap().fill(0x5A);
+
+        emulator.inject(Fault::PageFault { offset: 4096 });
+        src.copy_to(&engine, &mut dst).unwrap();
+        assert_eq!(dst.as_slice(), src.as_slice());
+    }
+}
@@ -0,0 +1,313 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Scripted mock backend for testing DSA error handling.
+//!
+//! Faults injected into an [`Emulator`] trigger on whatever descriptor
+//! comes next. Code that issues several kinds of operations needs more
+//! control than that to test its error handling, such as failing exactly
+//! the third copy. A [`MockBackend`] scripts the response to individual
+//! calls, counted per opcode:
+//!
+//! * [`Response::Crc`] completes a CRC operation with a given CRC;
+//! * [`Response::Fault`] triggers an emulator [`Fault`] on that call;
+//! * [`Response::Status`] completes with a raw completion status;
+//! * [`Response::Delay`] completes only after the completion has been
+//!   polled a number of times.
+//!
+//! Calls without a scripted response are executed by an emulator and
+//! behave as on hardware. Every submitted descriptor is a call, including
+//! resubmissions after the work queue was full; descriptors inside a
+//! batch are not scripted, the batch is. Blocking operations poll the
+//! mock while they wait. Operations completed by the completion reactor
+//! (futures, callbacks) do not, so a test drives their delays with
+//! [`MockBackend::poll`]. Results of a mocked engine are not checked by
+//! the `verify` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dsa_rust::emulator::Fault;
+//! use dsa_rust::mock::{MockBackend, Response};
+//! use dsa_rust::{DsaEngine, DsaError, DsaOpcode};
+//! use std::sync::Arc;
+//!
+//! let mock = Arc::new(MockBackend::new());
+//! mock.respond(DsaOpcode::CrcGen, 1, Response::Crc(0xDEAD_BEEF));
+//! mock.respond(DsaOpcode::MemMove, 3, Response::Fault(Fault::PageFault { offset: 0 }));
+//! # #[cfg(dsa_portal)]
+//! # {
+//! let engine = DsaEngine::mocked(Arc::clone(&mock))?;
+//!
+//! assert_eq!(engine.crc32(&[0u8; 64])?, 0xDEAD_BEEF);
+//! let mut dst = vec![0u8; 4096];
+//! engine.memcpy(&mut dst, &[1u8; 4096])?;
+//! engine.memcpy(&mut dst, &[2u8; 4096])?;
+//! assert!(matches!(
+//!     engine.memcpy(&mut dst, &[3u8; 4096]),
+//!     Err(DsaError::PageFault { .. })
+//! ));
+//! # }
+//! # Ok::<(), DsaError>(())
+//! ```
+
+use crate::descriptor::{DsaCompletionRecord, DsaHwDesc};
+use crate::emulator::{record_error, Emulator, Fault};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::opcode::DsaOpcode;
+use crate::wq::WorkQueue;
+use std::collections::HashMap;
+use std::sync::atomic::{fence, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// What a scripted call does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    /// Execute the operation and report this CRC as its result.
+    Crc(u32),
+    /// Trigger `fault` on this call, as [`Emulator::inject`] would.
+    /// `Fault::QueueFull { count }` also rejects the next `count - 1`
+    /// submissions of any opcode.
+    Fault(Fault),
+    /// Complete with this raw completion status without executing the
+    /// operation.
+    Status(u8),
+    /// Execute the operation, but complete it only after the mock has been
+    /// polled `polls` times. The completion is written even if the wait
+    /// gave up, so the engine that submitted it must outlive the polls.
+    Delay { polls: u32 },
+}
+
+/// A completion held back by [`Response::Delay`].
+#[derive(Debug)]
+struct Delayed {
+    /// Completion record of the submitted descriptor.
+    record: *mut DsaCompletionRecord,
+    /// Record the emulator completed instead.
+    scratch: Box<DsaCompletionRecord>,
+    polls: u32,
+}
+
+// The record is only written while the submitter waits for it
+unsafe impl Send for Delayed {}
+
+#[derive(Debug, Default)]
+struct Script {
+    /// Responses by opcode and call number.
+    responses: HashMap<(u8, u64), Response>,
+    /// Calls so far by opcode.
+    calls: HashMap<u8, u64>,
+    delayed: Vec<Delayed>,
+}
+
+/// A work queue backend whose responses are scripted per call.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    emulator: Emulator,
+    script: Mutex<Script>,
+}
+
+impl MockBackend {
+    /// Create a mock without scripted responses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Script the response to call number `call` (counting from 1) of
+    /// opcode `op`, replacing an earlier response to the same call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `call` is 0.
+    pub fn respond(&self, op: DsaOpcode, call: u64, response: Response) -> &Self {
+        assert!(call > 0, "calls are counted from 1");
+        let mut script = self.script.lock().unwrap();
+        script.responses.insert((op.as_u8(), call), response);
+        self
+    }
+
+    /// Script the response to the next call of opcode `op`.
+    pub fn respond_next(&self, op: DsaOpcode, response: Response) -> &Self {
+        self.respond(op, self.calls(op) + 1, response)
+    }
+
+    /// Number of calls of opcode `op` so far.
+    pub fn calls(&self, op: DsaOpcode) -> u64 {
+        let script = self.script.lock().unwrap();
+        script.calls.get(&op.as_u8()).copied().unwrap_or(0)
+    }
+
+    /// Number of delayed operations not completed yet.
+    pub fn delayed(&self) -> usize {
+        self.script.lock().unwrap().delayed.len()
+    }
+
+    /// Count one poll, completing the delayed operations that are due.
+    pub fn poll(&self) {
+        let mut script = self.script.lock().unwrap();
+        script.delayed.retain_mut(|delayed| {
+            delayed.polls = delayed.polls.saturating_sub(1);
+            if delayed.polls > 0 {
+                return true;
+            }
+            unsafe { deliver(delayed.record, &delayed.scratch) };
+            false
+        });
+    }
+
+    /// Execute a descriptor according to the script.
+    ///
+    /// # Safety
+    ///
+    /// As for [`Emulator::submit`].
+    pub(crate) unsafe fn submit(&self, desc: &DsaHwDesc) -> Result<(), DsaError> {
+        let op = desc.opcode();
+        let response = {
+            let mut script = self.script.lock().unwrap();
+            let calls = script.calls.entry(op).or_insert(0);
+            *calls += 1;
+            let call = *calls;
+            script.responses.remove(&(op, call))
+        };
+        let record = desc.completion_addr as *mut DsaCompletionRecord;
+        // Without a usable record there is nothing to script
+        let Some(response) = response.filter(|_| !record.is_null() && record_error(desc).is_none())
+        else {
+            return self.emulator.submit(desc);
+        };
+
+        match response {
+            Response::Fault(fault) => {
+                self.emulator.inject(fault);
+                self.emulator.submit(desc)
+            }
+            Response::Status(status) => {
+                let mut scratch = DsaCompletionRecord::new();
+                scratch.status = status;
+                deliver(record, &scratch);
+                Ok(())
+            }
+            Response::Crc(crc) => {
+                let mut scratch = self.run_into_scratch(desc)?;
+                scratch.result_value = crc as u64;
+                deliver(record, &scratch);
+                Ok(())
+            }
+            Response::Delay { polls } => {
+                let scratch = self.run_into_scratch(desc)?;
+                if polls == 0 {
+                    deliver(record, &scratch);
+                } else {
+                    self.script.lock().unwrap().delayed.push(Delayed {
+                        record,
+                        scratch,
+                        polls,
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Execute `desc` with its completion written to a record of its own.
+    unsafe fn run_into_scratch(
+        &self,
+        desc: &DsaHwDesc,
+    ) -> Result<Box<DsaCompletionRecord>, DsaError> {
+        let mut scratch = Box::new(DsaCompletionRecord::new());
+        let mut redirected = *desc;
+        redirected.completion_addr = &mut *scratch as *mut DsaCompletionRecord as u64;
+        self.emulator.submit(&redirected)?;
+        Ok(scratch)
+    }
+}
+
+/// Copy `scratch` to `record`, status last.
+unsafe fn deliver(record: *mut DsaCompletionRecord, scratch: &DsaCompletionRecord) {
+    let mut pending = *scratch;
+    pending.status = 0;
+    std::ptr::write(record, pending);
+    fence(Ordering::Release);
+    std::ptr::write_volatile(&mut (*record).status, scratch.status);
+}
+
+impl DsaEngine {
+    /// Create an engine whose operations are answered by `mock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::PlatformNotSupported` on platforms other than
+    /// Linux, where operations do not go through descriptors.
+    pub fn mocked(mock: Arc<MockBackend>) -> Result<Self, DsaError> {
+        WorkQueue::mocked(mock).map(Self::from_wq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_counts_calls() {
+        let mock = MockBackend::new();
+        mock.respond_next(DsaOpcode::Noop, Response::Status(0x10));
+        let mut records = [DsaCompletionRecord::new(); 2];
+        for record in records.iter_mut() {
+            unsafe { mock.submit(&DsaHwDesc::noop(record)).unwrap() };
+        }
+        assert_eq!(records[0].status, 0x10);
+        assert_eq!(records[1].status, 0x01);
+        assert_eq!(mock.calls(DsaOpcode::Noop), 2);
+        assert_eq!(mock.calls(DsaOpcode::MemMove), 0);
+
+        // A delayed completion is written on the last of its polls
+        let mut record = DsaCompletionRecord::new();
+        mock.respond_next(DsaOpcode::Noop, Response::Delay { polls: 2 });
+        unsafe { mock.submit(&DsaHwDesc::noop(&mut record)).unwrap() };
+        mock.poll();
+        assert!(!record.is_complete());
+        assert_eq!(mock.delayed(), 1);
+        mock.poll();
+        assert!(record.is_complete());
+        assert_eq!(mock.delayed(), 0);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_mocked_engine() {
+        let mock = Arc::new(MockBackend::new());
+        let engine = DsaEngine::mocked(Arc::clone(&mock)).unwrap();
+        let data = vec![5u8; 4096];
+
+        mock.respond(DsaOpcode::CrcGen, 2, Response::Crc(42))
+            .respond(DsaOpcode::CrcGen, 3, Response::Delay { polls: 10 })
+            .respond(DsaOpcode::CrcGen, 4, Response::Status(0x1F));
+        assert_eq!(
+            engine.crc32(&data).unwrap(),
+            crate::crc::software_crc32(&data, 0)
+        );
+        assert_eq!(engine.crc32(&data).unwrap(), 42);
+        // The blocking wait polls the delayed operation to completion
+        assert_eq!(
+            engine.crc32(&data).unwrap(),
+            crate::crc::software_crc32(&data, 0)
+        );
+        assert!(matches!(
+            engine.crc32(&data),
+            Err(DsaError::OperationFailed { status: 0x1F, .. })
+        ));
+
+        // A full queue rejects the call
+        mock.respond(
+            DsaOpcode::Noop,
+            2,
+            Response::Fault(Fault::QueueFull { count: 1 }),
+        );
+        engine.noop().unwrap();
+        assert!(matches!(engine.noop(), Err(DsaError::QueueFull { .. })));
+        engine.noop().unwrap();
+        assert_eq!(mock.calls(DsaOpcode::Noop), 3);
+    }
+}
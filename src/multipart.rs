@@ -0,0 +1,137 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Per-part CRC32s for multipart uploads.
+//!
+//! Object stores check the integrity of a multipart upload with one
+//! checksum per part. [`DsaEngine::crc32_parts`] splits a buffer into parts
+//! of a fixed size and computes all their CRC32s in batches, so the device
+//! works on many parts at once. Parts larger than the work queue's maximum
+//! transfer size are split into several descriptors whose CRC32s are joined
+//! with [`crc32c_combine`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::multipart::MULTIPART_PART_SIZE;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let object = vec![0u8; 100 << 20];
+//! for (part, crc) in engine.crc32_parts(&object, MULTIPART_PART_SIZE)?.iter().enumerate() {
+//!     println!("part {}: {crc:#010x}", part + 1);
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::crc::crc32c_combine;
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+
+/// A common part size for multipart uploads, 8 MiB.
+pub const MULTIPART_PART_SIZE: usize = 8 << 20;
+
+impl DsaEngine {
+    /// Compute the CRC32 of every part of `data`.
+    ///
+    /// Part `i` is `data[i * part_size..]`, up to `part_size` bytes; the
+    /// last part may be shorter.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Data to split into parts
+    /// * `part_size` - Bytes per part
+    ///
+    /// # Returns
+    ///
+    /// The CRC32 of each part, in order; empty if `data` is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `part_size` is zero, or an
+    /// error if an operation fails.
+    pub fn crc32_parts(&self, data: &[u8], part_size: usize) -> Result<Vec<u32>, DsaError> {
+        if part_size == 0 {
+            return Err(DsaError::InvalidArgument(
+                "part size must be nonzero".to_string(),
+            ));
+        }
+
+        // Descriptors cover at most the maximum transfer size, so large
+        // parts are checksummed in pieces that never straddle two parts
+        let max_transfer = usize::try_from(self.capabilities().max_transfer_size)
+            .unwrap_or(usize::MAX)
+            .max(1);
+        self.crc32_pieces(data, part_size, part_size.min(max_transfer))
+    }
+
+    /// Compute the CRC32 of every part of `data`, submitting pieces of at
+    /// most `piece_size` bytes.
+    fn crc32_pieces(
+        &self,
+        data: &[u8],
+        part_size: usize,
+        piece_size: usize,
+    ) -> Result<Vec<u32>, DsaError> {
+        let pieces: Vec<&[u8]> = data
+            .chunks(part_size)
+            .flat_map(|part| part.chunks(piece_size))
+            .collect();
+        let mut crcs = self.crc32_many(&pieces)?.into_iter().zip(pieces);
+
+        Ok(data
+            .chunks(part_size)
+            .map(|part| {
+                crcs.by_ref()
+                    .take(part.len().div_ceil(piece_size))
+                    .fold(0, |crc, (piece_crc, piece)| {
+                        crc32c_combine(crc, piece_crc, piece.len() as u64)
+                    })
+            })
+            .collect())
+    }
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use crate::emulator::Emulator;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_crc32_parts() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let data: Vec<u8> = (0..10 * 4096 + 123).map(|i| (i % 251) as u8).collect();
+
+        for part_size in [4096, 5000, data.len(), 2 * data.len()] {
+            let expected: Vec<u32> = data
+                .chunks(part_size)
+                .map(|chunk| crate::crc::software_crc32(chunk, 0))
+                .collect();
+            assert_eq!(engine.crc32_parts(&data, part_size).unwrap(), expected);
+        }
+        assert!(engine.crc32_parts(&[], 4096).unwrap().is_empty());
+        assert!(matches!(
+            engine.crc32_parts(&data, 0),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_parts_larger_than_a_descriptor() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let data: Vec<u8> = (0..3 * 4096 + 7).map(|i| (i % 241) as u8).collect();
+        let expected: Vec<u32> = data
+            .chunks(4096)
+            .map(|chunk| crate::crc::software_crc32(chunk, 0))
+            .collect();
+        for piece_size in [1000, 2048, 4096] {
+            assert_eq!(
+                engine.crc32_pieces(&data, 4096, piece_size).unwrap(),
+                expected
+            );
+        }
+    }
+}
@@ -7,11 +7,13 @@
 //! These opcodes are defined in the Intel DSA Architecture Specification
 //! and match the Linux kernel's `include/uapi/linux/idxd.h` definitions.
 
+use crate::error::{DescriptorError, DsaError};
+
 /// DSA operation codes.
 ///
 /// Each operation has a unique 8-bit opcode that is placed in the
 /// descriptor's opcode field.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
 pub enum DsaOpcode {
     /// No operation - used for testing/synchronization.
@@ -70,9 +72,56 @@ pub enum DsaOpcode {
 
     /// Cache flush.
     CacheFlush = 0x20,
+
+    /// Update the attributes of an inter-domain window (DSA 2.0).
+    UpdateWindow = 0x21,
+
+    /// Memory move between address spaces (DSA 2.0).
+    InterDomainMemMove = 0x23,
+
+    /// Memory fill in another address space (DSA 2.0).
+    InterDomainFill = 0x24,
+
+    /// Memory compare across address spaces (DSA 2.0).
+    InterDomainCompare = 0x25,
+
+    /// Compare with immediate value in another address space (DSA 2.0).
+    InterDomainCompareImm = 0x26,
+
+    /// Cache flush in another address space (DSA 2.0).
+    InterDomainCacheFlush = 0x27,
 }
 
 impl DsaOpcode {
+    /// All defined opcodes, in ascending numeric order.
+    pub const ALL: [DsaOpcode; 25] = [
+        Self::Noop,
+        Self::Batch,
+        Self::Drain,
+        Self::MemMove,
+        Self::MemFill,
+        Self::Compare,
+        Self::CompareImm,
+        Self::CreateDelta,
+        Self::ApplyDelta,
+        Self::Dualcast,
+        Self::TranslFetch,
+        Self::CrcGen,
+        Self::CopyCrc,
+        Self::DifCheck,
+        Self::DifInsert,
+        Self::DifStrip,
+        Self::DifUpdate,
+        Self::DixGen,
+        Self::CacheFlush,
+        Self::UpdateWindow,
+        Self::InterDomainMemMove,
+        Self::InterDomainFill,
+        Self::InterDomainCompare,
+        Self::InterDomainCompareImm,
+        Self::InterDomainCacheFlush,
+    ];
+
     /// Returns the opcode as a u8 value.
     #[inline]
     pub const fn as_u8(self) -> u8 {
@@ -101,16 +150,85 @@ impl DsaOpcode {
             Self::DifUpdate => "DIF_UPDATE",
             Self::DixGen => "DIX_GEN",
             Self::CacheFlush => "CACHE_FLUSH",
+            Self::UpdateWindow => "UPDATE_WINDOW",
+            Self::InterDomainMemMove => "INTER_DOMAIN_MEMMOVE",
+            Self::InterDomainFill => "INTER_DOMAIN_MEMFILL",
+            Self::InterDomainCompare => "INTER_DOMAIN_COMPARE",
+            Self::InterDomainCompareImm => "INTER_DOMAIN_COMPARE_IMM",
+            Self::InterDomainCacheFlush => "INTER_DOMAIN_CACHE_FLUSH",
         }
     }
 }
 
-impl std::fmt::Display for DsaOpcode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for DsaOpcode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} ({:#04x})", self.name(), self.as_u8())
     }
 }
 
+impl TryFrom<u8> for DsaOpcode {
+    type Error = DsaError;
+
+    /// Convert a raw opcode byte.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidDescriptor` if the byte is not a defined
+    /// opcode.
+    fn try_from(value: u8) -> Result<Self, DsaError> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|op| op.as_u8() == value)
+            .ok_or(DsaError::InvalidDescriptor(DescriptorError::UnknownOpcode(
+                value,
+            )))
+    }
+}
+
+/// An opcode byte decoded from a descriptor.
+///
+/// Unlike [`DsaOpcode`], decoding never fails: bytes that are not defined
+/// opcodes (for example from newer hardware) are kept as `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodedOpcode {
+    /// A defined opcode.
+    Known(DsaOpcode),
+    /// An opcode byte this crate does not know.
+    Unknown(u8),
+}
+
+impl DecodedOpcode {
+    /// Returns the raw opcode byte.
+    pub const fn as_u8(self) -> u8 {
+        match self {
+            Self::Known(op) => op.as_u8(),
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<u8> for DecodedOpcode {
+    fn from(value: u8) -> Self {
+        DsaOpcode::try_from(value).map_or(Self::Unknown(value), Self::Known)
+    }
+}
+
+impl From<DsaOpcode> for DecodedOpcode {
+    fn from(op: DsaOpcode) -> Self {
+        Self::Known(op)
+    }
+}
+
+impl core::fmt::Display for DecodedOpcode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Known(op) => op.fmt(f),
+            Self::Unknown(value) => write!(f, "UNKNOWN ({value:#04x})"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +246,33 @@ mod tests {
         assert_eq!(DsaOpcode::CacheFlush.as_u8(), 0x20);
     }
 
+    #[test]
+    fn test_all_sorted() {
+        assert!(DsaOpcode::ALL
+            .windows(2)
+            .all(|w| w[0].as_u8() < w[1].as_u8()));
+    }
+
+    #[test]
+    fn test_try_from_u8() {
+        for op in DsaOpcode::ALL {
+            assert_eq!(DsaOpcode::try_from(op.as_u8()).unwrap(), op);
+        }
+        assert!(DsaOpcode::try_from(0x02).is_err());
+        assert!(DsaOpcode::try_from(0xFF).is_err());
+    }
+
+    #[test]
+    fn test_decoded_opcode() {
+        assert_eq!(
+            DecodedOpcode::from(0x10),
+            DecodedOpcode::Known(DsaOpcode::CrcGen)
+        );
+        assert_eq!(DecodedOpcode::from(0x7F), DecodedOpcode::Unknown(0x7F));
+        assert_eq!(DecodedOpcode::from(0x7F).as_u8(), 0x7F);
+        assert_eq!(DecodedOpcode::from(0x7F).to_string(), "UNKNOWN (0x7f)");
+    }
+
     #[test]
     fn test_opcode_display() {
         assert_eq!(format!("{}", DsaOpcode::CrcGen), "CRC_GEN (0x10)");
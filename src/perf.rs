@@ -0,0 +1,444 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Device performance counters through the kernel's perf PMU.
+//!
+//! The crate's own counters ([`DsaEngine::degrade_stats`], the operation
+//! trace) only see the descriptors of one engine. The device counts
+//! everything it processes, for every process and work queue, in its
+//! performance monitor, which the IDXD driver registers as a perf PMU named
+//! after the device (`dsa0`, `iax1`, ...) under
+//! `/sys/bus/event_source/devices`. A [`PerfCounters`] opens the events
+//! behind [`PerfStats`] on one device with `perf_event_open`, so device
+//! utilization and bandwidth can be read next to the crate's counters.
+//!
+//! An event is selected by a category and a mask of events within it
+//! ([`PerfEvent`]); where these go in the perf configuration is read from
+//! the PMU's `format` directory. The defaults in [`PerfEvents`] may be
+//! overridden for device generations that encode them differently.
+//!
+//! Counting device-wide events needs `CAP_PERFMON`, or
+//! `kernel.perf_event_paranoid` at 0 or below.
+//!
+//! Requires the `perf-events` feature; Linux only.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let counters = engine.perf_counters()?;
+//! // ... run the workload
+//! let stats = counters.stats()?;
+//! println!(
+//!     "{}: {} descriptors, {:.1} GB/s read",
+//!     stats.device,
+//!     stats.work_descriptors,
+//!     stats.read_bandwidth() / 1e9
+//! );
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::fd::FromRawFd;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const SYSFS_PMU_PATH: &str = "/sys/bus/event_source/devices";
+const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
+
+/// `perf_event_open` flag closing the counter on exec.
+const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+/// An event of a device's performance monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PerfEvent {
+    /// Event category, such as work queue, engine or address translation
+    /// events.
+    pub category: u8,
+    /// Mask of the events counted within the category.
+    pub event: u32,
+}
+
+impl PerfEvent {
+    /// An event by category and event mask.
+    pub const fn new(category: u8, event: u32) -> Self {
+        Self { category, event }
+    }
+}
+
+/// The events read into a [`PerfStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerfEvents {
+    /// Work descriptors processed by the device's engines.
+    pub work_descriptors: PerfEvent,
+    /// Cycles in which an engine was processing a descriptor.
+    pub engine_busy: PerfEvent,
+    /// Bytes read from memory.
+    pub bytes_read: PerfEvent,
+    /// Bytes written to memory.
+    pub bytes_written: PerfEvent,
+}
+
+impl Default for PerfEvents {
+    fn default() -> Self {
+        Self {
+            work_descriptors: PerfEvent::new(0x1, 0x1),
+            engine_busy: PerfEvent::new(0x1, 0x2),
+            bytes_read: PerfEvent::new(0x3, 0x1),
+            bytes_written: PerfEvent::new(0x3, 0x2),
+        }
+    }
+}
+
+/// Device counts since a [`PerfCounters`] was opened.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PerfStats {
+    /// Device the counts are for (e.g., "dsa0").
+    pub device: String,
+    /// Work descriptors processed.
+    pub work_descriptors: u64,
+    /// Engine-busy cycles, summed over the device's engines; divided by
+    /// the device clock cycles in `elapsed`, the engine utilization.
+    pub engine_busy: u64,
+    /// Bytes read from memory.
+    pub bytes_read: u64,
+    /// Bytes written to memory.
+    pub bytes_written: u64,
+    /// Time since the counters were opened.
+    pub elapsed: Duration,
+}
+
+impl PerfStats {
+    /// Bytes read per second over `elapsed`.
+    pub fn read_bandwidth(&self) -> f64 {
+        self.bytes_read as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Bytes written per second over `elapsed`.
+    pub fn write_bandwidth(&self) -> f64 {
+        self.bytes_written as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Counters of one device's performance monitor, counting from when they
+/// were opened until dropped.
+#[derive(Debug)]
+pub struct PerfCounters {
+    device: String,
+    /// Counters of the events of [`PerfEvents`], in field order.
+    counters: [File; 4],
+    opened: Instant,
+}
+
+impl PerfCounters {
+    /// Open the default [`PerfEvents`] of `device`, e.g. "dsa0".
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the device has no perf PMU,
+    /// `DsaError::InvalidArgument` if an event does not fit the PMU's
+    /// format, or `DsaError::PermissionDenied` if the process may not
+    /// count device events.
+    pub fn open(device: &str) -> Result<Self, DsaError> {
+        Self::open_with(device, PerfEvents::default())
+    }
+
+    /// Open `events` of `device`.
+    ///
+    /// # Errors
+    ///
+    /// As [`PerfCounters::open`].
+    pub fn open_with(device: &str, events: PerfEvents) -> Result<Self, DsaError> {
+        Pmu::read(&Path::new(SYSFS_PMU_PATH).join(device))?.open(device, events)
+    }
+
+    /// Device the counters are for.
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    /// Read the counts since the counters were opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if a counter cannot be read.
+    pub fn stats(&self) -> Result<PerfStats, DsaError> {
+        let mut counts = [0u64; 4];
+        for (count, mut counter) in counts.iter_mut().zip(&self.counters) {
+            let mut value = [0u8; 8];
+            counter.read_exact(&mut value)?;
+            *count = u64::from_ne_bytes(value);
+        }
+        let [work_descriptors, engine_busy, bytes_read, bytes_written] = counts;
+        Ok(PerfStats {
+            device: self.device.clone(),
+            work_descriptors,
+            engine_busy,
+            bytes_read,
+            bytes_written,
+            elapsed: self.opened.elapsed(),
+        })
+    }
+}
+
+impl DsaEngine {
+    /// Open the default performance counters of the engine's device.
+    ///
+    /// The counts cover everything the device processes, not only this
+    /// engine's descriptors.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the work queue's device cannot be found in
+    /// sysfs, or an error as [`PerfCounters::open`].
+    pub fn perf_counters(&self) -> Result<PerfCounters, DsaError> {
+        let unknown = || {
+            DsaError::InvalidArgument(format!(
+                "no device for work queue {}",
+                self.work_queue().path().display()
+            ))
+        };
+        let name = self.work_queue().path().file_name().ok_or_else(unknown)?;
+        // The work queue's sysfs entry links into its device's directory
+        let wq_dir = fs::canonicalize(Path::new(SYSFS_DSA_PATH).join(name))?;
+        let device = wq_dir
+            .parent()
+            .and_then(Path::file_name)
+            .ok_or_else(unknown)?;
+        PerfCounters::open(&device.to_string_lossy())
+    }
+}
+
+/// Position of a field in the perf configuration words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    /// Index of the word: 0 for `config`, 1 for `config1`, 2 for `config2`.
+    word: usize,
+    shift: u32,
+    width: u32,
+}
+
+impl Field {
+    /// Parse a PMU format attribute, such as `config:4-31` or `config1:0`.
+    fn parse(format: &str) -> Option<Self> {
+        let (word, bits) = format.trim().split_once(':')?;
+        let word = match word {
+            "config" => 0,
+            "config1" => 1,
+            "config2" => 2,
+            _ => return None,
+        };
+        let (lo, hi) = bits.split_once('-').unwrap_or((bits, bits));
+        let (lo, hi): (u32, u32) = (lo.parse().ok()?, hi.parse().ok()?);
+        (lo <= hi && hi < 64).then(|| Self {
+            word,
+            shift: lo,
+            width: hi - lo + 1,
+        })
+    }
+
+    /// Store `value` in `config`; false if it does not fit.
+    fn set(&self, config: &mut [u64; 3], value: u64) -> bool {
+        if self.width < 64 && value >> self.width != 0 {
+            return false;
+        }
+        config[self.word] |= value << self.shift;
+        true
+    }
+}
+
+/// A device's perf PMU, as described in sysfs.
+#[derive(Debug)]
+struct Pmu {
+    /// Perf event type of the PMU.
+    pmu_type: u32,
+    /// CPU to open device-wide events on.
+    cpu: i32,
+    category: Field,
+    event: Field,
+}
+
+impl Pmu {
+    /// Read the PMU whose sysfs directory is `dir`.
+    fn read(dir: &Path) -> Result<Self, DsaError> {
+        let invalid = |what: &str| {
+            DsaError::InvalidArgument(format!("invalid perf PMU {what} in {}", dir.display()))
+        };
+        let pmu_type = fs::read_to_string(dir.join("type"))?
+            .trim()
+            .parse()
+            .map_err(|_| invalid("type"))?;
+        // Device PMUs count on any one CPU of their mask
+        let cpu = match fs::read_to_string(dir.join("cpumask")) {
+            Ok(mask) => mask
+                .trim()
+                .split([',', '-'])
+                .next()
+                .and_then(|cpu| cpu.parse().ok())
+                .ok_or_else(|| invalid("cpumask"))?,
+            Err(_) => 0,
+        };
+        let field = |name: &str| {
+            let format = fs::read_to_string(dir.join("format").join(name))?;
+            Field::parse(&format).ok_or_else(|| invalid(name))
+        };
+        Ok(Self {
+            pmu_type,
+            cpu,
+            category: field("event_category")?,
+            event: field("event")?,
+        })
+    }
+
+    /// Perf configuration words selecting `event`.
+    fn config(&self, event: PerfEvent) -> Result<[u64; 3], DsaError> {
+        let mut config = [0u64; 3];
+        if !self.category.set(&mut config, event.category.into())
+            || !self.event.set(&mut config, event.event.into())
+        {
+            return Err(DsaError::InvalidArgument(format!(
+                "{event:?} does not fit the PMU's event format"
+            )));
+        }
+        Ok(config)
+    }
+
+    /// Open `events` on this PMU of `device`.
+    fn open(&self, device: &str, events: PerfEvents) -> Result<PerfCounters, DsaError> {
+        let events = [
+            events.work_descriptors,
+            events.engine_busy,
+            events.bytes_read,
+            events.bytes_written,
+        ];
+        let mut counters = Vec::with_capacity(events.len());
+        for event in events {
+            counters.push(self.open_event(device, self.config(event)?)?);
+        }
+        Ok(PerfCounters {
+            device: device.to_string(),
+            counters: counters.try_into().expect("one counter per event"),
+            opened: Instant::now(),
+        })
+    }
+
+    /// Open one counting event with configuration `config`.
+    fn open_event(&self, device: &str, config: [u64; 3]) -> Result<File, DsaError> {
+        let attr = PerfEventAttr {
+            pmu_type: self.pmu_type,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config: config[0],
+            config1: config[1],
+            config2: config[2],
+            ..PerfEventAttr::default()
+        };
+        // SAFETY: attr is a valid perf_event_attr of the size it declares
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                -1 as libc::pid_t,
+                self.cpu,
+                -1 as libc::c_int,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            let err = std::io::Error::last_os_error();
+            return Err(match err.raw_os_error() {
+                Some(libc::EACCES | libc::EPERM) => DsaError::PermissionDenied(format!(
+                    "counting {device} events needs CAP_PERFMON or kernel.perf_event_paranoid <= 0"
+                )),
+                _ => err.into(),
+            });
+        }
+        // SAFETY: perf_event_open returned a new descriptor that we own
+        Ok(unsafe { File::from_raw_fd(fd as libc::c_int) })
+    }
+}
+
+/// The leading fields of the kernel's `struct perf_event_attr`, up to
+/// `config2` (`PERF_ATTR_SIZE_VER1`); the kernel zero-extends the rest.
+#[repr(C)]
+#[derive(Debug, Default)]
+struct PerfEventAttr {
+    pmu_type: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    /// Bit field of `disabled`, `inherit`, ...; all clear counts at once.
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+    config2: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field() {
+        let event = Field::parse("config:4-31\n").unwrap();
+        assert_eq!(
+            event,
+            Field {
+                word: 0,
+                shift: 4,
+                width: 28
+            }
+        );
+        assert_eq!(
+            Field::parse("config1:7"),
+            Some(Field {
+                word: 1,
+                shift: 7,
+                width: 1
+            })
+        );
+        assert_eq!(Field::parse("config3:0-3"), None);
+        assert_eq!(Field::parse("config:8-4"), None);
+        assert_eq!(Field::parse("config:0-64"), None);
+
+        let mut config = [0u64; 3];
+        assert!(event.set(&mut config, 0x0FFF_FFFF));
+        assert!(!event.set(&mut config, 0x1000_0000));
+        assert_eq!(config, [0xFFFF_FFF0, 0, 0]);
+        assert_eq!(std::mem::size_of::<PerfEventAttr>(), 72);
+    }
+
+    #[test]
+    fn test_pmu() {
+        let dir = std::env::temp_dir().join(format!("dsa-perf-{}", std::process::id()));
+        fs::create_dir_all(dir.join("format")).unwrap();
+        fs::write(dir.join("type"), "42\n").unwrap();
+        fs::write(dir.join("cpumask"), "56-111\n").unwrap();
+        fs::write(dir.join("format/event_category"), "config:0-3\n").unwrap();
+        fs::write(dir.join("format/event"), "config:4-31\n").unwrap();
+
+        let pmu = Pmu::read(&dir).unwrap();
+        assert_eq!((pmu.pmu_type, pmu.cpu), (42, 56));
+        assert_eq!(pmu.config(PerfEvent::new(0x3, 0x2)).unwrap(), [0x23, 0, 0]);
+        assert!(matches!(
+            pmu.config(PerfEvent::new(0x10, 0x1)),
+            Err(DsaError::InvalidArgument(_))
+        ));
+
+        fs::remove_file(dir.join("format/event")).unwrap();
+        assert!(matches!(Pmu::read(&dir), Err(DsaError::Io(_))));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            PerfCounters::open("dsa-perf-test-missing"),
+            Err(DsaError::Io(_))
+        ));
+    }
+}
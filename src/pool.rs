@@ -0,0 +1,396 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Engine pool with priority-based routing.
+//!
+//! A [`DsaEnginePool`] holds engines for several work queues, grouped by
+//! [`Priority`]. Latency-sensitive operations are routed to their own queues
+//! (typically dedicated work queues), so they never wait behind bulk traffic
+//! on a shared queue.
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Scheduling class of an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Priority {
+    /// Small, latency-sensitive operations on the request path.
+    Latency,
+    /// Large background operations where throughput matters more than
+    /// latency.
+    #[default]
+    Bulk,
+}
+
+/// How a pool chooses among the engines of one priority class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Balance {
+    /// Use each engine in turn.
+    #[default]
+    RoundRobin,
+    /// Prefer the engine with the least estimated outstanding work, based
+    /// on each engine's in-flight operations and recent throughput.
+    ///
+    /// A queue slowed by a noisy neighbor in its engine group completes
+    /// fewer bytes per second and therefore receives less work.
+    Bandwidth,
+}
+
+/// Recent load of one engine.
+#[derive(Default)]
+struct Load {
+    /// Operations currently running on the engine.
+    in_flight: AtomicU64,
+    /// Moving average of nanoseconds per KiB processed (0 until measured).
+    ns_per_kib: AtomicU64,
+}
+
+impl Load {
+    /// Weight of the moving average: each sample contributes 1/8.
+    const EWMA_SHIFT: u32 = 3;
+
+    /// Estimated time to drain the engine's work plus one more operation.
+    fn cost(&self) -> u64 {
+        let in_flight = self.in_flight.load(Ordering::Relaxed);
+        (in_flight + 1).saturating_mul(self.ns_per_kib.load(Ordering::Relaxed))
+    }
+
+    /// Fold a completed operation into the throughput estimate.
+    fn record(&self, bytes: usize, elapsed: Duration) {
+        let kib = (bytes as u64).div_ceil(1024).max(1);
+        let sample = (elapsed.as_nanos() as u64 / kib).max(1);
+        // Concurrent updates may lose a sample, which only slows adaptation
+        let old = self.ns_per_kib.load(Ordering::Relaxed);
+        let new = if old == 0 {
+            sample
+        } else {
+            old - (old >> Self::EWMA_SHIFT) + (sample >> Self::EWMA_SHIFT)
+        };
+        self.ns_per_kib.store(new, Ordering::Relaxed);
+    }
+}
+
+/// An engine and its load statistics.
+struct Member {
+    engine: DsaEngine,
+    load: Load,
+}
+
+/// Engines for one priority class.
+#[derive(Default)]
+struct Class {
+    members: Vec<Member>,
+    next: AtomicUsize,
+}
+
+impl Class {
+    fn pick(&self, balance: Balance) -> Option<&Member> {
+        let n = self.members.len();
+        if n == 0 {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % n;
+        match balance {
+            Balance::RoundRobin => Some(&self.members[start]),
+            // Scan from the rotating start so ties are spread across engines
+            Balance::Bandwidth => (0..n)
+                .map(|i| &self.members[(start + i) % n])
+                .min_by_key(|member| member.load.cost()),
+        }
+    }
+}
+
+/// A set of DSA engines that routes operations by priority.
+///
+/// Each operation is tagged with a [`Priority`] and runs on an engine of
+/// that class, chosen according to the pool's [`Balance`]. If a class has
+/// no engines, the other class is used, so a
+/// pool with a single queue still accepts all operations.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::pool::{DsaEnginePool, Priority};
+///
+/// let pool = DsaEnginePool::open_all()?;
+/// let crc = pool.crc32(Priority::Latency, b"request header")?;
+///
+/// let mut scratch = vec![0xFFu8; 1 << 20];
+/// pool.memset(Priority::Bulk, &mut scratch, 0)?;
+/// # Ok::<(), dsa_rust::DsaError>(())
+/// ```
+#[derive(Default)]
+pub struct DsaEnginePool {
+    latency: Class,
+    bulk: Class,
+    balance: Balance,
+}
+
+impl DsaEnginePool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open every enabled work queue on every DSA device.
+    ///
+    /// Dedicated work queues serve [`Priority::Latency`] and shared work
+    /// queues serve [`Priority::Bulk`]. Queues that cannot be opened (for
+    /// example a dedicated queue already reserved by another process) are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::NoWorkQueue` if no work queue could be opened.
+    #[cfg(dsa_portal)]
+    pub fn open_all() -> Result<Self, DsaError> {
+        use crate::wq::WorkQueueType;
+
+        let mut pool = Self::new();
+        for device in crate::device::discover_devices()? {
+            for wq_info in device.work_queues.iter().filter(|wq| wq.state.is_enabled()) {
+                match device.open_enabled_wq(&wq_info.name) {
+                    Ok(wq) => {
+                        let priority = match wq_info.wq_type {
+                            WorkQueueType::Dedicated => Priority::Latency,
+                            WorkQueueType::Shared => Priority::Bulk,
+                        };
+                        pool.add(priority, DsaEngine::from_wq(wq));
+                    }
+                    Err(e) => log::warn!("Skipping work queue {}: {}", wq_info.name, e),
+                }
+            }
+        }
+
+        if pool.is_empty() {
+            return Err(DsaError::NoWorkQueue);
+        }
+        Ok(pool)
+    }
+
+    /// Open a pool with a single engine serving both priorities.
+    #[cfg(not(dsa_portal))]
+    pub fn open_all() -> Result<Self, DsaError> {
+        let mut pool = Self::new();
+        pool.add(Priority::Bulk, DsaEngine::open_first()?);
+        Ok(pool)
+    }
+
+    /// Add an engine serving `priority`.
+    pub fn add(&mut self, priority: Priority, engine: DsaEngine) {
+        self.class_mut(priority).members.push(Member {
+            engine,
+            load: Load::default(),
+        });
+    }
+
+    /// Set how engines within a priority class are chosen.
+    pub fn set_balance(&mut self, balance: Balance) {
+        self.balance = balance;
+    }
+
+    /// Get how engines within a priority class are chosen.
+    pub fn balance(&self) -> Balance {
+        self.balance
+    }
+
+    /// Number of engines serving `priority` directly.
+    pub fn len(&self, priority: Priority) -> usize {
+        self.class(priority).members.len()
+    }
+
+    /// Returns true if the pool has no engines.
+    pub fn is_empty(&self) -> bool {
+        self.latency.members.is_empty() && self.bulk.members.is_empty()
+    }
+
+    /// Select the engine for the next operation of `priority`.
+    ///
+    /// Operations run directly on the returned engine are not counted
+    /// towards its load; prefer the pool's own operation methods when
+    /// using [`Balance::Bandwidth`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::NoWorkQueue` if the pool is empty.
+    pub fn engine(&self, priority: Priority) -> Result<&DsaEngine, DsaError> {
+        self.member(priority).map(|member| &member.engine)
+    }
+
+    /// Compute a CRC32 on an engine serving `priority`.
+    pub fn crc32(&self, priority: Priority, data: &[u8]) -> Result<u32, DsaError> {
+        self.dispatch(priority, data.len(), |engine| engine.crc32(data))
+    }
+
+    /// Compute CRC32s of many buffers on an engine serving `priority`.
+    pub fn crc32_many(&self, priority: Priority, bufs: &[&[u8]]) -> Result<Vec<u32>, DsaError> {
+        let bytes = bufs.iter().map(|buf| buf.len()).sum();
+        self.dispatch(priority, bytes, |engine| engine.crc32_many(bufs))
+    }
+
+    /// Copy memory on an engine serving `priority`.
+    pub fn memcpy(&self, priority: Priority, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+        self.dispatch(priority, src.len(), |engine| engine.memcpy(dst, src))
+    }
+
+    /// Fill memory on an engine serving `priority`.
+    pub fn memset(&self, priority: Priority, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
+        let bytes = dst.len();
+        self.dispatch(priority, bytes, |engine| engine.memset(dst, pattern))
+    }
+
+    /// Compare memory on an engine serving `priority`.
+    pub fn memcmp(&self, priority: Priority, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
+        self.dispatch(priority, a.len(), |engine| engine.memcmp(a, b))
+    }
+
+    /// Compute the CRC32 of one large buffer on all engines serving
+    /// `priority` at once, like [`DsaEngine::crc32_parallel`].
+    ///
+    /// The buffer is split into one contiguous part per engine, the chunks
+    /// of all parts are in flight together, and the CRC32s of the parts
+    /// are joined with [`crc32c_combine`](crate::crc32c_combine).
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `chunk_size` is zero,
+    /// `DsaError::NoWorkQueue` if the pool is empty, or an error if an
+    /// operation fails.
+    pub fn crc32_parallel(
+        &self,
+        priority: Priority,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Result<u32, DsaError> {
+        let class = match self.class(priority) {
+            class if class.members.is_empty() => match priority {
+                Priority::Latency => &self.bulk,
+                Priority::Bulk => &self.latency,
+            },
+            class => class,
+        };
+        let engines: Vec<&DsaEngine> = class.members.iter().map(|m| &m.engine).collect();
+        for member in &class.members {
+            member.load.in_flight.fetch_add(1, Ordering::Relaxed);
+        }
+        let result = crate::crc_streams::crc32_split(&engines, data, chunk_size);
+        for member in &class.members {
+            member.load.in_flight.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Pick an engine, falling back to the other priority class.
+    fn member(&self, priority: Priority) -> Result<&Member, DsaError> {
+        let fallback = match priority {
+            Priority::Latency => &self.bulk,
+            Priority::Bulk => &self.latency,
+        };
+        self.class(priority)
+            .pick(self.balance)
+            .or_else(|| fallback.pick(self.balance))
+            .ok_or(DsaError::NoWorkQueue)
+    }
+
+    /// Run `op` on an engine for `priority`, tracking the engine's load.
+    fn dispatch<T>(
+        &self,
+        priority: Priority,
+        bytes: usize,
+        op: impl FnOnce(&DsaEngine) -> Result<T, DsaError>,
+    ) -> Result<T, DsaError> {
+        let member = self.member(priority)?;
+        member.load.in_flight.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        let result = op(&member.engine);
+        member.load.in_flight.fetch_sub(1, Ordering::Relaxed);
+        if result.is_ok() {
+            member.load.record(bytes, start.elapsed());
+        }
+        result
+    }
+
+    fn class(&self, priority: Priority) -> &Class {
+        match priority {
+            Priority::Latency => &self.latency,
+            Priority::Bulk => &self.bulk,
+        }
+    }
+
+    fn class_mut(&mut self, priority: Priority) -> &mut Class {
+        match priority {
+            Priority::Latency => &mut self.latency,
+            Priority::Bulk => &mut self.bulk,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_pool() {
+        let pool = DsaEnginePool::new();
+        assert!(pool.is_empty());
+        assert!(matches!(
+            pool.engine(Priority::Latency),
+            Err(DsaError::NoWorkQueue)
+        ));
+    }
+
+    #[test]
+    fn test_load_cost() {
+        let load = Load::default();
+        assert_eq!(load.cost(), 0);
+
+        load.record(4096, Duration::from_micros(4));
+        assert_eq!(load.ns_per_kib.load(Ordering::Relaxed), 1000);
+        load.in_flight.store(2, Ordering::Relaxed);
+        assert_eq!(load.cost(), 3000);
+
+        // A slow sample moves the average by 1/8 of the difference
+        load.record(1024, Duration::from_micros(9));
+        assert_eq!(load.ns_per_kib.load(Ordering::Relaxed), 2000);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_crc32_parallel() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut pool = DsaEnginePool::new();
+        for _ in 0..4 {
+            let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+            pool.add(Priority::Bulk, engine);
+        }
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i % 253) as u8).collect();
+        let crc = pool.crc32_parallel(Priority::Latency, &data, 4096).unwrap();
+        assert_eq!(crc, crate::crc::software_crc32(&data, 0));
+        assert_eq!(emulator.submitted(), 16);
+        assert!(matches!(
+            DsaEnginePool::new().crc32_parallel(Priority::Bulk, &data, 4096),
+            Err(DsaError::NoWorkQueue)
+        ));
+    }
+
+    #[test]
+    fn test_priority_fallback() {
+        // Requires DSA hardware (or the software fallback on Windows)
+        let Ok(engine) = DsaEngine::open_first() else {
+            return;
+        };
+        let mut pool = DsaEnginePool::new();
+        pool.add(Priority::Bulk, engine);
+
+        assert_eq!(pool.len(Priority::Latency), 0);
+        let bulk = pool.engine(Priority::Bulk).unwrap() as *const DsaEngine;
+        let latency = pool.engine(Priority::Latency).unwrap() as *const DsaEngine;
+        assert_eq!(bulk, latency);
+    }
+}
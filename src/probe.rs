@@ -0,0 +1,183 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Startup health probe.
+//!
+//! [`DsaEngine::probe`] runs a short series of operations on the live work
+//! queue and reports no-op round-trip latency, submission latency and copy
+//! bandwidth, so a service can verify at startup that its accelerator is
+//! healthy and performs within expected bounds.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//! use std::time::Duration;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let report = engine.probe()?;
+//! if report.noop_latency.median > Duration::from_micros(50) {
+//!     eprintln!("DSA is slow: {report:?}");
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::time::{Duration, Instant};
+
+/// Number of timed no-ops per latency measurement.
+const LATENCY_SAMPLES: usize = 64;
+
+/// Copy sizes whose bandwidth is measured.
+pub const PROBE_COPY_SIZES: [usize; 3] = [4 << 10, 64 << 10, 1 << 20];
+
+/// Bytes copied per bandwidth measurement, spread over repeated copies.
+const BANDWIDTH_BYTES: usize = 16 << 20;
+
+/// Distribution of a latency over the probe's samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    /// Fastest sample.
+    pub min: Duration,
+    /// Median sample.
+    pub median: Duration,
+    /// Slowest sample.
+    pub max: Duration,
+}
+
+impl Latency {
+    /// Summarize `samples`, which must not be empty.
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        Self {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Copy throughput at one transfer size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bandwidth {
+    /// Bytes per copy.
+    pub size: usize,
+    /// Sustained throughput of back-to-back blocking copies.
+    pub bytes_per_sec: f64,
+}
+
+/// Measurements taken by [`DsaEngine::probe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeReport {
+    /// Time from submitting a no-op to observing its completion.
+    pub noop_latency: Latency,
+    /// Time for the portal to accept a descriptor: the ENQCMD round trip on
+    /// a shared work queue, the posted MOVDIR64B store on a dedicated one.
+    /// `None` where operations run in software.
+    pub submit_latency: Option<Latency>,
+    /// Copy bandwidth at each of [`PROBE_COPY_SIZES`].
+    pub copy_bandwidth: Vec<Bandwidth>,
+}
+
+impl ProbeReport {
+    /// Copy bandwidth measured at `size`, in bytes per second.
+    pub fn bandwidth_at(&self, size: usize) -> Option<f64> {
+        self.copy_bandwidth
+            .iter()
+            .find(|bandwidth| bandwidth.size == size)
+            .map(|bandwidth| bandwidth.bytes_per_sec)
+    }
+}
+
+impl DsaEngine {
+    /// Measure latency and bandwidth on this engine's work queue.
+    ///
+    /// Runs a few hundred no-ops and about 50 MiB of copies, typically
+    /// taking a few milliseconds on hardware. The rate limit is bypassed,
+    /// but the queue-full policy applies.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of any probe operation; a device that fails
+    /// the probe is not healthy.
+    pub fn probe(&self) -> Result<ProbeReport, DsaError> {
+        let wq = self.work_queue();
+
+        let mut samples = Vec::with_capacity(LATENCY_SAMPLES);
+        for _ in 0..LATENCY_SAMPLES {
+            let start = Instant::now();
+            self.retry(|| wq.noop())?;
+            samples.push(start.elapsed());
+        }
+        let noop_latency = Latency::from_samples(samples);
+
+        #[cfg(dsa_portal)]
+        let submit_latency = {
+            let mut samples = Vec::with_capacity(LATENCY_SAMPLES);
+            for _ in 0..LATENCY_SAMPLES {
+                samples.push(self.retry(|| wq.timed_noop())?);
+            }
+            Some(Latency::from_samples(samples))
+        };
+        #[cfg(not(dsa_portal))]
+        let submit_latency = None;
+
+        let src = vec![0xA5u8; PROBE_COPY_SIZES[PROBE_COPY_SIZES.len() - 1]];
+        let mut dst = vec![0u8; src.len()];
+        let mut copy_bandwidth = Vec::with_capacity(PROBE_COPY_SIZES.len());
+        for size in PROBE_COPY_SIZES {
+            let (src, dst) = (&src[..size], &mut dst[..size]);
+            // Warm up: fault in the pages and the device translation cache
+            self.retry(|| wq.memcpy(dst, src))?;
+
+            let copies = (BANDWIDTH_BYTES / size).max(1);
+            let start = Instant::now();
+            for _ in 0..copies {
+                self.retry(|| wq.memcpy(dst, src))?;
+            }
+            let elapsed = start.elapsed().as_secs_f64().max(f64::MIN_POSITIVE);
+            copy_bandwidth.push(Bandwidth {
+                size,
+                bytes_per_sec: (copies * size) as f64 / elapsed,
+            });
+        }
+
+        Ok(ProbeReport {
+            noop_latency,
+            submit_latency,
+            copy_bandwidth,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_from_samples() {
+        let samples = [5, 1, 3, 2, 4].map(Duration::from_micros).to_vec();
+        let latency = Latency::from_samples(samples);
+        assert_eq!(latency.min, Duration::from_micros(1));
+        assert_eq!(latency.median, Duration::from_micros(3));
+        assert_eq!(latency.max, Duration::from_micros(5));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_probe_emulated() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let report = engine.probe().unwrap();
+        assert!(report.noop_latency.min <= report.noop_latency.median);
+        assert!(report.noop_latency.median <= report.noop_latency.max);
+        assert!(report.submit_latency.is_some());
+        assert_eq!(report.copy_bandwidth.len(), PROBE_COPY_SIZES.len());
+        assert!(report.bandwidth_at(4 << 10).unwrap() > 0.0);
+        assert!(report.bandwidth_at(12345).is_none());
+    }
+}
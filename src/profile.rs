@@ -0,0 +1,249 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Operation profiles.
+//!
+//! Getting good results from DSA means choosing descriptor flags and a
+//! completion wait strategy per workload: whether the destination is
+//! written to cache, whether the device resolves page faults itself, and
+//! whether to spin or sleep while an operation is pending. A [`Profile`]
+//! bundles these choices under a name:
+//!
+//! | Profile | Flags | Waiting |
+//! |---|---|---|
+//! | [`LowLatency`](Profile::LowLatency) | destination to cache | spin |
+//! | [`Throughput`](Profile::Throughput) | block on fault | [`Backoff::default`] |
+//! | [`Persistent`](Profile::Persistent) | destination readback, block on fault | sleep early |
+//!
+//! A profile applies to every operation of an engine with
+//! [`DsaEngine::set_profile`], or to a single call through
+//! [`OpContext::with_profile`](crate::cancel::OpContext::with_profile), which
+//! takes precedence. Cache control and readback are only set on
+//! descriptors that write a destination. Block on fault is only set if the
+//! work queue supports it ([`Capabilities::block_on_fault`]). Descriptors
+//! inside a batch keep their own flags.
+//!
+//! Completion is always polled; there is no interrupt wait. Spinning and
+//! sleeping are the two ends of the [`Backoff`] a profile selects.
+//! Profiles only affect descriptors; the Windows software fallback ignores
+//! them.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::cancel::OpContext;
+//! use dsa_rust::{DsaEngine, Profile};
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! engine.set_profile(Some(Profile::Throughput));
+//!
+//! // One small copy whose result is read right away
+//! let src = [7u8; 256];
+//! let mut dst = [0u8; 256];
+//! let ctx = OpContext::new().with_profile(Profile::LowLatency);
+//! engine.memcpy_with_context(&mut dst, &src, &ctx)?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::backoff::Backoff;
+use crate::capabilities::Capabilities;
+use crate::descriptor::DescriptorFlags;
+use crate::engine::DsaEngine;
+use crate::opcode::DsaOpcode;
+use std::time::Duration;
+
+/// A named bundle of descriptor flags and a completion wait strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Profile {
+    /// Small operations whose result is used immediately: the destination
+    /// is written to cache and completion is polled in a tight loop.
+    LowLatency,
+    /// Large transfers: the device resolves page faults instead of
+    /// completing partially, and waiting backs off to sleeping.
+    Throughput,
+    /// Writes to persistent memory: the device reads the destination back
+    /// before completing, so completion means the data is durable, and
+    /// waiting sleeps early.
+    Persistent,
+}
+
+impl Profile {
+    /// All profiles.
+    pub const ALL: [Profile; 3] = [Self::LowLatency, Self::Throughput, Self::Persistent];
+
+    /// Descriptor flags the profile sets, where applicable.
+    pub fn flags(self) -> DescriptorFlags {
+        match self {
+            Self::LowLatency => DescriptorFlags::CACHE_CTRL,
+            Self::Throughput => DescriptorFlags::BLOCK_ON_FAULT,
+            Self::Persistent => DescriptorFlags::DEST_READBACK | DescriptorFlags::BLOCK_ON_FAULT,
+        }
+    }
+
+    /// How the profile waits for completion.
+    pub fn backoff(self) -> Backoff {
+        match self {
+            Self::LowLatency => Backoff::SPIN,
+            Self::Throughput => Backoff::default(),
+            Self::Persistent => Backoff {
+                spin: Duration::ZERO,
+                pause: Duration::from_micros(10),
+                max_pauses: 64,
+                sleep: Duration::from_micros(50),
+            },
+        }
+    }
+
+    /// The flags the profile sets on a descriptor with opcode `op`, on a
+    /// work queue that does or does not support block on fault.
+    pub(crate) fn flags_for(self, op: u8, block_on_fault: bool) -> DescriptorFlags {
+        let Ok(op) = DsaOpcode::try_from(op) else {
+            return DescriptorFlags::empty();
+        };
+        let mut flags = self.flags();
+        if !writes_destination(op) {
+            flags.remove(DescriptorFlags::CACHE_CTRL | DescriptorFlags::DEST_READBACK);
+        }
+        if !block_on_fault || matches!(op, DsaOpcode::Noop | DsaOpcode::Batch | DsaOpcode::Drain) {
+            flags.remove(DescriptorFlags::BLOCK_ON_FAULT);
+        }
+        flags
+    }
+
+    /// The flags the profile sets on a descriptor with opcode `op`, given
+    /// the engine's capabilities.
+    pub fn flags_with(self, op: DsaOpcode, caps: &Capabilities) -> DescriptorFlags {
+        self.flags_for(op.as_u8(), caps.block_on_fault)
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::LowLatency => "low-latency",
+            Self::Throughput => "throughput",
+            Self::Persistent => "persistent",
+        })
+    }
+}
+
+/// Returns true if descriptors with opcode `op` write a destination buffer.
+fn writes_destination(op: DsaOpcode) -> bool {
+    matches!(
+        op,
+        DsaOpcode::MemMove
+            | DsaOpcode::MemFill
+            | DsaOpcode::CreateDelta
+            | DsaOpcode::ApplyDelta
+            | DsaOpcode::Dualcast
+            | DsaOpcode::CopyCrc
+            | DsaOpcode::DifInsert
+            | DsaOpcode::DifStrip
+            | DsaOpcode::DifUpdate
+            | DsaOpcode::DixGen
+            | DsaOpcode::InterDomainMemMove
+            | DsaOpcode::InterDomainFill
+    )
+}
+
+impl DsaEngine {
+    /// Apply `profile` to every operation, or (with `None`) stop adding
+    /// profile flags.
+    ///
+    /// Setting a profile also sets its [`Backoff`]; clearing it leaves the
+    /// backoff as it is. A later [`DsaEngine::set_backoff`] overrides the
+    /// profile's backoff.
+    pub fn set_profile(&mut self, profile: Option<Profile>) {
+        self.work_queue_mut().set_profile(profile);
+    }
+
+    /// Get the profile applied to every operation, if any.
+    pub fn profile(&self) -> Option<Profile> {
+        self.work_queue().profile()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_for() {
+        let copy = DsaOpcode::MemMove.as_u8();
+        let crc = DsaOpcode::CrcGen.as_u8();
+        assert_eq!(
+            Profile::LowLatency.flags_for(copy, true),
+            DescriptorFlags::CACHE_CTRL
+        );
+        assert!(Profile::LowLatency.flags_for(crc, true).is_empty());
+        assert_eq!(
+            Profile::Persistent.flags_for(copy, true),
+            DescriptorFlags::DEST_READBACK | DescriptorFlags::BLOCK_ON_FAULT
+        );
+        assert_eq!(
+            Profile::Persistent.flags_for(copy, false),
+            DescriptorFlags::DEST_READBACK
+        );
+        assert_eq!(
+            Profile::Throughput.flags_for(crc, true),
+            DescriptorFlags::BLOCK_ON_FAULT
+        );
+        assert!(Profile::Throughput
+            .flags_for(DsaOpcode::Batch.as_u8(), true)
+            .is_empty());
+        assert!(Profile::Throughput.flags_for(0xFF, true).is_empty());
+
+        // No profile sets conflicting flags
+        for profile in Profile::ALL {
+            assert_eq!(profile.flags().conflict(), None, "{profile}");
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_profile() {
+        use crate::cancel::OpContext;
+        use crate::emulator::{Emulator, Fault};
+        use crate::error::DsaError;
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        assert_eq!(engine.profile(), None);
+        engine.set_profile(Some(Profile::LowLatency));
+        assert_eq!(engine.profile(), Some(Profile::LowLatency));
+        assert_eq!(engine.work_queue().backoff(), Backoff::SPIN);
+
+        let src = vec![3u8; 4096];
+        let mut dst = vec![0u8; 4096];
+        engine.memcpy(&mut dst, &src).unwrap();
+        assert_eq!(dst, src);
+
+        // Without block on fault the fault reaches the work queue's caller
+        emulator.inject(Fault::PageFault { offset: 100 });
+        assert!(matches!(
+            engine.work_queue().memset(&mut dst, 0),
+            Err(DsaError::PageFault { .. })
+        ));
+
+        // Per call, a profile that blocks on faults lets the device resolve it
+        let ctx = OpContext::new().with_profile(Profile::Persistent);
+        emulator.inject(Fault::PageFault { offset: 100 });
+        engine.memset_with_context(&mut dst, 0, &ctx).unwrap();
+        assert!(dst.iter().all(|&b| b == 0));
+
+        engine.set_profile(Some(Profile::Throughput));
+        emulator.inject(Fault::PageFault { offset: 100 });
+        assert_eq!(
+            engine.work_queue().crc32(&src, 0).unwrap(),
+            crate::crc::software_crc32(&src, 0)
+        );
+
+        // Clearing the profile keeps its backoff
+        engine.set_profile(None);
+        assert_eq!(engine.profile(), None);
+        assert_eq!(engine.work_queue().backoff(), Backoff::default());
+    }
+}
@@ -0,0 +1,197 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Submission rate limiting.
+//!
+//! A [`RateLimit`] caps the bytes and/or operations per second an engine
+//! submits. Limits are enforced with token buckets that hold up to one second
+//! of budget, so short bursts run at full speed while sustained traffic is
+//! held to the configured rate. This lets a background bulk job share a work
+//! queue with latency-sensitive traffic without sleeps in application code.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Bytes/sec and operations/sec limits for an engine.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::{DsaEngine, RateLimit};
+///
+/// let mut engine = DsaEngine::open_first()?;
+/// // Background scrubbing: at most 512 MiB/s and 10k operations/s
+/// engine.set_rate_limit(Some(
+///     RateLimit::bytes_per_sec(512 << 20).with_ops_per_sec(10_000),
+/// ));
+/// # Ok::<(), dsa_rust::DsaError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimit {
+    /// Maximum bytes processed per second, or `None` for no byte limit.
+    pub bytes_per_sec: Option<u64>,
+    /// Maximum operations submitted per second, or `None` for no op limit.
+    pub ops_per_sec: Option<u64>,
+}
+
+impl RateLimit {
+    /// Limit only the byte rate.
+    pub fn bytes_per_sec(rate: u64) -> Self {
+        Self {
+            bytes_per_sec: Some(rate),
+            ops_per_sec: None,
+        }
+    }
+
+    /// Limit only the operation rate.
+    pub fn ops_per_sec(rate: u64) -> Self {
+        Self {
+            bytes_per_sec: None,
+            ops_per_sec: Some(rate),
+        }
+    }
+
+    /// Add or replace the byte rate limit.
+    pub fn with_bytes_per_sec(mut self, rate: u64) -> Self {
+        self.bytes_per_sec = Some(rate);
+        self
+    }
+
+    /// Add or replace the operation rate limit.
+    pub fn with_ops_per_sec(mut self, rate: u64) -> Self {
+        self.ops_per_sec = Some(rate);
+        self
+    }
+}
+
+/// A token bucket holding up to one second of budget.
+///
+/// Tokens may go negative: a caller takes what it needs and waits off the
+/// debt, so requests larger than the bucket are still admitted at the
+/// configured average rate.
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens added per second; also the bucket capacity.
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            rate,
+            tokens: rate,
+            last: now,
+        }
+    }
+
+    /// Take `n` tokens and return how long the caller must wait.
+    fn take(&mut self, n: u64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last = now;
+
+        self.tokens -= n as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// Enforces a [`RateLimit`] across all threads using an engine.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    buckets: Mutex<(Option<TokenBucket>, Option<TokenBucket>)>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        let now = Instant::now();
+        Self {
+            limit,
+            buckets: Mutex::new((
+                limit.bytes_per_sec.map(|rate| TokenBucket::new(rate, now)),
+                limit.ops_per_sec.map(|rate| TokenBucket::new(rate, now)),
+            )),
+        }
+    }
+
+    pub(crate) fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Reserve budget for `ops` operations totalling `bytes` bytes and
+    /// return how long the caller must wait before submitting.
+    fn reserve(&self, bytes: u64, ops: u64, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let (byte_bucket, op_bucket) = &mut *buckets;
+        let byte_wait = byte_bucket
+            .as_mut()
+            .map_or(Duration::ZERO, |b| b.take(bytes, now));
+        let op_wait = op_bucket
+            .as_mut()
+            .map_or(Duration::ZERO, |b| b.take(ops, now));
+        byte_wait.max(op_wait)
+    }
+
+    /// Block until `ops` operations totalling `bytes` bytes may be submitted.
+    pub(crate) fn acquire(&self, bytes: u64, ops: u64) {
+        let wait = self.reserve(bytes, ops, Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1000, start);
+
+        // A full second of budget is available immediately
+        assert_eq!(bucket.take(1000, start), Duration::ZERO);
+        // The next request must wait for its tokens to accrue
+        assert_eq!(bucket.take(500, start), Duration::from_millis(500));
+        // After the wait, the debt is repaid
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.take(0, later), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_refill_is_capped() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(100, start);
+        let much_later = start + Duration::from_secs(60);
+        assert_eq!(bucket.take(100, much_later), Duration::ZERO);
+        assert!(bucket.take(1, much_later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_limiter_uses_slowest_bucket() {
+        let limiter = RateLimiter::new(RateLimit::bytes_per_sec(1 << 20).with_ops_per_sec(10));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(4096, 10, now), Duration::ZERO);
+        // Byte budget remains, but the op budget is exhausted
+        assert_eq!(limiter.reserve(4096, 1, now), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = RateLimiter::new(RateLimit::default());
+        assert_eq!(
+            limiter.reserve(u64::MAX, u64::MAX, Instant::now()),
+            Duration::ZERO
+        );
+    }
+}
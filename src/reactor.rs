@@ -0,0 +1,441 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Completion reactor.
+//!
+//! Operations that are submitted without blocking the caller register their
+//! completion record with a process-wide reactor thread. The reactor polls
+//! all outstanding records and, when one completes, marks the operation done
+//! and wakes whoever is waiting for it (an async task's `Waker` or a
+//! blocked thread), or runs the operation's completion callback.
+
+use crate::descriptor::DsaCompletionRecord;
+use crate::error::{DsaError, ErrorContext};
+use std::any::Any;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::Waker;
+use std::time::Instant;
+
+/// Callback run on the reactor thread when an operation completes, or with
+/// an error if it could not be submitted.
+pub(crate) type Callback = Box<dyn FnOnce(Result<&DsaCompletionRecord, DsaError>) + Send>;
+
+/// Liveness of a work queue, shared by its portal and the operations
+/// submitted through it.
+#[derive(Debug)]
+pub(crate) struct QueueLiveness {
+    /// Work queue name, for error messages.
+    name: String,
+    gone: AtomicBool,
+}
+
+impl QueueLiveness {
+    pub(crate) fn new(name: String) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            gone: AtomicBool::new(false),
+        })
+    }
+
+    /// Record that the work queue was disabled or its device reset.
+    pub(crate) fn mark_gone(&self) {
+        self.gone.store(true, Ordering::Release);
+    }
+
+    /// Returns true once the work queue has gone away.
+    pub(crate) fn is_gone(&self) -> bool {
+        self.gone.load(Ordering::Acquire)
+    }
+
+    /// The error reported for operations on the vanished work queue.
+    pub(crate) fn error(&self) -> DsaError {
+        DsaError::WorkQueueDisabled(self.name.clone())
+    }
+}
+
+/// An in-flight operation tracked by the reactor.
+///
+/// The completion record, and the buffers the descriptor points into, live
+/// inside this heap allocation, so they stay in place for as long as any
+/// `Arc<InFlight>` exists. The reactor holds one reference until the
+/// hardware has written the record. Whoever submitted the operation only
+/// shares it: forgetting their reference leaks it, but never releases
+/// memory the hardware still accesses.
+#[repr(C)]
+pub(crate) struct InFlight {
+    record: UnsafeCell<DsaCompletionRecord>,
+    done: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+    /// Signalled together with `done` for blocking waiters.
+    done_cv: Condvar,
+    callback: Mutex<Option<Callback>>,
+    /// Work queue in-flight counter, decremented on completion.
+    counter: Mutex<Option<Arc<AtomicUsize>>>,
+    /// Liveness of the work queue the operation was submitted to.
+    queue: OnceLock<Arc<QueueLiveness>>,
+    /// Set instead of a completion when the work queue went away.
+    failure: Mutex<Option<DsaError>>,
+    /// The submitted descriptor and when it was submitted, for errors.
+    origin: OnceLock<(ErrorContext, Instant)>,
+    /// Buffers the descriptor points into, kept until completion.
+    buffers: Mutex<Option<Box<dyn Any + Send>>>,
+    /// Released on completion, such as a place in a fair share.
+    release: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+// SAFETY: The record is written by hardware and only read after `done` is
+// observed with acquire ordering; all other state is synchronized.
+unsafe impl Send for InFlight {}
+unsafe impl Sync for InFlight {}
+
+impl InFlight {
+    /// Allocate a new in-flight operation with a zeroed completion record.
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            record: UnsafeCell::new(DsaCompletionRecord::new()),
+            done: AtomicBool::new(false),
+            waker: Mutex::new(None),
+            done_cv: Condvar::new(),
+            callback: Mutex::new(None),
+            counter: Mutex::new(None),
+            queue: OnceLock::new(),
+            failure: Mutex::new(None),
+            origin: OnceLock::new(),
+            buffers: Mutex::new(None),
+            release: Mutex::new(None),
+        })
+    }
+
+    /// Keep `buffers` alive at least until the operation completes.
+    ///
+    /// Must be called before the descriptor is submitted.
+    pub(crate) fn hold(&self, buffers: Box<dyn Any + Send>) {
+        *self.buffers.lock().unwrap() = Some(buffers);
+    }
+
+    /// Take back the buffers of a completed operation.
+    ///
+    /// Returns `None` if the operation was failed, as the hardware may
+    /// still access them.
+    pub(crate) fn take_buffers(&self) -> Option<Box<dyn Any + Send>> {
+        debug_assert!(self.is_done());
+        self.buffers.lock().unwrap().take()
+    }
+
+    /// Set the callback to run on completion.
+    ///
+    /// Must be called before the operation is registered with the reactor.
+    pub(crate) fn set_callback(&self, callback: Callback) {
+        *self.callback.lock().unwrap() = Some(callback);
+    }
+
+    /// Remove the completion callback, for reporting a submission failure.
+    pub(crate) fn take_callback(&self) -> Option<Callback> {
+        self.callback.lock().unwrap().take()
+    }
+
+    /// Count this operation in `counter` until it completes.
+    pub(crate) fn track(&self, counter: &Arc<AtomicUsize>) {
+        counter.fetch_add(1, Ordering::AcqRel);
+        *self.counter.lock().unwrap() = Some(Arc::clone(counter));
+    }
+
+    /// Drop `guard` once the operation completes.
+    pub(crate) fn release_on_completion(&self, guard: Box<dyn Any + Send>) {
+        *self.release.lock().unwrap() = Some(guard);
+    }
+
+    /// Fail this operation if `queue` goes away before it completes.
+    pub(crate) fn watch(&self, queue: &Arc<QueueLiveness>) {
+        let _ = self.queue.set(Arc::clone(queue));
+    }
+
+    /// Returns true if the operation's work queue has gone away.
+    fn is_orphaned(&self) -> bool {
+        self.queue.get().is_some_and(|queue| queue.is_gone())
+    }
+
+    /// The outcome of a completed operation: its record, or the error it
+    /// was failed with.
+    pub(crate) fn outcome(&self) -> Result<&DsaCompletionRecord, DsaError> {
+        match self.failure.lock().unwrap().take() {
+            Some(err) => Err(err),
+            None => Ok(self.record()),
+        }
+    }
+
+    /// Record the submitted descriptor, for the context of its errors.
+    pub(crate) fn set_origin(&self, context: ErrorContext) {
+        let _ = self.origin.set((context, Instant::now()));
+    }
+
+    /// Attach the submitted descriptor to an error of this operation.
+    pub(crate) fn add_context(&self, err: DsaError) -> DsaError {
+        match self.origin.get() {
+            Some((context, submitted)) => err.with_context(|| ErrorContext {
+                elapsed: submitted.elapsed(),
+                ..context.clone()
+            }),
+            None => err,
+        }
+    }
+
+    /// Completion record for building the descriptor.
+    ///
+    /// Must only be called before the descriptor is submitted.
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) fn record_mut(&self) -> &mut DsaCompletionRecord {
+        // SAFETY: called once, before submission, while no one else reads it.
+        unsafe { &mut *self.record.get() }
+    }
+
+    /// Completion record, for reading results.
+    pub(crate) fn record(&self) -> &DsaCompletionRecord {
+        // SAFETY: hardware writes are observed through volatile status reads.
+        unsafe { &*self.record.get() }
+    }
+
+    /// Returns true once the reactor has observed completion.
+    pub(crate) fn is_done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    /// Register a waker to be woken on completion.
+    ///
+    /// Returns true if the operation already completed.
+    pub(crate) fn register(&self, waker: &Waker) -> bool {
+        let mut slot = self.waker.lock().unwrap();
+        if self.is_done() {
+            return true;
+        }
+        match slot.as_ref() {
+            Some(existing) if existing.will_wake(waker) => {}
+            _ => *slot = Some(waker.clone()),
+        }
+        false
+    }
+
+    /// Block the calling thread until the operation completes.
+    pub(crate) fn wait(&self) {
+        let mut slot = self.waker.lock().unwrap();
+        while !self.is_done() {
+            slot = self.done_cv.wait(slot).unwrap();
+        }
+    }
+
+    /// Mark the operation complete, wake any waiter and run the callback.
+    pub(crate) fn complete(&self) {
+        // Settle the accounting first, so waiters observe it
+        if let Some(counter) = self.counter.lock().unwrap().take() {
+            counter.fetch_sub(1, Ordering::AcqRel);
+        }
+        drop(self.release.lock().unwrap().take());
+        let waker = {
+            let mut slot = self.waker.lock().unwrap();
+            self.done.store(true, Ordering::Release);
+            self.done_cv.notify_all();
+            slot.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        if let Some(callback) = self.take_callback() {
+            // A panicking callback must not take down the reactor thread
+            let outcome = self.outcome();
+            let run = std::panic::AssertUnwindSafe(|| callback(outcome));
+            if std::panic::catch_unwind(run).is_err() {
+                log::error!("DSA completion callback panicked");
+            }
+        }
+    }
+
+    /// Complete the operation with `err` instead of a completion record.
+    ///
+    /// The hardware may still write the record and access the buffers
+    /// later, so both are leaked.
+    pub(crate) fn fail(self: &Arc<Self>, err: DsaError) {
+        *self.failure.lock().unwrap() = Some(err);
+        std::mem::forget(Arc::clone(self));
+        std::mem::forget(self.buffers.lock().unwrap().take());
+        self.complete();
+    }
+}
+
+struct Shared {
+    pending: Mutex<Vec<Arc<InFlight>>>,
+    wakeup: Condvar,
+}
+
+/// Handle to the process-wide completion reactor.
+#[derive(Clone)]
+pub(crate) struct Reactor {
+    shared: Arc<Shared>,
+}
+
+impl Reactor {
+    /// Get the process-wide reactor, starting its thread on first use.
+    pub(crate) fn global() -> &'static Reactor {
+        static REACTOR: OnceLock<Reactor> = OnceLock::new();
+        REACTOR.get_or_init(|| {
+            let reactor = Reactor {
+                shared: Arc::new(Shared {
+                    pending: Mutex::new(Vec::new()),
+                    wakeup: Condvar::new(),
+                }),
+            };
+            let shared = Arc::clone(&reactor.shared);
+            std::thread::Builder::new()
+                .name("dsa-reactor".to_string())
+                .spawn(move || run(&shared))
+                .expect("failed to spawn DSA reactor thread");
+            reactor
+        })
+    }
+
+    /// Start tracking a submitted operation.
+    pub(crate) fn register(&self, op: Arc<InFlight>) {
+        self.shared.pending.lock().unwrap().push(op);
+        self.shared.wakeup.notify_one();
+    }
+
+    /// Number of operations currently tracked.
+    pub(crate) fn pending(&self) -> usize {
+        self.shared.pending.lock().unwrap().len()
+    }
+}
+
+fn run(shared: &Shared) {
+    loop {
+        let mut pending = shared.pending.lock().unwrap();
+        while pending.is_empty() {
+            pending = shared.wakeup.wait(pending).unwrap();
+        }
+
+        // Finish operations only after releasing the lock: their callbacks
+        // may submit and register further operations
+        let mut finished = Vec::new();
+        pending.retain(|op| {
+            if op.record().is_complete() {
+                finished.push((Arc::clone(op), true));
+                false
+            } else if op.is_orphaned() {
+                finished.push((Arc::clone(op), false));
+                false
+            } else {
+                true
+            }
+        });
+        drop(pending);
+
+        for (op, completed) in finished {
+            if completed {
+                op.complete();
+            } else {
+                let err = op.queue.get().map(|queue| queue.error());
+                op.fail(err.expect("orphaned operations watch a queue"));
+            }
+        }
+
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reactor_completes_registered_op() {
+        let op = InFlight::new();
+        Reactor::global().register(Arc::clone(&op));
+        assert!(!op.is_done());
+
+        // Simulate the hardware writing the completion record
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x01) };
+
+        let waiter = Arc::clone(&op);
+        let handle = std::thread::spawn(move || waiter.wait());
+        handle.join().unwrap();
+        assert!(op.is_done());
+        assert!(op.record().get_status().is_success());
+    }
+
+    #[test]
+    fn test_callback_runs_on_reactor() {
+        let op = InFlight::new();
+        let (tx, rx) = std::sync::mpsc::channel();
+        op.set_callback(Box::new(move |record| {
+            let thread = std::thread::current().name().map(str::to_string);
+            tx.send((record.map(|r| r.status), thread)).unwrap();
+        }));
+        Reactor::global().register(Arc::clone(&op));
+
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x01) };
+
+        let (status, thread) = rx.recv().unwrap();
+        assert_eq!(status.unwrap(), 0x01);
+        assert_eq!(thread.as_deref(), Some("dsa-reactor"));
+    }
+
+    #[test]
+    fn test_callback_registers_op() {
+        let second = InFlight::new();
+        let (tx, rx) = std::sync::mpsc::channel();
+        second.set_callback(Box::new(move |record| {
+            tx.send(record.map(|r| r.status)).unwrap();
+        }));
+        unsafe { std::ptr::write_volatile(&mut second.record_mut().status, 0x01) };
+
+        // Chaining an operation from a callback must not deadlock the reactor
+        let first = InFlight::new();
+        let chained = Arc::clone(&second);
+        first.set_callback(Box::new(move |_| Reactor::global().register(chained)));
+        Reactor::global().register(Arc::clone(&first));
+        unsafe { std::ptr::write_volatile(&mut first.record_mut().status, 0x01) };
+
+        let status = rx.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(status.unwrap(), 0x01);
+        assert!(first.is_done() && second.is_done());
+    }
+
+    #[test]
+    fn test_track_counts_until_complete() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let op = InFlight::new();
+        op.track(&counter);
+        assert_eq!(counter.load(Ordering::Acquire), 1);
+        op.complete();
+        assert_eq!(counter.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn test_orphaned_op_fails() {
+        let queue = QueueLiveness::new("wq0.0".to_string());
+        let op = InFlight::new();
+        let (tx, rx) = std::sync::mpsc::channel();
+        op.set_callback(Box::new(move |record| {
+            tx.send(record.map(|r| r.status)).unwrap();
+        }));
+        op.watch(&queue);
+        Reactor::global().register(Arc::clone(&op));
+
+        queue.mark_gone();
+        assert!(matches!(
+            rx.recv().unwrap(),
+            Err(DsaError::WorkQueueDisabled(name)) if name == "wq0.0"
+        ));
+        assert!(op.is_done());
+    }
+
+    #[test]
+    fn test_register_after_done() {
+        let op = InFlight::new();
+        op.complete();
+
+        assert!(op.register(Waker::noop()));
+        assert!(op.is_done());
+    }
+}
@@ -0,0 +1,48 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Async runtime integration.
+//!
+//! [`DsaFuture`](crate::DsaFuture) works on any executor. This module adds
+//! what does depend on the runtime: running the blocking, multi-descriptor
+//! operations (`crc32_many`, `fill_pages`, ...) on the runtime's blocking
+//! thread pool. When several runtime features are enabled, tokio is
+//! preferred, then async-std, then smol.
+
+/// Run a blocking closure on the runtime's blocking thread pool.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::DsaEngine;
+/// use std::sync::Arc;
+///
+/// async fn checksum_all(engine: Arc<DsaEngine>, bufs: Vec<Vec<u8>>) -> Vec<u32> {
+///     dsa_rust::rt::unblock(move || {
+///         let slices: Vec<&[u8]> = bufs.iter().map(Vec::as_slice).collect();
+///         engine.crc32_many(&slices).unwrap()
+///     })
+///     .await
+/// }
+/// ```
+pub async fn unblock<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    #[cfg(feature = "tokio")]
+    {
+        tokio::task::spawn_blocking(f)
+            .await
+            .expect("blocking DSA task panicked")
+    }
+    #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+    {
+        async_std::task::spawn_blocking(f).await
+    }
+    #[cfg(all(feature = "smol", not(any(feature = "tokio", feature = "async-std"))))]
+    {
+        smol::unblock(f).await
+    }
+}
@@ -0,0 +1,92 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Sealing an engine before entering a sandbox.
+//!
+//! A process that restricts itself with seccomp or landlock after start-up
+//! can keep using DSA only if the engine no longer touches the filesystem.
+//! Opening an engine opens the work queue and maps its portal, but some
+//! state is read from sysfs lazily: capabilities, the work queue's state
+//! when a wait times out, its threshold and occupancy when it is full, and
+//! the device again when a disabled work queue comes back.
+//! [`DsaEngine::seal`] reads all of it up front and guarantees that the
+//! engine does not open, read or map a file afterwards; submitting and
+//! waiting only write the mapped portal and read memory.
+//!
+//! The price is that a sealed engine cannot notice that its work queue was
+//! disabled: a wait on a disabled work queue times out instead of failing
+//! with [`DsaError::WorkQueueDisabled`](crate::DsaError::WorkQueueDisabled),
+//! and a work queue marked gone is not reopened. [`QueueFull`](crate::DsaError::QueueFull)
+//! errors report the threshold read when sealing and no occupancy.
+//!
+//! Sealing starts the completion thread used by futures and callbacks.
+//! Everything else that creates threads or opens files must be set up
+//! before entering the sandbox: fair sharing
+//! ([`DsaEngine::set_fair_share`]), the offload pool of
+//! [`DsaEngine::spawn`] (start it with one spawned operation), interrupt
+//! handles and the diagnostics that read sysfs by design, such as device
+//! discovery, [`probe`](crate::probe) and [`topology`](crate::topology).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//! use std::path::Path;
+//!
+//! let mut engine = DsaEngine::open(Path::new("/dev/dsa/wq0.0"))?;
+//! engine.seal();
+//!
+//! // Enter the sandbox here; the engine keeps working
+//! let crc = engine.crc32(&[0u8; 4096])?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+
+impl DsaEngine {
+    /// Read everything the engine would otherwise read from the filesystem
+    /// later, so that it never accesses a file again.
+    ///
+    /// Sealing is permanent and idempotent. On platforms without work queue
+    /// devices the engine never accesses files, and sealing does nothing.
+    pub fn seal(&mut self) {
+        self.work_queue_mut().seal();
+    }
+
+    /// Returns true if the engine is sealed and never accesses a file.
+    pub fn is_sealed(&self) -> bool {
+        self.work_queue().is_sealed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(all(dsa_portal, feature = "backend-hw"))]
+    #[test]
+    fn test_sealed_engine() {
+        use super::*;
+        use crate::error::DsaError;
+        use crate::fair::FairShare;
+        use crate::wq::WorkQueue;
+
+        // Any read-write mappable file stands in for a device node
+        let path = std::env::temp_dir().join(format!("dsa-sandbox-{}", std::process::id()));
+        std::fs::write(&path, []).unwrap();
+
+        let mut engine = DsaEngine::from_wq(WorkQueue::open(&path).unwrap());
+        assert!(!engine.is_sealed());
+        let caps = engine.capabilities();
+        engine.seal();
+        engine.seal();
+        assert!(engine.is_sealed());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(engine.capabilities(), caps);
+        assert!(matches!(
+            engine.set_fair_share(Some(FairShare::default())),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        engine.set_fair_share(None).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Sharding one large copy or fill across concurrent descriptors.
+//!
+//! A device has several engines behind each work queue group, but a single
+//! descriptor runs on one of them. A large `memcpy` or `memset` submitted as
+//! one descriptor, or as chunks that wait for each other, leaves the other
+//! engines idle. With [`DsaEngine::set_sharding`], the engine cuts such a
+//! transfer into contiguous shards and submits them all before waiting for
+//! any, so the device dispatches them to its engines in parallel.
+//!
+//! Sharding pays off only for transfers large enough that the time to move
+//! a shard dominates the cost of a descriptor; transfers that would yield
+//! shards smaller than [`ShardPolicy::min_shard`] use fewer shards, down to
+//! one descriptor.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::shard::ShardPolicy;
+//! use dsa_rust::DsaEngine;
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! engine.set_sharding(Some(ShardPolicy::default()));
+//!
+//! let src = vec![7u8; 64 << 20];
+//! let mut dst = vec![0u8; 64 << 20];
+//! // Four descriptors of 16 MiB, in flight together
+//! engine.memcpy(&mut dst, &src)?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+
+#[cfg(dsa_portal)]
+use crate::descriptor::{DsaCompletionRecord, DsaHwDesc};
+
+/// Default number of shards, the engines in a typical group.
+pub const DEFAULT_SHARDS: usize = 4;
+
+/// Default minimum bytes per shard.
+pub const DEFAULT_MIN_SHARD: usize = 256 << 10;
+
+/// Shards are cut at multiples of a cache line.
+const SHARD_ALIGN: usize = 64;
+
+/// How large transfers are split into concurrent descriptors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShardPolicy {
+    /// Maximum number of descriptors per transfer.
+    pub shards: usize,
+    /// Minimum bytes per shard.
+    pub min_shard: usize,
+}
+
+impl Default for ShardPolicy {
+    fn default() -> Self {
+        Self {
+            shards: DEFAULT_SHARDS,
+            min_shard: DEFAULT_MIN_SHARD,
+        }
+    }
+}
+
+impl ShardPolicy {
+    /// Bytes per shard for a transfer of `len` bytes; the last shard may
+    /// be shorter. A result of at least `len` means one descriptor.
+    pub fn shard_len(&self, len: usize) -> usize {
+        let count = self.shards.min(len / self.min_shard.max(1)).max(1);
+        len.div_ceil(count).next_multiple_of(SHARD_ALIGN)
+    }
+}
+
+impl DsaEngine {
+    /// Split large `memcpy` and `memset` calls into shards that are in
+    /// flight together.
+    ///
+    /// Pass `None` to submit every transfer as one descriptor. The number
+    /// of shards is capped at the work queue's
+    /// [`max_in_flight`](crate::Capabilities::max_in_flight). See the
+    /// [`shard`](crate::shard) module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `policy.shards` is zero.
+    pub fn set_sharding(&mut self, policy: Option<ShardPolicy>) {
+        self.sharding = policy.map(|policy| {
+            assert!(policy.shards > 0, "shard count must be nonzero");
+            let max_in_flight = (self.capabilities().max_in_flight as usize).max(1);
+            ShardPolicy {
+                shards: policy.shards.min(max_in_flight),
+                ..policy
+            }
+        });
+    }
+
+    /// Get the current shard policy, if sharding is enabled.
+    pub fn sharding(&self) -> Option<ShardPolicy> {
+        self.sharding
+    }
+
+    /// Copy `src` into `dst` in shards as planned by `policy`.
+    pub(crate) fn memcpy_sharded(
+        &self,
+        policy: &ShardPolicy,
+        dst: &mut [u8],
+        src: &[u8],
+    ) -> Result<(), DsaError> {
+        let shard = policy.shard_len(src.len());
+        if shard >= src.len() || dst.len() < src.len() {
+            return self.work_queue().memcpy(dst, src);
+        }
+        #[cfg(dsa_portal)]
+        {
+            let dst = dst.as_mut_ptr();
+            self.run_shards(src.len(), shard, |start, len, completion| {
+                let src = src[start..].as_ptr();
+                DsaHwDesc::mem_move(dst.wrapping_add(start), src, len, completion)
+            })
+        }
+        #[cfg(not(dsa_portal))]
+        self.work_queue().memcpy(dst, src)
+    }
+
+    /// Fill `dst` with `pattern` in shards as planned by `policy`.
+    pub(crate) fn memset_sharded(
+        &self,
+        policy: &ShardPolicy,
+        dst: &mut [u8],
+        pattern: u64,
+    ) -> Result<(), DsaError> {
+        let shard = policy.shard_len(dst.len());
+        if shard >= dst.len() {
+            return self.work_queue().memset(dst, pattern);
+        }
+        #[cfg(dsa_portal)]
+        {
+            let len = dst.len();
+            let dst = dst.as_mut_ptr();
+            self.run_shards(len, shard, |start, len, completion| {
+                DsaHwDesc::mem_fill(dst.wrapping_add(start), len, pattern, completion)
+            })
+        }
+        #[cfg(not(dsa_portal))]
+        self.work_queue().memset(dst, pattern)
+    }
+
+    /// Submit one descriptor per `shard` bytes of a `len`-byte transfer,
+    /// then wait for all of them.
+    ///
+    /// `build` gets the offset and length of a shard. Every submitted shard
+    /// is waited for even after an error, since the hardware may still
+    /// access the buffers; the first error is returned. Once a shard times
+    /// out, the rest are abandoned like it.
+    #[cfg(dsa_portal)]
+    fn run_shards(
+        &self,
+        len: usize,
+        shard: usize,
+        build: impl Fn(usize, usize, &mut DsaCompletionRecord) -> DsaHwDesc,
+    ) -> Result<(), DsaError> {
+        let mut ops = Vec::with_capacity(len.div_ceil(shard));
+        let mut result = Ok(());
+        for start in (0..len).step_by(shard) {
+            let shard_len = shard.min(len - start);
+            match self
+                .work_queue()
+                .start(|completion| build(start, shard_len, completion))
+            {
+                Ok(op) => ops.push(op),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        for op in ops {
+            let outcome = self
+                .work_queue()
+                .wait_started(&op)
+                .and_then(|()| op.outcome().and_then(crate::wq::check_completion))
+                .map_err(|e| op.add_context(e));
+            if let Err(e @ DsaError::Timeout { .. }) = outcome {
+                return result.and(Err(e));
+            }
+            result = result.and(outcome);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_len() {
+        let policy = ShardPolicy {
+            shards: 4,
+            min_shard: 1000,
+        };
+        assert_eq!(policy.shard_len(0), 0);
+        assert_eq!(policy.shard_len(999), 1024);
+        assert_eq!(policy.shard_len(2500), 1280);
+        assert_eq!(policy.shard_len(100_000), 25_024);
+        assert_eq!(
+            ShardPolicy {
+                min_shard: 0,
+                ..policy
+            }
+            .shard_len(256),
+            64
+        );
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_sharded_copy_and_fill() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        assert_eq!(engine.sharding(), None);
+        let policy = ShardPolicy {
+            shards: 4,
+            min_shard: 4096,
+        };
+        engine.set_sharding(Some(policy));
+        assert_eq!(engine.sharding(), Some(policy));
+
+        let src: Vec<u8> = (0..100_003).map(|i| (i % 251) as u8).collect();
+        let mut dst = vec![0u8; src.len()];
+        engine.memcpy(&mut dst, &src).unwrap();
+        assert_eq!(dst, src);
+        assert_eq!(emulator.submitted(), 4);
+
+        engine.memset(&mut dst, 0x0102_0304_0506_0708).unwrap();
+        assert!(dst
+            .chunks(8)
+            .all(|c| c == &[8, 7, 6, 5, 4, 3, 2, 1][..c.len()]));
+        assert_eq!(emulator.submitted(), 8);
+
+        // Too small to shard
+        engine.memcpy(&mut dst[..5000], &src[..5000]).unwrap();
+        assert_eq!(emulator.submitted(), 9);
+
+        emulator.inject(Fault::InvalidFlags);
+        assert!(matches!(
+            engine.memcpy(&mut dst, &src),
+            Err(DsaError::OperationFailed { status: 0x10, .. })
+        ));
+        assert!(matches!(
+            engine.memcpy(&mut dst[..10], &src),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_stalled_shard_times_out() {
+        use crate::mock::{MockBackend, Response};
+        use crate::opcode::DsaOpcode;
+        use std::sync::Arc;
+
+        let mock = Arc::new(MockBackend::new());
+        mock.respond(DsaOpcode::MemMove, 2, Response::Delay { polls: 1 });
+        let mut engine = DsaEngine::mocked(Arc::clone(&mock)).unwrap();
+        engine.work_queue_mut().set_spin_iterations(100);
+        engine.set_sharding(Some(ShardPolicy {
+            shards: 4,
+            min_shard: 4096,
+        }));
+
+        let src = vec![7u8; 64 << 10];
+        let mut dst = vec![0u8; src.len()];
+        assert!(matches!(
+            engine.memcpy(&mut dst, &src),
+            Err(DsaError::Timeout { .. })
+        ));
+        assert!(engine.work_queue().abandoned());
+
+        // The reactor still completes the abandoned shard
+        mock.poll();
+        while engine.work_queue().in_flight() > 0 {
+            std::thread::yield_now();
+        }
+        assert_eq!(dst, src);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    #[should_panic(expected = "shard count must be nonzero")]
+    fn test_zero_shards() {
+        let mut engine =
+            DsaEngine::emulated(std::sync::Arc::new(crate::emulator::Emulator::new())).unwrap();
+        engine.set_sharding(Some(ShardPolicy {
+            shards: 0,
+            min_shard: 1,
+        }));
+    }
+}
@@ -0,0 +1,255 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Thread-offload variants of blocking operations.
+//!
+//! Applications that do not run an async executor but must not block the
+//! calling thread can hand an operation to a small internal worker pool
+//! with [`DsaEngine::crc32_spawn`], [`DsaEngine::memcpy_spawn`] or, for
+//! anything else, [`DsaEngine::spawn`]. Each returns a [`DsaJoinHandle`],
+//! which is joined like a [`std::thread::JoinHandle`]. The pool has
+//! [`SPAWN_WORKERS`] threads, started on first use and shared by all
+//! engines; a worker spends nearly all its time waiting for the device.
+//!
+//! The operation owns its buffers. A spawned copy hands its destination
+//! back; pass shared buffers (`Arc<[u8]>`, ...) to keep using a source.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//! use std::sync::Arc;
+//!
+//! let engine = Arc::new(DsaEngine::open_first()?);
+//! let data: Arc<[u8]> = vec![0u8; 1 << 20].into();
+//! let crc = engine.crc32_spawn(Arc::clone(&data));
+//! let copy = engine.memcpy_spawn(vec![0u8; data.len()], data);
+//!
+//! // ... other work ...
+//!
+//! let crc = crc.join().unwrap()?;
+//! let copy = copy.join().unwrap()?;
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+
+/// Number of threads in the offload pool.
+pub const SPAWN_WORKERS: usize = 4;
+
+/// A spawned operation, ready to run on a worker.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Result slot shared by a job and its handle.
+struct Slot<T> {
+    result: Mutex<Option<std::thread::Result<T>>>,
+    done: Condvar,
+}
+
+/// Handle to an operation running on the offload pool.
+///
+/// Dropping the handle detaches the operation: it still runs to completion
+/// and its result is discarded.
+pub struct DsaJoinHandle<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> DsaJoinHandle<T> {
+    /// Wait for the operation to finish.
+    ///
+    /// # Errors
+    ///
+    /// Returns the panic payload if the operation panicked, as
+    /// [`std::thread::JoinHandle::join`] does.
+    pub fn join(self) -> std::thread::Result<T> {
+        let mut result = self.slot.result.lock().unwrap();
+        loop {
+            if let Some(result) = result.take() {
+                return result;
+            }
+            result = self.slot.done.wait(result).unwrap();
+        }
+    }
+
+    /// Returns true if the operation has finished, so that
+    /// [`DsaJoinHandle::join`] returns without waiting.
+    pub fn is_finished(&self) -> bool {
+        self.slot.result.lock().unwrap().is_some()
+    }
+}
+
+impl<T> std::fmt::Debug for DsaJoinHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DsaJoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// Queue of the offload pool, starting its workers on first use.
+fn pool() -> &'static Mutex<Sender<Job>> {
+    static POOL: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..SPAWN_WORKERS {
+            let receiver = Arc::clone(&receiver);
+            std::thread::Builder::new()
+                .name(format!("dsa-spawn-{i}"))
+                .spawn(move || worker_loop(&receiver))
+                .expect("failed to spawn DSA offload worker");
+        }
+        Mutex::new(sender)
+    })
+}
+
+fn worker_loop(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // Release the queue before running the job, so others can take one
+        let job = receiver.lock().unwrap().recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Run `f` on the offload pool.
+fn spawn_job<T, F>(f: F) -> DsaJoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let slot = Arc::new(Slot {
+        result: Mutex::new(None),
+        done: Condvar::new(),
+    });
+    let job_slot = Arc::clone(&slot);
+    let job: Job = Box::new(move || {
+        // A panicking operation must not take its worker down
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        *job_slot.result.lock().unwrap() = Some(result);
+        job_slot.done.notify_all();
+    });
+    // The workers never exit, so the queue stays open
+    pool()
+        .lock()
+        .unwrap()
+        .send(job)
+        .expect("DSA offload pool is gone");
+    DsaJoinHandle { slot }
+}
+
+impl DsaEngine {
+    /// Run `f` with this engine on the offload pool.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use dsa_rust::DsaEngine;
+    /// use std::sync::Arc;
+    ///
+    /// let engine = Arc::new(DsaEngine::open_first()?);
+    /// let bufs = vec![vec![1u8; 4096]; 16];
+    /// let handle = engine.spawn(move |engine| {
+    ///     let slices: Vec<&[u8]> = bufs.iter().map(Vec::as_slice).collect();
+    ///     engine.crc32_many(&slices)
+    /// });
+    /// let crcs = handle.join().unwrap()?;
+    /// # Ok::<(), dsa_rust::DsaError>(())
+    /// ```
+    pub fn spawn<T, F>(self: &Arc<Self>, f: F) -> DsaJoinHandle<T>
+    where
+        F: FnOnce(&DsaEngine) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let engine = Arc::clone(self);
+        spawn_job(move || f(&engine))
+    }
+
+    /// Compute the CRC32 of `data` on the offload pool, like
+    /// [`DsaEngine::crc32`].
+    pub fn crc32_spawn<B>(self: &Arc<Self>, data: B) -> DsaJoinHandle<Result<u32, DsaError>>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        self.spawn(move |engine| engine.crc32(data.as_ref()))
+    }
+
+    /// Copy `src` into `dst` on the offload pool, like
+    /// [`DsaEngine::memcpy`].
+    ///
+    /// # Returns
+    ///
+    /// A handle to `dst` once the copy has completed.
+    pub fn memcpy_spawn<D, S>(
+        self: &Arc<Self>,
+        mut dst: D,
+        src: S,
+    ) -> DsaJoinHandle<Result<D, DsaError>>
+    where
+        D: AsMut<[u8]> + Send + 'static,
+        S: AsRef<[u8]> + Send + 'static,
+    {
+        self.spawn(move |engine| {
+            engine.memcpy(dst.as_mut(), src.as_ref())?;
+            Ok(dst)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panics_reach_the_handle() {
+        let handle = spawn_job(|| -> u32 { panic!("offloaded panic") });
+        assert!(handle.join().is_err());
+
+        // The worker survived and keeps running jobs
+        let handles: Vec<_> = (0..2 * SPAWN_WORKERS as u32)
+            .map(|i| spawn_job(move || i * 2))
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), i as u32 * 2);
+        }
+    }
+
+    #[test]
+    fn test_is_finished() {
+        let (sender, receiver) = mpsc::channel::<()>();
+        let handle = spawn_job(move || receiver.recv().is_ok());
+        assert!(!handle.is_finished());
+        sender.send(()).unwrap();
+        assert!(handle.join().unwrap());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_spawned_operations() {
+        use crate::emulator::Emulator;
+
+        let engine = Arc::new(DsaEngine::emulated(Arc::new(Emulator::new())).unwrap());
+        let data: Arc<[u8]> = (0..10_000).map(|i| (i % 251) as u8).collect();
+
+        let crc = engine.crc32_spawn(Arc::clone(&data));
+        let copy = engine.memcpy_spawn(vec![0u8; data.len()], Arc::clone(&data));
+        assert_eq!(
+            crc.join().unwrap().unwrap(),
+            crate::crc::software_crc32(&data, 0)
+        );
+        assert_eq!(copy.join().unwrap().unwrap(), &data[..]);
+
+        let short = engine.memcpy_spawn(vec![0u8; 10], data);
+        assert!(matches!(
+            short.join().unwrap(),
+            Err(DsaError::BufferSizeMismatch { .. })
+        ));
+    }
+}
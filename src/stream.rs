@@ -0,0 +1,270 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Stream of completions.
+//!
+//! A [`CompletionStream`] keeps many operations in flight and yields each
+//! one as soon as it completes, in completion order rather than submission
+//! order. Pipelined consumers can process results as they arrive instead of
+//! joining a whole batch at the end.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::callback::DsaOp;
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let mut stream = engine.completion_stream();
+//! for block in 0..64 {
+//!     stream.submit(DsaOp::Crc32 { data: vec![block as u8; 65536], seed: 0 });
+//! }
+//! // In async code, with `futures::StreamExt`:
+//! // while let Some(done) = stream.next().await { ... }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::callback::{DsaOp, DsaOutput};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// An operation yielded by a [`CompletionStream`].
+#[derive(Debug)]
+pub struct Completed {
+    /// Identifier returned by [`CompletionStream::submit`].
+    pub id: u64,
+    /// The operation, returning ownership of its buffers.
+    pub op: DsaOp,
+    /// Result of the operation.
+    pub result: Result<DsaOutput, DsaError>,
+}
+
+/// Completions waiting to be yielded, shared with the completion callbacks.
+#[derive(Default)]
+struct Ready {
+    completed: VecDeque<Completed>,
+    waker: Option<Waker>,
+}
+
+/// Operations in flight on one engine, yielded in completion order.
+///
+/// The stream returns `None` once every submitted operation has been
+/// yielded. Like `FuturesUnordered`, it can be polled again after more
+/// operations are submitted.
+///
+/// Dropping the stream does not cancel its operations: they own their
+/// buffers and complete in the background.
+pub struct CompletionStream<'e> {
+    engine: &'e DsaEngine,
+    /// Operations kept in flight at most, the work queue's
+    /// [`max_in_flight`](crate::Capabilities::max_in_flight).
+    budget: usize,
+    ready: Arc<Mutex<Ready>>,
+    next_id: u64,
+    /// Submitted operations not yet yielded.
+    outstanding: usize,
+}
+
+impl<'e> CompletionStream<'e> {
+    /// Create an empty stream submitting to `engine`.
+    pub fn new(engine: &'e DsaEngine) -> Self {
+        Self {
+            engine,
+            budget: (engine.capabilities().max_in_flight as usize).max(1),
+            ready: Arc::new(Mutex::new(Ready::default())),
+            next_id: 0,
+            outstanding: 0,
+        }
+    }
+
+    /// Submit an operation.
+    ///
+    /// Waits while the work queue's
+    /// [`max_in_flight`](crate::Capabilities::max_in_flight) operations are
+    /// in flight, for at most the completion polling budget. Failures,
+    /// including submission failures and `DsaError::QueueFull` if no room
+    /// was found, are reported through the stream like any other
+    /// completion.
+    ///
+    /// # Returns
+    ///
+    /// The identifier the operation's [`Completed`] will carry, assigned in
+    /// submission order starting from zero.
+    pub fn submit(&mut self, op: DsaOp) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.outstanding += 1;
+
+        let ready = Arc::clone(&self.ready);
+        let deliver = move |op, result| {
+            let waker = {
+                let mut ready = ready.lock().unwrap();
+                ready.completed.push_back(Completed { id, op, result });
+                ready.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        };
+        match self.engine.work_queue().wait_for_room(self.budget) {
+            Ok(()) => self.engine.submit_with_callback(op, deliver),
+            Err(e) => deliver(op, Err(e)),
+        }
+        id
+    }
+
+    /// Number of submitted operations that have not been yielded yet.
+    pub fn outstanding(&self) -> usize {
+        self.outstanding
+    }
+
+    /// Returns true if every submitted operation has been yielded.
+    pub fn is_empty(&self) -> bool {
+        self.outstanding == 0
+    }
+}
+
+impl Stream for CompletionStream<'_> {
+    type Item = Completed;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Completed>> {
+        let this = self.get_mut();
+        if this.outstanding == 0 {
+            return Poll::Ready(None);
+        }
+
+        let mut ready = this.ready.lock().unwrap();
+        match ready.completed.pop_front() {
+            Some(completed) => {
+                this.outstanding -= 1;
+                Poll::Ready(Some(completed))
+            }
+            None => {
+                ready.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.outstanding, Some(self.outstanding))
+    }
+}
+
+impl DsaEngine {
+    /// Create a [`CompletionStream`] submitting to this engine.
+    pub fn completion_stream(&self) -> CompletionStream<'_> {
+        CompletionStream::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Poll the stream on the current thread until it yields.
+    #[cfg(dsa_portal)]
+    fn next(stream: &mut CompletionStream<'_>) -> Option<Completed> {
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(item) = Pin::new(&mut *stream).poll_next(&mut cx) {
+                return item;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_stream_yields_every_completion() {
+        use crate::emulator::Emulator;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let mut stream = engine.completion_stream();
+        for i in 0..8u8 {
+            stream.submit(DsaOp::Crc32 {
+                data: vec![i; 1024],
+                seed: 0,
+            });
+        }
+        // Rejected before submission, but still yielded
+        stream.submit(DsaOp::Memcmp {
+            a: vec![0; 4],
+            b: vec![0; 8],
+        });
+        assert_eq!(stream.size_hint(), (9, Some(9)));
+
+        let mut ids = Vec::new();
+        while let Some(done) = next(&mut stream) {
+            match done.op {
+                DsaOp::Crc32 { data, .. } => {
+                    assert_eq!(
+                        done.result.unwrap(),
+                        DsaOutput::Crc32(crate::crc::software_crc32(&data, 0))
+                    )
+                }
+                _ => assert!(done.result.is_err()),
+            }
+            ids.push(done.id);
+        }
+        ids.sort_unstable();
+        assert_eq!(ids, (0..9).collect::<Vec<_>>());
+        assert!(stream.is_empty());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_in_flight_bounded() {
+        use crate::capabilities::DEFAULT_MAX_IN_FLIGHT;
+        use crate::mock::{MockBackend, Response};
+        use crate::opcode::DsaOpcode;
+        use std::time::Duration;
+
+        let mock = Arc::new(MockBackend::new());
+        let budget = u64::from(DEFAULT_MAX_IN_FLIGHT);
+        for call in 1..=budget {
+            mock.respond(DsaOpcode::Noop, call, Response::Delay { polls: 1 });
+        }
+        let engine = DsaEngine::mocked(Arc::clone(&mock)).unwrap();
+        let mut stream = engine.completion_stream();
+
+        // The operation beyond the budget waits until these complete
+        let poller = {
+            let mock = Arc::clone(&mock);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                let calls = mock.calls(DsaOpcode::Noop);
+                mock.poll();
+                calls
+            })
+        };
+        for _ in 0..=budget {
+            stream.submit(DsaOp::Noop);
+        }
+        assert_eq!(poller.join().unwrap(), budget);
+
+        while let Some(done) = next(&mut stream) {
+            done.result.unwrap();
+        }
+        assert_eq!(mock.calls(DsaOpcode::Noop), budget + 1);
+    }
+
+    #[test]
+    fn test_empty_stream_ends() {
+        let Ok(engine) = DsaEngine::open_first() else {
+            return;
+        };
+        let mut stream = engine.completion_stream();
+        let mut cx = Context::from_waker(Waker::noop());
+        assert!(matches!(
+            Pin::new(&mut stream).poll_next(&mut cx),
+            Poll::Ready(None)
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Strict validation of descriptors before submission.
+//!
+//! With the `strict-validation` feature, every descriptor is checked
+//! before it is written to the portal, and a problem is returned as
+//! `DsaError::InvalidDescriptor` instead of an invalid flags (0x10) or
+//! transfer size (0x13) status from the device. On top of
+//! [`DsaHwDesc::validate`], the checks are:
+//!
+//! - transfer sizes: lengths that do not fit the 32-bit size field, empty
+//!   transfers and transfers above the work queue's maximum;
+//! - batches: more descriptors than the work queue's batch limit, and
+//!   every descriptor in the list, which `validate` does not dereference;
+//! - overlapping source and destination of copying operations other than
+//!   MemMove, which alone has move semantics;
+//! - opcodes the work queue does not support, and block-on-fault or
+//!   completion interrupt flags it does not allow.
+//!
+//! This is a debugging aid for intermittent failures: it costs a few
+//! comparisons per descriptor and a pass over each batch list. IAA
+//! descriptors and the software fallback are not checked.
+
+use crate::capabilities::Capabilities;
+use crate::descriptor::{DescriptorFlags, DsaHwDesc};
+use crate::error::{DescriptorError, DsaError};
+use crate::opcode::DsaOpcode;
+
+/// Check that a transfer of `len` bytes fits a descriptor's size field.
+pub(crate) fn check_len(len: usize) -> Result<(), DsaError> {
+    if u32::try_from(len).is_err() {
+        return Err(DsaError::InvalidDescriptor(
+            DescriptorError::TransferTooLarge {
+                len: len as u64,
+                max: u32::MAX as u64,
+            },
+        ));
+    }
+    Ok(())
+}
+
+/// Check `desc`, and the descriptors of a batch, against `caps`.
+///
+/// # Safety
+///
+/// The descriptor list of a batch must be valid for reads.
+pub(crate) unsafe fn check(desc: &DsaHwDesc, caps: &Capabilities) -> Result<(), DsaError> {
+    #[cfg(feature = "iaa")]
+    if crate::iaa::IaaOpcode::try_from(desc.opcode()).is_ok() {
+        return Ok(());
+    }
+    let invalid = |problem: DescriptorError| Err(DsaError::InvalidDescriptor(problem));
+    desc.validate()?;
+    let op = DsaOpcode::try_from(desc.opcode())?;
+    if !caps.supports(op) {
+        return invalid(DescriptorError::UnsupportedOpcode(op));
+    }
+
+    let flags = DescriptorFlags::from_bits_truncate(desc.flags_opcode & 0x00FFFFFF);
+    let mut unsupported = DescriptorFlags::empty();
+    if !caps.block_on_fault {
+        unsupported |= DescriptorFlags::BLOCK_ON_FAULT;
+    }
+    if !caps.interrupts {
+        unsupported |= DescriptorFlags::COMPLETION_INTERRUPT;
+    }
+    if flags.intersects(unsupported) {
+        return invalid(DescriptorError::UnsupportedFlags(flags & unsupported));
+    }
+
+    match op {
+        DsaOpcode::Noop | DsaOpcode::Drain => return Ok(()),
+        DsaOpcode::Batch => {
+            if desc.xfer_size > caps.max_batch_size {
+                return invalid(DescriptorError::BatchTooLarge {
+                    count: desc.xfer_size,
+                    max: caps.max_batch_size,
+                });
+            }
+            let list = desc.src_addr as *const DsaHwDesc;
+            let descs = std::slice::from_raw_parts(list, desc.xfer_size as usize);
+            for sub in descs {
+                if sub.opcode() == DsaOpcode::Batch as u8 {
+                    return invalid(DescriptorError::NestedBatch);
+                }
+                check(sub, caps)?;
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let len = u64::from(desc.xfer_size);
+    if len == 0 {
+        return invalid(DescriptorError::EmptyTransfer(op));
+    }
+    if len > caps.max_transfer_size {
+        return invalid(DescriptorError::TransferTooLarge {
+            len,
+            max: caps.max_transfer_size,
+        });
+    }
+    if let Some(dst_len) = copied_len(desc, op) {
+        let (src, dst) = (desc.src_addr, desc.dst_addr);
+        if src < dst.saturating_add(dst_len) && dst < src.saturating_add(len) {
+            return invalid(DescriptorError::OverlappingBuffers(op));
+        }
+    }
+    Ok(())
+}
+
+/// Bytes written to the destination of an operation that copies its
+/// source there; `None` for other operations, and for MemMove, whose
+/// buffers may overlap.
+fn copied_len(desc: &DsaHwDesc, op: DsaOpcode) -> Option<u64> {
+    let len = desc.xfer_size as usize;
+    let block = crate::descriptor::dif_block_size(desc.as_dif().dif_flags);
+    let dif = crate::t10pi::DIF_SIZE;
+    let dst_len = match op {
+        DsaOpcode::CopyCrc | DsaOpcode::DifUpdate => len,
+        DsaOpcode::DifInsert => len / block * (block + dif),
+        DsaOpcode::DifStrip => len / (block + dif) * block,
+        _ => return None,
+    };
+    Some(dst_len as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::DsaCompletionRecord;
+
+    fn invalid(result: Result<(), DsaError>) -> DescriptorError {
+        match result {
+            Err(DsaError::InvalidDescriptor(problem)) => problem,
+            other => panic!("expected an invalid descriptor, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let caps = Capabilities::emulated(32);
+        let mut record = DsaCompletionRecord::new();
+        let mut buf = vec![0u8; 8192];
+        let p = buf.as_mut_ptr();
+
+        let copy = DsaHwDesc::mem_move(p.wrapping_add(100), p, 4096, &mut record);
+        unsafe { check(&copy, &caps) }.unwrap();
+        let copy_crc = DsaHwDesc::copy_crc(p.wrapping_add(100), p, 4096, 0, &mut record);
+        assert_eq!(
+            invalid(unsafe { check(&copy_crc, &caps) }),
+            DescriptorError::OverlappingBuffers(DsaOpcode::CopyCrc)
+        );
+        let copy_crc = DsaHwDesc::copy_crc(p.wrapping_add(4096), p, 4096, 0, &mut record);
+        unsafe { check(&copy_crc, &caps) }.unwrap();
+
+        let empty = DsaHwDesc::crc_gen(p, 0, 0, &mut record);
+        assert_eq!(
+            invalid(unsafe { check(&empty, &caps) }),
+            DescriptorError::EmptyTransfer(DsaOpcode::CrcGen)
+        );
+        let limited = Capabilities {
+            max_transfer_size: 1024,
+            supported_ops: vec![DsaOpcode::CrcGen],
+            block_on_fault: false,
+            ..caps.clone()
+        };
+        let crc = DsaHwDesc::crc_gen(p, 4096, 0, &mut record);
+        assert_eq!(
+            invalid(unsafe { check(&crc, &limited) }),
+            DescriptorError::TransferTooLarge {
+                len: 4096,
+                max: 1024
+            }
+        );
+        assert_eq!(
+            invalid(unsafe { check(&copy, &limited) }),
+            DescriptorError::UnsupportedOpcode(DsaOpcode::MemMove)
+        );
+        let mut blocking = DsaHwDesc::crc_gen(p, 512, 0, &mut record);
+        blocking.add_flags(DescriptorFlags::BLOCK_ON_FAULT);
+        assert_eq!(
+            invalid(unsafe { check(&blocking, &limited) }),
+            DescriptorError::UnsupportedFlags(DescriptorFlags::BLOCK_ON_FAULT)
+        );
+
+        assert!(check_len(4096).is_ok());
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            invalid(check_len(1 << 32)),
+            DescriptorError::TransferTooLarge {
+                len: 1 << 32,
+                max: u32::MAX as u64
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_batch() {
+        let caps = Capabilities::emulated(2);
+        let mut records = [DsaCompletionRecord::new(); 4];
+        let mut buf = vec![0u8; 4096];
+        let p = buf.as_mut_ptr();
+        let [r0, r1, r2, r3] = &mut records;
+
+        let descs = [
+            DsaHwDesc::crc_gen(p, 4096, 0, r0),
+            DsaHwDesc::crc_gen(p, 0, 0, r1),
+        ];
+        let batch = DsaHwDesc::batch(descs.as_ptr(), 2, r2);
+        assert_eq!(
+            invalid(unsafe { check(&batch, &caps) }),
+            DescriptorError::EmptyTransfer(DsaOpcode::CrcGen)
+        );
+        let batch = DsaHwDesc::batch(descs.as_ptr(), 3, r3);
+        assert_eq!(
+            invalid(unsafe { check(&batch, &caps) }),
+            DescriptorError::BatchTooLarge { count: 3, max: 2 }
+        );
+    }
+}
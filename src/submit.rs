@@ -12,6 +12,21 @@
 //! - **ENQCMD**: Non-posted 64-byte write for Shared Work Queues (SWQ).
 //!   Reports queue full/device busy via EFLAGS.ZF, requires PASID.
 //!
+//! # Memory Ordering
+//!
+//! Both instructions perform a 64-byte direct store, which is weakly ordered
+//! with respect to ordinary stores, like a non-temporal store:
+//!
+//! - **Before submission**, stores to the source buffers and the descriptor
+//!   may still be in the store buffer when the device reads them. An
+//!   [`sfence`] must separate them from the submission. `WorkQueue` issues
+//!   it on every submission; callers of [`movdir64b`] and [`enqcmd`] must
+//!   do so themselves.
+//! - **After submission**, later ordinary stores (for example, setting a
+//!   flag another thread polls before it reuses a buffer) may become visible
+//!   before a posted MOVDIR64B reaches the portal. Call
+//!   `WorkQueue::submission_fence` between the submissions and such stores.
+//!
 //! # Safety
 //!
 //! These functions are unsafe because:
@@ -21,6 +36,21 @@
 
 use crate::descriptor::DsaHwDesc;
 
+/// Store fence: order all earlier stores, including direct stores to a
+/// portal, before any later store.
+///
+/// See [Memory Ordering](self#memory-ordering) for when it is needed.
+#[inline]
+pub fn sfence() {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: SFENCE has no preconditions; SSE is part of the x86_64 baseline
+    unsafe {
+        core::arch::x86_64::_mm_sfence()
+    };
+    #[cfg(not(target_arch = "x86_64"))]
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
+
 /// Submit a descriptor to a Dedicated Work Queue using MOVDIR64B.
 ///
 /// # Safety
@@ -34,6 +64,9 @@ use crate::descriptor::DsaHwDesc;
 /// MOVDIR64B is a posted write - it does not wait for the device to accept
 /// the descriptor. The caller must ensure not to exceed the work queue depth.
 ///
+/// The store is weakly ordered: issue [`sfence`] after writing the buffers
+/// and descriptor, and before any later store that signals the submission.
+///
 /// # Instruction Details
 ///
 /// `MOVDIR64B r64, m512` reads 64 bytes from the source memory operand and
@@ -81,6 +114,9 @@ pub unsafe fn movdir64b(portal: *mut u8, desc: &DsaHwDesc) {
 /// ENQCMD is a non-posted write - it waits for the device to respond.
 /// The instruction sets EFLAGS.ZF=0 on success, ZF=1 on failure (queue full/retry).
 ///
+/// Issue [`sfence`] after writing the buffers and descriptor, as for
+/// [`movdir64b`].
+///
 /// # Instruction Details
 ///
 /// `ENQCMD r64, m512` reads 64 bytes from the source memory operand,
@@ -208,6 +244,14 @@ mod tests {
         assert_ne!(SubmitMode::Dedicated, SubmitMode::Shared);
     }
 
+    #[test]
+    fn test_sfence() {
+        let mut buf = [0u8; 64];
+        buf[0] = 1;
+        sfence();
+        assert_eq!(buf[0], 1);
+    }
+
     // Note: Actual submission tests require real DSA hardware
     // and are skipped in unit tests.
 }
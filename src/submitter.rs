@@ -0,0 +1,455 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Dedicated submitter thread.
+//!
+//! When many threads submit to one shared work queue, their ENQCMD
+//! instructions contend for the same portal and retry against each other;
+//! a dedicated work queue must not be written by several threads at once
+//! at all. A [`Submitter`] moves an engine onto a thread of its own: any
+//! number of application threads push operations into a lock-free MPSC
+//! queue, and the submitter thread is the only one that writes the portal,
+//! submitting in queue order.
+//!
+//! Completions are observed by the completion reactor, as for
+//! [`DsaEngine::submit_with_callback`], so the submitter thread never waits
+//! for the hardware. It does wait out a full queue according to the
+//! engine's [`QueueFullPolicy`](crate::QueueFullPolicy), which holds back
+//! later operations and so preserves submission order. Unless the engine
+//! is already serialized, the submitter serializes it with a budget of the
+//! work queue's [`max_in_flight`](crate::Capabilities::max_in_flight), so
+//! a burst never puts more descriptors in flight than a dedicated work
+//! queue holds.
+//!
+//! Operations that queue up while the submitter thread is busy are
+//! coalesced: the thread takes up to [`Coalescing::max_batch`] of them at
+//! once and submits them as a single batch descriptor, so a burst of small,
+//! metadata-sized operations costs one portal write instead of one each.
+//! The callbacks of a coalesced batch run when the whole batch has
+//! completed.
+//!
+//! By default a batch holds only what was already waiting, so coalescing
+//! never delays an operation. Setting [`Coalescing::max_delay`] lets the
+//! thread hold a partial batch open for more operations: it is flushed once
+//! it reaches `max_batch` operations or its oldest operation has waited
+//! `max_delay`, whichever comes first. This trades a bounded queueing delay
+//! for larger batches when operations trickle in.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::callback::{DsaOp, DsaOutput};
+//! use dsa_rust::submitter::Submitter;
+//! use dsa_rust::DsaEngine;
+//! use std::sync::Arc;
+//!
+//! let submitter = Arc::new(Submitter::new(DsaEngine::open_first()?));
+//! let workers: Vec<_> = (0..8)
+//!     .map(|_| {
+//!         let submitter = Arc::clone(&submitter);
+//!         std::thread::spawn(move || {
+//!             let op = DsaOp::Crc32 { data: vec![0u8; 4096], seed: 0 };
+//!             let (_, result) = submitter.call(op);
+//!             result
+//!         })
+//!     })
+//!     .collect();
+//! for worker in workers {
+//!     assert!(matches!(worker.join().unwrap()?, DsaOutput::Crc32(_)));
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::callback::{DsaOp, DsaOutput, OpCallback};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// An operation waiting in the submission queue.
+struct Request {
+    op: DsaOp,
+    callback: OpCallback,
+    queued_at: Instant,
+}
+
+/// How a [`Submitter`] coalesces queued operations into batch descriptors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coalescing {
+    /// Most operations submitted in one batch descriptor. Values above the
+    /// device batch size limit of 1024 are clamped; values below 2 disable
+    /// coalescing.
+    pub max_batch: usize,
+    /// Longest an operation waits in the queue for a batch to fill, e.g.
+    /// 20 µs. Zero flushes as soon as the waiting operations are taken.
+    ///
+    /// The wait is bounded by the operating system's timer resolution, and
+    /// an operation may also wait while the thread is busy submitting.
+    pub max_delay: Duration,
+}
+
+impl Default for Coalescing {
+    /// Coalesce up to 32 waiting operations per batch, without delay.
+    fn default() -> Self {
+        Self {
+            max_batch: 32,
+            max_delay: Duration::ZERO,
+        }
+    }
+}
+
+/// Front end that funnels operations from many threads to one submitter
+/// thread owning an engine.
+///
+/// Dropping the submitter submits the operations still queued, then stops
+/// the thread. Operations already submitted complete in the background.
+pub struct Submitter {
+    queue: Option<Sender<Request>>,
+    /// Requests pushed but not yet submitted.
+    queued: Arc<AtomicUsize>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Submitter {
+    /// Move `engine` onto a new submitter thread that coalesces with the
+    /// default [`Coalescing`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread cannot be spawned.
+    pub fn new(engine: DsaEngine) -> Self {
+        Self::with_coalescing(engine, Some(Coalescing::default()))
+    }
+
+    /// Move `engine` onto a new submitter thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - The engine whose work queue the thread submits to
+    /// * `coalescing` - How queued operations are batched, or `None` to
+    ///   submit every operation on its own
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thread cannot be spawned.
+    pub fn with_coalescing(mut engine: DsaEngine, coalescing: Option<Coalescing>) -> Self {
+        if engine.work_queue().serialized().is_none() {
+            let budget = (engine.capabilities().max_in_flight as usize).max(1);
+            engine.set_serialized(Some(budget));
+        }
+        let coalescing = coalescing.map_or(
+            Coalescing {
+                max_batch: 1,
+                max_delay: Duration::ZERO,
+            },
+            |coalescing| Coalescing {
+                max_batch: coalescing.max_batch.clamp(1, DEFAULT_MAX_BATCH_SIZE),
+                ..coalescing
+            },
+        );
+        let (queue, requests) = mpsc::channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&queued);
+        let thread = std::thread::Builder::new()
+            .name("dsa-submitter".to_string())
+            .spawn(move || run(&engine, &requests, &counter, coalescing))
+            .expect("failed to spawn DSA submitter thread");
+        Self {
+            queue: Some(queue),
+            queued,
+            thread: Some(thread),
+        }
+    }
+
+    /// Queue an operation and invoke `callback` when it completes.
+    ///
+    /// Returns without waiting for the submitter thread. The callback is
+    /// invoked exactly once, as by [`DsaEngine::submit_with_callback`]: on
+    /// the completion reactor thread, or on the submitter thread if the
+    /// operation is rejected before submission or needs no hardware.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the submitter thread has panicked.
+    pub fn submit<F>(&self, op: DsaOp, callback: F)
+    where
+        F: FnOnce(DsaOp, Result<DsaOutput, DsaError>) + Send + 'static,
+    {
+        let request = Request {
+            op,
+            callback: Box::new(callback),
+            queued_at: Instant::now(),
+        };
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.queue
+            .as_ref()
+            .and_then(|queue| queue.send(request).ok())
+            .expect("DSA submitter thread panicked");
+    }
+
+    /// Queue an operation and block until it completes.
+    ///
+    /// # Returns
+    ///
+    /// The operation, with ownership of its buffers, and its result.
+    pub fn call(&self, op: DsaOp) -> (DsaOp, Result<DsaOutput, DsaError>) {
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.submit(op, move |op, result| {
+            let _ = tx.send((op, result));
+        });
+        rx.recv().expect("DSA callbacks are invoked exactly once")
+    }
+
+    /// Number of operations queued but not yet submitted.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Submitter {
+    fn drop(&mut self) {
+        // Closing the queue lets the thread finish once it is drained
+        drop(self.queue.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Submitter thread: submit requests in queue order until the queue closes,
+/// coalescing them as configured.
+fn run(
+    engine: &DsaEngine,
+    requests: &Receiver<Request>,
+    queued: &AtomicUsize,
+    coalescing: Coalescing,
+) {
+    while let Ok(request) = requests.recv() {
+        let deadline = request.queued_at + coalescing.max_delay;
+        let mut burst = vec![request];
+        while burst.len() < coalescing.max_batch {
+            let next = match deadline.checked_duration_since(Instant::now()) {
+                Some(wait) if !wait.is_zero() => requests.recv_timeout(wait).ok(),
+                _ => requests.try_recv().ok(),
+            };
+            match next {
+                Some(request) => burst.push(request),
+                None => break,
+            }
+        }
+        queued.fetch_sub(burst.len(), Ordering::Relaxed);
+
+        if burst.len() == 1 {
+            let request = burst.pop().unwrap();
+            engine.submit_boxed(request.op, request.callback, true);
+        } else {
+            let ops = burst
+                .into_iter()
+                .map(|request| (request.op, request.callback))
+                .collect();
+            engine.submit_coalesced(ops);
+        }
+    }
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use crate::emulator::Emulator;
+
+    #[test]
+    fn test_many_threads_one_portal() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let submitter = Arc::new(Submitter::new(engine));
+        let threads: Vec<_> = (0..8u8)
+            .map(|t| {
+                let submitter = Arc::clone(&submitter);
+                std::thread::spawn(move || {
+                    for i in 0..50u8 {
+                        let data = vec![t ^ i; 256];
+                        let expected = crate::crc::software_crc32(&data, 0);
+                        let (_, result) = submitter.call(DsaOp::Crc32 { data, seed: 0 });
+                        assert_eq!(result.unwrap(), DsaOutput::Crc32(expected));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(submitter.queued(), 0);
+    }
+
+    #[test]
+    fn test_drop_submits_queued_operations() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let submitter = Submitter::new(engine);
+        let (tx, rx) = mpsc::channel();
+        for i in 0..100 {
+            let tx = tx.clone();
+            submitter.submit(DsaOp::Noop, move |_, result| {
+                tx.send((i, result.is_ok())).unwrap();
+            });
+        }
+        drop(submitter);
+        drop(tx);
+        let done: Vec<_> = rx.iter().collect();
+        assert_eq!(done.len(), 100);
+        assert!(done.iter().all(|&(_, ok)| ok));
+    }
+
+    #[test]
+    fn test_rejected_operation_reported() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let submitter = Submitter::new(engine);
+        let op = DsaOp::Memcpy {
+            dst: vec![0; 4],
+            src: vec![0; 8],
+        };
+        let (_, result) = submitter.call(op);
+        assert!(matches!(result, Err(DsaError::BufferSizeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_queued_operations_coalesced() {
+        use crate::emulator::Fault;
+        use crate::QueueFullPolicy;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        engine.set_queue_full_policy(QueueFullPolicy::WaitAndRetry {
+            backoff: Duration::from_millis(100),
+            timeout: Duration::from_secs(10),
+        });
+        let submitter = Submitter::new(engine);
+
+        // Hold the thread in a queue-full backoff while a burst queues up
+        emulator.inject(Fault::QueueFull { count: 1 });
+        let (tx, rx) = mpsc::channel();
+        let first = tx.clone();
+        submitter.submit(DsaOp::Noop, move |_, result| {
+            first.send((usize::MAX, result)).unwrap();
+        });
+        while submitter.queued() > 0 {
+            std::thread::yield_now();
+        }
+        for i in 0..10u8 {
+            let tx = tx.clone();
+            let data = vec![i; 64];
+            submitter.submit(DsaOp::Crc32 { data, seed: 0 }, move |_, result| {
+                tx.send((i as usize, result)).unwrap();
+            });
+        }
+        drop(tx);
+
+        let done: Vec<_> = rx.iter().take(11).collect();
+        for (i, result) in done {
+            if i == usize::MAX {
+                assert_eq!(result.unwrap(), DsaOutput::Noop);
+            } else {
+                let expected = crate::crc::software_crc32(&[i as u8; 64], 0);
+                assert_eq!(result.unwrap(), DsaOutput::Crc32(expected));
+            }
+        }
+        // The no-op, then one batch for the burst, counted before dropping
+        // the engine may add a drain
+        assert_eq!(emulator.submitted(), 2);
+        drop(submitter);
+    }
+
+    #[test]
+    fn test_in_flight_bounded() {
+        use crate::capabilities::DEFAULT_MAX_IN_FLIGHT;
+        use crate::mock::{MockBackend, Response};
+        use crate::opcode::DsaOpcode;
+
+        let mock = Arc::new(MockBackend::new());
+        let budget = u64::from(DEFAULT_MAX_IN_FLIGHT);
+        for call in 1..=budget {
+            mock.respond(DsaOpcode::Noop, call, Response::Delay { polls: 1 });
+        }
+        let engine = DsaEngine::mocked(Arc::clone(&mock)).unwrap();
+        let submitter = Submitter::with_coalescing(engine, None);
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..=budget {
+            let tx = tx.clone();
+            submitter.submit(DsaOp::Noop, move |_, result| tx.send(result).unwrap());
+        }
+
+        // The operation beyond the budget waits for room
+        while submitter.queued() > 0 {
+            std::thread::yield_now();
+        }
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(mock.calls(DsaOpcode::Noop), budget);
+
+        mock.poll();
+        assert!(rx
+            .iter()
+            .take(budget as usize + 1)
+            .all(|result| result.is_ok()));
+        assert_eq!(mock.calls(DsaOpcode::Noop), budget + 1);
+    }
+
+    #[test]
+    fn test_coalescing_disabled() {
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let submitter = Submitter::with_coalescing(engine, None);
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..20 {
+            let tx = tx.clone();
+            submitter.submit(DsaOp::Noop, move |_, result| tx.send(result).unwrap());
+        }
+        assert!(rx.iter().take(20).all(|result| result.is_ok()));
+        assert_eq!(emulator.submitted(), 20);
+    }
+
+    #[test]
+    fn test_partial_batch_flushed_after_delay() {
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let coalescing = Coalescing {
+            max_batch: 16,
+            max_delay: Duration::from_millis(100),
+        };
+        let submitter = Submitter::with_coalescing(engine, Some(coalescing));
+
+        let (tx, rx) = mpsc::channel();
+        let start = Instant::now();
+        for _ in 0..3 {
+            let tx = tx.clone();
+            submitter.submit(DsaOp::Noop, move |_, result| tx.send(result).unwrap());
+        }
+        assert!(rx.iter().take(3).all(|result| result.is_ok()));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(emulator.submitted(), 1);
+    }
+
+    #[test]
+    fn test_full_batch_flushed_before_delay() {
+        let emulator = Arc::new(Emulator::new());
+        let engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let coalescing = Coalescing {
+            max_batch: 4,
+            max_delay: Duration::from_secs(60),
+        };
+        let submitter = Submitter::with_coalescing(engine, Some(coalescing));
+
+        let (tx, rx) = mpsc::channel();
+        let start = Instant::now();
+        for _ in 0..4 {
+            let tx = tx.clone();
+            submitter.submit(DsaOp::Noop, move |_, result| tx.send(result).unwrap());
+        }
+        assert!(rx.iter().take(4).all(|result| result.is_ok()));
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert_eq!(emulator.submitted(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Device software error register (SWERR).
+//!
+//! Errors a device cannot report in a completion record, such as a
+//! descriptor with an invalid completion record address, and hardware
+//! errors are logged in the device's software error register. The IDXD
+//! driver exposes the last logged error as the device's `errors` sysfs
+//! attribute: four 64-bit words, of which the first says whether an error
+//! is logged at all. [`DsaDevice::errors`] reads and decodes it into a
+//! [`SoftwareError`].
+//!
+//! With [`DsaEngine::set_attach_device_errors`], a blocking operation that
+//! fails with a hardware error status also reads the register of its work
+//! queue's device and attaches it to the error's
+//! [`ErrorContext`](crate::error::ErrorContext), so the failure and the
+//! device log can be read together.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::discover_devices;
+//!
+//! for device in discover_devices()? {
+//!     if let Some(error) = device.errors()? {
+//!         eprintln!("{}: {error}", device.name);
+//!     }
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::device::DsaDevice;
+use crate::engine::DsaEngine;
+use crate::error::{status_name, DsaError};
+use crate::opcode::DecodedOpcode;
+
+/// An error logged in a device's software error register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftwareError {
+    /// Error code, using the completion status codes
+    /// ([`status_name`]).
+    pub status: u8,
+    /// More errors occurred after this one was logged and were lost.
+    pub overflow: bool,
+    /// Index of the work queue the descriptor was submitted to, if known.
+    pub work_queue: Option<u8>,
+    /// Opcode of the failing descriptor, if known.
+    pub opcode: Option<DecodedOpcode>,
+    /// PASID of the failing descriptor, if known.
+    pub pasid: Option<u32>,
+    /// Whether the failing descriptor was privileged.
+    pub privileged: bool,
+    /// Index of the failing descriptor in its batch, if it was in one.
+    pub batch_index: Option<u16>,
+    /// Invalid flags of the descriptor, for an invalid flags error.
+    pub invalid_flags: u32,
+    /// Faulting address, for a translation error.
+    pub fault_addr: u64,
+    /// Whether the fault was on a write.
+    pub fault_write: bool,
+}
+
+impl SoftwareError {
+    /// Decode the four words of the register; `None` if no error is
+    /// logged.
+    pub fn from_words(words: [u64; 4]) -> Option<Self> {
+        let word = words[0];
+        let bit = |n: u32| word >> n & 1 != 0;
+        if !bit(0) {
+            return None;
+        }
+        let desc_valid = bit(2);
+        let batch = bit(4);
+        Some(Self {
+            status: (word >> 8) as u8,
+            overflow: bit(1),
+            work_queue: bit(3).then_some((word >> 16) as u8),
+            opcode: desc_valid.then(|| DecodedOpcode::from((word >> 32) as u8)),
+            pasid: desc_valid.then_some((word >> 40) as u32 & 0xF_FFFF),
+            privileged: bit(6),
+            batch_index: (desc_valid && batch).then_some(words[1] as u16),
+            invalid_flags: (words[1] >> 32) as u32,
+            fault_addr: words[2],
+            fault_write: bit(5),
+        })
+    }
+
+    /// Parse the `errors` sysfs attribute, four hexadecimal words separated
+    /// by whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `errors` is not four
+    /// hexadecimal words.
+    pub fn parse(errors: &str) -> Result<Option<Self>, DsaError> {
+        let invalid = || DsaError::InvalidArgument(format!("invalid errors in sysfs: {errors}"));
+        let mut words = [0u64; 4];
+        let mut fields = errors.split_whitespace();
+        for word in words.iter_mut() {
+            let field = fields.next().ok_or_else(invalid)?;
+            let digits = field.strip_prefix("0x").unwrap_or(field);
+            *word = u64::from_str_radix(digits, 16).map_err(|_| invalid())?;
+        }
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self::from_words(words))
+    }
+}
+
+impl std::fmt::Display for SoftwareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "status={:#04x} ({})",
+            self.status,
+            status_name(self.status)
+        )?;
+        if let Some(opcode) = self.opcode {
+            write!(f, ", {opcode}")?;
+        }
+        if let Some(index) = self.batch_index {
+            write!(f, " at batch index {index}")?;
+        }
+        if let Some(wq) = self.work_queue {
+            write!(f, " on work queue {wq}")?;
+        }
+        if self.invalid_flags != 0 {
+            write!(f, ", invalid flags {:#x}", self.invalid_flags)?;
+        }
+        if self.fault_addr != 0 {
+            let access = if self.fault_write { "write" } else { "read" };
+            write!(f, ", {access} fault at {:#x}", self.fault_addr)?;
+        }
+        if self.overflow {
+            write!(f, "; later errors were lost")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if `err` reports a hardware error status, for which the
+/// device logs details in its software error register.
+pub(crate) fn is_hardware_error(err: &DsaError) -> bool {
+    matches!(err, DsaError::OperationFailed { status, .. } if matches!(status & 0x7F, 0x1F..=0x21))
+}
+
+/// Read the software error register of the device whose sysfs directory
+/// is `device_dir`.
+#[cfg(dsa_portal)]
+pub(crate) fn read(device_dir: &std::path::Path) -> Result<Option<SoftwareError>, DsaError> {
+    SoftwareError::parse(&std::fs::read_to_string(device_dir.join("errors"))?)
+}
+
+impl DsaDevice {
+    /// Read the last error logged in the device's software error register.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no error is logged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `errors` attribute cannot be read or parsed,
+    /// or `DsaError::PlatformNotSupported` on platforms other than Linux.
+    #[cfg(dsa_portal)]
+    pub fn errors(&self) -> Result<Option<SoftwareError>, DsaError> {
+        read(&self.sysfs_path)
+    }
+
+    /// Read the last error logged in the device's software error register.
+    #[cfg(not(dsa_portal))]
+    pub fn errors(&self) -> Result<Option<SoftwareError>, DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+}
+
+impl DsaEngine {
+    /// Attach the device's software error register to the context of
+    /// blocking operations that fail with a hardware error status.
+    ///
+    /// Off by default, since it reads sysfs when such an error occurs.
+    /// Sealed engines never attach it.
+    pub fn set_attach_device_errors(&mut self, attach: bool) {
+        self.work_queue_mut().set_attach_device_errors(attach);
+    }
+
+    /// Returns true if device errors are attached to hardware errors.
+    pub fn attaches_device_errors(&self) -> bool {
+        self.work_queue().attaches_device_errors()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::DsaOpcode;
+
+    #[test]
+    fn test_parse() {
+        let idle = "0x0000000000000000 0x0000000000000000 0x0000000000000000 0x0000000000000000\n";
+        assert_eq!(SoftwareError::parse(idle).unwrap(), None);
+        assert!(SoftwareError::parse("0x1 0x0 0x0").is_err());
+        assert!(SoftwareError::parse("0x1 0x0 0x0 0x0 0x0").is_err());
+        assert!(SoftwareError::parse("0x1 0x0 0xZ 0x0").is_err());
+
+        // CRC descriptor at index 3 of a batch on work queue 2, PASID 5
+        let word0: u64 = 1 | 1 << 2 | 1 << 3 | 1 << 4 | 0x20 << 8 | 2 << 16 | 0x10 << 32 | 5 << 40;
+        let errors = format!("{word0:#018x} 0x0000000000000003 0x0000000000001000 0x0");
+        let error = SoftwareError::parse(&errors).unwrap().unwrap();
+        assert_eq!(error.status, 0x20);
+        assert_eq!(error.work_queue, Some(2));
+        assert_eq!(error.opcode, Some(DecodedOpcode::Known(DsaOpcode::CrcGen)));
+        assert_eq!(error.pasid, Some(5));
+        assert_eq!(error.batch_index, Some(3));
+        assert_eq!(error.fault_addr, 0x1000);
+        assert!(!error.overflow);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "status=0x20 (hardware error), {} at batch index 3 on work queue 2, read fault at 0x1000",
+                DecodedOpcode::Known(DsaOpcode::CrcGen)
+            )
+        );
+
+        // Without a valid descriptor, only the error code is known
+        let error = SoftwareError::from_words([1 | 1 << 1 | 0x19 << 8, 0, 0, 0]).unwrap();
+        assert_eq!(error.opcode, None);
+        assert_eq!(error.work_queue, None);
+        assert!(error.overflow);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_attach_to_hardware_errors() {
+        use crate::mock::{MockBackend, Response};
+        use std::sync::Arc;
+
+        let failed = |status| DsaError::OperationFailed {
+            status,
+            result: 0,
+            bytes_completed: 0,
+            context: None,
+        };
+        assert!(is_hardware_error(&failed(0x21)));
+        assert!(!is_hardware_error(&failed(0x13)));
+        assert!(!is_hardware_error(&DsaError::Cancelled));
+
+        let mock = Arc::new(MockBackend::new());
+        let mut engine = DsaEngine::mocked(Arc::clone(&mock)).unwrap();
+        assert!(!engine.attaches_device_errors());
+        engine.set_attach_device_errors(true);
+        assert!(engine.attaches_device_errors());
+
+        // A software work queue has no device to read
+        mock.respond_next(DsaOpcode::Noop, Response::Status(0x1F));
+        let err = engine.noop().unwrap_err();
+        assert!(is_hardware_error(&err));
+        assert_eq!(err.context().unwrap().device_error, None);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_device_errors() {
+        let dir = std::env::temp_dir().join(format!("dsa-swerr-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let device = DsaDevice {
+            name: "dsa0".to_string(),
+            sysfs_path: dir.clone(),
+            work_queues: Vec::new(),
+            state: "enabled".to_string(),
+            pasid_enabled: true,
+            max_groups: 4,
+            max_engines: 4,
+            max_work_queues: 8,
+            version: 0x100,
+            pci_address: None,
+        };
+        assert!(device.errors().is_err());
+
+        std::fs::write(dir.join("errors"), "0x0 0x0 0x0 0x0\n").unwrap();
+        assert_eq!(device.errors().unwrap(), None);
+        std::fs::write(dir.join("errors"), "0x0000000000002101 0x0 0x0 0x0\n").unwrap();
+        let error = device.errors().unwrap().unwrap();
+        assert_eq!(error.status, 0x21);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
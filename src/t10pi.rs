@@ -0,0 +1,640 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! T10 Protection Information (DIF) for storage sectors.
+//!
+//! Storage stacks protect each sector with an 8-byte Data Integrity Field:
+//! a CRC16 guard of the sector data, a 16-bit application tag and a 32-bit
+//! reference tag, all big-endian. Protected buffers hold 520-byte sectors
+//! for 512-byte data sectors, or 4104-byte sectors for 4096-byte ones.
+//!
+//! A [`SectorFormatter`] converts between data buffers and protected
+//! buffers with the DSA DIF operations: [`insert`](SectorFormatter::insert)
+//! adds the fields, [`strip`](SectorFormatter::strip) checks and removes
+//! them, [`check`](SectorFormatter::check) only checks them, and
+//! [`update`](SectorFormatter::update) checks them and replaces the tags.
+//! The reference tag of the first sector is set with
+//! [`with_ref_tag`](SectorFormatter::with_ref_tag), usually to the low 32
+//! bits of its LBA, and increments by one per sector (Type 1 protection).
+//! The application tag is the same for every sector.
+//!
+//! Buffers must hold whole sectors. A failed check returns
+//! `DsaError::DifCheckFailed`, whose `bytes_completed` locates the first
+//! bad sector within the whole buffer.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::t10pi::{SectorFormatter, SectorSize};
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let lba = 2048;
+//! let formatter = SectorFormatter::new(SectorSize::Bytes512).with_ref_tag(lba);
+//!
+//! let data = vec![0xA5u8; 8 * 512];
+//! let mut protected = vec![0u8; formatter.protected_len(data.len())?];
+//! formatter.insert(&engine, &mut protected, &data)?;
+//!
+//! let mut stripped = vec![0u8; data.len()];
+//! formatter.strip(&engine, &mut stripped, &protected)?;
+//! assert_eq!(stripped, data);
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::descriptor::{DifStatus, DifTags};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+
+/// Size of the Data Integrity Field appended to each block.
+pub const DIF_SIZE: usize = crate::descriptor::DIF_SIZE;
+
+/// Bytes of protected sectors per descriptor; larger buffers are split.
+const SECTOR_CHUNK_SIZE: usize = 1 << 20;
+
+/// Size of a data sector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectorSize {
+    /// 512-byte sectors, 520 bytes when protected.
+    Bytes512,
+    /// 4096-byte sectors, 4104 bytes when protected.
+    Bytes4096,
+}
+
+impl SectorSize {
+    /// Bytes of data per sector.
+    pub const fn data_size(self) -> usize {
+        match self {
+            Self::Bytes512 => 512,
+            Self::Bytes4096 => 4096,
+        }
+    }
+
+    /// Bytes per sector including its DIF.
+    pub const fn protected_size(self) -> usize {
+        self.data_size() + DIF_SIZE
+    }
+
+    /// DIF flags selecting this block size.
+    pub const fn dif_flags(self) -> u8 {
+        match self {
+            Self::Bytes512 => 0,
+            Self::Bytes4096 => 2,
+        }
+    }
+}
+
+/// Converts between data buffers and buffers of protected sectors.
+///
+/// By default the first reference tag and the application tag are zero,
+/// and every application tag bit is checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorFormatter {
+    sector: SectorSize,
+    tags: DifTags,
+}
+
+impl SectorFormatter {
+    /// Create a formatter for sectors of `sector` bytes.
+    pub fn new(sector: SectorSize) -> Self {
+        Self {
+            sector,
+            tags: DifTags::default(),
+        }
+    }
+
+    /// Start the reference tags at `ref_tag`.
+    pub fn with_ref_tag(mut self, ref_tag: u32) -> Self {
+        self.tags.ref_tag = ref_tag;
+        self
+    }
+
+    /// Use `app_tag` as the application tag.
+    ///
+    /// Checks ignore the application tag bits set in `mask`.
+    pub fn with_app_tag(mut self, app_tag: u16, mask: u16) -> Self {
+        self.tags.app_tag = app_tag;
+        self.tags.app_tag_mask = mask;
+        self
+    }
+
+    /// The sector size.
+    pub fn sector_size(&self) -> SectorSize {
+        self.sector
+    }
+
+    /// The tags of the first sector.
+    pub fn tags(&self) -> DifTags {
+        self.tags
+    }
+
+    /// Length of the protected buffer for `data_len` bytes of data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `data_len` is not a whole
+    /// number of data sectors.
+    pub fn protected_len(&self, data_len: usize) -> Result<usize, DsaError> {
+        Ok(self.sectors(data_len, self.sector.data_size())? * self.sector.protected_size())
+    }
+
+    /// Length of the data in a protected buffer of `protected_len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `protected_len` is not a whole
+    /// number of protected sectors.
+    pub fn data_len(&self, protected_len: usize) -> Result<usize, DsaError> {
+        Ok(self.sectors(protected_len, self.sector.protected_size())? * self.sector.data_size())
+    }
+
+    /// Number of `sector_size`-byte sectors in `len` bytes.
+    fn sectors(&self, len: usize, sector_size: usize) -> Result<usize, DsaError> {
+        if !len.is_multiple_of(sector_size) {
+            return Err(DsaError::InvalidArgument(format!(
+                "{len} bytes is not a whole number of {sector_size}-byte sectors"
+            )));
+        }
+        Ok(len / sector_size)
+    }
+
+    /// Sectors per descriptor.
+    fn chunk_sectors(&self) -> usize {
+        (SECTOR_CHUNK_SIZE / self.sector.protected_size()).max(1)
+    }
+
+    /// Tags of the sector `sectors` after the first.
+    fn tags_at(&self, sectors: usize) -> DifTags {
+        DifTags {
+            ref_tag: self.tags.ref_tag.wrapping_add(sectors as u32),
+            ..self.tags
+        }
+    }
+
+    /// Protect the data sectors of `src` into `dst`.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - Engine to run the operations on
+    /// * `dst` - Protected buffer of [`protected_len`](Self::protected_len)
+    ///   bytes
+    /// * `src` - Data sectors
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `src` is not a whole number
+    /// of sectors, `DsaError::BufferSizeMismatch` if `dst` is too small, or
+    /// an error if an operation fails.
+    pub fn insert(&self, engine: &DsaEngine, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+        let required = self.protected_len(src.len())?;
+        check_dst(dst.len(), required)?;
+
+        let (data_size, protected_size) = (self.sector.data_size(), self.sector.protected_size());
+        let chunks = src
+            .chunks(self.chunk_sectors() * data_size)
+            .zip(dst[..required].chunks_mut(self.chunk_sectors() * protected_size));
+        for (i, (src, dst)) in chunks.enumerate() {
+            let tags = self.tags_at(i * self.chunk_sectors());
+            engine.throttle(src.len(), 1);
+            engine.retry(|| {
+                engine
+                    .work_queue()
+                    .dif_insert(dst, src, self.sector.dif_flags(), tags)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Check the protected sectors of `src` and copy their data to `dst`.
+    ///
+    /// If a check fails, the data of the sectors before the failing one
+    /// may have been copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::DifCheckFailed` if a sector fails its check,
+    /// `DsaError::InvalidArgument` if `src` is not a whole number of
+    /// sectors, `DsaError::BufferSizeMismatch` if `dst` is too small, or an
+    /// error if an operation fails.
+    pub fn strip(&self, engine: &DsaEngine, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+        let required = self.data_len(src.len())?;
+        check_dst(dst.len(), required)?;
+
+        let (data_size, protected_size) = (self.sector.data_size(), self.sector.protected_size());
+        let chunks = src
+            .chunks(self.chunk_sectors() * protected_size)
+            .zip(dst[..required].chunks_mut(self.chunk_sectors() * data_size));
+        for (i, (src, dst)) in chunks.enumerate() {
+            let tags = self.tags_at(i * self.chunk_sectors());
+            engine.throttle(src.len(), 1);
+            engine
+                .retry(|| {
+                    engine
+                        .work_queue()
+                        .dif_strip(dst, src, self.sector.dif_flags(), tags)
+                })
+                .map_err(|e| at_offset(e, i * self.chunk_sectors() * protected_size))?;
+        }
+        Ok(())
+    }
+
+    /// Check the protected sectors of `src`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::DifCheckFailed` if a sector fails its check,
+    /// `DsaError::InvalidArgument` if `src` is not a whole number of
+    /// sectors, or an error if an operation fails.
+    pub fn check(&self, engine: &DsaEngine, src: &[u8]) -> Result<(), DsaError> {
+        self.data_len(src.len())?;
+
+        let protected_size = self.sector.protected_size();
+        for (i, src) in src
+            .chunks(self.chunk_sectors() * protected_size)
+            .enumerate()
+        {
+            let tags = self.tags_at(i * self.chunk_sectors());
+            engine.throttle(src.len(), 1);
+            engine
+                .retry(|| {
+                    engine
+                        .work_queue()
+                        .dif_check(src, self.sector.dif_flags(), tags)
+                })
+                .map_err(|e| at_offset(e, i * self.chunk_sectors() * protected_size))?;
+        }
+        Ok(())
+    }
+
+    /// Check the protected sectors of `src` and copy them to `dst` with
+    /// the tags of `to`.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - Engine to run the operations on
+    /// * `dst` - Protected buffer at least as long as `src`
+    /// * `src` - Protected sectors with this formatter's tags
+    /// * `to` - Formatter with the new tags
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::DifCheckFailed` if a sector fails its check,
+    /// `DsaError::InvalidArgument` if `src` is not a whole number of
+    /// sectors or `to` has a different sector size,
+    /// `DsaError::BufferSizeMismatch` if `dst` is too small, or an error if
+    /// an operation fails.
+    pub fn update(
+        &self,
+        engine: &DsaEngine,
+        dst: &mut [u8],
+        src: &[u8],
+        to: &SectorFormatter,
+    ) -> Result<(), DsaError> {
+        if to.sector != self.sector {
+            return Err(DsaError::InvalidArgument(format!(
+                "cannot update {:?} sectors to {:?} sectors",
+                self.sector, to.sector
+            )));
+        }
+        self.data_len(src.len())?;
+        check_dst(dst.len(), src.len())?;
+
+        let chunk_size = self.chunk_sectors() * self.sector.protected_size();
+        let chunks = src.chunks(chunk_size).zip(dst.chunks_mut(chunk_size));
+        for (i, (src, dst)) in chunks.enumerate() {
+            let src_tags = self.tags_at(i * self.chunk_sectors());
+            let dest_tags = to.tags_at(i * self.chunk_sectors());
+            engine.throttle(src.len(), 1);
+            engine
+                .retry(|| {
+                    engine.work_queue().dif_update(
+                        dst,
+                        src,
+                        self.sector.dif_flags(),
+                        src_tags,
+                        dest_tags,
+                    )
+                })
+                .map_err(|e| at_offset(e, i * chunk_size))?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that a destination of `len` bytes holds `required` bytes.
+fn check_dst(len: usize, required: usize) -> Result<(), DsaError> {
+    if len < required {
+        return Err(DsaError::BufferSizeMismatch {
+            expected: required,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
+/// Make the position of a failed DIF check relative to the whole buffer
+/// rather than the chunk starting at `offset`.
+fn at_offset(err: DsaError, offset: usize) -> DsaError {
+    match err {
+        DsaError::DifCheckFailed {
+            status,
+            bytes_completed,
+        } => DsaError::DifCheckFailed {
+            status,
+            bytes_completed: bytes_completed + offset as u32,
+        },
+        err => err,
+    }
+}
+
+/// CRC16 lookup table for the T10-DIF polynomial 0x8BB7.
+const CRC16_TABLE: [u16; 256] = {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8BB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Compute the T10-DIF guard tag (CRC16, polynomial 0x8BB7, unreflected,
+/// zero initial value) of `data`.
+pub fn crc16_t10dif(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (crc << 8) ^ CRC16_TABLE[((crc >> 8) as u8 ^ byte) as usize]
+    })
+}
+
+/// A DIF check that failed in software.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DifFailure {
+    /// The failed checks.
+    pub status: DifStatus,
+    /// Source bytes before the failing block.
+    pub bytes_completed: usize,
+    /// Tags the failing block was checked against.
+    pub tags: DifTags,
+}
+
+impl From<DifFailure> for DsaError {
+    fn from(failure: DifFailure) -> Self {
+        DsaError::DifCheckFailed {
+            status: failure.status,
+            bytes_completed: failure.bytes_completed as u32,
+        }
+    }
+}
+
+/// Tags of the block after one with `tags`.
+fn next(tags: DifTags) -> DifTags {
+    DifTags {
+        ref_tag: tags.ref_tag.wrapping_add(1),
+        ..tags
+    }
+}
+
+/// The DIF of a data block.
+fn dif(data: &[u8], tags: DifTags) -> [u8; DIF_SIZE] {
+    let mut dif = [0u8; DIF_SIZE];
+    dif[..2].copy_from_slice(&crc16_t10dif(data).to_be_bytes());
+    dif[2..4].copy_from_slice(&tags.app_tag.to_be_bytes());
+    dif[4..].copy_from_slice(&tags.ref_tag.to_be_bytes());
+    dif
+}
+
+/// The checks a protected block fails against `tags`.
+fn check_block(block: &[u8], tags: DifTags) -> DifStatus {
+    let (data, dif) = block.split_at(block.len() - DIF_SIZE);
+    let guard = u16::from_be_bytes([dif[0], dif[1]]);
+    let app_tag = u16::from_be_bytes([dif[2], dif[3]]);
+    let ref_tag = u32::from_be_bytes([dif[4], dif[5], dif[6], dif[7]]);
+
+    let mut status = DifStatus::empty();
+    status.set(DifStatus::GUARD_MISMATCH, guard != crc16_t10dif(data));
+    status.set(
+        DifStatus::APP_TAG_MISMATCH,
+        (app_tag ^ tags.app_tag) & !tags.app_tag_mask != 0,
+    );
+    status.set(DifStatus::REF_TAG_MISMATCH, ref_tag != tags.ref_tag);
+    status
+}
+
+/// Protect the `block`-byte data blocks of `src` into `dst`.
+///
+/// Returns the tags of the next block.
+pub(crate) fn insert(dst: &mut [u8], src: &[u8], block: usize, mut tags: DifTags) -> DifTags {
+    for (data, protected) in src.chunks(block).zip(dst.chunks_mut(block + DIF_SIZE)) {
+        protected[..block].copy_from_slice(data);
+        protected[block..].copy_from_slice(&dif(data, tags));
+        tags = next(tags);
+    }
+    tags
+}
+
+/// Check the protected blocks of `src`, passing each block that passes and
+/// its tags to `f`.
+///
+/// Returns the tags of the next block.
+pub(crate) fn check(
+    src: &[u8],
+    block: usize,
+    mut tags: DifTags,
+    mut f: impl FnMut(usize, &[u8], DifTags),
+) -> Result<DifTags, DifFailure> {
+    for (i, protected) in src.chunks(block + DIF_SIZE).enumerate() {
+        let status = check_block(protected, tags);
+        if !status.is_empty() {
+            return Err(DifFailure {
+                status,
+                bytes_completed: i * (block + DIF_SIZE),
+                tags,
+            });
+        }
+        f(i, &protected[..block], tags);
+        tags = next(tags);
+    }
+    Ok(tags)
+}
+
+/// Check the protected blocks of `src` and copy their data to `dst`.
+pub(crate) fn strip(
+    dst: &mut [u8],
+    src: &[u8],
+    block: usize,
+    tags: DifTags,
+) -> Result<DifTags, DifFailure> {
+    check(src, block, tags, |i, data, _| {
+        dst[i * block..(i + 1) * block].copy_from_slice(data);
+    })
+}
+
+/// Check the protected blocks of `src` and copy them to `dst` with DIFs
+/// computed from `dest_tags`.
+///
+/// Returns the source and destination tags of the next block.
+pub(crate) fn update(
+    dst: &mut [u8],
+    src: &[u8],
+    block: usize,
+    src_tags: DifTags,
+    mut dest_tags: DifTags,
+) -> Result<(DifTags, DifTags), DifFailure> {
+    let src_tags = check(src, block, src_tags, |i, data, _| {
+        let protected = &mut dst[i * (block + DIF_SIZE)..(i + 1) * (block + DIF_SIZE)];
+        protected[..block].copy_from_slice(data);
+        protected[block..].copy_from_slice(&dif(data, dest_tags));
+        dest_tags = next(dest_tags);
+    })?;
+    Ok((src_tags, dest_tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16_t10dif() {
+        assert_eq!(crc16_t10dif(b"123456789"), 0xD0DB);
+        assert_eq!(crc16_t10dif(&[]), 0);
+    }
+
+    #[test]
+    fn test_software_dif() {
+        let tags = DifTags {
+            ref_tag: 10,
+            app_tag_mask: 0x00FF,
+            app_tag: 0x1200,
+        };
+        let data: Vec<u8> = (0..3 * 512).map(|i| (i % 251) as u8).collect();
+        let mut protected = vec![0u8; 3 * 520];
+        assert_eq!(insert(&mut protected, &data, 512, tags).ref_tag, 13);
+        assert_eq!(&protected[1556..1560], &12u32.to_be_bytes());
+
+        let mut stripped = vec![0u8; data.len()];
+        strip(&mut stripped, &protected, 512, tags).unwrap();
+        assert_eq!(stripped, data);
+
+        // Masked application tag bits are not checked
+        let masked = DifTags {
+            app_tag: 0x12FF,
+            ..tags
+        };
+        check(&protected, 512, masked, |_, _, _| {}).unwrap();
+
+        protected[520 + 7] ^= 1;
+        assert_eq!(
+            check(&protected, 512, tags, |_, _, _| {}),
+            Err(DifFailure {
+                status: DifStatus::GUARD_MISMATCH,
+                bytes_completed: 520,
+                tags: next(tags),
+            })
+        );
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_sector_formatter() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        for sector in [SectorSize::Bytes512, SectorSize::Bytes4096] {
+            let formatter = SectorFormatter::new(sector)
+                .with_ref_tag(u32::MAX - 2)
+                .with_app_tag(0xBEEF, 0);
+            let sectors = 2 * formatter.chunk_sectors() + 3;
+            let data: Vec<u8> = (0..sectors * sector.data_size())
+                .map(|i| (i % 251) as u8)
+                .collect();
+
+            let mut protected = vec![0u8; formatter.protected_len(data.len()).unwrap()];
+            formatter.insert(&engine, &mut protected, &data).unwrap();
+            formatter.check(&engine, &protected).unwrap();
+            let mut stripped = vec![0u8; data.len()];
+            formatter.strip(&engine, &mut stripped, &protected).unwrap();
+            assert_eq!(stripped, data);
+
+            let to = SectorFormatter::new(sector).with_ref_tag(7);
+            let mut updated = vec![0u8; protected.len()];
+            formatter
+                .update(&engine, &mut updated, &protected, &to)
+                .unwrap();
+            to.check(&engine, &updated).unwrap();
+            let mut expected = vec![0u8; protected.len()];
+            to.insert(&engine, &mut expected, &data).unwrap();
+            assert_eq!(updated, expected);
+
+            // A bad sector in a later chunk is located in the whole buffer
+            let bad = formatter.chunk_sectors() + 1;
+            protected[bad * sector.protected_size()] ^= 0xFF;
+            let err = formatter.check(&engine, &protected).unwrap_err();
+            assert!(matches!(
+                err,
+                DsaError::DifCheckFailed { status, bytes_completed }
+                    if status == DifStatus::GUARD_MISMATCH
+                        && bytes_completed as usize == bad * sector.protected_size()
+            ));
+            assert!(formatter.strip(&engine, &mut stripped, &protected).is_err());
+            assert!(matches!(
+                to.check(&engine, &updated[..100]),
+                Err(DsaError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_sector_formatter_errors() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let formatter = SectorFormatter::new(SectorSize::Bytes512);
+        let mut protected = vec![0u8; 2 * 520];
+        assert!(matches!(
+            formatter.insert(&engine, &mut protected, &[0u8; 1000]),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            formatter.insert(&engine, &mut protected[..1000], &[0u8; 1024]),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 1040,
+                actual: 1000
+            })
+        ));
+        let other = SectorFormatter::new(SectorSize::Bytes4096);
+        let src = protected.clone();
+        assert!(matches!(
+            formatter.update(&engine, &mut protected, &src, &other),
+            Err(DsaError::InvalidArgument(_))
+        ));
+
+        // Wrong reference and application tags
+        formatter
+            .insert(&engine, &mut protected, &[0u8; 1024])
+            .unwrap();
+        let err = formatter
+            .with_ref_tag(1)
+            .with_app_tag(1, 0)
+            .check(&engine, &protected)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            DsaError::DifCheckFailed { status, bytes_completed: 0 }
+                if status == DifStatus::REF_TAG_MISMATCH | DifStatus::APP_TAG_MISMATCH
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Device-to-socket topology.
+//!
+//! Each DSA device is a PCI function attached to one CPU socket. Operations
+//! submitted from CPUs on the same socket avoid a cross-socket hop, so
+//! NUMA-aware servers should pair worker threads with local accelerators.
+//! [`DeviceTopology`] reports the socket, NUMA node and local CPUs of a
+//! device, read from the PCI device's sysfs attributes.
+
+use crate::device::DsaDevice;
+use crate::error::DsaError;
+
+/// Sysfs base path for CPU topology (Linux only).
+#[cfg(dsa_portal)]
+const SYSFS_CPU_PATH: &str = "/sys/devices/system/cpu";
+
+/// Placement of a DSA device relative to the CPUs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceTopology {
+    /// Device name (e.g., "dsa0").
+    pub device: String,
+    /// PCI address of the device (e.g., "0000:6a:01.0"), if known.
+    pub pci_address: Option<String>,
+    /// NUMA node the device is attached to, if the platform reports one.
+    pub numa_node: Option<u32>,
+    /// CPU socket (physical package) the device is attached to, if known.
+    pub socket: Option<u32>,
+    /// CPUs local to the device, in ascending order.
+    pub local_cpus: Vec<usize>,
+}
+
+impl DeviceTopology {
+    /// Returns true if `cpu` is local to the device.
+    pub fn is_local_cpu(&self, cpu: usize) -> bool {
+        self.local_cpus.binary_search(&cpu).is_ok()
+    }
+}
+
+impl DsaDevice {
+    /// Get this device's socket, NUMA node and local CPUs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device's sysfs entry cannot be read, or
+    /// `DsaError::PlatformNotSupported` on platforms other than Linux.
+    #[cfg(dsa_portal)]
+    pub fn topology(&self) -> Result<DeviceTopology, DsaError> {
+        // The device directory lives under its PCI function's directory
+        let device_dir = std::fs::canonicalize(&self.sysfs_path)?;
+        let pci_dir = device_dir.parent().ok_or_else(|| {
+            DsaError::InvalidArgument(format!("{} has no parent PCI device", device_dir.display()))
+        })?;
+        read_topology(&self.name, pci_dir, std::path::Path::new(SYSFS_CPU_PATH))
+    }
+
+    /// Get this device's socket, NUMA node and local CPUs.
+    #[cfg(not(dsa_portal))]
+    pub fn topology(&self) -> Result<DeviceTopology, DsaError> {
+        Err(DsaError::PlatformNotSupported)
+    }
+}
+
+/// Get the topology of every DSA device on the system.
+///
+/// # Errors
+///
+/// Returns an error if device discovery or any topology lookup fails.
+pub fn device_topology() -> Result<Vec<DeviceTopology>, DsaError> {
+    crate::device::discover_devices()?
+        .iter()
+        .map(DsaDevice::topology)
+        .collect()
+}
+
+/// Build a device's topology from its PCI sysfs directory.
+#[cfg(dsa_portal)]
+fn read_topology(
+    device: &str,
+    pci_dir: &std::path::Path,
+    cpu_root: &std::path::Path,
+) -> Result<DeviceTopology, DsaError> {
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+
+    // A NUMA node of -1 means the platform did not report one
+    let numa_node = read(pci_dir.join("numa_node")).and_then(|s| s.parse::<u32>().ok());
+    let local_cpus = match read(pci_dir.join("local_cpulist")) {
+        Some(list) => parse_cpulist(&list)?,
+        None => Vec::new(),
+    };
+    let socket = local_cpus.first().and_then(|cpu| {
+        read(cpu_root.join(format!("cpu{cpu}/topology/physical_package_id")))
+            .and_then(|s| s.parse::<u32>().ok())
+    });
+
+    Ok(DeviceTopology {
+        device: device.to_string(),
+        pci_address: pci_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        numa_node,
+        socket,
+        local_cpus,
+    })
+}
+
+/// Parse a Linux CPU list such as `"0-3,8,10-11"`.
+///
+/// # Returns
+///
+/// The CPUs in ascending order, without duplicates.
+///
+/// # Errors
+///
+/// Returns `DsaError::InvalidArgument` if the list is malformed.
+pub fn parse_cpulist(list: &str) -> Result<Vec<usize>, DsaError> {
+    let invalid = || DsaError::InvalidArgument(format!("invalid CPU list {list:?}"));
+    let parse = |s: &str| s.trim().parse::<usize>().map_err(|_| invalid());
+
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|r| !r.trim().is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse(first)?, parse(last)?);
+                if first > last {
+                    return Err(invalid());
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(parse(range)?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpulist() {
+        assert_eq!(
+            parse_cpulist("0-3,8,10-11\n").unwrap(),
+            vec![0, 1, 2, 3, 8, 10, 11]
+        );
+        assert_eq!(parse_cpulist("5").unwrap(), vec![5]);
+        assert_eq!(parse_cpulist("").unwrap(), Vec::<usize>::new());
+        assert_eq!(parse_cpulist("2,0-2").unwrap(), vec![0, 1, 2]);
+        assert!(parse_cpulist("3-1").is_err());
+        assert!(parse_cpulist("a-b").is_err());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_read_topology() {
+        let root = std::env::temp_dir().join(format!("dsa-topology-{}", std::process::id()));
+        let pci_dir = root.join("pci0000:6a/0000:6a:01.0");
+        let cpu_dir = root.join("cpu");
+        std::fs::create_dir_all(&pci_dir).unwrap();
+        std::fs::create_dir_all(cpu_dir.join("cpu56/topology")).unwrap();
+        std::fs::write(pci_dir.join("numa_node"), "1\n").unwrap();
+        std::fs::write(pci_dir.join("local_cpulist"), "56-59\n").unwrap();
+        std::fs::write(cpu_dir.join("cpu56/topology/physical_package_id"), "1\n").unwrap();
+
+        let topology = read_topology("dsa2", &pci_dir, &cpu_dir).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(topology.device, "dsa2");
+        assert_eq!(topology.pci_address.as_deref(), Some("0000:6a:01.0"));
+        assert_eq!(topology.numa_node, Some(1));
+        assert_eq!(topology.socket, Some(1));
+        assert!(topology.is_local_cpu(57));
+        assert!(!topology.is_local_cpu(0));
+    }
+}
@@ -0,0 +1,376 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Operation trace ring for post-mortem debugging.
+//!
+//! When a service hits a hardware error, the operations just before it are
+//! what explains it, and logs are usually too coarse to show them. With
+//! [`DsaEngine::set_operation_trace`], an engine keeps its last N
+//! descriptors (opcode, size, submission time, latency and completion
+//! status) in a fixed-size in-memory ring. The ring can be read with
+//! [`DsaEngine::operation_trace`] or formatted with
+//! [`DsaEngine::dump_operation_trace`] at any time, and is logged at error
+//! level whenever an operation fails if [`TraceConfig::dump_on_error`] is
+//! set.
+//!
+//! Descriptors are recorded when the work queue submits them. The
+//! completion of blocking operations is recorded when it is observed;
+//! operations completed by the completion reactor (futures, callbacks)
+//! stay [`TraceOutcome::Pending`]. A batch is one entry. Tracing is
+//! available on Linux; elsewhere the trace stays empty.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::trace::TraceConfig;
+//! use dsa_rust::DsaEngine;
+//!
+//! let mut engine = DsaEngine::open_first()?;
+//! engine.set_operation_trace(Some(TraceConfig::default()));
+//!
+//! if let Err(e) = engine.crc32(&[0u8; 4096]) {
+//!     eprintln!("CRC failed: {e}\n{}", engine.dump_operation_trace());
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::descriptor::{DsaCompletionRecord, DsaHwDesc};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::opcode::DecodedOpcode;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default number of operations kept.
+pub const DEFAULT_TRACE_CAPACITY: usize = 256;
+
+/// Completion status of a successful operation.
+const STATUS_SUCCESS: u8 = 0x01;
+
+/// Configuration of an operation trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceConfig {
+    /// Number of most recent operations kept.
+    pub capacity: usize,
+    /// Log the trace at error level when an operation fails.
+    pub dump_on_error: bool,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_TRACE_CAPACITY,
+            dump_on_error: true,
+        }
+    }
+}
+
+/// What became of a traced operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceOutcome {
+    /// Submitted; no completion observed, or not yet.
+    Pending,
+    /// The work queue did not accept the descriptor.
+    Rejected,
+    /// The device wrote the completion record with this status and result.
+    Completed { status: u8, result: u8 },
+    /// The wait was given up (timeout, cancellation, work queue gone)
+    /// before the device completed the operation.
+    Abandoned,
+}
+
+/// One traced operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// Sequence number, counting every operation traced by the engine.
+    pub seq: u64,
+    /// Opcode of the descriptor.
+    pub opcode: DecodedOpcode,
+    /// Transfer size of the descriptor in bytes (descriptors, for a batch).
+    pub xfer_size: u32,
+    /// Wall-clock time of submission.
+    pub submitted: SystemTime,
+    /// Time from submission until the outcome was observed.
+    pub latency: Option<Duration>,
+    /// What became of the operation.
+    pub outcome: TraceOutcome,
+}
+
+impl TraceEntry {
+    /// Returns true if the operation was rejected, failed or abandoned.
+    pub fn is_failure(&self) -> bool {
+        match self.outcome {
+            TraceOutcome::Pending => false,
+            TraceOutcome::Completed { status, .. } => status != STATUS_SUCCESS,
+            TraceOutcome::Rejected | TraceOutcome::Abandoned => true,
+        }
+    }
+}
+
+impl std::fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let since_epoch = self
+            .submitted
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            f,
+            "#{} {}.{:06} {} {} bytes: ",
+            self.seq,
+            since_epoch.as_secs(),
+            since_epoch.subsec_micros(),
+            self.opcode,
+            self.xfer_size
+        )?;
+        match self.outcome {
+            TraceOutcome::Pending => write!(f, "pending")?,
+            TraceOutcome::Rejected => write!(f, "rejected")?,
+            TraceOutcome::Completed { status, result } => {
+                write!(f, "status {status:#04x}, result {result:#04x}")?
+            }
+            TraceOutcome::Abandoned => write!(f, "abandoned")?,
+        }
+        if let Some(latency) = self.latency {
+            write!(f, " after {latency:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A traced operation with what is needed to match its completion.
+struct Slot {
+    entry: TraceEntry,
+    started: Instant,
+    /// Completion record address of the descriptor.
+    record: u64,
+}
+
+struct Ring {
+    next_seq: u64,
+    slots: VecDeque<Slot>,
+}
+
+/// The ring of a work queue's most recent operations.
+pub(crate) struct TraceRing {
+    config: TraceConfig,
+    ring: Mutex<Ring>,
+}
+
+impl TraceRing {
+    pub(crate) fn new(config: TraceConfig) -> Self {
+        Self {
+            config,
+            ring: Mutex::new(Ring {
+                next_seq: 0,
+                slots: VecDeque::with_capacity(config.capacity),
+            }),
+        }
+    }
+
+    /// Record the submission of `desc`, which the work queue accepted or
+    /// not.
+    pub(crate) fn submitted(&self, desc: &DsaHwDesc, accepted: bool) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        let mut ring = self.ring.lock().unwrap();
+        if ring.slots.len() == self.config.capacity {
+            ring.slots.pop_front();
+        }
+        let seq = ring.next_seq;
+        ring.next_seq += 1;
+        ring.slots.push_back(Slot {
+            entry: TraceEntry {
+                seq,
+                opcode: DecodedOpcode::from(desc.opcode()),
+                xfer_size: desc.xfer_size,
+                submitted: SystemTime::now(),
+                latency: (!accepted).then_some(Duration::ZERO),
+                outcome: if accepted {
+                    TraceOutcome::Pending
+                } else {
+                    TraceOutcome::Rejected
+                },
+            },
+            started: Instant::now(),
+            record: desc.completion_addr,
+        });
+        let rejected = !accepted && self.config.dump_on_error;
+        drop(ring);
+        if rejected {
+            self.log();
+        }
+    }
+
+    /// Record the outcome of waiting for `record`.
+    pub(crate) fn completed(&self, record: &DsaCompletionRecord, waited: &Result<(), DsaError>) {
+        let addr = record as *const DsaCompletionRecord as u64;
+        let outcome = if record.is_complete() {
+            TraceOutcome::Completed {
+                status: record.status,
+                result: record.result,
+            }
+        } else {
+            TraceOutcome::Abandoned
+        };
+        {
+            let mut ring = self.ring.lock().unwrap();
+            // Records are reused, so the latest submission is the one
+            let Some(slot) =
+                ring.slots.iter_mut().rev().find(|slot| {
+                    slot.record == addr && slot.entry.outcome == TraceOutcome::Pending
+                })
+            else {
+                return;
+            };
+            slot.entry.outcome = outcome;
+            slot.entry.latency = Some(slot.started.elapsed());
+        }
+        if waited.is_err() && self.config.dump_on_error {
+            self.log();
+        }
+    }
+
+    /// The traced operations, oldest first.
+    pub(crate) fn entries(&self) -> Vec<TraceEntry> {
+        let ring = self.ring.lock().unwrap();
+        ring.slots.iter().map(|slot| slot.entry.clone()).collect()
+    }
+
+    /// The traced operations, one per line, oldest first.
+    pub(crate) fn dump(&self) -> String {
+        let mut out = String::new();
+        for entry in self.entries() {
+            let _ = writeln!(out, "{entry}");
+        }
+        out
+    }
+
+    #[cold]
+    fn log(&self) {
+        log::error!("DSA operation trace, oldest first:\n{}", self.dump());
+    }
+}
+
+impl DsaEngine {
+    /// Enable, reconfigure or (with `None`) disable the operation trace.
+    ///
+    /// Reconfiguring starts an empty trace.
+    pub fn set_operation_trace(&mut self, config: Option<TraceConfig>) {
+        self.work_queue_mut().set_trace(config.map(TraceRing::new));
+    }
+
+    /// Get the current trace configuration, if tracing is enabled.
+    pub fn operation_trace_config(&self) -> Option<TraceConfig> {
+        self.work_queue().trace().map(|trace| trace.config)
+    }
+
+    /// The traced operations, oldest first; empty if tracing is disabled.
+    pub fn operation_trace(&self) -> Vec<TraceEntry> {
+        self.work_queue()
+            .trace()
+            .map(TraceRing::entries)
+            .unwrap_or_default()
+    }
+
+    /// The traced operations formatted one per line, oldest first.
+    pub fn dump_operation_trace(&self) -> String {
+        self.work_queue()
+            .trace()
+            .map(TraceRing::dump)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::DsaOpcode;
+
+    #[test]
+    fn test_ring_keeps_the_latest() {
+        let trace = TraceRing::new(TraceConfig {
+            capacity: 3,
+            dump_on_error: false,
+        });
+        let mut records = vec![DsaCompletionRecord::new(); 5];
+        for record in records.iter_mut() {
+            trace.submitted(&DsaHwDesc::noop(record), true);
+        }
+        let entries = trace.entries();
+        assert_eq!(
+            entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert!(entries
+            .iter()
+            .all(|entry| entry.outcome == TraceOutcome::Pending));
+
+        // Completion of an evicted operation is ignored
+        trace.completed(&records[0], &Ok(()));
+        records[4].status = STATUS_SUCCESS;
+        trace.completed(&records[4], &Ok(()));
+        records[3].status = 0x10;
+        records[3].result = 0x02;
+        trace.completed(&records[3], &Ok(()));
+        trace.completed(&records[2], &Err(DsaError::Cancelled));
+
+        let entries = trace.entries();
+        assert_eq!(entries[0].outcome, TraceOutcome::Abandoned);
+        assert_eq!(
+            entries[1].outcome,
+            TraceOutcome::Completed {
+                status: 0x10,
+                result: 0x02
+            }
+        );
+        assert!(entries[1].is_failure());
+        assert!(!entries[2].is_failure());
+        assert!(entries.iter().all(|entry| entry.latency.is_some()));
+        assert_eq!(entries[2].opcode, DecodedOpcode::Known(DsaOpcode::Noop));
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_trace() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        assert!(engine.operation_trace().is_empty());
+        engine.set_operation_trace(Some(TraceConfig {
+            capacity: 8,
+            dump_on_error: false,
+        }));
+
+        let data = vec![1u8; 4096];
+        engine.crc32(&data).unwrap();
+        emulator.inject(Fault::InvalidFlags);
+        assert!(engine.crc32(&data[..100]).is_err());
+        emulator.inject(Fault::QueueFull { count: 1 });
+        assert!(engine.noop().is_err());
+
+        let entries = engine.operation_trace();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].opcode, DecodedOpcode::Known(DsaOpcode::CrcGen));
+        assert_eq!(entries[0].xfer_size, 4096);
+        assert_eq!(
+            entries[0].outcome,
+            TraceOutcome::Completed {
+                status: STATUS_SUCCESS,
+                result: 0
+            }
+        );
+        assert!(entries[1].is_failure());
+        assert_eq!(entries[2].outcome, TraceOutcome::Rejected);
+        assert_eq!(engine.dump_operation_trace().lines().count(), 3);
+
+        engine.set_operation_trace(None);
+        assert!(engine.operation_trace().is_empty());
+    }
+}
@@ -0,0 +1,621 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! io_uring interop for ingest pipelines.
+//!
+//! A [`UringPipeline`] drives disk reads through an io_uring ring and hands
+//! each filled buffer to the engine for a CRC32 or a copy, without a thread
+//! of its own. Both kinds of completion are awaited in one place: DSA
+//! completions are reported by the completion reactor, which wakes the ring
+//! through an eventfd the ring polls, so [`UringPipeline::wait`] blocks in a
+//! single `io_uring_enter` until either a read or an operation is done.
+//!
+//! The pipeline owns every buffer while the kernel or the device is using
+//! it and returns it with the completion, and holds a reference to every
+//! file with a read in flight.
+//!
+//! Requires the `io-uring` feature; Linux only.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::uring::{PipelineEvent, UringPipeline};
+//! use dsa_rust::DsaEngine;
+//! use dsa_rust::callback::DsaOutput;
+//! use std::fs::File;
+//! use std::sync::Arc;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let file = Arc::new(File::open("/var/lib/data.bin")?);
+//! let mut pipeline = UringPipeline::new(&engine, 64)?;
+//! for chunk in 0..16u64 {
+//!     pipeline.read_crc32(&file, chunk << 20, vec![0; 1 << 20])?;
+//! }
+//! while let Some(event) = pipeline.wait()? {
+//!     match event {
+//!         PipelineEvent::Dsa { id, result: Ok(DsaOutput::Crc32(crc)), .. } => {
+//!             println!("chunk {id}: {crc:#010x}")
+//!         }
+//!         PipelineEvent::Dsa { id, result, .. } => eprintln!("chunk {id}: {result:?}"),
+//!         PipelineEvent::Read { id, result, .. } => eprintln!("chunk {id}: {result:?}"),
+//!     }
+//! }
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::callback::{DsaOp, DsaOutput};
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use io_uring::{opcode, types, IoUring};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+use std::sync::{Arc, Mutex};
+
+/// User data of the poll request on the wakeup eventfd; read identifiers
+/// count up from zero and never reach it.
+const WAKEUP: u64 = u64::MAX;
+
+/// A completion yielded by [`UringPipeline::wait`].
+#[derive(Debug)]
+pub enum PipelineEvent {
+    /// A read submitted with [`UringPipeline::read`] completed, or a
+    /// chained read failed before its operation was submitted.
+    Read {
+        /// Identifier returned when the read was submitted.
+        id: u64,
+        /// The buffer, returning ownership; only the first bytes read are
+        /// valid.
+        buf: Vec<u8>,
+        /// Number of bytes read; less than the buffer length at end of file.
+        result: Result<usize, DsaError>,
+    },
+    /// An operation completed: one submitted with [`UringPipeline::submit`],
+    /// or the operation a chained read handed its buffer to.
+    Dsa {
+        /// Identifier returned when the operation or read was submitted.
+        id: u64,
+        /// The operation, returning ownership of its buffers.
+        op: DsaOp,
+        /// Result of the operation.
+        result: Result<DsaOutput, DsaError>,
+    },
+}
+
+/// What to do with a buffer once its read completes.
+enum Then {
+    /// Yield the buffer.
+    Yield,
+    /// Compute the CRC32 of the bytes read.
+    Crc32,
+    /// Copy the bytes read into the start of the destination.
+    Copy(Vec<u8>),
+}
+
+/// A read in flight; the kernel writes into `buf` until it completes.
+struct PendingRead {
+    buf: Vec<u8>,
+    then: Then,
+    /// The file read from, kept open until the read completes, since the
+    /// ring only holds its raw descriptor.
+    _file: Arc<dyn AsFd + Send + Sync>,
+}
+
+/// State shared with the completion callbacks of the operations.
+struct Shared {
+    completed: Mutex<VecDeque<PipelineEvent>>,
+    /// Nonblocking eventfd the ring polls for operation completions.
+    wakeup: OwnedFd,
+}
+
+impl Shared {
+    /// Queue a completion and wake the ring.
+    fn push(&self, event: PipelineEvent) {
+        self.completed.lock().unwrap().push_back(event);
+        let one = 1u64.to_ne_bytes();
+        // SAFETY: writes 8 bytes from a valid buffer to an owned eventfd;
+        // it only fails if the counter would overflow, which still wakes
+        unsafe { libc::write(self.wakeup.as_raw_fd(), one.as_ptr().cast(), one.len()) };
+    }
+
+    /// Reset the eventfd counter after the ring observed it.
+    fn drain_wakeup(&self) {
+        let mut count = [0u8; 8];
+        // SAFETY: reads 8 bytes into a valid buffer; the eventfd is
+        // nonblocking, so this returns EAGAIN if the counter is zero
+        unsafe {
+            libc::read(
+                self.wakeup.as_raw_fd(),
+                count.as_mut_ptr().cast(),
+                count.len(),
+            )
+        };
+    }
+}
+
+/// Reads through io_uring and DSA operations on the read buffers, awaited
+/// together on the calling thread.
+///
+/// Reads are queued in the ring's submission queue and submitted by the
+/// next [`UringPipeline::wait`], so a burst of reads costs one system call.
+/// Files are shared with the pipeline until their reads complete, so the
+/// caller may drop its reference at any time.
+///
+/// Dropping the pipeline waits for its reads in flight, because the kernel
+/// writes into their buffers. Operations in flight own their buffers and
+/// complete in the background.
+pub struct UringPipeline<'e> {
+    engine: &'e DsaEngine,
+    ring: IoUring,
+    shared: Arc<Shared>,
+    reads: HashMap<u64, PendingRead>,
+    /// Completions found in the ring, not yet yielded.
+    ready: VecDeque<PipelineEvent>,
+    /// True while a poll request on the eventfd is in the ring.
+    wakeup_armed: bool,
+    next_id: u64,
+    /// Submitted reads and operations not yet yielded.
+    outstanding: usize,
+}
+
+impl<'e> UringPipeline<'e> {
+    /// Create a pipeline submitting to `engine`, with a ring of `entries`
+    /// submission queue entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the ring or the eventfd cannot be created,
+    /// for example when io_uring is disabled by `kernel.io_uring_disabled`
+    /// or a seccomp filter.
+    pub fn new(engine: &'e DsaEngine, entries: u32) -> Result<Self, DsaError> {
+        let ring = IoUring::new(entries)?;
+        // SAFETY: eventfd takes no pointers
+        let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(Self {
+            engine,
+            ring,
+            shared: Arc::new(Shared {
+                completed: Mutex::new(VecDeque::new()),
+                // SAFETY: `fd` is a new descriptor owned by nobody else
+                wakeup: unsafe { OwnedFd::from_raw_fd(fd) },
+            }),
+            reads: HashMap::new(),
+            ready: VecDeque::new(),
+            wakeup_armed: false,
+            next_id: 0,
+            outstanding: 0,
+        })
+    }
+
+    /// Read from `file` at `offset` into `buf`, and yield the buffer.
+    ///
+    /// Reads are at most `u32::MAX` bytes; a longer buffer is read short.
+    ///
+    /// # Returns
+    ///
+    /// The identifier the read's [`PipelineEvent::Read`] will carry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the submission queue is full and cannot be
+    /// flushed to the kernel.
+    pub fn read(
+        &mut self,
+        file: &Arc<impl AsFd + Send + Sync + 'static>,
+        offset: u64,
+        buf: Vec<u8>,
+    ) -> Result<u64, DsaError> {
+        self.push_read(file, offset, buf, Then::Yield)
+    }
+
+    /// Read from `file` at `offset` into `buf`, then compute the CRC32 of
+    /// the bytes read on the device.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the [`PipelineEvent::Dsa`] carrying a
+    /// [`DsaOp::Crc32`] with the bytes read, or of a
+    /// [`PipelineEvent::Read`] if the read fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the submission queue is full and cannot be
+    /// flushed to the kernel.
+    pub fn read_crc32(
+        &mut self,
+        file: &Arc<impl AsFd + Send + Sync + 'static>,
+        offset: u64,
+        buf: Vec<u8>,
+    ) -> Result<u64, DsaError> {
+        self.push_read(file, offset, buf, Then::Crc32)
+    }
+
+    /// Read from `file` at `offset` into `buf`, then copy the bytes read
+    /// into the start of `dst` on the device.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the [`PipelineEvent::Dsa`] carrying a
+    /// [`DsaOp::Memcpy`] with `dst` and the read buffer, or of a
+    /// [`PipelineEvent::Read`] if the read fails. The copy fails with
+    /// `DsaError::BufferSizeMismatch` if `dst` is shorter than the bytes
+    /// read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the submission queue is full and cannot be
+    /// flushed to the kernel.
+    pub fn read_copy(
+        &mut self,
+        file: &Arc<impl AsFd + Send + Sync + 'static>,
+        offset: u64,
+        buf: Vec<u8>,
+        dst: Vec<u8>,
+    ) -> Result<u64, DsaError> {
+        self.push_read(file, offset, buf, Then::Copy(dst))
+    }
+
+    /// Submit an operation to the engine.
+    ///
+    /// # Returns
+    ///
+    /// The identifier the operation's [`PipelineEvent::Dsa`] will carry.
+    pub fn submit(&mut self, op: DsaOp) -> u64 {
+        let id = self.next_id();
+        self.outstanding += 1;
+        self.start(id, op);
+        id
+    }
+
+    /// Number of submitted reads and operations that have not been yielded
+    /// yet.
+    pub fn outstanding(&self) -> usize {
+        self.outstanding
+    }
+
+    /// Returns true if every submitted read and operation has been yielded.
+    pub fn is_empty(&self) -> bool {
+        self.outstanding == 0
+    }
+
+    /// Wait for the next completion, in completion order.
+    ///
+    /// Queued reads are submitted first. A chained read that completes
+    /// hands its buffer to the engine and is not yielded itself.
+    ///
+    /// # Returns
+    ///
+    /// The next completion, or `None` once everything submitted has been
+    /// yielded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if submitting to or waiting on the ring fails.
+    pub fn wait(&mut self) -> Result<Option<PipelineEvent>, DsaError> {
+        loop {
+            if self.outstanding == 0 {
+                return Ok(None);
+            }
+            let event = self
+                .ready
+                .pop_front()
+                .or_else(|| self.shared.completed.lock().unwrap().pop_front());
+            if let Some(event) = event {
+                self.outstanding -= 1;
+                return Ok(Some(event));
+            }
+
+            if self.reap() {
+                continue;
+            }
+            // An operation that completes after the queue was checked leaves
+            // the eventfd readable, which completes the poll at once
+            if !self.wakeup_armed {
+                let poll = opcode::PollAdd::new(
+                    types::Fd(self.shared.wakeup.as_raw_fd()),
+                    libc::POLLIN as u32,
+                )
+                .build()
+                .user_data(WAKEUP);
+                self.push(&poll)?;
+                self.wakeup_armed = true;
+            }
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Queue a read request; its buffer and file are kept until it
+    /// completes.
+    fn push_read(
+        &mut self,
+        file: &Arc<impl AsFd + Send + Sync + 'static>,
+        offset: u64,
+        mut buf: Vec<u8>,
+        then: Then,
+    ) -> Result<u64, DsaError> {
+        let id = self.next_id;
+        let len = u32::try_from(buf.len()).unwrap_or(u32::MAX);
+        let read = opcode::Read::new(types::Fd(file.as_fd().as_raw_fd()), buf.as_mut_ptr(), len)
+            .offset(offset)
+            .build()
+            .user_data(id);
+        self.push(&read)?;
+
+        self.next_id();
+        self.outstanding += 1;
+        self.reads.insert(
+            id,
+            PendingRead {
+                buf,
+                then,
+                _file: Arc::clone(file) as Arc<dyn AsFd + Send + Sync>,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Push a request, flushing the submission queue to the kernel if it is
+    /// full.
+    fn push(&mut self, entry: &io_uring::squeue::Entry) -> Result<(), DsaError> {
+        loop {
+            // SAFETY: read buffers and files are owned by `self.reads` until
+            // their completion is reaped, or until `drop` has waited for it
+            if unsafe { self.ring.submission().push(entry) }.is_ok() {
+                return Ok(());
+            }
+            match self.ring.submit() {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Handle the completions in the ring.
+    ///
+    /// Returns true if an event became ready to yield.
+    fn reap(&mut self) -> bool {
+        let completions: Vec<(u64, i32)> = self
+            .ring
+            .completion()
+            .map(|cqe| (cqe.user_data(), cqe.result()))
+            .collect();
+
+        let ready = self.ready.len();
+        for (id, res) in completions {
+            if id == WAKEUP {
+                self.wakeup_armed = false;
+                self.shared.drain_wakeup();
+                continue;
+            }
+            let Some(PendingRead { mut buf, then, .. }) = self.reads.remove(&id) else {
+                continue;
+            };
+            if res < 0 {
+                let err = io::Error::from_raw_os_error(-res).into();
+                self.ready.push_back(PipelineEvent::Read {
+                    id,
+                    buf,
+                    result: Err(err),
+                });
+                continue;
+            }
+
+            let read = res as usize;
+            match then {
+                Then::Yield => self.ready.push_back(PipelineEvent::Read {
+                    id,
+                    buf,
+                    result: Ok(read),
+                }),
+                Then::Crc32 => {
+                    buf.truncate(read);
+                    self.start(id, DsaOp::Crc32 { data: buf, seed: 0 });
+                }
+                Then::Copy(dst) => {
+                    buf.truncate(read);
+                    self.start(id, DsaOp::Memcpy { dst, src: buf });
+                }
+            }
+        }
+        self.ready.len() > ready
+    }
+
+    /// Submit an operation whose completion is queued as event `id`.
+    fn start(&self, id: u64, op: DsaOp) {
+        let shared = Arc::clone(&self.shared);
+        self.engine.submit_with_callback(op, move |op, result| {
+            shared.push(PipelineEvent::Dsa { id, op, result });
+        });
+    }
+}
+
+impl Drop for UringPipeline<'_> {
+    fn drop(&mut self) {
+        // The kernel may still write into the buffers of reads in flight
+        while !self.reads.is_empty() {
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    // Without the ring the buffers can never be released
+                    log::error!("failed to wait for io_uring reads: {e}");
+                    std::mem::forget(std::mem::take(&mut self.reads));
+                    return;
+                }
+            }
+            for cqe in self.ring.completion() {
+                self.reads.remove(&cqe.user_data());
+            }
+        }
+    }
+}
+
+// The tests run operations on the emulator
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use crate::emulator::Emulator;
+    use std::io::Write;
+
+    /// A temporary file holding `data`.
+    fn temp_file(name: &str, data: &[u8]) -> Arc<std::fs::File> {
+        let path = std::env::temp_dir().join(format!("dsa-uring-{}-{name}", std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(data)
+            .unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        Arc::new(file)
+    }
+
+    /// Collect every event, keyed by identifier.
+    fn drain(pipeline: &mut UringPipeline<'_>) -> HashMap<u64, PipelineEvent> {
+        let mut events = HashMap::new();
+        while let Some(event) = pipeline.wait().unwrap() {
+            let (PipelineEvent::Read { id, .. } | PipelineEvent::Dsa { id, .. }) = event;
+            assert!(events.insert(id, event).is_none());
+        }
+        events
+    }
+
+    #[test]
+    fn test_read_then_crc32_and_copy() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        // io_uring may be disabled in the test environment
+        let Ok(mut pipeline) = UringPipeline::new(&engine, 4) else {
+            return;
+        };
+        let data: Vec<u8> = (0..40_000).map(|i| (i % 253) as u8).collect();
+        let file = temp_file("chain", &data);
+
+        // More requests than ring entries
+        let chunk = 4096;
+        let mut crcs = Vec::new();
+        for offset in (0..data.len()).step_by(chunk) {
+            crcs.push(
+                pipeline
+                    .read_crc32(&file, offset as u64, vec![0; chunk])
+                    .unwrap(),
+            );
+        }
+        let copy = pipeline
+            .read_copy(&file, 100, vec![0; 1000], vec![0xFF; 1024])
+            .unwrap();
+        let read = pipeline.read(&file, 39_990, vec![0; 64]).unwrap();
+        let short = pipeline
+            .read_copy(&file, 0, vec![0; 64], vec![0; 8])
+            .unwrap();
+        let noop = pipeline.submit(DsaOp::Noop);
+        assert_eq!(pipeline.outstanding(), crcs.len() + 4);
+        // The pipeline keeps the file open for the reads still queued
+        drop(file);
+
+        let mut events = drain(&mut pipeline);
+        assert!(pipeline.is_empty());
+        for (i, id) in crcs.iter().enumerate() {
+            let Some(PipelineEvent::Dsa { op, result, .. }) = events.remove(id) else {
+                panic!("no CRC32 for chunk {i}");
+            };
+            let expected = &data[i * chunk..((i + 1) * chunk).min(data.len())];
+            assert_eq!(
+                op,
+                DsaOp::Crc32 {
+                    data: expected.to_vec(),
+                    seed: 0
+                }
+            );
+            assert_eq!(
+                result.unwrap(),
+                DsaOutput::Crc32(crate::crc::software_crc32(expected, 0))
+            );
+        }
+        match events.remove(&copy) {
+            Some(PipelineEvent::Dsa {
+                op: DsaOp::Memcpy { dst, .. },
+                result: Ok(DsaOutput::Memcpy),
+                ..
+            }) => {
+                assert_eq!(&dst[..1000], &data[100..1100]);
+                assert!(dst[1000..].iter().all(|&b| b == 0xFF));
+            }
+            other => panic!("unexpected copy event {other:?}"),
+        }
+        match events.remove(&read) {
+            Some(PipelineEvent::Read {
+                buf,
+                result: Ok(10),
+                ..
+            }) => assert_eq!(&buf[..10], &data[39_990..]),
+            other => panic!("unexpected read event {other:?}"),
+        }
+        assert!(matches!(
+            events.remove(&short),
+            Some(PipelineEvent::Dsa {
+                result: Err(DsaError::BufferSizeMismatch { .. }),
+                ..
+            })
+        ));
+        assert!(matches!(
+            events.remove(&noop),
+            Some(PipelineEvent::Dsa {
+                result: Ok(DsaOutput::Noop),
+                ..
+            })
+        ));
+        assert!(pipeline.wait().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_failed_read() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let Ok(mut pipeline) = UringPipeline::new(&engine, 4) else {
+            return;
+        };
+        // Reading a directory fails with EISDIR
+        let dir = Arc::new(std::fs::File::open(std::env::temp_dir()).unwrap());
+        let id = pipeline.read_crc32(&dir, 0, vec![0; 16]).unwrap();
+        match pipeline.wait().unwrap() {
+            Some(PipelineEvent::Read {
+                id: read,
+                buf,
+                result: Err(DsaError::Io(e)),
+            }) => {
+                assert_eq!(read, id);
+                assert_eq!(buf.len(), 16);
+                assert_eq!(e.raw_os_error(), Some(libc::EISDIR));
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+        assert!(pipeline.wait().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_drop_waits_for_reads() {
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let Ok(mut pipeline) = UringPipeline::new(&engine, 4) else {
+            return;
+        };
+        let file = temp_file("drop", &[7; 8192]);
+        for _ in 0..8 {
+            pipeline.read(&file, 0, vec![0; 8192]).unwrap();
+        }
+        pipeline.submit(DsaOp::Noop);
+        drop(pipeline);
+    }
+}
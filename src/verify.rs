@@ -0,0 +1,136 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Differential verification of hardware results.
+//!
+//! With the `verify` feature, every successfully completed descriptor is
+//! checked against a software computation of the same operation. A mismatch
+//! is logged with the full descriptor and completion record; with
+//! `verify-panic` the process also panics, so a silent-corruption report can
+//! be bisected by flipping one feature. This is a debugging aid: it reads
+//! every buffer a second time on the CPU.
+
+use crate::descriptor::{DsaCompletionRecord, DsaHwDesc};
+use crate::opcode::DsaOpcode;
+
+/// Verify the result of a completed descriptor.
+///
+/// Descriptors that did not complete successfully are not checked; their
+/// errors are reported through the normal error path.
+///
+/// # Safety
+///
+/// The descriptor's buffers and completion record (and, for a batch, the
+/// descriptor list and all of its buffers and records) must still be valid.
+pub(crate) unsafe fn check(desc: &DsaHwDesc) {
+    let record = &*(desc.completion_addr as *const DsaCompletionRecord);
+    if desc.completion_addr == 0 || !record.get_status().is_success() {
+        return;
+    }
+
+    if let Some(problem) = mismatch(desc, record) {
+        log::error!(
+            "DSA result differs from software: {}\ndescriptor: {:#x?}\ncompletion: {:#x?}",
+            problem,
+            desc,
+            record
+        );
+        #[cfg(feature = "verify-panic")]
+        panic!("DSA result differs from software: {problem}");
+    }
+}
+
+/// Describe how the hardware result differs from software, if it does.
+unsafe fn mismatch(desc: &DsaHwDesc, record: &DsaCompletionRecord) -> Option<String> {
+    let op = desc.opcode();
+    let len = desc.xfer_size as usize;
+    let src = || std::slice::from_raw_parts(desc.src_addr as *const u8, len);
+
+    if op == DsaOpcode::Batch as u8 {
+        let descs = std::slice::from_raw_parts(desc.src_addr as *const DsaHwDesc, len);
+        for sub in descs {
+            check(sub);
+        }
+        None
+    } else if op == DsaOpcode::CrcGen as u8 {
+        let expected = crate::crc::software_crc32(src(), desc.as_crc().crc_seed);
+        (record.crc32_result() != expected).then(|| {
+            format!(
+                "CRC {:#010x}, software {:#010x}",
+                record.crc32_result(),
+                expected
+            )
+        })
+    } else if op == DsaOpcode::MemMove as u8 {
+        // Overlapping copies overwrite the source, so they cannot be rechecked
+        let (s, d) = (desc.src_addr, desc.dst_addr);
+        if s < d + len as u64 && d < s + len as u64 {
+            return None;
+        }
+        let dst = std::slice::from_raw_parts(d as *const u8, len);
+        first_difference(src(), dst).map(|offset| format!("copy differs at offset {offset}"))
+    } else if op == DsaOpcode::MemFill as u8 {
+        let dst = std::slice::from_raw_parts(desc.dst_addr as *const u8, len);
+        find_pattern(dst, desc.src_addr).map(|offset| format!("fill differs at offset {offset}"))
+    } else if op == DsaOpcode::Compare as u8 {
+        let other = std::slice::from_raw_parts(desc.dst_addr as *const u8, len);
+        compare_mismatch(record, first_difference(src(), other))
+    } else if op == DsaOpcode::CompareImm as u8 {
+        compare_mismatch(record, find_pattern(src(), desc.dst_addr))
+    } else {
+        None
+    }
+}
+
+fn first_difference(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter().zip(b).position(|(x, y)| x != y)
+}
+
+fn find_pattern(buf: &[u8], pattern: u64) -> Option<usize> {
+    let pattern = pattern.to_le_bytes();
+    buf.iter()
+        .enumerate()
+        .position(|(i, &byte)| byte != pattern[i % 8])
+}
+
+fn compare_mismatch(record: &DsaCompletionRecord, expected: Option<usize>) -> Option<String> {
+    let actual = record.mismatch_offset();
+    (actual != expected).then(|| format!("compare reported {actual:?}, software {expected:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_wrong_crc() {
+        let data = b"differential verification";
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::crc_gen(data.as_ptr(), data.len(), 0, &mut record);
+
+        record.status = 0x01;
+        record.result_value = crate::crc::software_crc32(data, 0) as u64;
+        assert_eq!(unsafe { mismatch(&desc, &record) }, None);
+
+        record.result_value ^= 1;
+        assert!(unsafe { mismatch(&desc, &record) }.is_some());
+    }
+
+    #[test]
+    fn test_detects_wrong_compare() {
+        let a = [1u8; 16];
+        let mut b = a;
+        b[3] = 2;
+        let mut record = DsaCompletionRecord::new();
+        let desc = DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), 16, &mut record);
+
+        record.status = 0x01;
+        record.result = 1;
+        record.bytes_completed = 3;
+        assert_eq!(unsafe { mismatch(&desc, &record) }, None);
+
+        record.bytes_completed = 4;
+        assert!(unsafe { mismatch(&desc, &record) }.is_some());
+    }
+}
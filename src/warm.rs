@@ -0,0 +1,387 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Translation warming.
+//!
+//! A freshly allocated buffer has no pages behind it until it is first
+//! touched. The CPU faults them in transparently, but a DSA descriptor that
+//! reaches such a page stops with a page fault partial completion. Services
+//! that copy into new buffers, or checksum newly read ones, see this on
+//! almost every large operation.
+//!
+//! With [`DsaEngine::set_translation_warming`], the engine remembers which
+//! pages its operations have already covered and warms the new ones before
+//! submitting: source pages are read, or have their device translations
+//! prefetched with a TranslFetch descriptor, and destination pages are
+//! written. Pages the engine has seen before are not touched again, so
+//! steady-state traffic on reused buffers pays only a lookup.
+//!
+//! The engine cannot see memory being unmapped. Call
+//! [`DsaEngine::forget_translations`] before freeing a buffer whose memory
+//! may be returned to the OS; a page fault on a page believed warm also
+//! removes it, so at worst the next operation warms it again.
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use core::ops::Range;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Granularity at which warm memory is tracked.
+const PAGE_SIZE: usize = 4096;
+
+/// How new source pages are warmed.
+///
+/// Destination pages are always written in software: a read translation
+/// does not make a zero page or copy-on-write page writable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarmMethod {
+    /// Read one byte of every new page.
+    #[default]
+    Touch,
+    /// Submit a TranslFetch descriptor for the new pages, touching them in
+    /// software instead if it fails, e.g. on pages not yet faulted in.
+    TranslFetch,
+}
+
+/// Which operations are warmed, and how.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use dsa_rust::{DsaEngine, WarmMethod, WarmPolicy};
+///
+/// let mut engine = DsaEngine::open_first()?;
+/// engine.set_translation_warming(Some(WarmPolicy {
+///     method: WarmMethod::TranslFetch,
+///     ..WarmPolicy::default()
+/// }));
+/// # Ok::<(), dsa_rust::DsaError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarmPolicy {
+    /// Buffers smaller than this many bytes are not warmed.
+    pub threshold: usize,
+    /// How new source pages are warmed.
+    pub method: WarmMethod,
+    /// Number of disjoint warm ranges remembered; once exceeded, everything
+    /// is forgotten and warmed again on next use.
+    pub max_ranges: usize,
+}
+
+impl Default for WarmPolicy {
+    /// Warm buffers of 64 KiB and more by touching, remembering up to 4096
+    /// ranges.
+    fn default() -> Self {
+        Self {
+            threshold: 64 << 10,
+            method: WarmMethod::Touch,
+            max_ranges: 4096,
+        }
+    }
+}
+
+/// The pages an engine's operations have already covered.
+#[derive(Debug)]
+pub(crate) struct WarmCache {
+    policy: WarmPolicy,
+    /// Disjoint, non-adjacent page-aligned ranges, keyed by start.
+    ranges: Mutex<BTreeMap<usize, usize>>,
+}
+
+impl WarmCache {
+    pub(crate) fn new(policy: WarmPolicy) -> Self {
+        Self {
+            policy,
+            ranges: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> WarmPolicy {
+        self.policy
+    }
+
+    /// Record `[addr, addr + len)` as warm and return the parts of it that
+    /// were not, clipped to the range.
+    ///
+    /// Ranges below the threshold are neither recorded nor returned.
+    pub(crate) fn claim(&self, addr: usize, len: usize) -> Vec<Range<usize>> {
+        if len == 0 || len < self.policy.threshold {
+            return Vec::new();
+        }
+        let end = addr.saturating_add(len);
+        let (first, last) = pages(addr, end);
+
+        let mut ranges = self.ranges.lock().unwrap();
+        let touching: Vec<(usize, usize)> = ranges
+            .range(..=last)
+            .rev()
+            .take_while(|(_, &range_end)| range_end >= first)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+
+        let mut cold = Vec::new();
+        let mut cursor = first;
+        for &(start, end) in touching.iter().rev() {
+            if start > cursor {
+                cold.push(cursor..start);
+            }
+            cursor = cursor.max(end);
+        }
+        if cursor < last {
+            cold.push(cursor..last);
+        }
+
+        for (start, _) in &touching {
+            ranges.remove(start);
+        }
+        let merged_start = touching
+            .last()
+            .map_or(first, |&(start, _)| start.min(first));
+        let merged_end = touching.first().map_or(last, |&(_, end)| end.max(last));
+        if ranges.len() >= self.policy.max_ranges {
+            ranges.clear();
+        }
+        ranges.insert(merged_start, merged_end);
+
+        cold.into_iter()
+            .map(|range| range.start.max(addr)..range.end.min(end))
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+
+    /// Forget the pages overlapping `[addr, addr + len)`.
+    pub(crate) fn forget(&self, addr: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let (first, last) = pages(addr, addr.saturating_add(len));
+
+        let mut ranges = self.ranges.lock().unwrap();
+        let overlapping: Vec<(usize, usize)> = ranges
+            .range(..last)
+            .rev()
+            .take_while(|(_, &range_end)| range_end > first)
+            .map(|(&start, &end)| (start, end))
+            .collect();
+        for (start, end) in overlapping {
+            ranges.remove(&start);
+            if start < first {
+                ranges.insert(start, first);
+            }
+            if end > last {
+                ranges.insert(last, end);
+            }
+        }
+    }
+
+    /// Forget everything.
+    pub(crate) fn clear(&self) {
+        self.ranges.lock().unwrap().clear();
+    }
+}
+
+/// The page-aligned range covering `[start, end)`.
+fn pages(start: usize, end: usize) -> (usize, usize) {
+    let first = start & !(PAGE_SIZE - 1);
+    let last = end
+        .checked_next_multiple_of(PAGE_SIZE)
+        .unwrap_or(usize::MAX & !(PAGE_SIZE - 1));
+    (first, last)
+}
+
+/// Read, or read and write back, one byte of every page of `range`.
+///
+/// # Safety
+///
+/// `range` must lie within a live allocation, which must be writable and
+/// not concurrently accessed if `write` is set.
+pub(crate) unsafe fn touch(range: Range<usize>, write: bool) {
+    let mut addr = range.start;
+    while addr < range.end {
+        let byte = addr as *mut u8;
+        let value = std::ptr::read_volatile(byte);
+        if write {
+            std::ptr::write_volatile(byte, value);
+        }
+        addr = (addr & !(PAGE_SIZE - 1)) + PAGE_SIZE;
+    }
+}
+
+impl DsaEngine {
+    /// Warm the pages of large buffers before operations reach them.
+    ///
+    /// Pass `None` to stop warming and forget which pages are warm. See
+    /// the [`warm`](crate::warm) module for details.
+    pub fn set_translation_warming(&mut self, policy: Option<WarmPolicy>) {
+        self.warm_cache = policy.map(WarmCache::new);
+    }
+
+    /// Get the current warming policy, if warming is enabled.
+    pub fn translation_warming(&self) -> Option<WarmPolicy> {
+        self.warm_cache.as_ref().map(WarmCache::policy)
+    }
+
+    /// Forget that the pages of `buf` are warm.
+    ///
+    /// Call before freeing a buffer whose memory may be unmapped, so a
+    /// later allocation at the same address is warmed again.
+    pub fn forget_translations(&self, buf: &[u8]) {
+        if let Some(cache) = &self.warm_cache {
+            cache.forget(buf.as_ptr() as usize, buf.len());
+        }
+    }
+
+    /// Forget that any pages are warm.
+    pub fn clear_translations(&self) {
+        if let Some(cache) = &self.warm_cache {
+            cache.clear();
+        }
+    }
+
+    /// Warm the new pages of a buffer an operation will read.
+    pub(crate) fn warm(&self, buf: &[u8]) {
+        let Some(cache) = &self.warm_cache else {
+            return;
+        };
+        for range in cache.claim(buf.as_ptr() as usize, buf.len()) {
+            if cache.policy().method == WarmMethod::TranslFetch {
+                // SAFETY: `claim` clips ranges to `buf`
+                let cold =
+                    unsafe { std::slice::from_raw_parts(range.start as *const u8, range.len()) };
+                if self.work_queue().transl_fetch(cold).is_ok() {
+                    continue;
+                }
+            }
+            // SAFETY: `claim` clips ranges to `buf`
+            unsafe { touch(range, false) };
+        }
+    }
+
+    /// Warm the new pages of a buffer an operation will write.
+    pub(crate) fn warm_mut(&self, buf: &mut [u8]) {
+        let Some(cache) = &self.warm_cache else {
+            return;
+        };
+        for range in cache.claim(buf.as_mut_ptr() as usize, buf.len()) {
+            // SAFETY: `claim` clips ranges to `buf`, which is borrowed mutably
+            unsafe { touch(range, true) };
+        }
+    }
+
+    /// Forget the faulting page of a page fault, so it is warmed again.
+    pub(crate) fn note_fault<T>(&self, result: Result<T, DsaError>) -> Result<T, DsaError> {
+        if let (Some(cache), Err(DsaError::PageFault { fault_addr, .. })) =
+            (&self.warm_cache, &result)
+        {
+            cache.forget(*fault_addr as usize, 1);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> WarmCache {
+        WarmCache::new(WarmPolicy {
+            threshold: 1,
+            ..WarmPolicy::default()
+        })
+    }
+
+    #[test]
+    fn test_claim_returns_cold_parts_once() {
+        let cache = cache();
+        let base = 0x10_0000;
+        assert_eq!(cache.claim(base + 100, 8000), vec![base + 100..base + 8100]);
+        assert!(cache.claim(base, 2 * PAGE_SIZE).is_empty());
+
+        // Only the pages beyond the warm range are cold
+        assert_eq!(
+            cache.claim(base, 4 * PAGE_SIZE),
+            vec![base + 2 * PAGE_SIZE..base + 4 * PAGE_SIZE]
+        );
+        assert_eq!(cache.ranges.lock().unwrap().len(), 1);
+
+        // A gap between two warm ranges is returned on its own
+        cache.claim(base + 6 * PAGE_SIZE, PAGE_SIZE);
+        assert_eq!(
+            cache.claim(base, 8 * PAGE_SIZE),
+            vec![
+                base + 4 * PAGE_SIZE..base + 6 * PAGE_SIZE,
+                base + 7 * PAGE_SIZE..base + 8 * PAGE_SIZE
+            ]
+        );
+        assert_eq!(cache.ranges.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_forget_splits_ranges() {
+        let cache = cache();
+        let base = 0x10_0000;
+        cache.claim(base, 4 * PAGE_SIZE);
+        cache.forget(base + PAGE_SIZE + 10, 1);
+        assert_eq!(
+            cache.claim(base, 4 * PAGE_SIZE),
+            vec![base + PAGE_SIZE..base + 2 * PAGE_SIZE]
+        );
+        cache.clear();
+        assert_eq!(cache.claim(base, 10), vec![base..base + 10]);
+    }
+
+    #[test]
+    fn test_threshold_and_capacity() {
+        let cache = WarmCache::new(WarmPolicy {
+            threshold: PAGE_SIZE,
+            method: WarmMethod::Touch,
+            max_ranges: 2,
+        });
+        assert!(cache.claim(0x10_0000, PAGE_SIZE - 1).is_empty());
+        for i in 0..3 {
+            cache.claim(0x10_0000 + 2 * i * PAGE_SIZE, PAGE_SIZE);
+        }
+        // The third range evicted the first two
+        assert_eq!(cache.ranges.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_engine_warms_and_refaults() {
+        use crate::emulator::{Emulator, Fault};
+        use std::sync::Arc;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut engine = DsaEngine::emulated(Arc::clone(&emulator)).unwrap();
+        let policy = WarmPolicy {
+            method: WarmMethod::TranslFetch,
+            ..WarmPolicy::default()
+        };
+        engine.set_translation_warming(Some(policy));
+        assert_eq!(engine.translation_warming(), Some(policy));
+
+        let src = vec![7u8; 256 << 10];
+        let mut dst = vec![0u8; src.len()];
+        engine.memcpy(&mut dst, &src).unwrap();
+        assert_eq!(dst, src);
+        let cache = engine.warm_cache.as_ref().unwrap();
+        assert!(cache.claim(src.as_ptr() as usize, src.len()).is_empty());
+
+        emulator.inject(Fault::PageFault { offset: 5000 });
+        assert!(engine.crc32(&src).is_err());
+        let fault_page = (src.as_ptr() as usize + 5000) & !(PAGE_SIZE - 1);
+        assert_eq!(
+            cache.claim(src.as_ptr() as usize, src.len()),
+            vec![fault_page..fault_page + PAGE_SIZE]
+        );
+
+        engine.forget_translations(&src);
+        assert!(!cache.claim(src.as_ptr() as usize, src.len()).is_empty());
+        engine.set_translation_warming(None);
+        assert_eq!(engine.translation_warming(), None);
+    }
+}
@@ -0,0 +1,368 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Background watcher for device errors and state changes.
+//!
+//! An engine notices that its work queue was disabled or its device reset
+//! only when an operation fails. A long-running service wants to know
+//! before that, to drain traffic to another device or raise an alert. A
+//! [`DeviceWatcher`] polls the state of every DSA and IAA device and work
+//! queue in sysfs, and each device's software error register
+//! ([`SoftwareError`]), on a thread of its own. Every change is passed to a
+//! callback as a [`DeviceEvent`].
+//!
+//! When a work queue leaves the enabled state, the watcher also marks every
+//! engine in the process that has it open as unhealthy
+//! ([`DsaEngine::is_healthy`]) before invoking the callback. Their
+//! in-flight operations fail with
+//! [`DsaError::WorkQueueDisabled`],
+//! and new operations fail the same way until the work queue is enabled
+//! again and an operation reopens it. A sealed engine cannot reopen its
+//! work queue and stays unhealthy.
+//!
+//! The IDXD driver does not emit uevents for state changes of configured
+//! devices, so the watcher polls; the interval bounds how late a change is
+//! seen. The callback runs on the watcher thread and should not block for
+//! long, since no changes are seen meanwhile.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::watch::{DeviceEvent, DeviceWatcher};
+//! use std::time::Duration;
+//!
+//! let watcher = DeviceWatcher::start(Duration::from_secs(1), |event| match event {
+//!     DeviceEvent::DeviceError { device, error } => log::error!("{device}: {error}"),
+//!     event => log::warn!("{event:?}"),
+//! })?;
+//! // ... serve requests; dropping the watcher stops it
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::swerr::SoftwareError;
+use crate::wq::WorkQueueState;
+use std::sync::mpsc::Sender;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A change seen by a [`DeviceWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// A device's state changed, e.g. to `disabled` when it was reset;
+    /// `None` if the device was removed.
+    DeviceState {
+        device: String,
+        state: Option<String>,
+    },
+    /// A work queue's state changed; `None` if the work queue was removed.
+    WorkQueueState {
+        work_queue: String,
+        state: Option<WorkQueueState>,
+    },
+    /// A device logged a new error in its software error register.
+    DeviceError {
+        device: String,
+        error: SoftwareError,
+    },
+}
+
+/// A thread polling devices for changes.
+///
+/// Dropping the watcher stops the thread.
+pub struct DeviceWatcher {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DeviceWatcher {
+    /// Start watching the devices in sysfs every `interval`.
+    ///
+    /// The devices are read once before returning; changes after that are
+    /// passed to `callback` in the order they are found.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::Io` if the thread cannot be spawned, or
+    /// `DsaError::PlatformNotSupported` on platforms other than Linux.
+    #[cfg(dsa_portal)]
+    pub fn start<F>(interval: Duration, callback: F) -> Result<Self, DsaError>
+    where
+        F: FnMut(DeviceEvent) + Send + 'static,
+    {
+        Self::start_at(
+            std::path::PathBuf::from(linux_impl::SYSFS_DSA_PATH),
+            interval,
+            callback,
+        )
+    }
+
+    /// Start watching the devices in sysfs every `interval`.
+    #[cfg(not(dsa_portal))]
+    pub fn start<F>(_interval: Duration, _callback: F) -> Result<Self, DsaError>
+    where
+        F: FnMut(DeviceEvent) + Send + 'static,
+    {
+        Err(DsaError::PlatformNotSupported)
+    }
+
+    /// Start watching the devices in directory `root`.
+    #[cfg(dsa_portal)]
+    fn start_at<F>(
+        root: std::path::PathBuf,
+        interval: Duration,
+        callback: F,
+    ) -> Result<Self, DsaError>
+    where
+        F: FnMut(DeviceEvent) + Send + 'static,
+    {
+        let snapshot = linux_impl::Snapshot::scan(&root);
+        let (stop, stopped) = std::sync::mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("dsa-watcher".to_string())
+            .spawn(move || linux_impl::run(&root, snapshot, interval, &stopped, callback))?;
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        // Closing the channel wakes the thread and lets it exit
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl DsaEngine {
+    /// Returns false once the engine's work queue was found disabled or
+    /// gone, by a failed operation or a [`DeviceWatcher`], until an
+    /// operation reopens it.
+    pub fn is_healthy(&self) -> bool {
+        self.work_queue().is_healthy()
+    }
+}
+
+#[cfg(dsa_portal)]
+mod linux_impl {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::sync::mpsc::{Receiver, RecvTimeoutError};
+
+    pub(super) const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
+
+    /// States and errors of the devices and work queues at one poll.
+    #[derive(Debug, Default)]
+    pub(super) struct Snapshot {
+        /// State and logged error by device name.
+        devices: BTreeMap<String, (String, Option<SoftwareError>)>,
+        work_queues: BTreeMap<String, WorkQueueState>,
+    }
+
+    impl Snapshot {
+        /// Read the devices and work queues in directory `root`; an
+        /// unreadable entry counts as removed.
+        pub(super) fn scan(root: &Path) -> Self {
+            let mut snapshot = Self::default();
+            let Ok(entries) = std::fs::read_dir(root) else {
+                return snapshot;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let path = entry.path();
+                let Ok(state) = std::fs::read_to_string(path.join("state")) else {
+                    continue;
+                };
+                if name.starts_with("wq") {
+                    let state = WorkQueueState::from(state.as_str());
+                    snapshot.work_queues.insert(name, state);
+                } else if name.starts_with("dsa") || name.starts_with("iax") {
+                    let error = crate::swerr::read(&path).ok().flatten();
+                    snapshot
+                        .devices
+                        .insert(name, (state.trim().to_string(), error));
+                }
+            }
+            snapshot
+        }
+
+        /// Changes from `self` to `next`, devices first.
+        pub(super) fn changes(&self, next: &Self) -> Vec<DeviceEvent> {
+            let mut events = Vec::new();
+            for (device, state, error) in changed(&self.devices, &next.devices) {
+                if state {
+                    events.push(DeviceEvent::DeviceState {
+                        device: device.clone(),
+                        state: next.devices.get(device).map(|(state, _)| state.clone()),
+                    });
+                }
+                if let Some(error) = error {
+                    events.push(DeviceEvent::DeviceError {
+                        device: device.clone(),
+                        error,
+                    });
+                }
+            }
+            for (work_queue, _, _) in changed(&self.work_queues, &next.work_queues) {
+                events.push(DeviceEvent::WorkQueueState {
+                    work_queue: work_queue.clone(),
+                    state: next.work_queues.get(work_queue).cloned(),
+                });
+            }
+            events
+        }
+    }
+
+    /// Entries of `old` and `new` whose state changed, or that gained a
+    /// new logged error.
+    fn changed<'a, V>(
+        old: &'a BTreeMap<String, V>,
+        new: &'a BTreeMap<String, V>,
+    ) -> Vec<(&'a String, bool, Option<SoftwareError>)>
+    where
+        V: Entry,
+    {
+        let names: std::collections::BTreeSet<_> = old.keys().chain(new.keys()).collect();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let (before, after) = (old.get(name), new.get(name));
+                let state = before.map(Entry::state) != after.map(Entry::state);
+                let error = after
+                    .and_then(Entry::error)
+                    .filter(|error| before.and_then(Entry::error) != Some(*error));
+                (state || error.is_some()).then_some((name, state, error))
+            })
+            .collect()
+    }
+
+    /// A watched device or work queue.
+    pub(super) trait Entry {
+        fn state(&self) -> String;
+        fn error(&self) -> Option<SoftwareError>;
+    }
+
+    impl Entry for (String, Option<SoftwareError>) {
+        fn state(&self) -> String {
+            self.0.clone()
+        }
+
+        fn error(&self) -> Option<SoftwareError> {
+            self.1
+        }
+    }
+
+    impl Entry for WorkQueueState {
+        fn state(&self) -> String {
+            self.to_string()
+        }
+
+        fn error(&self) -> Option<SoftwareError> {
+            None
+        }
+    }
+
+    /// Watcher thread: poll `root` every `interval` until `stopped` closes.
+    pub(super) fn run<F>(
+        root: &Path,
+        mut snapshot: Snapshot,
+        interval: Duration,
+        stopped: &Receiver<()>,
+        mut callback: F,
+    ) where
+        F: FnMut(DeviceEvent),
+    {
+        while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+            let next = Snapshot::scan(root);
+            for event in snapshot.changes(&next) {
+                if let DeviceEvent::WorkQueueState { work_queue, state } = &event {
+                    let enabled = state.as_ref().is_some_and(WorkQueueState::is_enabled);
+                    if !enabled && crate::wq::mark_work_queue_gone(work_queue) {
+                        log::warn!("work queue {} went away", work_queue);
+                    }
+                }
+                callback(event);
+            }
+            snapshot = next;
+        }
+    }
+}
+
+#[cfg(all(test, dsa_portal))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_watch_changes() {
+        let root = std::env::temp_dir().join(format!("dsa-watch-{}", std::process::id()));
+        let write = |attr: &str, value: &str| {
+            let path = root.join("sys").join(attr);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            // Replace the file at once, so the watcher never reads it half written
+            let staged = path.with_extension("new");
+            fs::write(&staged, format!("{value}\n")).unwrap();
+            fs::rename(staged, path).unwrap();
+        };
+        write("dsa7/state", "enabled");
+        write("dsa7/errors", "0x0 0x0 0x0 0x0");
+        write("wq7.3/state", "enabled");
+
+        #[cfg(feature = "backend-hw")]
+        let engine = {
+            // Any read-write mappable file stands in for the device node
+            let node = root.join("dev/wq7.3");
+            fs::create_dir_all(node.parent().unwrap()).unwrap();
+            fs::write(&node, []).unwrap();
+            DsaEngine::from_wq(crate::wq::WorkQueue::open(&node).unwrap())
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let watcher =
+            DeviceWatcher::start_at(root.join("sys"), Duration::from_millis(5), move |e| {
+                tx.send(e).unwrap();
+            })
+            .unwrap();
+        let next = || rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        write("dsa7/errors", "0x0000000000002001 0x0 0x0 0x0");
+        let DeviceEvent::DeviceError { device, error } = next() else {
+            panic!("expected a device error");
+        };
+        assert_eq!((device.as_str(), error.status), ("dsa7", 0x20));
+
+        write("wq7.3/state", "disabled");
+        assert_eq!(
+            next(),
+            DeviceEvent::WorkQueueState {
+                work_queue: "wq7.3".to_string(),
+                state: Some(WorkQueueState::Disabled),
+            }
+        );
+        #[cfg(feature = "backend-hw")]
+        {
+            assert!(!engine.is_healthy());
+            assert!(matches!(engine.noop(), Err(DsaError::WorkQueueDisabled(_))));
+        }
+
+        fs::remove_dir_all(root.join("sys/dsa7")).unwrap();
+        assert_eq!(
+            next(),
+            DeviceEvent::DeviceState {
+                device: "dsa7".to_string(),
+                state: None,
+            }
+        );
+        drop(watcher);
+        assert!(rx.recv().is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
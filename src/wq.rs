@@ -13,28 +13,87 @@
 //! Currently only Linux is supported. On other platforms, attempting to open
 //! a work queue will return `DsaError::PlatformNotSupported`.
 
+use crate::backoff::Backoff;
+use crate::cancel::OpContext;
+use crate::capabilities::Capabilities;
+use crate::descriptor::{CompletionStatus, DifTags, DsaCompletionRecord};
 use crate::error::DsaError;
+#[cfg(feature = "iaa")]
+use crate::iaa::Crc64Params;
+use crate::profile::Profile;
+use std::ops::Range;
 use std::path::Path;
 
-#[cfg(target_os = "linux")]
-use crate::submit::{enqcmd_retry, movdir64b};
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
+use crate::arena::Arena;
+#[cfg(dsa_portal)]
+use crate::backoff::Poller;
+#[cfg(dsa_portal)]
+use crate::descriptor::{DescriptorFlags, DsaHwDesc};
+use crate::emulator::Emulator;
+#[cfg(dsa_portal)]
+use crate::error::{ErrorContext, WqUnavailableReason};
+use crate::fair::FairShare;
+#[cfg(dsa_portal)]
+use crate::fair::{ShareToken, Tenancy};
+#[cfg(all(feature = "iaa", dsa_portal))]
+use crate::iaa::IaaCompletionRecord;
+use crate::mock::MockBackend;
+#[cfg(dsa_portal)]
+use crate::reactor::{InFlight, QueueLiveness, Reactor};
+#[cfg(dsa_portal)]
+use crate::submit::{enqcmd_retry, movdir64b, sfence};
+#[cfg(dsa_portal)]
+use crate::swerr::SoftwareError;
+use crate::trace::TraceRing;
+#[cfg(dsa_portal)]
+use std::collections::HashMap;
+#[cfg(dsa_portal)]
 use std::fs::File;
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 use std::os::unix::io::AsRawFd;
+#[cfg(dsa_portal)]
+use std::path::PathBuf;
+#[cfg(dsa_portal)]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(dsa_portal)]
+use std::sync::{Mutex, OnceLock, RwLock, Weak};
+#[cfg(dsa_portal)]
+use std::time::{Duration, Instant};
+
+/// Sysfs base path for DSA work queues (Linux only).
+#[cfg(dsa_portal)]
+const SYSFS_DSA_PATH: &str = "/sys/bus/dsa/devices";
 
 /// Portal size for mmap (one page).
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 const PORTAL_SIZE: usize = 4096;
 
+/// Largest transfer a descriptor can describe on any DSA device. Larger
+/// CRC inputs are split into descriptors of this size, each seeded with the
+/// CRC of the data before it.
+#[cfg(dsa_portal)]
+const MAX_XFER_SIZE: usize = 1 << 31;
+
 /// Default maximum retries for ENQCMD.
 const DEFAULT_MAX_RETRIES: u32 = 1000;
 
 /// Default spin iterations while waiting for completion.
 const DEFAULT_SPIN_ITERATIONS: u32 = 1_000_000;
 
+/// Longest a dropped work queue waits for in-flight operations.
+#[cfg(dsa_portal)]
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Default maximum number of descriptors per batch.
+///
+/// Matches the `max_batch_size` reported by current DSA devices.
+pub(crate) const DEFAULT_MAX_BATCH_SIZE: usize = 1024;
+
 /// Work queue type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WorkQueueType {
     /// Dedicated Work Queue - single user, uses MOVDIR64B.
     Dedicated,
@@ -42,48 +101,328 @@ pub enum WorkQueueType {
     Shared,
 }
 
+/// Work queue state as reported by sysfs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkQueueState {
+    /// Configured and accepting descriptors.
+    Enabled,
+    /// Not configured for use.
+    Disabled,
+    /// Being disabled; outstanding descriptors are draining.
+    Quiescing,
+    /// Any other state, with the raw sysfs value.
+    Unknown(String),
+}
+
+impl WorkQueueState {
+    /// Returns true if the work queue accepts descriptors.
+    pub fn is_enabled(&self) -> bool {
+        *self == Self::Enabled
+    }
+}
+
+impl From<&str> for WorkQueueState {
+    /// Parse a sysfs state value, ignoring case and surrounding whitespace.
+    fn from(s: &str) -> Self {
+        let s = s.trim();
+        match s.to_ascii_lowercase().as_str() {
+            "enabled" => Self::Enabled,
+            "disabled" => Self::Disabled,
+            "quiescing" => Self::Quiescing,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for WorkQueueState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Enabled => write!(f, "enabled"),
+            Self::Disabled => write!(f, "disabled"),
+            Self::Quiescing => write!(f, "quiescing"),
+            Self::Unknown(state) => write!(f, "{}", state),
+        }
+    }
+}
+
+/// Driver a work queue is bound to, as reported by sysfs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkQueueBinding {
+    /// The user-space character device driver (`user`); the only binding
+    /// this crate can submit through.
+    User,
+    /// The kernel's DMA engine driver (`dmaengine`).
+    DmaEngine,
+    /// Any other driver, with its name (e.g. `crypto` for IAA).
+    Other(String),
+    /// Not bound to any driver.
+    Unbound,
+}
+
+impl WorkQueueBinding {
+    /// Returns true if the work queue is bound to the user driver.
+    pub fn is_user(&self) -> bool {
+        *self == Self::User
+    }
+
+    /// Returns true if a kernel driver owns the work queue.
+    pub fn is_kernel(&self) -> bool {
+        matches!(self, Self::DmaEngine | Self::Other(_))
+    }
+}
+
+impl From<&str> for WorkQueueBinding {
+    /// Parse a driver name, ignoring surrounding whitespace; empty means
+    /// unbound.
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "user" => Self::User,
+            "dmaengine" => Self::DmaEngine,
+            "" => Self::Unbound,
+            driver => Self::Other(driver.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for WorkQueueBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User => write!(f, "user"),
+            Self::DmaEngine => write!(f, "dmaengine"),
+            Self::Other(driver) => write!(f, "{}", driver),
+            Self::Unbound => write!(f, "none"),
+        }
+    }
+}
+
 /// Information about a work queue (from sysfs).
 #[derive(Debug, Clone)]
 pub struct WorkQueueInfo {
     /// Work queue name (e.g., "wq0.0").
     pub name: String,
-    /// State ("enabled", "disabled", etc.).
-    pub state: String,
+    /// Current state.
+    pub state: WorkQueueState,
     /// Work queue type.
     pub wq_type: WorkQueueType,
     /// Queue size (number of entries).
     pub size: u32,
     /// Threshold for shared WQ.
     pub threshold: u32,
+    /// Whether the device blocks on page faults instead of reporting them.
+    pub block_on_fault: bool,
+    /// Maximum number of descriptors in one batch.
+    pub max_batch_size: u32,
+    /// Maximum transfer size of one descriptor in bytes.
+    pub max_transfer_size: u64,
+    /// Name the work queue was bound with (e.g., "app1"); empty if unset.
+    pub driver_name: String,
+    /// Number of processes that currently have the work queue open.
+    pub clients: u32,
+    /// Arbitration priority within the work queue's group (1-15).
+    pub priority: u32,
+    /// Whether address translation services are disabled for the queue.
+    pub ats_disable: bool,
+    /// Driver the work queue is bound to.
+    pub binding: WorkQueueBinding,
 }
 
 // ============================================================================
 // Linux Implementation
 // ============================================================================
 
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
 mod linux_impl {
     use super::*;
 
+    /// Where descriptors are submitted.
+    enum Portal {
+        /// Memory-mapped portal of a work queue device, replaced when the
+        /// work queue is reopened after it went away.
+        Mapped(RwLock<Arc<Mapping>>),
+        /// Software emulator.
+        Emulated(Arc<Emulator>),
+        /// Emulator with scripted responses.
+        Mocked(Arc<MockBackend>),
+    }
+
+    /// A work queue device opened and its portal mapped.
+    ///
+    /// Shared by every `WorkQueue` opened on the same device in this
+    /// process; unmapped when the last one is dropped.
+    struct Mapping {
+        /// Open file handle to the work queue device.
+        file: File,
+        /// Memory-mapped portal address.
+        addr: *mut u8,
+        /// Portal mapping size.
+        size: usize,
+        /// Set once the work queue was disabled or its device reset; the
+        /// portal must not be written after that.
+        liveness: Arc<QueueLiveness>,
+        /// Set once the device file is locked by `lock_exclusive`; a mapping
+        /// that replaces this one must take the lock again.
+        exclusive: AtomicBool,
+    }
+
+    // SAFETY: the portal is only written with whole-descriptor direct stores,
+    // which the device accepts from any thread.
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Mapping {
+        /// Take an advisory exclusive lock on the device file at `path`.
+        fn lock(&self, path: &Path) -> Result<(), DsaError> {
+            let ret = unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+            if ret != 0 {
+                let err = std::io::Error::last_os_error();
+                return if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+                    Err(DsaError::WorkQueueBusy(path.display().to_string()))
+                } else {
+                    Err(DsaError::Io(err))
+                };
+            }
+            self.exclusive.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+
+        /// Release the lock taken by `lock`.
+        fn unlock(&self) {
+            unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            unsafe {
+                libc::munmap(self.addr as *mut libc::c_void, self.size);
+            }
+        }
+    }
+
+    /// Submission serialization of a work queue shared by many threads.
+    struct Serial {
+        /// Held while a descriptor is written to the portal.
+        portal: Mutex<()>,
+        /// Most descriptors outstanding at once.
+        budget: usize,
+        /// Blocking operations admitted and not yet finished.
+        blocking: AtomicUsize,
+    }
+
+    /// What a sealed work queue read up front, so that it never accesses a
+    /// file again.
+    struct Sealed {
+        capabilities: Capabilities,
+        /// The work queue's threshold, for `QueueFull` errors.
+        threshold: Option<u32>,
+    }
+
+    /// A place in a serialized work queue's in-flight budget and in the
+    /// fair share, given back when dropped.
+    struct Permit<'a> {
+        blocking: Option<&'a AtomicUsize>,
+        share: Option<ShareToken>,
+    }
+
+    impl Drop for Permit<'_> {
+        fn drop(&mut self) {
+            if let Some(blocking) = self.blocking {
+                blocking.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+    }
+
+    /// Portals mapped in this process, by canonical device path.
+    static MAPPINGS: OnceLock<Mutex<HashMap<PathBuf, Weak<Mapping>>>> = OnceLock::new();
+
+    /// Get the live mapping of the work queue device at `path`, mapping it
+    /// if no handle in this process has it mapped.
+    fn shared_mapping(path: &Path) -> Result<Arc<Mapping>, DsaError> {
+        let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut mappings = MAPPINGS.get_or_init(Mutex::default).lock().unwrap();
+        let live = mappings
+            .get(&key)
+            .and_then(Weak::upgrade)
+            .filter(|mapping| !mapping.liveness.is_gone());
+        if let Some(mapping) = live {
+            return Ok(mapping);
+        }
+        let mapping = Arc::new(WorkQueue::map(path)?);
+        mappings.retain(|_, mapping| mapping.strong_count() > 0);
+        mappings.insert(key, Arc::downgrade(&mapping));
+        Ok(mapping)
+    }
+
+    /// Mark work queue `name` (e.g. `wq0.0`) gone in every mapping of it in
+    /// this process, which fails its in-flight operations.
+    ///
+    /// Returns true if the work queue was mapped.
+    pub(crate) fn mark_work_queue_gone(name: &str) -> bool {
+        let Some(mappings) = MAPPINGS.get() else {
+            return false;
+        };
+        let mut marked = false;
+        for (path, mapping) in mappings.lock().unwrap().iter() {
+            if path.file_name().is_some_and(|file| file == name) {
+                if let Some(mapping) = mapping.upgrade() {
+                    mapping.liveness.mark_gone();
+                    marked = true;
+                }
+            }
+        }
+        marked
+    }
+
     /// Handle to an open work queue.
     ///
     /// This struct manages the lifecycle of a work queue, including:
     /// - The file descriptor to the character device
     /// - The memory-mapped portal for descriptor submission
     pub struct WorkQueue {
-        /// File handle to the work queue device.
-        #[allow(dead_code)]
-        file: File,
-        /// Memory-mapped portal address.
-        portal: *mut u8,
-        /// Portal mapping size.
-        portal_size: usize,
+        /// Portal for descriptor submission.
+        portal: Portal,
+        /// Path to the work queue device.
+        path: PathBuf,
         /// Work queue type (determines submission method).
         wq_type: WorkQueueType,
         /// Maximum retries for ENQCMD.
         max_retries: u32,
         /// Spin iterations for completion polling.
         spin_iterations: u32,
+        /// Wait between completion polls.
+        backoff: Backoff,
+        /// Preallocated descriptor/completion record slots.
+        arena: Arena,
+        /// Operations submitted with `start` that have not completed.
+        pub(super) in_flight: Arc<AtomicUsize>,
+        /// Set once a synchronous wait timed out, leaving hardware work
+        /// that is not counted in `in_flight`.
+        abandoned: AtomicBool,
+        /// Submission lock and in-flight budget, if serialized.
+        serial: Option<Serial>,
+        /// Ring of recent operations, if tracing.
+        trace: Option<TraceRing>,
+        /// Slot in the table shared with other processes, if sharing
+        /// fairly.
+        fair: Option<Arc<Tenancy>>,
+        /// Profile whose flags are added to submitted descriptors.
+        profile: Option<Profile>,
+        /// Whether the work queue supports block on fault, read on first
+        /// use.
+        block_on_fault: OnceLock<bool>,
+        /// Capabilities that descriptors are checked against, read on
+        /// first use.
+        #[cfg(feature = "strict-validation")]
+        strict_caps: OnceLock<Capabilities>,
+        /// State read up front, once sealed.
+        sealed: Option<Sealed>,
+        /// Whether hardware errors carry the device's software error
+        /// register.
+        attach_device_errors: bool,
+        /// Whether errors are built without allocating.
+        compact_errors: bool,
+        /// Region size batch descriptors are grouped by, if any.
+        region_size: Option<usize>,
     }
 
     // SAFETY: WorkQueue can be sent between threads because:
@@ -93,7 +432,8 @@ mod linux_impl {
     unsafe impl Send for WorkQueue {}
 
     // SAFETY: WorkQueue can be shared between threads with proper synchronization
-    // The caller must ensure only one descriptor is in flight per completion record
+    // The caller must ensure only one descriptor is in flight per completion record;
+    // `set_serialized` additionally bounds the descriptors in flight
     unsafe impl Sync for WorkQueue {}
 
     impl WorkQueue {
@@ -106,9 +446,100 @@ mod linux_impl {
         /// # Errors
         ///
         /// Returns an error if:
+        /// - The crate was built without the `backend-hw` feature
+        ///   (`DsaError::PlatformNotSupported`)
         /// - The device cannot be opened (permissions, not found)
         /// - Memory mapping fails
+        ///
+        /// If sysfs explains the failure (work queue disabled, bound to a
+        /// kernel driver, unsupported mode, no PASID, missing device node),
+        /// `DsaError::WorkQueueUnavailable` is returned with the reason.
+        ///
+        /// # Sharing
+        ///
+        /// Work queues opened on the same device within one process share
+        /// the device file and portal mapping, so libraries that each open
+        /// their own engine do not map redundant portals. Settings, the
+        /// descriptor arena and the in-flight count remain per handle.
+        ///
+        /// The work queue is submitted to as a shared work queue and is not
+        /// reserved for this process. Open a dedicated work queue with
+        /// [`DsaDevice::open_enabled_wq`](crate::DsaDevice::open_enabled_wq),
+        /// or call [`set_wq_type`](Self::set_wq_type) and
+        /// [`lock_exclusive`](Self::lock_exclusive).
         pub fn open(path: &Path) -> Result<Self, DsaError> {
+            if !cfg!(feature = "backend-hw") {
+                return Err(DsaError::PlatformNotSupported);
+            }
+            let mapping = shared_mapping(path)?;
+
+            // TODO: Detect WQ type from sysfs or device properties
+            // For now, default to Shared (more common for user-space)
+            let wq_type = WorkQueueType::Shared;
+
+            Ok(Self {
+                portal: Portal::Mapped(RwLock::new(mapping)),
+                path: path.to_path_buf(),
+                wq_type,
+                max_retries: DEFAULT_MAX_RETRIES,
+                spin_iterations: DEFAULT_SPIN_ITERATIONS,
+                backoff: Backoff::default(),
+                arena: Arena::new(),
+                in_flight: Arc::new(AtomicUsize::new(0)),
+                abandoned: AtomicBool::new(false),
+                serial: None,
+                trace: None,
+                fair: None,
+                profile: None,
+                block_on_fault: OnceLock::new(),
+                #[cfg(feature = "strict-validation")]
+                strict_caps: OnceLock::new(),
+                sealed: None,
+                attach_device_errors: false,
+                compact_errors: false,
+                region_size: Some(crate::iotlb::DEFAULT_REGION),
+            })
+        }
+
+        /// Returns true if both work queues submit through the same mapping.
+        #[cfg(test)]
+        pub(super) fn shares_portal(&self, other: &Self) -> bool {
+            match (&self.portal, &other.portal) {
+                (Portal::Mapped(a), Portal::Mapped(b)) => {
+                    Arc::ptr_eq(&a.read().unwrap(), &b.read().unwrap())
+                }
+                _ => false,
+            }
+        }
+
+        /// Simulate the work queue being disabled.
+        #[cfg(test)]
+        pub(super) fn mark_gone(&self) {
+            if let Portal::Mapped(current) = &self.portal {
+                current.read().unwrap().liveness.mark_gone();
+            }
+        }
+
+        /// Open the work queue device at `path` and map its portal.
+        ///
+        /// Work queues a kernel driver owns are refused up front, as their
+        /// portal cannot be mapped.
+        fn map(path: &Path) -> Result<Mapping, DsaError> {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string());
+            if let Some(binding) = crate::device::wq_binding(&name) {
+                if binding.is_kernel() {
+                    return Err(DsaError::WorkQueueUnavailable {
+                        name,
+                        reason: WqUnavailableReason::KernelOwned {
+                            wq_type: binding.to_string(),
+                        },
+                    });
+                }
+            }
+
             // Open the work queue character device
             let file = File::options()
                 .read(true)
@@ -118,7 +549,7 @@ mod linux_impl {
                     if e.kind() == std::io::ErrorKind::PermissionDenied {
                         DsaError::PermissionDenied(path.display().to_string())
                     } else {
-                        DsaError::Io(e)
+                        diagnose_open_failure(path, DsaError::Io(e))
                     }
                 })?;
 
@@ -135,24 +566,58 @@ mod linux_impl {
             };
 
             if portal == libc::MAP_FAILED {
-                return Err(DsaError::MmapFailed(format!(
-                    "mmap failed for {}",
-                    path.display()
-                )));
+                return Err(diagnose_open_failure(
+                    path,
+                    DsaError::MmapFailed(format!("mmap failed for {}", path.display())),
+                ));
             }
 
-            // TODO: Detect WQ type from sysfs or device properties
-            // For now, default to Shared (more common for user-space)
-            let wq_type = WorkQueueType::Shared;
-
-            Ok(Self {
+            Ok(Mapping {
                 file,
-                portal: portal as *mut u8,
-                portal_size: PORTAL_SIZE,
-                wq_type,
+                addr: portal as *mut u8,
+                size: PORTAL_SIZE,
+                liveness: QueueLiveness::new(name),
+                exclusive: AtomicBool::new(false),
+            })
+        }
+
+        /// Create a work queue backed by a software emulator.
+        ///
+        /// Descriptors are executed by `emulator` instead of hardware, so
+        /// injected faults surface through the normal error paths.
+        pub fn emulated(emulator: Arc<Emulator>) -> Result<Self, DsaError> {
+            Ok(Self::software(Portal::Emulated(emulator)))
+        }
+
+        /// Create a work queue backed by a scripted mock.
+        pub fn mocked(mock: Arc<MockBackend>) -> Result<Self, DsaError> {
+            Ok(Self::software(Portal::Mocked(mock)))
+        }
+
+        /// Create a work queue that submits to a software `portal`.
+        fn software(portal: Portal) -> Self {
+            Self {
+                portal,
+                path: PathBuf::new(),
+                wq_type: WorkQueueType::Shared,
                 max_retries: DEFAULT_MAX_RETRIES,
                 spin_iterations: DEFAULT_SPIN_ITERATIONS,
-            })
+                backoff: Backoff::default(),
+                arena: Arena::new(),
+                in_flight: Arc::new(AtomicUsize::new(0)),
+                abandoned: AtomicBool::new(false),
+                serial: None,
+                trace: None,
+                fair: None,
+                profile: None,
+                block_on_fault: OnceLock::new(),
+                #[cfg(feature = "strict-validation")]
+                strict_caps: OnceLock::new(),
+                sealed: None,
+                attach_device_errors: false,
+                compact_errors: false,
+                region_size: Some(crate::iotlb::DEFAULT_REGION),
+            }
         }
 
         /// Set the work queue type.
@@ -165,16 +630,289 @@ mod linux_impl {
             self.max_retries = retries;
         }
 
+        /// Get the maximum retries for ENQCMD submissions.
+        pub fn max_retries(&self) -> u32 {
+            self.max_retries
+        }
+
         /// Set the spin iterations for completion polling.
+        ///
+        /// This bounds the number of polls before a blocking operation times
+        /// out; with [`Backoff`] the time between polls grows the longer the
+        /// operation is pending.
         pub fn set_spin_iterations(&mut self, iterations: u32) {
             self.spin_iterations = iterations;
         }
 
+        /// Get the spin iterations for completion polling.
+        pub fn spin_iterations(&self) -> u32 {
+            self.spin_iterations
+        }
+
+        /// Set how completion polling waits between polls.
+        pub fn set_backoff(&mut self, backoff: Backoff) {
+            self.backoff = backoff;
+        }
+
+        /// Get how completion polling waits between polls.
+        pub fn backoff(&self) -> Backoff {
+            self.backoff
+        }
+
+        /// Serialize submissions and bound the descriptors in flight.
+        ///
+        /// A `&WorkQueue` may be used from many threads at once, but every
+        /// thread then writes the portal independently, and nothing keeps
+        /// them from submitting more descriptors than the queue holds. In
+        /// serialized mode each portal write takes an internal lock, and a
+        /// submission first waits until fewer than `budget` descriptors,
+        /// blocking and non-blocking, are in flight. A submission that finds
+        /// no room within the completion polling budget fails with
+        /// `DsaError::QueueFull`, which the engine's queue-full policy may
+        /// retry. A `budget` no larger than the work queue size makes a
+        /// dedicated work queue safe to share between threads;
+        /// [`Capabilities::max_in_flight`] is the largest such budget.
+        ///
+        /// Pass `None` to submit without serialization, the default.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `budget` is zero.
+        pub fn set_serialized(&mut self, budget: Option<usize>) {
+            assert_ne!(budget, Some(0), "in-flight budget must be positive");
+            self.serial = budget.map(|budget| Serial {
+                portal: Mutex::new(()),
+                budget,
+                blocking: AtomicUsize::new(0),
+            });
+        }
+
+        /// The in-flight budget, if submissions are serialized.
+        pub fn serialized(&self) -> Option<usize> {
+            self.serial.as_ref().map(|serial| serial.budget)
+        }
+
+        /// Record operations in `trace`, or stop tracing with `None`.
+        pub(crate) fn set_trace(&mut self, trace: Option<TraceRing>) {
+            self.trace = trace;
+        }
+
+        /// The operation trace, if tracing.
+        pub(crate) fn trace(&self) -> Option<&TraceRing> {
+            self.trace.as_ref()
+        }
+
+        /// Add the flags of `profile` to every submitted descriptor and wait
+        /// with its backoff, or (with `None`) stop adding flags.
+        ///
+        /// See [`Profile`] for the descriptors the flags are added to.
+        pub fn set_profile(&mut self, profile: Option<Profile>) {
+            self.profile = profile;
+            if let Some(profile) = profile {
+                self.backoff = profile.backoff();
+            }
+        }
+
+        /// Get the profile whose flags are added to submitted descriptors.
+        pub fn profile(&self) -> Option<Profile> {
+            self.profile
+        }
+
+        /// Share the work queue fairly with other processes, or stop with
+        /// `None`; see [`FairShare`].
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the table shared with other processes cannot
+        /// be opened or mapped, or `DsaError::WorkQueueBusy` if it is full.
+        pub fn set_fair_share(&mut self, share: Option<FairShare>) -> Result<(), DsaError> {
+            let Some(share) = share else {
+                self.fair = None;
+                return Ok(());
+            };
+            if self.sealed.is_some() {
+                return Err(DsaError::InvalidArgument(
+                    "fair sharing must be set up before the work queue is sealed".to_string(),
+                ));
+            }
+            let name = self.name();
+            let capacity = share
+                .capacity
+                .unwrap_or_else(|| self.capabilities().max_in_flight);
+            let table = match (&self.portal, name) {
+                (Portal::Mapped(_), Some(name)) => {
+                    Some(PathBuf::from(format!("/dev/shm/dsa-rust-fair-{name}")))
+                }
+                _ => None,
+            };
+            self.fair = Some(Tenancy::join(table.as_deref(), capacity)?);
+            Ok(())
+        }
+
+        /// Get the fair sharing configuration, if sharing fairly.
+        pub fn fair_share(&self) -> Option<FairShare> {
+            self.fair.as_ref().map(|tenancy| FairShare {
+                capacity: Some(tenancy.capacity()),
+            })
+        }
+
+        /// Wait for room in the in-flight budget of a serialized work queue,
+        /// and in the fair share.
+        ///
+        /// The permit covers a blocking operation until it is dropped; a
+        /// non-blocking operation is counted in `in_flight` once started,
+        /// and holds its place in the fair share until it completes.
+        fn admit(&self) -> Result<Permit<'_>, DsaError> {
+            let share = match &self.fair {
+                Some(tenancy) => Some(self.wait_for_share(tenancy)?),
+                None => None,
+            };
+            let Some(serial) = &self.serial else {
+                return Ok(Permit {
+                    blocking: None,
+                    share,
+                });
+            };
+            let start = Instant::now();
+            let mut poller = Poller::new(self.backoff);
+            let mut outstanding = 0;
+            for _ in 0..self.spin_iterations {
+                let blocking = serial.blocking.load(Ordering::Acquire);
+                outstanding = blocking + self.in_flight();
+                if outstanding < serial.budget
+                    && serial
+                        .blocking
+                        .compare_exchange(
+                            blocking,
+                            blocking + 1,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        )
+                        .is_ok()
+                {
+                    return Ok(Permit {
+                        blocking: Some(&serial.blocking),
+                        share,
+                    });
+                }
+                poller.snooze();
+            }
+            Err(DsaError::QueueFull {
+                attempts: self.spin_iterations,
+                elapsed: start.elapsed(),
+                threshold: u32::try_from(serial.budget).ok(),
+                occupancy: u32::try_from(outstanding).ok(),
+            })
+        }
+
+        /// Wait until fewer than `budget` operations submitted without
+        /// waiting are in flight, so a burst of them cannot overflow a
+        /// dedicated work queue.
+        ///
+        /// Gives up with `DsaError::QueueFull` after the completion polling
+        /// budget.
+        pub(crate) fn wait_for_room(&self, budget: usize) -> Result<(), DsaError> {
+            let start = Instant::now();
+            let mut poller = Poller::new(self.backoff);
+            for _ in 0..self.spin_iterations {
+                if self.in_flight() < budget {
+                    return Ok(());
+                }
+                poller.snooze();
+            }
+            Err(DsaError::QueueFull {
+                attempts: self.spin_iterations,
+                elapsed: start.elapsed(),
+                threshold: u32::try_from(budget).ok(),
+                occupancy: u32::try_from(self.in_flight()).ok(),
+            })
+        }
+
+        /// Wait for room in the fair share of `tenancy`.
+        fn wait_for_share(&self, tenancy: &Arc<Tenancy>) -> Result<ShareToken, DsaError> {
+            let start = Instant::now();
+            let mut poller = Poller::new(self.backoff);
+            let mut occupancy = 0;
+            for attempt in 0..self.spin_iterations {
+                match tenancy.try_acquire() {
+                    Ok(token) => return Ok(token),
+                    Err(in_flight) => occupancy = in_flight,
+                }
+                if attempt == 0 {
+                    // Places held by exited processes are never given back
+                    tenancy.reclaim();
+                }
+                poller.snooze();
+            }
+            Err(DsaError::QueueFull {
+                attempts: self.spin_iterations,
+                elapsed: start.elapsed(),
+                threshold: Some(tenancy.capacity()),
+                occupancy: Some(occupancy),
+            })
+        }
+
         /// Get the work queue type.
         pub fn wq_type(&self) -> WorkQueueType {
             self.wq_type
         }
 
+        /// Path to the work queue device.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Number of operations submitted without waiting that have not
+        /// completed yet.
+        pub fn in_flight(&self) -> usize {
+            self.in_flight.load(Ordering::Acquire)
+        }
+
+        /// Report what this work queue can do.
+        ///
+        /// Read from sysfs on each call, unless the work queue is sealed.
+        pub fn capabilities(&self) -> Capabilities {
+            if let Some(sealed) = &self.sealed {
+                return sealed.capabilities.clone();
+            }
+            let max_batch_size = DEFAULT_MAX_BATCH_SIZE as u32;
+            match &self.portal {
+                Portal::Mapped(_) => {
+                    Capabilities::from_sysfs(&self.path, self.wq_type, max_batch_size)
+                }
+                Portal::Emulated(_) | Portal::Mocked(_) => Capabilities::emulated(max_batch_size),
+            }
+        }
+
+        /// Order preceding submissions before any later store.
+        ///
+        /// MOVDIR64B submissions are posted, weakly ordered stores. Call this
+        /// after a run of non-blocking submissions and before a store that
+        /// hands their buffers to another thread (setting a flag, publishing
+        /// a pointer), so that store cannot become visible before the
+        /// submissions reach the device. Blocking operations need no fence.
+        pub fn submission_fence(&self) {
+            sfence();
+        }
+
+        /// Take an advisory exclusive lock on the work queue device.
+        ///
+        /// A dedicated work queue's depth is accounted for by a single
+        /// submitter, so two processes sharing one would overrun it. The lock
+        /// is held until every work queue sharing the device's portal in
+        /// this process is dropped; other processes that call this on the
+        /// same device fail with `DsaError::WorkQueueBusy`. A work queue
+        /// that is reopened after it went away locks the new device file
+        /// again, and fails with `DsaError::WorkQueueBusy` if another process
+        /// took the lock in the meantime.
+        pub fn lock_exclusive(&self) -> Result<(), DsaError> {
+            let Portal::Mapped(mapping) = &self.portal else {
+                // An emulator is private to the process
+                return Ok(());
+            };
+            mapping.read().unwrap().lock(&self.path)
+        }
+
         /// Submit a descriptor to the work queue.
         ///
         /// # Safety
@@ -182,99 +920,1043 @@ mod linux_impl {
         /// The completion record in the descriptor must remain valid until
         /// the operation completes.
         unsafe fn submit(&self, desc: &DsaHwDesc) -> Result<(), DsaError> {
+            self.submit_as(desc, self.profile)
+        }
+
+        /// Submit a descriptor with the flags of `profile` added.
+        ///
+        /// # Safety
+        ///
+        /// As `submit`.
+        unsafe fn submit_as(
+            &self,
+            desc: &DsaHwDesc,
+            profile: Option<Profile>,
+        ) -> Result<(), DsaError> {
+            let flags = profile.map_or(DescriptorFlags::empty(), |profile| {
+                let block_on_fault = *self
+                    .block_on_fault
+                    .get_or_init(|| self.capabilities().block_on_fault);
+                profile.flags_for(desc.opcode(), block_on_fault)
+            });
+            let mut profiled = *desc;
+            let desc = if flags.is_empty() {
+                desc
+            } else {
+                profiled.add_flags(flags);
+                &profiled
+            };
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check(desc, self.strict_caps.get_or_init(|| self.capabilities()))?;
+            let submitted = self.submit_to_portal(desc);
+            if let Some(trace) = &self.trace {
+                trace.submitted(desc, submitted.is_ok());
+            }
+            submitted
+        }
+
+        /// Write a descriptor to the portal, or hand it to the emulator.
+        unsafe fn submit_to_portal(&self, desc: &DsaHwDesc) -> Result<(), DsaError> {
+            let _serialized = self
+                .serial
+                .as_ref()
+                .map(|serial| serial.portal.lock().unwrap());
+            let current = match &self.portal {
+                Portal::Mapped(current) => current,
+                Portal::Emulated(emulator) => return emulator.submit(desc),
+                Portal::Mocked(mock) => return mock.submit(desc),
+            };
+            let mut mapping = current.read().unwrap();
+            if mapping.liveness.is_gone() {
+                drop(mapping);
+                self.reopen(current)?;
+                mapping = current.read().unwrap();
+            }
+            let portal = mapping.addr;
+            // Make the descriptor and source buffers visible to the device
+            // before the weakly ordered direct store
+            sfence();
             match self.wq_type {
                 WorkQueueType::Dedicated => {
-                    movdir64b(self.portal, desc);
+                    movdir64b(portal, desc);
                     Ok(())
                 }
                 WorkQueueType::Shared => {
-                    if enqcmd_retry(self.portal, desc, self.max_retries) {
+                    let start = Instant::now();
+                    if enqcmd_retry(portal, desc, self.max_retries) {
                         Ok(())
                     } else {
-                        Err(DsaError::QueueFull)
+                        // A disabled work queue rejects every ENQCMD
+                        Err(self
+                            .check_liveness(&mapping)
+                            .unwrap_or_else(|| self.queue_full_error(start.elapsed())))
                     }
                 }
             }
         }
 
-        /// Wait for a completion record to be filled.
-        fn wait_for_completion(&self, record: &DsaCompletionRecord) -> Result<(), DsaError> {
-            for _ in 0..self.spin_iterations {
-                if record.is_complete() {
-                    let status = record.get_status();
-                    return match status {
-                        CompletionStatus::Success => Ok(()),
-                        CompletionStatus::PageFault => Err(DsaError::PageFault {
-                            fault_addr: record.fault_addr,
-                            bytes_completed: record.bytes_completed,
-                        }),
-                        _ => Err(DsaError::OperationFailed {
-                            status: record.status,
-                            result: record.result,
-                        }),
-                    };
-                }
-                core::hint::spin_loop();
+        /// Check sysfs whether the work queue behind `mapping` is still
+        /// enabled; if not, mark it gone, which fails its in-flight
+        /// operations, and return the error to report.
+        ///
+        /// A sealed work queue cannot check and assumes it is enabled.
+        #[cold]
+        fn check_liveness(&self, mapping: &Mapping) -> Option<DsaError> {
+            if self.sealed.is_some() || self.sysfs_state() == Some(WorkQueueState::Enabled) {
+                return None;
+            }
+            if !mapping.liveness.is_gone() {
+                log::warn!("work queue {} went away", self.path.display());
+                mapping.liveness.mark_gone();
             }
+            Some(mapping.liveness.error())
+        }
 
-            // Timeout - operation didn't complete in time
-            Err(DsaError::OperationFailed {
-                status: 0,
-                result: 0,
-            })
+        /// The work queue's state in sysfs, or `None` if it cannot be read
+        /// (for example because the device was removed).
+        fn sysfs_state(&self) -> Option<WorkQueueState> {
+            self.sysfs_attr("state")
+                .map(|state| WorkQueueState::from(state.as_str()))
         }
 
-        /// Compute CRC32 checksum of data.
-        pub fn crc32(&self, data: &[u8], seed: u32) -> Result<u32, DsaError> {
-            if data.is_empty() {
-                return Ok(seed);
+        /// Read attribute `attr` of the work queue from sysfs; `None` if it
+        /// cannot be read or the work queue is sealed.
+        fn sysfs_attr(&self, attr: &str) -> Option<String> {
+            if self.sealed.is_some() {
+                return None;
             }
-
-            let mut completion = DsaCompletionRecord::new();
-            let desc = DsaHwDesc::crc_gen(data.as_ptr(), data.len(), seed, &mut completion);
-
-            unsafe { self.submit(&desc)? };
-            self.wait_for_completion(&completion)?;
-
-            Ok(completion.crc32_result())
+            let name = self.path.file_name()?;
+            let path = Path::new(SYSFS_DSA_PATH).join(name).join(attr);
+            std::fs::read_to_string(path).ok()
         }
 
-        /// Copy memory from source to destination.
-        pub fn memcpy(&self, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
-            if dst.len() < src.len() {
-                return Err(DsaError::BufferSizeMismatch {
-                    expected: src.len(),
-                    actual: dst.len(),
-                });
+        /// Replace a mapping whose work queue went away, once the work queue
+        /// is enabled again.
+        #[cold]
+        fn reopen(&self, current: &RwLock<Arc<Mapping>>) -> Result<(), DsaError> {
+            let mut mapping = current.write().unwrap();
+            if !mapping.liveness.is_gone() {
+                // Another thread reopened it
+                return Ok(());
+            }
+            // A sealed work queue must not open the device again
+            if self.sealed.is_some() || self.sysfs_state() != Some(WorkQueueState::Enabled) {
+                return Err(mapping.liveness.error());
+            }
+            let reopened = shared_mapping(&self.path)?;
+            // The lock stays with the old device file; release it, since a
+            // device node that kept its inode cannot be locked again otherwise
+            if mapping.exclusive.load(Ordering::Relaxed)
+                && !reopened.exclusive.load(Ordering::Relaxed)
+            {
+                mapping.unlock();
+                reopened.lock(&self.path)?;
             }
+            *mapping = reopened;
+            log::info!("reopened work queue {}", self.path.display());
+            Ok(())
+        }
 
-            if src.is_empty() {
-                return Ok(());
+        /// Build a `QueueFull` error, reading threshold and occupancy from
+        /// sysfs; a sealed work queue reports the threshold read when
+        /// sealing and no occupancy, and with compact errors sysfs is not
+        /// read.
+        #[cold]
+        fn queue_full_error(&self, elapsed: Duration) -> DsaError {
+            let read = |attr: &str| -> Option<u32> {
+                if self.compact_errors {
+                    return None;
+                }
+                self.sysfs_attr(attr)?.trim().parse().ok()
+            };
+            DsaError::QueueFull {
+                attempts: self.max_retries,
+                elapsed,
+                threshold: match &self.sealed {
+                    Some(sealed) => sealed.threshold,
+                    None => read("threshold"),
+                },
+                occupancy: read("occupancy"),
             }
+        }
 
-            let mut completion = DsaCompletionRecord::new();
-            let desc =
-                DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), src.len(), &mut completion);
+        /// Read everything the work queue would read lazily, so that it
+        /// never accesses a file again; see
+        /// [`DsaEngine::seal`](crate::DsaEngine::seal).
+        pub fn seal(&mut self) {
+            if self.sealed.is_some() {
+                return;
+            }
+            let capabilities = self.capabilities();
+            let _ = self.block_on_fault.set(capabilities.block_on_fault);
+            let threshold = self
+                .sysfs_attr("threshold")
+                .and_then(|raw| raw.trim().parse().ok());
+            // Start the completion thread while threads may be created
+            Reactor::global();
+            self.sealed = Some(Sealed {
+                capabilities,
+                threshold,
+            });
+        }
 
-            unsafe { self.submit(&desc)? };
-            self.wait_for_completion(&completion)
+        /// Returns true once the work queue is sealed.
+        pub fn is_sealed(&self) -> bool {
+            self.sealed.is_some()
         }
 
-        /// Fill memory with a 64-bit pattern.
-        pub fn memset(&self, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
-            if dst.is_empty() {
-                return Ok(());
+        /// Returns false once the work queue was found disabled or gone,
+        /// until an operation reopens it.
+        pub fn is_healthy(&self) -> bool {
+            match &self.portal {
+                Portal::Mapped(current) => !current.read().unwrap().liveness.is_gone(),
+                Portal::Emulated(_) | Portal::Mocked(_) => true,
             }
+        }
 
-            let mut completion = DsaCompletionRecord::new();
-            let desc = DsaHwDesc::mem_fill(dst.as_mut_ptr(), dst.len(), pattern, &mut completion);
+        /// Name of the work queue, e.g. `wq0.0`.
+        fn name(&self) -> Option<String> {
+            self.path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        }
 
+        /// Context for an error of `desc`, submitted at `submitted`.
+        #[cold]
+        fn error_context(&self, desc: &DsaHwDesc, submitted: Instant) -> ErrorContext {
+            ErrorContext::new(desc, self.name(), submitted.elapsed())
+        }
+
+        /// Attach the context of `desc` to `err`, with the device's software
+        /// error register if `err` is a hardware error and attaching is on;
+        /// `err` is returned as it is with compact errors.
+        #[cold]
+        fn add_context(&self, err: DsaError, desc: &DsaHwDesc, submitted: Instant) -> DsaError {
+            if self.compact_errors {
+                return err;
+            }
+            let device_error = if self.attach_device_errors && crate::swerr::is_hardware_error(&err)
+            {
+                self.device_error()
+            } else {
+                None
+            };
+            err.with_context(|| ErrorContext {
+                device_error,
+                ..self.error_context(desc, submitted)
+            })
+        }
+
+        /// Read the software error register of the work queue's device;
+        /// `None` for software work queues, sealed work queues or if it
+        /// cannot be read.
+        fn device_error(&self) -> Option<SoftwareError> {
+            if self.sealed.is_some() {
+                return None;
+            }
+            let name = self.path.file_name()?;
+            // The work queue's sysfs entry links into its device's directory
+            let wq_dir = std::fs::canonicalize(Path::new(SYSFS_DSA_PATH).join(name)).ok()?;
+            crate::swerr::read(wq_dir.parent()?).ok().flatten()
+        }
+
+        /// Attach the device's software error register to hardware errors
+        /// of blocking operations.
+        pub fn set_attach_device_errors(&mut self, attach: bool) {
+            self.attach_device_errors = attach;
+        }
+
+        /// Returns true if hardware errors carry the device's software error
+        /// register.
+        pub fn attaches_device_errors(&self) -> bool {
+            self.attach_device_errors
+        }
+
+        /// Build errors without allocating: no context is attached and a
+        /// full work queue does not read sysfs.
+        pub fn set_compact_errors(&mut self, compact: bool) {
+            self.compact_errors = compact;
+        }
+
+        /// Returns true if errors are built without allocating.
+        pub fn compact_errors(&self) -> bool {
+            self.compact_errors
+        }
+
+        /// Group batch descriptors by `region`-aligned virtual regions, or
+        /// keep argument order with `None`.
+        pub fn set_region_grouping(&mut self, region: Option<usize>) {
+            self.region_size = region;
+        }
+
+        /// Get the region size batch descriptors are grouped by, if any.
+        pub fn region_grouping(&self) -> Option<usize> {
+            self.region_size
+        }
+
+        /// Order the items of a batch by the virtual region of `addr`, if
+        /// grouping is on.
+        fn group_by_region<T>(&self, items: &mut [T], addr: impl Fn(&T) -> usize) {
+            if let Some(region) = self.region_size {
+                crate::iotlb::group_by_region(items, region, addr);
+            }
+        }
+
+        /// Wait for a completion record to be filled.
+        ///
+        /// Stops waiting with `DsaError::Cancelled` or
+        /// `DsaError::DeadlineExceeded` once `ctx` expires.
+        fn wait_for_completion(
+            &self,
+            record: &DsaCompletionRecord,
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            let waited = self.poll_completion(record, ctx);
+            if let Some(trace) = &self.trace {
+                trace.completed(record, &waited);
+            }
+            waited
+        }
+
+        /// Poll a completion record until it is filled, as
+        /// `wait_for_completion`.
+        fn poll_completion(
+            &self,
+            record: &DsaCompletionRecord,
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            let backoff = ctx.profile().map_or(self.backoff, Profile::backoff);
+            let mut poller = Poller::new(backoff);
+            for _ in 0..self.spin_iterations {
+                if let Portal::Mocked(mock) = &self.portal {
+                    mock.poll();
+                }
+                if record.is_complete() {
+                    return super::check_completion(record);
+                }
+                ctx.check()?;
+                poller.snooze();
+            }
+
+            // A work queue that went away never completes its descriptors
+            if let Portal::Mapped(current) = &self.portal {
+                if let Some(err) = self.check_liveness(&current.read().unwrap()) {
+                    return Err(err);
+                }
+            }
+
+            // Timeout - operation didn't complete in time
+            Err(DsaError::Timeout { context: None })
+        }
+
+        /// Check the result of a completed descriptor against software,
+        /// except for scripted results, which differ on purpose.
+        #[cfg(feature = "verify")]
+        unsafe fn verify(&self, desc: &DsaHwDesc) {
+            if !matches!(self.portal, Portal::Mocked(_)) {
+                crate::verify::check(desc);
+            }
+        }
+
+        /// Run a single descriptor using a preallocated arena slot.
+        ///
+        /// `build` creates the descriptor for the slot's completion record and
+        /// `finish` extracts the result once the operation has completed.
+        /// Only the status byte of the record is reset. If all slots are in
+        /// use, a heap-allocated record is used instead.
+        fn execute<R>(
+            &self,
+            build: impl FnOnce(&mut DsaCompletionRecord) -> DsaHwDesc,
+            finish: impl FnOnce(&DsaCompletionRecord) -> R,
+        ) -> Result<R, DsaError> {
+            self.execute_in(&OpContext::NONE, build, finish)
+        }
+
+        /// Run a single descriptor like `execute`, giving up once `ctx`
+        /// expires.
+        ///
+        /// A record that is given up on, by timeout or by `ctx`, is never
+        /// reused, since the hardware may still write it.
+        fn execute_in<R>(
+            &self,
+            ctx: &OpContext,
+            build: impl FnOnce(&mut DsaCompletionRecord) -> DsaHwDesc,
+            finish: impl FnOnce(&DsaCompletionRecord) -> R,
+        ) -> Result<R, DsaError> {
+            let _permit = self.admit()?;
+            let profile = ctx.profile().or(self.profile);
+            let Some(slot) = self.arena.acquire() else {
+                let mut completion = Box::new(DsaCompletionRecord::new());
+                let desc = build(&mut completion);
+                let submitted = Instant::now();
+                unsafe { self.submit_as(&desc, profile)? };
+                let waited = self
+                    .wait_for_completion(&completion, ctx)
+                    .map_err(|e| self.add_context(e, &desc, submitted));
+                return match waited {
+                    Ok(()) => {
+                        #[cfg(feature = "verify")]
+                        unsafe {
+                            self.verify(&desc)
+                        };
+                        Ok(finish(&completion))
+                    }
+                    Err(e) if abandons_record(&e) => {
+                        // The hardware may still write the record; keep it alive
+                        Box::leak(completion);
+                        self.abandoned.store(true, Ordering::Relaxed);
+                        Err(e)
+                    }
+                    Err(e) => Err(e),
+                };
+            };
+
+            let desc = slot.store(build(slot.record()));
+            let submitted = Instant::now();
+            unsafe { self.submit_as(desc, profile)? };
+            let waited = self
+                .wait_for_completion(slot.completion(), ctx)
+                .map_err(|e| self.add_context(e, desc, submitted));
+            match waited {
+                Ok(()) => {
+                    #[cfg(feature = "verify")]
+                    unsafe {
+                        self.verify(desc)
+                    };
+                    Ok(finish(slot.completion()))
+                }
+                Err(e) if abandons_record(&e) => {
+                    // The hardware may still write the record; never reuse the slot
+                    slot.abandon();
+                    self.abandoned.store(true, Ordering::Relaxed);
+                    Err(e)
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Run a single IAA descriptor with a heap-allocated completion
+        /// record, which IAA needs 64-byte aligned.
+        #[cfg(feature = "iaa")]
+        fn execute_iaa<R>(
+            &self,
+            build: impl FnOnce(&mut IaaCompletionRecord) -> DsaHwDesc,
+            finish: impl FnOnce(&IaaCompletionRecord) -> R,
+        ) -> Result<R, DsaError> {
+            let _permit = self.admit()?;
+            let mut completion = Box::new(IaaCompletionRecord::new());
+            let desc = build(&mut completion);
+            let submitted = Instant::now();
             unsafe { self.submit(&desc)? };
-            self.wait_for_completion(&completion)
+            let waited = self
+                .wait_for_completion(completion.as_dsa(), &OpContext::NONE)
+                .map_err(|e| self.add_context(e, &desc, submitted));
+            match waited {
+                Ok(()) => Ok(finish(&completion)),
+                Err(e) if abandons_record(&e) => {
+                    // The hardware may still write the record; keep it alive
+                    Box::leak(completion);
+                    self.abandoned.store(true, Ordering::Relaxed);
+                    Err(e)
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Submit a single descriptor without waiting for it to complete.
+        ///
+        /// The completion record is owned by the returned operation, which
+        /// is registered with the completion reactor.
+        pub(crate) fn start(
+            &self,
+            build: impl FnOnce(&mut DsaCompletionRecord) -> DsaHwDesc,
+        ) -> Result<Arc<InFlight>, DsaError> {
+            let op = InFlight::new();
+            let desc = build(op.record_mut());
+            self.start_in_flight(&op, &desc)?;
+            Ok(op)
+        }
+
+        /// Wait for an operation submitted with `start`, giving up after
+        /// the completion polling budget.
+        ///
+        /// An operation given up on stays with the completion reactor,
+        /// which keeps its record alive until the hardware writes it.
+        pub(crate) fn wait_started(&self, op: &InFlight) -> Result<(), DsaError> {
+            let mut poller = Poller::new(self.backoff);
+            for _ in 0..self.spin_iterations {
+                if op.is_done() {
+                    return Ok(());
+                }
+                poller.snooze();
+            }
+            self.abandoned.store(true, Ordering::Relaxed);
+            Err(DsaError::Timeout { context: None })
+        }
+
+        /// Submit `desc`, whose completion record belongs to `op`, and
+        /// register `op` with the completion reactor.
+        pub(crate) fn start_in_flight(
+            &self,
+            op: &Arc<InFlight>,
+            desc: &DsaHwDesc,
+        ) -> Result<(), DsaError> {
+            let mut permit = self.admit()?;
+            unsafe { self.submit(desc)? };
+            if !self.compact_errors {
+                op.set_origin(ErrorContext::new(desc, self.name(), Duration::ZERO));
+            }
+            op.track(&self.in_flight);
+            if let Some(share) = permit.share.take() {
+                op.release_on_completion(Box::new(share));
+            }
+            drop(permit);
+            if let Portal::Mapped(current) = &self.portal {
+                op.watch(&current.read().unwrap().liveness);
+            }
+            Reactor::global().register(Arc::clone(op));
+            Ok(())
+        }
+
+        /// Returns true once a wait was given up while the hardware may
+        /// still access the descriptor's memory.
+        pub(crate) fn abandoned(&self) -> bool {
+            self.abandoned.load(Ordering::Relaxed)
+        }
+
+        /// Wait for outstanding work before the portal is unmapped.
+        ///
+        /// A Drain descriptor completes once every descriptor submitted
+        /// before it has completed, including those whose synchronous wait
+        /// timed out. The reactor then settles the in-flight count. Waiting
+        /// is bounded by `DRAIN_TIMEOUT`, so a wedged device cannot hang drop.
+        fn drain_outstanding(&self) {
+            if self.in_flight() == 0 && !self.abandoned.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Portal::Mapped(current) = &self.portal {
+                if current.read().unwrap().liveness.is_gone() {
+                    // Outstanding operations were failed when it went away
+                    return;
+                }
+            }
+            let deadline = Instant::now() + DRAIN_TIMEOUT;
+
+            let mut completion = Box::new(DsaCompletionRecord::new());
+            let desc = DsaHwDesc::drain(&mut completion);
+            if let Err(e) = unsafe { self.submit(&desc) } {
+                log::warn!("cannot drain {} before closing: {e}", self.path.display());
+            } else {
+                while !completion.is_complete() {
+                    if Instant::now() >= deadline {
+                        log::warn!("drain of {} timed out", self.path.display());
+                        // The hardware may still write the record; keep it alive
+                        Box::leak(completion);
+                        return;
+                    }
+                    std::thread::yield_now();
+                }
+            }
+
+            while self.in_flight() > 0 {
+                if Instant::now() >= deadline {
+                    log::warn!(
+                        "{} operations still in flight on {} at close",
+                        self.in_flight(),
+                        self.path.display()
+                    );
+                    return;
+                }
+                std::thread::yield_now();
+            }
+        }
+
+        /// Submit prepared descriptors as one batch and wait for completion.
+        ///
+        /// A single descriptor is submitted directly, since the hardware
+        /// rejects batches of fewer than two descriptors. The caller owns
+        /// `descs` and `records` and must leak them if the wait is given up.
+        fn submit_batch(
+            &self,
+            descs: &[DsaHwDesc],
+            records: &[DsaCompletionRecord],
+        ) -> Result<(), DsaError> {
+            match descs.len() {
+                0 => Ok(()),
+                1 => {
+                    let _permit = self.admit()?;
+                    let submitted = Instant::now();
+                    unsafe { self.submit(&descs[0])? };
+                    self.wait_for_completion(&records[0], &OpContext::NONE)
+                        .map_err(|e| self.add_context(e, &descs[0], submitted))?;
+                    #[cfg(feature = "verify")]
+                    unsafe {
+                        self.verify(&descs[0])
+                    };
+                    Ok(())
+                }
+                n => self
+                    .execute(
+                        |completion| DsaHwDesc::batch(descs.as_ptr(), n, completion),
+                        |_| (),
+                    )
+                    .map_err(|e| batch_error(e, n)),
+            }
+        }
+
+        /// Run descriptors in batches and return their completion records.
+        ///
+        /// Stops at the first error that `tolerate` rejects. If a wait is
+        /// given up, the hardware may still read the descriptors and write
+        /// the records, so both are leaked.
+        fn run_batches(
+            &self,
+            descs: Vec<DsaHwDesc>,
+            records: Vec<DsaCompletionRecord>,
+            tolerate: impl Fn(&DsaError) -> bool,
+        ) -> Result<Vec<DsaCompletionRecord>, DsaError> {
+            debug_assert_eq!(descs.len(), records.len());
+            let chunks = descs
+                .chunks(DEFAULT_MAX_BATCH_SIZE)
+                .zip(records.chunks(DEFAULT_MAX_BATCH_SIZE));
+            for (batch, batch_records) in chunks {
+                match self.submit_batch(batch, batch_records) {
+                    Err(e) if !tolerate(&e) => {
+                        if abandons_record(&e) {
+                            std::mem::forget(descs);
+                            std::mem::forget(records);
+                            self.abandoned.store(true, Ordering::Relaxed);
+                        }
+                        return Err(e);
+                    }
+                    _ => {}
+                }
+            }
+            Ok(records)
+        }
+
+        /// Run descriptors in batches, failing on the first error, and
+        /// return their completion records.
+        fn run_batch(
+            &self,
+            descs: Vec<DsaHwDesc>,
+            records: Vec<DsaCompletionRecord>,
+        ) -> Result<Vec<DsaCompletionRecord>, DsaError> {
+            self.run_batches(descs, records, |_| false)
+        }
+
+        /// Run descriptors in batches, leaving each outcome in its record.
+        ///
+        /// Unlike `run_batch`, a failed descriptor is not an error: its
+        /// record holds the status. An error is returned only if a batch
+        /// could not be submitted or did not complete in time.
+        pub(crate) fn run_descriptors(
+            &self,
+            descs: Vec<DsaHwDesc>,
+            records: Vec<DsaCompletionRecord>,
+        ) -> Result<Vec<DsaCompletionRecord>, DsaError> {
+            self.run_batches(descs, records, |e| {
+                matches!(
+                    e,
+                    DsaError::BatchFailed { .. }
+                        | DsaError::OperationFailed { .. }
+                        | DsaError::PageFault { .. }
+                )
+            })
+        }
+
+        /// Compute CRC32 checksums of many buffers using batch submission.
+        pub fn crc32_many(&self, bufs: &[&[u8]], seed: u32) -> Result<Vec<u32>, DsaError> {
+            self.crc32_many_chunked(bufs, seed, MAX_XFER_SIZE)
+        }
+
+        /// Compute CRC32 checksums of many buffers, batching those of at
+        /// most `max_xfer` bytes and chaining the others across descriptors.
+        pub(super) fn crc32_many_chunked(
+            &self,
+            bufs: &[&[u8]],
+            seed: u32,
+            max_xfer: usize,
+        ) -> Result<Vec<u32>, DsaError> {
+            // Empty buffers keep the seed and are not submitted to hardware
+            let mut crcs = vec![seed; bufs.len()];
+            for (crc, buf) in crcs.iter_mut().zip(bufs) {
+                if buf.len() > max_xfer {
+                    *crc = self.crc32_chunked(buf, seed, &OpContext::NONE, max_xfer)?;
+                }
+            }
+            let mut pending: Vec<usize> = (0..bufs.len())
+                .filter(|&i| !bufs[i].is_empty() && bufs[i].len() <= max_xfer)
+                .collect();
+            self.group_by_region(&mut pending, |&i| bufs[i].as_ptr() as usize);
+
+            for chunk in pending.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&i, record)| {
+                        DsaHwDesc::crc_gen(bufs[i].as_ptr(), bufs[i].len(), seed, record)
+                    })
+                    .collect();
+
+                let records = self.run_batch(descs, records)?;
+
+                for (&i, record) in chunk.iter().zip(&records) {
+                    crcs[i] = record.crc32_result();
+                }
+            }
+
+            Ok(crcs)
+        }
+
+        /// Compute CRC32 checksum of data.
+        pub fn crc32(&self, data: &[u8], seed: u32) -> Result<u32, DsaError> {
+            self.crc32_in(data, seed, &OpContext::NONE)
+        }
+
+        /// Compute CRC32 checksum of data, giving up once `ctx` expires.
+        pub(crate) fn crc32_in(
+            &self,
+            data: &[u8],
+            seed: u32,
+            ctx: &OpContext,
+        ) -> Result<u32, DsaError> {
+            self.crc32_chunked(data, seed, ctx, MAX_XFER_SIZE)
+        }
+
+        /// Compute CRC32 checksum of data in descriptors of at most
+        /// `max_xfer` bytes, chaining the CRC from one to the next.
+        pub(super) fn crc32_chunked(
+            &self,
+            data: &[u8],
+            seed: u32,
+            ctx: &OpContext,
+            max_xfer: usize,
+        ) -> Result<u32, DsaError> {
+            data.chunks(max_xfer).try_fold(seed, |crc, chunk| {
+                self.execute_in(
+                    ctx,
+                    |completion| DsaHwDesc::crc_gen(chunk.as_ptr(), chunk.len(), crc, completion),
+                    |completion| completion.crc32_result(),
+                )
+            })
+        }
+
+        /// Copy memory from source to destination.
+        pub fn memcpy(&self, dst: &mut [u8], src: &[u8]) -> Result<(), DsaError> {
+            self.memcpy_in(dst, src, &OpContext::NONE)
+        }
+
+        /// Copy memory from source to destination, giving up once `ctx`
+        /// expires.
+        pub(crate) fn memcpy_in(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            if dst.len() < src.len() {
+                return Err(DsaError::BufferSizeMismatch {
+                    expected: src.len(),
+                    actual: dst.len(),
+                });
+            }
+
+            if src.is_empty() {
+                return Ok(());
+            }
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(src.len())?;
+
+            self.execute_in(
+                ctx,
+                |completion| {
+                    DsaHwDesc::mem_move(dst.as_mut_ptr(), src.as_ptr(), src.len(), completion)
+                },
+                |_| (),
+            )
+        }
+
+        /// Copy memory from source to destination and compute the CRC32 of
+        /// the source.
+        pub fn copy_crc(&self, dst: &mut [u8], src: &[u8], seed: u32) -> Result<u32, DsaError> {
+            if dst.len() < src.len() {
+                return Err(DsaError::BufferSizeMismatch {
+                    expected: src.len(),
+                    actual: dst.len(),
+                });
+            }
+
+            self.copy_crc_chunked(dst, src, seed, MAX_XFER_SIZE)
+        }
+
+        /// Copy and compute the CRC32 in descriptors of at most `max_xfer`
+        /// bytes, chaining the CRC from one to the next.
+        pub(super) fn copy_crc_chunked(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            seed: u32,
+            max_xfer: usize,
+        ) -> Result<u32, DsaError> {
+            let mut chunks = dst.chunks_mut(max_xfer).zip(src.chunks(max_xfer));
+            chunks.try_fold(seed, |crc, (dst, src)| {
+                self.execute(
+                    |completion| {
+                        DsaHwDesc::copy_crc(
+                            dst.as_mut_ptr(),
+                            src.as_ptr(),
+                            src.len(),
+                            crc,
+                            completion,
+                        )
+                    },
+                    |completion| completion.crc32_result(),
+                )
+            })
+        }
+
+        /// Fill memory with a 64-bit pattern.
+        pub fn memset(&self, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
+            self.memset_in(dst, pattern, &OpContext::NONE)
+        }
+
+        /// Fill memory with a 64-bit pattern, giving up once `ctx` expires.
+        pub(crate) fn memset_in(
+            &self,
+            dst: &mut [u8],
+            pattern: u64,
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            if dst.is_empty() {
+                return Ok(());
+            }
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(dst.len())?;
+
+            self.execute_in(
+                ctx,
+                |completion| DsaHwDesc::mem_fill(dst.as_mut_ptr(), dst.len(), pattern, completion),
+                |_| (),
+            )
+        }
+
+        /// Wait until every descriptor submitted before, including those
+        /// whose wait was given up, has completed, giving up once `ctx`
+        /// expires.
+        pub(crate) fn drain_in(&self, ctx: &OpContext) -> Result<(), DsaError> {
+            self.execute_in(ctx, DsaHwDesc::drain, |_| ())
+        }
+
+        /// Fill many memory regions with a 64-bit pattern using batch submission.
+        pub fn memset_many(&self, bufs: &mut [&mut [u8]], pattern: u64) -> Result<(), DsaError> {
+            let mut pending: Vec<usize> =
+                (0..bufs.len()).filter(|&i| !bufs[i].is_empty()).collect();
+            self.group_by_region(&mut pending, |&i| bufs[i].as_ptr() as usize);
+
+            for chunk in pending.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&i, record)| {
+                        DsaHwDesc::mem_fill(bufs[i].as_mut_ptr(), bufs[i].len(), pattern, record)
+                    })
+                    .collect();
+
+                self.run_batch(descs, records)?;
+            }
+
+            Ok(())
+        }
+
+        /// Check whether each buffer consists of a repeated 64-bit pattern,
+        /// using batch submission.
+        ///
+        /// Buffer lengths must be multiples of 8 bytes.
+        pub fn match_pattern_many(
+            &self,
+            bufs: &[&[u8]],
+            pattern: u64,
+        ) -> Result<Vec<bool>, DsaError> {
+            // Empty buffers match and are not submitted to hardware
+            let mut matches = vec![true; bufs.len()];
+            let mut pending: Vec<usize> =
+                (0..bufs.len()).filter(|&i| !bufs[i].is_empty()).collect();
+            self.group_by_region(&mut pending, |&i| bufs[i].as_ptr() as usize);
+
+            for chunk in pending.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&i, record)| {
+                        DsaHwDesc::compare_imm(bufs[i].as_ptr(), bufs[i].len(), pattern, record)
+                    })
+                    .collect();
+
+                let records = self.run_batch(descs, records)?;
+
+                for (&i, record) in chunk.iter().zip(&records) {
+                    matches[i] = record.compare_result();
+                }
+            }
+
+            Ok(matches)
+        }
+
+        /// Compare each pair of buffers, using batch submission.
+        pub fn compare_many(&self, pairs: &[(&[u8], &[u8])]) -> Result<Vec<bool>, DsaError> {
+            super::check_pairs(pairs)?;
+            // Empty pairs are equal and are not submitted to hardware
+            let mut equal = vec![true; pairs.len()];
+            let mut pending: Vec<usize> = (0..pairs.len())
+                .filter(|&i| !pairs[i].0.is_empty())
+                .collect();
+            self.group_by_region(&mut pending, |&i| pairs[i].0.as_ptr() as usize);
+
+            for chunk in pending.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&i, record)| {
+                        let (a, b) = pairs[i];
+                        DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), record)
+                    })
+                    .collect();
+
+                let records = self.run_batch(descs, records)?;
+
+                for (&i, record) in chunk.iter().zip(&records) {
+                    equal[i] = record.compare_result();
+                }
+            }
+
+            Ok(equal)
+        }
+
+        /// Copy a `width` x `height` rectangle between strided buffers, one
+        /// descriptor per row, using batch submission.
+        pub fn copy_rect(
+            &self,
+            dst: &mut [u8],
+            dst_stride: usize,
+            src: &[u8],
+            src_stride: usize,
+            width: usize,
+            height: usize,
+        ) -> Result<(), DsaError> {
+            super::check_rect(dst.len(), dst_stride, src.len(), src_stride, width, height)?;
+            if width == 0 || height == 0 {
+                return Ok(());
+            }
+
+            let rows: Vec<usize> = (0..height).collect();
+            for chunk in rows.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&row, record)| {
+                        // SAFETY: check_rect guarantees every row is in bounds
+                        let (d, s) = unsafe {
+                            (
+                                dst.as_mut_ptr().add(row * dst_stride),
+                                src.as_ptr().add(row * src_stride),
+                            )
+                        };
+                        DsaHwDesc::mem_move(d, s, width, record)
+                    })
+                    .collect();
+
+                self.run_batch(descs, records)?;
+            }
+
+            Ok(())
+        }
+
+        /// Copy `element_len` bytes from each offset of `src` into
+        /// consecutive elements of `dst`, using batch submission.
+        pub fn gather(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            offsets: &[usize],
+            element_len: usize,
+        ) -> Result<(), DsaError> {
+            super::check_gather(dst.len(), src.len(), offsets, element_len)?;
+            if element_len == 0 {
+                return Ok(());
+            }
+
+            let mut elements: Vec<(usize, usize)> = offsets.iter().copied().enumerate().collect();
+            let base = src.as_ptr() as usize;
+            self.group_by_region(&mut elements, |&(_, offset)| base + offset);
+            for chunk in elements.chunks(DEFAULT_MAX_BATCH_SIZE) {
+                let mut records = vec![DsaCompletionRecord::new(); chunk.len()];
+                let descs: Vec<DsaHwDesc> = chunk
+                    .iter()
+                    .zip(records.iter_mut())
+                    .map(|(&(i, offset), record)| {
+                        // SAFETY: check_gather guarantees every element is in bounds
+                        let (d, s) = unsafe {
+                            (
+                                dst.as_mut_ptr().add(i * element_len),
+                                src.as_ptr().add(offset),
+                            )
+                        };
+                        DsaHwDesc::mem_move(d, s, element_len, record)
+                    })
+                    .collect();
+
+                self.run_batch(descs, records)?;
+            }
+
+            Ok(())
+        }
+
+        /// Zero `dst` and copy each `(offset, src)` extent into it, using
+        /// batch submission.
+        pub fn materialize(
+            &self,
+            dst: &mut [u8],
+            extents: &[(usize, &[u8])],
+        ) -> Result<(), DsaError> {
+            // Only the holes are filled, so no descriptor depends on another
+            let (order, holes) = super::check_extents(dst.len(), extents)?;
+            let mut records = vec![DsaCompletionRecord::new(); holes.len() + order.len()];
+            let mut records_iter = records.iter_mut();
+            let mut descs = Vec::with_capacity(records_iter.len());
+            for (hole, record) in holes.iter().zip(records_iter.by_ref()) {
+                // SAFETY: check_extents guarantees every hole is in bounds
+                let d = unsafe { dst.as_mut_ptr().add(hole.start) };
+                descs.push(DsaHwDesc::mem_fill(d, hole.len(), 0, record));
+            }
+            for (&i, record) in order.iter().zip(records_iter) {
+                let (offset, src) = extents[i];
+                // SAFETY: check_extents guarantees every extent is in bounds
+                let d = unsafe { dst.as_mut_ptr().add(offset) };
+                descs.push(DsaHwDesc::mem_move(d, src.as_ptr(), src.len(), record));
+            }
+
+            self.run_batch(descs, records)?;
+            Ok(())
         }
 
         /// Compare two memory regions.
         pub fn memcmp(&self, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
+            self.memcmp_in(a, b, &OpContext::NONE)
+        }
+
+        /// Compare two memory regions, giving up once `ctx` expires.
+        pub(crate) fn memcmp_in(
+            &self,
+            a: &[u8],
+            b: &[u8],
+            ctx: &OpContext,
+        ) -> Result<bool, DsaError> {
             if a.len() != b.len() {
                 return Err(DsaError::BufferSizeMismatch {
                     expected: a.len(),
@@ -285,31 +1967,361 @@ mod linux_impl {
             if a.is_empty() {
                 return Ok(true);
             }
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(a.len())?;
+
+            self.execute_in(
+                ctx,
+                |completion| DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), completion),
+                |completion| completion.compare_result(),
+            )
+        }
 
-            let mut completion = DsaCompletionRecord::new();
-            let desc = DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), &mut completion);
+        /// Find the offset of the first difference between two memory regions.
+        pub fn mismatch(&self, a: &[u8], b: &[u8]) -> Result<Option<usize>, DsaError> {
+            if a.len() != b.len() {
+                return Err(DsaError::BufferSizeMismatch {
+                    expected: a.len(),
+                    actual: b.len(),
+                });
+            }
 
-            unsafe { self.submit(&desc)? };
-            self.wait_for_completion(&completion)?;
+            if a.is_empty() {
+                return Ok(None);
+            }
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(a.len())?;
+
+            self.execute(
+                |completion| DsaHwDesc::compare(a.as_ptr(), b.as_ptr(), a.len(), completion),
+                |completion| completion.mismatch_offset(),
+            )
+        }
+
+        /// Find the first byte that deviates from a repeating 64-bit pattern.
+        ///
+        /// The hardware checks the 8-byte-aligned prefix; the remaining tail
+        /// and the exact byte within a mismatching word are checked on the CPU.
+        pub fn compare_pattern(&self, buf: &[u8], pattern: u64) -> Result<Option<usize>, DsaError> {
+            let hw_len = buf.len() & !7;
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(hw_len)?;
+            let start = if hw_len == 0 {
+                0
+            } else {
+                let mismatch = self.execute(
+                    |completion| DsaHwDesc::compare_imm(buf.as_ptr(), hw_len, pattern, completion),
+                    |completion| completion.mismatch_offset(),
+                )?;
+
+                match mismatch {
+                    Some(offset) => offset & !7,
+                    None => hw_len,
+                }
+            };
+
+            Ok(super::find_pattern_mismatch(buf, pattern, start))
+        }
+
+        /// Create a delta record of the words in which `modified` differs
+        /// from `original`.
+        ///
+        /// Returns the size of the record written to `delta`, or `None` if
+        /// it does not fit.
+        pub fn create_delta(
+            &self,
+            original: &[u8],
+            modified: &[u8],
+            delta: &mut [u8],
+        ) -> Result<Option<usize>, DsaError> {
+            crate::delta::check_create(original, modified)?;
+            if original.is_empty() {
+                return Ok(Some(0));
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::create_delta(
+                        original.as_ptr(),
+                        modified.as_ptr(),
+                        original.len(),
+                        delta.as_mut_ptr(),
+                        delta.len(),
+                        completion,
+                    )
+                },
+                |completion| completion.delta_record_size(),
+            )
+        }
+
+        /// Write the words recorded in `delta` into `dst`.
+        pub fn apply_delta(&self, delta: &[u8], dst: &mut [u8]) -> Result<(), DsaError> {
+            crate::delta::check_entries(delta, dst.len())?;
+            if delta.is_empty() {
+                return Ok(());
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::apply_delta(
+                        delta.as_ptr(),
+                        delta.len(),
+                        dst.as_mut_ptr(),
+                        dst.len(),
+                        completion,
+                    )
+                },
+                |_| (),
+            )
+        }
+
+        /// Protect the data blocks of `src` into `dst`.
+        ///
+        /// The caller checks that `src` holds whole blocks and `dst` their
+        /// protected form.
+        pub(crate) fn dif_insert(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            if src.is_empty() {
+                return Ok(());
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::dif_insert(
+                        src.as_ptr(),
+                        dst.as_mut_ptr(),
+                        src.len(),
+                        dif_flags,
+                        tags,
+                        completion,
+                    )
+                },
+                |_| (),
+            )
+        }
+
+        /// Check the protected blocks of `src` and copy their data to `dst`.
+        ///
+        /// The caller checks that `src` holds whole protected blocks and
+        /// `dst` their data.
+        pub(crate) fn dif_strip(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            if src.is_empty() {
+                return Ok(());
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::dif_strip(
+                        src.as_ptr(),
+                        dst.as_mut_ptr(),
+                        src.len(),
+                        dif_flags,
+                        tags,
+                        completion,
+                    )
+                },
+                |_| (),
+            )
+        }
+
+        /// Check the protected blocks of `src`.
+        pub(crate) fn dif_check(
+            &self,
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            if src.is_empty() {
+                return Ok(());
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::dif_check(src.as_ptr(), src.len(), dif_flags, tags, completion)
+                },
+                |_| (),
+            )
+        }
+
+        /// Check the protected blocks of `src` and copy them to `dst` with
+        /// new tags.
+        ///
+        /// The caller checks that `src` holds whole protected blocks and
+        /// `dst` is at least as long.
+        pub(crate) fn dif_update(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            src_tags: DifTags,
+            dest_tags: DifTags,
+        ) -> Result<(), DsaError> {
+            if src.is_empty() {
+                return Ok(());
+            }
+
+            self.execute(
+                |completion| {
+                    DsaHwDesc::dif_update(
+                        src.as_ptr(),
+                        dst.as_mut_ptr(),
+                        src.len(),
+                        dif_flags,
+                        src_tags,
+                        dest_tags,
+                        completion,
+                    )
+                },
+                |_| (),
+            )
+        }
+
+        /// Prefetch the device's address translations for `buf`.
+        pub fn transl_fetch(&self, buf: &[u8]) -> Result<(), DsaError> {
+            if buf.is_empty() {
+                return Ok(());
+            }
+            #[cfg(feature = "strict-validation")]
+            crate::strict::check_len(buf.len())?;
+
+            self.execute(
+                |completion| DsaHwDesc::transl_fetch(buf.as_ptr(), buf.len(), completion),
+                |_| (),
+            )
+        }
+
+        /// Compute a CRC64 of data on an IAA work queue.
+        #[cfg(feature = "iaa")]
+        pub fn crc64(&self, data: &[u8], params: &Crc64Params) -> Result<u64, DsaError> {
+            if data.is_empty() {
+                return Ok(crate::iaa::software_crc64(data, params));
+            }
+
+            self.execute_iaa(
+                |completion| DsaHwDesc::iaa_crc64(data.as_ptr(), data.len(), params, completion),
+                |completion| completion.crc,
+            )
+        }
+
+        /// Compress data to a raw DEFLATE stream on an IAA work queue.
+        #[cfg(feature = "iaa")]
+        pub fn compress(&self, dst: &mut [u8], src: &[u8]) -> Result<usize, DsaError> {
+            if src.is_empty() {
+                return crate::iaa::compress_empty(dst);
+            }
+
+            self.execute_iaa(
+                |completion| {
+                    DsaHwDesc::iaa_compress(
+                        dst.as_mut_ptr(),
+                        dst.len(),
+                        src.as_ptr(),
+                        src.len(),
+                        completion,
+                    )
+                },
+                |completion| completion.output_size as usize,
+            )
+        }
 
-            Ok(completion.compare_result())
+        /// Decompress a raw DEFLATE stream on an IAA work queue.
+        #[cfg(feature = "iaa")]
+        pub fn decompress(&self, dst: &mut [u8], src: &[u8]) -> Result<usize, DsaError> {
+            self.execute_iaa(
+                |completion| {
+                    DsaHwDesc::iaa_decompress(
+                        dst.as_mut_ptr(),
+                        dst.len(),
+                        src.as_ptr(),
+                        src.len(),
+                        completion,
+                    )
+                },
+                |completion| completion.output_size as usize,
+            )
         }
 
         /// Execute a no-op operation (for testing/benchmarking).
         pub fn noop(&self) -> Result<(), DsaError> {
-            let mut completion = DsaCompletionRecord::new();
-            let desc = DsaHwDesc::noop(&mut completion);
+            self.execute(DsaHwDesc::noop, |_| ())
+        }
 
+        /// Execute a no-op and return how long the portal took to accept it.
+        pub(crate) fn timed_noop(&self) -> Result<Duration, DsaError> {
+            let mut completion = Box::new(DsaCompletionRecord::new());
+            let desc = DsaHwDesc::noop(&mut completion);
+            let _permit = self.admit()?;
+            let start = Instant::now();
             unsafe { self.submit(&desc)? };
-            self.wait_for_completion(&completion)
+            let accepted = start.elapsed();
+            let waited = self
+                .wait_for_completion(&completion, &OpContext::NONE)
+                .map_err(|e| self.add_context(e, &desc, start));
+            match waited {
+                Ok(()) => Ok(accepted),
+                Err(e @ DsaError::Timeout { .. }) => {
+                    // The hardware may still write the record; keep it alive
+                    Box::leak(completion);
+                    self.abandoned.store(true, Ordering::Relaxed);
+                    Err(e)
+                }
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    /// Replace an open error with a sysfs-derived reason, if one is found.
+    fn diagnose_open_failure(path: &Path, err: DsaError) -> DsaError {
+        match crate::device::diagnose_wq(path) {
+            Some(reason) => DsaError::WorkQueueUnavailable {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string()),
+                reason,
+            },
+            None => err,
+        }
+    }
+
+    /// Convert an error from a batch descriptor into `DsaError::BatchFailed`.
+    ///
+    /// For batch descriptors, the completion record's `bytes_completed` field
+    /// holds the number of descriptors completed.
+    fn batch_error(err: DsaError, total: usize) -> DsaError {
+        let (status, descriptors_completed) = match err {
+            DsaError::OperationFailed {
+                status,
+                bytes_completed,
+                ..
+            } => (status, bytes_completed),
+            DsaError::PageFault {
+                bytes_completed, ..
+            } => (0x03, bytes_completed),
+            other => return other,
+        };
+        DsaError::BatchFailed {
+            status,
+            descriptors_completed,
+            descriptors_total: total as u32,
         }
     }
 
     impl Drop for WorkQueue {
         fn drop(&mut self) {
-            unsafe {
-                libc::munmap(self.portal as *mut libc::c_void, self.portal_size);
-            }
+            // The portal itself is unmapped with the last handle sharing it
+            self.drain_outstanding();
         }
     }
 }
@@ -318,16 +2330,18 @@ mod linux_impl {
 // Non-Linux Stub Implementation
 // ============================================================================
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_software)]
 mod windows_impl {
     use super::*;
+    use crate::opcode::DsaOpcode;
 
     /// Software-based work queue for Windows.
     ///
     /// On Windows, hardware DSA access is not available through userspace APIs.
     /// Intel's own DML library also uses software fallback on Windows.
     /// This implementation provides optimized software implementations for:
-    /// - CRC32 (using crc32fast which uses SIMD when available)
+    /// - CRC32 (using the configured [`SoftwareCrc`](crate::crc::SoftwareCrc)
+    ///   backend, the SSE4.2 `crc32` instruction by default)
     /// - Memory operations (using optimized std library functions)
     ///
     /// While not as fast as hardware DSA, these implementations are still
@@ -335,8 +2349,6 @@ mod windows_impl {
     pub struct WorkQueue {
         /// Indicates this is a software-only work queue
         is_software: bool,
-        /// CRC32 hasher for software fallback
-        crc_hasher: crc32fast::Hasher,
     }
 
     impl WorkQueue {
@@ -346,36 +2358,209 @@ mod windows_impl {
         /// since hardware DSA access is not available.
         pub fn open(_path: &Path) -> Result<Self, DsaError> {
             log::info!("Opening software-emulated DSA work queue (Windows)");
-            Ok(Self {
-                is_software: true,
-                crc_hasher: crc32fast::Hasher::new(),
-            })
+            Ok(Self { is_software: true })
+        }
+
+        /// Emulated work queues execute descriptors, which the Windows
+        /// software work queue does not build; not supported.
+        pub fn emulated(_emulator: Arc<Emulator>) -> Result<Self, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        /// Mocked work queues execute descriptors too; not supported.
+        pub fn mocked(_mock: Arc<MockBackend>) -> Result<Self, DsaError> {
+            Err(DsaError::PlatformNotSupported)
         }
 
         pub fn set_wq_type(&mut self, _wq_type: WorkQueueType) {}
         pub fn set_max_retries(&mut self, _retries: u32) {}
         pub fn set_spin_iterations(&mut self, _iterations: u32) {}
+        pub fn set_backoff(&mut self, _backoff: Backoff) {}
+        pub fn set_serialized(&mut self, _budget: Option<usize>) {}
+
+        /// Operations complete synchronously; nothing is retried or polled.
+        pub fn max_retries(&self) -> u32 {
+            0
+        }
+
+        pub fn spin_iterations(&self) -> u32 {
+            0
+        }
+
+        pub fn backoff(&self) -> Backoff {
+            Backoff::default()
+        }
+
+        /// Operations complete synchronously; never serialized.
+        pub fn serialized(&self) -> Option<usize> {
+            None
+        }
+
+        /// Descriptors are not used; nothing is traced.
+        pub(crate) fn set_trace(&mut self, _trace: Option<TraceRing>) {}
+
+        pub(crate) fn trace(&self) -> Option<&TraceRing> {
+            None
+        }
+
+        /// Descriptors are not used; profiles have no effect.
+        pub fn set_profile(&mut self, _profile: Option<Profile>) {}
+
+        /// Operations complete synchronously; nothing is shared.
+        pub fn set_fair_share(&mut self, _share: Option<FairShare>) -> Result<(), DsaError> {
+            Ok(())
+        }
+
+        pub fn fair_share(&self) -> Option<FairShare> {
+            None
+        }
+
+        /// Operations never access files; nothing to read up front.
+        pub fn seal(&mut self) {}
+
+        /// Operations never access files; always sealed.
+        pub fn is_sealed(&self) -> bool {
+            true
+        }
+
+        /// There is no device that could go away; always healthy.
+        pub fn is_healthy(&self) -> bool {
+            true
+        }
+
+        /// Operations fail without a device; nothing to attach.
+        pub fn set_attach_device_errors(&mut self, _attach: bool) {}
+
+        pub fn attaches_device_errors(&self) -> bool {
+            false
+        }
+
+        /// Software errors carry no context; they are always compact.
+        pub fn set_compact_errors(&mut self, _compact: bool) {}
+
+        pub fn compact_errors(&self) -> bool {
+            true
+        }
+
+        pub fn set_region_grouping(&mut self, _region: Option<usize>) {}
+
+        pub fn region_grouping(&self) -> Option<usize> {
+            None
+        }
+
+        pub fn profile(&self) -> Option<Profile> {
+            None
+        }
 
         pub fn wq_type(&self) -> WorkQueueType {
             WorkQueueType::Shared
         }
 
+        pub fn path(&self) -> &Path {
+            Path::new("")
+        }
+
+        /// Operations complete synchronously; always zero.
+        pub fn in_flight(&self) -> usize {
+            0
+        }
+
+        /// Operations complete synchronously; there is always room.
+        pub(crate) fn wait_for_room(&self, _budget: usize) -> Result<(), DsaError> {
+            Ok(())
+        }
+
+        /// Report the operations the software fallback implements.
+        pub fn capabilities(&self) -> Capabilities {
+            Capabilities::software(&[
+                DsaOpcode::Noop,
+                DsaOpcode::MemMove,
+                DsaOpcode::MemFill,
+                DsaOpcode::Compare,
+                DsaOpcode::CompareImm,
+                DsaOpcode::CreateDelta,
+                DsaOpcode::ApplyDelta,
+                DsaOpcode::CrcGen,
+                DsaOpcode::CopyCrc,
+                DsaOpcode::DifCheck,
+                DsaOpcode::DifInsert,
+                DsaOpcode::DifStrip,
+                DsaOpcode::DifUpdate,
+            ])
+        }
+
+        /// Operations complete synchronously; only orders ordinary stores.
+        pub fn submission_fence(&self) {
+            crate::submit::sfence();
+        }
+
+        /// Software work queues are private to the process; always succeeds.
+        pub fn lock_exclusive(&self) -> Result<(), DsaError> {
+            Ok(())
+        }
+
         /// Returns true if this is a software-emulated work queue.
         pub fn is_software_fallback(&self) -> bool {
             self.is_software
         }
 
-        /// Compute CRC32 checksum using crc32fast (SIMD-accelerated).
+        /// Compute CRC32 checksum with the software CRC backend.
         ///
-        /// Uses the IEEE polynomial (same as DSA hardware).
+        /// Computes CRC-32C, the same CRC as DSA hardware.
         pub fn crc32(&self, data: &[u8], seed: u32) -> Result<u32, DsaError> {
-            if data.is_empty() {
-                return Ok(seed);
-            }
+            Ok(crate::crc::software_crc32(data, seed))
+        }
 
-            let mut hasher = crc32fast::Hasher::new_with_initial(seed);
-            hasher.update(data);
-            Ok(hasher.finalize())
+        /// Compute a CRC32 if `ctx` has not expired; software operations
+        /// cannot be interrupted.
+        pub(crate) fn crc32_in(
+            &self,
+            data: &[u8],
+            seed: u32,
+            ctx: &OpContext,
+        ) -> Result<u32, DsaError> {
+            ctx.check()?;
+            self.crc32(data, seed)
+        }
+
+        pub(crate) fn memcpy_in(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            ctx.check()?;
+            self.memcpy(dst, src)
+        }
+
+        pub(crate) fn memset_in(
+            &self,
+            dst: &mut [u8],
+            pattern: u64,
+            ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            ctx.check()?;
+            self.memset(dst, pattern)
+        }
+
+        /// Operations complete synchronously; nothing is outstanding.
+        pub(crate) fn drain_in(&self, _ctx: &OpContext) -> Result<(), DsaError> {
+            Ok(())
+        }
+
+        pub(crate) fn memcmp_in(
+            &self,
+            a: &[u8],
+            b: &[u8],
+            ctx: &OpContext,
+        ) -> Result<bool, DsaError> {
+            ctx.check()?;
+            self.memcmp(a, b)
+        }
+
+        /// Compute CRC32 checksums of many buffers.
+        pub fn crc32_many(&self, bufs: &[&[u8]], seed: u32) -> Result<Vec<u32>, DsaError> {
+            bufs.iter().map(|buf| self.crc32(buf, seed)).collect()
         }
 
         /// Copy memory using optimized standard library copy.
@@ -395,6 +2580,12 @@ mod windows_impl {
             Ok(())
         }
 
+        /// Copy memory and compute the CRC32 of the source.
+        pub fn copy_crc(&self, dst: &mut [u8], src: &[u8], seed: u32) -> Result<u32, DsaError> {
+            self.memcpy(dst, src)?;
+            self.crc32(src, seed)
+        }
+
         /// Fill memory with a 64-bit pattern.
         pub fn memset(&self, dst: &mut [u8], pattern: u64) -> Result<(), DsaError> {
             if dst.is_empty() {
@@ -408,26 +2599,221 @@ mod windows_impl {
                 chunk.copy_from_slice(&pattern_bytes);
             }
 
-            // Handle remaining bytes
-            let remainder = dst.len() % 8;
-            if remainder > 0 {
-                let start = dst.len() - remainder;
-                dst[start..].copy_from_slice(&pattern_bytes[..remainder]);
-            }
+            // Handle remaining bytes
+            let remainder = dst.len() % 8;
+            if remainder > 0 {
+                let start = dst.len() - remainder;
+                dst[start..].copy_from_slice(&pattern_bytes[..remainder]);
+            }
+
+            Ok(())
+        }
+
+        /// Fill many memory regions with a 64-bit pattern.
+        pub fn memset_many(&self, bufs: &mut [&mut [u8]], pattern: u64) -> Result<(), DsaError> {
+            for buf in bufs.iter_mut() {
+                self.memset(buf, pattern)?;
+            }
+            Ok(())
+        }
+
+        /// Check whether each buffer consists of a repeated 64-bit pattern.
+        pub fn match_pattern_many(
+            &self,
+            bufs: &[&[u8]],
+            pattern: u64,
+        ) -> Result<Vec<bool>, DsaError> {
+            Ok(bufs
+                .iter()
+                .map(|buf| super::find_pattern_mismatch(buf, pattern, 0).is_none())
+                .collect())
+        }
+
+        /// Compare each pair of buffers.
+        pub fn compare_many(&self, pairs: &[(&[u8], &[u8])]) -> Result<Vec<bool>, DsaError> {
+            super::check_pairs(pairs)?;
+            Ok(pairs.iter().map(|(a, b)| a == b).collect())
+        }
+
+        /// Copy a `width` x `height` rectangle between strided buffers.
+        pub fn copy_rect(
+            &self,
+            dst: &mut [u8],
+            dst_stride: usize,
+            src: &[u8],
+            src_stride: usize,
+            width: usize,
+            height: usize,
+        ) -> Result<(), DsaError> {
+            super::check_rect(dst.len(), dst_stride, src.len(), src_stride, width, height)?;
+            if width == 0 {
+                return Ok(());
+            }
+
+            for row in 0..height {
+                let (d, s) = (row * dst_stride, row * src_stride);
+                dst[d..d + width].copy_from_slice(&src[s..s + width]);
+            }
+            Ok(())
+        }
+
+        /// Copy `element_len` bytes from each offset of `src` into
+        /// consecutive elements of `dst`.
+        pub fn gather(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            offsets: &[usize],
+            element_len: usize,
+        ) -> Result<(), DsaError> {
+            super::check_gather(dst.len(), src.len(), offsets, element_len)?;
+            if element_len == 0 {
+                return Ok(());
+            }
+
+            for (element, &offset) in dst.chunks_exact_mut(element_len).zip(offsets) {
+                element.copy_from_slice(&src[offset..offset + element_len]);
+            }
+            Ok(())
+        }
+
+        /// Zero `dst` and copy each `(offset, src)` extent into it.
+        pub fn materialize(
+            &self,
+            dst: &mut [u8],
+            extents: &[(usize, &[u8])],
+        ) -> Result<(), DsaError> {
+            let (order, holes) = super::check_extents(dst.len(), extents)?;
+            for hole in holes {
+                dst[hole].fill(0);
+            }
+            for i in order {
+                let (offset, src) = extents[i];
+                dst[offset..offset + src.len()].copy_from_slice(src);
+            }
+            Ok(())
+        }
+
+        /// Compare two memory regions.
+        pub fn memcmp(&self, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
+            if a.len() != b.len() {
+                return Err(DsaError::BufferSizeMismatch {
+                    expected: a.len(),
+                    actual: b.len(),
+                });
+            }
+
+            Ok(a == b)
+        }
+
+        /// Find the offset of the first difference between two memory regions.
+        pub fn mismatch(&self, a: &[u8], b: &[u8]) -> Result<Option<usize>, DsaError> {
+            if a.len() != b.len() {
+                return Err(DsaError::BufferSizeMismatch {
+                    expected: a.len(),
+                    actual: b.len(),
+                });
+            }
+
+            Ok(a.iter().zip(b).position(|(x, y)| x != y))
+        }
+
+        /// Find the first byte that deviates from a repeating 64-bit pattern.
+        pub fn compare_pattern(&self, buf: &[u8], pattern: u64) -> Result<Option<usize>, DsaError> {
+            Ok(super::find_pattern_mismatch(buf, pattern, 0))
+        }
+
+        /// Create a delta record in software.
+        pub fn create_delta(
+            &self,
+            original: &[u8],
+            modified: &[u8],
+            delta: &mut [u8],
+        ) -> Result<Option<usize>, DsaError> {
+            crate::delta::check_create(original, modified)?;
+            Ok(crate::delta::encode(original, modified, delta))
+        }
+
+        /// Apply a delta record in software.
+        pub fn apply_delta(&self, delta: &[u8], dst: &mut [u8]) -> Result<(), DsaError> {
+            crate::delta::check_entries(delta, dst.len())?;
+            crate::delta::decode(delta, dst);
+            Ok(())
+        }
+
+        /// Protect the data blocks of `src` into `dst` in software.
+        pub(crate) fn dif_insert(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            crate::t10pi::insert(dst, src, crate::descriptor::dif_block_size(dif_flags), tags);
+            Ok(())
+        }
+
+        /// Check the protected blocks of `src` and copy their data to `dst`
+        /// in software.
+        pub(crate) fn dif_strip(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            crate::t10pi::strip(dst, src, crate::descriptor::dif_block_size(dif_flags), tags)?;
+            Ok(())
+        }
 
+        /// Check the protected blocks of `src` in software.
+        pub(crate) fn dif_check(
+            &self,
+            src: &[u8],
+            dif_flags: u8,
+            tags: DifTags,
+        ) -> Result<(), DsaError> {
+            let block = crate::descriptor::dif_block_size(dif_flags);
+            crate::t10pi::check(src, block, tags, |_, _, _| {})?;
             Ok(())
         }
 
-        /// Compare two memory regions.
-        pub fn memcmp(&self, a: &[u8], b: &[u8]) -> Result<bool, DsaError> {
-            if a.len() != b.len() {
-                return Err(DsaError::BufferSizeMismatch {
-                    expected: a.len(),
-                    actual: b.len(),
-                });
-            }
+        /// Check the protected blocks of `src` and copy them to `dst` with
+        /// new tags in software.
+        pub(crate) fn dif_update(
+            &self,
+            dst: &mut [u8],
+            src: &[u8],
+            dif_flags: u8,
+            src_tags: DifTags,
+            dest_tags: DifTags,
+        ) -> Result<(), DsaError> {
+            let block = crate::descriptor::dif_block_size(dif_flags);
+            crate::t10pi::update(dst, src, block, src_tags, dest_tags)?;
+            Ok(())
+        }
 
-            Ok(a == b)
+        /// IAA is not available on Windows.
+        #[cfg(feature = "iaa")]
+        pub fn crc64(&self, _data: &[u8], _params: &Crc64Params) -> Result<u64, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        /// IAA is not available on Windows.
+        #[cfg(feature = "iaa")]
+        pub fn compress(&self, _dst: &mut [u8], _src: &[u8]) -> Result<usize, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        /// IAA is not available on Windows.
+        #[cfg(feature = "iaa")]
+        pub fn decompress(&self, _dst: &mut [u8], _src: &[u8]) -> Result<usize, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        /// Software operations use the CPU's translations; always succeeds.
+        pub fn transl_fetch(&self, _buf: &[u8]) -> Result<(), DsaError> {
+            Ok(())
         }
 
         /// No-op operation (completes immediately for software fallback).
@@ -437,7 +2823,7 @@ mod windows_impl {
     }
 }
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_software)))]
 mod stub_impl {
     use super::*;
 
@@ -454,71 +2840,889 @@ mod stub_impl {
             Err(DsaError::PlatformNotSupported)
         }
 
+        pub fn emulated(_emulator: Arc<Emulator>) -> Result<Self, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn mocked(_mock: Arc<MockBackend>) -> Result<Self, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn set_wq_type(&mut self, _wq_type: WorkQueueType) {}
         pub fn set_max_retries(&mut self, _retries: u32) {}
         pub fn set_spin_iterations(&mut self, _iterations: u32) {}
+        pub fn set_backoff(&mut self, _backoff: Backoff) {}
+        pub fn set_serialized(&mut self, _budget: Option<usize>) {}
+
+        /// Operations complete synchronously; nothing is retried or polled.
+        pub fn max_retries(&self) -> u32 {
+            0
+        }
+
+        pub fn spin_iterations(&self) -> u32 {
+            0
+        }
+
+        pub fn backoff(&self) -> Backoff {
+            Backoff::default()
+        }
+
+        /// Operations complete synchronously; never serialized.
+        pub fn serialized(&self) -> Option<usize> {
+            None
+        }
+
+        /// Descriptors are not used; nothing is traced.
+        pub(crate) fn set_trace(&mut self, _trace: Option<TraceRing>) {}
+
+        pub(crate) fn trace(&self) -> Option<&TraceRing> {
+            None
+        }
+
+        /// Descriptors are not used; profiles have no effect.
+        pub fn set_profile(&mut self, _profile: Option<Profile>) {}
+
+        /// Operations complete synchronously; nothing is shared.
+        pub fn set_fair_share(&mut self, _share: Option<FairShare>) -> Result<(), DsaError> {
+            Ok(())
+        }
+
+        pub fn fair_share(&self) -> Option<FairShare> {
+            None
+        }
+
+        /// Operations never access files; nothing to read up front.
+        pub fn seal(&mut self) {}
+
+        /// Operations never access files; always sealed.
+        pub fn is_sealed(&self) -> bool {
+            true
+        }
+
+        /// There is no device that could go away; always healthy.
+        pub fn is_healthy(&self) -> bool {
+            true
+        }
+
+        /// Operations fail without a device; nothing to attach.
+        pub fn set_attach_device_errors(&mut self, _attach: bool) {}
+
+        pub fn attaches_device_errors(&self) -> bool {
+            false
+        }
+
+        /// Software errors carry no context; they are always compact.
+        pub fn set_compact_errors(&mut self, _compact: bool) {}
+
+        pub fn compact_errors(&self) -> bool {
+            true
+        }
+
+        pub fn set_region_grouping(&mut self, _region: Option<usize>) {}
+
+        pub fn region_grouping(&self) -> Option<usize> {
+            None
+        }
+
+        pub fn profile(&self) -> Option<Profile> {
+            None
+        }
         pub fn wq_type(&self) -> WorkQueueType {
             WorkQueueType::Shared
         }
 
+        pub fn path(&self) -> &Path {
+            Path::new("")
+        }
+
+        /// Operations complete synchronously; always zero.
+        pub fn in_flight(&self) -> usize {
+            0
+        }
+
+        /// Operations complete synchronously; there is always room.
+        pub(crate) fn wait_for_room(&self, _budget: usize) -> Result<(), DsaError> {
+            Ok(())
+        }
+
+        /// No operations are available on this platform.
+        pub fn capabilities(&self) -> Capabilities {
+            Capabilities::software(&[])
+        }
+
+        pub fn submission_fence(&self) {}
+
+        pub fn lock_exclusive(&self) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn crc32(&self, _data: &[u8], _seed: u32) -> Result<u32, DsaError> {
             Err(DsaError::PlatformNotSupported)
         }
 
+        pub(crate) fn crc32_in(
+            &self,
+            _data: &[u8],
+            _seed: u32,
+            _ctx: &OpContext,
+        ) -> Result<u32, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn memcpy_in(
+            &self,
+            _dst: &mut [u8],
+            _src: &[u8],
+            _ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn memset_in(
+            &self,
+            _dst: &mut [u8],
+            _pattern: u64,
+            _ctx: &OpContext,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn drain_in(&self, _ctx: &OpContext) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn memcmp_in(
+            &self,
+            _a: &[u8],
+            _b: &[u8],
+            _ctx: &OpContext,
+        ) -> Result<bool, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn crc32_many(&self, _bufs: &[&[u8]], _seed: u32) -> Result<Vec<u32>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn memcpy(&self, _dst: &mut [u8], _src: &[u8]) -> Result<(), DsaError> {
             Err(DsaError::PlatformNotSupported)
         }
 
+        pub fn copy_crc(&self, _dst: &mut [u8], _src: &[u8], _seed: u32) -> Result<u32, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn memset(&self, _dst: &mut [u8], _pattern: u64) -> Result<(), DsaError> {
             Err(DsaError::PlatformNotSupported)
         }
 
+        pub fn memset_many(&self, _bufs: &mut [&mut [u8]], _pattern: u64) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn match_pattern_many(
+            &self,
+            _bufs: &[&[u8]],
+            _pattern: u64,
+        ) -> Result<Vec<bool>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn compare_many(&self, _pairs: &[(&[u8], &[u8])]) -> Result<Vec<bool>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn copy_rect(
+            &self,
+            _dst: &mut [u8],
+            _dst_stride: usize,
+            _src: &[u8],
+            _src_stride: usize,
+            _width: usize,
+            _height: usize,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn gather(
+            &self,
+            _dst: &mut [u8],
+            _src: &[u8],
+            _offsets: &[usize],
+            _element_len: usize,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn materialize(
+            &self,
+            _dst: &mut [u8],
+            _extents: &[(usize, &[u8])],
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn memcmp(&self, _a: &[u8], _b: &[u8]) -> Result<bool, DsaError> {
             Err(DsaError::PlatformNotSupported)
         }
 
+        pub fn mismatch(&self, _a: &[u8], _b: &[u8]) -> Result<Option<usize>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn compare_pattern(
+            &self,
+            _buf: &[u8],
+            _pattern: u64,
+        ) -> Result<Option<usize>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn create_delta(
+            &self,
+            _original: &[u8],
+            _modified: &[u8],
+            _delta: &mut [u8],
+        ) -> Result<Option<usize>, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn apply_delta(&self, _delta: &[u8], _dst: &mut [u8]) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn dif_insert(
+            &self,
+            _dst: &mut [u8],
+            _src: &[u8],
+            _dif_flags: u8,
+            _tags: DifTags,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn dif_strip(
+            &self,
+            _dst: &mut [u8],
+            _src: &[u8],
+            _dif_flags: u8,
+            _tags: DifTags,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn dif_check(
+            &self,
+            _src: &[u8],
+            _dif_flags: u8,
+            _tags: DifTags,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub(crate) fn dif_update(
+            &self,
+            _dst: &mut [u8],
+            _src: &[u8],
+            _dif_flags: u8,
+            _src_tags: DifTags,
+            _dest_tags: DifTags,
+        ) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        #[cfg(feature = "iaa")]
+        pub fn crc64(&self, _data: &[u8], _params: &Crc64Params) -> Result<u64, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        #[cfg(feature = "iaa")]
+        pub fn compress(&self, _dst: &mut [u8], _src: &[u8]) -> Result<usize, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        #[cfg(feature = "iaa")]
+        pub fn decompress(&self, _dst: &mut [u8], _src: &[u8]) -> Result<usize, DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
+        pub fn transl_fetch(&self, _buf: &[u8]) -> Result<(), DsaError> {
+            Err(DsaError::PlatformNotSupported)
+        }
+
         pub fn noop(&self) -> Result<(), DsaError> {
             Err(DsaError::PlatformNotSupported)
         }
     }
 }
 
+/// Find the first byte at or after `start` that deviates from `pattern`
+/// repeated from the beginning of `buf` in little-endian byte order.
+fn find_pattern_mismatch(buf: &[u8], pattern: u64, start: usize) -> Option<usize> {
+    let pattern_bytes = pattern.to_le_bytes();
+    buf.iter()
+        .enumerate()
+        .skip(start)
+        .find(|&(i, &byte)| byte != pattern_bytes[i % 8])
+        .map(|(i, _)| i)
+}
+
+/// Validate the geometry of a rectangular copy.
+///
+/// Rows must not be wider than either stride, and the last row must end
+/// within each buffer.
+fn check_rect(
+    dst_len: usize,
+    dst_stride: usize,
+    src_len: usize,
+    src_stride: usize,
+    width: usize,
+    height: usize,
+) -> Result<(), DsaError> {
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    if width > dst_stride || width > src_stride {
+        return Err(DsaError::InvalidArgument(format!(
+            "row width {width} exceeds stride (dst {dst_stride}, src {src_stride})"
+        )));
+    }
+
+    let required = |stride: usize| {
+        (height - 1)
+            .checked_mul(stride)
+            .and_then(|offset| offset.checked_add(width))
+            .ok_or_else(|| DsaError::InvalidArgument("rectangle size overflows".to_string()))
+    };
+    let (dst_required, src_required) = (required(dst_stride)?, required(src_stride)?);
+    if dst_len < dst_required {
+        return Err(DsaError::BufferSizeMismatch {
+            expected: dst_required,
+            actual: dst_len,
+        });
+    }
+    if src_len < src_required {
+        return Err(DsaError::BufferSizeMismatch {
+            expected: src_required,
+            actual: src_len,
+        });
+    }
+    Ok(())
+}
+
+/// Validate that both buffers of every pair have the same length.
+fn check_pairs(pairs: &[(&[u8], &[u8])]) -> Result<(), DsaError> {
+    match pairs.iter().find(|(a, b)| a.len() != b.len()) {
+        Some((a, b)) => Err(DsaError::BufferSizeMismatch {
+            expected: a.len(),
+            actual: b.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Validate the elements of a gather.
+///
+/// `dst` must hold all elements, and every element must lie within `src`.
+fn check_gather(
+    dst_len: usize,
+    src_len: usize,
+    offsets: &[usize],
+    element_len: usize,
+) -> Result<(), DsaError> {
+    let required = offsets
+        .len()
+        .checked_mul(element_len)
+        .ok_or_else(|| DsaError::InvalidArgument("gather size overflows".to_string()))?;
+    if dst_len < required {
+        return Err(DsaError::BufferSizeMismatch {
+            expected: required,
+            actual: dst_len,
+        });
+    }
+
+    for &offset in offsets {
+        let end = offset.checked_add(element_len).ok_or_else(|| {
+            DsaError::InvalidArgument(format!("element at offset {offset} overflows"))
+        })?;
+        if end > src_len {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: end,
+                actual: src_len,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Validate the extents of a materialization.
+///
+/// Every extent must lie within `dst`, and no two may overlap. Returns the
+/// indices of the non-empty extents in ascending order of offset, and the
+/// non-empty holes between them.
+fn check_extents(
+    dst_len: usize,
+    extents: &[(usize, &[u8])],
+) -> Result<(Vec<usize>, Vec<Range<usize>>), DsaError> {
+    for &(offset, src) in extents {
+        let end = offset.checked_add(src.len()).ok_or_else(|| {
+            DsaError::InvalidArgument(format!("extent at offset {offset} overflows"))
+        })?;
+        if end > dst_len {
+            return Err(DsaError::BufferSizeMismatch {
+                expected: end,
+                actual: dst_len,
+            });
+        }
+    }
+
+    let mut order: Vec<usize> = (0..extents.len())
+        .filter(|&i| !extents[i].1.is_empty())
+        .collect();
+    order.sort_by_key(|&i| extents[i].0);
+    let mut holes = Vec::with_capacity(order.len() + 1);
+    let mut end = 0;
+    for &i in &order {
+        let (offset, src) = extents[i];
+        if offset < end {
+            return Err(DsaError::InvalidArgument(format!(
+                "extent at offset {offset} overlaps the one before it"
+            )));
+        }
+        if end < offset {
+            holes.push(end..offset);
+        }
+        end = offset + src.len();
+    }
+    if end < dst_len {
+        holes.push(end..dst_len);
+    }
+    Ok((order, holes))
+}
+
+/// Map a completed record's status to a result.
+pub(crate) fn check_completion(record: &DsaCompletionRecord) -> Result<(), DsaError> {
+    match record.get_status() {
+        CompletionStatus::Success => Ok(()),
+        CompletionStatus::PageFault => Err(DsaError::PageFault {
+            fault_addr: record.fault_addr,
+            bytes_completed: record.bytes_completed,
+            context: None,
+        }),
+        CompletionStatus::DifError => Err(DsaError::DifCheckFailed {
+            status: record.dif_status(),
+            bytes_completed: record.bytes_completed,
+        }),
+        _ => Err(DsaError::OperationFailed {
+            status: record.status,
+            result: record.result,
+            bytes_completed: record.bytes_completed,
+            context: None,
+        }),
+    }
+}
+
+/// Returns true if the wait behind `err` was given up while the hardware
+/// may still write the completion record.
+pub(crate) fn abandons_record(err: &DsaError) -> bool {
+    matches!(
+        err,
+        DsaError::Timeout { .. }
+            | DsaError::Cancelled
+            | DsaError::DeadlineExceeded
+            | DsaError::WorkQueueDisabled(_)
+    )
+}
+
 // Re-export the appropriate implementation
-#[cfg(target_os = "linux")]
+#[cfg(dsa_portal)]
+pub(crate) use linux_impl::mark_work_queue_gone;
+#[cfg(dsa_portal)]
 pub use linux_impl::WorkQueue;
 
-#[cfg(target_os = "windows")]
+#[cfg(dsa_software)]
 pub use windows_impl::WorkQueue;
 
-#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[cfg(not(any(dsa_portal, dsa_software)))]
 pub use stub_impl::WorkQueue;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_check_rect() {
+        // 4x3 rectangle: last row starts at 2 * stride
+        assert!(check_rect(20, 8, 28, 12, 4, 3).is_ok());
+        assert!(check_rect(0, 0, 0, 0, 0, 0).is_ok());
+        assert!(matches!(
+            check_rect(19, 8, 28, 12, 4, 3),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 20,
+                actual: 19
+            })
+        ));
+        assert!(matches!(
+            check_rect(64, 4, 64, 8, 5, 2),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        assert!(check_rect(usize::MAX, usize::MAX, 8, 8, 8, 3).is_err());
+    }
+
+    #[test]
+    fn test_check_gather() {
+        assert!(check_gather(12, 16, &[0, 12, 4], 4).is_ok());
+        assert!(check_gather(0, 0, &[], 4).is_ok());
+        assert!(matches!(
+            check_gather(11, 16, &[0, 12, 4], 4),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 12,
+                actual: 11
+            })
+        ));
+        assert!(matches!(
+            check_gather(12, 16, &[0, 13, 4], 4),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 17,
+                actual: 16
+            })
+        ));
+        assert!(matches!(
+            check_gather(usize::MAX, 16, &[usize::MAX], 4),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_extents() {
+        let data = [1u8; 8];
+        let extents: [(usize, &[u8]); 4] = [(20, &data), (4, &data[..4]), (30, &[]), (12, &data)];
+        let (order, holes) = check_extents(32, &extents).unwrap();
+        assert_eq!(order, [1, 3, 0]);
+        assert_eq!(holes, [0..4, 8..12, 28..32]);
+        assert_eq!(check_extents(0, &[]).unwrap(), (vec![], vec![]));
+        assert_eq!(
+            check_extents(4, &[(0, &data[..4])]).unwrap(),
+            (vec![0], vec![])
+        );
+        assert!(matches!(
+            check_extents(16, &[(12, &data)]),
+            Err(DsaError::BufferSizeMismatch {
+                expected: 20,
+                actual: 16
+            })
+        ));
+        assert!(matches!(
+            check_extents(32, &[(8, &data), (4, &data)]),
+            Err(DsaError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            check_extents(32, &[(usize::MAX, &data)]),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_completion_dif_error() {
+        let mut record = DsaCompletionRecord::new();
+        record.status = 0x09;
+        record.result = 0x03;
+        record.bytes_completed = 1040;
+        match check_completion(&record) {
+            Err(DsaError::DifCheckFailed {
+                status,
+                bytes_completed: 1040,
+            }) => assert_eq!(
+                status,
+                crate::DifStatus::GUARD_MISMATCH | crate::DifStatus::APP_TAG_MISMATCH
+            ),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
     #[test]
     fn test_work_queue_type() {
         assert_eq!(WorkQueueType::Dedicated, WorkQueueType::Dedicated);
         assert_ne!(WorkQueueType::Dedicated, WorkQueueType::Shared);
     }
 
+    #[test]
+    fn test_work_queue_state() {
+        assert_eq!(WorkQueueState::from("enabled\n"), WorkQueueState::Enabled);
+        assert_eq!(WorkQueueState::from("Disabled"), WorkQueueState::Disabled);
+        assert_eq!(WorkQueueState::from("quiescing"), WorkQueueState::Quiescing);
+        assert_eq!(
+            WorkQueueState::from("locked"),
+            WorkQueueState::Unknown("locked".to_string())
+        );
+        assert_eq!(WorkQueueState::from("locked").to_string(), "locked");
+        assert!(!WorkQueueState::Quiescing.is_enabled());
+    }
+
+    #[test]
+    fn test_work_queue_binding() {
+        assert_eq!(WorkQueueBinding::from("user\n"), WorkQueueBinding::User);
+        assert_eq!(
+            WorkQueueBinding::from("dmaengine"),
+            WorkQueueBinding::DmaEngine
+        );
+        assert_eq!(WorkQueueBinding::from(""), WorkQueueBinding::Unbound);
+        let crypto = WorkQueueBinding::from("crypto");
+        assert_eq!(crypto, WorkQueueBinding::Other("crypto".to_string()));
+        assert!(crypto.is_kernel() && !crypto.is_user());
+        assert!(!WorkQueueBinding::Unbound.is_kernel());
+        assert_eq!(WorkQueueBinding::DmaEngine.to_string(), "dmaengine");
+        assert_eq!(WorkQueueBinding::Unbound.to_string(), "none");
+    }
+
     #[test]
     fn test_work_queue_info() {
         let info = WorkQueueInfo {
             name: "wq0.0".to_string(),
-            state: "enabled".to_string(),
+            state: WorkQueueState::Enabled,
             wq_type: WorkQueueType::Shared,
             size: 128,
             threshold: 64,
+            block_on_fault: false,
+            max_batch_size: 1024,
+            max_transfer_size: 2 * 1024 * 1024,
+            driver_name: "app1".to_string(),
+            clients: 1,
+            priority: 10,
+            ats_disable: false,
+            binding: WorkQueueBinding::User,
         };
 
         assert_eq!(info.name, "wq0.0");
-        assert_eq!(info.state, "enabled");
+        assert!(info.state.is_enabled());
         assert_eq!(info.wq_type, WorkQueueType::Shared);
     }
 
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    #[test]
+    fn test_find_pattern_mismatch() {
+        let pattern = 0x0807060504030201u64;
+        let mut buf: Vec<u8> = (0..20).map(|i| (i % 8 + 1) as u8).collect();
+        assert_eq!(find_pattern_mismatch(&buf, pattern, 0), None);
+
+        buf[13] = 0xFF;
+        assert_eq!(find_pattern_mismatch(&buf, pattern, 0), Some(13));
+        assert_eq!(find_pattern_mismatch(&buf, pattern, 8), Some(13));
+        assert_eq!(find_pattern_mismatch(&buf, pattern, 14), None);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_drop_waits_for_in_flight() {
+        let wq = WorkQueue::emulated(Arc::new(Emulator::new())).unwrap();
+        let ops: Vec<_> = (0..4).map(|_| wq.start(DsaHwDesc::noop).unwrap()).collect();
+        wq.submission_fence();
+        for op in &ops {
+            op.wait();
+        }
+        assert_eq!(wq.in_flight(), 0);
+
+        // An operation the hardware has not finished yet
+        let op = InFlight::new();
+        op.track(&wq.in_flight);
+        Reactor::global().register(Arc::clone(&op));
+        assert_eq!(wq.in_flight(), 1);
+
+        let dropper = std::thread::spawn(move || drop(wq));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!dropper.is_finished());
+
+        unsafe { std::ptr::write_volatile(&mut op.record_mut().status, 0x01) };
+        dropper.join().unwrap();
+        assert!(op.is_done());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_serialized_from_many_threads() {
+        let mut wq = WorkQueue::emulated(Arc::new(Emulator::new())).unwrap();
+        wq.set_serialized(Some(4));
+        assert_eq!(wq.serialized(), Some(4));
+        let wq = Arc::new(wq);
+        let threads: Vec<_> = (0..8u8)
+            .map(|t| {
+                let wq = Arc::clone(&wq);
+                std::thread::spawn(move || {
+                    for i in 0..100u8 {
+                        let data = [t ^ i; 128];
+                        let expected = crate::crc::software_crc32(&data, 0);
+                        assert_eq!(wq.crc32(&data, 0).unwrap(), expected);
+                        wq.start(DsaHwDesc::noop).unwrap().wait();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(wq.in_flight(), 0);
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_crc_chained_across_descriptors() {
+        let emulator = Arc::new(Emulator::new());
+        let wq = WorkQueue::emulated(Arc::clone(&emulator)).unwrap();
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let expected = crate::crc::software_crc32(&data, 0x1234_5678);
+
+        let crc = wq.crc32_chunked(&data, 0x1234_5678, &OpContext::NONE, 4096);
+        assert_eq!(crc.unwrap(), expected);
+        assert_eq!(emulator.submitted(), 3);
+
+        let mut dst = vec![0u8; data.len()];
+        assert_eq!(
+            wq.copy_crc_chunked(&mut dst, &data, 0x1234_5678, 3000)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(dst, data);
+        assert_eq!(wq.crc32_chunked(&[], 7, &OpContext::NONE, 4096).unwrap(), 7);
+
+        // Buffers above the transfer limit are chained, the others batched
+        let small = &data[..100];
+        let crcs = wq
+            .crc32_many_chunked(&[&data, small, &[]], 0x1234_5678, 4096)
+            .unwrap();
+        assert_eq!(
+            crcs,
+            [
+                expected,
+                crate::crc::software_crc32(small, 0x1234_5678),
+                0x1234_5678
+            ]
+        );
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_stalled_batch_abandoned() {
+        use crate::emulator::Fault;
+
+        let bufs = [vec![1u8; 4096], vec![2u8; 4096]];
+        let slices: Vec<&[u8]> = bufs.iter().map(Vec::as_slice).collect();
+        for count in [1, 2] {
+            let emulator = Arc::new(Emulator::new());
+            let mut wq = WorkQueue::emulated(Arc::clone(&emulator)).unwrap();
+            wq.set_spin_iterations(100);
+            assert!(!wq.abandoned());
+
+            // The descriptors and records stay allocated for the hardware
+            emulator.inject(Fault::Stall);
+            assert!(matches!(
+                wq.crc32_many(&slices[..count], 0),
+                Err(DsaError::Timeout { .. })
+            ));
+            assert!(wq.abandoned());
+        }
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_serialized_budget_exhausted() {
+        use crate::emulator::Fault;
+
+        let emulator = Arc::new(Emulator::new());
+        let mut wq = WorkQueue::emulated(Arc::clone(&emulator)).unwrap();
+        wq.set_serialized(Some(1));
+        wq.set_spin_iterations(100);
+
+        // An operation the hardware never finishes fills the budget
+        emulator.inject(Fault::Stall);
+        let stalled = wq.start(DsaHwDesc::noop).unwrap();
+        assert!(matches!(
+            wq.noop(),
+            Err(DsaError::QueueFull {
+                threshold: Some(1),
+                occupancy: Some(1),
+                ..
+            })
+        ));
+
+        // Completing it makes room again
+        unsafe { std::ptr::write_volatile(&mut stalled.record_mut().status, 0x01) };
+        stalled.wait();
+        wq.noop().unwrap();
+    }
+
+    #[cfg(all(dsa_portal, feature = "backend-hw"))]
+    #[test]
+    fn test_open_shares_mapping() {
+        // Any read-write mappable file stands in for a device node
+        let path = std::env::temp_dir().join(format!("dsa-wq-share-{}", std::process::id()));
+        std::fs::write(&path, []).unwrap();
+
+        let a = WorkQueue::open(&path).unwrap();
+        let b = WorkQueue::open(&path).unwrap();
+        assert!(a.shares_portal(&b));
+        drop(a);
+        drop(b);
+
+        // Reopening after the last handle is dropped maps the device again
+        let c = WorkQueue::open(&path).unwrap();
+        let d = WorkQueue::open(&path).unwrap();
+        assert!(c.shares_portal(&d));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(all(dsa_portal, feature = "backend-hw"))]
+    #[test]
+    fn test_gone_work_queue_fails_fast() {
+        let path = std::env::temp_dir().join(format!("dsa-wq-gone-{}", std::process::id()));
+        std::fs::write(&path, []).unwrap();
+
+        let wq = WorkQueue::open(&path).unwrap();
+        wq.mark_gone();
+        // Without an enabled sysfs entry the work queue is not reopened, and
+        // the portal is never written
+        assert!(matches!(
+            wq.noop(),
+            Err(DsaError::WorkQueueDisabled(name)) if path.ends_with(&name)
+        ));
+
+        // New handles do not pick up the dead mapping
+        let fresh = WorkQueue::open(&path).unwrap();
+        assert!(!fresh.shares_portal(&wq));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(all(dsa_portal, feature = "backend-hw"))]
+    #[test]
+    fn test_lock_exclusive() {
+        use std::os::fd::AsRawFd;
+
+        let path = std::env::temp_dir().join(format!("dsa-wq-lock-{}", std::process::id()));
+        std::fs::write(&path, []).unwrap();
+        let try_lock = |file: &std::fs::File| unsafe {
+            libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) == 0
+        };
+
+        let wq = WorkQueue::open(&path).unwrap();
+        wq.lock_exclusive().unwrap();
+        // Handles sharing the portal share the lock
+        let shared = WorkQueue::open(&path).unwrap();
+        shared.lock_exclusive().unwrap();
+
+        // Another open file, as in another process, cannot take it
+        let other = std::fs::File::open(&path).unwrap();
+        assert!(!try_lock(&other));
+        drop(wq);
+        assert!(!try_lock(&other));
+        drop(shared);
+        assert!(try_lock(&other));
+
+        assert!(matches!(
+            WorkQueue::open(&path).unwrap().lock_exclusive(),
+            Err(DsaError::WorkQueueBusy(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(not(any(dsa_portal, dsa_software)))]
     #[test]
     fn test_stub_returns_platform_not_supported() {
         use std::path::PathBuf;
@@ -526,7 +3730,7 @@ mod tests {
         assert!(matches!(result, Err(DsaError::PlatformNotSupported)));
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(dsa_software)]
     #[test]
     fn test_windows_software_fallback() {
         use std::path::PathBuf;
@@ -0,0 +1,214 @@
+// Intel Data Streaming Accelerator (DSA) Rust Bindings
+// Copyright 2025 Henk-Jan Lebbink
+// SPDX-License-Identifier: MIT
+
+//! Finding all-zero pages in a large region.
+//!
+//! Memory deduplication, VM snapshotting and sparse file writers want to
+//! know which pages of a region hold nothing but zeros.
+//! [`DsaEngine::find_zero_pages`] checks every page with a compare
+//! immediate descriptor against a zero pattern, submitting up to
+//! [`DEFAULT_MAX_BATCH_SIZE`] pages per batch, and returns a
+//! [`PageBitmap`] with one bit per page.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use dsa_rust::DsaEngine;
+//!
+//! let engine = DsaEngine::open_first()?;
+//! let region = vec![0u8; 64 << 20];
+//! let zero_pages = engine.find_zero_pages(&region, 4096)?;
+//! println!("{} of {} pages are zero", zero_pages.count_ones(), zero_pages.len());
+//! # Ok::<(), dsa_rust::DsaError>(())
+//! ```
+
+use crate::engine::DsaEngine;
+use crate::error::DsaError;
+use crate::wq::DEFAULT_MAX_BATCH_SIZE;
+
+/// A fixed-length set of bits, such as one per page.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PageBitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl PageBitmap {
+    /// Create a bitmap of `len` bits, all clear.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the bitmap has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get bit `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(
+            index < self.len,
+            "bit {index} out of range for {} bits",
+            self.len
+        );
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Set bit `index` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(
+            index < self.len,
+            "bit {index} out of range for {} bits",
+            self.len
+        );
+        let mask = 1 << (index % 64);
+        if value {
+            self.words[index / 64] |= mask;
+        } else {
+            self.words[index / 64] &= !mask;
+        }
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Indices of the set bits, in ascending order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&index| self.get(index))
+    }
+
+    /// The bits packed into words, bit `i` at bit `i % 64` of word `i / 64`.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+impl DsaEngine {
+    /// Find the pages of `region` that contain only zero bytes.
+    ///
+    /// The region is split into pages of `page_size` bytes; a shorter last
+    /// page is checked over its actual length. Pages are checked in batches
+    /// of [`DEFAULT_MAX_BATCH_SIZE`] compare immediate descriptors.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Memory to scan
+    /// * `page_size` - Bytes per page; a nonzero multiple of 8
+    ///
+    /// # Returns
+    ///
+    /// A bitmap with bit `i` set if page `i` is all zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DsaError::InvalidArgument` if `page_size` is zero or not a
+    /// multiple of 8, or an error if an operation fails.
+    pub fn find_zero_pages(&self, region: &[u8], page_size: usize) -> Result<PageBitmap, DsaError> {
+        if page_size == 0 || !page_size.is_multiple_of(8) {
+            return Err(DsaError::InvalidArgument(format!(
+                "page size {page_size} is not a nonzero multiple of 8"
+            )));
+        }
+
+        let mut zero_pages = PageBitmap::new(region.len().div_ceil(page_size));
+        let batch_bytes = page_size * DEFAULT_MAX_BATCH_SIZE;
+        for (batch, group) in region.chunks(batch_bytes).enumerate() {
+            // Compare immediate works on whole 8-byte words; a partial last
+            // page has its trailing bytes checked in software
+            let pages: Vec<&[u8]> = group
+                .chunks(page_size)
+                .map(|page| &page[..page.len() & !7])
+                .collect();
+            self.throttle(group.len(), pages.len());
+            let matches = self.retry(|| self.work_queue().match_pattern_many(&pages, 0))?;
+
+            for (i, (page, is_zero)) in group.chunks(page_size).zip(matches).enumerate() {
+                let tail = &page[page.len() & !7..];
+                if is_zero && tail.iter().all(|&byte| byte == 0) {
+                    zero_pages.set(batch * DEFAULT_MAX_BATCH_SIZE + i, true);
+                }
+            }
+        }
+        Ok(zero_pages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_bitmap() {
+        let mut bitmap = PageBitmap::new(130);
+        assert_eq!(bitmap.len(), 130);
+        assert_eq!(bitmap.as_words().len(), 3);
+        assert_eq!(bitmap.count_ones(), 0);
+
+        for index in [0, 63, 64, 129] {
+            bitmap.set(index, true);
+        }
+        assert!(bitmap.get(63) && bitmap.get(64) && !bitmap.get(65));
+        assert_eq!(bitmap.count_ones(), 4);
+        assert_eq!(bitmap.iter_ones().collect::<Vec<_>>(), vec![0, 63, 64, 129]);
+
+        bitmap.set(63, false);
+        assert_eq!(bitmap.as_words()[0], 1);
+        assert!(PageBitmap::default().is_empty());
+    }
+
+    #[cfg(dsa_portal)]
+    #[test]
+    fn test_find_zero_pages() {
+        use crate::emulator::Emulator;
+        use std::sync::Arc;
+
+        let engine = DsaEngine::emulated(Arc::new(Emulator::new())).unwrap();
+        let page_size = 4096;
+        let pages = DEFAULT_MAX_BATCH_SIZE + 10;
+        let mut region = vec![0u8; pages * page_size + 13];
+        region[3 * page_size] = 1;
+        region[5 * page_size + page_size - 1] = 0xFF;
+        region[(DEFAULT_MAX_BATCH_SIZE + 2) * page_size + 100] = 7;
+
+        let zero_pages = engine.find_zero_pages(&region, page_size).unwrap();
+        assert_eq!(zero_pages.len(), pages + 1);
+        let nonzero: Vec<usize> = (0..zero_pages.len())
+            .filter(|&i| !zero_pages.get(i))
+            .collect();
+        assert_eq!(nonzero, vec![3, 5, DEFAULT_MAX_BATCH_SIZE + 2]);
+
+        // A nonzero byte in the unaligned tail of the last page
+        *region.last_mut().unwrap() = 1;
+        let zero_pages = engine.find_zero_pages(&region, page_size).unwrap();
+        assert!(!zero_pages.get(pages));
+        assert_eq!(zero_pages.count_ones(), pages - 3);
+
+        assert!(engine.find_zero_pages(&[], page_size).unwrap().is_empty());
+        assert!(matches!(
+            engine.find_zero_pages(&region, 100),
+            Err(DsaError::InvalidArgument(_))
+        ));
+    }
+}